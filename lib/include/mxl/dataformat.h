@@ -4,7 +4,8 @@
 #pragma once
 
 /**
- * Fixed size in bytes of one MXL `video/smpte291` data format grain.
+ * Fixed size in bytes of one MXL `video/smpte291`, `video/smpte336m` or `application/json` data
+ * format grain.
  * This is large enough to hold all the ANC data in a single grain.
  * This size is a usual VFS page; no point in going smaller.
  */
@@ -350,6 +350,31 @@ extern "C"
     mxlStatus mxlFlowReaderGetGrainSliceNonBlocking(mxlFlowReader reader, uint64_t index, uint16_t minValidSlices, mxlGrainInfo* grain,
         uint8_t** payload);
 
+    /**
+     * Get a copy of the header of the Flow a writer writes to. Unlike opening a reader to query it,
+     * this does not update the lastReadTime of the flow.
+     *
+     * \param[in] writer A valid flow writer
+     * \param[out] info A valid pointer to an mxlFlowInfo structure.
+     *      On return, the structure will be updated with a copy of the current
+     *      flow info value.
+     * \return The result code. \see mxlStatus
+     */
+    MXL_EXPORT
+    mxlStatus mxlFlowWriterGetInfo(mxlFlowWriter writer, mxlFlowInfo* info);
+
+    /**
+     * Get a copy of the current runtime header of the Flow a writer writes to.
+     *
+     * \param[in] writer A valid flow writer
+     * \param[out] info A valid pointer to an mxlFlowRuntimeInfo structure.
+     *      On return, the structure will be updated with a copy of the current
+     *      flow runtime info value.
+     * \return The result code. \see mxlStatus
+     */
+    MXL_EXPORT
+    mxlStatus mxlFlowWriterGetRuntimeInfo(mxlFlowWriter writer, mxlFlowRuntimeInfo* info);
+
     /**
      * Get grain info for a given index. This is used to inspect the grain info without opening the grain for mutation.
      *
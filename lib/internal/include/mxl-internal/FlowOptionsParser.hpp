@@ -43,6 +43,13 @@ namespace mxl::lib
         [[nodiscard]]
         std::optional<std::uint32_t> getMaxSyncBatchSizeHint() const;
 
+        /**
+         * Accessor for the 'historyDuration' field, which expresses the depth, in nanoseconds, of the ring buffer of a newly created flow. When
+         * present, it overrides the domain level history duration for this flow only. It has no effect when opening an existing flow.
+         */
+        [[nodiscard]]
+        std::optional<std::uint64_t> getHistoryDuration() const;
+
         /**
          * Generic accessor for json fields.
          *
@@ -59,6 +66,8 @@ namespace mxl::lib
         std::optional<std::uint32_t> _maxSyncBatchSizeHint;
         /// \see mxlCommonFlowInfo::maxCommitBatchSizeHint
         std::optional<std::uint32_t> _maxCommitBatchSizeHint;
+        /// Per-flow override of the domain history duration, in nanoseconds.
+        std::optional<std::uint64_t> _historyDuration;
         /** The parsed flow object. */
         picojson::object _root;
     };
@@ -22,4 +22,33 @@ namespace mxl::lib
      */
     std::uint32_t get10BitAlphaLineLength(std::size_t width);
 
+    /**
+     * Length in bytes of a line in the UYVY video format (8-bit 4:2:2, 4 bytes per pixel pair).
+     * @param width The width of the video frame in pixels.
+     * @return The line length in bytes for the UYVY format, including padding of odd widths.
+     */
+    std::uint32_t getUyvyLineLength(std::size_t width);
+
+    /**
+     * Length in bytes of a line of the 8-bit luma plane of the I420 and NV12 video formats, which
+     * is also a line of the interleaved chroma plane of NV12.
+     * @param width The width of the video frame in pixels.
+     * @return The line length in bytes, padded to a multiple of 4 bytes.
+     */
+    std::uint32_t get420LumaLineLength(std::size_t width);
+
+    /**
+     * Length in bytes of a line of one of the two 8-bit chroma planes of the I420 video format.
+     * @param width The width of the video frame in pixels.
+     * @return The line length in bytes, padded to a multiple of 4 bytes.
+     */
+    std::uint32_t getI420ChromaLineLength(std::size_t width);
+
+    /**
+     * Length in bytes of a line in the RGBA and BGRA video formats (8-bit, 4 bytes per pixel).
+     * @param width The width of the video frame in pixels.
+     * @return The line length in bytes, without padding.
+     */
+    std::uint32_t getRgbaLineLength(std::size_t width);
+
 }
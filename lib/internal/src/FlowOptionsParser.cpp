@@ -67,6 +67,22 @@ namespace mxl::lib
                 throw std::invalid_argument{"maxSyncBatchSizeHint must be a multiple of maxCommitBatchSizeHint."};
             }
         }
+
+        auto historyDurationIt = _root.find("historyDuration");
+        if (historyDurationIt != _root.end())
+        {
+            if (!historyDurationIt->second.is<double>())
+            {
+                throw std::invalid_argument{"historyDuration must be a number."};
+            }
+
+            auto const v = historyDurationIt->second.get<double>();
+            if (v < 1)
+            {
+                throw std::invalid_argument{"historyDuration must be greater or equal to 1."};
+            }
+            _historyDuration = static_cast<std::uint64_t>(v);
+        }
     }
 
     std::optional<std::uint32_t> FlowOptionsParser::getMaxCommitBatchSizeHint() const
@@ -78,4 +94,9 @@ namespace mxl::lib
     {
         return _maxSyncBatchSizeHint;
     }
+
+    std::optional<std::uint64_t> FlowOptionsParser::getHistoryDuration() const
+    {
+        return _historyDuration;
+    }
 } // namespace mxl::lib
@@ -4,6 +4,7 @@
 #include "mxl-internal/FlowParser.hpp"
 #include <cstddef>
 #include <cstdint>
+#include <numeric>
 #include <ranges>
 #include <stdexcept>
 #include <string>
@@ -369,6 +370,40 @@ namespace mxl::lib
                     throw std::invalid_argument{std::move(msg)};
                 }
             }
+            else if (mediaType == "video/uyvy")
+            {
+                if (!_interlaced || ((height % 2) == 0))
+                {
+                    // Interlaced media is handled as separate fields.
+                    auto const h = _interlaced ? height / 2 : height;
+                    payloadSize = getUyvyLineLength(width) * h;
+                }
+                else
+                {
+                    auto msg = std::string{"Invalid video height for interlaced UYVY. Must be even."};
+                    throw std::invalid_argument{std::move(msg)};
+                }
+            }
+            else if ((mediaType == "video/rgba") || (mediaType == "video/bgra"))
+            {
+                if (!_interlaced || ((height % 2) == 0))
+                {
+                    // Interlaced media is handled as separate fields.
+                    auto const h = _interlaced ? height / 2 : height;
+                    payloadSize = getRgbaLineLength(width) * h;
+                }
+                else
+                {
+                    auto msg = std::string{"Invalid video height for interlaced RGBA. Must be even."};
+                    throw std::invalid_argument{std::move(msg)};
+                }
+            }
+            else if ((mediaType == "video/i420") || (mediaType == "video/nv12"))
+            {
+                // A slice holds two luma lines and their chroma line, in every plane.
+                auto const sliceLengths = getPayloadSliceLengths();
+                payloadSize = std::accumulate(sliceLengths.begin(), sliceLengths.end(), std::size_t{0}) * getTotalPayloadSlices();
+            }
             else
             {
                 auto msg = std::string{"Unsupported video media_type: "} + mediaType;
@@ -378,7 +413,9 @@ namespace mxl::lib
         else if (_format == MXL_DATA_FORMAT_DATA)
         {
             auto const mediaType = fetchAs<std::string>(_root, "media_type");
-            if (mediaType == "video/smpte291")
+            // ST 2038 ANC (video/smpte291), KLV (video/smpte336m) and JSON messages (application/json)
+            // share the fixed data grain size.
+            if ((mediaType == "video/smpte291") || (mediaType == "video/smpte336m") || (mediaType == "application/json"))
             {
                 payloadSize = MXL_DATA_FORMAT_GRAIN_SIZE;
             }
@@ -391,9 +428,10 @@ namespace mxl::lib
         else if (_format == MXL_DATA_FORMAT_AUDIO)
         {
             // TODO: Also check the media type once we agreed on how to encode
-            //      single precision IEEE floats.
+            //      single precision IEEE floats. 16 and 24 bits are the integer
+            //      samples of audio/L16 and audio/L24.
             auto const bitDepth = fetchAs<double>(_root, "bit_depth");
-            if ((bitDepth != 32.0) && (bitDepth != 64.0))
+            if ((bitDepth != 16.0) && (bitDepth != 24.0) && (bitDepth != 32.0) && (bitDepth != 64.0))
             {
                 auto msg = fmt::format("Unsupported bit depth: {}", bitDepth);
                 throw std::invalid_argument{std::move(msg)};
@@ -426,7 +464,7 @@ namespace mxl::lib
             case MXL_DATA_FORMAT_VIDEO:
             {
                 // For video flows the slice length is the byte-length of a single
-                // line of v210 video.
+                // line of video.
                 auto const width = static_cast<std::size_t>(fetchAs<double>(_root, "frame_width"));
                 auto const mediaType = fetchAs<std::string>(_root, "media_type");
                 auto const v210fillSize = getV210LineLength(width);
@@ -440,6 +478,27 @@ namespace mxl::lib
                     sliceLengths[0] = v210fillSize;
                     sliceLengths[1] = get10BitAlphaLineLength(width);
                 }
+                else if (mediaType == "video/uyvy")
+                {
+                    sliceLengths[0] = getUyvyLineLength(width);
+                }
+                else if ((mediaType == "video/rgba") || (mediaType == "video/bgra"))
+                {
+                    sliceLengths[0] = getRgbaLineLength(width);
+                }
+                else if (mediaType == "video/i420")
+                {
+                    // Two luma lines and one line of each chroma plane.
+                    sliceLengths[0] = 2 * get420LumaLineLength(width);
+                    sliceLengths[1] = getI420ChromaLineLength(width);
+                    sliceLengths[2] = getI420ChromaLineLength(width);
+                }
+                else if (mediaType == "video/nv12")
+                {
+                    // Two luma lines and one line of interleaved chroma.
+                    sliceLengths[0] = 2 * get420LumaLineLength(width);
+                    sliceLengths[1] = get420LumaLineLength(width);
+                }
                 else
                 {
                     auto msg = std::string{"Unsupported video media_type: "} + mediaType;
@@ -469,13 +528,27 @@ namespace mxl::lib
 
             case MXL_DATA_FORMAT_VIDEO:
             {
-                if (auto const mediaType = fetchAs<std::string>(_root, "media_type"); mediaType != "video/v210" && mediaType != "video/v210a")
+                auto const mediaType = fetchAs<std::string>(_root, "media_type");
+                if ((mediaType == "video/i420") || (mediaType == "video/nv12"))
+                {
+                    if (_interlaced)
+                    {
+                        auto msg = std::string{"Interlaced 4:2:0 video is not supported: "} + mediaType;
+                        throw std::invalid_argument{std::move(msg)};
+                    }
+
+                    // A slice holds a pair of lines, the last one padded for odd heights.
+                    auto const h = static_cast<std::size_t>(fetchAs<double>(_root, "frame_height"));
+                    return (h + 1) / 2;
+                }
+                if (mediaType != "video/v210" && mediaType != "video/v210a" && mediaType != "video/uyvy" && mediaType != "video/rgba" &&
+                    mediaType != "video/bgra")
                 {
                     auto msg = std::string{"Unsupported video media_type: "} + mediaType;
                     throw std::invalid_argument{std::move(msg)};
                 }
 
-                // For v210, the number of slices is always the number of video lines
+                // The number of slices is always the number of video lines
                 auto h = static_cast<std::size_t>(fetchAs<double>(_root, "frame_height"));
                 if (_interlaced)
                 {
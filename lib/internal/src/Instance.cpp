@@ -235,8 +235,9 @@ namespace mxl::lib
     {
         // Read the mandatory grain_rate field
         auto const grainRate = parser.getGrainRate();
-        // Compute the grain count based on our configured history duration
-        auto const grainCount = _historyDuration * grainRate.numerator / (1'000'000'000ULL * grainRate.denominator);
+        // Compute the grain count based on the history duration requested for this flow, or our configured one
+        auto const historyDuration = optionsParser.getHistoryDuration().value_or(_historyDuration);
+        auto const grainCount = historyDuration * grainRate.numerator / (1'000'000'000ULL * grainRate.denominator);
 
         auto const batchSizeDefault = parser.getTotalPayloadSlices();
 
@@ -259,11 +260,12 @@ namespace mxl::lib
     {
         // Read the mandatory grain_rate field
         auto const sampleRate = parser.getGrainRate();
-        // Compute the buffer length based on our configured history duration.
+        // Compute the buffer length based on the history duration requested for this flow, or our configured one.
         // The length is divided by 500M instead of 1B to effectively make it twice the
         // history duration, which is necessary, because only half of the buffer is
         // accessible for reading at any one point in time.
-        auto const bufferLength = _historyDuration * sampleRate.numerator / (500'000'000ULL * sampleRate.denominator);
+        auto const historyDuration = optionsParser.getHistoryDuration().value_or(_historyDuration);
+        auto const bufferLength = historyDuration * sampleRate.numerator / (500'000'000ULL * sampleRate.denominator);
 
         auto const sampleWordSize = parser.getPayloadSize();
         // FIXME: The page size is just an educated guess to round to for good measure
@@ -15,3 +15,27 @@ std::uint32_t mxl::lib::get10BitAlphaLineLength(std::size_t width)
 {
     return static_cast<std::uint32_t>((width + 2) / 3 * 4);
 }
+
+MXL_EXPORT
+std::uint32_t mxl::lib::getUyvyLineLength(std::size_t width)
+{
+    return static_cast<std::uint32_t>((width + 1) / 2 * 4);
+}
+
+MXL_EXPORT
+std::uint32_t mxl::lib::get420LumaLineLength(std::size_t width)
+{
+    return static_cast<std::uint32_t>((width + 3) / 4 * 4);
+}
+
+MXL_EXPORT
+std::uint32_t mxl::lib::getI420ChromaLineLength(std::size_t width)
+{
+    return static_cast<std::uint32_t>(((width + 1) / 2 + 3) / 4 * 4);
+}
+
+MXL_EXPORT
+std::uint32_t mxl::lib::getRgbaLineLength(std::size_t width)
+{
+    return static_cast<std::uint32_t>(width * 4);
+}
@@ -412,6 +412,52 @@ mxlStatus mxlFlowReaderGetGrainSliceNonBlocking(mxlFlowReader reader, uint64_t i
     }
 }
 
+extern "C"
+MXL_EXPORT
+mxlStatus mxlFlowWriterGetInfo(mxlFlowWriter writer, mxlFlowInfo* info)
+{
+    try
+    {
+        if (info != nullptr)
+        {
+            if (auto const cppWriter = to_FlowWriter(writer); cppWriter != nullptr)
+            {
+                *info = cppWriter->getFlowInfo();
+                return MXL_STATUS_OK;
+            }
+            return MXL_ERR_INVALID_FLOW_WRITER;
+        }
+        return MXL_ERR_INVALID_ARG;
+    }
+    catch (...)
+    {
+        return MXL_ERR_UNKNOWN;
+    }
+}
+
+extern "C"
+MXL_EXPORT
+mxlStatus mxlFlowWriterGetRuntimeInfo(mxlFlowWriter writer, mxlFlowRuntimeInfo* info)
+{
+    try
+    {
+        if (info != nullptr)
+        {
+            if (auto const cppWriter = to_FlowWriter(writer); cppWriter != nullptr)
+            {
+                *info = cppWriter->getFlowRuntimeInfo();
+                return MXL_STATUS_OK;
+            }
+            return MXL_ERR_INVALID_FLOW_WRITER;
+        }
+        return MXL_ERR_INVALID_ARG;
+    }
+    catch (...)
+    {
+        return MXL_ERR_UNKNOWN;
+    }
+}
+
 extern "C"
 MXL_EXPORT
 mxlStatus mxlFlowWriterGetGrainInfo(mxlFlowWriter writer, uint64_t index, mxlGrainInfo* grainInfo)
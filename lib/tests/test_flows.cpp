@@ -122,6 +122,16 @@ TEST_CASE_PERSISTENT_FIXTURE(mxl::tests::mxlDomainFixture, "Video Flow : Create/
     // We commited a new grain. This should have increased the lastWriteTime field.
     REQUIRE(runtimeInfo2.lastWriteTime > runtimeInfo1.lastWriteTime);
 
+    /// The writer sees the same head without going through a reader.
+    mxlFlowRuntimeInfo writerRuntimeInfo;
+    REQUIRE(mxlFlowWriterGetRuntimeInfo(writer, &writerRuntimeInfo) == MXL_STATUS_OK);
+    REQUIRE(writerRuntimeInfo.headIndex == runtimeInfo2.headIndex);
+
+    mxlFlowInfo writerInfo;
+    REQUIRE(mxlFlowWriterGetInfo(writer, &writerInfo) == MXL_STATUS_OK);
+    REQUIRE(writerInfo.config.common.format == MXL_DATA_FORMAT_VIDEO);
+    REQUIRE(mxlFlowWriterGetInfo(writer, nullptr) == MXL_ERR_INVALID_ARG);
+
     /// Release the reader
     REQUIRE(mxlReleaseFlowReader(instanceReader, reader) == MXL_STATUS_OK);
 
@@ -552,6 +562,20 @@ TEST_CASE_PERSISTENT_FIXTURE(mxl::tests::mxlDomainFixture, "Video Flow : Options
     REQUIRE(configInfo.common.maxCommitBatchSizeHint == 1080U);
     REQUIRE(configInfo.common.maxSyncBatchSizeHint == 1080U);
     REQUIRE(mxlReleaseFlowWriter(instanceWriter, writer) == MXL_STATUS_OK);
+
+    optsObj.clear();
+    optsObj["historyDuration"] = picojson::value{0.0}; // Invalid value
+    optsStr = picojson::value(optsObj).serialize();
+    REQUIRE(mxlCreateFlowWriter(instanceWriter, flowDef.c_str(), optsStr.c_str(), &writer, &configInfo, nullptr) == MXL_ERR_UNKNOWN);
+
+    optsObj.clear();
+    optsObj["historyDuration"] = picojson::value{1'000'000'000.0};
+    optsStr = picojson::value(optsObj).serialize();
+    flowWasCreated = false;
+    REQUIRE(mxlCreateFlowWriter(instanceWriter, flowDef.c_str(), optsStr.c_str(), &writer, &configInfo, &flowWasCreated) == MXL_STATUS_OK);
+    REQUIRE(flowWasCreated);
+    REQUIRE(configInfo.discrete.grainCount == 29U); // 1s worth of grains at 30000/1001
+    REQUIRE(mxlReleaseFlowWriter(instanceWriter, writer) == MXL_STATUS_OK);
     REQUIRE(mxlDestroyInstance(instanceWriter) == MXL_STATUS_OK);
 }
 
@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversion and timing helpers of `mxlsink` and `mxlsrc`, for applications
+//! that move buffers between MXL flows and `appsrc` / `appsink` themselves.
+//!
+//! These run the same code paths as the elements, so a flow written or read
+//! through them is indistinguishable from one handled by the elements.
+
+use std::sync::Mutex;
+
+use gstreamer as gst;
+use mxl::{FlowConfigInfo, GrainData, MxlInstance, Rational, flowdef::FlowDefDetails};
+
+use crate::format::klv::KLV_MEDIA_TYPE;
+use crate::mxlsink::{
+    self,
+    state::{Settings, State, init_state_from_caps},
+    write_ahead::{ClockWait, WriteAheadLimit, WriteAheadPolicy},
+};
+use crate::mxlsrc::{
+    create_discrete::grain_to_buffer, mxl_helper::caps_for_flow_def, state::DiscreteFormat,
+    timing::index_period,
+};
+
+/// Writer side of an MXL flow, as set up by `mxlsink` on caps negotiation.
+pub struct FlowWriterState {
+    state: State,
+    timestamp_offset: u64,
+    clock_wait: Mutex<ClockWait>,
+}
+
+impl FlowWriterState {
+    /// Creates flow `flow_id` with a flow definition derived from `caps`.
+    /// Accepts the same caps as `mxlsink`.
+    pub fn new(
+        instance: MxlInstance,
+        flow_id: &str,
+        caps: &gst::Caps,
+    ) -> Result<Self, gst::LoggableError> {
+        let settings = Settings {
+            flow_id: flow_id.to_owned(),
+            ..Settings::default()
+        };
+        let mut state = State {
+            instance,
+            flow_config: None,
+            flow_state: None,
+        };
+        init_state_from_caps(&mut state, caps, &settings, None)?;
+        Ok(Self {
+            state,
+            timestamp_offset: 0,
+            clock_wait: Mutex::default(),
+        })
+    }
+
+    /// Sets the amount added to a buffer's PTS to get its MXL (TAI) timestamp.
+    /// `mxlsink` uses `D + base_time`, with `D` the offset between MXL time and
+    /// the pipeline clock. Defaults to 0, for buffers timestamped in TAI.
+    pub fn set_timestamp_offset(&mut self, offset: u64) {
+        self.timestamp_offset = offset;
+    }
+
+    pub fn flow_config(&self) -> Option<&FlowConfigInfo> {
+        self.state.flow_config.as_ref()
+    }
+}
+
+/// Writes the buffer of `sample` to the flow, at the grain or sample index its
+/// timestamp maps to.
+pub fn push_sample_to_flow(
+    sample: &gst::Sample,
+    writer_state: &mut FlowWriterState,
+) -> Result<gst::FlowSuccess, gst::FlowError> {
+    let buffer = sample.buffer_owned().ok_or(gst::FlowError::Error)?;
+    let limit = WriteAheadLimit {
+        max_ahead: 0,
+        policy: WriteAheadPolicy::default(),
+        clock_wait: &writer_state.clock_wait,
+        element: None,
+        blocked: Default::default(),
+    };
+    mxlsink::render_buffer(
+        &mut writer_state.state,
+        &buffer,
+        writer_state.timestamp_offset,
+        &limit,
+    )
+}
+
+/// Caps of the samples produced for a flow with the definition `flow_def`
+/// and the `media_type`, as `mxlsrc` negotiates them.
+pub fn flow_def_caps(flow_def: &FlowDefDetails, media_type: &str) -> gst::Caps {
+    caps_for_flow_def(flow_def, media_type)
+}
+
+/// Converts a grain of a video or data flow of `media_type` to the sample
+/// `mxlsrc` would push for it, with the given PTS and a duration of one grain.
+pub fn grain_to_sample(
+    grain: &GrainData<'_>,
+    flow_def: &FlowDefDetails,
+    media_type: &str,
+    pts: gst::ClockTime,
+) -> Result<gst::Sample, gst::FlowError> {
+    let (format, rate) = match flow_def {
+        FlowDefDetails::Video(video) => (DiscreteFormat::Video, &video.grain_rate),
+        FlowDefDetails::Data(data) if media_type == KLV_MEDIA_TYPE => {
+            (DiscreteFormat::Klv, &data.grain_rate)
+        }
+        FlowDefDetails::Data(data) => (DiscreteFormat::Data, &data.grain_rate),
+        FlowDefDetails::Audio(_) => return Err(gst::FlowError::NotSupported),
+    };
+    let mut buffer = grain_to_buffer(format, grain.payload)?;
+    {
+        let buffer = buffer.get_mut().ok_or(gst::FlowError::Error)?;
+        buffer.set_pts(pts);
+        buffer.set_duration(index_period(&Rational::from(rate)));
+    }
+    Ok(gst::Sample::builder()
+        .buffer(&buffer)
+        .caps(&caps_for_flow_def(flow_def, media_type))
+        .build())
+}
@@ -4,13 +4,14 @@
 //! The MXL-time ↔ pipeline-clock offset shared by `mxlsrc` (reading) and
 //! `mxlsink` (writing).
 //!
-//! The MXL elements do **not** provide a pipeline clock. MXL time is
+//! The MXL elements do **not** provide a pipeline clock by default. MXL time is
 //! `clock_gettime(CLOCK_TAI)`, a wall clock that can step (NTP, leap seconds,
 //! or a hypervisor time sync), so pacing `GstBaseSink` on it stalls the
 //! pipeline. Instead the pipeline runs on whatever clock it selects (the
 //! default monotonic system clock) and each element tracks the constant offset
 //! `D = mxl_now - pipeline_clock_now`, sampled once and shared between the
-//! pipeline's MXL elements.
+//! pipeline's MXL elements. `mxlsrc provide-clock=true` opts into an
+//! [`MxlClock`](crate::mxlclock::MxlClock) instead, for which `D` is about zero.
 
 use gst::glib;
 use gst::prelude::*;
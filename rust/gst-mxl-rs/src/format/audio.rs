@@ -0,0 +1,103 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! MXL audio media types vs GStreamer `audio/x-raw` formats.
+//!
+//! `audio/float32` is `F32LE`, `audio/L16` is `S16LE` and `audio/L24` is `S24_32LE`. Flows store
+//! 24-bit samples in 3 bytes, so `S24_32LE` samples are packed and unpacked while
+//! (de-)interleaving, see [`SampleFormat::S24In32`].
+
+use mxl::{
+    SampleFormat,
+    flowdef::{AUDIO_FLOAT32, AUDIO_L16, AUDIO_L24},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    F32,
+    S16,
+    S24,
+}
+
+impl AudioFormat {
+    pub const ALL: [AudioFormat; 3] = [AudioFormat::F32, AudioFormat::S16, AudioFormat::S24];
+
+    /// Format of a GStreamer `audio/x-raw` `format` field, `None` for other formats.
+    pub fn from_gst_format(format: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|audio_format| audio_format.gst_format() == format)
+    }
+
+    /// Format of an audio flow's `media_type`, `None` for other media types.
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|audio_format| audio_format.media_type().eq_ignore_ascii_case(media_type))
+    }
+
+    pub fn gst_format(self) -> &'static str {
+        match self {
+            AudioFormat::F32 => "F32LE",
+            AudioFormat::S16 => "S16LE",
+            AudioFormat::S24 => "S24_32LE",
+        }
+    }
+
+    pub fn media_type(self) -> &'static str {
+        match self {
+            AudioFormat::F32 => AUDIO_FLOAT32,
+            AudioFormat::S16 => AUDIO_L16,
+            AudioFormat::S24 => AUDIO_L24,
+        }
+    }
+
+    /// Bit depth of the flow definition.
+    pub fn bit_depth(self) -> u8 {
+        match self {
+            AudioFormat::F32 => 32,
+            AudioFormat::S16 => 16,
+            AudioFormat::S24 => 24,
+        }
+    }
+
+    /// Sample format of the interleaved GStreamer buffers.
+    pub fn interleaved_format(self) -> SampleFormat {
+        match self {
+            AudioFormat::F32 => SampleFormat::F32,
+            AudioFormat::S16 => SampleFormat::S16,
+            AudioFormat::S24 => SampleFormat::S24In32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_round_trip_through_gst_formats_and_media_types() {
+        for format in AudioFormat::ALL {
+            assert_eq!(
+                AudioFormat::from_gst_format(format.gst_format()),
+                Some(format)
+            );
+            assert_eq!(
+                AudioFormat::from_media_type(format.media_type()),
+                Some(format)
+            );
+        }
+        assert_eq!(AudioFormat::from_gst_format("S24LE"), None);
+        assert_eq!(
+            AudioFormat::from_media_type("audio/l24"),
+            Some(AudioFormat::S24)
+        );
+    }
+
+    #[test]
+    fn s24_buffers_carry_24_bit_samples_in_32_bit_words() {
+        let format = AudioFormat::S24.interleaved_format();
+        assert_eq!(format.bytes_per_sample(), 4);
+        assert_eq!(format.bits(), u32::from(AudioFormat::S24.bit_depth()));
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! MXL `video/smpte291` data grains from and to GStreamer closed caption buffers and metas.
+//!
+//! A `closedcaption/x-cea-708, format=cdp` buffer holds one caption distribution packet, written
+//! as a single ST 334-1 ANC packet. A `closedcaption/x-cea-608, format=s334-1a` buffer holds
+//! triplets of a field/line byte and two caption bytes, each written as a CEA-608 ANC packet.
+//! Either way the grain is what `cctost2038anc` followed by `mxlsink` would write, so `mxlsrc`
+//! reads it back as `meta/x-st-2038`.
+//!
+//! The [`Caption`]s of a video frame, its `GstVideoCaptionMeta`, map to a grain the same way.
+
+use mxl::MXL_DATA_FORMAT_GRAIN_SIZE;
+use mxl::anc::{AncGrain, AncPacket};
+
+/// Captions of a video frame, in the formats of `GstVideoCaptionMeta` a grain can carry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Caption {
+    /// One CEA-708 CDP.
+    Cea708Cdp(Vec<u8>),
+    /// CEA-608 S334-1A triplets.
+    Cea608S3341a(Vec<u8>),
+}
+
+/// Make an MXL `video/smpte291` data grain from a GStreamer CEA-708 CDP buffer.
+pub fn mxl_smpte291_grain_from_gst_cdp(cdp: &[u8]) -> Result<Vec<u8>, mxl::Error> {
+    encode_grain(vec![AncPacket::cea708(cdp.to_vec())])
+}
+
+/// Make an MXL `video/smpte291` data grain from a GStreamer CEA-608 S334-1A buffer.
+pub fn mxl_smpte291_grain_from_gst_s334_1a(s334_1a: &[u8]) -> Result<Vec<u8>, mxl::Error> {
+    encode_grain(cea608_packets(s334_1a)?)
+}
+
+/// Make an MXL `video/smpte291` data grain from the captions of a video frame.
+pub fn mxl_smpte291_grain_from_captions(captions: &[Caption]) -> Result<Vec<u8>, mxl::Error> {
+    let mut packets = Vec::new();
+    for caption in captions {
+        match caption {
+            Caption::Cea708Cdp(cdp) => packets.push(AncPacket::cea708(cdp.clone())),
+            Caption::Cea608S3341a(s334_1a) => packets.extend(cea608_packets(s334_1a)?),
+        }
+    }
+    encode_grain(packets)
+}
+
+/// Read the captions of an MXL `video/smpte291` data grain: a [`Caption::Cea708Cdp`] for each
+/// CEA-708 packet, then a [`Caption::Cea608S3341a`] with the triplets of all CEA-608 packets.
+/// Other ANC packets are left out.
+pub fn captions_from_mxl_smpte291_grain(grain: &[u8]) -> Result<Vec<Caption>, mxl::Error> {
+    let mut captions = Vec::new();
+    let mut s334_1a = Vec::new();
+    for packet in AncGrain::decode(grain)?.packets {
+        if packet.is_cea708() {
+            captions.push(Caption::Cea708Cdp(packet.user_data));
+        } else if packet.is_cea608() && packet.user_data.len() == 3 {
+            s334_1a.extend_from_slice(&packet.user_data);
+        }
+    }
+    if !s334_1a.is_empty() {
+        captions.push(Caption::Cea608S3341a(s334_1a));
+    }
+    Ok(captions)
+}
+
+fn cea608_packets(s334_1a: &[u8]) -> Result<Vec<AncPacket>, mxl::Error> {
+    if !s334_1a.len().is_multiple_of(3) {
+        return Err(mxl::Error::Other(format!(
+            "S334-1A buffer of {} bytes is not made of triplets.",
+            s334_1a.len()
+        )));
+    }
+    Ok(s334_1a
+        .chunks_exact(3)
+        .map(|triplet| AncPacket::cea608(triplet[0], [triplet[1], triplet[2]]))
+        .collect())
+}
+
+fn encode_grain(packets: Vec<AncPacket>) -> Result<Vec<u8>, mxl::Error> {
+    let mut grain = vec![0; MXL_DATA_FORMAT_GRAIN_SIZE];
+    AncGrain::new(packets).encode(&mut grain)?;
+    Ok(grain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CDP of a 29.97 fps frame with two cc_data triplets, with its checksum.
+    const CDP: &[u8] = &[
+        0x96, 0x69, 0x10, 0x4f, 0x43, 0x00, 0x00, 0x72, 0xe2, 0xfc, 0x80, 0x80, 0xfd, 0x80, 0x80,
+        0x74, 0x00, 0x00, 0x3f,
+    ];
+
+    #[test]
+    fn cdp_grain_holds_one_cea708_packet() {
+        let grain = mxl_smpte291_grain_from_gst_cdp(CDP).expect("CDP grain");
+        assert_eq!(grain.len(), MXL_DATA_FORMAT_GRAIN_SIZE);
+        let packets = AncGrain::decode(&grain).expect("ANC grain").packets;
+        assert_eq!(packets.len(), 1);
+        assert!(packets[0].is_cea708());
+        assert_eq!(packets[0].user_data, CDP);
+    }
+
+    #[test]
+    fn s334_1a_grain_holds_a_cea608_packet_per_triplet() {
+        let s334_1a = [0x80, 0x94, 0x2c, 0x00, 0x80, 0x80];
+        let grain = mxl_smpte291_grain_from_gst_s334_1a(&s334_1a).expect("S334-1A grain");
+        let packets = AncGrain::decode(&grain).expect("ANC grain").packets;
+        assert_eq!(
+            packets,
+            vec![
+                AncPacket::cea608(0x80, [0x94, 0x2c]),
+                AncPacket::cea608(0x00, [0x80, 0x80]),
+            ]
+        );
+    }
+
+    #[test]
+    fn s334_1a_grain_rejects_partial_triplets() {
+        assert!(mxl_smpte291_grain_from_gst_s334_1a(&[0x80, 0x94]).is_err());
+    }
+
+    #[test]
+    fn captions_round_trip_through_a_grain() {
+        let captions = vec![
+            Caption::Cea708Cdp(CDP.to_vec()),
+            Caption::Cea608S3341a(vec![0x80, 0x94, 0x2c, 0x00, 0x80, 0x80]),
+        ];
+        let grain = mxl_smpte291_grain_from_captions(&captions).expect("caption grain");
+        assert_eq!(
+            captions_from_mxl_smpte291_grain(&grain).expect("captions"),
+            captions
+        );
+    }
+
+    #[test]
+    fn captions_skip_other_anc_packets() {
+        let mut grain = vec![0; MXL_DATA_FORMAT_GRAIN_SIZE];
+        AncGrain::new(vec![
+            AncPacket::scte104(&[0x01, 0x02]).expect("SCTE-104 packet"),
+            AncPacket::cea708(CDP.to_vec()),
+        ])
+        .encode(&mut grain)
+        .expect("ANC grain");
+        assert_eq!(
+            captions_from_mxl_smpte291_grain(&grain).expect("captions"),
+            vec![Caption::Cea708Cdp(CDP.to_vec())]
+        );
+    }
+}
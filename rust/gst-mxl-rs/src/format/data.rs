@@ -16,6 +16,9 @@ use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter};
 use mxl::MXL_DATA_FORMAT_GRAIN_SIZE;
 use std::io::Cursor;
 
+/// Media type of ANC data flows (RFC 8331).
+pub const SMPTE291_MEDIA_TYPE: &str = "video/smpte291";
+
 #[derive(Debug, thiserror::Error)]
 pub enum AncillaryMapError {
     #[error("invalid ancillary data: {0}")]
@@ -337,8 +340,10 @@ pub fn mxl_smpte291_grain_from_gst_st2038(st2038: &[u8]) -> Result<Vec<u8>, Anci
     Ok(smpte291)
 }
 
-/// Make a GStreamer `meta/x-st-2038` buffer from an MXL `video/smpte291` data grain.
-pub fn gst_st2038_from_mxl_smpte291_grain(smpte291: &[u8]) -> Result<Vec<u8>, AncillaryMapError> {
+/// Read the ANC packets of an MXL `video/smpte291` data grain.
+pub fn ancillary_metas_from_mxl_smpte291_grain(
+    smpte291: &[u8],
+) -> Result<Vec<AncillaryMeta>, AncillaryMapError> {
     let mut r = BitReader::endian(Cursor::new(smpte291), BigEndian);
     let length = r.read_to::<u16>()? as usize;
     let anc_count = r.read_to::<u8>()? as usize;
@@ -353,15 +358,24 @@ pub fn gst_st2038_from_mxl_smpte291_grain(smpte291: &[u8]) -> Result<Vec<u8>, An
                 "RFC 8331 Length exceeds MXL data grain size",
             ))?;
 
-    let mut st2038 = Vec::new();
+    let mut metas = Vec::with_capacity(anc_count);
     for _ in 0..anc_count {
-        st2038_from_smpte291_anc_packet(&mut st2038, &mut remaining)?;
+        metas.push(ancillary_meta_from_smpte291_anc_packet(&mut remaining)?);
     }
     if !remaining.is_empty() {
         return Err(AncillaryMapError::Invalid(
             "RFC 8331 Length does not match ANC_Count",
         ));
     }
+    Ok(metas)
+}
+
+/// Make a GStreamer `meta/x-st-2038` buffer from an MXL `video/smpte291` data grain.
+pub fn gst_st2038_from_mxl_smpte291_grain(smpte291: &[u8]) -> Result<Vec<u8>, AncillaryMapError> {
+    let mut st2038 = Vec::new();
+    for meta in ancillary_metas_from_mxl_smpte291_grain(smpte291)? {
+        st2038_anc_packet_from_ancillary_meta(&mut st2038, &meta)?;
+    }
     Ok(st2038)
 }
 
@@ -502,6 +516,17 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn ancillary_metas_from_mxl_smpte291_grain_reads_every_packet() {
+        let grain = mxl_smpte291_grain_from_gst_st2038(&ST2038_TEST_PACKETS.concat()).unwrap();
+        let actual = ancillary_metas_from_mxl_smpte291_grain(&grain).unwrap();
+        let expected: Vec<AncillaryMeta> = ST2038_TEST_PACKETS
+            .iter()
+            .map(|packet| ancillary_meta_from_st2038_anc_packet(&mut &packet[..]).unwrap())
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn mxl_smpte291_grain_from_gst_st2038_empty_st2038_zero_fills_grain() {
         let actual = mxl_smpte291_grain_from_gst_st2038(&[]).unwrap();
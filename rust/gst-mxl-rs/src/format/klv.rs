@@ -0,0 +1,51 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! MXL `video/smpte336m` data grains vs GStreamer `meta/x-klv` buffers.
+//!
+//! A KLV grain holds the KLV packets of one grain interval, in the length-prefixed framing of
+//! [`mxl::grain::packets`]. `mxlsink` writes each buffer as one packet. `mxlsrc` pushes one buffer
+//! per grain with its packets concatenated, which stays a valid KLV stream as KLV packets carry
+//! their own length.
+
+use mxl::{MXL_DATA_FORMAT_GRAIN_SIZE, decode_packets, encode_packets};
+
+/// Media type of KLV data flows (RFC 6597).
+pub const KLV_MEDIA_TYPE: &str = "video/smpte336m";
+
+/// Make an MXL `video/smpte336m` data grain from a GStreamer `meta/x-klv` buffer.
+pub fn mxl_klv_grain_from_gst_klv(klv: &[u8]) -> Result<Vec<u8>, mxl::Error> {
+    let mut grain = vec![0; MXL_DATA_FORMAT_GRAIN_SIZE];
+    encode_packets(&[klv], &mut grain)?;
+    Ok(grain)
+}
+
+/// Make a GStreamer `meta/x-klv` buffer from an MXL `video/smpte336m` data grain.
+pub fn gst_klv_from_mxl_klv_grain(grain: &[u8]) -> Result<Vec<u8>, mxl::Error> {
+    Ok(decode_packets(grain)?.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MISB ST 0601 UAS Datalink Local Set with a single precision time stamp item.
+    const KLV_PACKET: &[u8] = &[
+        0x06, 0x0e, 0x2b, 0x34, 0x02, 0x0b, 0x01, 0x01, 0x0e, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00,
+        0x00, 0x0a, 0x02, 0x08, 0x00, 0x04, 0x59, 0xf4, 0xa6, 0xaa, 0x4a, 0xa8,
+    ];
+
+    #[test]
+    fn klv_grain_round_trip() {
+        let grain = mxl_klv_grain_from_gst_klv(KLV_PACKET).expect("klv grain");
+        assert_eq!(grain.len(), MXL_DATA_FORMAT_GRAIN_SIZE);
+        let klv = gst_klv_from_mxl_klv_grain(&grain).expect("gst klv");
+        assert_eq!(klv, KLV_PACKET);
+    }
+
+    #[test]
+    fn klv_grain_rejects_oversize_buffer() {
+        let oversize = vec![0xaa; MXL_DATA_FORMAT_GRAIN_SIZE];
+        assert!(mxl_klv_grain_from_gst_klv(&oversize).is_err());
+    }
+}
@@ -2,4 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Buffer and grain **layout** helpers.
+pub mod audio;
+pub mod captions;
 pub mod data;
+pub mod klv;
+pub mod timecode;
+pub mod video;
@@ -0,0 +1,89 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! MXL `video/smpte291` data grains from and to the timecode of a video frame.
+//!
+//! The timecode of a frame, its `GstVideoTimeCodeMeta`, is written as a single ST 12-2 ancillary
+//! time code packet, the way an SDI link carries it, so the grain also reads back as
+//! `meta/x-st-2038`. Frames without a stored timecode get the one of their grain index.
+
+use mxl::anc::{AncGrain, AncPacket};
+use mxl::{MXL_DATA_FORMAT_GRAIN_SIZE, Rational, RationalExt, Timecode, index_to_timecode};
+
+/// Make an MXL `video/smpte291` data grain from the timecode of a frame of a flow of grain rate
+/// `rate`.
+pub fn mxl_smpte291_grain_from_timecode(
+    timecode: &Timecode,
+    rate: &Rational,
+) -> Result<Vec<u8>, mxl::Error> {
+    let mut grain = vec![0; MXL_DATA_FORMAT_GRAIN_SIZE];
+    AncGrain::new(vec![AncPacket::timecode(timecode, rate)?]).encode(&mut grain)?;
+    Ok(grain)
+}
+
+/// Read the timecode of the first ancillary time code packet of an MXL `video/smpte291` data
+/// grain, `None` if there is none.
+pub fn timecode_from_mxl_smpte291_grain(
+    grain: &[u8],
+    rate: &Rational,
+) -> Result<Option<Timecode>, mxl::Error> {
+    Ok(AncGrain::decode(grain)?
+        .packets
+        .iter()
+        .find_map(|packet| packet.atc_timecode(rate)))
+}
+
+/// Timecode of grain `index` of a flow of grain rate `rate`, drop-frame at the rates that define
+/// it (29.97 and 59.94 fps).
+pub fn index_timecode(index: u64, rate: &Rational) -> Result<Timecode, mxl::Error> {
+    let reduced = rate.reduced();
+    let drop_frame = reduced.denominator == 1001 && reduced.numerator % 30_000 == 0;
+    index_to_timecode(index, rate, drop_frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mxl::rates;
+
+    #[test]
+    fn timecode_round_trips_through_a_grain() {
+        let timecode: Timecode = "01:02:03;04".parse().expect("timecode");
+        let grain = mxl_smpte291_grain_from_timecode(&timecode, &rates::FPS_29_97).expect("grain");
+        assert_eq!(grain.len(), MXL_DATA_FORMAT_GRAIN_SIZE);
+        assert_eq!(
+            timecode_from_mxl_smpte291_grain(&grain, &rates::FPS_29_97).expect("ANC grain"),
+            Some(timecode)
+        );
+
+        let captions =
+            crate::format::captions::mxl_smpte291_grain_from_gst_s334_1a(&[0x80, 0x94, 0x2c])
+                .expect("caption grain");
+        assert_eq!(
+            timecode_from_mxl_smpte291_grain(&captions, &rates::FPS_29_97).expect("ANC grain"),
+            None
+        );
+    }
+
+    #[test]
+    fn index_timecode_drops_frames_at_29_97_only() {
+        assert_eq!(
+            index_timecode(1800, &rates::FPS_29_97)
+                .expect("timecode")
+                .to_string(),
+            "00:01:00;02"
+        );
+        assert_eq!(
+            index_timecode(1800, &rates::FPS_30)
+                .expect("timecode")
+                .to_string(),
+            "00:01:00:00"
+        );
+        assert_eq!(
+            index_timecode(50, &rates::FPS_25)
+                .expect("timecode")
+                .to_string(),
+            "00:00:02:00"
+        );
+    }
+}
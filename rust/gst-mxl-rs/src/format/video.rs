@@ -0,0 +1,223 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! MXL video media types vs GStreamer `video/x-raw` formats.
+//!
+//! Grains and buffers have the same layout, GStreamer's default strides and plane offsets, so
+//! they are copied as they are: `video/v210` is GStreamer `v210` with 10-bit 4:2:2 components,
+//! `video/uyvy` is `UYVY` with 8-bit 4:2:2 components, `video/i420` and `video/nv12` are `I420`
+//! and `NV12` with 8-bit 4:2:0 components, and `video/rgba` and `video/bgra` are `RGBA` and
+//! `BGRA` with 8-bit R, G, B and A components, for graphics carrying fill and key.
+//!
+//! GStreamer has no raw format for `video/v210a`, whose key follows the v210 fill as a plane of
+//! its own, so v210a flows are not mapped.
+
+use mxl::flowdef::Component;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFormat {
+    V210,
+    Uyvy,
+    I420,
+    Nv12,
+    Rgba,
+    Bgra,
+}
+
+/// A plane of a video grain: `lines` lines of `stride` bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plane {
+    pub stride: usize,
+    pub lines: usize,
+}
+
+impl Plane {
+    pub fn size(&self) -> usize {
+        self.stride * self.lines
+    }
+}
+
+impl VideoFormat {
+    pub const ALL: [VideoFormat; 6] = [
+        VideoFormat::V210,
+        VideoFormat::Uyvy,
+        VideoFormat::I420,
+        VideoFormat::Nv12,
+        VideoFormat::Rgba,
+        VideoFormat::Bgra,
+    ];
+
+    /// Format of a GStreamer `video/x-raw` `format` field, `None` for other formats.
+    pub fn from_gst_format(format: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|video_format| video_format.gst_format() == format)
+    }
+
+    /// Format of a video flow's `media_type`, `None` for other media types.
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|video_format| video_format.media_type() == media_type)
+    }
+
+    pub fn gst_format(self) -> &'static str {
+        match self {
+            VideoFormat::V210 => "v210",
+            VideoFormat::Uyvy => "UYVY",
+            VideoFormat::I420 => "I420",
+            VideoFormat::Nv12 => "NV12",
+            VideoFormat::Rgba => "RGBA",
+            VideoFormat::Bgra => "BGRA",
+        }
+    }
+
+    pub fn media_type(self) -> &'static str {
+        match self {
+            VideoFormat::V210 => "video/v210",
+            VideoFormat::Uyvy => "video/uyvy",
+            VideoFormat::I420 => "video/i420",
+            VideoFormat::Nv12 => "video/nv12",
+            VideoFormat::Rgba => "video/rgba",
+            VideoFormat::Bgra => "video/bgra",
+        }
+    }
+
+    /// Whether chroma has half the lines of luma, which MXL only supports for progressive video.
+    pub fn is_420(self) -> bool {
+        matches!(self, VideoFormat::I420 | VideoFormat::Nv12)
+    }
+
+    /// Whether pixels carry an alpha (key) sample next to the fill.
+    pub fn has_alpha(self) -> bool {
+        matches!(self, VideoFormat::Rgba | VideoFormat::Bgra)
+    }
+
+    /// The Y, Cb and Cr components, or the R, G, B and A components, of the flow definition of a
+    /// `width` x `height` picture.
+    pub fn components(self, width: i32, height: i32) -> Vec<Component> {
+        let (bit_depth, chroma_height) = match self {
+            VideoFormat::V210 => (10, height),
+            VideoFormat::Uyvy | VideoFormat::Rgba | VideoFormat::Bgra => (8, height),
+            VideoFormat::I420 | VideoFormat::Nv12 => (8, height / 2),
+        };
+        let component = |name: &str, width, height| Component {
+            name: name.into(),
+            width,
+            height,
+            bit_depth,
+        };
+        if self.has_alpha() {
+            return ["R", "G", "B", "A"]
+                .map(|name| component(name, width, height))
+                .to_vec();
+        }
+        vec![
+            component("Y", width, height),
+            component("Cb", width / 2, chroma_height),
+            component("Cr", width / 2, chroma_height),
+        ]
+    }
+
+    /// Planes of a progressive `width` x `height` grain, in payload order.
+    pub fn planes(self, width: usize, height: usize) -> Vec<Plane> {
+        let round_up_4 = |bytes: usize| bytes.div_ceil(4) * 4;
+        let line_pairs = height.div_ceil(2);
+        match self {
+            VideoFormat::V210 => vec![Plane {
+                stride: width.div_ceil(48) * 128,
+                lines: height,
+            }],
+            VideoFormat::Uyvy => vec![Plane {
+                stride: width.div_ceil(2) * 4,
+                lines: height,
+            }],
+            VideoFormat::Rgba | VideoFormat::Bgra => vec![Plane {
+                stride: width * 4,
+                lines: height,
+            }],
+            VideoFormat::I420 => {
+                let chroma = Plane {
+                    stride: round_up_4(width.div_ceil(2)),
+                    lines: line_pairs,
+                };
+                vec![
+                    Plane {
+                        stride: round_up_4(width),
+                        lines: line_pairs * 2,
+                    },
+                    chroma,
+                    chroma,
+                ]
+            }
+            VideoFormat::Nv12 => vec![
+                Plane {
+                    stride: round_up_4(width),
+                    lines: line_pairs * 2,
+                },
+                Plane {
+                    stride: round_up_4(width),
+                    lines: line_pairs,
+                },
+            ],
+        }
+    }
+
+    /// Payload size of a progressive `width` x `height` grain.
+    pub fn grain_size(self, width: usize, height: usize) -> usize {
+        self.planes(width, height).iter().map(Plane::size).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_round_trip_through_gst_formats_and_media_types() {
+        for format in VideoFormat::ALL {
+            assert_eq!(
+                VideoFormat::from_gst_format(format.gst_format()),
+                Some(format)
+            );
+            assert_eq!(
+                VideoFormat::from_media_type(format.media_type()),
+                Some(format)
+            );
+        }
+        assert_eq!(VideoFormat::from_gst_format("YUY2"), None);
+        assert_eq!(VideoFormat::from_media_type("video/v210a"), None);
+    }
+
+    #[test]
+    fn grain_sizes_follow_gstreamer_strides() {
+        assert_eq!(VideoFormat::V210.grain_size(1920, 1080), 5120 * 1080);
+        assert_eq!(VideoFormat::Uyvy.grain_size(63, 16), 128 * 16);
+        assert_eq!(VideoFormat::Bgra.grain_size(33, 17), 33 * 4 * 17);
+        // I420 of 1920x1080: 1920 luma bytes per line, 960 per chroma line.
+        assert_eq!(
+            VideoFormat::I420.grain_size(1920, 1080),
+            1920 * 1080 * 3 / 2
+        );
+        assert_eq!(
+            VideoFormat::Nv12.grain_size(1920, 1080),
+            1920 * 1080 * 3 / 2
+        );
+        // 33x17: luma lines of 36 bytes, chroma lines of 17 bytes padded to 20, 9 line pairs.
+        assert_eq!(VideoFormat::I420.grain_size(33, 17), 36 * 18 + 2 * 20 * 9);
+        assert_eq!(VideoFormat::Nv12.grain_size(33, 17), 36 * 18 + 36 * 9);
+    }
+
+    #[test]
+    fn alpha_formats_describe_rgba_components() {
+        let components = VideoFormat::Rgba.components(1920, 1080);
+        let names: Vec<_> = components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["R", "G", "B", "A"]);
+        assert!(
+            components
+                .iter()
+                .all(|c| c.width == 1920 && c.height == 1080 && c.bit_depth == 8)
+        );
+        assert_eq!(VideoFormat::Bgra.components(1920, 1080), components);
+    }
+}
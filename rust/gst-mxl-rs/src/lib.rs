@@ -15,14 +15,22 @@
 use gst::glib;
 use gstreamer as gst;
 
+pub mod bridge;
 mod clock;
 pub mod format;
+pub mod mxlavsrc;
+pub mod mxlbin;
+pub mod mxlclock;
+pub mod mxlgroupsink;
 pub mod mxlsink;
 pub mod mxlsrc;
 
 fn plugin_init(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
     mxlsrc::register(plugin)?;
     mxlsink::register(plugin)?;
+    mxlgroupsink::register(plugin)?;
+    mxlavsrc::register(plugin)?;
+    mxlbin::register(plugin)?;
     Ok(())
 }
 
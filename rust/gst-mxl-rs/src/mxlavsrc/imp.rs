@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gstreamer as gst;
+
+use std::sync::LazyLock;
+
+use crate::format::audio::AudioFormat;
+use crate::format::video::VideoFormat;
+use crate::mxlavsrc;
+use crate::mxlsrc::MxlSrc;
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "mxlavsrc",
+        gst::DebugColorFlags::empty(),
+        Some("MXL Audio/Video Source"),
+    )
+});
+
+/// The two readers. Both `mxlsrc` share the pipeline's clock offset, so a grain and the samples
+/// of the same TAI time get the same PTS on either pad.
+pub struct MxlAvSrc {
+    video: MxlSrc,
+    audio: MxlSrc,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for MxlAvSrc {
+    const NAME: &'static str = "GstRsMxlAvSrc";
+    type Type = mxlavsrc::MxlAvSrc;
+    type ParentType = gst::Bin;
+
+    fn with_class(_klass: &Self::Class) -> Self {
+        Self {
+            video: glib::Object::builder().property("name", "video").build(),
+            audio: glib::Object::builder().property("name", "audio").build(),
+        }
+    }
+}
+
+impl MxlAvSrc {
+    /// Ghosts the src pad of `src` as the `name` pad of the bin.
+    fn add_ghost_pad(&self, name: &str, src: &MxlSrc) -> Result<(), glib::BoolError> {
+        let obj = self.obj();
+        let templ = obj
+            .pad_template(name)
+            .ok_or_else(|| glib::bool_error!("No {} pad template", name))?;
+        let target = src
+            .static_pad("src")
+            .ok_or_else(|| glib::bool_error!("mxlsrc without src pad"))?;
+        let pad = gst::GhostPad::builder_from_template_with_target(&templ, &target)?
+            .name(name)
+            .build();
+        obj.add_pad(&pad)
+    }
+}
+
+impl ObjectImpl for MxlAvSrc {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("video-flow-id")
+                    .nick("Video Flow ID")
+                    .blurb("Video flow ID, read to the video_src pad")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("audio-flow-id")
+                    .nick("Audio Flow ID")
+                    .blurb("Audio flow ID, read to the audio_src pad")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("domain")
+                    .nick("Domain")
+                    .blurb("Domain of both flows")
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "video-flow-id" => self.video.set_property_from_value("video-flow-id", value),
+            "audio-flow-id" => self.audio.set_property_from_value("audio-flow-id", value),
+            "domain" => {
+                self.video.set_property_from_value("domain", value);
+                self.audio.set_property_from_value("domain", value);
+            }
+            other => {
+                gst::error!(CAT, imp = self, "Unknown property '{}'", other);
+            }
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "video-flow-id" => self.video.property_value("video-flow-id"),
+            "audio-flow-id" => self.audio.property_value("audio-flow-id"),
+            "domain" => self.video.property_value("domain"),
+            other => {
+                gst::error!(CAT, imp = self, "Unknown property '{}'", other);
+                pspec.default_value().clone()
+            }
+        }
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+        let obj = self.obj();
+        let result = obj
+            .add_many([&self.video, &self.audio])
+            .and_then(|()| self.add_ghost_pad("video_src", &self.video))
+            .and_then(|()| self.add_ghost_pad("audio_src", &self.audio));
+        if let Err(err) = result {
+            gst::error!(CAT, imp = self, "Failed to set up the readers: {}", err);
+        }
+    }
+}
+
+impl GstObjectImpl for MxlAvSrc {}
+
+impl ElementImpl for MxlAvSrc {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "MXL Audio/Video Source",
+                "Source/Video/Audio",
+                "Reads a video flow and an audio flow with TAI-aligned timestamps",
+                "Contributors to the Media eXchange Layer project",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Result<Vec<gst::PadTemplate>, glib::BoolError>> =
+            LazyLock::new(|| {
+                let video_caps = gst::Caps::builder("video/x-raw")
+                    .field(
+                        "format",
+                        gst::List::new(VideoFormat::ALL.map(VideoFormat::gst_format)),
+                    )
+                    .build();
+                let audio_caps = gst::Caps::builder("audio/x-raw")
+                    .field(
+                        "format",
+                        gst::List::new(AudioFormat::ALL.map(AudioFormat::gst_format)),
+                    )
+                    .build();
+
+                let video_pad_template = gst::PadTemplate::new(
+                    "video_src",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Always,
+                    &video_caps,
+                )?;
+                let audio_pad_template = gst::PadTemplate::new(
+                    "audio_src",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Always,
+                    &audio_caps,
+                )?;
+
+                Ok(vec![video_pad_template, audio_pad_template])
+            });
+
+        match PAD_TEMPLATES.as_ref() {
+            Ok(templates) => templates,
+            Err(err) => {
+                gst::error!(CAT, "Failed to create pad templates: {:?}", err);
+                &[]
+            }
+        }
+    }
+}
+
+impl BinImpl for MxlAvSrc {}
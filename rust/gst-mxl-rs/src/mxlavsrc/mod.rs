@@ -0,0 +1,24 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `mxlavsrc`: an `mxlsrc` for a video flow and one for an audio flow behind `video_src` and
+//! `audio_src` pads.
+
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+
+mod imp;
+
+glib::wrapper! {
+    pub struct MxlAvSrc(ObjectSubclass<imp::MxlAvSrc>) @extends gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy;
+}
+
+pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    gst::Element::register(
+        Some(plugin),
+        "mxlavsrc",
+        gst::Rank::NONE,
+        MxlAvSrc::static_type(),
+    )
+}
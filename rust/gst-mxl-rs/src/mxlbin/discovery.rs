@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Finding the flows of a group in a domain through their group hint tags.
+
+use mxl::MxlDomain;
+use mxl::flowdef::FlowDefDetails;
+use uuid::Uuid;
+
+use crate::mxlsink::state::GROUPHINT_TAG;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GroupFlowKind {
+    Video,
+    Audio,
+}
+
+/// A video or audio flow of a group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GroupFlow {
+    pub kind: GroupFlowKind,
+    pub id: Uuid,
+    /// The part of the group hint after the group, e.g. `Audio 0`.
+    pub role: String,
+}
+
+/// Group and role of a group hint, `<group>:<role>`.
+pub(crate) fn split_group_hint(hint: &str) -> Option<(&str, &str)> {
+    hint.split_once(':')
+}
+
+/// The video and audio flows of `domain` with a group hint of `group`, sorted by role, then ID.
+/// Data flows of the group are left out.
+pub(crate) fn group_flows(domain: &MxlDomain, group: &str) -> mxl::Result<Vec<GroupFlow>> {
+    let mut flows = Vec::new();
+    for id in domain.flow_ids()? {
+        // A flow removed, or not yet defined, while walking the domain.
+        let Ok(flow_def) = domain.flow_def(&id) else {
+            continue;
+        };
+        let kind = match flow_def.details {
+            FlowDefDetails::Video(_) => GroupFlowKind::Video,
+            FlowDefDetails::Audio(_) => GroupFlowKind::Audio,
+            FlowDefDetails::Data(_) => continue,
+        };
+        let role = flow_def
+            .tags
+            .get(GROUPHINT_TAG)
+            .into_iter()
+            .flatten()
+            .find_map(|hint| match split_group_hint(hint) {
+                Some((hint_group, role)) if hint_group == group => Some(role.to_owned()),
+                _ => None,
+            });
+        if let Some(role) = role {
+            flows.push(GroupFlow { kind, id, role });
+        }
+    }
+    flows.sort_by(|a, b| (&a.role, a.id).cmp(&(&b.role, b.id)));
+    Ok(flows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mxl_test_utils::{TestDomainGuard, audio_flow_def, data_flow_def, v210_flow_def};
+
+    /// Writes the definition of a flow of the group hint `hint` to the domain.
+    fn define_flow(domain: &MxlDomain, flow_def: &str, hint: &str) -> Uuid {
+        let id = Uuid::new_v4();
+        let mut flow_def: serde_json::Value = serde_json::from_str(flow_def).unwrap();
+        flow_def["id"] = id.to_string().into();
+        let mut tags = serde_json::Map::new();
+        tags.insert(GROUPHINT_TAG.to_owned(), serde_json::json!([hint]));
+        flow_def["tags"] = tags.into();
+        let dir = domain.flow_directory(&id);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("flow_def.json"), flow_def.to_string()).unwrap();
+        id
+    }
+
+    #[test]
+    fn group_flows_match_the_group_of_the_hint() {
+        let guard = TestDomainGuard::new("mxlbin_discovery");
+        let domain = MxlDomain::new(guard.path());
+        let audio = define_flow(&domain, &audio_flow_def(), "Studio:Audio 0");
+        let video = define_flow(&domain, &v210_flow_def(), "Studio:Video");
+        define_flow(&domain, &data_flow_def(), "Studio:Data");
+        define_flow(&domain, &v210_flow_def(), "Studio B:Video");
+        define_flow(&domain, &v210_flow_def(), "Studio");
+
+        assert_eq!(
+            group_flows(&domain, "Studio").unwrap(),
+            vec![
+                GroupFlow {
+                    kind: GroupFlowKind::Audio,
+                    id: audio,
+                    role: "Audio 0".to_owned(),
+                },
+                GroupFlow {
+                    kind: GroupFlowKind::Video,
+                    id: video,
+                    role: "Video".to_owned(),
+                },
+            ]
+        );
+        assert!(group_flows(&domain, "Studio C").unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_group_hint_splits_at_the_first_colon() {
+        assert_eq!(
+            split_group_hint("Media Function 1234 pipeline0:Video mxlsink0"),
+            Some(("Media Function 1234 pipeline0", "Video mxlsink0"))
+        );
+        assert_eq!(split_group_hint("no role"), None);
+    }
+}
@@ -0,0 +1,353 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gstreamer as gst;
+
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use mxl::MxlDomain;
+
+use crate::format::audio::AudioFormat;
+use crate::format::video::VideoFormat;
+use crate::mxlbin;
+use crate::mxlbin::discovery::{GroupFlow, GroupFlowKind, group_flows};
+use crate::mxlsrc::MxlSrc;
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new("mxlbin", gst::DebugColorFlags::empty(), Some("MXL Bin"))
+});
+
+const DEFAULT_DOMAIN: &str = "";
+const DEFAULT_GROUP_HINT: &str = "";
+const DEFAULT_DISCOVERY_TIMEOUT: u32 = 5_000;
+/// Interval between two looks at the domain while waiting for the group's flows.
+const DISCOVERY_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone)]
+struct Settings {
+    domain: String,
+    /// Group part of the group hints of the flows to read.
+    group_hint: String,
+    /// Milliseconds to wait for a flow of the group to appear.
+    discovery_timeout: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            domain: DEFAULT_DOMAIN.to_owned(),
+            group_hint: DEFAULT_GROUP_HINT.to_owned(),
+            discovery_timeout: DEFAULT_DISCOVERY_TIMEOUT,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MxlBin {
+    settings: Mutex<Settings>,
+    /// The reader of each discovered flow and its pad, from NULL→READY to READY→NULL.
+    readers: Mutex<Vec<(MxlSrc, gst::Pad)>>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for MxlBin {
+    const NAME: &'static str = "GstRsMxlBin";
+    type Type = mxlbin::MxlBin;
+    type ParentType = gst::Bin;
+}
+
+impl MxlBin {
+    /// Waits up to `discovery-timeout` for flows of the group and adds a reader and pad for each.
+    fn discover(&self) -> Result<(), gst::ErrorMessage> {
+        let settings = self
+            .settings
+            .lock()
+            .map_err(|_| gst::error_msg!(gst::CoreError::Failed, ["Missing settings"]))?
+            .clone();
+        if settings.domain.is_empty() || settings.group_hint.is_empty() {
+            return Err(gst::error_msg!(
+                gst::CoreError::Failed,
+                ["Set both domain and group-hint"]
+            ));
+        }
+
+        let domain = MxlDomain::new(&settings.domain);
+        let deadline = Instant::now() + Duration::from_millis(settings.discovery_timeout.into());
+        let flows = loop {
+            let flows = group_flows(&domain, &settings.group_hint).map_err(|e| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to list the flows of {}: {}", settings.domain, e]
+                )
+            })?;
+            if !flows.is_empty() || Instant::now() >= deadline {
+                break flows;
+            }
+            std::thread::sleep(DISCOVERY_INTERVAL);
+        };
+        if flows.is_empty() {
+            return Err(gst::error_msg!(
+                gst::ResourceError::NotFound,
+                [
+                    "No video or audio flow of group {} in {}",
+                    settings.group_hint,
+                    settings.domain
+                ]
+            ));
+        }
+
+        let (mut videos, mut audios) = (0, 0);
+        for flow in flows {
+            let (name, property) = match flow.kind {
+                GroupFlowKind::Video => {
+                    videos += 1;
+                    (format!("video_{}", videos - 1), "video-flow-id")
+                }
+                GroupFlowKind::Audio => {
+                    audios += 1;
+                    (format!("audio_{}", audios - 1), "audio-flow-id")
+                }
+            };
+            self.add_reader(&name, property, &flow, &settings.domain)
+                .map_err(|e| {
+                    gst::error_msg!(
+                        gst::CoreError::Failed,
+                        ["Failed to add a reader for flow {}: {}", flow.id, e]
+                    )
+                })?;
+        }
+        self.obj().no_more_pads();
+        Ok(())
+    }
+
+    /// Adds an `mxlsrc` for `flow` and ghosts its src pad as the `name` pad.
+    fn add_reader(
+        &self,
+        name: &str,
+        property: &str,
+        flow: &GroupFlow,
+        domain: &str,
+    ) -> Result<(), glib::BoolError> {
+        let obj = self.obj();
+        let src: MxlSrc = glib::Object::builder()
+            .property("name", name)
+            .property("domain", domain)
+            .property(property, flow.id.to_string())
+            .build();
+        obj.add(&src)?;
+
+        let template = match flow.kind {
+            GroupFlowKind::Video => "video_%u",
+            GroupFlowKind::Audio => "audio_%u",
+        };
+        let templ = obj
+            .pad_template(template)
+            .ok_or_else(|| glib::bool_error!("No {} pad template", template))?;
+        let target = src
+            .static_pad("src")
+            .ok_or_else(|| glib::bool_error!("mxlsrc without src pad"))?;
+        let pad = gst::GhostPad::builder_from_template_with_target(&templ, &target)?
+            .name(name)
+            .build();
+        obj.add_pad(&pad)?;
+
+        gst::info!(
+            CAT,
+            imp = self,
+            "Pad {} reads flow {} ({})",
+            name,
+            flow.id,
+            flow.role
+        );
+        if let Ok(mut readers) = self.readers.lock() {
+            readers.push((src, pad.upcast()));
+        }
+        Ok(())
+    }
+
+    /// Removes the readers and pads of [`Self::discover`].
+    fn remove_readers(&self) {
+        let readers = match self.readers.lock() {
+            Ok(mut readers) => std::mem::take(&mut *readers),
+            Err(_) => return,
+        };
+        let obj = self.obj();
+        for (src, pad) in readers {
+            let _ = obj.remove_pad(&pad);
+            let _ = src.set_state(gst::State::Null);
+            let _ = obj.remove(&src);
+        }
+    }
+}
+
+impl ObjectImpl for MxlBin {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("domain")
+                    .nick("Domain")
+                    .blurb("Domain to look for the flows in")
+                    .default_value(DEFAULT_DOMAIN)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("group-hint")
+                    .nick("Group Hint")
+                    .blurb(
+                        "Group of the urn:x-nmos:tag:grouphint/v1.0 tags of the flows to read, \
+                         the part before the colon, e.g. \"Media Function 1234 pipeline0\"",
+                    )
+                    .default_value(DEFAULT_GROUP_HINT)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("discovery-timeout")
+                    .nick("Discovery Timeout")
+                    .blurb("Milliseconds to wait for a flow of the group when starting")
+                    .default_value(DEFAULT_DISCOVERY_TIMEOUT)
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let Ok(mut settings) = self.settings.lock() else {
+            gst::error!(
+                CAT,
+                imp = self,
+                "Settings mutex poisoned — property change ignored"
+            );
+            return;
+        };
+        match pspec.name() {
+            "domain" => {
+                settings.domain = value
+                    .get::<Option<String>>()
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+            }
+            "group-hint" => {
+                settings.group_hint = value
+                    .get::<Option<String>>()
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+            }
+            "discovery-timeout" => {
+                if let Ok(timeout) = value.get::<u32>() {
+                    settings.discovery_timeout = timeout;
+                } else {
+                    gst::error!(
+                        CAT,
+                        imp = self,
+                        "Invalid type for discovery-timeout property"
+                    );
+                }
+            }
+            other => {
+                gst::error!(CAT, imp = self, "Unknown property '{}'", other);
+            }
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let Ok(settings) = self.settings.lock() else {
+            gst::error!(CAT, imp = self, "Settings mutex poisoned");
+            return pspec.default_value().clone();
+        };
+        match pspec.name() {
+            "domain" => settings.domain.to_value(),
+            "group-hint" => settings.group_hint.to_value(),
+            "discovery-timeout" => settings.discovery_timeout.to_value(),
+            other => {
+                gst::error!(CAT, imp = self, "Unknown property '{}'", other);
+                pspec.default_value().clone()
+            }
+        }
+    }
+}
+
+impl GstObjectImpl for MxlBin {}
+
+impl ElementImpl for MxlBin {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "MXL Bin",
+                "Source/Video/Audio",
+                "Reads the video and audio flows of a domain that share a group hint",
+                "Contributors to the Media eXchange Layer project",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Result<Vec<gst::PadTemplate>, glib::BoolError>> =
+            LazyLock::new(|| {
+                let video_caps = gst::Caps::builder("video/x-raw")
+                    .field(
+                        "format",
+                        gst::List::new(VideoFormat::ALL.map(VideoFormat::gst_format)),
+                    )
+                    .build();
+                let audio_caps = gst::Caps::builder("audio/x-raw")
+                    .field(
+                        "format",
+                        gst::List::new(AudioFormat::ALL.map(AudioFormat::gst_format)),
+                    )
+                    .build();
+
+                let video_pad_template = gst::PadTemplate::new(
+                    "video_%u",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Sometimes,
+                    &video_caps,
+                )?;
+                let audio_pad_template = gst::PadTemplate::new(
+                    "audio_%u",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Sometimes,
+                    &audio_caps,
+                )?;
+
+                Ok(vec![video_pad_template, audio_pad_template])
+            });
+
+        match PAD_TEMPLATES.as_ref() {
+            Ok(templates) => templates,
+            Err(err) => {
+                gst::error!(CAT, "Failed to create pad templates: {:?}", err);
+                &[]
+            }
+        }
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        if transition == gst::StateChange::NullToReady
+            && let Err(err) = self.discover()
+        {
+            self.remove_readers();
+            self.post_error_message(err);
+            return Err(gst::StateChangeError);
+        }
+        let result = self.parent_change_state(transition);
+        if transition == gst::StateChange::ReadyToNull {
+            self.remove_readers();
+        }
+        result
+    }
+}
+
+impl BinImpl for MxlBin {}
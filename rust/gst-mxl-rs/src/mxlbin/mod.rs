@@ -0,0 +1,25 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `mxlbin`: an `mxlsrc` for every video and audio flow of a domain whose group hint names the
+//! configured group, exposed as `video_%u` and `audio_%u` pads.
+
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+
+mod discovery;
+mod imp;
+
+glib::wrapper! {
+    pub struct MxlBin(ObjectSubclass<imp::MxlBin>) @extends gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy;
+}
+
+pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    gst::Element::register(
+        Some(plugin),
+        "mxlbin",
+        gst::Rank::NONE,
+        MxlBin::static_type(),
+    )
+}
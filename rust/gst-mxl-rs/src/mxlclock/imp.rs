@@ -0,0 +1,53 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use gst::glib;
+use gst::subclass::prelude::*;
+use gstreamer as gst;
+
+use std::sync::Mutex;
+
+use mxl::MxlInstance;
+
+use crate::mxlclock;
+
+#[derive(Default)]
+pub struct MxlClock {
+    /// Instance whose MXL time the clock reads, set right after construction.
+    instance: Mutex<Option<MxlInstance>>,
+}
+
+impl MxlClock {
+    pub(super) fn set_instance(&self, instance: MxlInstance) {
+        if let Ok(mut slot) = self.instance.lock() {
+            *slot = Some(instance);
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for MxlClock {
+    const NAME: &'static str = "GstMxlClock";
+    type Type = mxlclock::MxlClock;
+    type ParentType = gst::SystemClock;
+}
+
+impl ObjectImpl for MxlClock {}
+
+impl GstObjectImpl for MxlClock {}
+
+impl ClockImpl for MxlClock {
+    fn internal_time(&self) -> gst::ClockTime {
+        let now = match self.instance.lock() {
+            Ok(instance) => instance.as_ref().map(MxlInstance::get_time),
+            Err(_) => None,
+        };
+        match now {
+            Some(now) => gst::ClockTime::from_nseconds(now),
+            // Only before `set_instance`, which `MxlClock::new` calls before returning the clock.
+            None => self.parent_internal_time(),
+        }
+    }
+}
+
+impl SystemClockImpl for MxlClock {}
@@ -0,0 +1,29 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `MxlClock`: a `GstSystemClock` whose time is MXL time, TAI nanoseconds since the SMPTE ST 2059
+//! epoch, as `mxlsrc` provides it with `provide-clock`.
+//!
+//! A pipeline running on it paces against the facility timebase the MXL writers stamp their grains
+//! with, instead of drifting against the OS monotonic clock. Waits are scheduled relative to the
+//! current MXL time, so a step of the TAI clock moves the pending deadlines with it.
+
+use gst::glib;
+use gst::subclass::prelude::*;
+use gstreamer as gst;
+use mxl::MxlInstance;
+
+mod imp;
+
+glib::wrapper! {
+    pub struct MxlClock(ObjectSubclass<imp::MxlClock>) @extends gst::SystemClock, gst::Clock, gst::Object;
+}
+
+impl MxlClock {
+    /// Clock reading the MXL time of `instance`.
+    pub fn new(instance: MxlInstance) -> Self {
+        let clock: Self = glib::Object::new();
+        clock.imp().set_instance(instance);
+        clock
+    }
+}
@@ -0,0 +1,402 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gstreamer as gst;
+
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use uuid::Uuid;
+
+use crate::format::audio::AudioFormat;
+use crate::format::video::VideoFormat;
+use crate::mxlgroupsink;
+use crate::mxlsink::state::{default_group_name, sanitize_grouphint_component};
+
+static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+    gst::DebugCategory::new(
+        "mxlgroupsink",
+        gst::DebugColorFlags::empty(),
+        Some("MXL Group Sink"),
+    )
+});
+
+const DEFAULT_DOMAIN: &str = "";
+const DEFAULT_GROUP_NAME: &str = "";
+
+#[derive(Debug, Clone)]
+struct Settings {
+    domain: String,
+    /// Group name of every flow's group hint; empty uses the default group name of `mxlsink`.
+    group_name: String,
+    /// Empty generates a flow ID when the pad is requested.
+    video_flow_id: String,
+    /// Comma separated, the n-th for `audio_n`; missing or empty entries generate a flow ID.
+    audio_flow_ids: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            domain: DEFAULT_DOMAIN.to_owned(),
+            group_name: DEFAULT_GROUP_NAME.to_owned(),
+            video_flow_id: String::new(),
+            audio_flow_ids: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// The configured flow ID of `role`, if any.
+    fn flow_id(&self, role: Role) -> Option<String> {
+        let flow_id = match role {
+            Role::Video => self.video_flow_id.as_str(),
+            Role::Audio(index) => self
+                .audio_flow_ids
+                .split(',')
+                .nth(index as usize)
+                .unwrap_or_default(),
+        };
+        let flow_id = flow_id.trim();
+        (!flow_id.is_empty()).then(|| flow_id.to_owned())
+    }
+}
+
+/// The flow a child `mxlsink` publishes, which also names the child and its ghost pad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    Video,
+    Audio(u32),
+}
+
+impl Role {
+    fn from_child_name(name: &str) -> Option<Role> {
+        match name {
+            "video" => Some(Role::Video),
+            _ => Role::from_pad_name(name),
+        }
+    }
+
+    /// The audio role of an `audio_%u` pad name.
+    fn from_pad_name(name: &str) -> Option<Role> {
+        name.strip_prefix("audio_")
+            .and_then(|index| index.parse().ok())
+            .map(Role::Audio)
+    }
+
+    fn child_name(self) -> String {
+        match self {
+            Role::Video => "video".to_owned(),
+            Role::Audio(index) => format!("audio_{index}"),
+        }
+    }
+
+    fn pad_name(self) -> String {
+        match self {
+            Role::Video => "video_sink".to_owned(),
+            Role::Audio(index) => format!("audio_{index}"),
+        }
+    }
+
+    /// Role part of the group hint.
+    fn group_role(self) -> String {
+        match self {
+            Role::Video => "Video".to_owned(),
+            Role::Audio(index) => format!("Audio {index}"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MxlGroupSink {
+    settings: Mutex<Settings>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for MxlGroupSink {
+    const NAME: &'static str = "GstRsMxlGroupSink";
+    type Type = mxlgroupsink::MxlGroupSink;
+    type ParentType = gst::Bin;
+}
+
+impl MxlGroupSink {
+    /// Applies the domain and group hint to the `mxlsink` of `role`, and its flow ID if one is
+    /// configured.
+    fn configure(&self, sink: &gst::Element, role: Role) {
+        let Ok(settings) = self.settings.lock() else {
+            gst::error!(CAT, imp = self, "Settings mutex poisoned");
+            return;
+        };
+        let group_name = if settings.group_name.is_empty() {
+            default_group_name(Some(self.obj().upcast_ref()))
+        } else {
+            settings.group_name.clone()
+        };
+        let group_hint = format!(
+            "{}:{}",
+            sanitize_grouphint_component(&group_name),
+            role.group_role()
+        );
+        gst::debug!(
+            CAT,
+            imp = self,
+            "Configuring {} with group hint {}",
+            sink.name(),
+            group_hint
+        );
+        sink.set_property("domain", &settings.domain);
+        sink.set_property("group-hint", group_hint);
+        if let Some(flow_id) = settings.flow_id(role) {
+            sink.set_property("flow-id", flow_id);
+        }
+    }
+
+    /// The first `audio_%u` index without a pad.
+    fn next_audio_index(&self) -> u32 {
+        (0..)
+            .find(|index| {
+                self.obj()
+                    .static_pad(&Role::Audio(*index).pad_name())
+                    .is_none()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl ObjectImpl for MxlGroupSink {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+            vec![
+                glib::ParamSpecString::builder("domain")
+                    .nick("Domain")
+                    .blurb("Domain of every flow")
+                    .default_value(DEFAULT_DOMAIN)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("group-name")
+                    .nick("Group Name")
+                    .blurb(
+                        "Group name of the urn:x-nmos:tag:grouphint/v1.0 tag of every flow. \
+                         Empty uses the process and pipeline, like mxlsink.",
+                    )
+                    .default_value(DEFAULT_GROUP_NAME)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("video-flow-id")
+                    .nick("Video Flow ID")
+                    .blurb("Flow ID of the video_sink pad. Empty generates one.")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("audio-flow-ids")
+                    .nick("Audio Flow IDs")
+                    .blurb(
+                        "Comma separated flow IDs of the audio_%u pads, in pad order. \
+                         Missing or empty entries generate one.",
+                    )
+                    .mutable_ready()
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        let Ok(mut settings) = self.settings.lock() else {
+            gst::error!(
+                CAT,
+                imp = self,
+                "Settings mutex poisoned — property change ignored"
+            );
+            return;
+        };
+        let value = value
+            .get::<Option<String>>()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        match pspec.name() {
+            "domain" => settings.domain = value,
+            "group-name" => settings.group_name = value,
+            "video-flow-id" => settings.video_flow_id = value,
+            "audio-flow-ids" => settings.audio_flow_ids = value,
+            other => {
+                gst::error!(CAT, imp = self, "Unknown property '{}'", other);
+            }
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let Ok(settings) = self.settings.lock() else {
+            gst::error!(CAT, imp = self, "Settings mutex poisoned");
+            return pspec.default_value().clone();
+        };
+        match pspec.name() {
+            "domain" => settings.domain.to_value(),
+            "group-name" => settings.group_name.to_value(),
+            "video-flow-id" => settings.video_flow_id.to_value(),
+            "audio-flow-ids" => settings.audio_flow_ids.to_value(),
+            other => {
+                gst::error!(CAT, imp = self, "Unknown property '{}'", other);
+                pspec.default_value().clone()
+            }
+        }
+    }
+}
+
+impl GstObjectImpl for MxlGroupSink {}
+
+impl ElementImpl for MxlGroupSink {
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "MXL Group Sink",
+                "Sink/Video/Audio",
+                "Generates a video flow and audio flows sharing one group hint",
+                "Contributors to the Media eXchange Layer project",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: LazyLock<Result<Vec<gst::PadTemplate>, glib::BoolError>> =
+            LazyLock::new(|| {
+                let video_caps = gst::Caps::builder("video/x-raw")
+                    .field(
+                        "format",
+                        gst::List::new(VideoFormat::ALL.map(VideoFormat::gst_format)),
+                    )
+                    .build();
+                let mut audio_caps = gst::Caps::new_empty();
+                for ch in 1..64 {
+                    let mask = gst::Bitmask::from((1u64 << ch) - 1);
+                    audio_caps.make_mut().append(
+                        gst::Caps::builder("audio/x-raw")
+                            .field(
+                                "format",
+                                gst::List::new(AudioFormat::ALL.map(AudioFormat::gst_format)),
+                            )
+                            .field("layout", gst::List::new(["interleaved", "non-interleaved"]))
+                            .field("channels", ch)
+                            .field("channel-mask", mask)
+                            .build(),
+                    );
+                }
+
+                let video_pad_template = gst::PadTemplate::new(
+                    "video_sink",
+                    gst::PadDirection::Sink,
+                    gst::PadPresence::Request,
+                    &video_caps,
+                )?;
+                let audio_pad_template = gst::PadTemplate::new(
+                    "audio_%u",
+                    gst::PadDirection::Sink,
+                    gst::PadPresence::Request,
+                    &audio_caps,
+                )?;
+
+                Ok(vec![video_pad_template, audio_pad_template])
+            });
+
+        match PAD_TEMPLATES.as_ref() {
+            Ok(templates) => templates,
+            Err(err) => {
+                gst::error!(CAT, "Failed to create pad templates: {:?}", err);
+                &[]
+            }
+        }
+    }
+
+    fn request_new_pad(
+        &self,
+        templ: &gst::PadTemplate,
+        name: Option<&str>,
+        _caps: Option<&gst::Caps>,
+    ) -> Option<gst::Pad> {
+        let role = match templ.name_template().as_str() {
+            "video_sink" => Role::Video,
+            "audio_%u" => match name {
+                Some(name) => Role::from_pad_name(name)?,
+                None => Role::Audio(self.next_audio_index()),
+            },
+            other => {
+                gst::error!(CAT, imp = self, "Unknown pad template {}", other);
+                return None;
+            }
+        };
+        let obj = self.obj();
+        if obj.static_pad(&role.pad_name()).is_some() {
+            gst::error!(CAT, imp = self, "Pad {} already exists", role.pad_name());
+            return None;
+        }
+
+        let sink = gst::ElementFactory::make("mxlsink")
+            .name(role.child_name())
+            .build()
+            .inspect_err(|err| gst::error!(CAT, imp = self, "Failed to create mxlsink: {err}"))
+            .ok()?;
+        // Generated here rather than per start, so the flow keeps its ID across restarts.
+        sink.set_property("flow-id", Uuid::new_v4().to_string());
+        self.configure(&sink, role);
+        obj.add(&sink).ok()?;
+
+        let target = sink.static_pad("sink")?;
+        let pad = gst::GhostPad::builder_from_template_with_target(templ, &target)
+            .ok()?
+            .name(role.pad_name())
+            .build();
+        if obj.add_pad(&pad).is_err() {
+            let _ = obj.remove(&sink);
+            return None;
+        }
+        if sink.sync_state_with_parent().is_err() {
+            gst::warning!(CAT, imp = self, "Failed to sync {} state", sink.name());
+        }
+
+        gst::info!(
+            CAT,
+            imp = self,
+            "Requested pad {} writing flow {}",
+            pad.name(),
+            sink.property::<String>("flow-id")
+        );
+        Some(pad.upcast())
+    }
+
+    fn release_pad(&self, pad: &gst::Pad) {
+        let obj = self.obj();
+        let sink = pad
+            .downcast_ref::<gst::GhostPad>()
+            .and_then(|pad| pad.target())
+            .and_then(|target| target.parent_element());
+        let _ = pad.set_active(false);
+        let _ = obj.remove_pad(pad);
+        if let Some(sink) = sink {
+            let _ = sink.set_state(gst::State::Null);
+            let _ = obj.remove(&sink);
+        }
+    }
+
+    fn change_state(
+        &self,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        // The pipeline the default group name is taken from may only be known now.
+        if transition == gst::StateChange::NullToReady {
+            for child in self.obj().children() {
+                if let Some(role) = Role::from_child_name(&child.name()) {
+                    self.configure(&child, role);
+                }
+            }
+        }
+        self.parent_change_state(transition)
+    }
+}
+
+impl BinImpl for MxlGroupSink {}
@@ -0,0 +1,24 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `mxlgroupsink`: one `mxlsink` per request pad, publishing a video flow and
+//! any number of audio flows under a single group hint.
+
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+
+mod imp;
+
+glib::wrapper! {
+    pub struct MxlGroupSink(ObjectSubclass<imp::MxlGroupSink>) @extends gst::Bin, gst::Element, gst::Object, @implements gst::ChildProxy;
+}
+
+pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    gst::Element::register(
+        Some(plugin),
+        "mxlgroupsink",
+        gst::Rank::NONE,
+        MxlGroupSink::static_type(),
+    )
+}
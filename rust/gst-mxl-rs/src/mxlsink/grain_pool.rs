@@ -0,0 +1,450 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Buffer pool offered upstream for the `zero-copy` property of `mxlsink`: its buffers are the
+//! payloads of grains opened in the flow's ring, so upstream writes a frame in place and
+//! rendering it only commits the grain.
+//!
+//! The MXL writer holds a single open grain, so the pool opens one for the buffer it hands out,
+//! at the index after the grain last written (the current index before the first). Until that
+//! buffer is released, the pool hands out plain buffers, which render copies as without the pool.
+//! So does a pooled buffer whose timestamp maps to another index.
+//!
+//! The grain of a pooled buffer is only canceled, and its ring slot only reopened, once the
+//! buffer is released. Grains copied while the open grain of a pooled buffer keeps the writer
+//! busy, or whose slot a pooled buffer still covers, are written when it is released.
+//!
+//! The buffers share the writer with the element, so the flow stays mapped while upstream still
+//! fills one after the element stopped.
+
+use std::{
+    collections::VecDeque,
+    ptr::NonNull,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gstreamer as gst;
+use mxl::{AccessOptions, DetachedGrain, GrainWriteAccess, GrainWriter, MxlInstance, Rational};
+use tracing::trace;
+
+/// Copied grains kept while a pooled buffer holds the writer, beyond which the oldest is dropped.
+const MAX_DEFERRED_GRAINS: usize = 8;
+
+/// Pooled buffer that is out.
+struct Pooled {
+    serial: u64,
+    /// Index of the grain the buffer covers.
+    index: u64,
+    /// The grain, until it is committed from the buffer.
+    grain: Option<DetachedGrain>,
+}
+
+/// Grain copied from a buffer that could not be written yet.
+struct Deferred {
+    index: u64,
+    payload: Vec<u8>,
+}
+
+/// Grain writer of a discrete flow and the grain open for the pooled buffer that is out.
+pub(crate) struct PooledWriter {
+    writer: GrainWriter,
+    open_options: AccessOptions,
+    /// Payloads must fill the grain exactly, as video frames do; shorter ones are zero padded.
+    whole_grains: bool,
+    /// Payload size of the grains, once one was opened.
+    grain_size: Option<u32>,
+    pooled: Option<Pooled>,
+    deferred: VecDeque<Deferred>,
+    next_serial: u64,
+    /// Index after the grain last written from a buffer.
+    next_index: Option<u64>,
+}
+
+impl PooledWriter {
+    /// Whether the grain at `index` cannot be opened now: the writer's single open grain is the
+    /// one of the pooled buffer, or the buffer covers the ring slot of `index`.
+    fn is_blocked(&self, index: u64) -> bool {
+        let Some(pooled) = &self.pooled else {
+            return false;
+        };
+        if pooled.grain.is_some() {
+            return true;
+        }
+        match u64::from(self.writer.config_info().ring_length()) {
+            0 => true,
+            grain_count => index % grain_count == pooled.index % grain_count,
+        }
+    }
+
+    /// Takes the grain of the pooled buffer whose payload starts at `data`, if it is still open
+    /// at `index`.
+    pub(crate) fn take_pooled(&mut self, data: *const u8, index: u64) -> Option<DetachedGrain> {
+        let pooled = self.pooled.as_mut()?;
+        match &pooled.grain {
+            Some(grain) if std::ptr::eq(grain.payload_ptr(), data) && grain.index() == index => {
+                pooled.grain.take()
+            }
+            _ => None,
+        }
+    }
+
+    /// Writes `payload` as the grain at `index`, or keeps it until the pooled buffer blocking
+    /// that is released. A payload that does not fit the grains is rejected either way.
+    pub(crate) fn write_or_defer(&mut self, index: u64, payload: &[u8]) -> mxl::Result<()> {
+        if self.is_blocked(index) {
+            // The pooled buffer's grain was opened, so the size is known.
+            if let Some(grain_size) = self.grain_size {
+                check_size(self.whole_grains, payload.len(), grain_size)?;
+            }
+            trace!("Deferring grain {index} until the pooled buffer is released");
+            if self.deferred.len() == MAX_DEFERRED_GRAINS
+                && let Some(dropped) = self.deferred.pop_front()
+            {
+                trace!("Dropping deferred grain {}", dropped.index);
+            }
+            self.deferred.push_back(Deferred {
+                index,
+                payload: payload.to_vec(),
+            });
+            return Ok(());
+        }
+        self.write(index, payload)
+    }
+
+    /// Writes the deferred grains that are no longer blocked, in order.
+    fn flush_deferred(&mut self) {
+        while self
+            .deferred
+            .front()
+            .is_some_and(|deferred| !self.is_blocked(deferred.index))
+        {
+            let Some(Deferred { index, payload }) = self.deferred.pop_front() else {
+                break;
+            };
+            if let Err(err) = self.write(index, &payload) {
+                trace!("Failed to write deferred grain {index}: {err}");
+            }
+        }
+    }
+
+    /// Opens the grain at `index`, `None` while [blocked](Self::is_blocked).
+    pub(crate) fn open_grain(&mut self, index: u64) -> mxl::Result<Option<GrainWriteAccess<'_>>> {
+        if self.is_blocked(index) {
+            return Ok(None);
+        }
+        let access = self
+            .writer
+            .open_grain_with_options(index, &self.open_options)?;
+        self.grain_size = Some(access.max_size());
+        Ok(Some(access))
+    }
+
+    /// Commits the open grain of a pooled buffer, taken with [`Self::take_pooled`].
+    pub(crate) fn commit_pooled(&mut self, grain: DetachedGrain) -> mxl::Result<()> {
+        let index = grain.index();
+        let mut access = self.writer.resume_grain(grain)?;
+        access.set_flags(0);
+        let total_slices = access.total_slices();
+        access.commit(total_slices)?;
+        self.next_index = Some(index + 1);
+        self.flush_deferred();
+        Ok(())
+    }
+
+    fn write(&mut self, index: u64, payload: &[u8]) -> mxl::Result<()> {
+        let mut access = self
+            .writer
+            .open_grain_with_options(index, &self.open_options)?;
+        self.grain_size = Some(access.max_size());
+        // Dropping the access cancels the grain.
+        check_size(self.whole_grains, payload.len(), access.max_size())?;
+        let destination = access.payload_mut();
+        destination[..payload.len()].copy_from_slice(payload);
+        destination[payload.len()..].fill(0);
+        // The ring slot keeps the flags of the grain it held before, which may
+        // have been a slate.
+        access.set_flags(0);
+        let total_slices = access.total_slices();
+        access.commit(total_slices)?;
+        self.next_index = Some(index + 1);
+        Ok(())
+    }
+
+    /// The pooled buffer `serial` was released: cancels its grain if it is still open and writes
+    /// the grains it held back.
+    fn release_pooled(&mut self, serial: u64) {
+        if !matches!(&self.pooled, Some(pooled) if pooled.serial == serial) {
+            return;
+        }
+        if let Some(grain) = self.pooled.take().and_then(|pooled| pooled.grain) {
+            trace!(
+                "Canceling grain {} of released pooled buffer {serial}",
+                grain.index()
+            );
+            if let Err(err) = self
+                .writer
+                .resume_grain(grain)
+                .and_then(|access| access.cancel())
+            {
+                trace!("Failed to cancel the grain of pooled buffer {serial}: {err}");
+            }
+        }
+        self.flush_deferred();
+    }
+}
+
+/// Fails for a payload that does not fit the grains, see `PooledWriter::whole_grains`.
+pub(crate) fn check_size(
+    whole_grains: bool,
+    payload_len: usize,
+    grain_size: u32,
+) -> mxl::Result<()> {
+    let grain_size = grain_size as usize;
+    if payload_len > grain_size || (whole_grains && payload_len != grain_size) {
+        return Err(mxl::Error::Other(format!(
+            "Payload of {payload_len} bytes does not match the grain size {grain_size}."
+        )));
+    }
+    Ok(())
+}
+
+/// [`PooledWriter`] shared by the element's state and the buffers of its [`GrainPool`].
+#[derive(Clone)]
+pub(crate) struct SharedGrainWriter(Arc<Mutex<PooledWriter>>);
+
+impl SharedGrainWriter {
+    pub(crate) fn new(
+        writer: GrainWriter,
+        open_options: AccessOptions,
+        whole_grains: bool,
+    ) -> Self {
+        Self(Arc::new(Mutex::new(PooledWriter {
+            writer,
+            open_options,
+            whole_grains,
+            grain_size: None,
+            pooled: None,
+            deferred: VecDeque::new(),
+            next_serial: 0,
+            next_index: None,
+        })))
+    }
+
+    /// Poisoning is ignored: the writer stays usable after a panic of another user.
+    pub(crate) fn lock(&self) -> MutexGuard<'_, PooledWriter> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Releases the writer, or leaves that to the last pooled buffer still out. Without pooled
+    /// buffers, no grain is open and none is deferred.
+    pub(crate) fn destroy(self) -> mxl::Result<()> {
+        match Arc::try_unwrap(self.0) {
+            Ok(writer) => writer
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .writer
+                .destroy(),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Memory of a pooled buffer over the payload of its grain. It holds the pooled grain: the grain
+/// is released when the memory is, so the payload stays valid for as long as the buffer holding
+/// the memory.
+struct PooledGrain {
+    writer: SharedGrainWriter,
+    serial: u64,
+    payload: NonNull<[u8]>,
+}
+
+// SAFETY: the payload is plain memory of the mapped flow, and the writer is behind a mutex.
+unsafe impl Send for PooledGrain {}
+
+impl PooledGrain {
+    /// Makes `grain`, whose payload is `payload`, the pooled grain of `pooled_writer`, the
+    /// locked `writer`, held by the returned memory.
+    fn new(
+        writer: &SharedGrainWriter,
+        pooled_writer: &mut PooledWriter,
+        index: u64,
+        grain: DetachedGrain,
+        payload: NonNull<[u8]>,
+    ) -> Self {
+        let serial = pooled_writer.next_serial;
+        pooled_writer.next_serial += 1;
+        pooled_writer.pooled = Some(Pooled {
+            serial,
+            index,
+            grain: Some(grain),
+        });
+        Self {
+            writer: writer.clone(),
+            serial,
+            payload,
+        }
+    }
+}
+
+impl AsRef<[u8]> for PooledGrain {
+    fn as_ref(&self) -> &[u8] {
+        // SAFETY: the payload is that of the pooled grain `serial`, in the mapping of `writer`,
+        // which lives as long as the writer. The grain stays open, or committed from this memory,
+        // and its ring slot is not reopened until the memory is dropped, see
+        // `PooledWriter::is_blocked`.
+        unsafe { self.payload.as_ref() }
+    }
+}
+
+impl AsMut<[u8]> for PooledGrain {
+    fn as_mut(&mut self) -> &mut [u8] {
+        // SAFETY: as in `as_ref`. Nothing else writes the ring slot while the memory is alive, so
+        // its mapping is the only one over it.
+        unsafe { self.payload.as_mut() }
+    }
+}
+
+impl Drop for PooledGrain {
+    fn drop(&mut self) {
+        self.writer.lock().release_pooled(self.serial);
+    }
+}
+
+/// What the pool needs to open grains.
+struct Target {
+    writer: SharedGrainWriter,
+    instance: MxlInstance,
+    grain_rate: Rational,
+    grain_size: u32,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Default)]
+    pub struct GrainPool {
+        pub(super) target: Mutex<Option<Target>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for GrainPool {
+        const NAME: &'static str = "GstRsMxlGrainPool";
+        type Type = super::GrainPool;
+        type ParentType = gst::BufferPool;
+    }
+
+    impl ObjectImpl for GrainPool {}
+
+    impl GstObjectImpl for GrainPool {}
+
+    impl BufferPoolImpl for GrainPool {
+        /// Buffers get their memory when acquired, over a grain or allocated.
+        fn alloc_buffer(
+            &self,
+            _params: Option<&gst::BufferPoolAcquireParams>,
+        ) -> Result<gst::Buffer, gst::FlowError> {
+            Ok(gst::Buffer::new())
+        }
+
+        fn acquire_buffer(
+            &self,
+            params: Option<&gst::BufferPoolAcquireParams>,
+        ) -> Result<gst::Buffer, gst::FlowError> {
+            let mut buffer = self.parent_acquire_buffer(params)?;
+            let target = self.target.lock().unwrap_or_else(PoisonError::into_inner);
+            let Some(target) = target.as_ref() else {
+                return Err(gst::FlowError::NotNegotiated);
+            };
+            let memory = target
+                .open_pooled()
+                .unwrap_or_else(|| gst::Memory::with_size(target.grain_size as usize));
+            // Adding memory tags the buffer, so on release the pool frees it rather than queue
+            // it, and freeing it releases its grain.
+            buffer
+                .get_mut()
+                .ok_or(gst::FlowError::Error)?
+                .append_memory(memory);
+            Ok(buffer)
+        }
+    }
+}
+
+impl Target {
+    /// Memory over a newly opened grain, `None` while a pooled buffer is out or if the grain
+    /// cannot be opened in host memory.
+    fn open_pooled(&self) -> Option<gst::Memory> {
+        let mut writer = self.writer.lock();
+        if writer.pooled.is_some() || !writer.writer.payload_location().is_host() {
+            return None;
+        }
+        let index = writer
+            .next_index
+            .unwrap_or_else(|| self.instance.get_current_index(&self.grain_rate));
+        let (grain, payload) = match writer.open_grain(index) {
+            Ok(Some(mut access)) => {
+                let payload = NonNull::from(access.payload_mut());
+                // SAFETY: the grain is kept in `pooled`, which blocks opening another one until
+                // it is resumed, and the memory over its payload holds the writer.
+                (unsafe { access.detach() }, payload)
+            }
+            Ok(None) => return None,
+            Err(err) => {
+                trace!("Failed to open grain {index} for a pooled buffer: {err}");
+                return None;
+            }
+        };
+        let memory = PooledGrain::new(&self.writer, &mut writer, index, grain, payload);
+        drop(writer);
+        Some(gst::Memory::from_mut_slice(memory))
+    }
+}
+
+glib::wrapper! {
+    /// Pool handing out buffers over grains of a flow, see the [module documentation](self).
+    pub(crate) struct GrainPool(ObjectSubclass<imp::GrainPool>) @extends gst::BufferPool, gst::Object;
+}
+
+impl GrainPool {
+    /// Pool over the grains of `writer`. Opens and cancels a grain to learn their size, which
+    /// fails while a buffer of a previous pool is still out.
+    pub(crate) fn new(
+        writer: SharedGrainWriter,
+        instance: MxlInstance,
+        grain_rate: Rational,
+    ) -> mxl::Result<Self> {
+        let grain_size = {
+            let mut pooled_writer = writer.lock();
+            let index = instance.get_current_index(&grain_rate);
+            let access = pooled_writer.open_grain(index)?.ok_or_else(|| {
+                mxl::Error::Other("A pooled buffer of the flow is still out.".to_string())
+            })?;
+            let grain_size = access.max_size();
+            access.cancel()?;
+            grain_size
+        };
+        let pool = glib::Object::new::<Self>();
+        *pool
+            .imp()
+            .target
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Target {
+            writer,
+            instance,
+            grain_rate,
+            grain_size,
+        });
+        Ok(pool)
+    }
+
+    pub(crate) fn grain_size(&self) -> u32 {
+        self.imp()
+            .target
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map_or(0, |target| target.grain_size)
+    }
+}
@@ -17,29 +17,40 @@ use gst::subclass::prelude::*;
 use gst_base::prelude::BaseSinkExt;
 use gst_base::subclass::prelude::*;
 use gstreamer as gst;
-use gstreamer_audio as gst_audio;
 use gstreamer_base as gst_base;
 
-use mxl::MxlInstance;
 use mxl::config::get_mxl_so_path;
+use mxl::{MxlInstance, RationalExt};
 use tracing::trace;
 
+use std::sync::Arc;
 use std::sync::LazyLock;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 use crate::clock::ClockOffsetExt;
+use crate::format::audio::AudioFormat;
+use crate::format::video::VideoFormat;
 use crate::mxlsink;
+use crate::mxlsink::grain_pool::GrainPool;
+use crate::mxlsink::slate::{DEFAULT_FREEZE_DETECTION, DEFAULT_SLATE_COLOR, SlateThread};
 use crate::mxlsink::state::Context;
 use crate::mxlsink::state::DEFAULT_DOMAIN;
 use crate::mxlsink::state::DEFAULT_FLOW_ID;
+use crate::mxlsink::state::DEFAULT_ZERO_COPY;
 use crate::mxlsink::state::FlowState;
 use crate::mxlsink::state::Settings;
 use crate::mxlsink::state::State;
-use crate::mxlsink::state::init_state_with_audio;
-use crate::mxlsink::state::init_state_with_data;
-use crate::mxlsink::state::init_state_with_video;
-use crate::mxlsink::{render_continuous, render_discrete};
+use crate::mxlsink::state::init_state_from_caps;
+use crate::mxlsink::write_ahead::{
+    ClockWait, DEFAULT_MAX_WRITE_AHEAD, WriteAheadLimit, WriteAheadPolicy,
+};
+
+/// Granularity of the commit time reported in latency queries, so that every slightly slower
+/// commit does not post a latency message.
+const COMMIT_TIME_GRANULARITY: Duration = Duration::from_millis(1);
 
 pub(crate) static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
     gst::DebugCategory::new("mxlsink", gst::DebugColorFlags::empty(), Some("MXL Sink"))
@@ -48,8 +59,14 @@ pub(crate) static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
 #[derive(Default)]
 pub struct MxlSink {
     settings: Mutex<Settings>,
-    context: Mutex<Context>,
+    /// Shared with the slate thread.
+    context: Arc<Mutex<Context>>,
+    slate_thread: Mutex<Option<SlateThread>>,
+    clock_wait: Mutex<ClockWait>,
     shared_clock_offset: Mutex<Option<crate::clock::SharedClockOffset>>,
+    /// Longest render since `start()` in nanoseconds, rounded up to
+    /// [`COMMIT_TIME_GRANULARITY`], which latency queries add to the pipeline latency.
+    commit_time: AtomicU64,
 }
 
 #[glib::object_subclass]
@@ -102,6 +119,101 @@ impl ObjectImpl for MxlSink {
                     )
                     .mutable_ready()
                     .build(),
+                glib::ParamSpecUInt64::builder("max-write-ahead")
+                    .nick("Max Write Ahead")
+                    .blurb(
+                        "Maximum number of grains (video, data) or samples (audio) \
+                         a commit may land ahead of the current MXL index. \
+                         0 disables the limit.",
+                    )
+                    .default_value(DEFAULT_MAX_WRITE_AHEAD)
+                    .mutable_playing()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default(
+                    "write-ahead-policy",
+                    WriteAheadPolicy::default(),
+                )
+                .nick("Write Ahead Policy")
+                .blurb("What to do with buffers beyond max-write-ahead")
+                .mutable_playing()
+                .build(),
+                glib::ParamSpecUInt::builder("freeze-detection")
+                    .nick("Freeze Detection")
+                    .blurb(
+                        "Number of grain intervals without input after which \
+                         the slate is written to a video flow, until input \
+                         resumes. 0 disables the slate.",
+                    )
+                    .default_value(DEFAULT_FREEZE_DETECTION)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("slate-color")
+                    .nick("Slate Color")
+                    .blurb("Slate color as 0xRRGGBB, unless slate-location is set")
+                    .default_value(DEFAULT_SLATE_COLOR)
+                    .maximum(0xffffff)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("slate-location")
+                    .nick("Slate Location")
+                    .blurb(
+                        "Path to a pre-rendered grain in the flow's format used as the slate, \
+                         for example a color with a text overlay. Empty uses \
+                         slate-color.",
+                    )
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("slate-flow-id")
+                    .nick("Slate Flow ID")
+                    .blurb(
+                        "UUID of an application/json data flow marking the slate \
+                         grains of a video flow with an event at their index. \
+                         Empty marks no slate grains.",
+                    )
+                    .default_value(DEFAULT_FLOW_ID)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("open-options")
+                    .nick("Open Options")
+                    .blurb(
+                        "Comma separated key=value options passed to every grain \
+                         or samples open. Ignored by MXL versions without \
+                         per-open options.",
+                    )
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("zero-copy")
+                    .nick("Zero Copy")
+                    .blurb(
+                        "Offer upstream a buffer pool over the grains of a video \
+                         flow, so frames are written in place and only committed \
+                         on render.",
+                    )
+                    .default_value(DEFAULT_ZERO_COPY)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("caption-flow-id")
+                    .nick("Caption Flow ID")
+                    .blurb(
+                        "UUID of a video/smpte291 data flow the CEA-708 and \
+                         CEA-608 GstVideoCaptionMeta of video buffers are \
+                         written to, at the index of their grain. Empty writes \
+                         no captions.",
+                    )
+                    .default_value(DEFAULT_FLOW_ID)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("timecode-flow-id")
+                    .nick("Timecode Flow ID")
+                    .blurb(
+                        "UUID of a video/smpte291 data flow the \
+                         GstVideoTimeCodeMeta of video buffers are written to \
+                         as ST 12-2 ancillary time code, at the index of their \
+                         grain. Empty writes no timecodes.",
+                    )
+                    .default_value(DEFAULT_FLOW_ID)
+                    .mutable_ready()
+                    .build(),
             ]
         });
 
@@ -180,6 +292,84 @@ impl ObjectImpl for MxlSink {
                         .flatten()
                         .unwrap_or_default();
                 }
+                "max-write-ahead" => {
+                    if let Ok(max_write_ahead) = value.get::<u64>() {
+                        settings.max_write_ahead = max_write_ahead;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for max-write-ahead property");
+                    }
+                }
+                "write-ahead-policy" => {
+                    if let Ok(policy) = value.get::<WriteAheadPolicy>() {
+                        settings.write_ahead_policy = policy;
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for write-ahead-policy property"
+                        );
+                    }
+                }
+                "freeze-detection" => {
+                    if let Ok(freeze_detection) = value.get::<u32>() {
+                        settings.freeze_detection = freeze_detection;
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for freeze-detection property"
+                        );
+                    }
+                }
+                "slate-color" => {
+                    if let Ok(slate_color) = value.get::<u32>() {
+                        settings.slate_color = slate_color;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for slate-color property");
+                    }
+                }
+                "slate-location" => {
+                    settings.slate_location = value
+                        .get::<Option<String>>()
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default();
+                }
+                "slate-flow-id" => {
+                    settings.slate_flow_id = value
+                        .get::<Option<String>>()
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default();
+                }
+                "open-options" => {
+                    settings.open_options = value
+                        .get::<Option<String>>()
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default();
+                }
+                "zero-copy" => {
+                    if let Ok(zero_copy) = value.get::<bool>() {
+                        settings.zero_copy = zero_copy;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for zero-copy property");
+                    }
+                }
+                "caption-flow-id" => {
+                    settings.caption_flow_id = value
+                        .get::<Option<String>>()
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default();
+                }
+                "timecode-flow-id" => {
+                    settings.timecode_flow_id = value
+                        .get::<Option<String>>()
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default();
+                }
                 other => {
                     gst::error!(CAT, imp = self, "Unknown property '{}'", other);
                 }
@@ -201,6 +391,16 @@ impl ObjectImpl for MxlSink {
                 "label" => settings.label.to_value(),
                 "description" => settings.description.to_value(),
                 "group-hint" => settings.group_hint.to_value(),
+                "max-write-ahead" => settings.max_write_ahead.to_value(),
+                "write-ahead-policy" => settings.write_ahead_policy.to_value(),
+                "freeze-detection" => settings.freeze_detection.to_value(),
+                "slate-color" => settings.slate_color.to_value(),
+                "slate-location" => settings.slate_location.to_value(),
+                "slate-flow-id" => settings.slate_flow_id.to_value(),
+                "open-options" => settings.open_options.to_value(),
+                "zero-copy" => settings.zero_copy.to_value(),
+                "caption-flow-id" => settings.caption_flow_id.to_value(),
+                "timecode-flow-id" => settings.timecode_flow_id.to_value(),
                 _ => {
                     gst::error!(CAT, imp = self, "Unknown property {}", pspec.name());
                     glib::Value::from(&"")
@@ -237,15 +437,21 @@ impl ElementImpl for MxlSink {
 
                     caps_mut.append(
                         gst::Caps::builder("video/x-raw")
-                            .field("format", "v210")
+                            .field(
+                                "format",
+                                gst::List::new(VideoFormat::ALL.map(VideoFormat::gst_format)),
+                            )
                             .build(),
                     );
                     for ch in 1..64 {
                         let mask = gst::Bitmask::from((1u64 << ch) - 1);
                         caps.make_mut().append(
                             gst::Caps::builder("audio/x-raw")
-                                .field("format", "F32LE")
-                                .field("layout", "interleaved")
+                                .field(
+                                    "format",
+                                    gst::List::new(AudioFormat::ALL.map(AudioFormat::gst_format)),
+                                )
+                                .field("layout", gst::List::new(["interleaved", "non-interleaved"]))
                                 .field("channels", ch)
                                 .field("channel-mask", mask)
                                 .build(),
@@ -256,6 +462,21 @@ impl ElementImpl for MxlSink {
                             .field("alignment", "frame")
                             .build(),
                     );
+                    caps.make_mut().append(
+                        gst::Caps::builder("meta/x-klv")
+                            .field("parsed", true)
+                            .build(),
+                    );
+                    caps.make_mut().append(
+                        gst::Caps::builder("closedcaption/x-cea-708")
+                            .field("format", "cdp")
+                            .build(),
+                    );
+                    caps.make_mut().append(
+                        gst::Caps::builder("closedcaption/x-cea-608")
+                            .field("format", "s334-1a")
+                            .build(),
+                    );
                 }
 
                 let sink_pad_template = gst::PadTemplate::new(
@@ -290,6 +511,30 @@ impl ElementImpl for MxlSink {
     fn set_context(&self, context: &gst::Context) {
         self.handle_set_context(context);
     }
+
+    fn query(&self, query: &mut gst::QueryRef) -> bool {
+        if !ElementImplExt::parent_query(self, query) {
+            return false;
+        }
+        // `GstBaseSink` answers with the upstream latency plus `processing-deadline`, the time
+        // upstream has to deliver a buffer before the clock wait for it ends. A grain or batch
+        // of samples is readable only once rendering has copied and committed it.
+        if let gst::QueryViewMut::Latency(q) = query.view_mut() {
+            let (live, min, max) = q.result();
+            let commit_time =
+                gst::ClockTime::from_nseconds(self.commit_time.load(Ordering::Relaxed));
+            gst::debug!(
+                CAT,
+                imp = self,
+                "Adding commit time {} to latency {} / {:?}",
+                commit_time,
+                min,
+                max
+            );
+            q.set(live, min + commit_time, max.map(|max| max + commit_time));
+        }
+        true
+    }
 }
 
 impl crate::clock::ClockOffsetExt for MxlSink {
@@ -319,6 +564,7 @@ impl BaseSinkImpl for MxlSink {
         // and mispairing flows in st2038combiner.
         self.ensure_clock_offset()
             .map_err(|_| crate::clock::ClockOffsetError::Failed.into_error_message())?;
+        self.commit_time.store(0, Ordering::Relaxed);
 
         let mut context = self.context.lock().map_err(|e| {
             gst::error_msg!(gst::CoreError::Failed, ["Failed to get state mutex: {}", e])
@@ -340,6 +586,8 @@ impl BaseSinkImpl for MxlSink {
     }
 
     fn stop(&self) -> Result<(), gst::ErrorMessage> {
+        // The slate thread takes the context lock, so join it first.
+        self.stop_slate_thread();
         let mut context = self.context.lock().map_err(|e| {
             gst::error_msg!(
                 gst::CoreError::Failed,
@@ -352,6 +600,22 @@ impl BaseSinkImpl for MxlSink {
         if let Some(mut state) = context.state.take() {
             match state.flow_state.take() {
                 Some(FlowState::Discrete(discrete)) => {
+                    if let Some(captions) = discrete.captions {
+                        captions.destroy().map_err(|e| {
+                            gst::error_msg!(
+                                gst::CoreError::Failed,
+                                ["Failed to destroy caption writer: {}", e]
+                            )
+                        })?;
+                    }
+                    if let Some(timecodes) = discrete.timecodes {
+                        timecodes.destroy().map_err(|e| {
+                            gst::error_msg!(
+                                gst::CoreError::Failed,
+                                ["Failed to destroy timecode writer: {}", e]
+                            )
+                        })?;
+                    }
                     discrete.writer.destroy().map_err(|e| {
                         gst::error_msg!(
                             gst::CoreError::Failed,
@@ -399,23 +663,55 @@ impl BaseSinkImpl for MxlSink {
             }
         };
 
-        let mut context = self.context.lock().map_err(|_| gst::FlowError::Error)?;
-        let state = context.state.as_mut().ok_or(gst::FlowError::Error)?;
-        // Borrow the element for the duration of this render call so
-        // the format-specific paths can read its base time via
-        // `Element::base_time()` without `State` having to cache a
-        // strong ref (which would form a refcount cycle).
+        let (max_ahead, policy) = {
+            let settings = self.settings.lock().map_err(|_| gst::FlowError::Error)?;
+            (settings.max_write_ahead, settings.write_ahead_policy)
+        };
+        // Borrow the element for the duration of this render call so the
+        // write-ahead limit can wait on its clock without `State` having to
+        // cache a strong ref (which would form a refcount cycle).
         let element = self.obj();
         let element: &gst::Element = element.upcast_ref();
-        match &state.flow_state {
-            Some(FlowState::Discrete(_)) => {
-                render_discrete::discrete(state, element, buffer, offset)
-            }
-            Some(FlowState::Continuous(_)) => {
-                render_continuous::continuous(state, element, buffer, offset)
-            }
-            None => Err(gst::FlowError::Error),
+        let base_ts = element
+            .base_time()
+            .and_then(|base_time| base_time.nseconds().checked_add(offset))
+            .ok_or(gst::FlowError::Error)?;
+        let limit = WriteAheadLimit {
+            max_ahead,
+            policy,
+            clock_wait: &self.clock_wait,
+            element: Some(element),
+            blocked: Default::default(),
+        };
+
+        let started = Instant::now();
+        let mut context = self.context.lock().map_err(|_| gst::FlowError::Error)?;
+        let state = context.state.as_mut().ok_or(gst::FlowError::Error)?;
+        let result = mxlsink::render_buffer(state, buffer, base_ts, &limit);
+        drop(context);
+        if result.is_ok() {
+            self.note_commit_time(started.elapsed().saturating_sub(limit.blocked.get()));
+        }
+        result
+    }
+
+    fn unlock(&self) -> Result<(), gst::ErrorMessage> {
+        let mut clock_wait = self.clock_wait.lock().map_err(|e| {
+            gst::error_msg!(gst::CoreError::Failed, ["Failed to lock clock: {}", e])
+        })?;
+        if let Some(clock_id) = clock_wait.clock_id.take() {
+            clock_id.unschedule();
         }
+        clock_wait.flushing = true;
+        Ok(())
+    }
+
+    fn unlock_stop(&self) -> Result<(), gst::ErrorMessage> {
+        let mut clock_wait = self.clock_wait.lock().map_err(|e| {
+            gst::error_msg!(gst::CoreError::Failed, ["Failed to lock clock: {}", e])
+        })?;
+        clock_wait.flushing = false;
+        Ok(())
     }
 
     fn prepare(&self, buffer: &gst::Buffer) -> Result<gst::FlowSuccess, gst::FlowError> {
@@ -443,6 +739,50 @@ impl BaseSinkImpl for MxlSink {
     }
 
     fn set_caps(&self, caps: &gst::Caps) -> Result<(), gst::LoggableError> {
+        self.stop_slate_thread();
+        let slate_period = self.init_state(caps)?;
+
+        // Started without the context lock held, since the thread takes it.
+        if let Some(period) = slate_period {
+            let slate_thread = SlateThread::spawn(self.context.clone(), period)
+                .map_err(|e| gst::loggable_error!(CAT, "Failed to start slate thread: {}", e))?;
+            let mut slate = self
+                .slate_thread
+                .lock()
+                .map_err(|e| gst::loggable_error!(CAT, "Failed to lock slate mutex: {}", e))?;
+            *slate = Some(slate_thread);
+        }
+        Ok(())
+    }
+
+    fn fixate(&self, caps: gst::Caps) -> gst::Caps {
+        self.parent_fixate(caps)
+    }
+
+    fn propose_allocation(
+        &self,
+        query: &mut gst::query::Allocation,
+    ) -> Result<(), gst::LoggableError> {
+        if let Some(pool) = self.grain_pool()? {
+            let (caps, _) = query.get_owned();
+            let size = pool.grain_size();
+            let mut config = pool.config();
+            config.set_params(caps.as_deref(), size, 0, 0);
+            pool.set_config(config)
+                .map_err(|e| gst::loggable_error!(CAT, "Failed to configure grain pool: {}", e))?;
+            query.add_allocation_pool(Some(&pool), size, 0, 0);
+        }
+        self.parent_propose_allocation(query)
+    }
+}
+
+impl MxlSink {
+    /// Creates the flow writer for `caps`. Returns the grain interval of the
+    /// slate thread if the flow gets a slate.
+    fn init_state(
+        &self,
+        caps: &gst::Caps,
+    ) -> Result<Option<std::time::Duration>, gst::LoggableError> {
         let mut context = self
             .context
             .lock()
@@ -457,38 +797,80 @@ impl BaseSinkImpl for MxlSink {
             .lock()
             .map_err(|e| gst::loggable_error!(CAT, "Failed to lock settings mutex: {}", e))?;
 
-        let structure = caps
-            .structure(0)
-            .ok_or_else(|| gst::loggable_error!(CAT, "No structure in caps {}", caps))?;
         let element = self.obj();
-        let element: &gst::Element = element.upcast_ref();
-        let name = structure.name();
-        if name == "video/x-raw" {
-            init_state_with_video(state, structure, &settings, element)?;
-            Ok(())
-        } else if name == "audio/x-raw" {
-            let info = gst_audio::AudioInfo::from_caps(caps)
-                .map_err(|e| gst::loggable_error!(CAT, "Invalid audio caps: {}", e))?;
-
-            init_state_with_audio(state, info, &settings, element)?;
-            Ok(())
-        } else if name == "meta/x-st-2038" {
-            init_state_with_data(state, structure, &settings, element)?;
-            Ok(())
-        } else {
-            Err(gst::loggable_error!(CAT, "Unknown caps: {}", caps))
+        init_state_from_caps(state, caps, &settings, Some(element.upcast_ref()))?;
+        Ok(slate_period(state))
+    }
+
+    /// Pool over the grains of the flow, for video flows with `zero-copy`.
+    /// Records how long rendering a buffer took and posts a latency message when that is longer
+    /// than the commit time latency queries report so far.
+    fn note_commit_time(&self, elapsed: Duration) {
+        let granularity = COMMIT_TIME_GRANULARITY.as_nanos();
+        let rounded = elapsed.as_nanos().div_ceil(granularity) * granularity;
+        let rounded = u64::try_from(rounded).unwrap_or(u64::MAX);
+        let previous = self.commit_time.fetch_max(rounded, Ordering::Relaxed);
+        if rounded > previous {
+            gst::debug!(
+                CAT,
+                imp = self,
+                "Commit time grew to {}",
+                gst::ClockTime::from_nseconds(rounded)
+            );
+            let _ = self
+                .obj()
+                .post_message(gst::message::Latency::builder().src(&*self.obj()).build());
         }
     }
 
-    fn fixate(&self, caps: gst::Caps) -> gst::Caps {
-        self.parent_fixate(caps)
+    fn grain_pool(&self) -> Result<Option<GrainPool>, gst::LoggableError> {
+        let context = self
+            .context
+            .lock()
+            .map_err(|e| gst::loggable_error!(CAT, "Failed to lock context mutex: {}", e))?;
+        let Some(state) = context.state.as_ref() else {
+            return Ok(None);
+        };
+        let (Some(FlowState::Discrete(discrete)), Some(flow_config)) =
+            (state.flow_state.as_ref(), state.flow_config.as_ref())
+        else {
+            return Ok(None);
+        };
+        if !discrete.zero_copy {
+            return Ok(None);
+        }
+        let grain_rate = flow_config
+            .common()
+            .grain_rate()
+            .map_err(|e| gst::loggable_error!(CAT, "Invalid grain rate: {}", e))?;
+        GrainPool::new(discrete.writer.clone(), state.instance.clone(), grain_rate)
+            .map(Some)
+            .map_err(|e| gst::loggable_error!(CAT, "Failed to create grain pool: {}", e))
     }
 
-    fn propose_allocation(
-        &self,
-        query: &mut gst::query::Allocation,
-    ) -> Result<(), gst::LoggableError> {
-        self.parent_propose_allocation(query)
+    fn stop_slate_thread(&self) {
+        let slate_thread = self
+            .slate_thread
+            .lock()
+            .ok()
+            .and_then(|mut slate_thread| slate_thread.take());
+        if let Some(slate_thread) = slate_thread {
+            slate_thread.stop();
+        }
+    }
+}
+
+/// Grain interval of the slate thread, for video flows with a slate.
+fn slate_period(state: &State) -> Option<std::time::Duration> {
+    match &state.flow_state {
+        Some(FlowState::Discrete(discrete)) if discrete.slate.is_some() => state
+            .flow_config
+            .as_ref()?
+            .common()
+            .grain_rate()
+            .ok()?
+            .period(),
+        _ => None,
     }
 }
 
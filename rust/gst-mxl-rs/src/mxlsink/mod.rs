@@ -16,11 +16,34 @@ use gst::prelude::*;
 use gstreamer as gst;
 use gstreamer_base as gst_base;
 
+mod grain_pool;
 mod imp;
 mod render_continuous;
 mod render_discrete;
 mod sink_tests;
-mod state;
+mod slate;
+pub(crate) mod state;
+pub(crate) mod write_ahead;
+
+pub use write_ahead::WriteAheadPolicy;
+
+/// Writes `buffer` to the flow of `state` at the MXL timestamp `PTS + base_ts`.
+pub(crate) fn render_buffer(
+    state: &mut state::State,
+    buffer: &gst::Buffer,
+    base_ts: u64,
+    limit: &write_ahead::WriteAheadLimit<'_>,
+) -> Result<gst::FlowSuccess, gst::FlowError> {
+    match &state.flow_state {
+        Some(state::FlowState::Discrete(_)) => {
+            render_discrete::discrete(state, buffer, base_ts, limit)
+        }
+        Some(state::FlowState::Continuous(_)) => {
+            render_continuous::continuous(state, buffer, base_ts, limit)
+        }
+        None => Err(gst::FlowError::Error),
+    }
+}
 
 glib::wrapper! {
     pub struct MxlSink(ObjectSubclass<imp::MxlSink>) @extends gst_base::PushSrc, gst_base::BaseSink, gst::Element, gst::Object;
@@ -4,21 +4,20 @@
 use crate::mxlsink::{
     self,
     state::{ContinuousState, FlowState},
+    write_ahead::{Admission, WriteAheadLimit},
 };
 
-use gstreamer::{self as gst, prelude::ElementExt};
-use mxl::Rational;
+use gstreamer as gst;
+use gstreamer_audio as gst_audio;
+use mxl::{InterleaveOptions, Rational, RationalExt};
 use tracing::trace;
 
 pub(crate) fn continuous(
     state: &mut mxlsink::state::State,
-    element: &gst::Element,
     buffer: &gst::Buffer,
-    offset: u64,
+    base_ts: u64,
+    limit: &WriteAheadLimit<'_>,
 ) -> Result<gst::FlowSuccess, gst::FlowError> {
-    let base_time = element.base_time().ok_or(gst::FlowError::Error)?;
-    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
-    let src = map.as_slice();
     let buffer_length = state
         .flow_config
         .as_ref()
@@ -34,13 +33,32 @@ pub(crate) fn continuous(
         Some(FlowState::Continuous(continuous)) => continuous,
         _ => return Err(gst::FlowError::Error),
     };
-    let bytes_per_sample = (continuous_state.flow_def.bit_depth / 8) as usize;
+    // Size of an input sample, which differs from the flow's for `S24_32LE`.
+    let bytes_per_sample = continuous_state
+        .format
+        .interleaved_format()
+        .bytes_per_sample();
     let num_channels = continuous_state.flow_def.channel_count as usize;
-    let samples_per_buffer = src.len() / (num_channels * bytes_per_sample);
-    let sample_rate = Rational {
-        numerator: continuous_state.flow_def.sample_rate.numerator as i64,
-        denominator: continuous_state.flow_def.sample_rate.denominator as i64,
+    let map;
+    let audio_buffer;
+    let input = match continuous_state.info.layout() {
+        gst_audio::AudioLayout::NonInterleaved => {
+            audio_buffer =
+                gst_audio::AudioBufferRef::from_buffer_ref_readable(buffer, &continuous_state.info)
+                    .map_err(|_| gst::FlowError::Error)?;
+            let planes = (0..audio_buffer.n_planes())
+                .map(|plane| audio_buffer.plane_data(plane))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| gst::FlowError::Error)?;
+            Input::Planar(planes)
+        }
+        _ => {
+            map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+            Input::Interleaved(map.as_slice())
+        }
     };
+    let samples_per_buffer = input.samples(num_channels, bytes_per_sample);
+    let sample_rate = Rational::from(&continuous_state.flow_def.sample_rate);
 
     let mut remaining = samples_per_buffer;
     let mut src_offset_samples = 0;
@@ -48,26 +66,22 @@ pub(crate) fn continuous(
     // consecutive chunks map to consecutive sample indices.
     let mut base_mxl_ts = gst_pts
         .nseconds()
-        .checked_add(base_time.nseconds())
-        .and_then(|timestamp| timestamp.checked_add(offset))
+        .checked_add(base_ts)
         .ok_or(gst::FlowError::Error)?;
 
     while remaining > 0 {
         let chunk_mxl_ts = base_mxl_ts;
         let chunk_samples = remaining.min(max_chunk);
-        let chunk_bytes = chunk_samples * num_channels * bytes_per_sample;
-        let chunk = compute_chunk(
-            src,
+        let chunk = input.chunk(
             bytes_per_sample,
             num_channels,
             src_offset_samples,
-            chunk_bytes,
+            chunk_samples,
         );
-        let chunk_duration_ns =
-            (chunk_samples as u128 * sample_rate.denominator as u128 * 1_000_000_000u128)
-                / sample_rate.numerator as u128;
-        let chunk_duration_ns =
-            u64::try_from(chunk_duration_ns).map_err(|_| gst::FlowError::Error)?;
+        let chunk_duration_ns = sample_rate
+            .duration_of(chunk_samples as u64)
+            .and_then(|duration| u64::try_from(duration.as_nanos()).ok())
+            .ok_or(gst::FlowError::Error)?;
         base_mxl_ts = base_mxl_ts
             .checked_add(chunk_duration_ns)
             .ok_or(gst::FlowError::Error)?;
@@ -82,29 +96,77 @@ pub(crate) fn continuous(
             .map_err(|_| gst::FlowError::Error)?;
         trace!("AUDIO mapped mxl_index from pts: {:#?}", mxl_index);
 
-        // GstBaseSink (sync=true) has already waited for this buffer's running
-        // time, so commit straight to the ring here: no separate pacing.
-        commit_chunk(
-            continuous_state,
-            mxl_index,
-            chunk,
-            chunk_samples,
-            bytes_per_sample,
-            num_channels,
-        )?;
+        let last_index = mxl_index + chunk_samples as u64 - 1;
+        if limit.admit(&state.instance, last_index, &sample_rate)? == Admission::Write {
+            // GstBaseSink (sync=true) has already waited for this buffer's running
+            // time, so commit straight to the ring here: no separate pacing.
+            commit_chunk(continuous_state, mxl_index, chunk, chunk_samples)?;
+        }
         src_offset_samples += chunk_samples;
         remaining -= chunk_samples;
     }
     Ok(gst::FlowSuccess::Ok)
 }
 
+/// Samples of an input buffer.
+enum Input<'a> {
+    Interleaved(&'a [u8]),
+    /// One plane per channel, for `layout=non-interleaved`.
+    Planar(Vec<&'a [u8]>),
+}
+
+impl<'a> Input<'a> {
+    /// Samples per channel.
+    fn samples(&self, num_channels: usize, bytes_per_sample: usize) -> usize {
+        match self {
+            Input::Interleaved(src) => src.len() / (num_channels * bytes_per_sample),
+            Input::Planar(planes) => planes
+                .iter()
+                .map(|plane| plane.len() / bytes_per_sample)
+                .min()
+                .unwrap_or(0),
+        }
+    }
+
+    /// The `chunk_samples` samples per channel starting at `src_offset_samples`.
+    fn chunk(
+        &self,
+        bytes_per_sample: usize,
+        num_channels: usize,
+        src_offset_samples: usize,
+        chunk_samples: usize,
+    ) -> Input<'a> {
+        match self {
+            Input::Interleaved(src) => Input::Interleaved(compute_chunk(
+                src,
+                bytes_per_sample,
+                num_channels,
+                src_offset_samples,
+                chunk_samples * num_channels * bytes_per_sample,
+            )),
+            Input::Planar(planes) => Input::Planar(
+                planes
+                    .iter()
+                    .map(|plane| {
+                        compute_chunk(
+                            plane,
+                            bytes_per_sample,
+                            1,
+                            src_offset_samples,
+                            chunk_samples * bytes_per_sample,
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
 fn commit_chunk(
     continuous_state: &mut ContinuousState,
     index: u64,
-    chunk: &[u8],
+    chunk: Input<'_>,
     chunk_samples: usize,
-    bytes_per_sample: usize,
-    num_channels: usize,
 ) -> Result<(), gst::FlowError> {
     // `open_samples(end, count)` writes the `count` samples at absolute indices
     // `[end - count, end)` (last written is `end - 1`). `index` is this chunk's
@@ -115,76 +177,26 @@ fn commit_chunk(
         .ok_or(gst::FlowError::Error)?;
     let mut access = continuous_state
         .writer
-        .open_samples(end, chunk_samples)
+        .open_samples_with_options(end, chunk_samples, &continuous_state.open_options)
         .map_err(|_| gst::FlowError::Error)?;
-    write_samples_per_channel(
-        bytes_per_sample,
-        num_channels,
-        &mut access,
-        chunk_samples,
-        chunk,
-    )?;
-    access.commit().map_err(|_| gst::FlowError::Error)?;
-    Ok(())
-}
-
-fn write_samples_per_channel(
-    bytes_per_sample: usize,
-    num_channels: usize,
-    access: &mut mxl::SamplesWriteAccess<'_>,
-    samples_per_channel: usize,
-    src_chunk: &[u8],
-) -> Result<(), gst::FlowError> {
-    for ch in 0..num_channels {
-        let (plane1, plane2) = access
-            .channel_data_mut(ch)
-            .map_err(|_| gst::FlowError::Error)?;
-
-        let mut written = 0;
-        let offset = ch * bytes_per_sample;
-
-        for i in 0..samples_per_channel {
-            let sample_offset = i * num_channels * bytes_per_sample + offset;
-            if sample_offset + bytes_per_sample > src_chunk.len() {
-                break;
+    let options = InterleaveOptions::new().format(continuous_state.format.interleaved_format());
+    match chunk {
+        Input::Interleaved(chunk) => access
+            .deinterleave_from(chunk, &options)
+            .map_err(|_| gst::FlowError::Error)?,
+        // Planes go straight to the channel buffers, without a de-interleave pass.
+        Input::Planar(planes) => {
+            for (channel, plane) in planes.into_iter().enumerate() {
+                access
+                    .write_channel_from(channel, plane, &options)
+                    .map_err(|_| gst::FlowError::Error)?;
             }
-
-            if does_sample_fit_in_plane(bytes_per_sample, plane1, written) {
-                write_sample(bytes_per_sample, src_chunk, plane1, written, sample_offset);
-            } else if written < plane1.len() + plane2.len() {
-                let plane2_offset = written.saturating_sub(plane1.len());
-                if does_sample_fit_in_plane(bytes_per_sample, plane2, plane2_offset) {
-                    write_sample(
-                        bytes_per_sample,
-                        src_chunk,
-                        plane2,
-                        plane2_offset,
-                        sample_offset,
-                    );
-                }
-            }
-
-            written += bytes_per_sample;
         }
     }
+    access.commit().map_err(|_| gst::FlowError::Error)?;
     Ok(())
 }
 
-fn write_sample(
-    bytes_per_sample: usize,
-    src_chunk: &[u8],
-    plane1: &mut [u8],
-    written: usize,
-    sample_offset: usize,
-) {
-    plane1[written..written + bytes_per_sample]
-        .copy_from_slice(&src_chunk[sample_offset..sample_offset + bytes_per_sample]);
-}
-
-fn does_sample_fit_in_plane(bytes_per_sample: usize, plane: &mut [u8], offset: usize) -> bool {
-    offset + bytes_per_sample <= plane.len()
-}
-
 fn compute_chunk(
     src: &[u8],
     bytes_per_sample: usize,
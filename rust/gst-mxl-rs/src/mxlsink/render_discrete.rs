@@ -4,21 +4,24 @@
 use std::borrow::Cow;
 
 use crate::format;
+use crate::format::captions::Caption;
 use crate::mxlsink::{
     self,
     state::{DiscreteFormat, DiscreteState, FlowState},
+    write_ahead::{Admission, WriteAheadLimit},
 };
 
-use gstreamer::{self as gst, prelude::ElementExt};
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use mxl::{GrainWriter, Rational, Timecode};
 use tracing::trace;
 
 pub(crate) fn discrete(
     state: &mut mxlsink::state::State,
-    element: &gst::Element,
     buffer: &gst::Buffer,
-    offset: u64,
+    base_ts: u64,
+    limit: &WriteAheadLimit<'_>,
 ) -> Result<gst::FlowSuccess, gst::FlowError> {
-    let base_time = element.base_time().ok_or(gst::FlowError::Error)?;
     let gst_pts = buffer.pts().ok_or(gst::FlowError::Error)?;
 
     let flow_config = state.flow_config.as_ref().ok_or(gst::FlowError::Error)?;
@@ -29,16 +32,18 @@ pub(crate) fn discrete(
 
     let mxl_ts = gst_pts
         .nseconds()
-        .checked_add(base_time.nseconds())
-        .and_then(|timestamp| timestamp.checked_add(offset))
+        .checked_add(base_ts)
         .ok_or(gst::FlowError::Error)?;
-    let discrete_state = match state.flow_state.as_ref() {
+    let discrete_state = match state.flow_state.as_mut() {
         Some(FlowState::Discrete(discrete)) => discrete,
         _ => return Err(gst::FlowError::Error),
     };
     let media = match discrete_state.format {
         DiscreteFormat::Video => "VIDEO",
         DiscreteFormat::Data => "DATA",
+        DiscreteFormat::Klv => "KLV",
+        DiscreteFormat::Cea708 => "CEA-708",
+        DiscreteFormat::Cea608 => "CEA-608",
     };
     trace!("{media} gst PTS: {:#?}", gst_pts);
     trace!("{media} mapped mxl timestamp: {:#?}", mxl_ts);
@@ -47,37 +52,170 @@ pub(crate) fn discrete(
         .timestamp_to_index(mxl_ts, &grain_rate)
         .map_err(|_| gst::FlowError::Error)?;
     trace!("{media} mapped mxl_index from pts: {:#?}", mxl_index);
+    let mxl_index = match (discrete_state.format, discrete_state.last_index) {
+        // A grain carries the KLV of one buffer; a buffer following within the
+        // same grain interval moves to the next index.
+        (DiscreteFormat::Klv, Some(last)) if last >= mxl_index => last + 1,
+        _ => mxl_index,
+    };
+    if limit.admit(&state.instance, mxl_index, &grain_rate)? == Admission::Drop {
+        return Ok(gst::FlowSuccess::Ok);
+    }
 
     let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+    if matches!(discrete_state.format, DiscreteFormat::Klv) && map.is_empty() {
+        return Ok(gst::FlowSuccess::Ok);
+    }
+    // GstBaseSink (sync=true) has already waited for this buffer's running time,
+    // so commit straight to the ring here: no separate pacing.
+    if discrete_state.zero_copy && commit_pooled(discrete_state, map.as_ptr(), mxl_index)? {
+        trace!("{media} committed pooled grain {mxl_index}");
+    } else {
+        commit_buffer(&map, discrete_state, mxl_index, limit.element)?;
+    }
+    discrete_state.last_index = Some(mxl_index);
+    if let Some(slate) = discrete_state.slate.as_mut() {
+        slate.last_input_index = Some(mxl_index);
+    }
+    if let Some(captions) = discrete_state.captions.as_mut() {
+        write_captions(captions, buffer, mxl_index)?;
+    }
+    if let Some(timecodes) = discrete_state.timecodes.as_mut() {
+        write_timecode(timecodes, buffer, mxl_index, &grain_rate)?;
+    }
+
+    Ok(gst::FlowSuccess::Ok)
+}
+
+/// Copies the buffer into the grain at `mxl_index`. While a pooled buffer holds the writer, the
+/// copy is written once it is released. A payload that does not fit the grain is an error, rather
+/// than a grain committed truncated or with a stale tail.
+fn commit_buffer(
+    map: &[u8],
+    discrete_state: &mut DiscreteState,
+    mxl_index: u64,
+    element: Option<&gst::Element>,
+) -> Result<(), gst::FlowError> {
     let payload = match discrete_state.format {
-        DiscreteFormat::Video => Cow::Borrowed(map.as_slice()),
+        DiscreteFormat::Video => Cow::Borrowed(map),
         DiscreteFormat::Data => Cow::Owned(
-            format::data::mxl_smpte291_grain_from_gst_st2038(map.as_slice())
+            format::data::mxl_smpte291_grain_from_gst_st2038(map)
+                .map_err(|_| gst::FlowError::Error)?,
+        ),
+        DiscreteFormat::Klv => Cow::Owned(
+            format::klv::mxl_klv_grain_from_gst_klv(map).map_err(|_| gst::FlowError::Error)?,
+        ),
+        DiscreteFormat::Cea708 => Cow::Owned(
+            format::captions::mxl_smpte291_grain_from_gst_cdp(map)
+                .map_err(|_| gst::FlowError::Error)?,
+        ),
+        DiscreteFormat::Cea608 => Cow::Owned(
+            format::captions::mxl_smpte291_grain_from_gst_s334_1a(map)
                 .map_err(|_| gst::FlowError::Error)?,
         ),
     };
-    // GstBaseSink (sync=true) has already waited for this buffer's running time,
-    // so commit straight to the ring here: no separate pacing.
-    commit_grain(payload.as_ref(), discrete_state, mxl_index)?;
-
-    Ok(gst::FlowSuccess::Ok)
+    // Grains are never written to the ring slot of a pooled buffer still out, so `map` may be
+    // one.
+    discrete_state
+        .writer
+        .lock()
+        .write_or_defer(mxl_index, &payload)
+        .map_err(|err| {
+            if let Some(element) = element {
+                gst::element_error!(
+                    element,
+                    gst::ResourceError::Write,
+                    ["Failed to write grain {}: {}", mxl_index, err]
+                );
+            }
+            gst::FlowError::Error
+        })
 }
 
-fn commit_grain(
-    payload: &[u8],
+/// Commits the grain of the pooled buffer whose payload starts at `data`, if it is still open at
+/// `index`. Returns `false` for other buffers, which are copied; a pooled buffer rendered at
+/// another index keeps its grain open until it is released.
+fn commit_pooled(
     discrete_state: &DiscreteState,
+    data: *const u8,
     index: u64,
-) -> Result<(), gst::FlowError> {
-    let mut access = discrete_state
-        .writer
-        .open_grain(index)
-        .map_err(|_| gst::FlowError::Error)?;
-    let destination = access.payload_mut();
-    let copy_len = std::cmp::min(destination.len(), payload.len());
-    destination[..copy_len].copy_from_slice(&payload[..copy_len]);
-    let total_slices = access.total_slices();
-    access
-        .commit(total_slices)
+) -> Result<bool, gst::FlowError> {
+    let mut writer = discrete_state.writer.lock();
+    let Some(grain) = writer.take_pooled(data, index) else {
+        return Ok(false);
+    };
+    writer
+        .commit_pooled(grain)
         .map_err(|_| gst::FlowError::Error)?;
-    Ok(())
+    Ok(true)
+}
+
+/// Writes the `GstVideoCaptionMeta` of a video buffer to the caption flow, at the
+/// index of its grain. Frames without captions leave the index unwritten.
+fn write_captions(
+    writer: &mut GrainWriter,
+    buffer: &gst::BufferRef,
+    index: u64,
+) -> Result<(), gst::FlowError> {
+    let captions: Vec<Caption> = buffer
+        .iter_meta::<gst_video::video_meta::VideoCaptionMeta>()
+        .filter_map(|meta| match meta.caption_type() {
+            gst_video::VideoCaptionType::Cea708Cdp => {
+                Some(Caption::Cea708Cdp(meta.data().to_vec()))
+            }
+            gst_video::VideoCaptionType::Cea608S3341a => {
+                Some(Caption::Cea608S3341a(meta.data().to_vec()))
+            }
+            other => {
+                trace!("Skipping {other:?} captions of grain {index}");
+                None
+            }
+        })
+        .collect();
+    if captions.is_empty() {
+        return Ok(());
+    }
+    match format::captions::mxl_smpte291_grain_from_captions(&captions) {
+        Ok(grain) => writer
+            .write_grain_padded(index, &grain)
+            .map_err(|_| gst::FlowError::Error),
+        // Bad captions must not stop the video.
+        Err(err) => {
+            trace!("Skipping captions of grain {index}: {err}");
+            Ok(())
+        }
+    }
+}
+
+/// Writes the `GstVideoTimeCodeMeta` of a video buffer to the timecode flow, at
+/// the index of its grain. Frames without a timecode leave the index unwritten.
+fn write_timecode(
+    writer: &mut GrainWriter,
+    buffer: &gst::BufferRef,
+    index: u64,
+    grain_rate: &Rational,
+) -> Result<(), gst::FlowError> {
+    let Some(meta) = buffer.meta::<gst_video::video_meta::VideoTimeCodeMeta>() else {
+        return Ok(());
+    };
+    let tc = meta.tc();
+    let timecode = Timecode {
+        hours: tc.hours() as u8,
+        minutes: tc.minutes() as u8,
+        seconds: tc.seconds() as u8,
+        frames: tc.frames() as u8,
+        drop_frame: tc
+            .flags()
+            .contains(gst_video::VideoTimeCodeFlags::DROP_FRAME),
+    };
+    match format::timecode::mxl_smpte291_grain_from_timecode(&timecode, grain_rate) {
+        Ok(grain) => writer
+            .write_grain_padded(index, &grain)
+            .map_err(|_| gst::FlowError::Error),
+        // A timecode ST 12-1 cannot carry must not stop the video.
+        Err(err) => {
+            trace!("Skipping timecode {timecode} of grain {index}: {err}");
+            Ok(())
+        }
+    }
 }
@@ -38,7 +38,7 @@ mod tests {
         let pipeline = gst::Pipeline::builder().name("pipeline0").build();
         let element = gst::Bin::builder().name("mxlsink0").build();
         pipeline.add(&element).unwrap();
-        let hint = default_group_hint("Video", element.upcast_ref());
+        let hint = default_group_hint("Video", Some(element.upcast_ref()));
         assert!(hint.ends_with(":Video mxlsink0"), "unexpected hint: {hint}");
         assert!(
             hint.contains(" pipeline0:"),
@@ -47,12 +47,85 @@ mod tests {
         assert!(hint.starts_with("Media Function "));
 
         let element = gst::Bin::builder().name("sink:cam").build();
-        let hint = default_group_hint("Audio", element.upcast_ref());
+        let hint = default_group_hint("Audio", Some(element.upcast_ref()));
         assert!(hint.ends_with(":Audio sink-cam"), "unexpected hint: {hint}");
         assert!(
             !hint.contains(" pipeline"),
             "pipeline-less hint should omit pipeline token: {hint}"
         );
+
+        let hint = default_group_hint("Data", None);
+        assert!(hint.ends_with(":Data"), "unexpected hint: {hint}");
+    }
+
+    #[test]
+    fn freeze_detection_needs_input_and_timeout() {
+        use crate::format::video::VideoFormat;
+        use crate::mxlsink::slate::{Slate, is_frozen};
+
+        assert!(!is_frozen(None, 100, 5));
+        assert!(!is_frozen(Some(95), 100, 5));
+        assert!(is_frozen(Some(94), 100, 5));
+        assert!(!is_frozen(Some(0), 100, 0));
+
+        let mut slate = Slate::new(5, 0, "", VideoFormat::V210, (1920, 1080)).unwrap();
+        slate.last_input_index = Some(10);
+        assert!(slate.is_due(16));
+        slate.last_slate_index = Some(16);
+        assert!(!slate.is_due(16));
+        assert!(slate.is_due(17));
+    }
+
+    #[test]
+    fn slate_color_packs_bt709_v210() {
+        use crate::mxlsink::slate::{rgb_to_ycbcr709, v210_color_block};
+
+        assert_eq!(rgb_to_ycbcr709(0x000000), (64, 512, 512));
+        assert_eq!(rgb_to_ycbcr709(0xffffff), (940, 512, 512));
+        let (y, cb, cr) = rgb_to_ycbcr709(0x0000ff);
+        assert!(y < 200 && cb > 900 && cr < 512);
+
+        let block = v210_color_block(0x000000);
+        let word = |i: usize| u32::from_le_bytes(block[i * 4..i * 4 + 4].try_into().unwrap());
+        assert_eq!(word(0), 512 | (64 << 10) | (512 << 20));
+        assert_eq!(word(1), 64 | (512 << 10) | (64 << 20));
+    }
+
+    #[test]
+    fn slate_color_packs_bt709_uyvy() {
+        use crate::mxlsink::slate::uyvy_color_block;
+
+        assert_eq!(uyvy_color_block(0x000000), [128, 16, 128, 16]);
+        assert_eq!(uyvy_color_block(0xffffff), [128, 235, 128, 235]);
+    }
+
+    #[test]
+    fn slate_color_orders_rgba_and_bgra_pixels() {
+        use crate::format::video::VideoFormat;
+        use crate::mxlsink::slate::rgba_color_pixel;
+
+        assert_eq!(
+            rgba_color_pixel(0x102030, VideoFormat::Rgba),
+            [0x10, 0x20, 0x30, 0xff]
+        );
+        assert_eq!(
+            rgba_color_pixel(0x102030, VideoFormat::Bgra),
+            [0x30, 0x20, 0x10, 0xff]
+        );
+    }
+
+    #[test]
+    fn slate_color_fills_420_planes() {
+        use crate::format::video::VideoFormat;
+        use crate::mxlsink::slate::planar_color_grain;
+
+        // 4x2 pictures: 8 luma bytes, then 4 bytes of chroma per plane row.
+        let i420 = planar_color_grain(0x000000, VideoFormat::I420, 4, 2);
+        assert_eq!(i420, [[16; 8].as_slice(), &[128; 4], &[128; 4]].concat());
+        let nv12 = planar_color_grain(0x0000ff, VideoFormat::Nv12, 4, 2);
+        let (y, cb, cr) = crate::mxlsink::slate::rgb_to_ycbcr709(0x0000ff);
+        let [y, cb, cr] = [y, cb, cr].map(|component| (component >> 2) as u8);
+        assert_eq!(nv12, [y, y, y, y, y, y, y, y, cb, cr, cb, cr]);
     }
 
     #[test]
@@ -193,4 +266,120 @@ mod tests {
             Some(["Camera:Video".to_string()].as_slice())
         );
     }
+
+    #[test]
+    fn excess_write_ahead_honours_limit() {
+        use crate::mxlsink::write_ahead::excess_write_ahead;
+
+        // Disabled limit never triggers.
+        assert_eq!(excess_write_ahead(1_000, 10, 0), None);
+        // Writes behind or at the current index are always within the limit.
+        assert_eq!(excess_write_ahead(5, 10, 2), None);
+        assert_eq!(excess_write_ahead(12, 10, 2), None);
+        assert_eq!(excess_write_ahead(13, 10, 2), Some(1));
+        assert_eq!(excess_write_ahead(20, 10, 2), Some(8));
+    }
+
+    #[test]
+    fn latency_includes_processing_deadline_and_commit_time() {
+        gst::init().unwrap();
+        gst::Element::register(
+            None,
+            "mxlsink",
+            gst::Rank::NONE,
+            crate::mxlsink::MxlSink::static_type(),
+        )
+        .unwrap();
+        let guard = mxl_test_utils::TestDomainGuard::new("mxlsink_latency");
+
+        let pipeline = gst::parse::launch(&format!(
+            "videotestsrc name=src is-live=true \
+               ! video/x-raw,format=v210,width=64,height=36,framerate=25/1 \
+               ! mxlsink name=sink flow-id={} domain={} processing-deadline=5000000",
+            Uuid::new_v4(),
+            guard.domain()
+        ))
+        .unwrap()
+        .downcast::<gst::Pipeline>()
+        .unwrap();
+        let latency = |name: &str| {
+            let mut query = gst::query::Latency::new();
+            assert!(pipeline.by_name(name).unwrap().query(&mut query));
+            query.result()
+        };
+
+        pipeline.set_state(gst::State::Playing).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(400));
+        let (src_live, src_min, _) = latency("src");
+        let (sink_live, sink_min, _) = latency("sink");
+        pipeline.set_state(gst::State::Null).unwrap();
+
+        assert!(src_live && sink_live);
+        // Commits are reported rounded up to whole milliseconds, so at least 1 ms.
+        assert!(
+            sink_min >= src_min + gst::ClockTime::from_mseconds(6),
+            "sink latency {sink_min}, upstream latency {src_min}"
+        );
+    }
+
+    #[test]
+    fn grain_payloads_must_fit() {
+        use crate::mxlsink::grain_pool::check_size;
+
+        assert!(check_size(true, 4096, 4096).is_ok());
+        assert!(check_size(true, 4095, 4096).is_err());
+        assert!(check_size(true, 4097, 4096).is_err());
+        // Data grains are zero padded.
+        assert!(check_size(false, 100, 4096).is_ok());
+        assert!(check_size(false, 4097, 4096).is_err());
+    }
+
+    #[test]
+    fn short_video_buffer_is_an_error() {
+        gst::init().unwrap();
+        gst::Element::register(
+            None,
+            "mxlsink",
+            gst::Rank::NONE,
+            crate::mxlsink::MxlSink::static_type(),
+        )
+        .unwrap();
+        let guard = mxl_test_utils::TestDomainGuard::new("mxlsink_short_buffer");
+
+        let pipeline = gst::parse::launch(&format!(
+            "appsrc name=src format=time is-live=true do-timestamp=true \
+               caps=video/x-raw,format=v210,width=64,height=16,framerate=25/1 \
+               ! mxlsink flow-id={} domain={} sync=false",
+            Uuid::new_v4(),
+            guard.domain()
+        ))
+        .unwrap()
+        .downcast::<gst::Pipeline>()
+        .unwrap();
+        let appsrc = pipeline
+            .by_name("src")
+            .unwrap()
+            .downcast::<gstreamer_app::AppSrc>()
+            .unwrap();
+        pipeline.set_state(gst::State::Playing).unwrap();
+        // A v210 frame of 64x16 takes 4096 bytes.
+        let _ = appsrc.push_buffer(gst::Buffer::from_mut_slice(vec![0u8; 4000]));
+        let message = pipeline
+            .bus()
+            .unwrap()
+            .timed_pop_filtered(gst::ClockTime::from_seconds(5), &[gst::MessageType::Error]);
+        pipeline.set_state(gst::State::Null).unwrap();
+
+        let Some(message) = message else {
+            panic!("no error for a short buffer");
+        };
+        let gst::MessageView::Error(error) = message.view() else {
+            unreachable!();
+        };
+        assert!(
+            error.error().matches(gst::ResourceError::Write),
+            "unexpected error: {:?}",
+            error.error()
+        );
+    }
 }
@@ -0,0 +1,276 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Slate written to video flows while upstream stalls (`freeze-detection`).
+//!
+//! Once input has been seen, a background thread checks every grain interval
+//! whether the newest committed input grain is more than `freeze-detection`
+//! grains behind the current MXL index. While it is, the thread commits the
+//! slate at the current index, so that readers keep receiving grains. With
+//! `slate-flow-id`, every slate grain is also marked by a [`SLATE_EVENT`] at
+//! its index in an [event flow](mxl::events), so readers can tell slate grains
+//! apart from real input.
+
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use mxl::{GrainWriter, MXL_DATA_FORMAT_GRAIN_SIZE, encode_packets};
+use tracing::{info, trace};
+
+use crate::format::video::VideoFormat;
+use crate::mxlsink::grain_pool::PooledWriter;
+use crate::mxlsink::state::{Context, FlowState};
+
+pub(crate) const DEFAULT_FREEZE_DETECTION: u32 = 0;
+pub(crate) const DEFAULT_SLATE_COLOR: u32 = 0x000000;
+/// Event written to the `slate-flow-id` flow at the index of every slate grain.
+pub(crate) const SLATE_EVENT: &str = r#"{"slate":true}"#;
+
+/// Payload committed for slate grains.
+pub(crate) enum SlatePayload {
+    /// A solid color, as one repeating block of pixels in the flow's format.
+    Color(Vec<u8>),
+    /// A whole grain, pre-rendered and typically carrying a color and a text
+    /// overlay, or a solid color of a planar format.
+    Grain(Vec<u8>),
+}
+
+pub(crate) struct Slate {
+    pub payload: SlatePayload,
+    /// Grain intervals without input before the slate is written.
+    pub timeout: u32,
+    /// Newest grain index committed from input.
+    pub last_input_index: Option<u64>,
+    /// Newest grain index committed with the slate.
+    pub last_slate_index: Option<u64>,
+    /// Writer of the `slate-flow-id` flow.
+    pub markers: Option<GrainWriter>,
+}
+
+impl Slate {
+    /// Loads the pre-rendered grain at `location`, or renders `color` in
+    /// `format` for pictures of `(width, height)` if it is empty.
+    pub(crate) fn new(
+        timeout: u32,
+        color: u32,
+        location: &str,
+        format: VideoFormat,
+        (width, height): (usize, usize),
+    ) -> Result<Self, std::io::Error> {
+        let payload = if location.is_empty() {
+            match format {
+                VideoFormat::V210 => SlatePayload::Color(v210_color_block(color).to_vec()),
+                VideoFormat::Uyvy => SlatePayload::Color(uyvy_color_block(color).to_vec()),
+                VideoFormat::Rgba | VideoFormat::Bgra => {
+                    SlatePayload::Color(rgba_color_pixel(color, format).to_vec())
+                }
+                VideoFormat::I420 | VideoFormat::Nv12 => {
+                    SlatePayload::Grain(planar_color_grain(color, format, width, height))
+                }
+            }
+        } else {
+            SlatePayload::Grain(std::fs::read(location)?)
+        };
+        Ok(Self {
+            payload,
+            timeout,
+            last_input_index: None,
+            last_slate_index: None,
+            markers: None,
+        })
+    }
+
+    /// Whether the slate should be committed at `current_index`.
+    pub(crate) fn is_due(&self, current_index: u64) -> bool {
+        is_frozen(self.last_input_index, current_index, self.timeout)
+            && self.last_slate_index != Some(current_index)
+    }
+
+    fn fill(&self, destination: &mut [u8]) {
+        match &self.payload {
+            SlatePayload::Color(block) => {
+                for chunk in destination.chunks_mut(block.len()) {
+                    chunk.copy_from_slice(&block[..chunk.len()]);
+                }
+            }
+            SlatePayload::Grain(grain) => {
+                let copy_len = destination.len().min(grain.len());
+                destination[..copy_len].copy_from_slice(&grain[..copy_len]);
+            }
+        }
+    }
+
+    /// Skipped while a pooled buffer of `zero-copy` holds the writer.
+    fn commit(&mut self, writer: &mut PooledWriter, index: u64) -> mxl::Result<()> {
+        let Some(mut access) = writer.open_grain(index)? else {
+            trace!("Pooled buffer out, skipping slate at index {index}");
+            return Ok(());
+        };
+        self.fill(access.payload_mut());
+        let total_slices = access.total_slices();
+        access.commit(total_slices)?;
+        self.last_slate_index = Some(index);
+        if let Some(markers) = self.markers.as_mut() {
+            let mut marker = vec![0; MXL_DATA_FORMAT_GRAIN_SIZE];
+            encode_packets(&[SLATE_EVENT.as_bytes()], &mut marker)?;
+            markers.write_grain(index, &marker)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether input has stalled: it was seen once, but its newest grain is more
+/// than `timeout` grains behind `current_index`. `timeout == 0` disables the
+/// detection.
+pub(crate) fn is_frozen(last_input_index: Option<u64>, current_index: u64, timeout: u32) -> bool {
+    match last_input_index {
+        Some(last) if timeout > 0 => current_index.saturating_sub(last) > timeout as u64,
+        _ => false,
+    }
+}
+
+/// 10-bit BT.709 narrow range Y'CbCr of a `0xRRGGBB` color.
+pub(crate) fn rgb_to_ycbcr709(rgb: u32) -> (u32, u32, u32) {
+    let [_, r, g, b] = rgb.to_be_bytes().map(|c| c as f64 / 255.0);
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let cb = (b - y) / 1.8556;
+    let cr = (r - y) / 1.5748;
+    (
+        (64.0 + 876.0 * y).round() as u32,
+        (512.0 + 896.0 * cb).round() as u32,
+        (512.0 + 896.0 * cr).round() as u32,
+    )
+}
+
+/// Six pixels of `rgb` packed as v210 (four little-endian words of three
+/// 10-bit components).
+pub(crate) fn v210_color_block(rgb: u32) -> [u8; 16] {
+    let (y, cb, cr) = rgb_to_ycbcr709(rgb);
+    let pack = |a: u32, b: u32, c: u32| a | (b << 10) | (c << 20);
+    let words = [
+        pack(cb, y, cr),
+        pack(y, cb, y),
+        pack(cr, y, cb),
+        pack(y, cr, y),
+    ];
+    let mut block = [0u8; 16];
+    for (chunk, word) in block.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    block
+}
+
+/// Two pixels of `rgb` packed as UYVY, the 8 most significant bits of the
+/// 10-bit components.
+pub(crate) fn uyvy_color_block(rgb: u32) -> [u8; 4] {
+    let (y, cb, cr) = rgb_to_ycbcr709(rgb);
+    [cb, y, cr, y].map(|component| (component >> 2) as u8)
+}
+
+/// One opaque pixel of `rgb` in the RGBA or BGRA `format`.
+pub(crate) fn rgba_color_pixel(rgb: u32, format: VideoFormat) -> [u8; 4] {
+    let [_, r, g, b] = rgb.to_be_bytes();
+    match format {
+        VideoFormat::Bgra => [b, g, r, 0xff],
+        _ => [r, g, b, 0xff],
+    }
+}
+
+/// A grain of `rgb` in the 4:2:0 `format`, the 8 most significant bits of
+/// the 10-bit components in every plane.
+pub(crate) fn planar_color_grain(
+    rgb: u32,
+    format: VideoFormat,
+    width: usize,
+    height: usize,
+) -> Vec<u8> {
+    let (y, cb, cr) = rgb_to_ycbcr709(rgb);
+    let [y, cb, cr] = [y, cb, cr].map(|component| (component >> 2) as u8);
+    // Samples repeating along the lines of each plane.
+    let samples: &[&[u8]] = match format {
+        VideoFormat::Nv12 => &[&[y], &[cb, cr]],
+        _ => &[&[y], &[cb], &[cr]],
+    };
+    let mut grain = Vec::with_capacity(format.grain_size(width, height));
+    for (plane, samples) in format.planes(width, height).iter().zip(samples) {
+        grain.extend(samples.iter().copied().cycle().take(plane.size()));
+    }
+    grain
+}
+
+/// Background thread writing the slate while input is frozen.
+pub(crate) struct SlateThread {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl SlateThread {
+    /// Checks the flow in `context` every `period` (one grain interval).
+    pub(crate) fn spawn(context: Arc<Mutex<Context>>, period: Duration) -> std::io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = thread::Builder::new().name("mxlsink-slate".into()).spawn({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(period);
+                    if !tick(&context) {
+                        break;
+                    }
+                }
+            }
+        })?;
+        Ok(Self { stop, handle })
+    }
+
+    pub(crate) fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if self.handle.join().is_err() {
+            info!("Slate thread panicked");
+        }
+    }
+}
+
+/// Writes the slate if it is due. Returns `false` once the context is unusable.
+fn tick(context: &Mutex<Context>) -> bool {
+    let Ok(mut context) = context.lock() else {
+        return false;
+    };
+    let Some(state) = context.state.as_mut() else {
+        return true;
+    };
+    let Some(FlowState::Discrete(discrete)) = state.flow_state.as_mut() else {
+        return true;
+    };
+    let Some(slate) = discrete.slate.as_mut() else {
+        return true;
+    };
+    let Some(grain_rate) = state
+        .flow_config
+        .as_ref()
+        .and_then(|config| config.common().grain_rate().ok())
+    else {
+        return true;
+    };
+    let current_index = state.instance.get_current_index(&grain_rate);
+    if !slate.is_due(current_index) {
+        return true;
+    }
+    let starting = match (slate.last_slate_index, slate.last_input_index) {
+        (Some(slate_index), Some(input_index)) => slate_index < input_index,
+        _ => true,
+    };
+    if starting {
+        info!("Input stalled, writing slate from index {current_index}");
+    }
+    trace!("Writing slate at index {current_index}");
+    if let Err(err) = slate.commit(&mut discrete.writer.lock(), current_index) {
+        trace!("Failed to write slate at index {current_index}: {err}");
+    }
+    true
+}
@@ -3,17 +3,22 @@
 
 use std::{collections::HashMap, process, str::FromStr};
 
+use crate::format;
+use crate::format::audio::AudioFormat;
+use crate::format::video::VideoFormat;
+use crate::mxlsink::grain_pool::SharedGrainWriter;
 use crate::mxlsink::imp::CAT;
+use crate::mxlsink::slate::{DEFAULT_FREEZE_DETECTION, DEFAULT_SLATE_COLOR, Slate};
+use crate::mxlsink::write_ahead::{DEFAULT_MAX_WRITE_AHEAD, WriteAheadPolicy};
 use gst::StructureRef;
 use gst::prelude::*;
 use gst_audio::AudioInfo;
 use gstreamer as gst;
 use gstreamer_audio as gst_audio;
 use mxl::{
-    FlowConfigInfo, GrainWriter, MxlInstance, SamplesWriter,
+    AccessOptions, FlowConfigInfo, GrainWriter, MxlInstance, SamplesWriter,
     flowdef::{
-        Component, FlowDef, FlowDefAudio, FlowDefData, FlowDefDetails, FlowDefVideo, InterlaceMode,
-        Rate,
+        FlowDef, FlowDefAudio, FlowDefData, FlowDefDetails, FlowDefVideo, InterlaceMode, Rate,
     },
 };
 use tracing::trace;
@@ -22,6 +27,7 @@ use uuid::Uuid;
 
 pub(crate) const DEFAULT_FLOW_ID: &str = "";
 pub(crate) const DEFAULT_DOMAIN: &str = "";
+pub(crate) const DEFAULT_ZERO_COPY: bool = false;
 pub(crate) const GROUPHINT_TAG: &str = "urn:x-nmos:tag:grouphint/v1.0";
 
 #[derive(Debug, Clone)]
@@ -36,6 +42,33 @@ pub(crate) struct Settings {
     pub description: String,
     /// `urn:x-nmos:tag:grouphint/v1.0` value. Empty keeps the built-in default.
     pub group_hint: String,
+    /// Maximum distance, in grains or samples, a commit may land ahead of the
+    /// current MXL index. 0 disables the limit.
+    pub max_write_ahead: u64,
+    /// What to do with writes beyond `max_write_ahead`.
+    pub write_ahead_policy: WriteAheadPolicy,
+    /// Grain intervals without input before the slate is written to a video
+    /// flow. 0 disables the slate.
+    pub freeze_detection: u32,
+    /// `0xRRGGBB` color of the slate, unless `slate_location` is set.
+    pub slate_color: u32,
+    /// Path to a pre-rendered v210 slate grain. Empty uses `slate_color`.
+    pub slate_location: String,
+    /// UUID of the `application/json` flow marking the slate grains of a
+    /// video flow. Empty marks none.
+    pub slate_flow_id: String,
+    /// Comma separated `key=value` options of every grain or samples open, see
+    /// [`AccessOptions::parse`].
+    pub open_options: String,
+    /// Offer upstream a pool of buffers over grains of a video flow, see
+    /// `mxlsink/grain_pool.rs`.
+    pub zero_copy: bool,
+    /// UUID of the `video/smpte291` flow the `GstVideoCaptionMeta` of video
+    /// buffers are written to. Empty writes no captions.
+    pub caption_flow_id: String,
+    /// UUID of the `video/smpte291` flow the `GstVideoTimeCodeMeta` of video
+    /// buffers are written to. Empty writes no timecodes.
+    pub timecode_flow_id: String,
 }
 
 impl Default for Settings {
@@ -46,6 +79,16 @@ impl Default for Settings {
             label: String::new(),
             description: String::new(),
             group_hint: String::new(),
+            max_write_ahead: DEFAULT_MAX_WRITE_AHEAD,
+            write_ahead_policy: WriteAheadPolicy::default(),
+            freeze_detection: DEFAULT_FREEZE_DETECTION,
+            slate_color: DEFAULT_SLATE_COLOR,
+            slate_location: String::new(),
+            slate_flow_id: DEFAULT_FLOW_ID.to_owned(),
+            open_options: String::new(),
+            zero_copy: DEFAULT_ZERO_COPY,
+            caption_flow_id: DEFAULT_FLOW_ID.to_owned(),
+            timecode_flow_id: DEFAULT_FLOW_ID.to_owned(),
         }
     }
 }
@@ -72,7 +115,7 @@ pub(crate) fn format_sample_rate_khz(rate_hz: i32) -> String {
 }
 
 /// Replace `:` so grouphint group/role components stay well-formed.
-fn sanitize_grouphint_component(s: &str) -> String {
+pub(crate) fn sanitize_grouphint_component(s: &str) -> String {
     s.replace(':', "-")
 }
 
@@ -88,18 +131,28 @@ fn containing_pipeline_name(element: &gst::Element) -> Option<String> {
     None
 }
 
-/// Built-in group hint: process- and pipeline-scoped group name, plus a
-/// media-type role that includes the GStreamer element name so multiple sinks
-/// of the same type stay unique
-/// (`Media Function 12345 pipeline0:Video mxlsink0`).
-pub(crate) fn default_group_hint(media_role: &str, element: &gst::Element) -> String {
-    let group = match containing_pipeline_name(element) {
+/// Built-in group name, the part of a group hint before the role:
+/// `Media Function 12345 pipeline0`, or `Media Function 12345` outside a pipeline.
+pub(crate) fn default_group_name(element: Option<&gst::Element>) -> String {
+    match element.and_then(containing_pipeline_name) {
         Some(pipeline_name) => {
             format!("Media Function {} {}", process::id(), pipeline_name)
         }
         None => format!("Media Function {}", process::id()),
+    }
+}
+
+/// Built-in group hint: process- and pipeline-scoped group name, plus a
+/// media-type role that includes the GStreamer element name so multiple sinks
+/// of the same type stay unique
+/// (`Media Function 12345 pipeline0:Video mxlsink0`). Without an element (flows
+/// written through [`crate::bridge`]) the role is the media type alone.
+pub(crate) fn default_group_hint(media_role: &str, element: Option<&gst::Element>) -> String {
+    let group = default_group_name(element);
+    let role = match element {
+        Some(element) => format!("{media_role} {}", element.name()),
+        None => media_role.to_string(),
     };
-    let role = format!("{media_role} {}", element.name());
     format!(
         "{}:{}",
         sanitize_grouphint_component(&group),
@@ -107,6 +160,22 @@ pub(crate) fn default_group_hint(media_role: &str, element: &gst::Element) -> St
     )
 }
 
+/// Group hint of a data flow written next to a video flow: the video flow's
+/// group with `media_role`.
+fn companion_group_hint(
+    settings: &Settings,
+    media_role: &str,
+    element: Option<&gst::Element>,
+) -> String {
+    match settings.group_hint.split_once(':') {
+        Some((group, _)) => format!("{group}:{media_role}"),
+        None if !settings.group_hint.is_empty() => {
+            format!("{}:{media_role}", settings.group_hint)
+        }
+        None => default_group_hint(media_role, element),
+    }
+}
+
 /// Resolve optional `label` / `description` / `group-hint` property overrides
 /// against the built-in defaults used when those properties are empty.
 /// `default_name` is used for both `label` and `description` when unset.
@@ -152,16 +221,36 @@ pub(crate) enum FlowState {
 pub(crate) enum DiscreteFormat {
     Video,
     Data,
+    Klv,
+    /// CEA-708 CDP buffers, written as ANC packets of a `video/smpte291` flow.
+    Cea708,
+    /// CEA-608 S334-1A buffers, written as ANC packets of a `video/smpte291` flow.
+    Cea608,
 }
 
 pub(crate) struct DiscreteState {
     pub format: DiscreteFormat,
-    pub writer: GrainWriter,
+    pub writer: SharedGrainWriter,
+    /// Index of the last grain written from a buffer.
+    pub last_index: Option<u64>,
+    /// Written while input stalls, video only; `None` when `freeze-detection` is 0.
+    pub slate: Option<Slate>,
+    /// Upstream may render buffers of a [`crate::mxlsink::grain_pool::GrainPool`], video only.
+    pub zero_copy: bool,
+    /// Writer of the flow of `caption-flow-id`, video only.
+    pub captions: Option<GrainWriter>,
+    /// Writer of the flow of `timecode-flow-id`, video only.
+    pub timecodes: Option<GrainWriter>,
 }
 
 pub(crate) struct ContinuousState {
     pub writer: SamplesWriter,
     pub flow_def: FlowDefAudio,
+    /// Sample format of the input buffers.
+    pub format: AudioFormat,
+    /// Input caps, whose layout tells interleaved buffers from ones with a plane per channel.
+    pub info: AudioInfo,
+    pub open_options: AccessOptions,
 }
 
 #[derive(Default)]
@@ -169,15 +258,63 @@ pub(crate) struct Context {
     pub state: Option<State>,
 }
 
+/// Parses the `open-options` property.
+fn open_options(settings: &Settings) -> Result<AccessOptions, gst::LoggableError> {
+    AccessOptions::parse(&settings.open_options)
+        .map_err(|e| gst::loggable_error!(CAT, "Invalid open-options: {}", e))
+}
+
+/// Creates the flow writer matching `caps`: raw video, audio, ST 2038
+/// ancillary data, closed captions, or KLV metadata.
+pub(crate) fn init_state_from_caps(
+    state: &mut State,
+    caps: &gst::Caps,
+    settings: &Settings,
+    element: Option<&gst::Element>,
+) -> Result<(), gst::LoggableError> {
+    let structure = caps
+        .structure(0)
+        .ok_or_else(|| gst::loggable_error!(CAT, "No structure in caps {}", caps))?;
+    let name = structure.name();
+    if name == "video/x-raw" {
+        init_state_with_video(state, structure, settings, element)
+    } else if name == "audio/x-raw" {
+        let info = AudioInfo::from_caps(caps)
+            .map_err(|e| gst::loggable_error!(CAT, "Invalid audio caps: {}", e))?;
+        init_state_with_audio(state, info, settings, element)
+    } else if name == "meta/x-st-2038" {
+        init_state_with_data(state, structure, settings, element, DiscreteFormat::Data)
+    } else if name == "meta/x-klv" {
+        init_state_with_data(state, structure, settings, element, DiscreteFormat::Klv)
+    } else if name == "closedcaption/x-cea-708" || name == "closedcaption/x-cea-608" {
+        let format = match (name, structure.get::<&str>("format")) {
+            ("closedcaption/x-cea-708", Ok("cdp")) => DiscreteFormat::Cea708,
+            ("closedcaption/x-cea-608", Ok("s334-1a")) => DiscreteFormat::Cea608,
+            _ => {
+                return Err(gst::loggable_error!(
+                    CAT,
+                    "Unsupported caption format, use CEA-708 cdp or CEA-608 s334-1a: {}",
+                    caps
+                ));
+            }
+        };
+        init_state_with_data(state, structure, settings, element, format)
+    } else {
+        Err(gst::loggable_error!(CAT, "Unknown caps: {}", caps))
+    }
+}
+
 pub(crate) fn init_state_with_video(
     state: &mut State,
     structure: &StructureRef,
     settings: &Settings,
-    element: &gst::Element,
+    element: Option<&gst::Element>,
 ) -> Result<(), gst::LoggableError> {
-    let format = structure
-        .get::<String>("format")
-        .unwrap_or_else(|_| "v210".to_string());
+    let format = match structure.get::<String>("format") {
+        Ok(format) => VideoFormat::from_gst_format(&format)
+            .ok_or_else(|| gst::loggable_error!(CAT, "Unsupported video format: {}", format))?,
+        Err(_) => VideoFormat::V210,
+    };
     let width = structure.get::<i32>("width").unwrap_or(1920);
     let height = structure.get::<i32>("height").unwrap_or(1080);
     let framerate = structure
@@ -188,6 +325,13 @@ pub(crate) fn init_state_with_video(
         .unwrap_or_else(|_| "progressive".to_string());
     let interlace_mode =
         InterlaceMode::from_str(interlace.as_str()).unwrap_or(InterlaceMode::Progressive);
+    if format.is_420() && interlace_mode != InterlaceMode::Progressive {
+        return Err(gst::loggable_error!(
+            CAT,
+            "Interlaced {} is not supported",
+            format.gst_format()
+        ));
+    }
     let colorimetry = structure
         .get::<String>("colorimetry")
         .unwrap_or_else(|_| "BT709".to_string());
@@ -207,26 +351,7 @@ pub(crate) fn init_state_with_video(
         frame_height: height,
         interlace_mode,
         colorspace: colorimetry,
-        components: vec![
-            Component {
-                name: "Y".into(),
-                width,
-                height,
-                bit_depth: 10,
-            },
-            Component {
-                name: "Cb".into(),
-                width: width / 2,
-                height,
-                bit_depth: 10,
-            },
-            Component {
-                name: "Cr".into(),
-                width: width / 2,
-                height,
-                bit_depth: 10,
-            },
-        ],
+        components: format.components(width, height),
     };
     let flow_def = FlowDef {
         id: Uuid::parse_str(&settings.flow_id)
@@ -236,7 +361,7 @@ pub(crate) fn init_state_with_video(
         format: "urn:x-nmos:format:video".into(),
         label,
         parents: vec![],
-        media_type: format!("video/{}", format),
+        media_type: format.media_type().into(),
         details: mxl::flowdef::FlowDefDetails::Video(flow_def_details),
     };
     let instance = &state.instance;
@@ -258,9 +383,63 @@ pub(crate) fn init_state_with_video(
     let writer = flow_writer
         .to_grain_writer()
         .map_err(|e| gst::loggable_error!(CAT, "Failed to create grain writer: {}", e))?;
+    let mut slate = match settings.freeze_detection {
+        0 => None,
+        timeout => Some(
+            Slate::new(
+                timeout,
+                settings.slate_color,
+                &settings.slate_location,
+                format,
+                (width.max(0) as usize, height.max(0) as usize),
+            )
+            .map_err(|e| {
+                gst::loggable_error!(
+                    CAT,
+                    "Failed to read slate {}: {}",
+                    settings.slate_location,
+                    e
+                )
+            })?,
+        ),
+    };
+    if let Some(slate) = slate.as_mut() {
+        slate.markers = companion_data_flow(
+            &state.instance,
+            settings,
+            element,
+            &settings.slate_flow_id,
+            "Slate",
+            mxl::events::EVENT_MEDIA_TYPE,
+            framerate,
+        )?;
+    }
+    let captions = companion_data_flow(
+        &state.instance,
+        settings,
+        element,
+        &settings.caption_flow_id,
+        "Caption",
+        format::data::SMPTE291_MEDIA_TYPE,
+        framerate,
+    )?;
+    let timecodes = companion_data_flow(
+        &state.instance,
+        settings,
+        element,
+        &settings.timecode_flow_id,
+        "Timecode",
+        format::data::SMPTE291_MEDIA_TYPE,
+        framerate,
+    )?;
     state.flow_state = Some(FlowState::Discrete(DiscreteState {
         format: DiscreteFormat::Video,
-        writer,
+        writer: SharedGrainWriter::new(writer, open_options(settings)?, true),
+        last_index: None,
+        slate,
+        zero_copy: settings.zero_copy,
+        captions,
+        timecodes,
     }));
     state.flow_config = Some(flow);
 
@@ -271,12 +450,13 @@ pub(crate) fn init_state_with_audio(
     state: &mut State,
     info: AudioInfo,
     settings: &Settings,
-    element: &gst::Element,
+    element: Option<&gst::Element>,
 ) -> Result<(), gst::LoggableError> {
+    let format = AudioFormat::from_gst_format(info.format().to_str())
+        .ok_or_else(|| gst::loggable_error!(CAT, "Unsupported audio format: {}", info.format()))?;
     let channels = info.channels() as i32;
     let rate = info.rate() as i32;
-    let bit_depth = info.depth() as u8;
-    let format = info.format().to_string();
+    let bit_depth = format.bit_depth();
     let default_name = format!(
         "MXL Audio Flow, {} ch, {}",
         channels,
@@ -301,7 +481,7 @@ pub(crate) fn init_state_with_audio(
         format: "urn:x-nmos:format:audio".into(),
         tags,
         label,
-        media_type: "audio/float32".to_string(),
+        media_type: format.media_type().into(),
         parents: vec![],
         details: FlowDefDetails::Audio(flow_def_details.clone()),
     };
@@ -327,53 +507,133 @@ pub(crate) fn init_state_with_audio(
     state.flow_state = Some(FlowState::Continuous(ContinuousState {
         writer,
         flow_def: flow_def_details,
+        format,
+        info,
+        open_options: open_options(settings)?,
     }));
     state.flow_config = Some(flow);
 
     trace!(
         "Made it to the end of set_caps with format {}, channel_count {}, sample_rate {}, bit_depth {}",
-        format, channels, rate, bit_depth
+        format.gst_format(),
+        channels,
+        rate,
+        bit_depth
     );
     Ok(())
 }
 
+/// Creates a `video/smpte336m` data flow for [`DiscreteFormat::Klv`], or a
+/// `video/smpte291` one for ST 2038 and caption formats.
 pub(crate) fn init_state_with_data(
     state: &mut State,
     structure: &StructureRef,
     settings: &Settings,
-    element: &gst::Element,
+    element: Option<&gst::Element>,
+    format: DiscreteFormat,
 ) -> Result<(), gst::LoggableError> {
+    let (media_type, flow_name) = match format {
+        DiscreteFormat::Klv => (format::klv::KLV_MEDIA_TYPE, "KLV"),
+        DiscreteFormat::Cea708 | DiscreteFormat::Cea608 => {
+            (format::data::SMPTE291_MEDIA_TYPE, "Caption")
+        }
+        _ => (format::data::SMPTE291_MEDIA_TYPE, "Data"),
+    };
     let framerate = structure
         .get::<gst::Fraction>("framerate")
         .unwrap_or_else(|_| gst::Fraction::new(30000, 1001));
     let default_name = format!(
-        "MXL Data Flow, {} Hz",
+        "MXL {} Flow, {} Hz",
+        flow_name,
         format_framerate(framerate.numer(), framerate.denom())
     );
     let (label, description, tags) =
         resolve_flow_metadata(settings, default_name, default_group_hint("Data", element));
-    let flow_def_details = FlowDefData {
-        grain_rate: Rate {
-            numerator: framerate.numer(),
-            denominator: framerate.denom(),
-        },
-    };
-    let flow_def = FlowDef {
-        id: Uuid::parse_str(&settings.flow_id)
+    let flow_def = data_flow_def(
+        &settings.flow_id,
+        label,
+        description,
+        tags,
+        media_type,
+        framerate,
+    )?;
+    let (writer, flow) = create_grain_writer(&state.instance, &flow_def)?;
+    state.flow_state = Some(FlowState::Discrete(DiscreteState {
+        format,
+        writer: SharedGrainWriter::new(writer, open_options(settings)?, false),
+        last_index: None,
+        slate: None,
+        zero_copy: false,
+        captions: None,
+        timecodes: None,
+    }));
+    state.flow_config = Some(flow);
+
+    Ok(())
+}
+
+/// Creates the `video/smpte291` flow `flow_id` written next to a video flow, in
+/// its group with `media_role`. `None` when `flow_id` is empty.
+fn companion_data_flow(
+    instance: &MxlInstance,
+    settings: &Settings,
+    element: Option<&gst::Element>,
+    flow_id: &str,
+    media_role: &str,
+    media_type: &str,
+    framerate: gst::Fraction,
+) -> Result<Option<GrainWriter>, gst::LoggableError> {
+    if flow_id.is_empty() {
+        return Ok(None);
+    }
+    let name = format!(
+        "MXL {media_role} Flow, {} Hz",
+        format_framerate(framerate.numer(), framerate.denom())
+    );
+    let mut tags = HashMap::new();
+    tags.insert(
+        GROUPHINT_TAG.to_string(),
+        vec![companion_group_hint(settings, media_role, element)],
+    );
+    let flow_def = data_flow_def(flow_id, name.clone(), name, tags, media_type, framerate)?;
+    Ok(Some(create_grain_writer(instance, &flow_def)?.0))
+}
+
+/// Definition of data flow `flow_id` of `media_type`, at `framerate` grains per second.
+fn data_flow_def(
+    flow_id: &str,
+    label: String,
+    description: String,
+    tags: HashMap<String, Vec<String>>,
+    media_type: &str,
+    framerate: gst::Fraction,
+) -> Result<FlowDef, gst::LoggableError> {
+    Ok(FlowDef {
+        id: Uuid::parse_str(flow_id)
             .map_err(|e| gst::loggable_error!(CAT, "Flow ID is invalid: {}", e))?,
         description,
         tags,
         format: "urn:x-nmos:format:data".into(),
         label,
         parents: vec![],
-        media_type: "video/smpte291".into(),
-        details: FlowDefDetails::Data(flow_def_details),
-    };
-    let instance = &state.instance;
+        media_type: media_type.into(),
+        details: FlowDefDetails::Data(FlowDefData {
+            grain_rate: Rate {
+                numerator: framerate.numer(),
+                denominator: framerate.denom(),
+            },
+        }),
+    })
+}
 
+/// Creates the flow of `flow_def` and its grain writer.
+fn create_grain_writer(
+    instance: &MxlInstance,
+    flow_def: &FlowDef,
+) -> Result<(GrainWriter, FlowConfigInfo), gst::LoggableError> {
     let (flow_writer, flow, is_created) = instance
         .create_flow_writer(
-            serde_json::to_string(&flow_def)
+            serde_json::to_string(flow_def)
                 .map_err(|e| gst::loggable_error!(CAT, "Failed to convert: {}", e))?
                 .as_str(),
             None,
@@ -388,11 +648,5 @@ pub(crate) fn init_state_with_data(
     let writer = flow_writer
         .to_grain_writer()
         .map_err(|e| gst::loggable_error!(CAT, "Failed to create grain writer: {}", e))?;
-    state.flow_state = Some(FlowState::Discrete(DiscreteState {
-        format: DiscreteFormat::Data,
-        writer,
-    }));
-    state.flow_config = Some(flow);
-
-    Ok(())
+    Ok((writer, flow))
 }
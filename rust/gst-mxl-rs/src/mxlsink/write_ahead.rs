@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Enforcement of the `max-write-ahead` property.
+//!
+//! A producer that runs far ahead of real time (`sync=false`, a file source, a
+//! bursting encoder) commits grains or samples for indices readers will only
+//! reach much later. Because the flow is a ring, writing too far ahead
+//! overwrites data that readers are still consuming. The limit caps how far
+//! beyond the current MXL index a commit may land.
+
+use std::cell::Cell;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+use mxl::{MxlInstance, Rational};
+use tracing::trace;
+
+pub(crate) const DEFAULT_MAX_WRITE_AHEAD: u64 = 0;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstMxlSinkWriteAheadPolicy")]
+pub enum WriteAheadPolicy {
+    #[default]
+    #[enum_value(
+        name = "Block: wait on the pipeline clock until the write is within the limit",
+        nick = "block"
+    )]
+    Block = 0,
+    #[enum_value(name = "Drop: discard writes beyond the limit", nick = "drop")]
+    Drop = 1,
+}
+
+/// Pending clock wait, so that `unlock()` can interrupt a blocked render.
+#[derive(Default)]
+pub(crate) struct ClockWait {
+    pub clock_id: Option<gst::SingleShotClockId>,
+    pub flushing: bool,
+}
+
+/// What to do with a write after checking it against the limit.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Admission {
+    Write,
+    Drop,
+}
+
+/// How many indices past the limit `index` lands, or `None` if it is within
+/// the limit. `max_ahead == 0` disables the limit.
+pub(crate) fn excess_write_ahead(index: u64, current_index: u64, max_ahead: u64) -> Option<u64> {
+    if max_ahead == 0 {
+        return None;
+    }
+    let ahead = index.saturating_sub(current_index);
+    (ahead > max_ahead).then(|| ahead - max_ahead)
+}
+
+pub(crate) struct WriteAheadLimit<'a> {
+    pub max_ahead: u64,
+    pub policy: WriteAheadPolicy,
+    pub clock_wait: &'a Mutex<ClockWait>,
+    /// Element whose clock blocked writes wait on. Without one (or without a
+    /// clock) the wait falls back to the MXL instance's sleep.
+    pub element: Option<&'a gst::Element>,
+    /// Time writes spent blocked on the limit, which is not commit time.
+    pub blocked: Cell<Duration>,
+}
+
+impl WriteAheadLimit<'_> {
+    /// Checks a write landing on `index` (the last index it touches) against
+    /// the limit, blocking on the pipeline clock under the `block` policy.
+    pub(crate) fn admit(
+        &self,
+        instance: &MxlInstance,
+        index: u64,
+        rate: &Rational,
+    ) -> Result<Admission, gst::FlowError> {
+        let current_index = instance.get_current_index(rate);
+        if excess_write_ahead(index, current_index, self.max_ahead).is_none() {
+            return Ok(Admission::Write);
+        }
+        if self.policy == WriteAheadPolicy::Drop {
+            trace!(
+                "Dropping write to index {index}: more than {} ahead of {current_index}",
+                self.max_ahead
+            );
+            return Ok(Admission::Drop);
+        }
+
+        // Wait until `index - max_ahead` becomes the current index.
+        let wait = instance
+            .get_duration_until_index(index - self.max_ahead, rate)
+            .map_err(|_| gst::FlowError::Error)?;
+        trace!("Write to index {index} is ahead of {current_index}, blocking for {wait:?}");
+        self.blocked.set(self.blocked.get() + wait);
+        let Some(clock) = self.element.and_then(|element| element.clock()) else {
+            instance.sleep_for(wait);
+            return Ok(Admission::Write);
+        };
+        let clock_id = clock.new_single_shot_id(
+            clock.time() + gst::ClockTime::from_nseconds(wait.as_nanos() as u64),
+        );
+        {
+            let mut clock_wait = self.clock_wait.lock().map_err(|_| gst::FlowError::Error)?;
+            if clock_wait.flushing {
+                return Err(gst::FlowError::Flushing);
+            }
+            clock_wait.clock_id = Some(clock_id.clone());
+        }
+        let (result, _jitter) = clock_id.wait();
+        if let Ok(mut clock_wait) = self.clock_wait.lock() {
+            clock_wait.clock_id = None;
+        }
+        match result {
+            Ok(_) | Err(gst::ClockError::Early) => Ok(Admission::Write),
+            Err(gst::ClockError::Unscheduled) => Err(gst::FlowError::Flushing),
+            Err(_) => Err(gst::FlowError::Error),
+        }
+    }
+}
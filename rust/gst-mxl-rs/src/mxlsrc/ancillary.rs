@@ -0,0 +1,203 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `GstAncillaryMeta`, `GstVideoCaptionMeta` or `GstVideoTimeCodeMeta` on video buffers from a
+//! companion `video/smpte291` data flow.
+//!
+//! With `ancillary-flow-id` set next to `video-flow-id`, each video buffer carries the ANC packets
+//! of the data grain of the same TAI time, so captions and triggers travel with the frames as
+//! they would from a capture card. This saves `st2038combiner` and a second `mxlsrc` in
+//! pipelines that only pass the ancillary data through. With `caption-flow-id` it carries the
+//! CEA-708 and CEA-608 packets of the grain as caption metas instead, as `mxlsink` writes them
+//! from the caption metas of its video buffers. With `timecode-flow-id` it carries the timecode
+//! of the grain's ancillary time code packet, and with `timecode` buffers without one get the
+//! timecode of their grain index, so timecode survives a hop through MXL either way.
+
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use mxl::{GrainReader, MxlInstance, Rational, Timecode};
+use tracing::trace;
+
+use crate::format::captions::{Caption, captions_from_mxl_smpte291_grain};
+use crate::format::data::{AncillaryMeta, ancillary_metas_from_mxl_smpte291_grain};
+use crate::format::timecode::{index_timecode, timecode_from_mxl_smpte291_grain};
+
+/// How long to wait for a data grain the producer has not committed yet. Data flows are sparse,
+/// so a grain that has not shown up by then is assumed to be missing.
+const ANCILLARY_GRAIN_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// Meta the packets of a data grain are attached as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachedMeta {
+    /// A `GstAncillaryMeta` per ANC packet, for `ancillary-flow-id`.
+    Ancillary,
+    /// `GstVideoCaptionMeta` for the caption packets, for `caption-flow-id`.
+    Captions,
+    /// A `GstVideoTimeCodeMeta` for the ancillary time code packet, for `timecode-flow-id`.
+    Timecode,
+}
+
+pub struct AncillaryReader {
+    pub grain_reader: GrainReader,
+    pub grain_rate: Rational,
+    pub meta: AttachedMeta,
+}
+
+impl AncillaryReader {
+    /// Adds the packets of the data grain of the same time as video grain `video_index` to
+    /// `buffer`. Nothing is added when there is no such grain.
+    pub(crate) fn attach(
+        &self,
+        instance: &MxlInstance,
+        buffer: &mut gst::BufferRef,
+        video_index: u64,
+        video_rate: &Rational,
+    ) {
+        let index = match instance
+            .index_to_timestamp(video_index, video_rate)
+            .and_then(|timestamp| instance.timestamp_to_index(timestamp, &self.grain_rate))
+        {
+            Ok(index) => index,
+            Err(err) => {
+                trace!("No data grain index for video grain {video_index}: {err}");
+                return;
+            }
+        };
+        let grain = match self
+            .grain_reader
+            .get_complete_grain(index, ANCILLARY_GRAIN_TIMEOUT)
+        {
+            // An older grain in the slot means the producer skipped this index.
+            Ok(grain) if grain.index == index => grain,
+            Ok(grain) => {
+                trace!("Slot for data grain {index} holds {}", grain.index);
+                return;
+            }
+            Err(err) => {
+                trace!("No data grain {index}: {err}");
+                return;
+            }
+        };
+        match self.meta {
+            AttachedMeta::Ancillary => match ancillary_metas_from_mxl_smpte291_grain(grain.payload)
+            {
+                Ok(metas) => {
+                    trace!(
+                        "Attaching {} ANC packets of data grain {index}",
+                        metas.len()
+                    );
+                    for meta in &metas {
+                        add_ancillary_meta(buffer, meta);
+                    }
+                }
+                Err(err) => trace!("Invalid data grain {index}: {err}"),
+            },
+            AttachedMeta::Captions => match captions_from_mxl_smpte291_grain(grain.payload) {
+                Ok(captions) => {
+                    trace!(
+                        "Attaching {} captions of data grain {index}",
+                        captions.len()
+                    );
+                    for caption in &captions {
+                        add_caption_meta(buffer, caption);
+                    }
+                }
+                Err(err) => trace!("Invalid data grain {index}: {err}"),
+            },
+            AttachedMeta::Timecode => {
+                match timecode_from_mxl_smpte291_grain(grain.payload, video_rate) {
+                    Ok(Some(timecode)) => {
+                        trace!("Attaching timecode {timecode} of data grain {index}");
+                        add_timecode_meta(buffer, &timecode, video_rate);
+                    }
+                    Ok(None) => trace!("No timecode in data grain {index}"),
+                    Err(err) => trace!("Invalid data grain {index}: {err}"),
+                }
+            }
+        }
+    }
+}
+
+/// Adds the timecode of video grain `video_index` to `buffer`, unless a timecode flow gave it one.
+pub(crate) fn add_index_timecode(buffer: &mut gst::BufferRef, video_index: u64, rate: &Rational) {
+    if buffer
+        .meta::<gst_video::video_meta::VideoTimeCodeMeta>()
+        .is_some()
+    {
+        return;
+    }
+    match index_timecode(video_index, rate) {
+        Ok(timecode) => add_timecode_meta(buffer, &timecode, rate),
+        Err(err) => trace!("No timecode for video grain {video_index}: {err}"),
+    }
+}
+
+fn add_timecode_meta(buffer: &mut gst::BufferRef, timecode: &Timecode, rate: &Rational) {
+    let flags = if timecode.drop_frame {
+        gst_video::VideoTimeCodeFlags::DROP_FRAME
+    } else {
+        gst_video::VideoTimeCodeFlags::empty()
+    };
+    let tc = gst_video::VideoTimeCode::new(
+        gst::Fraction::new(rate.numerator as i32, rate.denominator as i32),
+        None,
+        flags,
+        timecode.hours.into(),
+        timecode.minutes.into(),
+        timecode.seconds.into(),
+        timecode.frames.into(),
+        0,
+    );
+    match gst_video::ValidVideoTimeCode::try_from(tc) {
+        Ok(tc) => {
+            gst_video::video_meta::VideoTimeCodeMeta::add(buffer, &tc);
+        }
+        Err(_) => trace!("Timecode {timecode} is not valid at the video rate"),
+    }
+}
+
+fn add_caption_meta(buffer: &mut gst::BufferRef, caption: &Caption) {
+    let (caption_type, data) = match caption {
+        Caption::Cea708Cdp(cdp) => (gst_video::VideoCaptionType::Cea708Cdp, cdp),
+        Caption::Cea608S3341a(s334_1a) => (gst_video::VideoCaptionType::Cea608S3341a, s334_1a),
+    };
+    gst_video::video_meta::VideoCaptionMeta::add(buffer, caption_type, data);
+}
+
+fn add_ancillary_meta(buffer: &mut gst::BufferRef, packet: &AncillaryMeta) {
+    let mut meta = gst_video::video_meta::AncillaryMeta::add(buffer);
+    meta.set_c_not_y_channel(packet.c_not_y_channel);
+    meta.set_line(packet.line);
+    meta.set_offset(packet.offset);
+    meta.set_did(packet.did);
+    meta.set_sdid_block_number(packet.sdid_block_number);
+    meta.set_data(gst::glib::Slice::from(packet.data.clone()));
+    // After the data, which sets the count without its parity bits.
+    meta.set_data_count(packet.data_count);
+    meta.set_checksum(packet.checksum);
+}
+
+/// Removes the `GstAncillaryMeta`, `GstVideoCaptionMeta` and `GstVideoTimeCodeMeta` of `buffer`,
+/// so a buffer standing in for a missed grain does not repeat the captions or timecode of the
+/// grain it copies.
+pub(crate) fn remove_ancillary_metas(buffer: &mut gst::BufferRef) {
+    buffer.foreach_meta_mut(|meta| {
+        if meta
+            .downcast_ref::<gst_video::video_meta::AncillaryMeta>()
+            .is_some()
+            || meta
+                .downcast_ref::<gst_video::video_meta::VideoCaptionMeta>()
+                .is_some()
+            || meta
+                .downcast_ref::<gst_video::video_meta::VideoTimeCodeMeta>()
+                .is_some()
+        {
+            ControlFlow::Continue(gst::BufferMetaForeachAction::Remove)
+        } else {
+            ControlFlow::Continue(gst::BufferMetaForeachAction::Keep)
+        }
+    });
+}
@@ -0,0 +1,143 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Handling of the `catch-up-mode` property.
+//!
+//! A reader that falls behind the writer by more than the ring can only resume
+//! at data still in the ring. By default an audio reader then jumps close to
+//! the head, skipping samples that could still be read, so it does not fall
+//! behind again right away. Analysis pipelines may rather get all the data
+//! that is left, late, and monitoring ones only ever the newest.
+
+use gst::glib;
+use gstreamer as gst;
+
+use crate::mxlsrc::timing::{ReadStep, resolve_read_step};
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstMxlSrcCatchUpMode")]
+pub enum CatchUpMode {
+    #[default]
+    #[enum_value(
+        name = "Auto: once lapped, resume at the oldest grain or close to the newest samples",
+        nick = "auto"
+    )]
+    Auto = 0,
+    #[enum_value(
+        name = "Never: once lapped, resume at the oldest grain or samples still in the ring",
+        nick = "never"
+    )]
+    Never = 1,
+    #[enum_value(
+        name = "Always latest: skip to the newest grain or samples whenever behind",
+        nick = "always-latest"
+    )]
+    AlwaysLatest = 2,
+}
+
+/// Next grain to read on a discrete flow, like [`resolve_read_step`], but with
+/// [`CatchUpMode::AlwaysLatest`] skipping to `lag` grains behind `head` with
+/// DISCONT whenever the reader is further behind. `lag` must be within the ring.
+pub(crate) fn discrete_read_step(
+    mode: CatchUpMode,
+    index: u64,
+    head: u64,
+    grain_count: u32,
+    lag: u64,
+) -> ReadStep {
+    match (mode, resolve_read_step(index, head, grain_count)) {
+        (CatchUpMode::AlwaysLatest, ReadStep::Read { index, .. })
+            if index < head.saturating_sub(lag) =>
+        {
+            ReadStep::Read {
+                index: head.saturating_sub(lag),
+                discont: true,
+            }
+        }
+        (_, step) => step,
+    }
+}
+
+/// Sample index an audio reader at `index` skips to, `None` to read on. The
+/// reader is lapped below `oldest_valid`, and `latest` is where a reader close to
+/// the head of the flow reads.
+pub(crate) fn continuous_catch_up(
+    mode: CatchUpMode,
+    index: u64,
+    oldest_valid: u64,
+    latest: u64,
+) -> Option<u64> {
+    match mode {
+        CatchUpMode::Auto => (index < oldest_valid).then_some(latest),
+        CatchUpMode::Never => (index < oldest_valid).then_some(oldest_valid),
+        CatchUpMode::AlwaysLatest => (index < latest).then_some(latest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAIN_COUNT: u32 = 300;
+    const HEAD: u64 = 53_429_298_000;
+
+    #[test]
+    fn only_always_latest_skips_readable_grains() {
+        for mode in [CatchUpMode::Auto, CatchUpMode::Never] {
+            assert_eq!(
+                discrete_read_step(mode, HEAD - 10, HEAD, GRAIN_COUNT, 0),
+                ReadStep::Read {
+                    index: HEAD - 10,
+                    discont: false,
+                }
+            );
+        }
+        assert_eq!(
+            discrete_read_step(CatchUpMode::AlwaysLatest, HEAD - 10, HEAD, GRAIN_COUNT, 2),
+            ReadStep::Read {
+                index: HEAD - 2,
+                discont: true,
+            }
+        );
+        assert_eq!(
+            discrete_read_step(CatchUpMode::AlwaysLatest, HEAD - 2, HEAD, GRAIN_COUNT, 2),
+            ReadStep::Read {
+                index: HEAD - 2,
+                discont: false,
+            }
+        );
+        assert_eq!(
+            discrete_read_step(CatchUpMode::AlwaysLatest, HEAD + 1, HEAD, GRAIN_COUNT, 0),
+            ReadStep::WaitForProducer
+        );
+    }
+
+    #[test]
+    fn audio_catch_up_target_follows_the_mode() {
+        let (oldest_valid, latest) = (1_000, 5_000);
+        assert_eq!(
+            continuous_catch_up(CatchUpMode::Auto, 900, oldest_valid, latest),
+            Some(latest)
+        );
+        assert_eq!(
+            continuous_catch_up(CatchUpMode::Never, 900, oldest_valid, latest),
+            Some(oldest_valid)
+        );
+        assert_eq!(
+            continuous_catch_up(CatchUpMode::AlwaysLatest, 900, oldest_valid, latest),
+            Some(latest)
+        );
+        for mode in [CatchUpMode::Auto, CatchUpMode::Never] {
+            assert_eq!(continuous_catch_up(mode, 2_000, oldest_valid, latest), None);
+        }
+        assert_eq!(
+            continuous_catch_up(CatchUpMode::AlwaysLatest, 2_000, oldest_valid, latest),
+            Some(latest)
+        );
+        assert_eq!(
+            continuous_catch_up(CatchUpMode::AlwaysLatest, latest, oldest_valid, latest),
+            None
+        );
+    }
+}
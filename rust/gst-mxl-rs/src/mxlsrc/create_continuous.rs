@@ -3,23 +3,27 @@
 
 use std::time::{Duration, Instant};
 
-use crate::mxlsrc::imp::{CreateState, MxlSrc};
-use crate::mxlsrc::mxl_helper::pts_subtrahend;
-use crate::mxlsrc::state::{ContinuousState, FlowState, State};
-use crate::mxlsrc::timing::pts_for_index;
+use crate::mxlsrc::catch_up::{CatchUpMode, continuous_catch_up};
+use crate::mxlsrc::imp::{CAT, CreateState, MxlSrc};
+use crate::mxlsrc::mxl_helper::{ensure_domain_available, pts_subtrahend};
+use crate::mxlsrc::state::{BatchHints, ContinuousState, FlowState, State};
+use crate::mxlsrc::timing::{pts_for_index, ring_latency, set_tai_reference};
+use gst::prelude::*;
+use gst::subclass::prelude::*;
 use gst::{Buffer, ClockTime};
 use gstreamer as gst;
-use mxl::{FlowInfo, SamplesData};
+use mxl::{FlowInfo, InterleaveOptions, Rational, RationalExt};
 use tracing::trace;
 
-const GET_SAMPLE_TIMEOUT: Duration = Duration::from_secs(2);
-const PRODUCER_TIMEOUT: Duration = Duration::from_millis(100);
 const DEFAULT_BATCH_SIZE: u32 = 48;
 
 pub(crate) fn create_continuous(
     src: &MxlSrc,
     state: &mut State,
     offset: u64,
+    locked_batch_size: u32,
+    latency_time: u32,
+    catch_up_mode: CatchUpMode,
 ) -> Result<CreateState, gst::FlowError> {
     let subtrahend = pts_subtrahend(src, offset)?;
     let continuous_state = match state.flow_state.as_mut() {
@@ -27,10 +31,13 @@ pub(crate) fn create_continuous(
         _ => return Err(gst::FlowError::Error),
     };
 
-    let reader_info = continuous_state
-        .reader
-        .get_info()
-        .map_err(|_| gst::FlowError::Error)?;
+    let reader_info = match continuous_state.reader.get_info() {
+        Ok(info) => info,
+        Err(_) => {
+            ensure_domain_available(src, &state.domain)?;
+            return Err(gst::FlowError::Error);
+        }
+    };
 
     let continuous_flow_info = reader_info
         .config
@@ -43,18 +50,35 @@ pub(crate) fn create_continuous(
         .sample_rate()
         .map_err(|_| gst::FlowError::Error)?;
 
-    let batch_size = DEFAULT_BATCH_SIZE
-        .min(continuous_flow_info.bufferLength / 2)
-        .min(reader_info.config.common().max_commit_batch_size_hint());
+    let common = reader_info.config.common();
+    let hints = BatchHints {
+        commit: common.max_commit_batch_size_hint(),
+        sync: common.max_sync_batch_size_hint(),
+    };
+    watch_batch_hints(src, continuous_state, hints, locked_batch_size);
+
+    let batch_size = batch_size(
+        requested_batch_size(locked_batch_size, latency_time, &sample_rate, hints.commit),
+        continuous_flow_info.bufferLength,
+        hints.commit,
+    );
     let ring = continuous_flow_info.bufferLength as u64;
     let batch = batch_size as u64;
+    let read_offset = continuous_read_offset(continuous_state.read_offset, ring, batch);
 
-    continuous_state_init(batch, &reader_info, continuous_state);
+    continuous_state_init(batch + read_offset, &reader_info, continuous_state);
 
     let head = reader_info.runtime.head_index();
     wait_for_sample(head, batch, continuous_state)?;
 
-    if is_reader_late(head, batch, ring, continuous_state)? {
+    if is_reader_late(
+        head,
+        batch,
+        read_offset,
+        ring,
+        catch_up_mode,
+        continuous_state,
+    )? {
         resync_state(continuous_state);
     }
 
@@ -63,20 +87,27 @@ pub(crate) fn create_continuous(
     // sample we want, so pass `index + batch` as the end; `pts_for_index(index)`
     // then stamps that first returned sample.
     let read_once = |idx: u64| {
-        continuous_state
-            .samples_reader
-            .get_samples(idx + batch, batch as usize, GET_SAMPLE_TIMEOUT)
+        continuous_state.samples_reader.get_samples_with_options(
+            idx + batch,
+            batch as usize,
+            continuous_state.timeouts.sample,
+            &continuous_state.open_options,
+        )
     };
 
     let samples = match read_once(continuous_state.index) {
         Ok(s) => s,
+        Err(mxl::Error::FlowInvalid) => return Ok(CreateState::FlowInvalid),
         Err(_) => {
+            ensure_domain_available(src, &state.domain)?;
             return Ok(CreateState::NoDataCreated);
         }
     };
 
     // MXL stores each channel separately; GStreamer expects interleaved audio.
-    let interleaved = interleave_audio(&samples)?;
+    let interleaved = samples
+        .interleave(&InterleaveOptions::new().format(continuous_state.format.interleaved_format()))
+        .map_err(|_| gst::FlowError::Error)?;
 
     let Some(pts) = pts_for_index(
         &state.instance,
@@ -99,7 +130,13 @@ pub(crate) fn create_continuous(
 
     let is_discont = std::mem::take(&mut continuous_state.next_discont);
 
-    let buffer = build_buffer(pts, samples, is_discont, interleaved)?;
+    let mut buffer = build_buffer(pts, is_discont, interleaved)?;
+    set_tai_reference(
+        &state.instance,
+        buffer.get_mut().ok_or(gst::FlowError::Error)?,
+        continuous_state.index..continuous_state.index + batch,
+        &sample_rate,
+    )?;
 
     continuous_state.index += batch;
 
@@ -112,6 +149,112 @@ pub(crate) fn create_continuous(
     Ok(CreateState::DataCreated(buffer))
 }
 
+/// Samples per buffer `batch-size` and `latency-time` ask for, `0` for neither: `batch-size` if
+/// set, else `latency_time` microseconds of samples at `sample_rate`, rounded up to whole producer
+/// commits of `commit_hint` samples so that a read does not wait for part of a commit.
+pub(crate) fn requested_batch_size(
+    locked_batch_size: u32,
+    latency_time: u32,
+    sample_rate: &Rational,
+    commit_hint: u32,
+) -> u32 {
+    if locked_batch_size != 0 || latency_time == 0 {
+        return locked_batch_size;
+    }
+    let samples = sample_rate
+        .count_in(Duration::from_micros(latency_time.into()))
+        .max(1);
+    let samples = match u64::from(commit_hint) {
+        0 => samples,
+        commit_hint => samples.div_ceil(commit_hint) * commit_hint,
+    };
+    u32::try_from(samples).unwrap_or(u32::MAX)
+}
+
+/// Samples per buffer: the requested size if set, else the producer's commit batch size hint
+/// capped at [`DEFAULT_BATCH_SIZE`]. Either way at most half the ring, so a batch can be read while
+/// the producer writes the other half.
+pub(crate) fn batch_size(locked: u32, buffer_length: u32, commit_hint: u32) -> u32 {
+    let batch_size = match locked {
+        0 => DEFAULT_BATCH_SIZE.min(commit_hint),
+        locked => locked,
+    };
+    batch_size.min(buffer_length / 2).max(1)
+}
+
+/// Min and max live latency of the continuous flow of `reader_info` read with `batch-size`
+/// `locked_batch_size` and `latency-time` `latency_time`, `read_offset` samples behind the head. A batch is pushed once its last sample is committed, so its first sample is a batch
+/// and the offset old, and the reader is late once that sample is more than the ring minus a
+/// batch old.
+pub(crate) fn continuous_latency(
+    reader_info: &FlowInfo,
+    locked_batch_size: u32,
+    latency_time: u32,
+    read_offset: u32,
+) -> Option<(ClockTime, ClockTime)> {
+    let continuous_flow_info = reader_info.config.continuous().ok()?;
+    let common = reader_info.config.common();
+    let sample_rate = common.sample_rate().ok()?;
+    let ring = continuous_flow_info.bufferLength;
+    let commit_hint = common.max_commit_batch_size_hint();
+    let batch = batch_size(
+        requested_batch_size(locked_batch_size, latency_time, &sample_rate, commit_hint),
+        ring,
+        commit_hint,
+    );
+    let read_offset = continuous_read_offset(read_offset, ring.into(), batch.into());
+    Some(ring_latency(
+        u64::from(batch) + read_offset,
+        ring.saturating_sub(batch).into(),
+        &sample_rate,
+    ))
+}
+
+/// Samples behind the head a reader of `batch` samples attaches and catches up at, on top of a
+/// batch, for a `read-offset` of `read_offset`. At most the ring minus three batches, so a
+/// catch-up, two batches and the offset behind the head, still lands within the ring.
+pub(crate) fn continuous_read_offset(read_offset: u32, ring: u64, batch: u64) -> u64 {
+    u64::from(read_offset).min(ring.saturating_sub(batch.saturating_mul(3)))
+}
+
+/// Logs and posts an info message when the producer changes its batch size hints, which the
+/// source follows unless `batch-size` locks its batch size, and then a latency message.
+fn watch_batch_hints(
+    src: &MxlSrc,
+    continuous_state: &mut ContinuousState,
+    hints: BatchHints,
+    locked_batch_size: u32,
+) {
+    let Some(previous) = continuous_state.batch_hints.replace(hints) else {
+        return;
+    };
+    if previous == hints {
+        return;
+    }
+
+    let text = format!(
+        "Producer batch size hints changed from commit {} / sync {} to commit {} / sync {} samples",
+        previous.commit, previous.sync, hints.commit, hints.sync
+    );
+    gst::info!(CAT, imp = src, "{text}");
+    let details = gst::Structure::builder("mxl-batch-hints")
+        .field("commit-batch-size", hints.commit)
+        .field("sync-batch-size", hints.sync)
+        .field("locked", locked_batch_size != 0)
+        .build();
+    let message = gst::message::Info::builder(gst::CoreError::Negotiation, &text)
+        .src(&*src.obj())
+        .details(details)
+        .build();
+    let _ = src.obj().post_message(message);
+    if locked_batch_size == 0 {
+        // The batch size, hence the latency, follows the commit hint.
+        let _ = src
+            .obj()
+            .post_message(gst::message::Latency::builder().src(&*src.obj()).build());
+    }
+}
+
 fn continuous_state_init(
     batch: u64,
     reader_info: &FlowInfo,
@@ -134,7 +277,7 @@ fn wait_for_sample(
 ) -> Result<(), gst::FlowError> {
     let start = Instant::now();
     while continuous_state.index + batch > head {
-        if start.elapsed() > PRODUCER_TIMEOUT {
+        if start.elapsed() > continuous_state.timeouts.producer {
             return Ok(());
         }
         head = wait_for_producer(head, batch, continuous_state)?;
@@ -163,27 +306,26 @@ fn wait_for_producer(
 fn is_reader_late(
     head: u64,
     batch: u64,
+    read_offset: u64,
     ring: u64,
+    catch_up_mode: CatchUpMode,
     continuous_state: &mut ContinuousState,
 ) -> Result<bool, gst::FlowError> {
     let oldest_valid = head.saturating_sub(ring.saturating_sub(batch));
-    if continuous_state.index < oldest_valid {
-        catch_up(head, batch, continuous_state, oldest_valid);
-        Ok(true)
-    } else {
-        Ok(false)
+    let latest = define_cushion(head, batch).saturating_sub(read_offset);
+    match continuous_catch_up(catch_up_mode, continuous_state.index, oldest_valid, latest) {
+        Some(target) => {
+            trace!(
+                "CATCH-UP (pre-read): index {} (oldest {}). Jumping -> {}, head={}",
+                continuous_state.index, oldest_valid, target, head
+            );
+            continuous_state.index = target;
+            Ok(true)
+        }
+        None => Ok(false),
     }
 }
 
-fn catch_up(head: u64, batch: u64, continuous_state: &mut ContinuousState, oldest_valid: u64) {
-    let target = define_cushion(head, batch);
-    continuous_state.index = target;
-    trace!(
-        "CATCH-UP (pre-read): index {} < oldest {}. Jumping -> {}, head={}",
-        continuous_state.index, oldest_valid, target, head
-    );
-}
-
 fn define_cushion(head: u64, batch: u64) -> u64 {
     //Jump to (head - 2 × batch) to give reader headroom in case the producer has already advanced to avoid being immediately late again
     let cushion = batch.saturating_mul(2);
@@ -191,19 +333,14 @@ fn define_cushion(head: u64, batch: u64) -> u64 {
     head.saturating_sub(cushion)
 }
 
+/// The interleaved samples, whose size differs from the flow's for `S24_32LE`.
 fn build_buffer(
     pts: ClockTime,
-    samples: SamplesData<'_>,
     next_discont: bool,
     interleaved: Vec<u8>,
 ) -> Result<Buffer, gst::FlowError> {
-    let mut buf_size = 0;
-    for i in 0..samples.num_of_channels() {
-        let (a, b) = samples.channel_data(i).map_err(|_| gst::FlowError::Error)?;
-        buf_size += a.len() + b.len();
-    }
-
-    let mut buffer = gst::Buffer::with_size(buf_size).map_err(|_| gst::FlowError::Error)?;
+    let mut buffer =
+        gst::Buffer::with_size(interleaved.len()).map_err(|_| gst::FlowError::Error)?;
 
     {
         let buffer = buffer.get_mut().ok_or(gst::FlowError::Error)?;
@@ -219,35 +356,9 @@ fn build_buffer(
     Ok(buffer)
 }
 
-fn interleave_audio(samples: &SamplesData<'_>) -> Result<Vec<u8>, gst::FlowError> {
-    let num_channels = samples.num_of_channels();
-    let mut channels: Vec<Vec<u8>> = Vec::with_capacity(num_channels);
-    let mut total_samples_per_channel = 0;
-
-    for ch in 0..num_channels {
-        let (data1, data2) = samples
-            .channel_data(ch)
-            .map_err(|_| gst::FlowError::Error)?;
-        let mut combined = Vec::with_capacity(data1.len() + data2.len());
-        combined.extend_from_slice(data1);
-        combined.extend_from_slice(data2);
-        total_samples_per_channel = combined.len() / std::mem::size_of::<f32>();
-        channels.push(combined);
-    }
-    let mut interleaved =
-        Vec::with_capacity(total_samples_per_channel * num_channels * std::mem::size_of::<f32>());
-    for frame in 0..total_samples_per_channel {
-        for chan in &channels {
-            let offset = frame * std::mem::size_of::<f32>();
-            interleaved.extend_from_slice(&chan[offset..offset + std::mem::size_of::<f32>()]);
-        }
-    }
-    Ok(interleaved)
-}
-
 #[cfg(test)]
 mod ring_tests {
-    use super::define_cushion;
+    use super::{continuous_read_offset, define_cushion};
 
     #[test]
     fn define_cushion_leaves_two_batches_of_headroom() {
@@ -255,6 +366,14 @@ mod ring_tests {
         assert_eq!(define_cushion(10, 48), 0);
     }
 
+    #[test]
+    fn read_offset_leaves_room_for_a_catch_up() {
+        assert_eq!(continuous_read_offset(0, 4_800, 48), 0);
+        assert_eq!(continuous_read_offset(480, 4_800, 48), 480);
+        assert_eq!(continuous_read_offset(10_000, 4_800, 48), 4_800 - 144);
+        assert_eq!(continuous_read_offset(10, 96, 48), 0);
+    }
+
     #[test]
     fn oldest_valid_sample_index_formula() {
         let ring = 480u64;
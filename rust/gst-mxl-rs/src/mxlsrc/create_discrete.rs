@@ -4,35 +4,56 @@
 use std::time::Duration;
 
 use crate::format;
+use crate::mxlsrc::ancillary::add_index_timecode;
+use crate::mxlsrc::catch_up::{CatchUpMode, discrete_read_step};
+use crate::mxlsrc::gaps::{GapMode, missed_grains, repeated_grain};
 use crate::mxlsrc::imp::{CreateState, MxlSrc};
-use crate::mxlsrc::mxl_helper::pts_subtrahend;
+use crate::mxlsrc::mxl_helper::{ensure_domain_available, pts_subtrahend};
 use crate::mxlsrc::state::{DiscreteFormat, FlowState, State};
 use crate::mxlsrc::timing::{
-    ReadStep, discrete_grain_count, flow_head_index, index_period, pts_for_index, resolve_read_step,
+    ReadStep, discrete_grain_count, discrete_read_offset, flow_head_index, index_period,
+    pts_for_index, set_tai_reference,
 };
+use crate::mxlsrc::zero_copy::wrap_grain;
 use gstreamer as gst;
 use tracing::trace;
 
-const GET_GRAIN_TIMEOUT: Duration = Duration::from_secs(5);
 pub(super) const MXL_GRAIN_FLAG_INVALID: u32 = 0x00000001;
 
 pub(crate) fn create_discrete(
     src: &MxlSrc,
     state: &mut State,
     offset: u64,
+    min_slices: u32,
+    gap_mode: GapMode,
+    catch_up_mode: CatchUpMode,
+    zero_copy: bool,
 ) -> Result<CreateState, gst::FlowError> {
     let subtrahend = pts_subtrahend(src, offset)?;
     let instance = &state.instance;
+    let domain = state.domain.as_str();
     let discrete_state = match state.flow_state.as_mut() {
         Some(FlowState::Discrete(discrete)) => discrete,
         _ => return Err(gst::FlowError::Error),
     };
     let rate = discrete_state.grain_rate;
-    let head = flow_head_index(&discrete_state.grain_reader)?;
+    let head = match flow_head_index(&discrete_state.grain_reader) {
+        Ok(head) => head,
+        Err(err) => {
+            ensure_domain_available(src, domain)?;
+            return Err(err);
+        }
+    };
     let grain_count = discrete_grain_count(&discrete_state.grain_reader)?;
     let media = match discrete_state.format {
         DiscreteFormat::Video => "video",
         DiscreteFormat::Data => "data",
+        DiscreteFormat::Klv => "klv",
+    };
+    // Only video has a grain for every index; data and KLV are sparse.
+    let gap_mode = match discrete_state.format {
+        DiscreteFormat::Video => gap_mode,
+        DiscreteFormat::Data | DiscreteFormat::Klv => GapMode::Skip,
     };
 
     if !discrete_state.is_initialized {
@@ -40,27 +61,67 @@ pub(crate) fn create_discrete(
             // Flow exists but the producer has not committed a grain yet.
             return Ok(CreateState::NoDataCreated);
         }
-        // Attach live at the newest committed grain.
-        discrete_state.index = head;
+        // Attach live at the newest committed grain, or `read-offset` grains behind it.
+        discrete_state.index = head.saturating_sub(discrete_read_offset(
+            discrete_state.read_offset,
+            grain_count,
+        ));
         discrete_state.is_initialized = true;
     }
 
-    let (read_index, jumped) = match resolve_read_step(discrete_state.index, head, grain_count) {
-        ReadStep::WaitForProducer => return Ok(CreateState::NoDataCreated),
+    let read_step = discrete_read_step(
+        catch_up_mode,
+        discrete_state.index,
+        head,
+        grain_count,
+        discrete_read_offset(discrete_state.read_offset, grain_count),
+    );
+    let (read_index, jumped) = match read_step {
+        ReadStep::WaitForProducer => {
+            // The head of a flow whose writer went away stops moving, so ask MXL whether the
+            // flow is still the one the reader opened: a read that does not wait tells.
+            return match discrete_state.grain_reader.get_complete_grain_with_options(
+                discrete_state.index,
+                Duration::ZERO,
+                &discrete_state.open_options,
+            ) {
+                Err(mxl::Error::FlowInvalid) => Ok(CreateState::FlowInvalid),
+                _ => Ok(CreateState::NoDataCreated),
+            };
+        }
         ReadStep::Read { index, discont } => (index, discont),
     };
     if jumped {
         trace!("Fell behind ring: jumped to oldest retained grain {read_index} (head={head})");
     }
 
+    // Partial grains only make sense for video: a data grain must be complete
+    // to be converted to ST 2038 or KLV.
+    let min_slices = match discrete_state.format {
+        DiscreteFormat::Video => partial_grain_min_slices(min_slices),
+        DiscreteFormat::Data | DiscreteFormat::Klv => None,
+    };
+
     trace!("Getting {media} grain with index: {read_index}");
-    let grain_data = match discrete_state
-        .grain_reader
-        .get_complete_grain(read_index, GET_GRAIN_TIMEOUT)
-    {
+    let grain_result = match min_slices {
+        Some(min_slices) => discrete_state.grain_reader.get_grain_slice_with_options(
+            read_index,
+            min_slices,
+            discrete_state.timeouts.grain,
+            &discrete_state.open_options,
+        ),
+        None => discrete_state.grain_reader.get_complete_grain_with_options(
+            read_index,
+            discrete_state.timeouts.grain,
+            &discrete_state.open_options,
+        ),
+    };
+    let grain_data = match grain_result {
         Ok(grain) => grain,
+        Err(mxl::Error::FlowInvalid) => return Ok(CreateState::FlowInvalid),
         Err(err) => {
             trace!("error: {err}");
+            ensure_domain_available(src, domain)?;
             return Ok(CreateState::NoDataCreated);
         }
     };
@@ -72,6 +133,20 @@ pub(crate) fn create_discrete(
     // means it has not been produced yet (wait rather than emit stale data), a
     // newer one means the writer lapped us mid-read (catch up with DISCONT).
     let (read_index, slot_discont) = match grain_data.index {
+        // KLV is sparse: `read_index` is not past the head, so an older grain
+        // in its slot means the producer skipped that index.
+        actual if actual < read_index && matches!(discrete_state.format, DiscreteFormat::Klv) => {
+            trace!("Slot for index {read_index} still holds {actual}; no KLV at this index");
+            discrete_state.index = read_index + 1;
+            return Ok(CreateState::NoDataCreated);
+        }
+        // Past grains are committed: the producer skipped this index. Unless
+        // gaps are skipped, move on rather than wait for the writer to lap it.
+        actual if actual < read_index && read_index < head && gap_mode != GapMode::Skip => {
+            trace!("Slot for index {read_index} still holds {actual}; producer skipped it");
+            discrete_state.index = read_index + 1;
+            return Ok(CreateState::NoDataCreated);
+        }
         actual if actual < read_index => {
             trace!("Slot for index {read_index} still holds {actual}; waiting for producer");
             return Ok(CreateState::NoDataCreated);
@@ -98,12 +173,10 @@ pub(crate) fn create_discrete(
     let is_discont = jumped || slot_discont || deferred_discont;
 
     let mut buffer = match discrete_state.format {
-        DiscreteFormat::Video => gst::Buffer::from_slice(grain_data.payload.to_vec()),
-        DiscreteFormat::Data => {
-            let st2038 = format::data::gst_st2038_from_mxl_smpte291_grain(grain_data.payload)
-                .map_err(|_| gst::FlowError::Error)?;
-            gst::Buffer::from_slice(st2038)
+        DiscreteFormat::Video if zero_copy => {
+            wrap_grain(&discrete_state.grain_reader, grain_data.payload)
         }
+        format => grain_to_buffer(format, grain_data.payload)?,
     };
     {
         let buffer = buffer.get_mut().ok_or(gst::FlowError::Error)?;
@@ -112,9 +185,104 @@ pub(crate) fn create_discrete(
         if is_discont {
             buffer.set_flags(gst::BufferFlags::DISCONT);
         }
+        for ancillary in &discrete_state.ancillary {
+            ancillary.attach(instance, buffer, read_index, &rate);
+        }
+        if discrete_state.index_timecode {
+            add_index_timecode(buffer, read_index, &rate);
+        }
+        set_tai_reference(instance, buffer, read_index..read_index + 1, &rate)?;
     }
 
-    trace!(pts = ?buffer.pts(), index = read_index, "Produced {media} buffer");
+    trace!(
+        pts = ?buffer.pts(),
+        index = read_index,
+        valid_slices = grain_data.valid_slices,
+        total_slices = grain_data.total_slices,
+        "Produced {media} buffer"
+    );
     discrete_state.index = read_index + 1;
-    Ok(CreateState::DataCreated(buffer))
+    let missed = discrete_state
+        .last_pushed
+        .replace(read_index)
+        .and_then(|last| missed_grains(last + 1, read_index));
+    let previous = match gap_mode {
+        GapMode::Repeat => discrete_state.last_buffer.replace(buffer.clone()),
+        GapMode::Skip | GapMode::Gap => None,
+    };
+    match (gap_mode, missed, previous) {
+        (GapMode::Gap, Some(missed), _) => {
+            // The gap keeps the timeline, the data still is not continuous.
+            buffer
+                .get_mut()
+                .ok_or(gst::FlowError::Error)?
+                .set_flags(gst::BufferFlags::DISCONT);
+            let Some(start) = pts_for_index(instance, missed.start, &rate, subtrahend)? else {
+                return Ok(CreateState::DataCreated(buffer));
+            };
+            trace!("Missed {media} grains {missed:?}, pushing a gap");
+            let gap = gst::event::Gap::builder(start)
+                .duration(pts.saturating_sub(start))
+                .build();
+            Ok(CreateState::GapThenData(gap, buffer))
+        }
+        (GapMode::Repeat, Some(missed), Some(previous)) => {
+            trace!("Missed {media} grains {missed:?}, repeating the previous one");
+            let mut list = gst::BufferList::new_sized((missed.end - missed.start) as usize + 1);
+            {
+                let list = list.get_mut().ok_or(gst::FlowError::Error)?;
+                let repeated_index = missed.start - 1;
+                let period = index_period(&rate);
+                for index in missed {
+                    // Grains before running-time 0 are skipped, as when read.
+                    if let Some(pts) = pts_for_index(instance, index, &rate, subtrahend)? {
+                        let mut repeated =
+                            repeated_grain(&previous, repeated_index, index, pts, period)?;
+                        {
+                            let repeated = repeated.get_mut().ok_or(gst::FlowError::Error)?;
+                            if discrete_state.index_timecode {
+                                add_index_timecode(repeated, index, &rate);
+                            }
+                            set_tai_reference(instance, repeated, index..index + 1, &rate)?;
+                        }
+                        list.add(repeated);
+                    }
+                }
+                list.add(buffer);
+            }
+            Ok(CreateState::DataListCreated(list))
+        }
+        _ => Ok(CreateState::DataCreated(buffer)),
+    }
+}
+
+/// Minimum number of valid slices to request for a partial grain, or `None`
+/// to wait for complete grains. The library clamps the request to the flow's
+/// slice count, so flows with fewer slices than `min_slices` are read whole.
+pub(crate) fn partial_grain_min_slices(min_slices: u32) -> Option<u16> {
+    match min_slices {
+        0 => None,
+        min_slices => Some(u16::try_from(min_slices).unwrap_or(u16::MAX)),
+    }
+}
+
+/// Converts a grain payload to the buffer `mxlsrc` pushes for it: the raw v210
+/// frame for video, ST 2038 packets for data, the grain's KLV packets for KLV.
+pub(crate) fn grain_to_buffer(
+    format: DiscreteFormat,
+    payload: &[u8],
+) -> Result<gst::Buffer, gst::FlowError> {
+    match format {
+        DiscreteFormat::Video => Ok(gst::Buffer::from_slice(payload.to_vec())),
+        DiscreteFormat::Data => {
+            let st2038 = format::data::gst_st2038_from_mxl_smpte291_grain(payload)
+                .map_err(|_| gst::FlowError::Error)?;
+            Ok(gst::Buffer::from_slice(st2038))
+        }
+        DiscreteFormat::Klv => {
+            let klv = format::klv::gst_klv_from_mxl_klv_grain(payload)
+                .map_err(|_| gst::FlowError::Error)?;
+            Ok(gst::Buffer::from_slice(klv))
+        }
+    }
 }
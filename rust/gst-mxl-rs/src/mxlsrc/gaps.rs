@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Handling of the `gap-mode` property for video flows.
+//!
+//! A video grain the reader misses, because it fell behind the ring or the
+//! producer skipped its index, leaves a hole between two buffer PTS. By default
+//! the hole is announced with a GAP event ahead of the next buffer, which
+//! carries DISCONT, so a recorder writing constant frame durations does not
+//! compress it away. `skip` only flags the next buffer, `repeat` fills the hole
+//! with copies of the previous frame.
+
+use std::ops::Range;
+
+use gst::glib;
+use gstreamer as gst;
+
+use crate::mxlsrc::ancillary::remove_ancillary_metas;
+
+/// Custom meta on buffers `GapMode::Repeat` pushes for missed grains. Its
+/// structure holds the `index` of the missed grain and the `repeated-index` of
+/// the grain whose payload it repeats.
+pub const REPEATED_GRAIN_META: &str = "GstMxlRepeatedGrainMeta";
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstMxlSrcGapMode")]
+pub enum GapMode {
+    #[enum_value(
+        name = "Skip: push nothing for missed grains, DISCONT on the next buffer",
+        nick = "skip"
+    )]
+    Skip = 0,
+    #[enum_value(
+        name = "Gap: push a GAP event spanning the missed grains, DISCONT on the next buffer",
+        nick = "gap"
+    )]
+    #[default]
+    Gap = 1,
+    #[enum_value(
+        name = "Repeat: push the previous frame again for every missed grain",
+        nick = "repeat"
+    )]
+    Repeat = 2,
+}
+
+/// Grain indices missed between the `expected` next grain and the grain
+/// actually read at `read_index`.
+pub(crate) fn missed_grains(expected: u64, read_index: u64) -> Option<Range<u64>> {
+    (read_index > expected).then_some(expected..read_index)
+}
+
+/// Copy of `previous`, the buffer of grain `repeated_index`, standing in for
+/// the missed grain `index`.
+pub(crate) fn repeated_grain(
+    previous: &gst::Buffer,
+    repeated_index: u64,
+    index: u64,
+    pts: gst::ClockTime,
+    duration: gst::ClockTime,
+) -> Result<gst::Buffer, gst::FlowError> {
+    let mut buffer = previous.copy();
+    {
+        let buffer = buffer.get_mut().ok_or(gst::FlowError::Error)?;
+        buffer.set_pts(pts);
+        buffer.set_duration(duration);
+        buffer.unset_flags(gst::BufferFlags::DISCONT);
+        remove_ancillary_metas(buffer);
+        let mut meta = gst::meta::CustomMeta::add(buffer, REPEATED_GRAIN_META)
+            .map_err(|_| gst::FlowError::Error)?;
+        let structure = meta.mut_structure();
+        structure.set("index", index);
+        structure.set("repeated-index", repeated_index);
+    }
+    Ok(buffer)
+}
+
+pub(crate) fn register_meta() {
+    gst::meta::CustomMeta::register(REPEATED_GRAIN_META, &[]);
+}
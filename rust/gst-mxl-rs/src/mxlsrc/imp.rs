@@ -24,16 +24,38 @@ use tracing::trace;
 
 use std::sync::LazyLock;
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::clock::ClockOffsetExt;
+use crate::format::audio::AudioFormat;
+use crate::format::video::VideoFormat;
+use crate::mxlclock::MxlClock;
 use crate::mxlsrc;
-use crate::mxlsrc::create_continuous::create_continuous;
+use crate::mxlsrc::catch_up::CatchUpMode;
+use crate::mxlsrc::create_continuous::{continuous_latency, create_continuous};
 use crate::mxlsrc::create_discrete::create_discrete;
+use crate::mxlsrc::gaps::GapMode;
 use crate::mxlsrc::mxl_helper;
 use crate::mxlsrc::state::Context;
+use crate::mxlsrc::state::DEFAULT_BATCH_SIZE;
+use crate::mxlsrc::state::DEFAULT_CATCH_UP_MODE;
 use crate::mxlsrc::state::DEFAULT_DOMAIN;
+use crate::mxlsrc::state::DEFAULT_EOS_ON_STALE;
 use crate::mxlsrc::state::DEFAULT_FLOW_ID;
+use crate::mxlsrc::state::DEFAULT_FLOW_WAIT_TIMEOUT;
+use crate::mxlsrc::state::DEFAULT_GAP_MODE;
+use crate::mxlsrc::state::DEFAULT_GRAIN_TIMEOUT;
+use crate::mxlsrc::state::DEFAULT_LATENCY_TIME;
+use crate::mxlsrc::state::DEFAULT_MAX_RECONNECT_ATTEMPTS;
+use crate::mxlsrc::state::DEFAULT_MIN_SLICES;
+use crate::mxlsrc::state::DEFAULT_PRODUCER_TIMEOUT;
+use crate::mxlsrc::state::DEFAULT_PROVIDE_CLOCK;
+use crate::mxlsrc::state::DEFAULT_READ_OFFSET;
+use crate::mxlsrc::state::DEFAULT_RECONNECT_INTERVAL;
+use crate::mxlsrc::state::DEFAULT_SAMPLE_TIMEOUT;
+use crate::mxlsrc::state::DEFAULT_TIMECODE;
+use crate::mxlsrc::state::DEFAULT_WAIT_FOR_FLOW;
+use crate::mxlsrc::state::DEFAULT_ZERO_COPY;
 use crate::mxlsrc::state::FlowState;
 use crate::mxlsrc::state::Settings;
 use crate::mxlsrc::timing;
@@ -62,11 +84,20 @@ pub struct MxlSrc {
     pub(crate) context: Mutex<Context>,
     pub(crate) clock_wait: Mutex<ClockWait>,
     shared_clock_offset: Mutex<Option<crate::clock::SharedClockOffset>>,
+    /// Clock offered to the pipeline with `provide-clock`, created on first request.
+    clock: Mutex<Option<MxlClock>>,
 }
 
 pub enum CreateState {
     DataCreated(Buffer),
+    /// Buffers standing in for missed grains, then the grain read.
+    DataListCreated(gst::BufferList),
+    /// GAP event spanning missed grains, to push before the grain read.
+    GapThenData(gst::Event, Buffer),
     NoDataCreated,
+    /// The flow was recreated since the reader was opened, typically by a writer that
+    /// restarted: reattach to it.
+    FlowInvalid,
 }
 
 #[glib::object_subclass]
@@ -98,12 +129,199 @@ impl ObjectImpl for MxlSrc {
                     .default_value(DEFAULT_FLOW_ID)
                     .mutable_ready()
                     .build(),
+                glib::ParamSpecString::builder("ancillary-flow-id")
+                    .nick("AncillaryFlowID")
+                    .blurb(
+                        "video/smpte291 data flow ID whose grains are attached to the video \
+                         buffers of the same time as GstAncillaryMeta",
+                    )
+                    .default_value(DEFAULT_FLOW_ID)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("caption-flow-id")
+                    .nick("CaptionFlowID")
+                    .blurb(
+                        "video/smpte291 data flow ID whose CEA-708 and CEA-608 packets are \
+                         attached to the video buffers of the same time as GstVideoCaptionMeta",
+                    )
+                    .default_value(DEFAULT_FLOW_ID)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("timecode-flow-id")
+                    .nick("TimecodeFlowID")
+                    .blurb(
+                        "video/smpte291 data flow ID whose ST 12-2 ancillary time code is \
+                         attached to the video buffers of the same time as GstVideoTimeCodeMeta",
+                    )
+                    .default_value(DEFAULT_FLOW_ID)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("timecode")
+                    .nick("Timecode")
+                    .blurb(
+                        "Attach a GstVideoTimeCodeMeta with the timecode of their grain index to \
+                         video buffers that did not get one from timecode-flow-id",
+                    )
+                    .default_value(DEFAULT_TIMECODE)
+                    .mutable_ready()
+                    .build(),
                 glib::ParamSpecString::builder("domain")
                     .nick("Domain")
                     .blurb("Domain")
                     .default_value(DEFAULT_DOMAIN)
                     .mutable_ready()
                     .build(),
+                glib::ParamSpecUInt::builder("min-slices")
+                    .nick("MinSlices")
+                    .blurb(
+                        "Push a video grain once this many slices (lines) are valid, 0 to wait \
+                         for complete grains",
+                    )
+                    .maximum(u16::MAX as u32)
+                    .default_value(DEFAULT_MIN_SLICES)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("batch-size")
+                    .nick("BatchSize")
+                    .blurb(
+                        "Samples per audio buffer, 0 to follow the producer's batch size hints \
+                         (at most 48 samples)",
+                    )
+                    .default_value(DEFAULT_BATCH_SIZE)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("latency-time")
+                    .nick("LatencyTime")
+                    .blurb(
+                        "Microseconds of samples per audio buffer, rounded up to whole producer \
+                         commits, when batch-size is 0. 0 follows the producer's batch size hints",
+                    )
+                    .default_value(DEFAULT_LATENCY_TIME)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("read-offset")
+                    .nick("ReadOffset")
+                    .blurb(
+                        "Grains (video, data) or samples (audio) to read behind the head of the \
+                         flow, on top of the batch read for audio. Trades latency for robustness \
+                         against a producer committing late",
+                    )
+                    .default_value(DEFAULT_READ_OFFSET)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("grain-timeout")
+                    .nick("GrainTimeout")
+                    .blurb(
+                        "Milliseconds to wait for a video or data grain before checking the \
+                         domain and trying again",
+                    )
+                    .minimum(1)
+                    .default_value(DEFAULT_GRAIN_TIMEOUT)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("sample-timeout")
+                    .nick("SampleTimeout")
+                    .blurb(
+                        "Milliseconds to wait for a batch of audio samples before checking the \
+                         domain and trying again",
+                    )
+                    .minimum(1)
+                    .default_value(DEFAULT_SAMPLE_TIMEOUT)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("producer-timeout")
+                    .nick("ProducerTimeout")
+                    .blurb(
+                        "Milliseconds an audio reader ahead of the producer polls for its samples \
+                         before waiting on the read itself",
+                    )
+                    .default_value(DEFAULT_PRODUCER_TIMEOUT)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("wait-for-flow")
+                    .nick("WaitForFlow")
+                    .blurb(
+                        "Wait for flows that do not exist yet, so the consumer can start before \
+                         the producer. Otherwise a missing flow is an error",
+                    )
+                    .default_value(DEFAULT_WAIT_FOR_FLOW)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("flow-wait-timeout")
+                    .nick("FlowWaitTimeout")
+                    .blurb(
+                        "Milliseconds to wait for flows that do not exist yet before failing, \
+                         0 to wait until the source stops",
+                    )
+                    .default_value(DEFAULT_FLOW_WAIT_TIMEOUT)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("max-reconnect-attempts")
+                    .nick("MaxReconnectAttempts")
+                    .blurb(
+                        "Times in a row to reattach to a flow recreated by its writer before \
+                         failing, 0 for no limit. Pushing a buffer resets the count",
+                    )
+                    .default_value(DEFAULT_MAX_RECONNECT_ATTEMPTS)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecUInt::builder("reconnect-interval")
+                    .nick("ReconnectInterval")
+                    .blurb(
+                        "Milliseconds to wait before reattaching to a flow recreated by its writer",
+                    )
+                    .default_value(DEFAULT_RECONNECT_INTERVAL)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("eos-on-stale")
+                    .nick("EosOnStale")
+                    .blurb(
+                        "End the stream once the flow is stale: recreated by its writer, or \
+                         without a new grain or sample for grain-timeout or sample-timeout. \
+                         Otherwise reattach and wait for the writer",
+                    )
+                    .default_value(DEFAULT_EOS_ON_STALE)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("gap-mode", DEFAULT_GAP_MODE)
+                    .nick("Gap Mode")
+                    .blurb("What to push for video grains missed by the reader")
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecEnum::builder_with_default("catch-up-mode", DEFAULT_CATCH_UP_MODE)
+                    .nick("CatchUpMode")
+                    .blurb(
+                        "Where a reader behind the head of the flow resumes: where the writer \
+                         lapped it, at the oldest data still in the ring, or always at the newest",
+                    )
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecString::builder("open-options")
+                    .nick("OpenOptions")
+                    .blurb(
+                        "Comma separated key=value options passed to every grain or samples \
+                         read. Ignored by MXL versions without per-read options.",
+                    )
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("zero-copy")
+                    .nick("ZeroCopy")
+                    .blurb(
+                        "Push video buffers wrapping the grains in the MXL ring instead of copies. \
+                         Downstream must release them before the writer wraps around the ring.",
+                    )
+                    .default_value(DEFAULT_ZERO_COPY)
+                    .mutable_ready()
+                    .build(),
+                glib::ParamSpecBoolean::builder("provide-clock")
+                    .nick("ProvideClock")
+                    .blurb(
+                        "Offer the pipeline a clock running on MXL (TAI) time. A step of the \
+                         host's TAI clock then steps the pipeline clock too.",
+                    )
+                    .default_value(DEFAULT_PROVIDE_CLOCK)
+                    .mutable_ready()
+                    .build(),
             ]
         });
 
@@ -156,6 +374,42 @@ impl ObjectImpl for MxlSrc {
                         gst::error!(CAT, imp = self, "Invalid type for data-flow-id property");
                     }
                 }
+                "ancillary-flow-id" => {
+                    if let Ok(flow_id) = value.get::<String>() {
+                        settings.ancillary_flow = Some(flow_id);
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for ancillary-flow-id property"
+                        );
+                    }
+                }
+                "caption-flow-id" => {
+                    if let Ok(flow_id) = value.get::<String>() {
+                        settings.caption_flow = Some(flow_id);
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for caption-flow-id property");
+                    }
+                }
+                "timecode-flow-id" => {
+                    if let Ok(flow_id) = value.get::<String>() {
+                        settings.timecode_flow = Some(flow_id);
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for timecode-flow-id property"
+                        );
+                    }
+                }
+                "timecode" => {
+                    if let Ok(timecode) = value.get::<bool>() {
+                        settings.timecode = timecode;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for timecode property");
+                    }
+                }
                 "domain" => {
                     if let Ok(domain) = value.get::<String>() {
                         gst::info!(
@@ -170,6 +424,148 @@ impl ObjectImpl for MxlSrc {
                         gst::error!(CAT, imp = self, "Invalid type for domain property");
                     }
                 }
+                "min-slices" => {
+                    if let Ok(min_slices) = value.get::<u32>() {
+                        settings.min_slices = min_slices;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for min-slices property");
+                    }
+                }
+                "batch-size" => {
+                    if let Ok(batch_size) = value.get::<u32>() {
+                        settings.batch_size = batch_size;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for batch-size property");
+                    }
+                }
+                "latency-time" => {
+                    if let Ok(latency_time) = value.get::<u32>() {
+                        settings.latency_time = latency_time;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for latency-time property");
+                    }
+                }
+                "read-offset" => {
+                    if let Ok(read_offset) = value.get::<u32>() {
+                        settings.read_offset = read_offset;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for read-offset property");
+                    }
+                }
+                "grain-timeout" => {
+                    if let Ok(grain_timeout) = value.get::<u32>() {
+                        settings.grain_timeout = grain_timeout;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for grain-timeout property");
+                    }
+                }
+                "sample-timeout" => {
+                    if let Ok(sample_timeout) = value.get::<u32>() {
+                        settings.sample_timeout = sample_timeout;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for sample-timeout property");
+                    }
+                }
+                "wait-for-flow" => {
+                    if let Ok(wait_for_flow) = value.get::<bool>() {
+                        settings.wait_for_flow = wait_for_flow;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for wait-for-flow property");
+                    }
+                }
+                "flow-wait-timeout" => {
+                    if let Ok(flow_wait_timeout) = value.get::<u32>() {
+                        settings.flow_wait_timeout = flow_wait_timeout;
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for flow-wait-timeout property"
+                        );
+                    }
+                }
+                "max-reconnect-attempts" => {
+                    if let Ok(max_reconnect_attempts) = value.get::<u32>() {
+                        settings.max_reconnect_attempts = max_reconnect_attempts;
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for max-reconnect-attempts property"
+                        );
+                    }
+                }
+                "reconnect-interval" => {
+                    if let Ok(reconnect_interval) = value.get::<u32>() {
+                        settings.reconnect_interval = reconnect_interval;
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for reconnect-interval property"
+                        );
+                    }
+                }
+                "eos-on-stale" => {
+                    if let Ok(eos_on_stale) = value.get::<bool>() {
+                        settings.eos_on_stale = eos_on_stale;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for eos-on-stale property");
+                    }
+                }
+                "producer-timeout" => {
+                    if let Ok(producer_timeout) = value.get::<u32>() {
+                        settings.producer_timeout = producer_timeout;
+                    } else {
+                        gst::error!(
+                            CAT,
+                            imp = self,
+                            "Invalid type for producer-timeout property"
+                        );
+                    }
+                }
+                "gap-mode" => {
+                    if let Ok(gap_mode) = value.get::<GapMode>() {
+                        settings.gap_mode = gap_mode;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for gap-mode property");
+                    }
+                }
+                "catch-up-mode" => {
+                    if let Ok(catch_up_mode) = value.get::<CatchUpMode>() {
+                        settings.catch_up_mode = catch_up_mode;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for catch-up-mode property");
+                    }
+                }
+                "open-options" => {
+                    settings.open_options = value
+                        .get::<Option<String>>()
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default();
+                }
+                "zero-copy" => {
+                    if let Ok(zero_copy) = value.get::<bool>() {
+                        settings.zero_copy = zero_copy;
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for zero-copy property");
+                    }
+                }
+                "provide-clock" => {
+                    if let Ok(provide_clock) = value.get::<bool>() {
+                        settings.provide_clock = provide_clock;
+                        if provide_clock {
+                            self.obj()
+                                .set_element_flags(gst::ElementFlags::PROVIDE_CLOCK);
+                        } else {
+                            self.obj()
+                                .unset_element_flags(gst::ElementFlags::PROVIDE_CLOCK);
+                        }
+                    } else {
+                        gst::error!(CAT, imp = self, "Invalid type for provide-clock property");
+                    }
+                }
                 other => {
                     gst::error!(CAT, imp = self, "Unknown property '{}'", other);
                 }
@@ -189,7 +585,28 @@ impl ObjectImpl for MxlSrc {
                 "video-flow-id" => settings.video_flow.to_value(),
                 "audio-flow-id" => settings.audio_flow.to_value(),
                 "data-flow-id" => settings.data_flow.to_value(),
+                "ancillary-flow-id" => settings.ancillary_flow.to_value(),
+                "caption-flow-id" => settings.caption_flow.to_value(),
+                "timecode-flow-id" => settings.timecode_flow.to_value(),
+                "timecode" => settings.timecode.to_value(),
                 "domain" => settings.domain.to_value(),
+                "min-slices" => settings.min_slices.to_value(),
+                "batch-size" => settings.batch_size.to_value(),
+                "latency-time" => settings.latency_time.to_value(),
+                "read-offset" => settings.read_offset.to_value(),
+                "grain-timeout" => settings.grain_timeout.to_value(),
+                "sample-timeout" => settings.sample_timeout.to_value(),
+                "producer-timeout" => settings.producer_timeout.to_value(),
+                "wait-for-flow" => settings.wait_for_flow.to_value(),
+                "flow-wait-timeout" => settings.flow_wait_timeout.to_value(),
+                "max-reconnect-attempts" => settings.max_reconnect_attempts.to_value(),
+                "reconnect-interval" => settings.reconnect_interval.to_value(),
+                "eos-on-stale" => settings.eos_on_stale.to_value(),
+                "gap-mode" => settings.gap_mode.to_value(),
+                "catch-up-mode" => settings.catch_up_mode.to_value(),
+                "open-options" => settings.open_options.to_value(),
+                "zero-copy" => settings.zero_copy.to_value(),
+                "provide-clock" => settings.provide_clock.to_value(),
                 _ => {
                     gst::error!(CAT, imp = self, "Unknown property {}", pspec.name());
                     glib::Value::from(&"")
@@ -227,12 +644,18 @@ impl ElementImpl for MxlSrc {
 
                     caps_mut.append(
                         gst::Caps::builder("video/x-raw")
-                            .field("format", "v210")
+                            .field(
+                                "format",
+                                gst::List::new(VideoFormat::ALL.map(VideoFormat::gst_format)),
+                            )
                             .build(),
                     );
                     caps.make_mut().append(
                         gst::Caps::builder("audio/x-raw")
-                            .field("format", "F32LE")
+                            .field(
+                                "format",
+                                gst::List::new(AudioFormat::ALL.map(AudioFormat::gst_format)),
+                            )
                             .build(),
                     );
                     caps.make_mut().append(
@@ -240,6 +663,11 @@ impl ElementImpl for MxlSrc {
                             .field("alignment", "frame")
                             .build(),
                     );
+                    caps.make_mut().append(
+                        gst::Caps::builder("meta/x-klv")
+                            .field("parsed", true)
+                            .build(),
+                    );
                 }
                 let src_pad_template = gst::PadTemplate::new(
                     "src",
@@ -267,6 +695,20 @@ impl ElementImpl for MxlSrc {
         self.parent_change_state(transition)
     }
 
+    fn provide_clock(&self) -> Option<gst::Clock> {
+        let mut clock = self.clock.lock().ok()?;
+        if clock.is_none() {
+            match mxl_helper::ensure_instance(self) {
+                Ok(instance) => *clock = Some(MxlClock::new(instance)),
+                Err(err) => {
+                    gst::warning!(CAT, imp = self, "Not providing an MXL clock: {:?}", err);
+                    return None;
+                }
+            }
+        }
+        clock.clone().map(|clock| clock.upcast())
+    }
+
     fn set_clock(&self, clock: Option<&gst::Clock>) -> bool {
         self.handle_set_clock(clock)
     }
@@ -328,7 +770,8 @@ impl BaseSrcImpl for MxlSrc {
 
         // `start()` does not attach the MXL reader (so PLAYING is reachable
         // before the producer creates the flow). Attach here on the streaming
-        // thread. `init_mxl_reader` polls until the flow exists; `unlock()` sets
+        // thread. `init_mxl_reader` polls until the flow exists, as long as
+        // `wait-for-flow` and `flow-wait-timeout` allow; `unlock()` sets
         // `clock_wait.flushing` so teardown can interrupt that wait.
         let need_init = {
             let context = self
@@ -364,9 +807,10 @@ impl BaseSrcImpl for MxlSrc {
             .ok_or(gst::loggable_error!(CAT, "Failed to get state"))?
             .instance;
         let flow_id = mxl_helper::get_flow_type_id(&settings)?;
-        let json_flow_description = mxl_helper::get_mxl_flow_json(instance, flow_id)?;
-        let flow_description = mxl_helper::get_flow_def(self, json_flow_description)?;
-        mxl_helper::set_json_caps(self, flow_description)
+        let flow_def = instance
+            .get_flow_def_typed(flow_id)
+            .map_err(|e| gst::loggable_error!(CAT, "Failed to get flow definition: {}", e))?;
+        mxl_helper::set_json_caps(self, flow_def.details, &flow_def.media_type)
     }
 
     fn set_caps(&self, caps: &gst::Caps) -> Result<(), gst::LoggableError> {
@@ -379,10 +823,10 @@ impl BaseSrcImpl for MxlSrc {
             let format = structure
                 .get::<String>("format")
                 .map_err(|e| gst::loggable_error!(CAT, "Failed to set caps {}", e))?;
-            if format != "v210" {
+            if VideoFormat::from_gst_format(&format).is_none() {
                 return Err(gst::loggable_error!(
                     CAT,
-                    "Unsupported video format (expected v210): {}",
+                    "Unsupported video format (expected v210, UYVY, I420, NV12, RGBA or BGRA): {}",
                     format
                 ));
             }
@@ -418,10 +862,10 @@ impl BaseSrcImpl for MxlSrc {
             let format = structure
                 .get::<String>("format")
                 .map_err(|e| gst::loggable_error!(CAT, "Failed to get format from caps: {}", e))?;
-            if format != "F32LE" {
+            if AudioFormat::from_gst_format(&format).is_none() {
                 return Err(gst::loggable_error!(
                     CAT,
-                    "Unsupported audio format (expected F32LE): {}",
+                    "Unsupported audio format (expected F32LE, S16LE or S24_32LE): {}",
                     format
                 ));
             }
@@ -453,6 +897,9 @@ impl BaseSrcImpl for MxlSrc {
                 alignment
             );
             Ok(())
+        } else if name == "meta/x-klv" {
+            trace!("Negotiated caps: {}", caps);
+            Ok(())
         } else {
             Err(gst::loggable_error!(
                 CAT,
@@ -513,9 +960,9 @@ impl BaseSrcImpl for MxlSrc {
 
     fn query(&self, query: &mut gst::QueryRef) -> bool {
         if let gst::QueryViewMut::Latency(q) = query.view_mut()
-            && let Some(latency) = self.live_latency()
+            && let Some((min, max)) = self.live_latency()
         {
-            q.set(true, latency, gst::ClockTime::NONE);
+            q.set(true, min, max);
             return true;
         }
         BaseSrcImplExt::parent_query(self, query)
@@ -580,6 +1027,15 @@ impl PushSrcImpl for MxlSrc {
                     CreateState::DataCreated(buffer) => {
                         return Ok(CreateSuccess::NewBuffer(buffer));
                     }
+                    CreateState::DataListCreated(list) => {
+                        return Ok(CreateSuccess::NewBufferList(list));
+                    }
+                    CreateState::GapThenData(gap, buffer) => {
+                        // Pushed from the streaming thread, after the context
+                        // lock is released, ahead of the buffer it precedes.
+                        self.obj().src_pad().push_event(gap);
+                        return Ok(CreateSuccess::NewBuffer(buffer));
+                    }
                     CreateState::NoDataCreated => {
                         // The producer has not committed the next grain yet. Only
                         // bail when the pipeline is tearing us down (basesrc calls
@@ -593,6 +1049,7 @@ impl PushSrcImpl for MxlSrc {
                         }
                         std::thread::sleep(Duration::from_millis(1));
                     }
+                    CreateState::FlowInvalid => self.reconnect()?,
                 },
                 Err(e) => return Err(e),
             }
@@ -601,29 +1058,199 @@ impl PushSrcImpl for MxlSrc {
 }
 
 impl MxlSrc {
-    /// Live latency to advertise: one grain period of the attached discrete flow.
+    /// Live latency to advertise, min and max.
     ///
     /// A grain becomes readable only once the producer has committed it, and this
     /// source delivers the most recently committed grain, so at any instant the
     /// reader may sit up to one grain period behind the live edge. One period is
-    /// therefore the source latency downstream should budget. Returns `None` before
-    /// the flow is attached (or for audio), leaving the BaseSrc default in place.
-    fn live_latency(&self) -> Option<gst::ClockTime> {
+    /// therefore the minimum latency downstream should budget. Audio buffers are
+    /// pushed once their last sample is committed, so the minimum is one batch.
+    /// `read-offset` adds to the minimum. The maximum is how old data may get
+    /// before the writer overwrites it in the ring. Returns `None` before the flow
+    /// is attached, leaving the BaseSrc default in place.
+    fn live_latency(&self) -> Option<(gst::ClockTime, gst::ClockTime)> {
+        // Read settings before taking the context lock, `negotiate()` locks them in that order.
+        let (locked_batch_size, latency_time) = {
+            let settings = self.settings.lock().ok()?;
+            (settings.batch_size, settings.latency_time)
+        };
         let context = self.context.lock().ok()?;
         let state = context.state.as_ref()?;
         match state.flow_state.as_ref()? {
-            FlowState::Discrete(discrete) => Some(timing::index_period(&discrete.grain_rate)),
-            FlowState::Continuous(_) => None,
+            FlowState::Discrete(discrete) => {
+                let grain_count = timing::discrete_grain_count(&discrete.grain_reader).ok()?;
+                Some(timing::ring_latency(
+                    1 + timing::discrete_read_offset(discrete.read_offset, grain_count),
+                    grain_count.into(),
+                    &discrete.grain_rate,
+                ))
+            }
+            FlowState::Continuous(continuous) => continuous_latency(
+                &continuous.reader.get_info().ok()?,
+                locked_batch_size,
+                latency_time,
+                continuous.read_offset,
+            ),
         }
     }
 
     fn try_create(&self, offset: u64) -> Result<CreateState, gst::FlowError> {
+        // Read settings before taking the context lock, `negotiate()` locks them in that order.
+        let (
+            min_slices,
+            batch_size,
+            latency_time,
+            gap_mode,
+            catch_up_mode,
+            zero_copy,
+            eos_on_stale,
+        ) = {
+            let settings = self.settings.lock().map_err(|_| gst::FlowError::Error)?;
+            (
+                settings.min_slices,
+                settings.batch_size,
+                settings.latency_time,
+                settings.gap_mode,
+                settings.catch_up_mode,
+                settings.zero_copy,
+                settings.eos_on_stale,
+            )
+        };
         let mut context = self.context.lock().map_err(|_| gst::FlowError::Error)?;
         let state = context.state.as_mut().ok_or(gst::FlowError::Error)?;
-        match &state.flow_state {
-            Some(FlowState::Discrete(_)) => create_discrete(self, state, offset),
-            Some(FlowState::Continuous(_)) => create_continuous(self, state, offset),
-            None => Err(gst::FlowError::Error),
+        let (created, stale_timeout) = match &state.flow_state {
+            Some(FlowState::Discrete(discrete)) => {
+                let grain_timeout = discrete.timeouts.grain;
+                (
+                    create_discrete(
+                        self,
+                        state,
+                        offset,
+                        min_slices,
+                        gap_mode,
+                        catch_up_mode,
+                        zero_copy,
+                    )?,
+                    grain_timeout,
+                )
+            }
+            Some(FlowState::Continuous(continuous)) => {
+                let sample_timeout = continuous.timeouts.sample;
+                (
+                    create_continuous(
+                        self,
+                        state,
+                        offset,
+                        batch_size,
+                        latency_time,
+                        catch_up_mode,
+                    )?,
+                    sample_timeout,
+                )
+            }
+            None => return Err(gst::FlowError::Error),
+        };
+        match created {
+            CreateState::FlowInvalid if eos_on_stale => {
+                gst::info!(
+                    CAT,
+                    imp = self,
+                    "Flow recreated by its writer, ending the stream"
+                );
+                Err(gst::FlowError::Eos)
+            }
+            CreateState::NoDataCreated
+                if eos_on_stale
+                    && context
+                        .last_data
+                        .is_some_and(|last_data| last_data.elapsed() > stale_timeout) =>
+            {
+                gst::info!(
+                    CAT,
+                    imp = self,
+                    "No data from the flow for {:?}, ending the stream",
+                    stale_timeout
+                );
+                Err(gst::FlowError::Eos)
+            }
+            CreateState::NoDataCreated | CreateState::FlowInvalid => Ok(created),
+            _ => {
+                context.reconnect_attempts = 0;
+                context.last_data = Some(Instant::now());
+                Ok(created)
+            }
         }
     }
+
+    /// Reattaches to a flow recreated since its reader was opened, after `reconnect-interval`.
+    ///
+    /// Each attempt posts a warning so that a writer restarting over and over shows on the bus.
+    /// More than `max-reconnect-attempts` in a row without a buffer pushed in between post a
+    /// `ResourceError::Read` and stop the streaming task. Attaching waits for the flow to be
+    /// created again as `wait-for-flow` and `flow-wait-timeout` allow.
+    fn reconnect(&self) -> Result<(), gst::FlowError> {
+        let (flow_id, max_attempts, interval) = {
+            let settings = self.settings.lock().map_err(|_| gst::FlowError::Error)?;
+            (
+                settings.flow_id().cloned().unwrap_or_default(),
+                settings.max_reconnect_attempts,
+                Duration::from_millis(settings.reconnect_interval.into()),
+            )
+        };
+        let attempt = {
+            let mut context = self.context.lock().map_err(|_| gst::FlowError::Error)?;
+            // Drop the stale readers now rather than after the interval.
+            if let Some(state) = context.state.as_mut() {
+                state.flow_state = None;
+            }
+            context.reconnect_attempts += 1;
+            context.reconnect_attempts
+        };
+        if max_attempts != 0 && attempt > max_attempts {
+            gst::element_imp_error!(
+                self,
+                gst::ResourceError::Read,
+                [
+                    "MXL flow {} was recreated again after {} reconnect attempts",
+                    flow_id,
+                    max_attempts
+                ]
+            );
+            return Err(gst::FlowError::Error);
+        }
+        gst::element_imp_warning!(
+            self,
+            gst::ResourceError::Read,
+            [
+                "MXL flow {} was recreated, reconnecting (attempt {})",
+                flow_id,
+                attempt
+            ]
+        );
+
+        let deadline = Instant::now() + interval;
+        while Instant::now() < deadline {
+            if mxl_helper::is_flushing(self) {
+                return Err(gst::FlowError::Flushing);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        if let Err(err) = mxl_helper::init(self) {
+            if mxl_helper::is_flushing(self) {
+                return Err(gst::FlowError::Flushing);
+            }
+            gst::element_imp_error!(
+                self,
+                gst::ResourceError::Read,
+                ["Failed to reconnect to MXL flow {}: {}", flow_id, err]
+            );
+            return Err(gst::FlowError::Error);
+        }
+        // The writer may have recreated the flow with another format or rate.
+        self.obj().src_pad().mark_reconfigure();
+        let _ = self
+            .obj()
+            .post_message(gst::message::Latency::builder().src(&*self.obj()).build());
+        Ok(())
+    }
 }
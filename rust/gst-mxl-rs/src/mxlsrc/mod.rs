@@ -16,19 +16,27 @@ use gst::prelude::*;
 use gstreamer as gst;
 use gstreamer_base as gst_base;
 
+pub(crate) mod ancillary;
+pub(crate) mod catch_up;
 mod create_continuous;
-mod create_discrete;
+pub(crate) mod create_discrete;
+pub(crate) mod gaps;
 mod imp;
-mod mxl_helper;
+pub(crate) mod mxl_helper;
 mod src_tests;
-mod state;
-mod timing;
+pub(crate) mod state;
+pub(crate) mod timing;
+mod zero_copy;
 
 glib::wrapper! {
     pub struct MxlSrc(ObjectSubclass<imp::MxlSrc>) @extends gst_base::PushSrc, gst_base::BaseSrc, gst::Element, gst::Object;
 }
 
+pub use catch_up::CatchUpMode;
+pub use gaps::GapMode;
+
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    gaps::register_meta();
     gst::Element::register(
         Some(plugin),
         "mxlsrc",
@@ -2,19 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+    path::Path,
     sync::{LazyLock, MutexGuard},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use glib::subclass::types::ObjectSubclassExt;
 use gst_base::prelude::*;
 use gstreamer as gst;
 use gstreamer_base as gst_base;
-use mxl::{FlowReader, MxlInstance, config::get_mxl_so_path, flowdef::*};
+use mxl::{AccessOptions, FlowReader, MxlInstance, config::get_mxl_so_path, flowdef::*};
 
+use crate::format::audio::AudioFormat;
+use crate::format::data::SMPTE291_MEDIA_TYPE;
+use crate::format::klv::KLV_MEDIA_TYPE;
+use crate::format::video::VideoFormat;
 use crate::mxlsrc::{
+    ancillary::{AncillaryReader, AttachedMeta},
     imp::*,
     state::{ContinuousState, DiscreteFormat, DiscreteState, FlowState, Settings, State},
+    zero_copy::SharedGrainReader,
 };
 
 static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
@@ -42,108 +49,77 @@ pub(crate) fn pts_subtrahend(src: &MxlSrc, offset: u64) -> Result<u64, gst::Flow
     Ok(offset.saturating_add(base_time.nseconds()))
 }
 
-pub(crate) fn get_mxl_flow_json(
-    instance: &MxlInstance,
-    flow_id: &str,
-) -> Result<serde_json::Value, gst::LoggableError> {
-    let flow_def = instance
-        .get_flow_def(flow_id)
-        .map_err(|e| gst::loggable_error!(CAT, "Failed to get flow definition: {}", e))?;
-    let serde_json: serde_json::Value = serde_json::from_str(flow_def.as_str())
-        .map_err(|e| gst::loggable_error!(CAT, "Invalid JSON: {}", e))?;
-    Ok(serde_json)
-}
-
-pub(crate) fn set_json_caps(src: &MxlSrc, json: FlowDefDetails) -> Result<(), gst::LoggableError> {
-    match json {
-        FlowDefDetails::Video(video) => {
-            let caps = gst::Caps::builder("video/x-raw")
-                .field("format", "v210")
-                .field("width", video.frame_width)
-                .field("height", video.frame_height)
-                .field(
-                    "framerate",
-                    gst::Fraction::new(video.grain_rate.numerator, video.grain_rate.denominator),
-                )
-                .field("interlace-mode", video.interlace_mode.as_str())
-                .field("colorimetry", video.colorspace.to_lowercase())
-                .build();
-
-            src.obj()
-                .set_caps(&caps)
-                .map_err(|err| gst::loggable_error!(CAT, "Failed to set caps: {}", err))?;
-
-            gst::info!(CAT, imp = src, "Negotiated caps: {}", caps);
-            Ok(())
-        }
-        FlowDefDetails::Audio(audio) => {
-            let caps = gst::Caps::builder("audio/x-raw")
-                .field("format", "F32LE")
-                .field("rate", audio.sample_rate.numerator)
-                .field("channels", audio.channel_count)
-                .field("layout", "interleaved")
-                .field(
-                    "channel-mask",
-                    generate_channel_mask_from_channels(audio.channel_count as u32),
-                )
-                .build();
-            src.obj()
-                .set_caps(&caps)
-                .map_err(|err| gst::loggable_error!(CAT, "Failed to set caps: {}", err))?;
-
-            gst::info!(CAT, imp = src, "Negotiated caps: {}", caps);
-            Ok(())
-        }
-        FlowDefDetails::Data(data) => {
-            let caps = gst::Caps::builder("meta/x-st-2038")
-                .field(
-                    "framerate",
-                    gst::Fraction::new(data.grain_rate.numerator, data.grain_rate.denominator),
-                )
-                .field("alignment", "frame")
-                .build();
+pub(crate) fn set_json_caps(
+    src: &MxlSrc,
+    json: FlowDefDetails,
+    media_type: &str,
+) -> Result<(), gst::LoggableError> {
+    let caps = caps_for_flow_def(&json, media_type);
+    src.obj()
+        .set_caps(&caps)
+        .map_err(|err| gst::loggable_error!(CAT, "Failed to set caps: {}", err))?;
 
-            src.obj()
-                .set_caps(&caps)
-                .map_err(|err| gst::loggable_error!(CAT, "Failed to set caps: {}", err))?;
+    gst::info!(CAT, imp = src, "Negotiated caps: {}", caps);
+    Ok(())
+}
 
-            gst::info!(CAT, imp = src, "Negotiated caps: {}", caps);
-            Ok(())
-        }
+/// Caps `mxlsrc` produces for a flow with the given definition and media type.
+/// Video and audio flows of unknown media types are taken for v210 and `audio/float32`.
+pub(crate) fn caps_for_flow_def(flow_def: &FlowDefDetails, media_type: &str) -> gst::Caps {
+    match flow_def {
+        FlowDefDetails::Video(video) => gst::Caps::builder("video/x-raw")
+            .field(
+                "format",
+                VideoFormat::from_media_type(media_type)
+                    .unwrap_or(VideoFormat::V210)
+                    .gst_format(),
+            )
+            .field("width", video.frame_width)
+            .field("height", video.frame_height)
+            .field(
+                "framerate",
+                gst::Fraction::new(video.grain_rate.numerator, video.grain_rate.denominator),
+            )
+            .field("interlace-mode", video.interlace_mode.as_str())
+            .field("colorimetry", video.colorspace.to_lowercase())
+            .build(),
+        FlowDefDetails::Audio(audio) => gst::Caps::builder("audio/x-raw")
+            .field(
+                "format",
+                AudioFormat::from_media_type(media_type)
+                    .unwrap_or(AudioFormat::F32)
+                    .gst_format(),
+            )
+            .field("rate", audio.sample_rate.numerator)
+            .field("channels", audio.channel_count)
+            .field("layout", "interleaved")
+            .field(
+                "channel-mask",
+                generate_channel_mask_from_channels(audio.channel_count as u32),
+            )
+            .build(),
+        FlowDefDetails::Data(data) if media_type == KLV_MEDIA_TYPE => klv_caps(data),
+        FlowDefDetails::Data(data) => gst::Caps::builder("meta/x-st-2038")
+            .field(
+                "framerate",
+                gst::Fraction::new(data.grain_rate.numerator, data.grain_rate.denominator),
+            )
+            .field("alignment", "frame")
+            .build(),
     }
 }
 
-pub(crate) fn get_flow_def(
-    src: &MxlSrc,
-    serde_json: serde_json::Value,
-) -> Result<FlowDefDetails, gst::LoggableError> {
-    let format = serde_json
-        .get("format")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown");
-    let json = match format {
-        "urn:x-nmos:format:video" => {
-            let flow: FlowDefVideo = serde_json::from_value(serde_json)
-                .map_err(|e| gst::loggable_error!(CAT, "Invalid video flow JSON: {}", e))?;
-            FlowDefDetails::Video(flow)
-        }
-        "urn:x-nmos:format:audio" => {
-            let flow: FlowDefAudio = serde_json::from_value(serde_json)
-                .map_err(|e| gst::loggable_error!(CAT, "Invalid audio flow JSON: {}", e))?;
-            FlowDefDetails::Audio(flow)
-        }
-        "urn:x-nmos:format:data" => {
-            let flow: FlowDefData = serde_json::from_value(serde_json)
-                .map_err(|e| gst::loggable_error!(CAT, "Invalid data flow JSON: {}", e))?;
-            FlowDefDetails::Data(flow)
-        }
-        _ => {
-            gst::warning!(CAT, imp = src, "Unknown format '{}'", format);
-            return Err(gst::loggable_error!(CAT, "Unknown format {}", format));
-        }
-    };
-    Ok(json)
+/// Caps `mxlsrc` produces for a `video/smpte336m` data flow.
+pub(crate) fn klv_caps(data: &FlowDefData) -> gst::Caps {
+    gst::Caps::builder("meta/x-klv")
+        .field("parsed", true)
+        .field(
+            "framerate",
+            gst::Fraction::new(data.grain_rate.numerator, data.grain_rate.denominator),
+        )
+        .build()
 }
+
 pub(crate) fn generate_channel_mask_from_channels(channels: u32) -> gst::Bitmask {
     let mask = if channels >= 64 {
         u64::MAX
@@ -161,8 +137,8 @@ enum FlowKind {
     Data,
 }
 
-/// Blocks until `create_flow_reader` succeeds, sleeping briefly and retrying
-/// while MXL returns `FlowNotFound`.
+/// Reader of `flow_id`, polling for the flow until `deadline` (`None` for no limit) if it does
+/// not exist yet. A flow still missing then posts a `ResourceError::NotFound`.
 ///
 /// Before each attempt, checks `is_flushing` and returns an error if so, to
 /// avoid blocking teardown via `unlock` if the flow has not yet been created.
@@ -174,6 +150,7 @@ fn init_mxl_reader(
     mxlsrc: &MxlSrc,
     instance: &MxlInstance,
     flow_id: &str,
+    deadline: Option<Instant>,
 ) -> Result<FlowReader, gst::ErrorMessage> {
     let mut warned = false;
     loop {
@@ -186,6 +163,17 @@ fn init_mxl_reader(
         match instance.create_flow_reader(flow_id) {
             Ok(reader) => break Ok(reader),
             Err(mxl::Error::FlowNotFound) => {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    gst::element_imp_error!(
+                        mxlsrc,
+                        gst::ResourceError::NotFound,
+                        ["MXL flow {} does not exist", flow_id]
+                    );
+                    break Err(gst::error_msg!(
+                        gst::ResourceError::NotFound,
+                        ["MXL flow {} does not exist", flow_id]
+                    ));
+                }
                 if !warned {
                     eprintln!("Waiting for flow to be created...");
                     warned = true;
@@ -255,18 +243,90 @@ pub(crate) fn ensure_instance(mxlsrc: &MxlSrc) -> Result<MxlInstance, gst::Error
     Ok(instance)
 }
 
+/// Checks that the domain directory is still present after a failed read.
+///
+/// A tmpfs that was unmounted, or a domain directory removed while PLAYING, makes every
+/// subsequent read fail, and `create()` would otherwise keep retrying forever. When the domain is
+/// gone this posts a `ResourceError::NotFound` naming it and returns `FlowError::Error`, which
+/// stops the streaming task. Otherwise the failure is treated as a transient producer stall.
+pub(crate) fn ensure_domain_available(src: &MxlSrc, domain: &str) -> Result<(), gst::FlowError> {
+    if Path::new(domain).is_dir() {
+        return Ok(());
+    }
+    gst::element_imp_error!(
+        src,
+        gst::ResourceError::NotFound,
+        ["MXL domain {} is no longer available", domain]
+    );
+    Err(gst::FlowError::Error)
+}
+
 pub(crate) fn init(mxlsrc: &MxlSrc) -> Result<(), gst::ErrorMessage> {
-    let (flow_kind, flow_id) = {
+    let (
+        flow_kind,
+        flow_id,
+        attached_flow_ids,
+        index_timecode,
+        domain,
+        open_options,
+        read_offset,
+        timeouts,
+    ) = {
         let settings = mxlsrc
             .settings
             .lock()
             .map_err(|_| gst::error_msg!(gst::CoreError::Failed, ["Missing settings"]))?;
+        let domain = settings.domain.clone();
+        let open_options = AccessOptions::parse(&settings.open_options).map_err(|e| {
+            gst::error_msg!(gst::CoreError::Failed, ["Invalid open-options: {}", e])
+        })?;
+        let attached_flow_ids: Vec<(AttachedMeta, String)> = [
+            (AttachedMeta::Ancillary, settings.ancillary_flow.clone()),
+            (AttachedMeta::Captions, settings.caption_flow.clone()),
+            (AttachedMeta::Timecode, settings.timecode_flow.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(meta, flow_id)| Some((meta, flow_id?)))
+        .collect();
+        if !attached_flow_ids.is_empty() && settings.video_flow.is_none() {
+            return Err(gst::error_msg!(
+                gst::CoreError::Failed,
+                ["ancillary-flow-id, caption-flow-id and timecode-flow-id need video-flow-id"]
+            ));
+        }
         if let Some(flow_id) = settings.video_flow.clone() {
-            (FlowKind::Video, flow_id)
+            (
+                FlowKind::Video,
+                flow_id,
+                attached_flow_ids,
+                settings.timecode,
+                domain,
+                open_options,
+                settings.read_offset,
+                settings.read_timeouts(),
+            )
         } else if let Some(flow_id) = settings.audio_flow.clone() {
-            (FlowKind::Audio, flow_id)
+            (
+                FlowKind::Audio,
+                flow_id,
+                Vec::new(),
+                false,
+                domain,
+                open_options,
+                settings.read_offset,
+                settings.read_timeouts(),
+            )
         } else if let Some(flow_id) = settings.data_flow.clone() {
-            (FlowKind::Data, flow_id)
+            (
+                FlowKind::Data,
+                flow_id,
+                Vec::new(),
+                false,
+                domain,
+                open_options,
+                settings.read_offset,
+                settings.read_timeouts(),
+            )
         } else {
             return Err(gst::error_msg!(
                 gst::CoreError::Failed,
@@ -275,11 +335,22 @@ pub(crate) fn init(mxlsrc: &MxlSrc) -> Result<(), gst::ErrorMessage> {
         }
     };
 
+    let flow_deadline = mxlsrc
+        .settings
+        .lock()
+        .map_err(|_| gst::error_msg!(gst::CoreError::Failed, ["Missing settings"]))?
+        .flow_wait_deadline(Instant::now());
     let instance = ensure_instance(mxlsrc)?;
 
     // Wait for the flow to be created without holding `settings` or `context` mutexes
     // across the poll/sleep loop.
-    let reader = init_mxl_reader(mxlsrc, &instance, flow_id.as_str())?;
+    let reader = init_mxl_reader(mxlsrc, &instance, flow_id.as_str(), flow_deadline)?;
+    let ancillary = attached_flow_ids
+        .iter()
+        .map(|(meta, flow_id)| {
+            init_ancillary_reader(mxlsrc, &instance, flow_id, *meta, flow_deadline)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let binding = reader.get_info();
     let reader_info = binding.as_ref();
 
@@ -317,18 +388,39 @@ pub(crate) fn init(mxlsrc: &MxlSrc) -> Result<(), gst::ErrorMessage> {
 
             context.state = Some(State {
                 instance,
+                domain: domain.clone(),
                 flow_state: Some(FlowState::Discrete(DiscreteState {
                     format: DiscreteFormat::Video,
                     grain_rate,
                     index: 0,
+                    read_offset,
+                    timeouts,
                     is_initialized: false,
                     next_discont: false,
-                    grain_reader,
+                    grain_reader: SharedGrainReader::new(grain_reader),
+                    open_options,
+                    last_pushed: None,
+                    last_buffer: None,
+                    ancillary,
+                    index_timecode,
                 })),
             });
         }
         FlowKind::Audio => {
-            let reader_samples = init_mxl_reader(mxlsrc, &instance, flow_id.as_str())?;
+            let flow_def = instance.get_flow_def_typed(flow_id.as_str()).map_err(|e| {
+                gst::error_msg!(
+                    gst::CoreError::Failed,
+                    ["Failed to read MXL flow definition: {}", e]
+                )
+            })?;
+            let format = AudioFormat::from_media_type(&flow_def.media_type).ok_or_else(|| {
+                gst::error_msg!(
+                    gst::CoreError::Failed,
+                    ["Unsupported audio media type: {}", flow_def.media_type]
+                )
+            })?;
+            let reader_samples =
+                init_mxl_reader(mxlsrc, &instance, flow_id.as_str(), flow_deadline)?;
             let samples_reader = reader_samples.to_samples_reader().map_err(|e| {
                 gst::error_msg!(
                     gst::CoreError::Failed,
@@ -337,12 +429,18 @@ pub(crate) fn init(mxlsrc: &MxlSrc) -> Result<(), gst::ErrorMessage> {
             })?;
             context.state = Some(State {
                 instance,
+                domain: domain.clone(),
                 flow_state: Some(FlowState::Continuous(ContinuousState {
                     reader,
                     samples_reader,
                     is_initialized: false,
                     index: 0,
+                    read_offset,
+                    timeouts,
                     next_discont: false,
+                    format,
+                    open_options,
+                    batch_hints: None,
                 })),
             });
         }
@@ -363,6 +461,16 @@ pub(crate) fn init(mxlsrc: &MxlSrc) -> Result<(), gst::ErrorMessage> {
                         ["Failed to initialize MXL discrete flow info: {}", e]
                     )
                 })?;
+            let flow_def = instance.get_flow_def_typed(flow_id.as_str()).map_err(|e| {
+                gst::error_msg!(
+                    gst::CoreError::Failed,
+                    ["Failed to read MXL flow definition: {}", e]
+                )
+            })?;
+            let format = match flow_def.media_type.as_str() {
+                KLV_MEDIA_TYPE => DiscreteFormat::Klv,
+                _ => DiscreteFormat::Data,
+            };
             let grain_reader = reader.to_grain_reader().map_err(|e| {
                 gst::error_msg!(
                     gst::CoreError::Failed,
@@ -372,20 +480,90 @@ pub(crate) fn init(mxlsrc: &MxlSrc) -> Result<(), gst::ErrorMessage> {
 
             context.state = Some(State {
                 instance,
+                domain: domain.clone(),
                 flow_state: Some(FlowState::Discrete(DiscreteState {
-                    format: DiscreteFormat::Data,
+                    format,
                     grain_rate,
                     index: 0,
+                    read_offset,
+                    timeouts,
                     is_initialized: false,
                     next_discont: false,
-                    grain_reader,
+                    grain_reader: SharedGrainReader::new(grain_reader),
+                    open_options,
+                    last_pushed: None,
+                    last_buffer: None,
+                    ancillary: Vec::new(),
+                    index_timecode: false,
                 })),
             });
         }
     }
+    context.last_data = Some(Instant::now());
     Ok(())
 }
 
+/// Reader of the `video/smpte291` flow of `ancillary-flow-id`, `caption-flow-id` or
+/// `timecode-flow-id`, waiting for it like the video flow.
+fn init_ancillary_reader(
+    mxlsrc: &MxlSrc,
+    instance: &MxlInstance,
+    flow_id: &str,
+    meta: AttachedMeta,
+    deadline: Option<Instant>,
+) -> Result<AncillaryReader, gst::ErrorMessage> {
+    let reader = init_mxl_reader(mxlsrc, instance, flow_id, deadline)?;
+    let flow_def = instance.get_flow_def_typed(flow_id).map_err(|e| {
+        gst::error_msg!(
+            gst::CoreError::Failed,
+            ["Failed to read MXL flow definition: {}", e]
+        )
+    })?;
+    if flow_def.media_type != SMPTE291_MEDIA_TYPE {
+        return Err(gst::error_msg!(
+            gst::CoreError::Failed,
+            [
+                "{} must be a {} flow, not {}",
+                match meta {
+                    AttachedMeta::Ancillary => "ancillary-flow-id",
+                    AttachedMeta::Captions => "caption-flow-id",
+                    AttachedMeta::Timecode => "timecode-flow-id",
+                },
+                SMPTE291_MEDIA_TYPE,
+                flow_def.media_type
+            ]
+        ));
+    }
+    let grain_rate = reader
+        .get_info()
+        .map_err(|e| {
+            gst::error_msg!(
+                gst::CoreError::Failed,
+                ["Failed to initialize MXL reader info: {}", e]
+            )
+        })?
+        .config
+        .common()
+        .grain_rate()
+        .map_err(|e| {
+            gst::error_msg!(
+                gst::CoreError::Failed,
+                ["Failed to initialize MXL discrete flow info: {}", e]
+            )
+        })?;
+    let grain_reader = reader.to_grain_reader().map_err(|e| {
+        gst::error_msg!(
+            gst::CoreError::Failed,
+            ["Failed to initialize MXL grain reader: {}", e]
+        )
+    })?;
+    Ok(AncillaryReader {
+        grain_reader,
+        grain_rate,
+        meta,
+    })
+}
+
 fn init_mxl_instance(domain: &str) -> Result<MxlInstance, gst::ErrorMessage> {
     let mxl_api = mxl::load_api(get_mxl_so_path())
         .map_err(|e| gst::error_msg!(gst::CoreError::Failed, ["Failed to load MXL API: {}", e]))?;
@@ -20,17 +20,166 @@ mod tests {
         let element = gst::ElementFactory::make("mxlsrc")
             .property("video-flow-id", "test_flow")
             .property("domain", "mydomain")
+            .property("min-slices", 540u32)
+            .property("batch-size", 256u32)
+            .property("latency-time", 10_000u32)
+            .property("read-offset", 2u32)
+            .property("grain-timeout", 40u32)
+            .property("sample-timeout", 20u32)
+            .property("producer-timeout", 0u32)
+            .property("max-reconnect-attempts", 3u32)
+            .property("reconnect-interval", 250u32)
+            .property("eos-on-stale", true)
+            .property_from_str("gap-mode", "repeat")
+            .property_from_str("catch-up-mode", "always-latest")
+            .property("open-options", "priority=3")
+            .property("provide-clock", true)
             .build()
             .map_err(|e| glib::Error::new(CoreError::Failed, &e.message))?;
 
         let flow_id: String = element.property("video-flow-id");
         let domain: String = element.property("domain");
+        let min_slices: u32 = element.property("min-slices");
+        let batch_size: u32 = element.property("batch-size");
+        let gap_mode: crate::mxlsrc::GapMode = element.property("gap-mode");
+        let open_options: String = element.property("open-options");
 
         assert_eq!(flow_id, "test_flow");
         assert_eq!(domain, "mydomain");
+        assert_eq!(min_slices, 540);
+        assert_eq!(batch_size, 256);
+        assert_eq!(element.property::<u32>("latency-time"), 10_000);
+        assert_eq!(element.property::<u32>("read-offset"), 2);
+        assert_eq!(element.property::<u32>("grain-timeout"), 40);
+        assert_eq!(element.property::<u32>("sample-timeout"), 20);
+        assert_eq!(element.property::<u32>("producer-timeout"), 0);
+        assert_eq!(element.property::<u32>("max-reconnect-attempts"), 3);
+        assert_eq!(element.property::<u32>("reconnect-interval"), 250);
+        assert!(element.property::<bool>("eos-on-stale"));
+        assert_eq!(gap_mode, crate::mxlsrc::GapMode::Repeat);
+        assert_eq!(
+            element.property::<crate::mxlsrc::CatchUpMode>("catch-up-mode"),
+            crate::mxlsrc::CatchUpMode::AlwaysLatest
+        );
+        assert_eq!(open_options, "priority=3");
+        assert!(element.property::<bool>("provide-clock"));
+        assert!(
+            element
+                .element_flags()
+                .contains(gst::ElementFlags::PROVIDE_CLOCK)
+        );
         Ok(())
     }
 
+    #[test]
+    fn partial_grain_min_slices_maps_zero_to_complete_grains() {
+        use crate::mxlsrc::create_discrete::partial_grain_min_slices;
+
+        assert_eq!(partial_grain_min_slices(0), None);
+        assert_eq!(partial_grain_min_slices(540), Some(540));
+    }
+
+    #[test]
+    fn missed_grains_spans_indices_between_reads() {
+        use crate::mxlsrc::gaps::missed_grains;
+
+        assert_eq!(missed_grains(10, 10), None);
+        assert_eq!(missed_grains(10, 9), None);
+        assert_eq!(missed_grains(10, 13), Some(10..13));
+    }
+
+    #[test]
+    fn repeated_grain_carries_its_own_timing_and_meta() -> Result<(), glib::Error> {
+        use crate::mxlsrc::gaps::{REPEATED_GRAIN_META, register_meta, repeated_grain};
+
+        gst::init()?;
+        register_meta();
+        let mut previous = gst::Buffer::from_slice(vec![0u8; 16]);
+        previous
+            .get_mut()
+            .expect("writable buffer")
+            .set_flags(gst::BufferFlags::DISCONT);
+
+        let period = gst::ClockTime::from_mseconds(40);
+        let repeated = repeated_grain(&previous, 7, 8, gst::ClockTime::from_mseconds(320), period)
+            .expect("repeated grain");
+        assert_eq!(repeated.pts(), Some(gst::ClockTime::from_mseconds(320)));
+        assert_eq!(repeated.duration(), Some(period));
+        assert!(!repeated.flags().contains(gst::BufferFlags::DISCONT));
+        let meta = gst::meta::CustomMeta::from_buffer(&repeated, REPEATED_GRAIN_META)
+            .expect("repeated grain meta");
+        assert_eq!(meta.structure().get::<u64>("index").ok(), Some(8));
+        assert_eq!(meta.structure().get::<u64>("repeated-index").ok(), Some(7));
+        Ok(())
+    }
+
+    #[test]
+    fn batch_size_follows_hints_unless_locked() {
+        use crate::mxlsrc::create_continuous::batch_size;
+
+        assert_eq!(batch_size(0, 4800, 32), 32);
+        assert_eq!(batch_size(0, 4800, 1024), 48);
+        assert_eq!(batch_size(0, 4800, 0), 1);
+        assert_eq!(batch_size(256, 4800, 32), 256);
+        assert_eq!(batch_size(4000, 4800, 32), 2400);
+    }
+
+    #[test]
+    fn latency_time_rounds_up_to_producer_commits() {
+        use crate::mxlsrc::create_continuous::requested_batch_size;
+        use mxl::rates::HZ_48000;
+
+        // `batch-size` wins, and neither set follows the hints.
+        assert_eq!(requested_batch_size(256, 10_000, &HZ_48000, 32), 256);
+        assert_eq!(requested_batch_size(0, 0, &HZ_48000, 32), 0);
+        // 10 ms are 480 samples, 15 commits of 32.
+        assert_eq!(requested_batch_size(0, 10_000, &HZ_48000, 32), 480);
+        assert_eq!(requested_batch_size(0, 10_000, &HZ_48000, 100), 500);
+        assert_eq!(requested_batch_size(0, 10_000, &HZ_48000, 0), 480);
+        assert_eq!(requested_batch_size(0, 1, &HZ_48000, 0), 1);
+    }
+
+    #[test]
+    fn grain_to_sample_matches_src_buffers() {
+        use crate::bridge::grain_to_sample;
+        use mxl::{
+            GrainData,
+            flowdef::{FlowDefDetails, FlowDefVideo, InterlaceMode, Rate},
+        };
+
+        gst::init().unwrap();
+        let flow_def = FlowDefDetails::Video(FlowDefVideo {
+            grain_rate: Rate {
+                numerator: 25,
+                denominator: 1,
+            },
+            frame_width: 48,
+            frame_height: 2,
+            interlace_mode: InterlaceMode::Progressive,
+            colorspace: "BT709".into(),
+            components: vec![],
+        });
+        let payload = [7u8; 256];
+        let grain = GrainData {
+            payload: &payload,
+            total_size: payload.len(),
+            flags: 0,
+            valid_slices: 2,
+            total_slices: 2,
+            index: 100,
+        };
+
+        let pts = gst::ClockTime::from_seconds(4);
+        let sample = grain_to_sample(&grain, &flow_def, "video/v210", pts).unwrap();
+        let buffer = sample.buffer().unwrap();
+        assert_eq!(buffer.pts(), Some(pts));
+        assert_eq!(buffer.duration(), Some(gst::ClockTime::from_mseconds(40)));
+        assert_eq!(buffer.map_readable().unwrap().as_slice(), &payload);
+        let caps = sample.caps().unwrap().structure(0).unwrap();
+        assert_eq!(caps.name(), "video/x-raw");
+        assert_eq!(caps.get::<i32>("width").unwrap(), 48);
+    }
+
     #[test]
     #[cfg_attr(feature = "tracing", tracing_test::traced_test)]
     fn start_valid_pipeline() -> Result<(), glib::Error> {
@@ -114,4 +263,88 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn provide_clock_runs_on_mxl_time() -> Result<(), glib::Error> {
+        gst::init()?;
+        gst::Element::register(None, "mxlsrc", gst::Rank::NONE, MxlSrc::type_())
+            .map_err(|e| glib::Error::new(CoreError::Failed, &e.message))?;
+        let guard = mxl_test_utils::TestDomainGuard::new("mxlsrc_provide_clock");
+
+        let pipeline = gst::parse::launch(&format!(
+            "mxlsrc video-flow-id=5fbd2d2c-8a0b-4f6e-9f7a-1f4c0c6a2b11 domain={} \
+               provide-clock=true ! fakesink",
+            guard.domain()
+        ))
+        .map_err(|e| glib::Error::new(CoreError::Failed, &e.to_string()))?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| glib::Error::new(CoreError::Failed, "Not a pipeline"))?;
+
+        // Without a flow the reader waits, but the pipeline still selects a clock.
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|_| glib::Error::new(CoreError::Failed, "Pipeline state change failed"))?;
+        thread::sleep(Duration::from_millis(200));
+        let clock = pipeline.clock();
+        pipeline
+            .set_state(gst::State::Null)
+            .map_err(|_| glib::Error::new(CoreError::Failed, "Pipeline state change failed"))?;
+
+        let clock = clock.expect("pipeline clock");
+        assert_eq!(clock.type_().name(), "GstMxlClock");
+        // MXL time is TAI, currently 37 s ahead of the UTC-based Unix time.
+        let unix_now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time after the Unix epoch");
+        let mxl_now = Duration::from_nanos(clock.time().nseconds());
+        assert!(
+            mxl_now.abs_diff(unix_now) < Duration::from_secs(60),
+            "MXL clock at {mxl_now:?}, Unix time {unix_now:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_flow_fails_after_flow_wait_timeout() -> Result<(), glib::Error> {
+        gst::init()?;
+        gst::Element::register(None, "mxlsrc", gst::Rank::NONE, MxlSrc::type_())
+            .map_err(|e| glib::Error::new(CoreError::Failed, &e.message))?;
+        let guard = mxl_test_utils::TestDomainGuard::new("mxlsrc_flow_wait_timeout");
+
+        let pipeline = gst::parse::launch(&format!(
+            "mxlsrc video-flow-id=0e4c5d8e-3f0a-4b71-9d6a-5a1c2e7f9b30 domain={} \
+               flow-wait-timeout=200 ! fakesink",
+            guard.domain()
+        ))
+        .map_err(|e| glib::Error::new(CoreError::Failed, &e.to_string()))?;
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|_| glib::Error::new(CoreError::Failed, "Pipeline state change failed"))?;
+        let started = std::time::Instant::now();
+        let error = pipeline
+            .bus()
+            .expect("pipeline bus")
+            .timed_pop_filtered(gst::ClockTime::from_seconds(5), &[gst::MessageType::Error]);
+        let waited = started.elapsed();
+        pipeline
+            .set_state(gst::State::Null)
+            .map_err(|_| glib::Error::new(CoreError::Failed, "Pipeline state change failed"))?;
+
+        let Some(error) = error else {
+            panic!("no error for a missing flow");
+        };
+        let gst::MessageView::Error(error) = error.view() else {
+            unreachable!();
+        };
+        assert!(
+            error.error().matches(gst::ResourceError::NotFound),
+            "unexpected error: {:?}",
+            error.error()
+        );
+        assert!(
+            waited >= Duration::from_millis(150),
+            "failed after {waited:?}"
+        );
+        Ok(())
+    }
 }
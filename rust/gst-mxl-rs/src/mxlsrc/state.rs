@@ -1,17 +1,94 @@
 // SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
 // SPDX-License-Identifier: Apache-2.0
 
-use mxl::{FlowReader, GrainReader, MxlInstance, Rational, SamplesReader};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use gstreamer as gst;
+use mxl::{AccessOptions, FlowReader, MxlInstance, Rational, SamplesReader};
+
+use crate::format::audio::AudioFormat;
+use crate::mxlsrc::{
+    ancillary::AncillaryReader, catch_up::CatchUpMode, gaps::GapMode, zero_copy::SharedGrainReader,
+};
 
 pub(crate) const DEFAULT_FLOW_ID: &str = "";
 pub(crate) const DEFAULT_DOMAIN: &str = "";
+pub(crate) const DEFAULT_MIN_SLICES: u32 = 0;
+pub(crate) const DEFAULT_BATCH_SIZE: u32 = 0;
+pub(crate) const DEFAULT_LATENCY_TIME: u32 = 0;
+pub(crate) const DEFAULT_READ_OFFSET: u32 = 0;
+pub(crate) const DEFAULT_GRAIN_TIMEOUT: u32 = 5_000;
+pub(crate) const DEFAULT_SAMPLE_TIMEOUT: u32 = 2_000;
+pub(crate) const DEFAULT_PRODUCER_TIMEOUT: u32 = 100;
+pub(crate) const DEFAULT_WAIT_FOR_FLOW: bool = true;
+pub(crate) const DEFAULT_FLOW_WAIT_TIMEOUT: u32 = 0;
+pub(crate) const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 0;
+pub(crate) const DEFAULT_RECONNECT_INTERVAL: u32 = 1_000;
+pub(crate) const DEFAULT_EOS_ON_STALE: bool = false;
+pub(crate) const DEFAULT_GAP_MODE: GapMode = GapMode::Gap;
+pub(crate) const DEFAULT_CATCH_UP_MODE: CatchUpMode = CatchUpMode::Auto;
+pub(crate) const DEFAULT_ZERO_COPY: bool = false;
+pub(crate) const DEFAULT_TIMECODE: bool = false;
+pub(crate) const DEFAULT_PROVIDE_CLOCK: bool = false;
 
 #[derive(Debug, Clone)]
 pub struct Settings {
     pub video_flow: Option<String>,
     pub audio_flow: Option<String>,
     pub data_flow: Option<String>,
+    /// `video/smpte291` data flow whose grains are attached to the video buffers as
+    /// `GstAncillaryMeta`, see `mxlsrc/ancillary.rs`.
+    pub ancillary_flow: Option<String>,
+    /// `video/smpte291` data flow whose caption packets are attached to the video buffers as
+    /// `GstVideoCaptionMeta`.
+    pub caption_flow: Option<String>,
+    /// `video/smpte291` data flow whose ancillary time code is attached to the video buffers as
+    /// `GstVideoTimeCodeMeta`.
+    pub timecode_flow: Option<String>,
+    /// Attach the timecode of their grain index to video buffers without a stored one.
+    pub timecode: bool,
     pub domain: String,
+    /// Slices (lines) of a video grain that must be valid before it is pushed; `0` waits for
+    /// complete grains.
+    pub min_slices: u32,
+    /// Samples per audio buffer; `0` follows the producer's batch size hints.
+    pub batch_size: u32,
+    /// Microseconds of samples per audio buffer when `batch_size` is `0`; `0` follows the
+    /// producer's batch size hints.
+    pub latency_time: u32,
+    /// Grains or samples to read behind the head of the flow, on top of the batch read for audio.
+    pub read_offset: u32,
+    /// Milliseconds to wait for a video or data grain.
+    pub grain_timeout: u32,
+    /// Milliseconds to wait for a batch of audio samples.
+    pub sample_timeout: u32,
+    /// Milliseconds an audio reader ahead of the producer polls its head before reading anyway.
+    pub producer_timeout: u32,
+    /// Wait for flows that do not exist yet when attaching instead of failing.
+    pub wait_for_flow: bool,
+    /// Milliseconds to wait for flows that do not exist yet; `0` waits until the source stops.
+    pub flow_wait_timeout: u32,
+    /// Reattaches in a row, without a buffer pushed in between, before a flow recreated under the
+    /// reader is an error; `0` for no limit.
+    pub max_reconnect_attempts: u32,
+    /// Milliseconds to wait before reattaching to a recreated flow.
+    pub reconnect_interval: u32,
+    /// End the stream once the flow is stale, recreated or without a new grain or sample for
+    /// `grain_timeout` or `sample_timeout`, instead of reattaching or waiting.
+    pub eos_on_stale: bool,
+    /// What to push for video grains the reader missed.
+    pub gap_mode: GapMode,
+    /// Where a reader behind the head of the flow resumes.
+    pub catch_up_mode: CatchUpMode,
+    /// Comma separated `key=value` options of every grain or samples read, see
+    /// [`AccessOptions::parse`].
+    pub open_options: String,
+    /// Push video buffers wrapping the grains in the ring instead of copies, see
+    /// `mxlsrc/zero_copy.rs`.
+    pub zero_copy: bool,
+    /// Offer the pipeline an `MxlClock` running on MXL time, see `mxlclock`.
+    pub provide_clock: bool,
 }
 
 impl Default for Settings {
@@ -20,7 +97,28 @@ impl Default for Settings {
             video_flow: None,
             audio_flow: None,
             data_flow: None,
+            ancillary_flow: None,
+            caption_flow: None,
+            timecode_flow: None,
+            timecode: DEFAULT_TIMECODE,
             domain: DEFAULT_DOMAIN.to_owned(),
+            min_slices: DEFAULT_MIN_SLICES,
+            batch_size: DEFAULT_BATCH_SIZE,
+            latency_time: DEFAULT_LATENCY_TIME,
+            read_offset: DEFAULT_READ_OFFSET,
+            grain_timeout: DEFAULT_GRAIN_TIMEOUT,
+            sample_timeout: DEFAULT_SAMPLE_TIMEOUT,
+            producer_timeout: DEFAULT_PRODUCER_TIMEOUT,
+            wait_for_flow: DEFAULT_WAIT_FOR_FLOW,
+            flow_wait_timeout: DEFAULT_FLOW_WAIT_TIMEOUT,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            reconnect_interval: DEFAULT_RECONNECT_INTERVAL,
+            eos_on_stale: DEFAULT_EOS_ON_STALE,
+            gap_mode: DEFAULT_GAP_MODE,
+            catch_up_mode: DEFAULT_CATCH_UP_MODE,
+            open_options: String::new(),
+            zero_copy: DEFAULT_ZERO_COPY,
+            provide_clock: DEFAULT_PROVIDE_CLOCK,
         }
     }
 }
@@ -40,10 +138,39 @@ impl Settings {
             .or(self.audio_flow.as_ref())
             .or(self.data_flow.as_ref())
     }
+
+    /// Until when attaching at `now` waits for flows that do not exist yet: `now` without
+    /// `wait-for-flow`, `None` for no limit.
+    pub(crate) fn flow_wait_deadline(&self, now: Instant) -> Option<Instant> {
+        match (self.wait_for_flow, self.flow_wait_timeout) {
+            (false, _) => Some(now),
+            (true, 0) => None,
+            (true, timeout) => Some(now + Duration::from_millis(timeout.into())),
+        }
+    }
+
+    pub(crate) fn read_timeouts(&self) -> ReadTimeouts {
+        ReadTimeouts {
+            grain: Duration::from_millis(self.grain_timeout.into()),
+            sample: Duration::from_millis(self.sample_timeout.into()),
+            producer: Duration::from_millis(self.producer_timeout.into()),
+        }
+    }
+}
+
+/// How long reads wait for the producer, from `grain-timeout`, `sample-timeout` and
+/// `producer-timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadTimeouts {
+    pub grain: Duration,
+    pub sample: Duration,
+    pub producer: Duration,
 }
 
 pub struct State {
     pub instance: MxlInstance,
+    /// Domain path the instance was opened on, kept to diagnose read failures.
+    pub domain: String,
     /// Reader state after attach; `None` until the flow is ready.
     pub flow_state: Option<FlowState>,
 }
@@ -58,6 +185,7 @@ pub enum FlowState {
 pub enum DiscreteFormat {
     Video,
     Data,
+    Klv,
 }
 
 pub struct DiscreteState {
@@ -65,9 +193,22 @@ pub struct DiscreteState {
     pub grain_rate: Rational,
     /// Next absolute MXL grain index to read.
     pub index: u64,
+    /// Grains behind the head the reader attaches at, see `timing::discrete_read_offset`.
+    pub read_offset: u32,
+    pub timeouts: ReadTimeouts,
     pub is_initialized: bool,
     pub next_discont: bool,
-    pub grain_reader: GrainReader,
+    pub grain_reader: Arc<SharedGrainReader>,
+    pub open_options: AccessOptions,
+    /// Index of the last grain pushed, the start of a gap to the next one.
+    pub last_pushed: Option<u64>,
+    /// Buffer of the last video grain pushed, kept for [`GapMode::Repeat`].
+    pub last_buffer: Option<gst::Buffer>,
+    /// Readers of the data flows attached to video buffers, for `ancillary-flow-id`,
+    /// `caption-flow-id` and `timecode-flow-id`.
+    pub ancillary: Vec<AncillaryReader>,
+    /// Video buffers without a `GstVideoTimeCodeMeta` get the timecode of their grain index.
+    pub index_timecode: bool,
 }
 
 pub struct ContinuousState {
@@ -75,7 +216,22 @@ pub struct ContinuousState {
     pub samples_reader: SamplesReader,
     pub is_initialized: bool,
     pub index: u64,
+    /// Samples behind the head the reader attaches and catches up at, on top of a batch.
+    pub read_offset: u32,
+    pub timeouts: ReadTimeouts,
     pub next_discont: bool,
+    /// Format of the interleaved buffers pushed.
+    pub format: AudioFormat,
+    pub open_options: AccessOptions,
+    /// Producer's batch size hints as of the previous buffer.
+    pub batch_hints: Option<BatchHints>,
+}
+
+/// Commit and sync batch size hints of the producer, in samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchHints {
+    pub commit: u32,
+    pub sync: u32,
 }
 
 #[derive(Default)]
@@ -84,4 +240,8 @@ pub struct Context {
     /// conversions can share it. Cheap to clone (`Arc`-backed).
     pub instance: Option<MxlInstance>,
     pub state: Option<State>,
+    /// Reattaches to a recreated flow since the last buffer pushed.
+    pub reconnect_attempts: u32,
+    /// When the last buffer was pushed, or the flow attached if none was since.
+    pub last_data: Option<Instant>,
 }
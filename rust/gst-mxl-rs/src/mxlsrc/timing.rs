@@ -19,8 +19,19 @@
 //! — while the PTS still lands in the pipeline's running-time base so live
 //! elements can synchronise the flows against the pipeline clock.
 
+use std::ops::{ControlFlow, Range};
+use std::sync::LazyLock;
+
 use gstreamer as gst;
-use mxl::{GrainReader, MxlInstance, Rational};
+use mxl::{GrainReader, MxlInstance, Rational, RationalExt};
+
+/// Reference of the `GstReferenceTimestampMeta` on every `mxlsrc` buffer: the
+/// MXL timestamp of its grain or first sample, TAI nanoseconds since the
+/// ST 2059 epoch, which is the PTP timescale.
+pub(crate) const TAI_REFERENCE: &str = "timestamp/x-ptp";
+
+static TAI_REFERENCE_CAPS: LazyLock<gst::Caps> =
+    LazyLock::new(|| gst::Caps::new_empty_simple(TAI_REFERENCE));
 
 /// Oldest absolute grain index still retained in a ring of `grain_count` grains
 /// whose newest committed grain is `head`.
@@ -28,6 +39,12 @@ pub(crate) fn oldest_retained_index(head: u64, grain_count: u32) -> u64 {
     head.saturating_sub((grain_count as u64).saturating_sub(1))
 }
 
+/// Grains behind the head a discrete reader attaches at for a `read-offset` of `read_offset`, at
+/// most half a ring of `grain_count` grains so the grain read stays well inside the ring.
+pub(crate) fn discrete_read_offset(read_offset: u32, grain_count: u32) -> u64 {
+    u64::from(read_offset.min(grain_count / 2))
+}
+
 /// What a discrete reader should do for its next grain.
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum ReadStep {
@@ -114,13 +131,70 @@ pub(crate) fn pts_for_index(
 
 /// One grain/sample period at `rate`.
 pub(crate) fn index_period(rate: &Rational) -> gst::ClockTime {
-    let period_ns = (1_000_000_000u128 * rate.denominator as u128 / rate.numerator as u128) as u64;
+    let period_ns = rate.period().map_or(0, |period| period.as_nanos() as u64);
     gst::ClockTime::from_nseconds(period_ns)
 }
 
+/// Min and max live latency of a reader at `rate` whose buffers hold data at least `lag` grains
+/// or samples old when pushed, and which falls behind the ring once its data is `history` of
+/// them old.
+pub(crate) fn ring_latency(
+    lag: u64,
+    history: u64,
+    rate: &Rational,
+) -> (gst::ClockTime, gst::ClockTime) {
+    let duration = |count| {
+        gst::ClockTime::from_nseconds(
+            rate.duration_of(count)
+                .map_or(0, |duration| duration.as_nanos() as u64),
+        )
+    };
+    (duration(lag), duration(history.max(lag)))
+}
+
+/// Attaches the MXL timestamp of `indices` at `rate` to `buffer` as a
+/// `GstReferenceTimestampMeta`, so downstream can relate the buffer to the
+/// house clock whatever the pipeline's running time. Replaces the timestamp of
+/// a buffer `buffer` was copied from.
+pub(crate) fn set_tai_reference(
+    instance: &MxlInstance,
+    buffer: &mut gst::BufferRef,
+    indices: Range<u64>,
+    rate: &Rational,
+) -> Result<(), gst::FlowError> {
+    let timestamp = |index| {
+        instance
+            .index_to_timestamp(index, rate)
+            .map_err(|_| gst::FlowError::Error)
+    };
+    let start = timestamp(indices.start)?;
+    let end = timestamp(indices.end)?;
+    buffer.foreach_meta_mut(|meta| {
+        let is_tai = meta
+            .downcast_ref::<gst::ReferenceTimestampMeta>()
+            .is_some_and(|meta| meta.reference().is_strictly_equal(&TAI_REFERENCE_CAPS));
+        ControlFlow::Continue(if is_tai {
+            gst::BufferMetaForeachAction::Remove
+        } else {
+            gst::BufferMetaForeachAction::Keep
+        })
+    });
+    gst::ReferenceTimestampMeta::add(
+        buffer,
+        &TAI_REFERENCE_CAPS,
+        gst::ClockTime::from_nseconds(start),
+        gst::ClockTime::from_nseconds(end.saturating_sub(start)),
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ReadStep, oldest_retained_index, resolve_read_step};
+    use super::{
+        ReadStep, discrete_read_offset, oldest_retained_index, resolve_read_step, ring_latency,
+    };
+    use gstreamer as gst;
+    use mxl::rates;
 
     const GRAIN_COUNT: u32 = 300;
 
@@ -135,6 +209,13 @@ mod tests {
         assert_eq!(oldest_retained_index(400, GRAIN_COUNT), 101);
     }
 
+    #[test]
+    fn read_offset_stays_within_half_the_ring() {
+        assert_eq!(discrete_read_offset(0, GRAIN_COUNT), 0);
+        assert_eq!(discrete_read_offset(3, GRAIN_COUNT), 3);
+        assert_eq!(discrete_read_offset(1_000, GRAIN_COUNT), 150);
+    }
+
     #[test]
     fn waits_before_first_commit() {
         // head == 0 means the producer has not committed anything yet.
@@ -215,4 +296,21 @@ mod tests {
             resolve_read_step(grain, head, GRAIN_COUNT)
         );
     }
+
+    #[test]
+    fn ring_latency_spans_lag_to_history() {
+        let (min, max) = ring_latency(1, u64::from(GRAIN_COUNT), &rates::FPS_25);
+        assert_eq!(min, gst::ClockTime::from_mseconds(40));
+        assert_eq!(max, gst::ClockTime::from_seconds(12));
+
+        let (min, max) = ring_latency(48, 4_800 - 48, &rates::HZ_48000);
+        assert_eq!(min, gst::ClockTime::from_mseconds(1));
+        assert_eq!(max, gst::ClockTime::from_mseconds(99));
+    }
+
+    #[test]
+    fn ring_latency_max_is_never_below_min() {
+        let (min, max) = ring_latency(4, 2, &rates::FPS_25);
+        assert_eq!(min, max);
+    }
 }
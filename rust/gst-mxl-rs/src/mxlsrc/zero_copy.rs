@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Video buffers wrapping the grain payload in the MXL ring instead of a copy, for the
+//! `zero-copy` property of `mxlsrc`.
+//!
+//! The buffer's memory is read-only and owns a reference to the grain reader, so the flow stays
+//! mapped until the last buffer is freed, even after the element stopped. The memory is the
+//! writer's ring slot though: the writer reuses it once it has written as many grains as the ring
+//! holds. Downstream must be done with a buffer within the ring's history, or it sees a later
+//! frame.
+
+use std::{ops::Deref, sync::Arc};
+
+use gstreamer as gst;
+use mxl::GrainReader;
+
+/// Grain reader shared between the element and the buffers wrapping its grains.
+pub(crate) struct SharedGrainReader(GrainReader);
+
+// SAFETY: the buffers only hold the reader to keep the flow mapped and never call it, so only
+// the element's streaming thread uses it, as with an unshared reader.
+unsafe impl Sync for SharedGrainReader {}
+
+impl SharedGrainReader {
+    pub(crate) fn new(reader: GrainReader) -> Arc<Self> {
+        Arc::new(Self(reader))
+    }
+}
+
+impl Deref for SharedGrainReader {
+    type Target = GrainReader;
+
+    fn deref(&self) -> &GrainReader {
+        &self.0
+    }
+}
+
+/// Payload of a grain in the mapped flow, valid as long as its reader is.
+struct MappedGrain {
+    _reader: Arc<SharedGrainReader>,
+    data: *const u8,
+    len: usize,
+}
+
+// SAFETY: the payload is plain memory of the mapped flow, and the reader is `Send + Sync`.
+unsafe impl Send for MappedGrain {}
+
+impl AsRef<[u8]> for MappedGrain {
+    fn as_ref(&self) -> &[u8] {
+        // SAFETY: `data` and `len` come from a grain payload of `_reader`, whose mapping lives as
+        // long as the reader.
+        unsafe { std::slice::from_raw_parts(self.data, self.len) }
+    }
+}
+
+/// Read-only buffer over `payload`, a grain read from `reader`.
+pub(crate) fn wrap_grain(reader: &Arc<SharedGrainReader>, payload: &[u8]) -> gst::Buffer {
+    gst::Buffer::from_slice(MappedGrain {
+        _reader: reader.clone(),
+        data: payload.as_ptr(),
+        len: payload.len(),
+    })
+}
@@ -7,7 +7,9 @@
 //! `/dev/shm` and read back through `mxlsrc`. The continuous path shares the
 //! clock/offset/pacing machinery with the discrete video and data paths, so
 //! this guards that the audio side survives those changes: the reader must
-//! deliver monotonic-PTS F32LE samples whose spectrum still peaks at 440 Hz.
+//! deliver monotonic-PTS samples whose spectrum still peaks at 440 Hz, for the
+//! F32LE, S16LE and S24_32LE formats of `audio/float32`, `audio/L16` and
+//! `audio/L24` flows, and for non-interleaved input to `mxlsink`.
 
 #[macro_use]
 mod common;
@@ -43,11 +45,19 @@ fn goertzel_power(samples: &[f32], sample_rate: f64, freq: f64) -> f64 {
     s1 * s1 + s2 * s2 - coeff * s1 * s2
 }
 
-/// Left channel of an interleaved F32LE stereo buffer.
-fn left_channel(interleaved: &[u8]) -> Vec<f32> {
+/// Left channel of an interleaved stereo buffer of `format`, at full scale 1.0.
+fn left_channel(interleaved: &[u8], format: &str) -> Vec<f32> {
+    let sample_size = if format == "S16LE" { 2 } else { 4 };
     interleaved
-        .chunks_exact(std::mem::size_of::<f32>() * CHANNELS as usize)
-        .map(|frame| f32::from_le_bytes(frame[..4].try_into().expect("4-byte f32")))
+        .chunks_exact(sample_size * CHANNELS as usize)
+        .map(|frame| match format {
+            "F32LE" => f32::from_le_bytes(frame[..4].try_into().expect("4-byte f32")),
+            "S16LE" => f32::from(i16::from_le_bytes([frame[0], frame[1]])) / 32768.0,
+            _ => {
+                let sample = i32::from_le_bytes(frame[..4].try_into().expect("4-byte word"));
+                sample as f32 / (1 << 23) as f32
+            }
+        })
         .collect()
 }
 
@@ -68,6 +78,28 @@ impl Drop for RoundTrip {
 /// F32LE sine → `mxlsink` → MXL → `mxlsrc` → `appsink`, spectrum-checked at 440 Hz.
 #[test]
 fn f32le_tone_round_trip_via_mxl() {
+    check_tone_round_trip("F32LE", "interleaved");
+}
+
+#[test]
+fn s16le_tone_round_trip_via_mxl() {
+    check_tone_round_trip("S16LE", "interleaved");
+}
+
+/// 24-bit samples are packed to 3 bytes in the flow and sign-extended again.
+#[test]
+fn s24_32le_tone_round_trip_via_mxl() {
+    check_tone_round_trip("S24_32LE", "interleaved");
+}
+
+/// Planes are written to the channels as they are, `mxlsrc` interleaves them.
+#[test]
+fn non_interleaved_tone_round_trip_via_mxl() {
+    check_tone_round_trip("F32LE", "non-interleaved");
+}
+
+/// `layout` is that of the `mxlsink` input, `mxlsrc` always pushes interleaved buffers.
+fn check_tone_round_trip(format: &str, layout: &str) {
     init();
     const FACTORIES: &[&str] = &[
         "appsink",
@@ -83,11 +115,14 @@ fn f32le_tone_round_trip_via_mxl() {
     }
 
     let flow_id = uuid::Uuid::new_v4().to_string();
-    let domain_guard = common::TestDomainGuard::new("audio_round_trip");
+    let domain_guard = common::TestDomainGuard::new(&format!(
+        "audio_round_trip_{}_{layout}",
+        format.to_lowercase()
+    ));
     let domain = domain_guard.domain();
 
     // `audiotestsrc` defaults to S16LE; `audioconvert` + the capsfilter pin the
-    // F32LE/interleaved/rate/channels that `mxlsink` accepts. num-buffers bounds
+    // format/layout/rate/channels that `mxlsink` accepts. num-buffers bounds
     // the run at ~3.2 s of audio (150 × 1024 samples). is-live=true makes it
     // produce in real time rather than dumping all buffers at once, modelling a
     // real audio source: mxlsink can only hold a not-yet-due grain, not un-burst
@@ -96,7 +131,7 @@ fn f32le_tone_round_trip_via_mxl() {
     let producer_desc = format!(
         "audiotestsrc wave=sine freq={TONE_HZ} num-buffers=150 samplesperbuffer=1024 is-live=true \
            ! audioconvert \
-           ! audio/x-raw,format=F32LE,layout=interleaved,channels={CHANNELS},rate={SAMPLE_RATE} \
+           ! audio/x-raw,format={format},layout={layout},channels={CHANNELS},rate={SAMPLE_RATE} \
            ! queue \
            ! mxlsink flow-id={flow_id} domain={domain}"
     );
@@ -141,7 +176,7 @@ fn f32le_tone_round_trip_via_mxl() {
         let caps = sample.caps().expect("sample caps");
         let s = caps.structure(0).expect("caps structure");
         assert_eq!(s.name(), "audio/x-raw");
-        assert_eq!(s.get::<String>("format").as_deref(), Ok("F32LE"));
+        assert_eq!(s.get::<String>("format").as_deref(), Ok(format));
         assert_eq!(s.get::<i32>("rate"), Ok(SAMPLE_RATE as i32));
         assert_eq!(s.get::<i32>("channels"), Ok(CHANNELS as i32));
 
@@ -153,7 +188,7 @@ fn f32le_tone_round_trip_via_mxl() {
         prev_pts = Some(pts);
 
         let map = buffer.map_readable().expect("map readable");
-        mono.extend(left_channel(map.as_slice()));
+        mono.extend(left_channel(map.as_slice(), format));
     }
 
     assert_bus_no_errors(&rt);
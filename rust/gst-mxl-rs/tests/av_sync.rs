@@ -20,6 +20,8 @@
 //! mxlsrc (audio flow) ! queue ! appsink audio_sink (sync=false)
 //! ```
 //!
+//! The same check runs with both flows read by one `mxlavsrc` instead of two `mxlsrc`.
+//!
 //! Requires Linux `/dev/shm` (tmpfs); the aligned test sources are registered
 //! directly from the `gst-avsynctest-rs` dev-dependency.
 
@@ -156,7 +158,13 @@ fn drain_audio(sink: &gst_app::AppSink, done: &std::sync::atomic::AtomicBool) ->
     out
 }
 
-fn build(video_flow: &str, audio_flow: &str, domain: &str, is_live: bool) -> RoundTrip {
+fn build(
+    video_flow: &str,
+    audio_flow: &str,
+    domain: &str,
+    is_live: bool,
+    av_src: bool,
+) -> RoundTrip {
     let producer_desc = format!(
         "avsyncvideotestsrc is-live={is_live} num-buffers={NUM_FRAMES} \
              pip-interval={PIP_INTERVAL_NS} width={WIDTH} height={HEIGHT} \
@@ -175,11 +183,25 @@ fn build(video_flow: &str, audio_flow: &str, domain: &str, is_live: bool) -> Rou
         .downcast::<gst::Pipeline>()
         .expect("producer pipeline");
 
+    let (video_src, audio_src) = if av_src {
+        (
+            format!(
+                "mxlavsrc name=src video-flow-id={video_flow} audio-flow-id={audio_flow} \
+                   domain={domain} src.video_src"
+            ),
+            "src.audio_src".to_owned(),
+        )
+    } else {
+        (
+            format!("mxlsrc video-flow-id={video_flow} domain={domain}"),
+            format!("mxlsrc audio-flow-id={audio_flow} domain={domain}"),
+        )
+    };
     let consumer_desc = format!(
-        "mxlsrc video-flow-id={video_flow} domain={domain} \
+        "{video_src} \
            ! queue \
            ! appsink name=video_sink sync=false caps=video/x-raw,format=v210 \
-         mxlsrc audio-flow-id={audio_flow} domain={domain} \
+         {audio_src} \
            ! queue \
            ! appsink name=audio_sink sync=false caps=audio/x-raw,format=F32LE"
     );
@@ -276,12 +298,13 @@ fn assert_av_aligned(video: &[(u8, u64)], pips: &[u64]) {
     );
 }
 
-fn run_case(test: &str, is_live: bool) {
+fn run_case(test: &str, is_live: bool, av_src: bool) {
     init();
     const FACTORIES: &[&str] = &[
         "appsink",
         "avsyncaudiotestsrc",
         "avsyncvideotestsrc",
+        "mxlavsrc",
         "mxlsink",
         "mxlsrc",
         "queue",
@@ -295,7 +318,7 @@ fn run_case(test: &str, is_live: bool) {
     let domain_guard = common::TestDomainGuard::new(test);
     let domain = domain_guard.domain();
 
-    let rt = build(&video_flow, &audio_flow, &domain, is_live);
+    let rt = build(&video_flow, &audio_flow, &domain, is_live, av_src);
     rt.producer
         .set_state(gst::State::Playing)
         .expect("producer Playing");
@@ -328,12 +351,18 @@ fn run_case(test: &str, is_live: bool) {
 /// Live producer: both sources pace themselves against the pipeline clock.
 #[test]
 fn av_sync_live_via_mxl() {
-    run_case("av_sync_live", true);
+    run_case("av_sync_live", true, false);
 }
 
 /// Non-live producer: sources push as fast as possible; the sync=true mxlsinks
 /// pace the commits.
 #[test]
 fn av_sync_non_live_via_mxl() {
-    run_case("av_sync_non_live", false);
+    run_case("av_sync_non_live", false, false);
+}
+
+/// Live producer, both flows read through the `video_src` and `audio_src` pads of one `mxlavsrc`.
+#[test]
+fn av_sync_live_via_mxlavsrc() {
+    run_case("av_sync_live_avsrc", true, true);
 }
@@ -0,0 +1,191 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! GStreamer integration test for the caption flow of a video flow.
+//!
+//! v210 frames carrying a CEA-708 `GstVideoCaptionMeta` are written by one
+//! `mxlsink` with `caption-flow-id`, which puts the captions of each frame in a
+//! `video/smpte291` flow at the frame's grain index. An `mxlsrc` with the same
+//! `caption-flow-id` must hand every frame back with its own captions:
+//!
+//! ```text
+//! appsrc (v210 + GstVideoCaptionMeta) ! mxlsink flow-id=V caption-flow-id=C
+//! mxlsrc video-flow-id=V caption-flow-id=C ! queue ! appsink
+//! ```
+
+#[macro_use]
+mod common;
+
+use common::{
+    FRAME_PERIOD_NS, FRAMERATE_DEN, FRAMERATE_NUM, VIDEO_HEIGHT, VIDEO_WIDTH, assert_bus_no_errors,
+    collect_bus_errors, init, skip_reason,
+};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+
+/// Frames pushed, about 2 s of video.
+const PUSH_COUNT: usize = 60;
+/// Frames the reader may miss before it attaches to the flows.
+const ATTACH_SLACK: usize = 5;
+
+/// Caption bytes of producer frame `frame_idx`, which the test only needs to
+/// tell apart: a CDP header with the frame index as its sequence counter.
+fn cdp(frame_idx: u8) -> Vec<u8> {
+    vec![
+        0x96, 0x69, 0x0b, 0x4f, 0x43, 0x00, frame_idx, 0x74, 0x00, frame_idx, 0x00,
+    ]
+}
+
+/// Stops the writer then the reader on drop (including on panic), so no MXL
+/// worker thread outlives the domain dir.
+struct RoundTrip {
+    producer: gst::Pipeline,
+    consumer: gst::Pipeline,
+}
+
+impl Drop for RoundTrip {
+    fn drop(&mut self) {
+        let _ = self.producer.set_state(gst::State::Null);
+        let _ = self.consumer.set_state(gst::State::Null);
+    }
+}
+
+/// Push the frames at frame cadence, stamping the frame index into byte 0 of
+/// the v210 payload and into the CDP of its caption meta.
+fn push_frames(appsrc: &gst_app::AppSrc, frame_bytes: usize) {
+    let frame_period = std::time::Duration::from_nanos(FRAME_PERIOD_NS);
+    let start = std::time::Instant::now();
+    for i in 0..PUSH_COUNT {
+        let frame_idx = i as u8;
+        let mut buf = gst::Buffer::with_size(frame_bytes).expect("v210 buffer");
+        {
+            let b = buf.get_mut().expect("buffer mut");
+            b.set_pts(gst::ClockTime::from_nseconds(i as u64 * FRAME_PERIOD_NS));
+            b.set_duration(gst::ClockTime::from_nseconds(FRAME_PERIOD_NS));
+            b.map_writable().expect("buffer writable").as_mut_slice()[0] = frame_idx;
+            gst_video::video_meta::VideoCaptionMeta::add(
+                b,
+                gst_video::VideoCaptionType::Cea708Cdp,
+                &cdp(frame_idx),
+            );
+        }
+        if appsrc.push_buffer(buf).is_err() {
+            break;
+        }
+        if let Some(remaining) = (start + frame_period * (i as u32 + 1))
+            .checked_duration_since(std::time::Instant::now())
+        {
+            std::thread::sleep(remaining);
+        }
+    }
+}
+
+/// `mxlsink` writes the caption metas of the video buffers to the caption flow
+/// and `mxlsrc` attaches them again to the frames of the same grain index.
+#[test]
+fn caption_meta_round_trip_via_mxl() {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "appsrc", "mxlsink", "mxlsrc", "queue"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let video_flow_id = uuid::Uuid::new_v4().to_string();
+    let caption_flow_id = uuid::Uuid::new_v4().to_string();
+    let domain_guard = common::TestDomainGuard::new("caption_meta");
+    let domain = domain_guard.domain();
+
+    let video_info =
+        gst_video::VideoInfo::builder(gst_video::VideoFormat::V210, VIDEO_WIDTH, VIDEO_HEIGHT)
+            .fps(gst::Fraction::new(FRAMERATE_NUM, FRAMERATE_DEN))
+            .build()
+            .expect("v210 VideoInfo");
+    let frame_bytes = video_info.size();
+
+    let producer_desc = format!(
+        "appsrc name=src format=time caps={} \
+           ! mxlsink flow-id={video_flow_id} caption-flow-id={caption_flow_id} domain={domain}",
+        video_info.to_caps().expect("v210 caps")
+    );
+    let consumer_desc = format!(
+        "mxlsrc video-flow-id={video_flow_id} caption-flow-id={caption_flow_id} domain={domain} \
+           ! queue \
+           ! appsink name=sink sync=false"
+    );
+    let producer = gst::parse::launch(&producer_desc)
+        .expect("parse producer")
+        .downcast::<gst::Pipeline>()
+        .expect("producer pipeline");
+    let appsrc = producer
+        .by_name("src")
+        .expect("appsrc")
+        .downcast::<gst_app::AppSrc>()
+        .expect("AppSrc downcast");
+    let consumer = gst::parse::launch(&consumer_desc)
+        .expect("parse consumer")
+        .downcast::<gst::Pipeline>()
+        .expect("consumer pipeline");
+    let appsink = consumer
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+
+    let rt = RoundTrip { producer, consumer };
+    rt.producer
+        .set_state(gst::State::Playing)
+        .expect("producer Playing");
+    rt.consumer
+        .set_state(gst::State::Playing)
+        .expect("consumer Playing");
+    let pusher = std::thread::spawn(move || push_frames(&appsrc, frame_bytes));
+
+    let last_frame = (PUSH_COUNT - 1) as u8;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+    let mut frames = Vec::new();
+    let mut bare = Vec::new();
+    while frames.last() != Some(&last_frame) && std::time::Instant::now() < deadline {
+        let Some(sample) = appsink.try_pull_sample(gst::ClockTime::from_seconds(2)) else {
+            if appsink.is_eos() {
+                break;
+            }
+            continue;
+        };
+        let buffer = sample.buffer().expect("sample buffer");
+        let frame_idx = buffer.map_readable().expect("readable").as_slice()[0];
+        let captions: Vec<_> = buffer
+            .iter_meta::<gst_video::video_meta::VideoCaptionMeta>()
+            .map(|meta| (meta.caption_type(), meta.data().to_vec()))
+            .collect();
+        match captions.as_slice() {
+            [] => bare.push(frame_idx),
+            [(caption_type, data)] => {
+                assert_eq!(*caption_type, gst_video::VideoCaptionType::Cea708Cdp);
+                assert_eq!(*data, cdp(frame_idx), "frame {frame_idx}: captions");
+            }
+            _ => panic!("frame {frame_idx}: {} caption metas", captions.len()),
+        }
+        frames.push(frame_idx);
+    }
+    pusher.join().expect("push thread");
+
+    assert_bus_no_errors("consumer", &collect_bus_errors(&rt.consumer));
+    assert_bus_no_errors("producer", &collect_bus_errors(&rt.producer));
+    assert_eq!(
+        frames.last(),
+        Some(&last_frame),
+        "mxlsrc did not read through the last frame: {frames:?}"
+    );
+    assert!(
+        frames.len() >= PUSH_COUNT - ATTACH_SLACK,
+        "mxlsrc read too few frames: {frames:?}"
+    );
+    // Both flows are written from the same buffer, the caption grain right
+    // after the video grain, so only the reader's first frame may miss it.
+    assert!(
+        bare.iter().all(|&frame| frame == frames[0]),
+        "frames without captions: {bare:?}"
+    );
+}
@@ -13,13 +13,14 @@
 // Each integration test binary pulls in this module but uses a different subset.
 #![allow(dead_code)]
 
-use std::path::PathBuf;
 use std::sync::Once;
 
 use gst::prelude::*;
 use gstreamer as gst;
 use gstreamer_app as gst_app;
 use gstreamer_video as gst_video;
+use mxl_test_utils::SHM_HINT;
+pub use mxl_test_utils::TestDomainGuard;
 
 pub const FRAMERATE_NUM: i32 = 30_000;
 pub const FRAMERATE_DEN: i32 = 1_001;
@@ -29,9 +30,6 @@ pub const FRAME_PERIOD_NS: u64 =
 pub const VIDEO_WIDTH: u32 = 2;
 pub const VIDEO_HEIGHT: u32 = 2;
 
-const SHM_MXL_HINT: &str = "MXL uses mkdtemp(3) under /dev/shm; run integration tests \
-    on Linux with tmpfs (/dev/shm)";
-
 /// Print a libtest-style `test <name> ... skipped, <reason>` line and return
 /// from the calling test. Mirrors the line a static `#[ignore = "..."]` emits,
 /// so a skipped prerequisite is visible (under `--nocapture`) instead of hidden.
@@ -49,7 +47,7 @@ macro_rules! skip {
 
 static REGISTER: Once = Once::new();
 
-/// Initialise GStreamer and register `mxlsrc`/`mxlsink` once per process.
+/// Initialise GStreamer and register the MXL elements once per process.
 pub fn init() {
     REGISTER.call_once(|| {
         gst::init().expect("gst::init");
@@ -67,6 +65,27 @@ pub fn init() {
             gstmxl::mxlsink::MxlSink::static_type(),
         )
         .expect("register mxlsink");
+        gst::Element::register(
+            None,
+            "mxlgroupsink",
+            gst::Rank::NONE,
+            gstmxl::mxlgroupsink::MxlGroupSink::static_type(),
+        )
+        .expect("register mxlgroupsink");
+        gst::Element::register(
+            None,
+            "mxlavsrc",
+            gst::Rank::NONE,
+            gstmxl::mxlavsrc::MxlAvSrc::static_type(),
+        )
+        .expect("register mxlavsrc");
+        gst::Element::register(
+            None,
+            "mxlbin",
+            gst::Rank::NONE,
+            gstmxl::mxlbin::MxlBin::static_type(),
+        )
+        .expect("register mxlbin");
     });
 }
 
@@ -75,7 +94,7 @@ pub fn init() {
 /// work on this platform, then that every named element factory is registered.
 /// Call [`init`] first so the factory lookup sees the registered elements.
 pub fn skip_reason(factories: &[&str]) -> Option<String> {
-    if let Some(reason) = dev_shm_skip_reason() {
+    if let Some(reason) = mxl_test_utils::shm_unavailable_reason() {
         return Some(reason);
     }
     let missing: Vec<&str> = factories
@@ -92,63 +111,6 @@ pub fn skip_reason(factories: &[&str]) -> Option<String> {
     None
 }
 
-/// `Some(reason)` when MXL's `mkdtemp(3)` domains under `/dev/shm` are
-/// unavailable (non-Linux, or a sandbox blocking tmpfs); `None` when usable.
-#[cfg(target_os = "linux")]
-fn dev_shm_skip_reason() -> Option<String> {
-    use std::ffi::CStr;
-
-    let mut path = b"/dev/shm/mxl_gst_shm_probeXXXXXX\0".to_vec();
-    let created = unsafe {
-        unsafe extern "C" {
-            fn mkdtemp(template: *mut i8) -> *mut i8;
-        }
-        mkdtemp(path.as_mut_ptr() as *mut i8)
-    };
-    if created.is_null() {
-        let err = std::io::Error::last_os_error();
-        return Some(format!(
-            "mkdtemp(3) on /dev/shm failed: {err}; {SHM_MXL_HINT}"
-        ));
-    }
-    let dir = unsafe { CStr::from_ptr(created) };
-    std::fs::remove_dir_all(dir.to_str().expect("mkdtemp path")).ok();
-    None
-}
-
-#[cfg(not(target_os = "linux"))]
-fn dev_shm_skip_reason() -> Option<String> {
-    Some(format!("not Linux; {SHM_MXL_HINT}"))
-}
-
-/// Per-test MXL domain under `/dev/shm`, removed on drop.
-pub struct TestDomainGuard {
-    dir: PathBuf,
-}
-
-impl TestDomainGuard {
-    pub fn new(test: &str) -> Self {
-        let dir = PathBuf::from(format!(
-            "/dev/shm/mxl_gst_test_domain_{test}_{}",
-            uuid::Uuid::new_v4()
-        ));
-        std::fs::create_dir_all(&dir).unwrap_or_else(|e| {
-            panic!("create test domain {}: {e}\n{SHM_MXL_HINT}", dir.display())
-        });
-        Self { dir }
-    }
-
-    pub fn domain(&self) -> String {
-        self.dir.to_string_lossy().into_owned()
-    }
-}
-
-impl Drop for TestDomainGuard {
-    fn drop(&mut self) {
-        let _ = std::fs::remove_dir_all(&self.dir);
-    }
-}
-
 fn extend_with_even_odd_parity(v: u8) -> u16 {
     if v.count_ones() & 1 == 0 {
         0x1_00 | (v as u16)
@@ -276,7 +238,7 @@ pub fn assert_bus_no_errors(label: &str, errors: &[String]) {
         .iter()
         .any(|e| e.contains("mkdtemp") || e.contains("ENOENT") || e.contains("Flow not found"));
     let hint = if shm {
-        format!("\n{SHM_MXL_HINT}")
+        format!("\n{SHM_HINT}")
     } else {
         String::new()
     };
@@ -3,7 +3,7 @@
 
 //! GStreamer integration tests for the MXL discrete data flow path.
 //!
-//! Two end-to-end round trips, both with `mxlsink` and `mxlsrc` connected
+//! Three end-to-end round trips, all with `mxlsink` and `mxlsrc` connected
 //! through a per-test MXL domain under `/dev/shm` torn down on drop:
 //!
 //! 1. [`st2038_round_trip_via_mxl`]: synthetic ST 2038 ANC packets
@@ -11,9 +11,11 @@
 //! 2. [`cea608_round_trip_via_mxl`]: CEA-608 closed captions from
 //!    an SRT file, encoded to ST 2038, sent over MXL, decoded back to
 //!    plain text.
+//! 3. [`cea608_captions_round_trip_via_mxl`]: the same captions written by
+//!    `mxlsink` from `closedcaption/x-cea-608` buffers, without ST 2038.
 //!
-//! The per-test domain (`common::TestDomainGuard`) mirrors
-//! `rust/mxl/tests/basic_tests.rs::TestDomainGuard`.
+//! The per-test domain is `mxl_test_utils::TestDomainGuard`, shared with the
+//! `mxl` tests.
 
 #[macro_use]
 mod common;
@@ -215,6 +217,26 @@ fn st2038_round_trip_via_mxl() {
 /// from source.
 #[test]
 fn cea608_round_trip_via_mxl() {
+    check_cea608_round_trip(
+        "cea608_round_trip",
+        "closedcaption/x-cea-608,framerate=30000/1001 \
+         ! cctost2038anc \
+         ! meta/x-st-2038,alignment=frame,framerate=30000/1001",
+    );
+}
+
+/// `mxlsink` wraps the S334-1A triplets in ANC packets itself, `mxlsrc` reads them as ST 2038.
+#[test]
+fn cea608_captions_round_trip_via_mxl() {
+    check_cea608_round_trip(
+        "cea608_captions_round_trip",
+        "closedcaption/x-cea-608,format=s334-1a,framerate=30000/1001",
+    );
+}
+
+/// Captions of example.srt through `to_mxl`, the caps and elements between
+/// `ccconverter` and `mxlsink`, decoded back from the data flow.
+fn check_cea608_round_trip(test: &str, to_mxl: &str) {
     init();
     const FACTORIES: &[&str] = &[
         "appsink",
@@ -233,7 +255,7 @@ fn cea608_round_trip_via_mxl() {
     }
 
     let flow_id = uuid::Uuid::new_v4().to_string();
-    let domain_guard = common::TestDomainGuard::new("cea608_round_trip");
+    let domain_guard = common::TestDomainGuard::new(test);
     let domain = domain_guard.domain();
     let srt_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/example.srt");
 
@@ -262,9 +284,7 @@ fn cea608_round_trip_via_mxl() {
          ! subparse \
          ! tttocea608 mode=pop-on \
          ! ccconverter \
-         ! closedcaption/x-cea-608,framerate=30000/1001 \
-         ! cctost2038anc \
-         ! meta/x-st-2038,alignment=frame,framerate=30000/1001 \
+         ! {to_mxl} \
          ! mxlsink flow-id={flow_id} domain={domain}"
     );
     let consumer_desc = format!(
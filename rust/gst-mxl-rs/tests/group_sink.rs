@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `mxlgroupsink` with a video and two audio request pads: every flow is created with the
+//! configured flow ID, and all three carry group hints of the same group. `mxlbin` then finds the
+//! flows of that group and reads them through its `video_%u` and `audio_%u` pads.
+
+#[macro_use]
+mod common;
+
+use common::{FRAMERATE_DEN, FRAMERATE_NUM, init, skip_reason};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+
+const AUDIO: &str =
+    "audiotestsrc is-live=true ! audioconvert ! audio/x-raw,format=F32LE,channels=2,rate=48000";
+const GROUPHINT_TAG: &str = "urn:x-nmos:tag:grouphint/v1.0";
+
+/// Stops the pipeline on drop, including on panic.
+struct Pipeline(gst::Pipeline);
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        let _ = self.0.set_state(gst::State::Null);
+    }
+}
+
+/// The group hint of the flow's definition, once the flow exists.
+fn wait_for_group_hint(domain: &std::path::Path, flow_id: &str) -> String {
+    let path = domain.join(format!("{flow_id}.mxl-flow/flow_def.json"));
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    loop {
+        if let Ok(json) = std::fs::read_to_string(&path) {
+            let flow_def: serde_json::Value = serde_json::from_str(&json).expect("flow def JSON");
+            return flow_def["tags"][GROUPHINT_TAG][0]
+                .as_str()
+                .expect("group hint tag")
+                .to_owned();
+        }
+        assert!(
+            std::time::Instant::now() < deadline,
+            "flow {flow_id} was not created"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn group_sink_publishes_flows_of_one_group() {
+    init();
+    const FACTORIES: &[&str] = &[
+        "audioconvert",
+        "audiotestsrc",
+        "mxlgroupsink",
+        "mxlsink",
+        "videotestsrc",
+    ];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let domain_guard = common::TestDomainGuard::new("group_sink");
+    let video_flow_id = uuid::Uuid::new_v4().to_string();
+    let audio_flow_ids = [uuid::Uuid::new_v4(), uuid::Uuid::new_v4()].map(|id| id.to_string());
+    let pipeline = gst::parse::launch(&format!(
+        "mxlgroupsink name=group domain={} group-name=Studio video-flow-id={video_flow_id} \
+           audio-flow-ids={} \
+         videotestsrc is-live=true \
+           ! video/x-raw,format=v210,width=64,height=16,framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+           ! group.video_sink \
+         {AUDIO} ! group.audio_0 \
+         {AUDIO} ! group.audio_1",
+        domain_guard.domain(),
+        audio_flow_ids.join(","),
+    ))
+    .expect("parse pipeline")
+    .downcast::<gst::Pipeline>()
+    .expect("pipeline");
+    let pipeline = Pipeline(pipeline);
+    pipeline.0.set_state(gst::State::Playing).expect("Playing");
+
+    assert_eq!(
+        wait_for_group_hint(domain_guard.path(), &video_flow_id),
+        "Studio:Video"
+    );
+    assert_eq!(
+        wait_for_group_hint(domain_guard.path(), &audio_flow_ids[0]),
+        "Studio:Audio 0"
+    );
+    assert_eq!(
+        wait_for_group_hint(domain_guard.path(), &audio_flow_ids[1]),
+        "Studio:Audio 1"
+    );
+
+    let errors = common::collect_bus_errors(&pipeline.0);
+    common::assert_bus_no_errors("pipeline", &errors);
+}
+
+#[test]
+fn group_bin_reads_the_flows_of_a_group() {
+    init();
+    const FACTORIES: &[&str] = &[
+        "appsink",
+        "audioconvert",
+        "audiotestsrc",
+        "mxlbin",
+        "mxlgroupsink",
+        "mxlsink",
+        "mxlsrc",
+        "videotestsrc",
+    ];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let domain_guard = common::TestDomainGuard::new("group_bin");
+    let domain = domain_guard.domain();
+    let video_flow_id = uuid::Uuid::new_v4().to_string();
+    let audio_flow_id = uuid::Uuid::new_v4().to_string();
+    let launch = |description: &str| {
+        gst::parse::launch(description)
+            .expect("parse pipeline")
+            .downcast::<gst::Pipeline>()
+            .expect("pipeline")
+    };
+    let producer = Pipeline(launch(&format!(
+        "mxlgroupsink name=group domain={domain} group-name=Studio \
+           video-flow-id={video_flow_id} audio-flow-ids={audio_flow_id} \
+         videotestsrc is-live=true \
+           ! video/x-raw,format=v210,width=64,height=16,framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+           ! group.video_sink \
+         {AUDIO} ! group.audio_0"
+    )));
+    producer.0.set_state(gst::State::Playing).expect("Playing");
+    // `mxlbin` only reads the flows of the group that exist when it starts.
+    for flow_id in [&video_flow_id, &audio_flow_id] {
+        wait_for_group_hint(domain_guard.path(), flow_id);
+    }
+
+    let consumer = Pipeline(launch(&format!(
+        "mxlbin name=bin domain={domain} group-hint=Studio \
+         bin.video_0 ! appsink name=video sync=false \
+         bin.audio_0 ! appsink name=audio sync=false"
+    )));
+    consumer.0.set_state(gst::State::Playing).expect("Playing");
+
+    for (name, media) in [("video", "video/x-raw"), ("audio", "audio/x-raw")] {
+        let appsink = consumer
+            .0
+            .by_name(name)
+            .expect("appsink")
+            .downcast::<gst_app::AppSink>()
+            .expect("AppSink downcast");
+        let sample = appsink
+            .try_pull_sample(gst::ClockTime::from_seconds(5))
+            .unwrap_or_else(|| panic!("no {name} sample"));
+        let caps = sample.caps().expect("sample caps");
+        assert_eq!(caps.structure(0).expect("caps structure").name(), media);
+    }
+
+    let errors = common::collect_bus_errors(&consumer.0);
+    common::assert_bus_no_errors("consumer", &errors);
+}
@@ -0,0 +1,85 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `mxlsrc` payload fidelity against the `mxl_test_utils` loopback writer.
+//!
+//! The loopback thread writes a pattern grain at every index of a v210 flow, and every buffer
+//! `mxlsrc` delivers must hold the pattern of one grain, byte for byte, with grains following one
+//! another in order, whether `mxlsrc` copies the grains or wraps them with `zero-copy`.
+
+#[macro_use]
+mod common;
+
+use common::{VIDEO_HEIGHT, VIDEO_WIDTH, init, skip_reason};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use mxl_test_utils::{
+    loopback::{Loopback, verify_pattern},
+    resize_video_flow_def, setup_test, v210_flow_def,
+};
+
+#[test]
+fn mxlsrc_delivers_loopback_grains_intact() {
+    check_loopback_fidelity(false);
+}
+
+#[test]
+fn mxlsrc_delivers_zero_copy_grains_intact() {
+    check_loopback_fidelity(true);
+}
+
+fn check_loopback_fidelity(zero_copy: bool) {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "mxlsrc"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let (mxl_instance, domain_guard) = setup_test(if zero_copy {
+        "loopback_fidelity_zero_copy"
+    } else {
+        "loopback_fidelity"
+    });
+    let loopback = Loopback::start(
+        &mxl_instance,
+        &resize_video_flow_def(&v210_flow_def(), VIDEO_WIDTH, VIDEO_HEIGHT),
+    );
+    let consumer = gst::parse::launch(&format!(
+        "mxlsrc video-flow-id={} domain={} zero-copy={zero_copy} ! appsink name=sink sync=false",
+        loopback.flow_id(),
+        domain_guard.domain()
+    ))
+    .expect("parse consumer")
+    .downcast::<gst::Pipeline>()
+    .expect("consumer pipeline");
+    let appsink = consumer
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+    consumer
+        .set_state(gst::State::Playing)
+        .expect("consumer Playing");
+
+    let mut previous = None;
+    for _ in 0..10 {
+        let sample = appsink
+            .try_pull_sample(gst::ClockTime::from_seconds(2))
+            .expect("mxlsrc delivered no buffer");
+        let map = sample
+            .buffer()
+            .expect("sample buffer")
+            .map_readable()
+            .expect("readable");
+        let index = verify_pattern(map.as_slice())
+            .unwrap_or_else(|mismatch| panic!("mxlsrc delivered a damaged grain: {mismatch}"));
+        if let Some(previous) = previous {
+            assert!(index > previous, "grain {index} delivered after {previous}");
+        }
+        previous = Some(index);
+    }
+
+    consumer.set_state(gst::State::Null).expect("consumer Null");
+    loopback.stop();
+}
@@ -0,0 +1,175 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration test for video grains the producer skipped.
+//!
+//! With the default `gap-mode`, `mxlsrc` must announce the skipped grains with a
+//! GAP event spanning them, followed by the next grain's buffer flagged DISCONT,
+//! rather than silently jumping ahead.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+
+use common::{FRAME_PERIOD_NS, build_producer, init, skip_reason};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+
+/// What `mxlsrc` pushed downstream, in order.
+#[derive(Debug, Clone, Copy)]
+enum Item {
+    Gap {
+        start: gst::ClockTime,
+        duration: Option<gst::ClockTime>,
+    },
+    Buffer {
+        pts: gst::ClockTime,
+        discont: bool,
+        frame_idx: u8,
+    },
+}
+
+fn push_frames(appsrc: &gst_app::AppSrc, frame_bytes: usize, frames: std::ops::Range<u64>) {
+    for i in frames {
+        let mut buf = gst::Buffer::with_size(frame_bytes).expect("v210 buffer");
+        {
+            let b = buf.get_mut().expect("buffer mut");
+            b.set_pts(gst::ClockTime::from_nseconds(i * FRAME_PERIOD_NS));
+            b.set_duration(gst::ClockTime::from_nseconds(FRAME_PERIOD_NS));
+            let mut map = b.map_writable().expect("buffer writable");
+            map.as_mut_slice()[0] = i as u8;
+        }
+        appsrc.push_buffer(buf).expect("push v210 buffer");
+    }
+}
+
+/// Frames the producer skips show up as a GAP event covering them, then a DISCONT buffer.
+#[test]
+fn skipped_frames_push_gap_then_discont_buffer() {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "appsrc", "mxlsink", "mxlsrc", "st2038extractor"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let video_flow_id = uuid::Uuid::new_v4().to_string();
+    let data_flow_id = uuid::Uuid::new_v4().to_string();
+    let domain_guard = common::TestDomainGuard::new("skipped_frames_gap");
+    let domain = domain_guard.domain();
+
+    let (producer, appsrc, frame_bytes) = build_producer(&video_flow_id, &data_flow_id, &domain);
+
+    let consumer_desc = format!(
+        "mxlsrc name=src video-flow-id={video_flow_id} domain={domain} \
+           ! appsink name=video_sink sync=false caps=video/x-raw,format=v210"
+    );
+    let consumer = gst::parse::launch(&consumer_desc)
+        .expect("parse consumer")
+        .downcast::<gst::Pipeline>()
+        .expect("consumer pipeline");
+
+    let items = Arc::new(Mutex::new(Vec::new()));
+    let src_pad = consumer
+        .by_name("src")
+        .expect("mxlsrc")
+        .static_pad("src")
+        .expect("mxlsrc src pad");
+    src_pad.add_probe(
+        gst::PadProbeType::BUFFER | gst::PadProbeType::EVENT_DOWNSTREAM,
+        {
+            let items = items.clone();
+            move |_, info| {
+                let item = match &info.data {
+                    Some(gst::PadProbeData::Buffer(buffer)) => Some(Item::Buffer {
+                        pts: buffer.pts().expect("PTS"),
+                        discont: buffer.flags().contains(gst::BufferFlags::DISCONT),
+                        frame_idx: buffer.map_readable().expect("readable").as_slice()[0],
+                    }),
+                    Some(gst::PadProbeData::Event(event)) => match event.view() {
+                        gst::EventView::Gap(gap) => {
+                            let (start, duration) = gap.get();
+                            Some(Item::Gap { start, duration })
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(item) = item {
+                    items.lock().unwrap().push(item);
+                }
+                gst::PadProbeReturn::Ok
+            }
+        },
+    );
+
+    producer
+        .set_state(gst::State::Playing)
+        .expect("producer Playing");
+    consumer
+        .set_state(gst::State::Playing)
+        .expect("consumer Playing");
+
+    push_frames(&appsrc, frame_bytes, 0..4);
+    thread::sleep(Duration::from_millis(300));
+    // Frames 4 to 7 are never produced.
+    push_frames(&appsrc, frame_bytes, 8..12);
+    thread::sleep(Duration::from_millis(500));
+
+    producer.set_state(gst::State::Null).expect("producer Null");
+    consumer.set_state(gst::State::Null).expect("consumer Null");
+
+    let items = items.lock().unwrap().clone();
+    let gap_at = items
+        .iter()
+        .position(|item| matches!(item, Item::Gap { .. }))
+        .unwrap_or_else(|| panic!("no GAP event for the skipped frames: {items:?}"));
+    let (
+        Item::Gap { start, duration },
+        Some(&Item::Buffer {
+            pts,
+            discont,
+            frame_idx,
+        }),
+    ) = (items[gap_at], items.get(gap_at + 1))
+    else {
+        panic!("GAP event not followed by a buffer: {items:?}");
+    };
+    let Some(&Item::Buffer { pts: last_pts, .. }) =
+        gap_at.checked_sub(1).and_then(|before| items.get(before))
+    else {
+        panic!("GAP event without a buffer before it: {items:?}");
+    };
+
+    assert_eq!(frame_idx, 8, "GAP event must precede frame 8: {items:?}");
+    assert!(discont, "buffer after the GAP event must be DISCONT");
+    assert_eq!(
+        Some(pts),
+        start.checked_add(duration.expect("GAP duration")),
+        "GAP event must end at the next buffer"
+    );
+    // The gap covers frames 4 to 7, up to rounding of the grain rate.
+    let start_after_last = start
+        .nseconds()
+        .abs_diff(last_pts.nseconds() + FRAME_PERIOD_NS);
+    assert!(
+        start_after_last <= 1,
+        "GAP event must start one frame after frame 3: {items:?}"
+    );
+    let skipped = duration.unwrap().nseconds().abs_diff(4 * FRAME_PERIOD_NS);
+    assert!(
+        skipped <= 1,
+        "GAP event must span the 4 skipped frames: {items:?}"
+    );
+    assert_eq!(
+        items
+            .iter()
+            .filter(|item| matches!(item, Item::Gap { .. }))
+            .count(),
+        1,
+        "only the skipped frames leave a gap: {items:?}"
+    );
+}
@@ -7,7 +7,9 @@
 //! PTS steps exactly one frame period, the grain content must advance exactly
 //! one frame (no repeats or skips). This exercises the absolute-index read path
 //! and the eviction catch-up. (Cross-flow PTS agreement within one pipeline is
-//! covered by the disjoint test in `video_data_sync`.)
+//! covered by the disjoint test in `video_data_sync`.) Every buffer must also
+//! carry the TAI timestamp of its grain as a `timestamp/x-ptp`
+//! `GstReferenceTimestampMeta`, a fixed offset from its PTS.
 
 use std::thread;
 use std::time::Duration;
@@ -47,6 +49,8 @@ fn push_frames(appsrc: &gst_app::AppSrc, frame_bytes: usize, start: usize, count
 struct VideoSample {
     pts: gst::ClockTime,
     frame_idx: u8,
+    /// Timestamp of the `timestamp/x-ptp` reference meta.
+    tai: Option<gst::ClockTime>,
 }
 
 fn pull_video_samples(
@@ -58,11 +62,20 @@ fn pull_video_samples(
     while let Some(sample) = sink.try_pull_sample(timeout) {
         let buffer = sample.buffer().expect("sample buffer");
         let pts = buffer.pts().expect("PTS");
+        let tai = buffer
+            .iter_meta::<gst::ReferenceTimestampMeta>()
+            .find(|meta| {
+                meta.reference()
+                    .structure(0)
+                    .is_some_and(|s| s.name() == "timestamp/x-ptp")
+            })
+            .map(|meta| meta.timestamp());
         let map = buffer.map_readable().expect("readable");
         assert_eq!(map.len(), frame_bytes);
         out.push(VideoSample {
             pts,
             frame_idx: map.as_slice()[0],
+            tai,
         });
     }
     out
@@ -96,6 +109,27 @@ fn assert_pts_tracks_content(samples: &[VideoSample]) {
     }
 }
 
+/// Every buffer carries the TAI timestamp of its grain, which is its PTS plus
+/// the pipeline's fixed MXL-time → running-time offset.
+fn assert_tai_reference_tracks_pts(samples: &[VideoSample]) {
+    let offsets: Vec<u64> = samples
+        .iter()
+        .map(|sample| {
+            let tai = sample.tai.unwrap_or_else(|| {
+                panic!(
+                    "frame {} without a TAI reference timestamp",
+                    sample.frame_idx
+                )
+            });
+            tai.nseconds() - sample.pts.nseconds()
+        })
+        .collect();
+    assert!(
+        offsets.windows(2).all(|w| w[0] == w[1]),
+        "TAI reference timestamps drift from PTS: {offsets:?}"
+    );
+}
+
 /// After a consumer stall, consecutive buffers must keep PTS aligned with grain content.
 #[test]
 fn lag_catch_up_pts_tracks_grain_content() {
@@ -166,6 +200,7 @@ fn lag_catch_up_pts_tracks_grain_content() {
         "need at least two samples after stall to verify PTS/content lockstep"
     );
     assert_pts_tracks_content(&samples);
+    assert_tai_reference_tracks_pts(&samples);
 
     producer.set_state(gst::State::Null).expect("producer Null");
     consumer.set_state(gst::State::Null).expect("consumer Null");
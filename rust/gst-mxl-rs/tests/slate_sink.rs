@@ -0,0 +1,94 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration test for the slate of `mxlsink`: once input stalls, slate grains
+//! are written to the video flow and marked by events at their index in the
+//! `slate-flow-id` flow.
+
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+
+use common::{FRAMERATE_DEN, FRAMERATE_NUM, VIDEO_HEIGHT, VIDEO_WIDTH, init, skip_reason};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use mxl::events::EventSubscriber;
+
+/// Byte the input frame is filled with, which the slate never holds.
+const INPUT_FILL: u8 = 0xaa;
+
+/// After input stalls, the slate flow marks the slate grains of the video flow.
+#[test]
+fn stalled_input_writes_marked_slate_grains() {
+    init();
+    if let Some(reason) = skip_reason(&["appsrc", "mxlsink"]) {
+        skip!(reason);
+    }
+
+    let (instance, domain_guard) = mxl_test_utils::setup_test("slate_sink");
+    let domain = domain_guard.domain();
+    let video_flow_id = uuid::Uuid::new_v4().to_string();
+    let slate_flow_id = uuid::Uuid::new_v4().to_string();
+
+    let producer = gst::parse::launch(&format!(
+        "appsrc name=src format=time \
+           caps=video/x-raw,format=v210,\
+                width={VIDEO_WIDTH},\
+                height={VIDEO_HEIGHT},\
+                framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+           ! mxlsink flow-id={video_flow_id} domain={domain} freeze-detection=2 \
+               slate-flow-id={slate_flow_id}"
+    ))
+    .expect("parse producer")
+    .downcast::<gst::Pipeline>()
+    .expect("producer pipeline");
+    let appsrc = producer
+        .by_name("src")
+        .expect("appsrc")
+        .downcast::<gst_app::AppSrc>()
+        .expect("AppSrc downcast");
+    producer
+        .set_state(gst::State::Playing)
+        .expect("producer Playing");
+
+    let frame_bytes =
+        gst_video::VideoInfo::builder(gst_video::VideoFormat::V210, VIDEO_WIDTH, VIDEO_HEIGHT)
+            .fps(gst::Fraction::new(FRAMERATE_NUM, FRAMERATE_DEN))
+            .build()
+            .expect("v210 VideoInfo")
+            .size();
+    let mut frame = gst::Buffer::from_mut_slice(vec![INPUT_FILL; frame_bytes]);
+    frame
+        .get_mut()
+        .expect("buffer mut")
+        .set_pts(gst::ClockTime::ZERO);
+    appsrc.push_buffer(frame).expect("push v210 buffer");
+
+    // The input never resumes, so every grain from now on is a slate.
+    let mut subscriber =
+        EventSubscriber::open(&instance, slate_flow_id.as_str()).expect("open the slate flow");
+    let events = subscriber
+        .recv(Duration::from_secs(2))
+        .expect("receive slate events");
+    let event = events.first().expect("no slate event");
+    assert_eq!(event.value, serde_json::json!({ "slate": true }));
+
+    let reader = instance
+        .create_flow_reader(video_flow_id.as_str())
+        .expect("open the video flow")
+        .to_grain_reader()
+        .expect("video grain reader");
+    let grain = reader
+        .get_complete_grain(event.index, Duration::from_secs(1))
+        .expect("slate grain");
+    assert!(
+        grain.payload.iter().any(|&byte| byte != INPUT_FILL),
+        "grain {} marked as slate holds the input frame",
+        event.index
+    );
+
+    producer.set_state(gst::State::Null).expect("producer Null");
+}
@@ -0,0 +1,241 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! GStreamer integration tests for the timecode of video buffers.
+//!
+//! v210 frames carrying a drop-frame `GstVideoTimeCodeMeta` are written by one
+//! `mxlsink` with `timecode-flow-id`, which puts the timecode of each frame in a
+//! `video/smpte291` flow as ST 12-2 ancillary time code at the frame's grain
+//! index. An `mxlsrc` with the same `timecode-flow-id` must hand every frame
+//! back with its own timecode, and one with `timecode=true` alone the timecode
+//! of the frame's grain index:
+//!
+//! ```text
+//! appsrc (v210 + GstVideoTimeCodeMeta) ! mxlsink flow-id=V timecode-flow-id=T
+//! mxlsrc video-flow-id=V timecode-flow-id=T ! queue ! appsink
+//! mxlsrc video-flow-id=V timecode=true ! queue ! appsink
+//! ```
+
+#[macro_use]
+mod common;
+
+use common::{
+    FRAME_PERIOD_NS, FRAMERATE_DEN, FRAMERATE_NUM, VIDEO_HEIGHT, VIDEO_WIDTH, assert_bus_no_errors,
+    collect_bus_errors, init, skip_reason,
+};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+
+/// Frames pushed, about 2 s of video.
+const PUSH_COUNT: usize = 60;
+/// Frames the reader may miss before it attaches to the flows.
+const ATTACH_SLACK: usize = 5;
+const FACTORIES: &[&str] = &["appsink", "appsrc", "mxlsink", "mxlsrc", "queue"];
+
+/// Timecode of producer frame `frame_idx`, 10:00:00;00 onward.
+fn producer_timecode(frame_idx: u8) -> gst_video::ValidVideoTimeCode {
+    let tc = gst_video::VideoTimeCode::new(
+        gst::Fraction::new(FRAMERATE_NUM, FRAMERATE_DEN),
+        None,
+        gst_video::VideoTimeCodeFlags::DROP_FRAME,
+        10,
+        0,
+        u32::from(frame_idx) / 30,
+        u32::from(frame_idx) % 30,
+        0,
+    );
+    gst_video::ValidVideoTimeCode::try_from(tc).expect("valid timecode")
+}
+
+/// Stops the writer then the reader on drop (including on panic), so no MXL
+/// worker thread outlives the domain dir.
+struct RoundTrip {
+    producer: gst::Pipeline,
+    consumer: gst::Pipeline,
+}
+
+impl Drop for RoundTrip {
+    fn drop(&mut self) {
+        let _ = self.producer.set_state(gst::State::Null);
+        let _ = self.consumer.set_state(gst::State::Null);
+    }
+}
+
+/// Push the frames at frame cadence, stamping the frame index into byte 0 of
+/// the v210 payload and attaching its timecode.
+fn push_frames(appsrc: &gst_app::AppSrc, frame_bytes: usize) {
+    let frame_period = std::time::Duration::from_nanos(FRAME_PERIOD_NS);
+    let start = std::time::Instant::now();
+    for i in 0..PUSH_COUNT {
+        let frame_idx = i as u8;
+        let mut buf = gst::Buffer::with_size(frame_bytes).expect("v210 buffer");
+        {
+            let b = buf.get_mut().expect("buffer mut");
+            b.set_pts(gst::ClockTime::from_nseconds(i as u64 * FRAME_PERIOD_NS));
+            b.set_duration(gst::ClockTime::from_nseconds(FRAME_PERIOD_NS));
+            b.map_writable().expect("buffer writable").as_mut_slice()[0] = frame_idx;
+            gst_video::video_meta::VideoTimeCodeMeta::add(b, &producer_timecode(frame_idx));
+        }
+        if appsrc.push_buffer(buf).is_err() {
+            break;
+        }
+        if let Some(remaining) = (start + frame_period * (i as u32 + 1))
+            .checked_duration_since(std::time::Instant::now())
+        {
+            std::thread::sleep(remaining);
+        }
+    }
+}
+
+/// Runs the producer and an `mxlsrc video-flow-id=V {src_properties}` consumer
+/// and returns the frame index and timecode of every frame read, through the
+/// producer's last frame.
+fn read_timecodes(
+    name: &str,
+    src_properties: &str,
+) -> Vec<(u8, Option<gst_video::ValidVideoTimeCode>)> {
+    let video_flow_id = uuid::Uuid::new_v4().to_string();
+    let timecode_flow_id = uuid::Uuid::new_v4().to_string();
+    let domain_guard = common::TestDomainGuard::new(name);
+    let domain = domain_guard.domain();
+
+    let video_info =
+        gst_video::VideoInfo::builder(gst_video::VideoFormat::V210, VIDEO_WIDTH, VIDEO_HEIGHT)
+            .fps(gst::Fraction::new(FRAMERATE_NUM, FRAMERATE_DEN))
+            .build()
+            .expect("v210 VideoInfo");
+    let frame_bytes = video_info.size();
+
+    let producer_desc = format!(
+        "appsrc name=src format=time caps={} \
+           ! mxlsink flow-id={video_flow_id} timecode-flow-id={timecode_flow_id} domain={domain}",
+        video_info.to_caps().expect("v210 caps")
+    );
+    let src_properties = src_properties.replace("{timecode_flow_id}", &timecode_flow_id);
+    let consumer_desc = format!(
+        "mxlsrc video-flow-id={video_flow_id} {src_properties} domain={domain} \
+           ! queue \
+           ! appsink name=sink sync=false"
+    );
+    let producer = gst::parse::launch(&producer_desc)
+        .expect("parse producer")
+        .downcast::<gst::Pipeline>()
+        .expect("producer pipeline");
+    let appsrc = producer
+        .by_name("src")
+        .expect("appsrc")
+        .downcast::<gst_app::AppSrc>()
+        .expect("AppSrc downcast");
+    let consumer = gst::parse::launch(&consumer_desc)
+        .expect("parse consumer")
+        .downcast::<gst::Pipeline>()
+        .expect("consumer pipeline");
+    let appsink = consumer
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+
+    let rt = RoundTrip { producer, consumer };
+    rt.producer
+        .set_state(gst::State::Playing)
+        .expect("producer Playing");
+    rt.consumer
+        .set_state(gst::State::Playing)
+        .expect("consumer Playing");
+    let pusher = std::thread::spawn(move || push_frames(&appsrc, frame_bytes));
+
+    let last_frame = (PUSH_COUNT - 1) as u8;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+    let mut frames = Vec::new();
+    while frames.last().map(|(frame_idx, _)| *frame_idx) != Some(last_frame)
+        && std::time::Instant::now() < deadline
+    {
+        let Some(sample) = appsink.try_pull_sample(gst::ClockTime::from_seconds(2)) else {
+            if appsink.is_eos() {
+                break;
+            }
+            continue;
+        };
+        let buffer = sample.buffer().expect("sample buffer");
+        let frame_idx = buffer.map_readable().expect("readable").as_slice()[0];
+        let timecode = buffer
+            .meta::<gst_video::video_meta::VideoTimeCodeMeta>()
+            .map(|meta| meta.tc());
+        frames.push((frame_idx, timecode));
+    }
+    pusher.join().expect("push thread");
+
+    assert_bus_no_errors("consumer", &collect_bus_errors(&rt.consumer));
+    assert_bus_no_errors("producer", &collect_bus_errors(&rt.producer));
+    assert_eq!(
+        frames.last().map(|(frame_idx, _)| *frame_idx),
+        Some(last_frame),
+        "mxlsrc did not read through the last frame"
+    );
+    assert!(
+        frames.len() >= PUSH_COUNT - ATTACH_SLACK,
+        "mxlsrc read {} frames",
+        frames.len()
+    );
+    frames
+}
+
+/// `mxlsink` writes the timecode of the video buffers to the timecode flow and
+/// `mxlsrc` attaches it again to the frames of the same grain index.
+#[test]
+fn timecode_meta_round_trip_via_mxl() {
+    init();
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let frames = read_timecodes("timecode_meta", "timecode-flow-id={timecode_flow_id}");
+    // Both flows are written from the same buffer, the timecode grain right
+    // after the video grain, so only the reader's first frame may miss it.
+    for (position, (frame_idx, timecode)) in frames.iter().enumerate() {
+        let Some(timecode) = timecode else {
+            assert_eq!(position, 0, "frame {frame_idx} without timecode");
+            continue;
+        };
+        let expected = producer_timecode(*frame_idx);
+        assert_eq!(
+            timecode.to_string(),
+            expected.to_string(),
+            "frame {frame_idx}: timecode"
+        );
+        assert!(
+            timecode
+                .flags()
+                .contains(gst_video::VideoTimeCodeFlags::DROP_FRAME),
+            "frame {frame_idx}: drop-frame flag"
+        );
+    }
+}
+
+/// With `timecode=true` alone, `mxlsrc` attaches the timecode of each frame's
+/// grain index, so consecutive frames carry consecutive timecodes.
+#[test]
+fn timecode_from_grain_index_via_mxl() {
+    init();
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let frames = read_timecodes("timecode_index", "timecode=true");
+    let offsets: Vec<i64> = frames
+        .iter()
+        .map(|(frame_idx, timecode)| {
+            let timecode = timecode
+                .as_ref()
+                .unwrap_or_else(|| panic!("frame {frame_idx} without timecode"));
+            timecode.frames_since_daily_jam() as i64 - i64::from(*frame_idx)
+        })
+        .collect();
+    assert!(
+        offsets.windows(2).all(|pair| pair[0] == pair[1]),
+        "timecodes do not follow the frames: {offsets:?}"
+    );
+}
@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! UYVY video through a `video/uyvy` MXL flow: `mxlsink` writes `videotestsrc` frames, and
+//! `mxlsrc` must negotiate `format=UYVY` and deliver the frames byte for byte.
+
+#[macro_use]
+mod common;
+
+use common::{FRAMERATE_DEN, FRAMERATE_NUM, init, skip_reason};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+
+/// An odd width, so lines carry the padding of the last pixel pair.
+const CAPS: &str = "video/x-raw,format=UYVY,width=63,height=16";
+
+/// Owns the pipelines and stops them on drop, including on panic.
+struct Pipelines(Vec<gst::Pipeline>);
+
+impl Drop for Pipelines {
+    fn drop(&mut self) {
+        for pipeline in &self.0 {
+            let _ = pipeline.set_state(gst::State::Null);
+        }
+    }
+}
+
+fn launch(description: &str) -> gst::Pipeline {
+    gst::parse::launch(description)
+        .expect("parse pipeline")
+        .downcast::<gst::Pipeline>()
+        .expect("pipeline")
+}
+
+fn appsink(pipeline: &gst::Pipeline) -> gst_app::AppSink {
+    pipeline
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast")
+}
+
+#[test]
+fn uyvy_round_trip_via_mxl() {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "mxlsink", "mxlsrc", "videotestsrc"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let reference = Pipelines(vec![launch(&format!(
+        "videotestsrc pattern=smpte num-buffers=1 \
+         ! {CAPS},framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} ! appsink name=sink sync=false"
+    ))]);
+    reference.0[0]
+        .set_state(gst::State::Playing)
+        .expect("Playing");
+    let sample = appsink(&reference.0[0])
+        .try_pull_sample(gst::ClockTime::from_seconds(5))
+        .expect("no reference frame");
+    let expected = sample
+        .buffer()
+        .expect("sample buffer")
+        .map_readable()
+        .expect("readable")
+        .to_vec();
+    drop(reference);
+
+    let domain_guard = common::TestDomainGuard::new("uyvy_round_trip");
+    let flow_id = uuid::Uuid::new_v4().to_string();
+    let pipelines = Pipelines(vec![
+        launch(&format!(
+            "videotestsrc pattern=smpte is-live=true \
+             ! {CAPS},framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+             ! mxlsink flow-id={flow_id} domain={}",
+            domain_guard.domain()
+        )),
+        launch(&format!(
+            "mxlsrc video-flow-id={flow_id} domain={} ! appsink name=sink sync=false",
+            domain_guard.domain()
+        )),
+    ]);
+    for pipeline in &pipelines.0 {
+        pipeline.set_state(gst::State::Playing).expect("Playing");
+    }
+
+    let appsink = appsink(&pipelines.0[1]);
+    for _ in 0..5 {
+        let sample = appsink
+            .try_pull_sample(gst::ClockTime::from_seconds(5))
+            .expect("mxlsrc delivered no buffer");
+        let format = sample
+            .caps()
+            .and_then(|caps| caps.structure(0))
+            .and_then(|structure| structure.get::<String>("format").ok());
+        assert_eq!(format.as_deref(), Some("UYVY"));
+        let map = sample
+            .buffer()
+            .expect("sample buffer")
+            .map_readable()
+            .expect("readable");
+        assert!(
+            map.as_slice() == expected.as_slice(),
+            "grain of {} bytes differs from the frame of {}",
+            map.len(),
+            expected.len()
+        );
+    }
+}
@@ -38,6 +38,12 @@
 //! mxlsrc (data flow)  ! queue ! comb.st2038
 //! st2038combiner name=comb drop-late-st2038=true ! queue ! appsink (sync=true)
 //! ```
+//!
+//! Attaching consumer (`v210_with_meta_via_mxlsrc_ancillary_flow`):
+//!
+//! ```text
+//! mxlsrc (video flow, ancillary-flow-id = data flow) ! queue ! appsink (sync=false)
+//! ```
 
 #[macro_use]
 mod common;
@@ -563,3 +569,96 @@ fn v210_with_meta_to_v210_with_meta_via_mxl() {
     );
     drop(rt);
 }
+
+/// Reads the video flow with a single `mxlsrc` that attaches the data flow's
+/// grains itself (`ancillary-flow-id`): every video frame must carry exactly
+/// the two `GstAncillaryMeta` of its own producer frame, as attached before
+/// `st2038extractor`.
+#[test]
+fn v210_with_meta_via_mxlsrc_ancillary_flow() {
+    init();
+    const FACTORIES: &[&str] = &[
+        "appsink",
+        "appsrc",
+        "mxlsink",
+        "mxlsrc",
+        "queue",
+        "st2038extractor",
+    ];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let video_flow_id = uuid::Uuid::new_v4().to_string();
+    let data_flow_id = uuid::Uuid::new_v4().to_string();
+    let domain_guard = common::TestDomainGuard::new("v210_ancillary_flow");
+    let domain = domain_guard.domain();
+
+    let (producer, appsrc, frame_bytes) = build_producer(&video_flow_id, &data_flow_id, &domain);
+
+    let consumer_desc = format!(
+        "mxlsrc video-flow-id={video_flow_id} ancillary-flow-id={data_flow_id} domain={domain} \
+           ! queue \
+           ! appsink name=sink sync=false caps=video/x-raw,format=v210"
+    );
+    let consumer = gst::parse::launch(&consumer_desc)
+        .expect("parse consumer")
+        .downcast::<gst::Pipeline>()
+        .expect("consumer pipeline");
+    let appsink = consumer
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+
+    let rt = RoundTrip { producer, consumer };
+    start_and_stream(&rt, &appsrc, frame_bytes);
+
+    // Frames read before the data flow has its first grain are bare; after the
+    // first paired frame, every frame must carry its own two packets.
+    let mut paired = 0usize;
+    let mut initial_bare: Vec<u8> = Vec::new();
+    let mut steady_bare: Vec<u8> = Vec::new();
+    let mut wrong: Vec<(u8, Vec<u8>)> = Vec::new();
+    let samples = pull_all_samples(&appsink, gst::ClockTime::from_seconds(2), |buffer| {
+        let frame_idx = buffer
+            .map_readable()
+            .expect("video buffer readable")
+            .as_slice()[0];
+        let stamps = ancillary_meta_frame_stamps(buffer);
+        if stamps.is_empty() {
+            if paired == 0 {
+                initial_bare.push(frame_idx);
+            } else {
+                steady_bare.push(frame_idx);
+            }
+        } else if stamps.len() == 1 && stamps.contains(&frame_idx) {
+            let sdids: Vec<u8> = buffer
+                .iter_meta::<gst_video::video_meta::AncillaryMeta>()
+                .map(|meta| ancillary_byte(meta.sdid_block_number()))
+                .collect();
+            assert_eq!(sdids, [0x01, 0x02], "frame {frame_idx}: ANC packets");
+            paired += 1;
+        } else {
+            wrong.push((frame_idx, stamps.into_iter().collect()));
+        }
+        frame_idx
+    });
+
+    assert_bus_no_errors("consumer", &collect_bus_errors(&rt.consumer));
+    let frames: BTreeSet<u8> = by_frame("video", &samples).into_keys().collect();
+    assert_contiguous_live_capture("video", &frames);
+    assert!(
+        wrong.is_empty(),
+        "mxlsrc attached mismatched ancillary: {wrong:?}"
+    );
+    assert!(
+        steady_bare.is_empty(),
+        "mxlsrc dropped ancillary in steady state (frames {steady_bare:?})"
+    );
+    assert!(
+        initial_bare.len() <= ATTACH_SLACK,
+        "too many initial attach misses: {initial_bare:?}"
+    );
+    drop(rt);
+}
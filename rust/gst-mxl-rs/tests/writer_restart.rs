@@ -0,0 +1,167 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A writer that restarts recreates its flow under the readers: `mxlsrc` must post a warning,
+//! reattach to the new flow and go on delivering its frames. With `eos-on-stale` it must end the
+//! stream as soon as the writer stops instead.
+//!
+//! ```text
+//! videotestsrc ! mxlsink flow-id=V      (stopped, then launched again)
+//! mxlsrc video-flow-id=V ! appsink
+//! ```
+
+#[macro_use]
+mod common;
+
+use common::{
+    FRAMERATE_DEN, FRAMERATE_NUM, assert_bus_no_errors, collect_bus_errors, init, skip_reason,
+};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+
+const CAPS: &str = "video/x-raw,format=UYVY,width=64,height=16";
+/// Frames the reader must deliver from the restarted writer.
+const FRAMES_AFTER_RESTART: usize = 10;
+
+/// Owns the pipelines and stops them on drop, including on panic.
+struct Pipelines(Vec<gst::Pipeline>);
+
+impl Drop for Pipelines {
+    fn drop(&mut self) {
+        for pipeline in &self.0 {
+            let _ = pipeline.set_state(gst::State::Null);
+        }
+    }
+}
+
+fn launch(description: &str) -> gst::Pipeline {
+    gst::parse::launch(description)
+        .expect("parse pipeline")
+        .downcast::<gst::Pipeline>()
+        .expect("pipeline")
+}
+
+#[test]
+fn mxlsrc_reconnects_to_restarted_writer() {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "mxlsink", "mxlsrc", "videotestsrc"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let domain_guard = common::TestDomainGuard::new("writer_restart");
+    let flow_id = uuid::Uuid::new_v4().to_string();
+    let producer_desc = format!(
+        "videotestsrc is-live=true \
+         ! {CAPS},framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+         ! mxlsink flow-id={flow_id} domain={}",
+        domain_guard.domain()
+    );
+    let mut pipelines = Pipelines(vec![
+        launch(&format!(
+            "mxlsrc video-flow-id={flow_id} domain={} reconnect-interval=100 \
+             ! appsink name=sink sync=false max-buffers=1 drop=true",
+            domain_guard.domain()
+        )),
+        launch(&producer_desc),
+    ]);
+    for pipeline in &pipelines.0 {
+        pipeline.set_state(gst::State::Playing).expect("Playing");
+    }
+    let consumer = pipelines.0[0].clone();
+    let appsink = consumer
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+    appsink
+        .try_pull_sample(gst::ClockTime::from_seconds(5))
+        .expect("mxlsrc delivered no buffer");
+
+    // Restart the writer: releasing it invalidates the flow the reader has open.
+    let producer = pipelines.0.pop().expect("producer");
+    producer.set_state(gst::State::Null).expect("Null");
+    drop(producer);
+    pipelines.0.push(launch(&producer_desc));
+    pipelines.0[1]
+        .set_state(gst::State::Playing)
+        .expect("Playing");
+
+    let warning = consumer
+        .bus()
+        .expect("consumer bus")
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(10),
+            &[gst::MessageType::Warning],
+        )
+        .expect("no warning for the recreated flow");
+    let gst::MessageView::Warning(warning) = warning.view() else {
+        unreachable!();
+    };
+    assert!(
+        warning.error().matches(gst::ResourceError::Read),
+        "unexpected warning: {:?}",
+        warning.error()
+    );
+
+    for frame in 0..FRAMES_AFTER_RESTART {
+        appsink
+            .try_pull_sample(gst::ClockTime::from_seconds(5))
+            .unwrap_or_else(|| panic!("no frame {frame} from the restarted writer"));
+    }
+    assert_bus_no_errors("consumer", &collect_bus_errors(&consumer));
+}
+
+#[test]
+fn mxlsrc_ends_stream_when_writer_stops_with_eos_on_stale() {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "mxlsink", "mxlsrc", "videotestsrc"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let domain_guard = common::TestDomainGuard::new("writer_restart_eos");
+    let flow_id = uuid::Uuid::new_v4().to_string();
+    let mut pipelines = Pipelines(vec![
+        launch(&format!(
+            "mxlsrc video-flow-id={flow_id} domain={} eos-on-stale=true \
+             ! appsink name=sink sync=false max-buffers=1 drop=true",
+            domain_guard.domain()
+        )),
+        launch(&format!(
+            "videotestsrc is-live=true \
+             ! {CAPS},framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+             ! mxlsink flow-id={flow_id} domain={}",
+            domain_guard.domain()
+        )),
+    ]);
+    for pipeline in &pipelines.0 {
+        pipeline.set_state(gst::State::Playing).expect("Playing");
+    }
+    let consumer = pipelines.0[0].clone();
+    consumer
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast")
+        .try_pull_sample(gst::ClockTime::from_seconds(5))
+        .expect("mxlsrc delivered no buffer");
+
+    let producer = pipelines.0.pop().expect("producer");
+    producer.set_state(gst::State::Null).expect("Null");
+    drop(producer);
+
+    let message = consumer
+        .bus()
+        .expect("consumer bus")
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(10),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        )
+        .expect("no EOS once the writer stopped");
+    assert!(
+        matches!(message.view(), gst::MessageView::Eos(_)),
+        "unexpected message: {message:?}"
+    );
+}
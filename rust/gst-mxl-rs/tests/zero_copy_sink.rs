@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `mxlsink` with `zero-copy`: `videotestsrc` draws its frames into buffers of the grain pool
+//! proposed by the sink, and the grains read back through `mxlsrc` must hold the same frame as
+//! one drawn into plain memory. A plain frame rendered while a pooled buffer is out must wait for
+//! the buffer's release rather than cancel its grain.
+
+#[macro_use]
+mod common;
+
+use common::{FRAMERATE_DEN, FRAMERATE_NUM, init, skip_reason};
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+
+const CAPS: &str = "video/x-raw,format=v210,width=64,height=16";
+
+/// Owns both pipelines and stops them on drop, including on panic.
+struct Pipelines(Vec<gst::Pipeline>);
+
+impl Drop for Pipelines {
+    fn drop(&mut self) {
+        for pipeline in &self.0 {
+            let _ = pipeline.set_state(gst::State::Null);
+        }
+    }
+}
+
+fn launch(description: &str) -> gst::Pipeline {
+    gst::parse::launch(description)
+        .expect("parse pipeline")
+        .downcast::<gst::Pipeline>()
+        .expect("pipeline")
+}
+
+fn pull_frame(appsink: &gst_app::AppSink) -> Vec<u8> {
+    let sample = appsink
+        .try_pull_sample(gst::ClockTime::from_seconds(5))
+        .expect("no buffer");
+    let buffer = sample.buffer().expect("sample buffer");
+    buffer.map_readable().expect("readable").to_vec()
+}
+
+#[test]
+fn zero_copy_sink_commits_frames_drawn_in_place() {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "mxlsink", "mxlsrc", "videotestsrc"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let reference = launch(&format!(
+        "videotestsrc pattern=smpte num-buffers=1 ! {CAPS},framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+         ! appsink name=sink sync=false"
+    ));
+    let appsink = reference
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+    reference.set_state(gst::State::Playing).expect("Playing");
+    let expected = pull_frame(&appsink);
+    reference.set_state(gst::State::Null).expect("Null");
+
+    let domain_guard = common::TestDomainGuard::new("zero_copy_sink");
+    let flow_id = uuid::Uuid::new_v4().to_string();
+    let producer = launch(&format!(
+        "videotestsrc pattern=smpte is-live=true ! {CAPS},framerate={FRAMERATE_NUM}/{FRAMERATE_DEN} \
+         ! mxlsink flow-id={flow_id} domain={} zero-copy=true",
+        domain_guard.domain()
+    ));
+    let consumer = launch(&format!(
+        "mxlsrc video-flow-id={flow_id} domain={} ! appsink name=sink sync=false",
+        domain_guard.domain()
+    ));
+    let pipelines = Pipelines(vec![producer, consumer]);
+    for pipeline in &pipelines.0 {
+        pipeline.set_state(gst::State::Playing).expect("Playing");
+    }
+    let appsink = pipelines.0[1]
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+
+    for _ in 0..5 {
+        let frame = pull_frame(&appsink);
+        assert!(
+            frame.len() >= expected.len(),
+            "grain of {} bytes, frame of {}",
+            frame.len(),
+            expected.len()
+        );
+        assert!(
+            frame[..expected.len()] == expected[..],
+            "grain differs from the frame drawn into plain memory"
+        );
+    }
+}
+
+/// Pulls frames until one starts with `marker`, `false` if none does within `timeout`.
+fn wait_for_marker(appsink: &gst_app::AppSink, marker: u8, timeout: std::time::Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    while let Some(remaining) = deadline.checked_duration_since(std::time::Instant::now()) {
+        let Some(sample) =
+            appsink.try_pull_sample(gst::ClockTime::from_nseconds(remaining.as_nanos() as u64))
+        else {
+            return false;
+        };
+        let buffer = sample.buffer().expect("sample buffer");
+        if buffer.map_readable().expect("readable").first() == Some(&marker) {
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn zero_copy_sink_defers_copies_while_a_pooled_buffer_is_out() {
+    init();
+    const FACTORIES: &[&str] = &["appsink", "appsrc", "mxlsink", "mxlsrc"];
+    if let Some(reason) = skip_reason(FACTORIES) {
+        skip!(reason);
+    }
+
+    let caps: gst::Caps = format!("{CAPS},framerate={FRAMERATE_NUM}/{FRAMERATE_DEN}")
+        .parse()
+        .expect("caps");
+    let frame_bytes = gst_video::VideoInfo::from_caps(&caps)
+        .expect("VideoInfo")
+        .size();
+    let frame = |marker: u8| gst::Buffer::from_mut_slice(vec![marker; frame_bytes]);
+
+    let domain_guard = common::TestDomainGuard::new("zero_copy_sink_deferred");
+    let flow_id = uuid::Uuid::new_v4().to_string();
+    let producer = launch(&format!(
+        "appsrc name=src format=time is-live=true do-timestamp=true caps={caps} \
+         ! mxlsink flow-id={flow_id} domain={} zero-copy=true sync=false",
+        domain_guard.domain()
+    ));
+    let consumer = launch(&format!(
+        "mxlsrc video-flow-id={flow_id} domain={} ! appsink name=sink sync=false",
+        domain_guard.domain()
+    ));
+    let pipelines = Pipelines(vec![producer, consumer]);
+    let appsrc = pipelines.0[0]
+        .by_name("src")
+        .expect("appsrc")
+        .downcast::<gst_app::AppSrc>()
+        .expect("AppSrc downcast");
+    let appsink = pipelines.0[1]
+        .by_name("sink")
+        .expect("appsink")
+        .downcast::<gst_app::AppSink>()
+        .expect("AppSink downcast");
+    for pipeline in &pipelines.0 {
+        pipeline.set_state(gst::State::Playing).expect("Playing");
+    }
+
+    // Frames until the reader attached, which also lets the sink create its writer.
+    let mut attached = false;
+    for _ in 0..100 {
+        appsrc.push_buffer(frame(0x11)).expect("push");
+        if wait_for_marker(&appsink, 0x11, std::time::Duration::from_millis(50)) {
+            attached = true;
+            break;
+        }
+    }
+    assert!(attached, "mxlsrc read no frame");
+
+    let mut query = gst::query::Allocation::new(Some(&caps), true);
+    assert!(
+        appsrc
+            .static_pad("src")
+            .expect("src pad")
+            .peer_query(&mut query),
+        "allocation query failed"
+    );
+    let pool = query
+        .allocation_pools()
+        .into_iter()
+        .find_map(|(pool, ..)| pool)
+        .expect("mxlsink proposed no pool");
+    pool.set_active(true).expect("pool active");
+    let pooled = pool.acquire_buffer(None).expect("pooled buffer");
+
+    // The pooled buffer holds the writer's open grain, so the plain frame waits for it.
+    appsrc.push_buffer(frame(0x22)).expect("push");
+    assert!(
+        !wait_for_marker(&appsink, 0x22, std::time::Duration::from_millis(500)),
+        "frame written while a pooled buffer was out"
+    );
+
+    drop(pooled);
+    assert!(
+        wait_for_marker(&appsink, 0x22, std::time::Duration::from_secs(5)),
+        "frame not written once the pooled buffer was released"
+    );
+    let _ = pool.set_active(false);
+}
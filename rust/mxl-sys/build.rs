@@ -4,6 +4,7 @@
 use bindgen::callbacks::ParseCallbacks;
 use std::env;
 use std::path::PathBuf;
+use std::process::Command;
 
 #[cfg(debug_assertions)]
 const BUILD_VARIANT: &str = "Linux-Clang-Debug";
@@ -13,6 +14,43 @@ const BUILD_VARIANT: &str = "Linux-Clang-Release";
 struct BindgenSpecs {
     header: String,
     includes_dirs: Vec<String>,
+    sanitizer: Option<&'static str>,
+}
+
+/// The sanitizer requested through the `asan` / `tsan` features, as named by `-fsanitize=`.
+fn requested_sanitizer() -> Option<&'static str> {
+    match (cfg!(feature = "asan"), cfg!(feature = "tsan")) {
+        (true, true) => panic!("The `asan` and `tsan` features are mutually exclusive."),
+        (true, false) => Some("address"),
+        (false, true) => Some("thread"),
+        (false, false) => None,
+    }
+}
+
+/// Locates the shared sanitizer runtime that has to be preloaded into processes loading the
+/// instrumented library, because Rust executables do not link it themselves.
+fn sanitizer_runtime(sanitizer: &str) -> Option<String> {
+    let short_name = match sanitizer {
+        "address" => "asan",
+        "thread" => "tsan",
+        _ => return None,
+    };
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").ok()?;
+    // Newer clang versions use a per-target runtime directory without the architecture suffix.
+    [
+        format!("libclang_rt.{short_name}-{arch}.so"),
+        format!("libclang_rt.{short_name}.so"),
+    ]
+    .iter()
+    .find_map(|name| {
+        let output = Command::new("clang++")
+            .arg(format!("-print-file-name={name}"))
+            .output()
+            .ok()?;
+        let path = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        // The compiler echoes the bare name back if it does not find the file.
+        PathBuf::from(&path).is_absolute().then_some(path)
+    })
 }
 
 fn get_bindgen_specs() -> BindgenSpecs {
@@ -24,6 +62,7 @@ fn get_bindgen_specs() -> BindgenSpecs {
     let manifest_dir =
         PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("failed to get current directory"));
     let repo_root = manifest_dir.parent().unwrap().parent().unwrap();
+    let mut sanitizer = None;
     let mut includes_dirs = vec![
         repo_root
             .join("lib")
@@ -41,7 +80,10 @@ fn get_bindgen_specs() -> BindgenSpecs {
         let lib_root = repo_root.join("lib");
         println!("cargo:rerun-if-changed={}", lib_root.display());
 
-        let dst = cmake::Config::new(repo_root)
+        sanitizer = requested_sanitizer();
+
+        let mut config = cmake::Config::new(repo_root);
+        config
             .generator("Ninja")
             .configure_arg("--preset")
             .configure_arg(BUILD_VARIANT)
@@ -50,8 +92,13 @@ fn get_bindgen_specs() -> BindgenSpecs {
             .define("BUILD_DOCS", "OFF")
             .define("BUILD_TESTS", "OFF")
             .define("BUILD_TOOLS", "OFF")
-            .define("CMAKE_INSTALL_LIBDIR", "lib")
-            .build();
+            .define("CMAKE_INSTALL_LIBDIR", "lib");
+        if let Some(sanitizer) = sanitizer {
+            config
+                .define("MXL_ENABLE_SANITIZERS", "ON")
+                .define("MXL_SANITIZER", sanitizer);
+        }
+        let dst = config.build();
 
         println!("cargo:rustc-link-search={}", dst.join("lib").display());
         println!("cargo:rustc-link-lib=mxl");
@@ -60,6 +107,7 @@ fn get_bindgen_specs() -> BindgenSpecs {
     BindgenSpecs {
         header,
         includes_dirs,
+        sanitizer,
     }
 }
 
@@ -90,6 +138,19 @@ fn main() {
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Could not write bindings");
+
+    let runtime = bindgen_specs.sanitizer.and_then(sanitizer_runtime);
+    std::fs::write(
+        out_path.join("sanitizer.rs"),
+        format!(
+            "/// Sanitizer the MXL library was built with, as named by `-fsanitize=`.\n\
+             pub const SANITIZER: Option<&str> = {:?};\n\
+             /// Shared runtime of [`SANITIZER`] that must be preloaded into the process.\n\
+             pub const SANITIZER_RUNTIME: Option<&str> = {:?};\n",
+            bindgen_specs.sanitizer, runtime
+        ),
+    )
+    .expect("Could not write sanitizer constants");
 }
 
 #[derive(Debug)]
@@ -17,3 +17,4 @@
 extern crate libloading;
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+include!(concat!(env!("OUT_DIR"), "/sanitizer.rs"));
@@ -0,0 +1,169 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers shared by the tests of the Rust crates and available to downstream integrators: a
+//! per-test MXL domain under `/dev/shm` removed on drop, the flow definitions of
+//! `lib/tests/data`, logging set up from `RUST_LOG`, and a [loopback](loopback) writer checking
+//! payload fidelity.
+//!
+//! ```ignore
+//! let (instance, domain) = mxl_test_utils::setup_test("my_test");
+//! let (writer, config, _) = instance.create_flow_writer(&v210_flow_def(), None)?;
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Once,
+};
+
+use mxl::{MxlInstance, config::get_mxl_so_path};
+
+pub mod loopback;
+
+/// Explanation appended to failures that come from a missing or unusable `/dev/shm`.
+pub const SHM_HINT: &str = "MXL uses mkdtemp(3) under /dev/shm; run integration tests on Linux \
+    with tmpfs (/dev/shm)";
+
+static LOG_ONCE: Once = Once::new();
+
+/// Sets up the logging to use the `RUST_LOG` environment variable and, if not present, print INFO
+/// and higher. Can be called from every test.
+pub fn setup_logging() {
+    LOG_ONCE.call_once(|| {
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter(
+                tracing_subscriber::EnvFilter::builder()
+                    .with_default_directive(tracing::level_filters::LevelFilter::INFO.into())
+                    .from_env_lossy(),
+            )
+            .try_init();
+    });
+}
+
+/// Per-test MXL domain under `/dev/shm`, removed on drop.
+///
+/// The directory name contains the test name and a random UUID, so tests running in parallel, or
+/// leftovers of a crashed run, never share a domain.
+pub struct TestDomainGuard {
+    dir: PathBuf,
+}
+
+impl TestDomainGuard {
+    pub fn new(test: &str) -> Self {
+        let dir = PathBuf::from(format!(
+            "/dev/shm/mxl_test_domain_{test}_{}",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap_or_else(|error| {
+            panic!(
+                "Failed to create test domain directory \"{}\": {error}\n{SHM_HINT}",
+                dir.display()
+            )
+        });
+        Self { dir }
+    }
+
+    pub fn domain(&self) -> String {
+        self.dir.to_string_lossy().into_owned()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for TestDomainGuard {
+    fn drop(&mut self) {
+        // A failed removal must not turn a failing test into an abort.
+        if let Err(error) = std::fs::remove_dir_all(&self.dir)
+            && !std::thread::panicking()
+        {
+            panic!(
+                "Failed to remove test domain directory \"{}\": {error}",
+                self.dir.display()
+            );
+        }
+    }
+}
+
+/// Sets up logging and a fresh domain, and creates an instance on it with the MXL library found by
+/// [`get_mxl_so_path`]. Fails early if a sanitizer runtime is required but not preloaded.
+pub fn setup_test(test: &str) -> (MxlInstance, TestDomainGuard) {
+    setup_logging();
+    mxl::config::check_sanitizer_env().unwrap();
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    let domain_guard = TestDomainGuard::new(test);
+    (
+        MxlInstance::new(mxl_api, domain_guard.domain().as_str(), "").unwrap(),
+        domain_guard,
+    )
+}
+
+/// `Some(reason)` when MXL's `mkdtemp(3)` domains under `/dev/shm` are unavailable (non-Linux, or a
+/// sandbox blocking tmpfs), `None` when usable. Lets tests skip instead of failing.
+#[cfg(target_os = "linux")]
+pub fn shm_unavailable_reason() -> Option<String> {
+    let mut template = b"/dev/shm/mxl_shm_probeXXXXXX\0".to_vec();
+    // SAFETY: `template` is a writable NUL-terminated buffer ending in `XXXXXX`.
+    let created = unsafe { libc::mkdtemp(template.as_mut_ptr().cast()) };
+    if created.is_null() {
+        let error = std::io::Error::last_os_error();
+        return Some(format!(
+            "mkdtemp(3) on /dev/shm failed: {error}; {SHM_HINT}"
+        ));
+    }
+    template.pop();
+    let _ = std::fs::remove_dir_all(String::from_utf8_lossy(&template).as_ref());
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn shm_unavailable_reason() -> Option<String> {
+    Some(format!("not Linux; {SHM_HINT}"))
+}
+
+/// Reads the flow definition at `path`, relative to the root of the MXL repository.
+pub fn read_flow_def<P: AsRef<Path>>(path: P) -> String {
+    let flow_config_file = mxl::config::get_mxl_repo_root().join(path);
+    std::fs::read_to_string(&flow_config_file).unwrap_or_else(|error| {
+        panic!(
+            "Error while reading flow definition from \"{}\": {error}",
+            flow_config_file.display()
+        )
+    })
+}
+
+/// 1080p `video/v210` flow.
+pub fn v210_flow_def() -> String {
+    read_flow_def("lib/tests/data/v210_flow.json")
+}
+
+/// `video/v210a` flow, v210 with a key.
+pub fn v210a_flow_def() -> String {
+    read_flow_def("lib/tests/data/v210a_flow.json")
+}
+
+/// Two channel `audio/float32` flow.
+pub fn audio_flow_def() -> String {
+    read_flow_def("lib/tests/data/audio_flow.json")
+}
+
+/// `video/smpte291` ancillary data flow.
+pub fn data_flow_def() -> String {
+    read_flow_def("lib/tests/data/data_flow.json")
+}
+
+/// `flow_def` with its picture resized, components included, e.g. to make grains quick to check.
+pub fn resize_video_flow_def(flow_def: &str, width: u32, height: u32) -> String {
+    let mut flow: serde_json::Value = serde_json::from_str(flow_def).unwrap();
+    flow["frame_width"] = width.into();
+    flow["frame_height"] = height.into();
+    if let Some(components) = flow["components"].as_array_mut() {
+        for component in components {
+            let chroma = component["name"] != "Y" && component["name"] != "A";
+            component["width"] = if chroma { width.div_ceil(2) } else { width }.into();
+            component["height"] = height.into();
+        }
+    }
+    flow.to_string()
+}
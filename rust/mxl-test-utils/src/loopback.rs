@@ -0,0 +1,251 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-process loopback: a writer thread filling a discrete flow with a known pattern, so tests
+//! check that their readers, or the elements built on them, deliver grains byte for byte rather
+//! than only without errors.
+//!
+//! Every grain starts with its index as a little-endian `u64`, followed by bytes derived from the
+//! index and their offset, so a payload tells which grain it is and whether it arrived intact:
+//!
+//! ```ignore
+//! let (instance, _domain) = setup_test("my_test");
+//! let loopback = Loopback::start(&instance, &v210_flow_def());
+//! let reader = loopback.reader();
+//! let grain = reader.get_complete_grain(index, timeout)?;
+//! verify_grain(&grain).unwrap();
+//! ```
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use mxl::{GrainData, GrainReader, MxlInstance, Rational};
+
+/// Size of the grain index at the start of every pattern payload.
+pub const PATTERN_HEADER_SIZE: usize = std::mem::size_of::<u64>();
+
+/// Byte at `offset` of the payload of grain `index`, past the header. 251 is prime, so the bytes
+/// do not repeat with any power-of-two stride.
+fn pattern_byte(index: u64, offset: usize) -> u8 {
+    (((index % 251) as usize + offset % 251 * 7) % 251) as u8
+}
+
+/// Fills `payload` with the pattern of grain `index`.
+///
+/// # Panics
+///
+/// If `payload` is shorter than [`PATTERN_HEADER_SIZE`].
+pub fn fill_pattern(index: u64, payload: &mut [u8]) {
+    let (header, rest) = payload.split_at_mut(PATTERN_HEADER_SIZE);
+    header.copy_from_slice(&index.to_le_bytes());
+    for (offset, byte) in rest.iter_mut().enumerate() {
+        *byte = pattern_byte(index, offset);
+    }
+}
+
+/// Checks that `payload` holds a whole pattern and returns the index of its grain.
+pub fn verify_pattern(payload: &[u8]) -> Result<u64, PatternMismatch> {
+    let Some((header, rest)) = payload.split_first_chunk::<PATTERN_HEADER_SIZE>() else {
+        return Err(PatternMismatch::TooShort { len: payload.len() });
+    };
+    let index = u64::from_le_bytes(*header);
+    for (offset, &actual) in rest.iter().enumerate() {
+        let expected = pattern_byte(index, offset);
+        if actual != expected {
+            return Err(PatternMismatch::Byte {
+                index,
+                offset: offset + PATTERN_HEADER_SIZE,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(index)
+}
+
+/// Checks that `grain` holds the pattern of its own index.
+pub fn verify_grain(grain: &GrainData<'_>) -> Result<(), PatternMismatch> {
+    let index = verify_pattern(grain.payload)?;
+    if index != grain.index {
+        return Err(PatternMismatch::WrongGrain {
+            expected: grain.index,
+            actual: index,
+        });
+    }
+    Ok(())
+}
+
+/// Why a payload does not hold the loopback pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternMismatch {
+    TooShort {
+        len: usize,
+    },
+    /// The payload of grain `index` differs at `offset`.
+    Byte {
+        index: u64,
+        offset: usize,
+        expected: u8,
+        actual: u8,
+    },
+    /// The payload of another grain was read.
+    WrongGrain {
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl std::fmt::Display for PatternMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooShort { len } => write!(
+                f,
+                "payload of {len} bytes cannot hold a pattern of {PATTERN_HEADER_SIZE} bytes or more"
+            ),
+            Self::Byte {
+                index,
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "grain {index} differs at byte {offset}: expected {expected:#04x}, got {actual:#04x}"
+            ),
+            Self::WrongGrain { expected, actual } => {
+                write!(
+                    f,
+                    "expected the payload of grain {expected}, got grain {actual}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternMismatch {}
+
+/// Writer thread of a loopback flow, see the [module documentation](self). Stops when dropped.
+pub struct Loopback {
+    instance: MxlInstance,
+    flow_id: uuid::Uuid,
+    grain_rate: Rational,
+    grains_written: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<mxl::Result<()>>>,
+}
+
+impl Loopback {
+    /// Creates the discrete flow `flow_def` and writes a pattern grain at every index, from the
+    /// current one and paced to the grain rate, until stopped.
+    ///
+    /// # Panics
+    ///
+    /// If the flow cannot be created or is continuous.
+    pub fn start(instance: &MxlInstance, flow_def: &str) -> Self {
+        let (flow_writer, flow_config_info, _) = instance
+            .create_flow_writer(flow_def, None)
+            .unwrap_or_else(|error| panic!("Failed to create the loopback flow: {error}"));
+        let flow_id = flow_config_info.common().id();
+        let grain_rate = flow_config_info.common().grain_rate().unwrap();
+        let mut writer = flow_writer.to_grain_writer().unwrap();
+        let grains_written = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let instance = instance.clone();
+            let grains_written = grains_written.clone();
+            let stop = stop.clone();
+            std::thread::Builder::new()
+                .name("mxl-loopback".to_string())
+                .spawn(move || {
+                    let mut index = instance.get_current_index(&grain_rate);
+                    while !stop.load(Ordering::Acquire) {
+                        instance.sleep_until_index(index, &grain_rate)?;
+                        let mut access = writer.open_grain(index)?;
+                        fill_pattern(index, access.payload_mut());
+                        let total_slices = access.total_slices();
+                        access.commit(total_slices)?;
+                        grains_written.fetch_add(1, Ordering::AcqRel);
+                        index += 1;
+                    }
+                    Ok(())
+                })
+                .unwrap()
+        };
+        Self {
+            instance: instance.clone(),
+            flow_id,
+            grain_rate,
+            grains_written,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn flow_id(&self) -> uuid::Uuid {
+        self.flow_id
+    }
+
+    pub fn grain_rate(&self) -> Rational {
+        self.grain_rate
+    }
+
+    pub fn grains_written(&self) -> u64 {
+        self.grains_written.load(Ordering::Acquire)
+    }
+
+    /// A new reader of the flow.
+    pub fn reader(&self) -> GrainReader {
+        self.instance
+            .create_flow_reader(self.flow_id)
+            .and_then(|reader| reader.to_grain_reader())
+            .unwrap_or_else(|error| panic!("Failed to read the loopback flow: {error}"))
+    }
+
+    /// Waits until `count` grains were written. Returns `false` after `timeout`.
+    pub fn wait_for_grains(&self, count: u64, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.grains_written() < count {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        true
+    }
+
+    /// Stops the writer and returns the number of grains it wrote.
+    ///
+    /// # Panics
+    ///
+    /// If writing a grain failed.
+    pub fn stop(mut self) -> u64 {
+        if let Err(error) = self.join() {
+            panic!("Loopback writer of flow {} failed: {error}", self.flow_id);
+        }
+        self.grains_written()
+    }
+
+    fn join(&mut self) -> mxl::Result<()> {
+        self.stop.store(true, Ordering::Release);
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(mxl::Error::Other(
+                "The loopback writer panicked.".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Loopback {
+    fn drop(&mut self) {
+        if let Err(error) = self.join() {
+            tracing::error!("Loopback writer of flow {} failed: {error}", self.flow_id);
+        }
+    }
+}
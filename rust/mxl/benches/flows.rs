@@ -0,0 +1,99 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Writing and reading grains and samples through the wrappers, against the MXL library found by
+//! `get_mxl_so_path` and a domain in `/dev/shm`.
+//!
+//! The library calls dominate; the benchmarks are there to catch copies and allocations creeping
+//! into the wrapper code around them.
+
+use std::time::Duration;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use mxl::{MxlInstance, config::get_mxl_so_path};
+use mxl_test_utils::{TestDomainGuard, audio_flow_def, v210_flow_def};
+
+const SAMPLE_BATCH: usize = 480;
+
+fn instance(domain: &TestDomainGuard) -> MxlInstance {
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    MxlInstance::new(mxl_api, &domain.domain(), "").unwrap()
+}
+
+fn grains(c: &mut Criterion) {
+    let domain = TestDomainGuard::new("bench");
+    let instance = instance(&domain);
+    let (writer, config, _) = instance.create_flow_writer(&v210_flow_def(), None).unwrap();
+    let flow_id = config.common().id().to_string();
+    let mut writer = writer.to_grain_writer().unwrap();
+    let reader = instance
+        .create_flow_reader(&flow_id)
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let rate = config.common().grain_rate().unwrap();
+
+    let mut group = c.benchmark_group("grains");
+    group.throughput(Throughput::Elements(1));
+    let mut index = instance.get_current_index(&rate);
+    group.bench_function("open_grain+commit", |b| {
+        b.iter(|| {
+            let access = writer.open_grain(index).unwrap();
+            let total_slices = access.total_slices();
+            access.commit(total_slices).unwrap();
+            index += 1;
+        })
+    });
+    // The last grain written stays in the ring as nothing is written after it.
+    let last = index - 1;
+    group.bench_function("get_complete_grain", |b| {
+        b.iter(|| {
+            reader
+                .get_complete_grain(last, Duration::from_secs(1))
+                .unwrap();
+        })
+    });
+    group.finish();
+}
+
+fn samples(c: &mut Criterion) {
+    let domain = TestDomainGuard::new("bench");
+    let instance = instance(&domain);
+    let (writer, config, _) = instance
+        .create_flow_writer(&audio_flow_def(), None)
+        .unwrap();
+    let flow_id = config.common().id().to_string();
+    let mut writer = writer.to_samples_writer().unwrap();
+    let reader = instance
+        .create_flow_reader(&flow_id)
+        .unwrap()
+        .to_samples_reader()
+        .unwrap();
+    let rate = config.common().sample_rate().unwrap();
+
+    let mut group = c.benchmark_group("samples");
+    group.throughput(Throughput::Elements(SAMPLE_BATCH as u64));
+    let mut index = instance.get_current_index(&rate);
+    group.bench_function("open_samples+commit", |b| {
+        b.iter(|| {
+            index += SAMPLE_BATCH as u64;
+            writer
+                .open_samples(index, SAMPLE_BATCH)
+                .unwrap()
+                .commit()
+                .unwrap();
+        })
+    });
+    let last = index;
+    group.bench_function("get_samples", |b| {
+        b.iter(|| {
+            reader
+                .get_samples(last, SAMPLE_BATCH, Duration::from_secs(1))
+                .unwrap();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, grains, samples);
+criterion_main!(benches);
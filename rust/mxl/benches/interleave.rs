@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Interleaving and de-interleaving of 10 ms of 48 kHz audio, which needs no MXL library.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use mxl::{InterleaveOptions, OwnedSamplesData, SampleFormat};
+
+const SAMPLES: usize = 480;
+
+fn planar(channels: usize) -> OwnedSamplesData {
+    OwnedSamplesData {
+        payload: (0..channels)
+            .map(|channel| {
+                (0..SAMPLES)
+                    .flat_map(|sample| ((sample + channel) as f32 / SAMPLES as f32).to_le_bytes())
+                    .collect()
+            })
+            .collect(),
+        format: SampleFormat::F32,
+    }
+}
+
+fn interleave(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interleave");
+    for channels in [2, 8, 16] {
+        let samples = planar(channels);
+        group.throughput(Throughput::Elements((SAMPLES * channels) as u64));
+        for format in [SampleFormat::F32, SampleFormat::S16, SampleFormat::S24] {
+            let options = InterleaveOptions::new().format(format);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{format:?}"), channels),
+                &samples,
+                |b, samples| b.iter(|| samples.interleave(&options)),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn deinterleave(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deinterleave");
+    for channels in [2, 8, 16] {
+        group.throughput(Throughput::Elements((SAMPLES * channels) as u64));
+        for format in [SampleFormat::F32, SampleFormat::S16, SampleFormat::S24] {
+            let options = InterleaveOptions::new().format(format);
+            let interleaved = planar(channels).interleave(&options).unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("{format:?}"), channels),
+                &interleaved,
+                |b, interleaved| {
+                    b.iter(|| OwnedSamplesData::deinterleave(interleaved, channels, &options))
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, interleave, deinterleave);
+criterion_main!(benches);
@@ -6,7 +6,7 @@ mod common;
 use std::time::Duration;
 
 use clap::Parser;
-use mxl::config::get_mxl_so_path;
+use mxl::{RationalExt, config::get_mxl_so_path};
 use tracing::{info, warn};
 
 const READ_TIMEOUT: Duration = Duration::from_secs(5);
@@ -27,6 +27,11 @@ pub struct Opts {
     /// will more or less fit 10 ms as a fallback.
     #[arg(long)]
     pub sample_batch_size: Option<u64>,
+
+    /// Print a JSON latency and throughput report every given number of seconds. Is only valid
+    /// for "discrete" flows.
+    #[arg(long)]
+    pub report_interval: Option<u64>,
 }
 
 fn main() -> Result<(), mxl::Error> {
@@ -43,8 +48,18 @@ fn main() -> Result<(), mxl::Error> {
                 "Sample batch size is only relevant for \"continuous\" flows.".to_owned(),
             ));
         }
-        read_grains(mxl_instance, reader.to_grain_reader()?, flow_info)
+        read_grains(
+            mxl_instance,
+            reader.to_grain_reader()?,
+            flow_info,
+            opts.report_interval.map(Duration::from_secs),
+        )
     } else {
+        if opts.report_interval.is_some() {
+            return Err(mxl::Error::Other(
+                "Report interval is only supported for \"discrete\" flows.".to_owned(),
+            ));
+        }
         read_samples(
             mxl_instance,
             reader.to_samples_reader()?,
@@ -58,18 +73,47 @@ fn read_grains(
     mxl_instance: mxl::MxlInstance,
     reader: mxl::GrainReader,
     flow_info: mxl::FlowInfo,
+    report_interval: Option<Duration>,
 ) -> Result<(), mxl::Error> {
+    let flow_id = flow_info.config.common().id();
     let rate = flow_info.config.common().grain_rate()?;
     let current_index = mxl_instance.get_current_index(&rate);
 
     info!("Grain rate: {}/{}", rate.numerator, rate.denominator);
 
+    let mut latencies = Vec::new();
+    let mut grains = 0;
+    let mut bytes = 0;
+    let mut window_start = mxl_instance.get_time();
     for index in current_index.. {
         let grain_data = reader.get_complete_grain(index, READ_TIMEOUT)?;
         info!(
             "Index: {index} Grain data len: {:?}",
             grain_data.payload.len()
         );
+
+        let Some(report_interval) = report_interval else {
+            continue;
+        };
+        let now = mxl_instance.get_time();
+        latencies.push(now.saturating_sub(mxl_instance.index_to_timestamp(index, &rate)?));
+        grains += 1;
+        bytes += grain_data.payload.len() as u64;
+        let window = Duration::from_nanos(now.saturating_sub(window_start));
+        if window >= report_interval {
+            let throughput =
+                mxl::ThroughputReport::new(flow_id, "flow-reader", now, window, grains, 0, bytes);
+            println!("{}", serde_json::to_string(&throughput).unwrap_or_default());
+            if let Some(latency) =
+                mxl::LatencyReport::from_samples(flow_id, "flow-reader", now, &latencies)
+            {
+                println!("{}", serde_json::to_string(&latency).unwrap_or_default());
+            }
+            latencies.clear();
+            grains = 0;
+            bytes = 0;
+            window_start = now;
+        }
     }
 
     Ok(())
@@ -96,7 +140,7 @@ fn read_samples(
         }
         batch_size as usize
     } else if common_flow_info.max_commit_batch_size_hint() == 0 {
-        let batch_size = (sample_rate.numerator / (100 * sample_rate.denominator)) as usize;
+        let batch_size = sample_rate.count_in(Duration::from_millis(10)) as usize;
         warn!(
             "Writer batch size not available, using fallback value of {}.",
             batch_size
@@ -6,7 +6,7 @@ mod common;
 use clap::Parser;
 use tracing::{info, warn};
 
-use mxl::config::get_mxl_so_path;
+use mxl::{RationalExt, config::get_mxl_so_path};
 
 #[derive(Debug, Parser)]
 #[command(version = clap::crate_version!(), author = clap::crate_authors!())]
@@ -74,7 +74,7 @@ fn main() -> Result<(), mxl::Error> {
 pub fn write_grains(
     mxl_instance: mxl::MxlInstance,
     flow_config_info: mxl::FlowConfigInfo,
-    writer: mxl::GrainWriter,
+    mut writer: mxl::GrainWriter,
     grain_count: Option<u64>,
 ) -> Result<(), mxl::Error> {
     let flow_id = flow_config_info.common().id().to_string();
@@ -122,14 +122,14 @@ pub fn write_grains(
 pub fn write_samples(
     mxl_instance: mxl::MxlInstance,
     flow_config_info: mxl::FlowConfigInfo,
-    writer: mxl::SamplesWriter,
+    mut writer: mxl::SamplesWriter,
     sample_count: Option<u64>,
     batch_size: Option<u64>,
 ) -> Result<(), mxl::Error> {
     let flow_id = flow_config_info.common().id().to_string();
     let sample_rate = flow_config_info.common().sample_rate()?;
     let batch_size =
-        batch_size.unwrap_or((sample_rate.numerator / (100 * sample_rate.denominator)) as u64);
+        batch_size.unwrap_or(sample_rate.count_in(std::time::Duration::from_millis(10)));
     let mut samples_index = mxl_instance.get_current_index(&sample_rate);
     info!(
         "Will write to flow \"{flow_id}\" with sample rate {}/{}, using batches of size {batch_size} samples, first batch ending at index {samples_index}.",
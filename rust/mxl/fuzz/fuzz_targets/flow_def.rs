@@ -0,0 +1,34 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Flow definitions as written by foreign writers go through every parser a Rust consumer uses.
+//! None of them may panic, whatever the input.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mxl::{
+    dump::{DumpFormat, GrainDump},
+    flowdef::{FlowDef, FlowDefAudio, FlowDefVideo},
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(flow_def) = serde_json::from_str::<FlowDef>(text) {
+        let _ = flow_def.audio_sample_format();
+    }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return;
+    };
+    let media_type = value["media_type"].as_str().unwrap_or_default().to_string();
+    if let Ok(audio) = serde_json::from_value::<FlowDefAudio>(value.clone()) {
+        let _ = audio.sample_format(&media_type);
+    }
+    if serde_json::from_value::<FlowDefVideo>(value).is_ok() {
+        for format in [DumpFormat::Raw, DumpFormat::Planar, DumpFormat::Y4m] {
+            let _ = GrainDump::new(Vec::new(), text, format);
+        }
+    }
+});
@@ -0,0 +1,16 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The buffer returned by `mxlGetFlowDef`, with any reported size, must not make
+//! `MxlInstance::get_flow_def` panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Vec<u8>, usize)| {
+    let (buffer, size) = input;
+    if let Ok(flow_def) = mxl::fuzzing::flow_def_from_buffer(buffer, size) {
+        assert!(!flow_def.contains('\0'));
+    }
+});
@@ -0,0 +1,510 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! SMPTE ST 291 ancillary data in `video/smpte291` grains.
+//!
+//! Data flows of media type `video/smpte291` carry the ANC packets of a frame the way
+//! ST 2110-40 does: an RFC 8331 payload from the *Length* field onward (see `docs/Architecture.md`),
+//! zero-padded to the grain size. [`AncGrain`] packs and parses that payload, and [`AncPacket`]
+//! takes care of the 10-bit words of ST 291, adding parity bits and the checksum on write and
+//! checking them on read, so applications only deal with the 8-bit DID, SDID and user data.
+//!
+//! Constructors and accessors for the common payloads are provided: CEA-708 caption distribution
+//! packets (SMPTE ST 334-1), CEA-608 caption data, SCTE-104 messages (SMPTE ST 2010) and
+//! ancillary time code (SMPTE ST 12-2).
+
+use crate::{Error, RationalExt, Result, Timecode};
+
+/// RFC 8331 *Length*, *ANC_Count*, *F* and reserved bits.
+const GRAIN_HEADER_SIZE: usize = 6;
+/// User data words of an ANC packet, whose *Data_Count* is 8 bits.
+pub const MAX_USER_DATA: usize = 255;
+
+/// CEA-708 caption distribution packet, SMPTE ST 334-1.
+pub const CEA708_DID: u8 = 0x61;
+pub const CEA708_SDID: u8 = 0x01;
+/// CEA-608 caption data, SMPTE ST 334-1.
+pub const CEA608_DID: u8 = 0x61;
+pub const CEA608_SDID: u8 = 0x02;
+/// SCTE-104 messages, SMPTE ST 2010.
+pub const SCTE104_DID: u8 = 0x41;
+pub const SCTE104_SDID: u8 = 0x07;
+/// ST 2010 payload descriptor of a message carried whole in one packet.
+const SCTE104_SINGLE_PACKET: u8 = 0x08;
+/// Ancillary time code, SMPTE ST 12-2.
+pub const ATC_DID: u8 = 0x60;
+pub const ATC_SDID: u8 = 0x60;
+/// User data words of an ATC packet, each carrying 4 bits of the ST 12-1 time code word.
+const ATC_USER_DATA: usize = 16;
+/// ST 12-2 payload type (DBB1) of a longitudinal time code.
+const ATC_LTC: u8 = 0x00;
+
+/// Field the ANC packets of a grain belong to, the RFC 8331 *F* bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AncField {
+    /// Progressive video, or no field specified.
+    #[default]
+    Unspecified,
+    First,
+    Second,
+}
+
+impl AncField {
+    fn bits(self) -> u32 {
+        match self {
+            Self::Unspecified => 0b00,
+            Self::First => 0b10,
+            Self::Second => 0b11,
+        }
+    }
+
+    fn from_bits(bits: u32) -> Result<Self> {
+        match bits {
+            0b00 => Ok(Self::Unspecified),
+            0b10 => Ok(Self::First),
+            0b11 => Ok(Self::Second),
+            _ => Err(Error::Other(
+                "Invalid RFC 8331 field bits 0b01.".to_string(),
+            )),
+        }
+    }
+}
+
+/// One ST 291 ancillary data packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AncPacket {
+    /// Carried in the color difference (C) rather than the luma (Y) data stream.
+    pub c_not_y: bool,
+    /// Line number, 0x7ff for "without specific line location".
+    pub line: u16,
+    /// Horizontal offset from the start of active video, 0xfff for "without specific location".
+    pub horizontal_offset: u16,
+    /// Data stream number of a link carrying several, the RFC 8331 *StreamNum* with *S* set.
+    pub stream: Option<u8>,
+    pub did: u8,
+    /// SDID of type 2 packets, or DBN of type 1 packets.
+    pub sdid: u8,
+    /// At most [`MAX_USER_DATA`] bytes.
+    pub user_data: Vec<u8>,
+}
+
+impl AncPacket {
+    /// Packet without a specific location, on the luma stream.
+    pub fn new(did: u8, sdid: u8, user_data: Vec<u8>) -> Self {
+        Self {
+            c_not_y: false,
+            line: 0x7ff,
+            horizontal_offset: 0xfff,
+            stream: None,
+            did,
+            sdid,
+            user_data,
+        }
+    }
+
+    /// Places the packet on a line, e.g. line 9 for captions in 1080 line formats.
+    pub fn at_line(mut self, line: u16) -> Self {
+        self.line = line;
+        self
+    }
+
+    /// Caption distribution packet carrying a CEA-708 CDP.
+    pub fn cea708(cdp: Vec<u8>) -> Self {
+        Self::new(CEA708_DID, CEA708_SDID, cdp)
+    }
+
+    /// CEA-608 caption data: the field/line byte followed by the two caption bytes.
+    pub fn cea608(field_line: u8, cc_data: [u8; 2]) -> Self {
+        Self::new(
+            CEA608_DID,
+            CEA608_SDID,
+            vec![field_line, cc_data[0], cc_data[1]],
+        )
+    }
+
+    /// SCTE-104 message carried in a single packet. Messages over 254 bytes must be split across
+    /// packets, which is not supported.
+    pub fn scte104(message: &[u8]) -> Result<Self> {
+        if message.len() >= MAX_USER_DATA {
+            return Err(Error::Other(format!(
+                "SCTE-104 message of {} bytes does not fit in one ANC packet.",
+                message.len()
+            )));
+        }
+        let mut user_data = Vec::with_capacity(message.len() + 1);
+        user_data.push(SCTE104_SINGLE_PACKET);
+        user_data.extend_from_slice(message);
+        Ok(Self::new(SCTE104_DID, SCTE104_SDID, user_data))
+    }
+
+    /// Ancillary time code packet carrying `timecode` as an LTC, for a flow of grain rate `rate`.
+    /// Above 30 fps the frames are counted in pairs with the frame pair flag of ST 12-1.
+    pub fn timecode(timecode: &Timecode, rate: &mxl_sys::Rational) -> Result<Self> {
+        let pair_flag_bit = frame_pair_flag_bit(rate)?;
+        let frames = match pair_flag_bit {
+            Some(_) => timecode.frames / 2,
+            None => timecode.frames,
+        };
+        if timecode.hours >= 24 || timecode.minutes >= 60 || timecode.seconds >= 60 || frames >= 40
+        {
+            return Err(Error::Other(format!(
+                "Timecode {timecode} does not fit in an ST 12-1 time code word."
+            )));
+        }
+        let bcd = |value: u8, units_bit: u32, tens_bit: u32| {
+            (value as u64 % 10) << units_bit | (value as u64 / 10) << tens_bit
+        };
+        let mut word = bcd(frames, 0, 8)
+            | bcd(timecode.seconds, 16, 24)
+            | bcd(timecode.minutes, 32, 40)
+            | bcd(timecode.hours, 48, 56)
+            | (timecode.drop_frame as u64) << 10;
+        if let Some(bit) = pair_flag_bit {
+            word |= (timecode.frames as u64 % 2) << bit;
+        }
+        // Bits 4-7 of each word carry a nibble of the time code word, bit 3 the distributed
+        // binary bits: the payload type in the first 8 words, no flags in the last 8.
+        let user_data = (0..ATC_USER_DATA)
+            .map(|i| {
+                let nibble = (word >> (4 * i) & 0xf) as u8;
+                let dbb = if i < 8 { ATC_LTC >> i & 1 } else { 0 };
+                nibble << 4 | dbb << 3
+            })
+            .collect();
+        Ok(Self::new(ATC_DID, ATC_SDID, user_data))
+    }
+
+    pub fn is_cea708(&self) -> bool {
+        (self.did, self.sdid) == (CEA708_DID, CEA708_SDID)
+    }
+
+    pub fn is_cea608(&self) -> bool {
+        (self.did, self.sdid) == (CEA608_DID, CEA608_SDID)
+    }
+
+    pub fn is_scte104(&self) -> bool {
+        (self.did, self.sdid) == (SCTE104_DID, SCTE104_SDID)
+    }
+
+    /// The SCTE-104 message of a single packet message, without the payload descriptor.
+    pub fn scte104_message(&self) -> Option<&[u8]> {
+        match self.user_data.split_first() {
+            Some((descriptor, message)) if self.is_scte104() && descriptor & 0x01 == 0 => {
+                Some(message)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_atc(&self) -> bool {
+        (self.did, self.sdid) == (ATC_DID, ATC_SDID)
+    }
+
+    /// The time code of an ancillary time code packet of a flow of grain rate `rate`, whatever
+    /// its payload type. `None` for other packets and time code words that are not valid BCD.
+    pub fn atc_timecode(&self, rate: &mxl_sys::Rational) -> Option<Timecode> {
+        if !self.is_atc() || self.user_data.len() != ATC_USER_DATA {
+            return None;
+        }
+        let word = self
+            .user_data
+            .iter()
+            .enumerate()
+            .fold(0u64, |word, (i, udw)| word | ((udw >> 4) as u64) << (4 * i));
+        let bcd = |units_bit: u32, tens_bit: u32, tens_mask: u64| {
+            let units = word >> units_bit & 0xf;
+            (units < 10).then(|| (units + 10 * (word >> tens_bit & tens_mask)) as u8)
+        };
+        let pair_flag_bit = frame_pair_flag_bit(rate).ok()?;
+        let frames = bcd(0, 8, 0x3)?;
+        let frames = match pair_flag_bit {
+            Some(bit) => frames * 2 + (word >> bit & 1) as u8,
+            None => frames,
+        };
+        let timecode = Timecode {
+            hours: bcd(48, 56, 0x3)?,
+            minutes: bcd(32, 40, 0x7)?,
+            seconds: bcd(16, 24, 0x7)?,
+            frames,
+            drop_frame: word >> 10 & 1 != 0,
+        };
+        (timecode.hours < 24 && timecode.minutes < 60 && timecode.seconds < 60).then_some(timecode)
+    }
+
+    /// Size of the packet in an RFC 8331 payload, including the word alignment.
+    fn encoded_size(&self) -> usize {
+        // C, Line, Horizontal_Offset, S and StreamNum, then 10-bit DID, SDID, Data_Count, the
+        // user data words and the checksum.
+        let bits = 32 + 10 * (self.user_data.len() + 4);
+        bits.div_ceil(32) * 4
+    }
+
+    fn encode(&self, writer: &mut BitWriter) -> Result<()> {
+        if self.user_data.len() > MAX_USER_DATA {
+            return Err(Error::Other(format!(
+                "ANC packet with {} user data words, at most {MAX_USER_DATA} fit.",
+                self.user_data.len()
+            )));
+        }
+        if self.line > 0x7ff || self.horizontal_offset > 0xfff || self.stream > Some(0x7f) {
+            return Err(Error::Other(
+                "ANC packet location out of range.".to_string(),
+            ));
+        }
+        let start = writer.len();
+        writer.write(1, self.c_not_y as u32);
+        writer.write(11, self.line as u32);
+        writer.write(12, self.horizontal_offset as u32);
+        writer.write(1, self.stream.is_some() as u32);
+        writer.write(7, self.stream.unwrap_or(0) as u32);
+        let words = [self.did, self.sdid, self.user_data.len() as u8]
+            .into_iter()
+            .chain(self.user_data.iter().copied())
+            .map(with_parity);
+        let mut checksum = 0u16;
+        for word in words {
+            checksum = checksum.wrapping_add(word & 0x1ff);
+            writer.write(10, word as u32);
+        }
+        writer.write(10, checksum_word(checksum) as u32);
+        writer.pad_to_word(start);
+        Ok(())
+    }
+
+    fn decode(reader: &mut BitReader) -> Result<Self> {
+        let start = reader.position();
+        let c_not_y = reader.read(1)? != 0;
+        let line = reader.read(11)? as u16;
+        let horizontal_offset = reader.read(12)? as u16;
+        let has_stream = reader.read(1)? != 0;
+        let stream_num = reader.read(7)? as u8;
+        let mut checksum = 0u16;
+        // User data words may use all 10 bits, only the header words must have parity.
+        let mut next_word = |reader: &mut BitReader, parity: bool| -> Result<u8> {
+            let word = reader.read(10)? as u16;
+            if parity && word != with_parity(word as u8) {
+                return Err(Error::Other(format!(
+                    "ANC word {word:#05x} has invalid parity."
+                )));
+            }
+            checksum = checksum.wrapping_add(word & 0x1ff);
+            Ok(word as u8)
+        };
+        let did = next_word(reader, true)?;
+        let sdid = next_word(reader, true)?;
+        let data_count = next_word(reader, true)?;
+        let user_data = (0..data_count)
+            .map(|_| next_word(reader, false))
+            .collect::<Result<Vec<_>>>()?;
+        let expected = checksum_word(checksum);
+        let found = reader.read(10)? as u16;
+        if found != expected {
+            return Err(Error::Other(format!(
+                "ANC packet {did:#04x}/{sdid:#04x} checksum {found:#05x}, expected {expected:#05x}."
+            )));
+        }
+        reader.skip_to_word(start)?;
+        Ok(Self {
+            c_not_y,
+            line,
+            horizontal_offset,
+            stream: has_stream.then_some(stream_num),
+            did,
+            sdid,
+            user_data,
+        })
+    }
+}
+
+/// The ANC packets of a `video/smpte291` grain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AncGrain {
+    pub field: AncField,
+    pub packets: Vec<AncPacket>,
+}
+
+impl AncGrain {
+    pub fn new(packets: Vec<AncPacket>) -> Self {
+        Self {
+            field: AncField::Unspecified,
+            packets,
+        }
+    }
+
+    /// Size of the RFC 8331 payload of the grain, before zero padding.
+    pub fn encoded_size(&self) -> usize {
+        GRAIN_HEADER_SIZE
+            + self
+                .packets
+                .iter()
+                .map(AncPacket::encoded_size)
+                .sum::<usize>()
+    }
+
+    /// Writes the grain payload to `destination`, typically the payload of a grain opened for
+    /// writing, zero-filling the rest.
+    pub fn encode(&self, destination: &mut [u8]) -> Result<()> {
+        let size = self.encoded_size();
+        if size > destination.len() {
+            return Err(Error::Other(format!(
+                "ANC packets of {size} bytes do not fit in a grain of {} bytes.",
+                destination.len()
+            )));
+        }
+        let anc_count = u8::try_from(self.packets.len())
+            .map_err(|_| Error::Other("More than 255 ANC packets in one grain.".to_string()))?;
+        let length = u16::try_from(size - GRAIN_HEADER_SIZE)
+            .map_err(|_| Error::Other("ANC packets exceed the RFC 8331 length.".to_string()))?;
+        let mut writer = BitWriter::default();
+        writer.write(16, length as u32);
+        writer.write(8, anc_count as u32);
+        writer.write(2, self.field.bits());
+        writer.write(22, 0);
+        for packet in &self.packets {
+            packet.encode(&mut writer)?;
+        }
+        let bytes = writer.into_bytes();
+        destination[..bytes.len()].copy_from_slice(&bytes);
+        destination[bytes.len()..].fill(0);
+        Ok(())
+    }
+
+    /// Parses a grain payload.
+    pub fn decode(payload: &[u8]) -> Result<Self> {
+        let header = payload
+            .get(..GRAIN_HEADER_SIZE)
+            .ok_or_else(|| Error::Other("ANC grain shorter than its header.".to_string()))?;
+        let mut reader = BitReader::new(header);
+        let length = reader.read(16)? as usize;
+        let anc_count = reader.read(8)?;
+        let field = AncField::from_bits(reader.read(2)?)?;
+        let packets = payload
+            .get(GRAIN_HEADER_SIZE..GRAIN_HEADER_SIZE + length)
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "RFC 8331 length {length} exceeds the grain of {} bytes.",
+                    payload.len()
+                ))
+            })?;
+        let mut reader = BitReader::new(packets);
+        let packets = (0..anc_count)
+            .map(|_| AncPacket::decode(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        if reader.position() != length * 8 {
+            return Err(Error::Other(
+                "RFC 8331 length does not match the ANC packets.".to_string(),
+            ));
+        }
+        Ok(Self { field, packets })
+    }
+}
+
+/// Bit of the ST 12-1 time code word flagging the second frame of a pair at `rate`, `None` at rates
+/// up to 30 fps that count every frame. It is the field mark bit of ST 12-1, whose position depends
+/// on the 25 or 30 fps family.
+fn frame_pair_flag_bit(rate: &mxl_sys::Rational) -> Result<Option<u32>> {
+    if !rate.is_valid() {
+        return Err(Error::Other(format!(
+            "Invalid timecode rate {}/{}",
+            rate.numerator, rate.denominator
+        )));
+    }
+    let nominal = (rate.numerator as u64).div_ceil(rate.denominator as u64);
+    Ok(match nominal {
+        0..=30 => None,
+        _ if nominal.is_multiple_of(25) => Some(59),
+        _ => Some(27),
+    })
+}
+
+/// 10-bit ST 291 word of an 8-bit value: bit 8 is the even parity of bits 0-7, bit 9 its inverse.
+fn with_parity(value: u8) -> u16 {
+    let parity = (value.count_ones() % 2) as u16;
+    value as u16 | parity << 8 | (parity ^ 1) << 9
+}
+
+/// Checksum word from the 9-bit sum of the DID, SDID, Data_Count and user data words.
+fn checksum_word(sum: u16) -> u16 {
+    let sum = sum & 0x1ff;
+    sum | (!sum & 0x100) << 1
+}
+
+/// MSB-first bit packing.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, bits: u32, value: u32) {
+        self.accumulator = self.accumulator << bits | (value as u64 & ((1 << bits) - 1));
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.accumulator >> self.bits) as u8);
+        }
+    }
+
+    /// Bits written so far.
+    fn len(&self) -> usize {
+        self.bytes.len() * 8 + self.bits as usize
+    }
+
+    /// Zero-pads to the next 32-bit boundary from `start`.
+    fn pad_to_word(&mut self, start: usize) {
+        let written = self.len() - start;
+        let padding = written.next_multiple_of(32) - written;
+        for _ in 0..padding / 8 {
+            self.write(8, 0);
+        }
+        self.write((padding % 8) as u32, 0);
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        debug_assert_eq!(self.bits, 0);
+        self.bytes
+    }
+}
+
+/// MSB-first bit unpacking.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn read(&mut self, bits: usize) -> Result<u32> {
+        if self.position + bits > self.bytes.len() * 8 {
+            return Err(Error::Other("ANC data ends within a packet.".to_string()));
+        }
+        let mut value = 0u32;
+        for _ in 0..bits {
+            let bit = self.bytes[self.position / 8] >> (7 - self.position % 8) & 1;
+            value = value << 1 | bit as u32;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Skips the zero padding up to the next 32-bit boundary from `start`.
+    fn skip_to_word(&mut self, start: usize) -> Result<()> {
+        let read = self.position - start;
+        let padding = read.next_multiple_of(32) - read;
+        if self.read(padding)? != 0 {
+            return Err(Error::Other(
+                "RFC 8331 word alignment bits must be zero.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,86 @@
 // SPDX-FileCopyrightText: 2025 2025 Contributors to the Media eXchange Layer project.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{path::Path, sync::Arc};
+use std::{ffi::CStr, fmt, path::Path, sync::Arc};
 
 use mxl_sys::libmxl;
 
-use crate::Result;
+use crate::{Error, Result, instrument::ffi_call};
 
 pub type MxlApi = libmxl;
 pub type MxlApiHandle = Arc<MxlApi>;
 
+/// Version information reported by a loaded MXL library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+    pub bugfix: u16,
+    pub build: u16,
+    /// Full version string, including the build suffix and git hash.
+    pub full: String,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.full.is_empty() {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.bugfix)
+        } else {
+            f.write_str(&self.full)
+        }
+    }
+}
+
+/// Queries on the loaded MXL library itself, independent of any instance.
+pub trait MxlApiExt {
+    /// Returns the version of the loaded library.
+    fn version(&self) -> Result<Version>;
+}
+
+impl MxlApiExt for MxlApi {
+    fn version(&self) -> Result<Version> {
+        let mut version = mxl_sys::VersionType::default();
+        Error::from_status(ffi_call!("mxlGetVersion"; unsafe {
+            self.get_version(&mut version)
+        }))?;
+        let full = if version.full.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(version.full) }
+                .to_string_lossy()
+                .into_owned()
+        };
+        Ok(Version {
+            major: version.major,
+            minor: version.minor,
+            bugfix: version.bugfix,
+            build: version.build,
+            full,
+        })
+    }
+}
+
+/// Loads the MXL library from the given path.
+///
+/// When the bindings were generated against a built library, the loaded library must have the
+/// same major version and at least the same minor version; anything else would risk struct layout
+/// mismatches that only show up as crashes later on.
 pub fn load_api(path_to_so_file: impl AsRef<Path>) -> Result<MxlApiHandle> {
-    Ok(Arc::new(unsafe {
-        libmxl::new(path_to_so_file.as_ref().as_os_str())?
-    }))
+    let api = unsafe { libmxl::new(path_to_so_file.as_ref().as_os_str())? };
+    #[cfg(not(feature = "mxl-not-built"))]
+    check_version(&api.version()?)?;
+    Ok(Arc::new(api))
+}
+
+#[cfg(not(feature = "mxl-not-built"))]
+fn check_version(version: &Version) -> Result<()> {
+    let expected_major = mxl_sys::MXL_VERSION_MAJOR;
+    let expected_minor = mxl_sys::MXL_VERSION_MINOR;
+    if u32::from(version.major) != expected_major || u32::from(version.minor) < expected_minor {
+        return Err(Error::IncompatibleVersion {
+            found: version.to_string(),
+            expected: format!("{expected_major}.{expected_minor}"),
+        });
+    }
+    Ok(())
 }
@@ -0,0 +1,190 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Creates a flow and writes a test signal into it at the flow's rate: colour bars or a frame
+//! counter for video, a tone or silence for audio.
+
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use mxl::{
+    MxlInstance, Rational, RationalExt,
+    config::get_mxl_so_path,
+    generate::{
+        AudioGenerator, AudioPattern, VideoGenerator, VideoPattern, audio_flow_def, video_flow_def,
+    },
+};
+use uuid::Uuid;
+
+/// Duration of a batch of samples.
+const BATCH_DURATION: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Parser)]
+#[command(version = clap::crate_version!(), author = clap::crate_authors!())]
+pub struct Opts {
+    /// The path to the shmem directory where the mxl domain is mapped.
+    #[arg(long)]
+    pub mxl_domain: String,
+
+    /// The id of the flow to create. A random one if not given.
+    #[arg(long)]
+    pub flow_id: Option<Uuid>,
+
+    /// The label of the flow.
+    #[arg(long, default_value = "mxl-gen")]
+    pub label: String,
+
+    /// The number of grains or samples to write. If not specified, will run until stopped.
+    #[arg(long)]
+    pub count: Option<u64>,
+
+    #[command(subcommand)]
+    pub signal: Signal,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Signal {
+    /// A `video/v210` flow.
+    Video {
+        #[arg(long, value_enum, default_value_t = VideoKind::Colorbars)]
+        pattern: VideoKind,
+
+        #[arg(long, default_value_t = 1920)]
+        width: u32,
+
+        #[arg(long, default_value_t = 1080)]
+        height: u32,
+
+        /// Grain rate as `numerator/denominator` or `numerator`.
+        #[arg(long, default_value = "30000/1001", value_parser = parse_rate)]
+        rate: Rational,
+    },
+    /// An `audio/float32` flow.
+    Audio {
+        #[arg(long, value_enum, default_value_t = AudioKind::Tone)]
+        pattern: AudioKind,
+
+        /// Frequency of the tone in Hz.
+        #[arg(long, default_value_t = 1000.0)]
+        frequency: f64,
+
+        /// Amplitude of the tone relative to full scale.
+        #[arg(long, default_value_t = 0.5)]
+        amplitude: f64,
+
+        #[arg(long, default_value_t = 2)]
+        channels: u32,
+
+        /// Sample rate in Hz.
+        #[arg(long, default_value_t = 48000)]
+        rate: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum VideoKind {
+    Colorbars,
+    Counter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AudioKind {
+    Tone,
+    Silence,
+}
+
+fn parse_rate(value: &str) -> Result<Rational, String> {
+    let (numerator, denominator) = value.split_once('/').unwrap_or((value, "1"));
+    let rate = Rational {
+        numerator: numerator
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid rate {value}"))?,
+        denominator: denominator
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid rate {value}"))?,
+    };
+    if !rate.is_valid() {
+        return Err(format!("Invalid rate {value}"));
+    }
+    Ok(rate)
+}
+
+fn main() -> Result<(), mxl::Error> {
+    let opts: Opts = Opts::parse();
+    let flow_id = opts.flow_id.unwrap_or_else(Uuid::new_v4);
+
+    let mxl_api = mxl::load_api(get_mxl_so_path())?;
+    let mxl_instance = MxlInstance::new(mxl_api, &opts.mxl_domain, "")?;
+    match opts.signal {
+        Signal::Video {
+            pattern,
+            width,
+            height,
+            rate,
+        } => {
+            let pattern = match pattern {
+                VideoKind::Colorbars => VideoPattern::ColorBars,
+                VideoKind::Counter => VideoPattern::Counter,
+            };
+            let generator = VideoGenerator::new(width as usize, height as usize, pattern)?;
+            let flow_def = video_flow_def(flow_id, &opts.label, width, height, rate);
+            let (writer, _, _) = mxl_instance.create_flow_writer(&flow_def, None)?;
+            let mut writer = writer.to_grain_writer()?;
+            eprintln!("Writing {pattern:?} to flow {flow_id}.");
+
+            let mut index = mxl_instance.get_current_index(&rate);
+            for _ in 0..opts.count.unwrap_or(u64::MAX) {
+                let mut access = writer.open_grain(index)?;
+                let total_slices = access.total_slices();
+                generator.render(index, access.payload_mut())?;
+                access.commit(total_slices)?;
+                index += 1;
+                mxl_instance.sleep_until_index(index, &rate)?;
+            }
+        }
+        Signal::Audio {
+            pattern,
+            frequency,
+            amplitude,
+            channels,
+            rate,
+        } => {
+            let pattern = match pattern {
+                AudioKind::Tone => AudioPattern::Tone {
+                    frequency,
+                    amplitude,
+                },
+                AudioKind::Silence => AudioPattern::Silence,
+            };
+            let generator = AudioGenerator::new(pattern, u64::from(rate))?;
+            let rate = Rational {
+                numerator: i64::from(rate),
+                denominator: 1,
+            };
+            let flow_def = audio_flow_def(flow_id, &opts.label, channels, rate);
+            let (writer, _, _) = mxl_instance.create_flow_writer(&flow_def, None)?;
+            let mut writer = writer.to_samples_writer()?;
+            eprintln!("Writing {pattern:?} to flow {flow_id}.");
+
+            let batch = rate.count_in(BATCH_DURATION).max(1);
+            let total = opts.count.unwrap_or(u64::MAX);
+            // Index of the last sample written.
+            let mut index = mxl_instance.get_current_index(&rate);
+            let mut written = 0;
+            while written < total {
+                let count = batch.min(total - written);
+                index += count;
+                let mut access = writer.open_samples(index, count as usize)?;
+                for channel in 0..access.channels() {
+                    generator.fill(index + 1 - count, access.channel_data_mut(channel)?);
+                }
+                access.commit()?;
+                written += count;
+                mxl_instance.sleep_until_index(index + batch, &rate)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lists the flows of an MXL domain with their format, rate, size, head index and whether their
+//! writer is still writing.
+
+use std::time::Duration;
+
+use clap::Parser;
+use mxl::{DataFormat, MxlInstance, config::get_mxl_so_path};
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(version = clap::crate_version!(), author = clap::crate_authors!())]
+pub struct Opts {
+    /// The path to the shmem directory where the mxl domain is mapped.
+    #[arg(long)]
+    pub mxl_domain: String,
+
+    /// Milliseconds without writes after which a flow is reported stale.
+    #[arg(long, default_value_t = 1000)]
+    pub stale_threshold_ms: u64,
+
+    /// Print a JSON object per flow instead of a table.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct FlowSummary {
+    id: String,
+    label: String,
+    format: String,
+    media_type: String,
+    rate: String,
+    /// `WxH` of video flows, channels of audio flows.
+    size: String,
+    head_index: u64,
+    /// `active`, `stale` or the error preventing the flow from being read.
+    state: String,
+}
+
+fn main() -> Result<(), mxl::Error> {
+    let opts: Opts = Opts::parse();
+
+    let mxl_api = mxl::load_api(get_mxl_so_path())?;
+    let mxl_instance = MxlInstance::open_read_only(mxl_api, &opts.mxl_domain)?;
+    let threshold = Duration::from_millis(opts.stale_threshold_ms);
+    let flows: Vec<FlowSummary> = mxl_instance
+        .domain()
+        .flow_ids()?
+        .into_iter()
+        .map(|id| {
+            summarize(&mxl_instance, &id.to_string(), threshold).unwrap_or_else(|error| {
+                FlowSummary {
+                    id: id.to_string(),
+                    state: error.to_string(),
+                    ..Default::default()
+                }
+            })
+        })
+        .collect();
+
+    if opts.json {
+        for flow in &flows {
+            println!("{}", serde_json::to_string(flow).unwrap_or_default());
+        }
+    } else {
+        print_table(&flows);
+    }
+    Ok(())
+}
+
+fn summarize(instance: &MxlInstance, id: &str, threshold: Duration) -> mxl::Result<FlowSummary> {
+    let flow_def: serde_json::Value = serde_json::from_str(&instance.get_flow_def(id)?)
+        .map_err(|error| mxl::Error::Other(format!("Invalid flow definition: {error}")))?;
+    let info = instance.create_flow_reader(id)?.get_info()?;
+    let common = info.config.common();
+    let rate = common.grain_or_sample_rate();
+    let size = match common.data_format() {
+        DataFormat::Video => format!("{}x{}", flow_def["frame_width"], flow_def["frame_height"]),
+        DataFormat::Audio => format!("{} ch", info.config.continuous()?.channelCount),
+        DataFormat::Data | DataFormat::Unspecified => String::new(),
+    };
+    let stale = info.runtime.is_stale(instance.get_time(), threshold);
+    Ok(FlowSummary {
+        id: id.to_string(),
+        label: flow_def["label"].as_str().unwrap_or_default().to_string(),
+        format: format!("{:?}", common.data_format()).to_lowercase(),
+        media_type: flow_def["media_type"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        rate: format!("{}/{}", rate.numerator, rate.denominator),
+        size,
+        head_index: info.runtime.head_index(),
+        state: if stale { "stale" } else { "active" }.to_string(),
+    })
+}
+
+fn print_table(flows: &[FlowSummary]) {
+    let header = [
+        "ID",
+        "FORMAT",
+        "MEDIA TYPE",
+        "RATE",
+        "SIZE",
+        "HEAD",
+        "STATE",
+        "LABEL",
+    ];
+    let rows: Vec<[String; 8]> = flows
+        .iter()
+        .map(|flow| {
+            [
+                flow.id.clone(),
+                flow.format.clone(),
+                flow.media_type.clone(),
+                flow.rate.clone(),
+                flow.size.clone(),
+                flow.head_index.to_string(),
+                flow.state.clone(),
+                flow.label.clone(),
+            ]
+        })
+        .collect();
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let print_row = |cells: &[&str]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+    print_row(&header);
+    for row in &rows {
+        print_row(&row.each_ref().map(String::as_str));
+    }
+}
@@ -0,0 +1,218 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Records the grains or samples of a flow to a file for a given time.
+//!
+//! Video is written as raw grains, planar 4:2:2 or Y4M, audio as raw interleaved samples or WAV.
+//! Grains or samples lost because the recorder fell behind the ring are reported and skipped. A
+//! flow whose writer stopped is waited for up to `--stale-timeout` before the recording ends.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use clap::{Parser, ValueEnum};
+use crossbeam_channel::RecvTimeoutError;
+use mxl::{
+    FlowEvent, InterleaveOptions, MxlInstance, OwnedSamplesData, RationalExt, ReaderService,
+    config::get_mxl_so_path,
+    dump::{DumpFormat, GrainDump, WavWriter},
+};
+
+/// Time without writes after which the flow is reported stale.
+const STALE_THRESHOLD: Duration = Duration::from_millis(500);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Grains or interleaved samples as they are in the flow.
+    Raw,
+    /// v210 unpacked to planar 10-bit 4:2:2.
+    Planar,
+    Y4m,
+    Wav,
+}
+
+#[derive(Debug, Parser)]
+#[command(version = clap::crate_version!(), author = clap::crate_authors!())]
+pub struct Opts {
+    /// The path to the shmem directory where the mxl domain is mapped.
+    #[arg(long)]
+    pub mxl_domain: String,
+
+    /// The id of the flow to record.
+    #[arg(long)]
+    pub flow_id: String,
+
+    /// Seconds to record.
+    #[arg(long)]
+    pub duration: f64,
+
+    /// The file to record to.
+    #[arg(long)]
+    pub output: PathBuf,
+
+    /// Format of the file. Defaults to the output's extension, `y4m` or `wav`, or raw.
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+
+    /// Seconds to wait for a stale flow to resume before ending the recording.
+    #[arg(long, default_value_t = 5.0)]
+    pub stale_timeout: f64,
+}
+
+enum Recorder {
+    Grains(GrainDump<BufWriter<File>>),
+    Wav(WavWriter<BufWriter<File>>),
+    RawSamples(BufWriter<File>),
+}
+
+impl Recorder {
+    fn samples(&mut self, samples: &OwnedSamplesData, count: usize) -> mxl::Result<()> {
+        let options = InterleaveOptions::new().format(samples.format);
+        let interleaved = samples.interleave(&options)?;
+        let len = options.interleaved_len(count, samples.payload.len());
+        match self {
+            Recorder::Wav(wav) => wav.write_interleaved(&interleaved[..len]),
+            Recorder::RawSamples(file) => Ok(file.write_all(&interleaved[..len])?),
+            Recorder::Grains(_) => {
+                Err(mxl::Error::Other("Samples of a discrete flow.".to_string()))
+            }
+        }
+    }
+
+    fn finish(self) -> mxl::Result<()> {
+        match self {
+            Recorder::Grains(dump) => dump.finish().map(drop),
+            Recorder::Wav(wav) => wav.finish().map(drop),
+            Recorder::RawSamples(mut file) => Ok(file.flush()?),
+        }
+    }
+}
+
+fn default_format(output: &Path) -> Format {
+    match output.extension().and_then(|extension| extension.to_str()) {
+        Some("y4m") => Format::Y4m,
+        Some("wav") => Format::Wav,
+        _ => Format::Raw,
+    }
+}
+
+fn main() -> Result<(), mxl::Error> {
+    let opts: Opts = Opts::parse();
+    let format = opts.format.unwrap_or_else(|| default_format(&opts.output));
+    let duration = Duration::try_from_secs_f64(opts.duration)
+        .map_err(|error| mxl::Error::Other(format!("Invalid duration: {error}")))?;
+    let stale_timeout = Duration::try_from_secs_f64(opts.stale_timeout)
+        .map_err(|error| mxl::Error::Other(format!("Invalid stale timeout: {error}")))?;
+
+    let mxl_api = mxl::load_api(get_mxl_so_path())?;
+    let mxl_instance = MxlInstance::open_read_only(mxl_api, &opts.mxl_domain)?;
+    let flow_def = mxl_instance.get_flow_def(&opts.flow_id)?;
+    let config = mxl_instance
+        .create_flow_reader(&opts.flow_id)?
+        .get_info()?
+        .config;
+    let rate = config.common().grain_or_sample_rate();
+    let total = rate.count_in(duration);
+
+    let mut recorder = if config.is_discrete_flow() {
+        let dump_format = match format {
+            Format::Raw => DumpFormat::Raw,
+            Format::Planar => DumpFormat::Planar,
+            Format::Y4m => DumpFormat::Y4m,
+            Format::Wav => {
+                return Err(mxl::Error::Other(
+                    "WAV files are only supported for audio flows.".to_string(),
+                ));
+            }
+        };
+        Recorder::Grains(GrainDump::create(&opts.output, &flow_def, dump_format)?)
+    } else {
+        match format {
+            Format::Raw => Recorder::RawSamples(BufWriter::new(File::create(&opts.output)?)),
+            Format::Wav => {
+                let sample_rate = u32::try_from(rate.numerator / rate.denominator.max(1))
+                    .map_err(|_| mxl::Error::Other(format!("Invalid sample rate {rate:?}")))?;
+                let channels = u16::try_from(config.continuous()?.channelCount)
+                    .map_err(|_| mxl::Error::Other("Too many channels for WAV.".to_string()))?;
+                let sample_format = mxl_instance
+                    .create_flow_reader(&opts.flow_id)?
+                    .to_samples_reader()?
+                    .sample_format();
+                Recorder::Wav(WavWriter::create(
+                    &opts.output,
+                    sample_rate,
+                    channels,
+                    sample_format,
+                )?)
+            }
+            Format::Planar | Format::Y4m => {
+                return Err(mxl::Error::Other(format!(
+                    "{format:?} files are only supported for video flows."
+                )));
+            }
+        }
+    };
+
+    let mut service = ReaderService::new(mxl_instance).stale_threshold(STALE_THRESHOLD);
+    let events = service.subscribe(&opts.flow_id)?;
+    let mut recorded = 0;
+    let mut stale_since = None;
+    while recorded < total {
+        let event = match events.recv_timeout(POLL_INTERVAL) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => {
+                if stale_since.is_some_and(|since: Instant| since.elapsed() >= stale_timeout) {
+                    eprintln!("The flow stayed stale for {stale_timeout:?}, stopping.");
+                    break;
+                }
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        match event {
+            FlowEvent::Grain { grain, .. } => {
+                if let Recorder::Grains(dump) = &mut recorder {
+                    dump.write_grain(&grain.payload)?;
+                }
+                recorded += 1;
+            }
+            FlowEvent::Samples { samples, .. } => {
+                let count = (samples.num_of_samples() as u64).min(total - recorded);
+                recorder.samples(&samples, count as usize)?;
+                recorded += count;
+            }
+            FlowEvent::Skipped { from, to } => {
+                eprintln!("Fell behind the writer, {} indices were lost.", to - from);
+            }
+            FlowEvent::Stale => {
+                eprintln!("The flow is stale, waiting for its writer.");
+                stale_since = Some(Instant::now());
+            }
+            FlowEvent::Resumed => {
+                eprintln!("The flow resumed.");
+                stale_since = None;
+            }
+            FlowEvent::Closed(error) => {
+                eprintln!("The flow cannot be read anymore: {error}");
+                break;
+            }
+        }
+    }
+    drop(service);
+    recorder.finish()?;
+    let unit = if config.is_discrete_flow() {
+        "grains"
+    } else {
+        "samples"
+    };
+    eprintln!(
+        "Recorded {recorded} of {total} {unit} to {}.",
+        opts.output.display()
+    );
+    Ok(())
+}
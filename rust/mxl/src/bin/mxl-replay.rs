@@ -0,0 +1,233 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Plays a recording, e.g. of `mxl-record`, into a flow at the flow's rate.
+//!
+//! Grains and sample batches are written at the indices of the current time and the writer sleeps
+//! until the next index is due, so readers see the recording as they would a live source.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::{Parser, ValueEnum};
+use mxl::{
+    GrainWriter, InterleaveOptions, MxlInstance, SampleBatcher, SampleFormat,
+    config::get_mxl_so_path,
+    dump::{DumpFormat, GrainReplay, WavReader},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Grains or interleaved samples as they are in the flow.
+    Raw,
+    /// v210 unpacked to planar 10-bit 4:2:2.
+    Planar,
+    Y4m,
+    Wav,
+}
+
+#[derive(Debug, Parser)]
+#[command(version = clap::crate_version!(), author = clap::crate_authors!())]
+pub struct Opts {
+    /// The path to the shmem directory where the mxl domain is mapped.
+    #[arg(long)]
+    pub mxl_domain: String,
+
+    /// The path to the json file which describes the flow to write to.
+    #[arg(long)]
+    pub flow_config_file: String,
+
+    /// The recording to play.
+    #[arg(long)]
+    pub input: PathBuf,
+
+    /// Format of the recording. Defaults to the input's extension, `y4m` or `wav`, or raw.
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+
+    /// Play the recording again from the start when it ends, until interrupted.
+    #[arg(long = "loop")]
+    pub looping: bool,
+}
+
+fn default_format(input: &Path) -> Format {
+    match input.extension().and_then(|extension| extension.to_str()) {
+        Some("y4m") => Format::Y4m,
+        Some("wav") => Format::Wav,
+        _ => Format::Raw,
+    }
+}
+
+fn main() -> Result<(), mxl::Error> {
+    let opts: Opts = Opts::parse();
+    let format = opts.format.unwrap_or_else(|| default_format(&opts.input));
+    let flow_def = std::fs::read_to_string(&opts.flow_config_file).map_err(|error| {
+        mxl::Error::Other(format!(
+            "Error while reading flow definition from \"{}\": {error}",
+            opts.flow_config_file
+        ))
+    })?;
+
+    let mxl_api = mxl::load_api(get_mxl_so_path())?;
+    let mxl_instance = MxlInstance::new(mxl_api, &opts.mxl_domain, "")?;
+    let (writer, config, _) = mxl_instance.create_flow_writer(&flow_def, None)?;
+    if config.is_discrete_flow() {
+        let dump_format = match format {
+            Format::Raw => DumpFormat::Raw,
+            Format::Planar => DumpFormat::Planar,
+            Format::Y4m => DumpFormat::Y4m,
+            Format::Wav => {
+                return Err(mxl::Error::Other(
+                    "WAV files are only supported for audio flows.".to_string(),
+                ));
+            }
+        };
+        let rate = config.common().grain_rate()?;
+        play_grains(
+            &mxl_instance,
+            writer.to_grain_writer()?,
+            &rate,
+            || GrainReplay::open(&opts.input, &flow_def, dump_format),
+            opts.looping,
+        )
+    } else {
+        let channels = config.continuous()?.channelCount as usize;
+        let rate = config.common().sample_rate()?;
+        let writer = writer.to_samples_writer()?;
+        let flow_format = writer.sample_format();
+        let batcher = SampleBatcher::new(writer, &config, mxl_instance.get_current_index(&rate))?;
+        let open = || -> mxl::Result<SamplesSource> {
+            match format {
+                Format::Wav => {
+                    let wav = WavReader::open(&opts.input)?;
+                    if usize::from(wav.channels()) != channels {
+                        return Err(mxl::Error::Other(format!(
+                            "The recording has {} channels, the flow {channels}.",
+                            wav.channels()
+                        )));
+                    }
+                    Ok(SamplesSource::Wav(wav))
+                }
+                Format::Raw => Ok(SamplesSource::Raw(
+                    BufReader::new(File::open(&opts.input)?),
+                    flow_format,
+                )),
+                Format::Planar | Format::Y4m => Err(mxl::Error::Other(format!(
+                    "{format:?} files are only supported for video flows."
+                ))),
+            }
+        };
+        play_samples(&mxl_instance, batcher, &rate, channels, open, opts.looping)
+    }
+}
+
+fn play_grains(
+    instance: &MxlInstance,
+    mut writer: GrainWriter,
+    rate: &mxl::Rational,
+    open: impl Fn() -> mxl::Result<GrainReplay<BufReader<File>>>,
+    looping: bool,
+) -> Result<(), mxl::Error> {
+    let mut replay = open()?;
+    let mut index = instance.get_current_index(rate);
+    let mut played = 0u64;
+    loop {
+        let payload = match replay.next_grain()? {
+            Some(payload) => payload,
+            None if looping && played > 0 => {
+                replay = open()?;
+                continue;
+            }
+            None => break,
+        };
+        let mut access = writer.open_grain(index)?;
+        let total_slices = access.total_slices();
+        let destination = access.payload_mut();
+        let len = destination.len().min(payload.len());
+        destination[..len].copy_from_slice(&payload[..len]);
+        access.commit(total_slices)?;
+        played += 1;
+        index += 1;
+        let late = instance.sleep_until_index(index, rate)?;
+        if late > Duration::ZERO {
+            eprintln!("Grain {index} is {late:?} late.");
+        }
+    }
+    eprintln!("Played {played} grains.");
+    Ok(())
+}
+
+enum SamplesSource {
+    Wav(WavReader<BufReader<File>>),
+    Raw(BufReader<File>, SampleFormat),
+}
+
+impl SamplesSource {
+    fn format(&self) -> SampleFormat {
+        match self {
+            SamplesSource::Wav(wav) => wav.format(),
+            SamplesSource::Raw(_, format) => *format,
+        }
+    }
+
+    /// Reads whole frames into `buffer`, returning the bytes read, `0` at the end.
+    fn read(&mut self, buffer: &mut [u8], frame_size: usize) -> mxl::Result<usize> {
+        match self {
+            SamplesSource::Wav(wav) => wav.read_interleaved(buffer),
+            SamplesSource::Raw(file, _) => {
+                let len = buffer.len() / frame_size * frame_size;
+                let mut filled = 0;
+                while filled < len {
+                    match file.read(&mut buffer[filled..len])? {
+                        0 => break,
+                        read => filled += read,
+                    }
+                }
+                Ok(filled / frame_size * frame_size)
+            }
+        }
+    }
+}
+
+fn play_samples(
+    instance: &MxlInstance,
+    batcher: SampleBatcher,
+    rate: &mxl::Rational,
+    channels: usize,
+    open: impl Fn() -> mxl::Result<SamplesSource>,
+    looping: bool,
+) -> Result<(), mxl::Error> {
+    let mut source = open()?;
+    let options = InterleaveOptions::new().format(source.format());
+    let frame_size = options.interleaved_len(1, channels);
+    let mut batcher = batcher.options(options);
+    let batch = batcher.get_batch_size();
+    let mut buffer = vec![0; batch * frame_size];
+    let mut played = 0u64;
+    loop {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            let read = source.read(&mut buffer[filled..], frame_size)?;
+            if read > 0 {
+                filled += read;
+            } else if looping && played + filled as u64 > 0 {
+                source = open()?;
+            } else {
+                break;
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+        played += batcher.push(&buffer[..filled])? as u64;
+        instance.sleep_until_index(batcher.index() + batch as u64, rate)?;
+    }
+    // The end of a recording that does not fill a batch.
+    played += batcher.flush()? as u64;
+    eprintln!("Played {played} samples.");
+    Ok(())
+}
@@ -0,0 +1,370 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cleanup of the flows of a process that is terminated.
+//!
+//! Flows outlive their writer: when a process is killed, readers keep seeing its flows, stale,
+//! until [`crate::MxlInstance::garbage_collect_flows`] runs. A [`CleanupGuard`] makes a domain
+//! heal by itself:
+//!
+//! - Installing it garbage-collects the flows left behind by processes that died earlier, e.g. a
+//!   previous run of this one.
+//! - On a `SIGINT`, `SIGTERM`, `SIGHUP` or `SIGQUIT` that would terminate the process, it releases
+//!   the writers and readers still open in the guarded instance through the library, with
+//!   [`CleanupAction::DeleteCreatedFlows`]. As when they are dropped, the library deletes the flows
+//!   no other process writes.
+//! - With [`CleanupGuard::cleanup_on_exit`], it does the same when the process exits, e.g. through
+//!   [`std::process::exit`]. This is opt-in: a process returning from `main` normally may leave
+//!   flows on purpose, and its writers are often still in use by other threads.
+//!
+//! Signal handlers installed before the guard keep working: the guard passes those signals on to
+//! them and leaves the flows to the application, which releases its writers or exits. Ignored
+//! signals stay ignored. Only a signal with the default disposition is handled by the guard, which
+//! releases the writers and readers and then terminates the process with the signal.
+//!
+//! Other threads may still be using the writers and readers the guard releases. Those must not
+//! call into them anymore, which holds when the process terminates right after, as on a signal.
+//! Dropping them afterwards is fine, they know they were released.
+//!
+//! `SIGKILL` and crashes cannot be intercepted. Their flows are collected by the next guard
+//! installed in the domain, or by any other call to `garbage_collect_flows`.
+//!
+//! ```ignore
+//! let _cleanup = CleanupGuard::install(&instance, CleanupAction::DeleteCreatedFlows)?;
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex, MutexGuard, Once, Weak,
+        atomic::{AtomicBool, AtomicI32, AtomicU8, AtomicUsize, Ordering},
+    },
+    thread::JoinHandle,
+};
+
+use crate::{Error, MxlInstance, Result, instance::InstanceContext, instrument::ffi_call};
+
+const SIGNALS: [libc::c_int; 4] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+/// Instance of the installed guard, whose writers and readers the cleanup releases.
+static GUARDED: Mutex<Option<Weak<InstanceContext>>> = Mutex::new(None);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+/// [`CleanupAction`] of the installed guard, [`NO_ACTION`] without one.
+static ACTION: AtomicU8 = AtomicU8::new(NO_ACTION);
+/// Write end of the pipe the signal handler hands signals to the cleanup thread through.
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+/// `sa_sigaction` and `sa_flags` of the handlers [`SIGNALS`] had before the guard, which
+/// [`on_signal`] passes the signals on to.
+static PREVIOUS_HANDLERS: [AtomicUsize; SIGNALS.len()] =
+    [const { AtomicUsize::new(libc::SIG_DFL) }; SIGNALS.len()];
+static PREVIOUS_FLAGS: [AtomicI32; SIGNALS.len()] = [const { AtomicI32::new(0) }; SIGNALS.len()];
+static AT_EXIT: Once = Once::new();
+/// Whether the exit hook cleans up, see [`CleanupGuard::cleanup_on_exit`].
+static ON_EXIT: AtomicBool = AtomicBool::new(false);
+
+const NO_ACTION: u8 = 0;
+
+/// What a [`CleanupGuard`] does with the flows of a terminated process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CleanupAction {
+    /// Releases the writers and readers still open, so the library deletes the flows this process
+    /// was the last writer of and readers stop waiting on them.
+    DeleteCreatedFlows = 1,
+    /// Leaves the flows to the garbage collection of another process.
+    Keep = 2,
+}
+
+/// Cleans up the flows of the process when it terminates, see the
+/// [module documentation](self). Only one guard can be installed at a time; dropping it restores
+/// the signal handlers it replaced.
+#[derive(Debug)]
+pub struct CleanupGuard {
+    action: CleanupAction,
+    previous: Vec<(libc::c_int, libc::sigaction)>,
+    read_fd: libc::c_int,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CleanupGuard {
+    /// Garbage-collects the flows of `instance`'s domain unless it is read-only, and installs the
+    /// signal handlers, chained to the previous ones.
+    pub fn install(instance: &MxlInstance, action: CleanupAction) -> Result<Self> {
+        if INSTALLED.swap(true, Ordering::AcqRel) {
+            return Err(Error::Other(
+                "A cleanup guard is already installed.".to_string(),
+            ));
+        }
+        if !instance.is_read_only()
+            && let Err(error) = instance.garbage_collect_flows()
+        {
+            tracing::warn!("Failed to garbage-collect the flows of the domain: {error}");
+        }
+
+        let mut fds = [-1; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            INSTALLED.store(false, Ordering::Release);
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let [read_fd, write_fd] = fds;
+        *lock_guarded() = Some(std::sync::Arc::downgrade(instance.context()));
+        SIGNAL_PIPE.store(write_fd, Ordering::Release);
+        ACTION.store(action as u8, Ordering::Release);
+        let mut guard = Self {
+            action,
+            previous: Vec::new(),
+            read_fd,
+            thread: None,
+        };
+        guard.thread = Some(
+            std::thread::Builder::new()
+                .name("mxl-cleanup".to_string())
+                .spawn(move || wait_for_signal(read_fd))?,
+        );
+        for (slot, signal) in SIGNALS.into_iter().enumerate() {
+            let mut previous: libc::sigaction = unsafe { std::mem::zeroed() };
+            if unsafe { libc::sigaction(signal, std::ptr::null(), &mut previous) } != 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            if previous.sa_sigaction == libc::SIG_IGN {
+                continue;
+            }
+            // Recorded before the guard's handler can run.
+            PREVIOUS_HANDLERS[slot].store(previous.sa_sigaction, Ordering::Release);
+            PREVIOUS_FLAGS[slot].store(previous.sa_flags, Ordering::Release);
+            let mut handler: libc::sigaction = unsafe { std::mem::zeroed() };
+            handler.sa_sigaction = on_signal
+                as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+                as libc::sighandler_t;
+            handler.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+            if unsafe { libc::sigaction(signal, &handler, std::ptr::null_mut()) } != 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            guard.previous.push((signal, previous));
+        }
+        Ok(guard)
+    }
+
+    /// Also applies the action when the process exits, which includes returning from `main`.
+    /// Disabled by default.
+    pub fn cleanup_on_exit(self, enabled: bool) -> Self {
+        if enabled {
+            AT_EXIT.call_once(|| {
+                if unsafe { libc::atexit(on_exit) } != 0 {
+                    tracing::warn!("Failed to register the MXL cleanup exit hook.");
+                }
+            });
+        }
+        ON_EXIT.store(enabled, Ordering::Release);
+        self
+    }
+
+    pub fn action(&self) -> CleanupAction {
+        self.action
+    }
+
+    /// Applies the guard's action now, for applications handling termination themselves. The
+    /// writers and readers of the instance must not be used afterwards, see the
+    /// [module documentation](self).
+    pub fn cleanup_now(&self) {
+        cleanup(self.action);
+    }
+}
+
+impl Drop for CleanupGuard {
+    fn drop(&mut self) {
+        for (signal, previous) in self.previous.drain(..) {
+            unsafe { libc::sigaction(signal, &previous, std::ptr::null_mut()) };
+        }
+        for handler in &PREVIOUS_HANDLERS {
+            handler.store(libc::SIG_DFL, Ordering::Release);
+        }
+        ACTION.store(NO_ACTION, Ordering::Release);
+        ON_EXIT.store(false, Ordering::Release);
+        let write_fd = SIGNAL_PIPE.swap(-1, Ordering::AcqRel);
+        // Ends the cleanup thread.
+        unsafe { libc::close(write_fd) };
+        if let Some(thread) = self.thread.take()
+            && thread.join().is_err()
+        {
+            tracing::error!("The MXL cleanup thread panicked.");
+        }
+        unsafe { libc::close(self.read_fd) };
+        *lock_guarded() = None;
+        INSTALLED.store(false, Ordering::Release);
+    }
+}
+
+/// Handles of an instance's writers or readers, with the references the library counts for each:
+/// it hands out the same handle to every writer, or reader, of a flow.
+#[derive(Default)]
+struct Handles(Mutex<HashMap<usize, usize>>);
+
+impl Handles {
+    fn lock(&self) -> MutexGuard<'_, HashMap<usize, usize>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn opened(&self, handle: usize) {
+        *self.lock().entry(handle).or_default() += 1;
+    }
+
+    fn closing(&self, handle: usize) -> bool {
+        let mut handles = self.lock();
+        let Some(references) = handles.get_mut(&handle) else {
+            return false;
+        };
+        *references -= 1;
+        if *references == 0 {
+            handles.remove(&handle);
+        }
+        true
+    }
+
+    fn take(&self) -> HashMap<usize, usize> {
+        std::mem::take(&mut *self.lock())
+    }
+}
+
+/// Writers and readers open in an instance, which a [`CleanupGuard`] releases through the library
+/// when the process terminates.
+#[derive(Default)]
+pub(crate) struct OpenHandles {
+    writers: Handles,
+    readers: Handles,
+}
+
+impl OpenHandles {
+    pub(crate) fn writer_opened(&self, writer: mxl_sys::FlowWriter) {
+        self.writers.opened(writer.expose_provenance());
+    }
+
+    /// Whether the owner of `writer` has to release it, `false` if a guard released it already.
+    pub(crate) fn writer_closing(&self, writer: mxl_sys::FlowWriter) -> bool {
+        self.writers.closing(writer.expose_provenance())
+    }
+
+    pub(crate) fn reader_opened(&self, reader: mxl_sys::FlowReader) {
+        self.readers.opened(reader.expose_provenance());
+    }
+
+    /// Whether the owner of `reader` has to release it, `false` if a guard released it already.
+    pub(crate) fn reader_closing(&self, reader: mxl_sys::FlowReader) -> bool {
+        self.readers.closing(reader.expose_provenance())
+    }
+
+    /// Releases every writer and reader still open, as often as they were handed out.
+    fn release_all(&self, context: &InstanceContext) {
+        for (reader, references) in self.readers.take() {
+            let reader: mxl_sys::FlowReader = std::ptr::with_exposed_provenance_mut(reader);
+            for _ in 0..references {
+                if let Err(error) = Error::from_status(ffi_call!("mxlReleaseFlowReader"; unsafe {
+                    context.api.release_flow_reader(context.instance, reader)
+                })) {
+                    tracing::warn!("Failed to release a flow reader: {error}");
+                }
+            }
+        }
+        for (writer, references) in self.writers.take() {
+            let writer: mxl_sys::FlowWriter = std::ptr::with_exposed_provenance_mut(writer);
+            for _ in 0..references {
+                if let Err(error) = Error::from_status(ffi_call!("mxlReleaseFlowWriter"; unsafe {
+                    context.api.release_flow_writer(context.instance, writer)
+                })) {
+                    tracing::warn!("Failed to release a flow writer: {error}");
+                }
+            }
+        }
+    }
+}
+
+fn lock_guarded() -> MutexGuard<'static, Option<Weak<InstanceContext>>> {
+    GUARDED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn cleanup(action: CleanupAction) {
+    if action != CleanupAction::DeleteCreatedFlows {
+        return;
+    }
+    let context = lock_guarded().as_ref().and_then(Weak::upgrade);
+    if let Some(context) = context {
+        context.open_handles.release_all(&context);
+    }
+}
+
+fn action() -> Option<CleanupAction> {
+    match ACTION.load(Ordering::Acquire) {
+        1 => Some(CleanupAction::DeleteCreatedFlows),
+        2 => Some(CleanupAction::Keep),
+        _ => None,
+    }
+}
+
+/// Passes the signal on to the handler it had before the guard or, if it had none, hands it to
+/// the cleanup thread. Only does what is allowed in a signal handler.
+extern "C" fn on_signal(
+    signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    context: *mut libc::c_void,
+) {
+    let Some(slot) = SIGNALS.iter().position(|&handled| handled == signal) else {
+        return;
+    };
+    match PREVIOUS_HANDLERS[slot].load(Ordering::Acquire) {
+        libc::SIG_DFL => {
+            let fd = SIGNAL_PIPE.load(Ordering::Acquire);
+            if fd >= 0 {
+                let byte = signal as u8;
+                unsafe { libc::write(fd, (&byte as *const u8).cast(), 1) };
+            }
+        }
+        libc::SIG_IGN => {}
+        // SAFETY: the handler was installed for this signal with these flags, which tell its
+        // signature.
+        handler if PREVIOUS_FLAGS[slot].load(Ordering::Acquire) & libc::SA_SIGINFO != 0 => unsafe {
+            let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                std::mem::transmute(handler);
+            handler(signal, info, context);
+        },
+        handler => unsafe {
+            let handler: extern "C" fn(libc::c_int) = std::mem::transmute(handler);
+            handler(signal);
+        },
+    }
+}
+
+extern "C" fn on_exit() {
+    if ON_EXIT.load(Ordering::Acquire)
+        && let Some(action) = action()
+    {
+        cleanup(action);
+    }
+}
+
+/// Cleans up after the first signal with the default disposition, then restores that disposition
+/// and raises the signal again, which terminates the process as it would have without the guard.
+/// Returns when the guard is dropped.
+fn wait_for_signal(read_fd: libc::c_int) {
+    let mut byte = 0u8;
+    loop {
+        match unsafe { libc::read(read_fd, (&mut byte as *mut u8).cast(), 1) } {
+            1 => break,
+            -1 if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted => {}
+            // The write end was closed by the guard.
+            _ => return,
+        }
+    }
+    let Some(action) = action() else {
+        return;
+    };
+    cleanup(action);
+    let signal = libc::c_int::from(byte);
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
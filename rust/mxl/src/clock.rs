@@ -0,0 +1,167 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Time sources of the pacing helpers.
+//!
+//! [`crate::DataGrainWriter`] and the [event](crate::events) publisher and subscriber read the
+//! time and wait through a [`TimeSource`]. By default that is their [`crate::MxlInstance`], i.e.
+//! the TAI clock of the library. Tests replace it with a [`VirtualClock`], which only moves when
+//! told to and whose sleeps return at once, so timing logic runs deterministically and without
+//! wall-clock waits:
+//!
+//! ```ignore
+//! let clock = VirtualClock::at_index(1_000, &rates::FPS_25);
+//! let mut writer = writer.to_data_writer()?.time_source(Arc::new(clock.clone()));
+//! assert_eq!(writer.write_packet(b"first")?, 1_000);
+//! // Waits for the next grain interval, on the virtual clock.
+//! assert_eq!(writer.write_packet(b"second")?, 1_001);
+//! assert_eq!(clock.current_index(&rates::FPS_25), 1_001);
+//! ```
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::{Error, MxlInstance, Rational, RationalExt, Result};
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// Clock in MXL timestamps, nanoseconds of TAI since the SMPTE ST 2059 epoch.
+///
+/// Only [`TimeSource::now`] and [`TimeSource::sleep_until`] are required. The index functions
+/// default to the rounding of the MXL library without an epoch offset.
+pub trait TimeSource: std::fmt::Debug + Send + Sync {
+    fn now(&self) -> u64;
+
+    /// Returns at or after `timestamp`, at once if it is not in the future.
+    fn sleep_until(&self, timestamp: u64);
+
+    /// Index of the grain or sample at `timestamp`. Fails for invalid rates.
+    fn timestamp_to_index(&self, timestamp: u64, rate: &Rational) -> Result<u64> {
+        if !rate.is_valid() {
+            return Err(invalid_rate(rate));
+        }
+        let index = (timestamp as i128 * rate.numerator as i128
+            + NANOS_PER_SECOND / 2 * rate.denominator as i128)
+            / (NANOS_PER_SECOND * rate.denominator as i128);
+        u64::try_from(index).map_err(|_| invalid_rate(rate))
+    }
+
+    /// Timestamp at which the grain or sample at `index` begins. Fails for invalid rates.
+    fn index_to_timestamp(&self, index: u64, rate: &Rational) -> Result<u64> {
+        if !rate.is_valid() {
+            return Err(invalid_rate(rate));
+        }
+        let timestamp = (index as i128 * rate.denominator as i128 * NANOS_PER_SECOND
+            + rate.numerator as i128 / 2)
+            / rate.numerator as i128;
+        u64::try_from(timestamp)
+            .map_err(|_| Error::Other(format!("Timestamp of index {index} is out of range.")))
+    }
+
+    /// Returns 0 for invalid rates, as [`MxlInstance::get_current_index`] does.
+    fn current_index(&self, rate: &Rational) -> u64 {
+        self.timestamp_to_index(self.now(), rate).unwrap_or(0)
+    }
+
+    /// Sleeps until `index` is the current index, see [`MxlInstance::sleep_until_index`].
+    fn sleep_until_index(&self, index: u64, rate: &Rational) -> Result<Duration> {
+        let due = self.index_to_timestamp(index, rate)?;
+        let now = self.now();
+        if now >= due {
+            return Ok(Duration::from_nanos(now - due));
+        }
+        self.sleep_until(due);
+        Ok(Duration::ZERO)
+    }
+}
+
+fn invalid_rate(rate: &Rational) -> Error {
+    Error::Other(format!(
+        "Invalid rate {}/{}.",
+        rate.numerator, rate.denominator
+    ))
+}
+
+/// The library's TAI clock, shifted by the instance's epoch offset in the index functions.
+impl TimeSource for MxlInstance {
+    fn now(&self) -> u64 {
+        self.get_time()
+    }
+
+    fn sleep_until(&self, timestamp: u64) {
+        let now = self.get_time();
+        if timestamp > now {
+            self.sleep_for(Duration::from_nanos(timestamp - now));
+        }
+    }
+
+    fn timestamp_to_index(&self, timestamp: u64, rate: &Rational) -> Result<u64> {
+        MxlInstance::timestamp_to_index(self, timestamp, rate)
+    }
+
+    fn index_to_timestamp(&self, index: u64, rate: &Rational) -> Result<u64> {
+        MxlInstance::index_to_timestamp(self, index, rate)
+    }
+
+    fn current_index(&self, rate: &Rational) -> u64 {
+        self.get_current_index(rate)
+    }
+
+    fn sleep_until_index(&self, index: u64, rate: &Rational) -> Result<Duration> {
+        MxlInstance::sleep_until_index(self, index, rate)
+    }
+}
+
+/// Clock that stands still until it is moved, for tests. Clones share the same time, so a test
+/// keeps one to move the clock of the helper it handed the other to.
+///
+/// Sleeping moves the clock to the end of the sleep, as if the time had passed.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualClock {
+    now: Arc<AtomicU64>,
+}
+
+impl VirtualClock {
+    pub fn new(timestamp: u64) -> Self {
+        Self {
+            now: Arc::new(AtomicU64::new(timestamp)),
+        }
+    }
+
+    /// Clock at the beginning of `index`. Starts at 0 for invalid rates.
+    pub fn at_index(index: u64, rate: &Rational) -> Self {
+        let clock = Self::default();
+        if let Ok(timestamp) = clock.index_to_timestamp(index, rate) {
+            clock.set(timestamp);
+        }
+        clock
+    }
+
+    pub fn set(&self, timestamp: u64) {
+        self.now.store(timestamp, Ordering::Release);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.now
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |now| {
+                Some(now.saturating_add(nanos))
+            })
+            .ok();
+    }
+}
+
+impl TimeSource for VirtualClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::Acquire)
+    }
+
+    fn sleep_until(&self, timestamp: u64) {
+        self.now.fetch_max(timestamp, Ordering::AcqRel);
+    }
+}
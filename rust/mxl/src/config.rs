@@ -23,3 +23,55 @@ pub fn get_mxl_so_path() -> std::path::PathBuf {
 pub fn get_mxl_repo_root() -> std::path::PathBuf {
     std::path::PathBuf::from_str(MXL_REPO_ROOT).expect("build error: 'MXL_REPO_ROOT' is invalid")
 }
+
+/// Sanitizer the MXL library was built with (`"address"` or `"thread"`), selected with the `asan`
+/// and `tsan` features.
+pub fn sanitizer() -> Option<&'static str> {
+    mxl_sys::SANITIZER
+}
+
+/// Environment a process loading an instrumented MXL library must be started with.
+///
+/// Rust executables are not instrumented, so the shared sanitizer runtime has to be preloaded and
+/// come first in the library list. Empty if the library is not instrumented.
+pub fn sanitizer_env() -> Vec<(&'static str, String)> {
+    let mut env = Vec::new();
+    if let Some(runtime) = mxl_sys::SANITIZER_RUNTIME {
+        env.push(("LD_PRELOAD", runtime.to_string()));
+    }
+    match mxl_sys::SANITIZER {
+        Some("address") => env.push((
+            "ASAN_OPTIONS",
+            "halt_on_error=1:detect_leaks=1:verify_asan_link_order=0".to_string(),
+        )),
+        Some("thread") => env.push((
+            "TSAN_OPTIONS",
+            "halt_on_error=1:second_deadlock_stack=1".to_string(),
+        )),
+        _ => {}
+    }
+    env
+}
+
+/// Checks that the current process was started with [`sanitizer_env`], so that a sanitizer run
+/// fails loudly instead of aborting in the dynamic loader or silently running uninstrumented.
+/// Meant to be called at the start of integration tests.
+pub fn check_sanitizer_env() -> crate::Result<()> {
+    let Some(runtime) = mxl_sys::SANITIZER_RUNTIME else {
+        return Ok(());
+    };
+    let preloaded = std::env::var("LD_PRELOAD")
+        .is_ok_and(|preload| preload.split([':', ' ']).any(|entry| entry == runtime));
+    if preloaded {
+        return Ok(());
+    }
+    let env = sanitizer_env()
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Err(crate::Error::Other(format!(
+        "The MXL library is built with the {} sanitizer, run with: {env}",
+        mxl_sys::SANITIZER.unwrap_or("unknown")
+    )))
+}
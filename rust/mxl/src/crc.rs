@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! CRC-32 checksums of grain payloads, to tell whether corrupted media was damaged in the MXL
+//! flow or by the element producing or consuming it.
+//!
+//! The writer records the checksum of every grain it writes in a companion
+//! [event flow](crate::events), so the media flow itself is left as is. Readers of the media flow
+//! that also open the companion flow check the grains they read against it:
+//!
+//! ```ignore
+//! let mut checksums = CrcPublisher::create(&instance, crc_flow_id, rate)?;
+//! writer.write_grain(index, &payload)?;
+//! checksums.record(index, &payload)?;
+//!
+//! let mut verifier = CrcVerifier::open(&instance, crc_flow_id)?;
+//! let grain = reader.get_complete_grain(index, timeout)?;
+//! if let CrcCheck::Mismatch { .. } = verifier.verify(grain.index, grain.payload)? {
+//!     tracing::error!("Grain {index} was corrupted in transport.");
+//! }
+//! ```
+//!
+//! Only whole grains are checked. The checksums are a debugging aid: computing them costs a pass
+//! over every payload on both ends, and the companion flow is paced like any event flow, so the
+//! writer must not write more than one grain per grain interval.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    MxlInstance, Rational, Result,
+    events::{EventPublisher, EventSubscriber},
+};
+
+/// Checksums kept by a [`CrcVerifier`] for grains not read yet.
+const MAX_PENDING_CHECKSUMS: usize = 1024;
+
+/// Checksum record published in the companion flow.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    index: u64,
+    crc32: u32,
+}
+
+/// CRC-32 (IEEE) of `payload`.
+pub fn payload_crc(payload: &[u8]) -> u32 {
+    crc32fast::hash(payload)
+}
+
+/// Publishes the checksums of the grains of a flow, see the [module documentation](self).
+#[derive(Debug)]
+pub struct CrcPublisher {
+    events: EventPublisher,
+}
+
+impl CrcPublisher {
+    /// Creates the companion flow `flow_id` at `rate`, the grain rate of the media flow.
+    pub fn create(instance: &MxlInstance, flow_id: Uuid, rate: Rational) -> Result<Self> {
+        Ok(Self {
+            events: EventPublisher::create(instance, flow_id, "crc32", rate)?,
+        })
+    }
+
+    pub fn flow_id(&self) -> Uuid {
+        self.events.flow_id()
+    }
+
+    /// Records the checksum of the grain at `index`, whose whole payload is `payload`.
+    pub fn record(&mut self, index: u64, payload: &[u8]) -> Result<()> {
+        self.events.publish(&Record {
+            index,
+            crc32: payload_crc(payload),
+        })?;
+        Ok(())
+    }
+}
+
+/// Outcome of [`CrcVerifier::verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrcCheck {
+    Match,
+    Mismatch {
+        expected: u32,
+        actual: u32,
+    },
+    /// No checksum was recorded for the grain, or it was not published yet.
+    Unknown,
+}
+
+/// Checks grains against the checksums of a [`CrcPublisher`], see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct CrcVerifier {
+    events: EventSubscriber,
+    expected: BTreeMap<u64, u32>,
+}
+
+impl CrcVerifier {
+    /// Opens the companion flow `flow_id`. Only grains recorded from now on can be checked.
+    pub fn open(instance: &MxlInstance, flow_id: Uuid) -> Result<Self> {
+        Ok(Self {
+            events: EventSubscriber::open(instance, flow_id)?,
+            expected: BTreeMap::new(),
+        })
+    }
+
+    /// Checks the whole payload of the grain at `index`.
+    pub fn verify(&mut self, index: u64, payload: &[u8]) -> Result<CrcCheck> {
+        for event in self.events.poll()? {
+            // Records of other tools sharing the flow are not checksums.
+            if let Ok(record) = serde_json::from_value::<Record>(event.value) {
+                self.expected.insert(record.index, record.crc32);
+            }
+        }
+        while self.expected.len() > MAX_PENDING_CHECKSUMS {
+            self.expected.pop_first();
+        }
+        let Some(expected) = self.expected.remove(&index) else {
+            return Ok(CrcCheck::Unknown);
+        };
+        let actual = payload_crc(payload);
+        Ok(if actual == expected {
+            CrcCheck::Match
+        } else {
+            CrcCheck::Mismatch { expected, actual }
+        })
+    }
+}
@@ -0,0 +1,231 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! CUDA interop for flows whose payloads are in GPU memory.
+//!
+//! The CUDA driver library is loaded at runtime, like the MXL library, so building with the `cuda`
+//! feature does not need the CUDA toolkit. A [`CudaContext`] binds the primary context of the
+//! flow's device, which is the context the CUDA runtime API uses too, so device pointers from
+//! [`crate::DevicePayload::device_ptr`] can be handed to kernels and libraries directly.
+//!
+//! ```ignore
+//! let cuda = mxl::cuda::CudaContext::for_location(reader.payload_location())?;
+//! let grain = reader.get_device_grain(index, u16::MAX, timeout)?;
+//! let _current = cuda.make_current()?;
+//! launch_kernel(grain.payload.device_ptr(), grain.payload.len());
+//! ```
+
+use std::{
+    ffi::{c_int, c_uint, c_void},
+    sync::{Arc, OnceLock},
+};
+
+use crate::{DevicePayload, Error, PayloadLocation, Result};
+
+/// `CUdeviceptr`, the device address of a payload.
+pub type CuDevicePtr = u64;
+
+type CuResult = c_int;
+type CuDevice = c_int;
+type CuContext = *mut c_void;
+
+const CUDA_SUCCESS: CuResult = 0;
+
+#[cfg(target_os = "linux")]
+const CUDA_LIBRARY: &str = "libcuda.so.1";
+#[cfg(not(target_os = "linux"))]
+const CUDA_LIBRARY: &str = "libcuda.dylib";
+
+/// Entry points of the CUDA driver API.
+struct Driver {
+    cu_init: unsafe extern "C" fn(c_uint) -> CuResult,
+    cu_device_get: unsafe extern "C" fn(*mut CuDevice, c_int) -> CuResult,
+    cu_device_primary_ctx_retain: unsafe extern "C" fn(*mut CuContext, CuDevice) -> CuResult,
+    cu_device_primary_ctx_release: unsafe extern "C" fn(CuDevice) -> CuResult,
+    cu_ctx_push_current: unsafe extern "C" fn(CuContext) -> CuResult,
+    cu_ctx_pop_current: unsafe extern "C" fn(*mut CuContext) -> CuResult,
+    cu_ctx_synchronize: unsafe extern "C" fn() -> CuResult,
+    cu_memcpy_dtoh: unsafe extern "C" fn(*mut c_void, CuDevicePtr, usize) -> CuResult,
+    cu_memcpy_htod: unsafe extern "C" fn(CuDevicePtr, *const c_void, usize) -> CuResult,
+    /// Keeps the entry points above loaded.
+    _library: libloading::Library,
+}
+
+impl Driver {
+    fn load() -> Result<Self> {
+        unsafe {
+            let library = libloading::Library::new(CUDA_LIBRARY)?;
+            let driver = Self {
+                cu_init: *library.get(b"cuInit\0")?,
+                cu_device_get: *library.get(b"cuDeviceGet\0")?,
+                cu_device_primary_ctx_retain: *library.get(b"cuDevicePrimaryCtxRetain\0")?,
+                cu_device_primary_ctx_release: *library.get(b"cuDevicePrimaryCtxRelease_v2\0")?,
+                cu_ctx_push_current: *library.get(b"cuCtxPushCurrent_v2\0")?,
+                cu_ctx_pop_current: *library.get(b"cuCtxPopCurrent_v2\0")?,
+                cu_ctx_synchronize: *library.get(b"cuCtxSynchronize\0")?,
+                cu_memcpy_dtoh: *library.get(b"cuMemcpyDtoH_v2\0")?,
+                cu_memcpy_htod: *library.get(b"cuMemcpyHtoD_v2\0")?,
+                _library: library,
+            };
+            check("cuInit", (driver.cu_init)(0))?;
+            Ok(driver)
+        }
+    }
+
+    /// The driver of the process, loaded and initialized by the first context.
+    fn get() -> Result<Arc<Self>> {
+        static DRIVER: OnceLock<std::result::Result<Arc<Driver>, String>> = OnceLock::new();
+        DRIVER
+            .get_or_init(|| {
+                Self::load()
+                    .map(Arc::new)
+                    .map_err(|error| error.to_string())
+            })
+            .clone()
+            .map_err(|error| Error::Other(format!("Failed to load the CUDA driver: {error}")))
+    }
+}
+
+fn check(call: &str, result: CuResult) -> Result<()> {
+    if result == CUDA_SUCCESS {
+        Ok(())
+    } else {
+        Err(Error::Other(format!(
+            "{call} failed with CUDA error {result}."
+        )))
+    }
+}
+
+/// The primary CUDA context of the GPU holding a flow's payloads, retained for as long as it
+/// lives.
+pub struct CudaContext {
+    driver: Arc<Driver>,
+    device: CuDevice,
+    context: CuContext,
+}
+
+/// The primary context may be used from any thread.
+unsafe impl Send for CudaContext {}
+unsafe impl Sync for CudaContext {}
+
+impl std::fmt::Debug for CudaContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CudaContext")
+            .field("device", &self.device)
+            .field("context", &self.context)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CudaContext {
+    /// Retains the primary context of the GPU with the given index.
+    pub fn new(device_index: i32) -> Result<Self> {
+        let driver = Driver::get()?;
+        let mut device: CuDevice = 0;
+        let mut context: CuContext = std::ptr::null_mut();
+        unsafe {
+            check(
+                "cuDeviceGet",
+                (driver.cu_device_get)(&mut device, device_index),
+            )?;
+            check(
+                "cuDevicePrimaryCtxRetain",
+                (driver.cu_device_primary_ctx_retain)(&mut context, device),
+            )?;
+        }
+        Ok(Self {
+            driver,
+            device,
+            context,
+        })
+    }
+
+    /// Context of the GPU at `location`, e.g. [`crate::GrainReader::payload_location`]. Fails for
+    /// host memory flows.
+    pub fn for_location(location: PayloadLocation) -> Result<Self> {
+        match location {
+            PayloadLocation::DeviceMemory { device_index } => Self::new(device_index),
+            PayloadLocation::HostMemory => Err(Error::Other(
+                "The flow's payloads are in host memory, not on a GPU.".to_string(),
+            )),
+        }
+    }
+
+    /// Makes the context current on the calling thread until the returned guard is dropped.
+    pub fn make_current(&self) -> Result<CurrentContext<'_>> {
+        check("cuCtxPushCurrent", unsafe {
+            (self.driver.cu_ctx_push_current)(self.context)
+        })?;
+        Ok(CurrentContext { context: self })
+    }
+
+    /// Copies a device payload to `destination`, which must be as large as the payload.
+    pub fn copy_to_host(&self, payload: &DevicePayload<'_>, destination: &mut [u8]) -> Result<()> {
+        if destination.len() != payload.len() {
+            return Err(Error::Other(format!(
+                "Cannot copy a payload of {} bytes to {} bytes.",
+                payload.len(),
+                destination.len()
+            )));
+        }
+        let _current = self.make_current()?;
+        check("cuMemcpyDtoH", unsafe {
+            (self.driver.cu_memcpy_dtoh)(
+                destination.as_mut_ptr().cast(),
+                payload.device_ptr(),
+                payload.len(),
+            )
+        })
+    }
+
+    /// Copies `source` to the start of a device payload, e.g. of
+    /// [`crate::GrainWriteAccess::device_payload`].
+    pub fn copy_from_host(&self, source: &[u8], payload: &DevicePayload<'_>) -> Result<()> {
+        if source.len() > payload.len() {
+            return Err(Error::Other(format!(
+                "Cannot copy {} bytes to a payload of {} bytes.",
+                source.len(),
+                payload.len()
+            )));
+        }
+        let _current = self.make_current()?;
+        check("cuMemcpyHtoD", unsafe {
+            (self.driver.cu_memcpy_htod)(payload.device_ptr(), source.as_ptr().cast(), source.len())
+        })
+    }
+
+    /// Waits for the work queued in the context, e.g. before committing a grain a kernel wrote.
+    pub fn synchronize(&self) -> Result<()> {
+        let _current = self.make_current()?;
+        check("cuCtxSynchronize", unsafe {
+            (self.driver.cu_ctx_synchronize)()
+        })
+    }
+}
+
+impl Drop for CudaContext {
+    fn drop(&mut self) {
+        if let Err(error) = check("cuDevicePrimaryCtxRelease", unsafe {
+            (self.driver.cu_device_primary_ctx_release)(self.device)
+        }) {
+            tracing::error!("Failed to release the CUDA context: {error}");
+        }
+    }
+}
+
+/// Guard of [`CudaContext::make_current`].
+#[derive(Debug)]
+pub struct CurrentContext<'a> {
+    context: &'a CudaContext,
+}
+
+impl Drop for CurrentContext<'_> {
+    fn drop(&mut self) {
+        let mut popped: CuContext = std::ptr::null_mut();
+        if let Err(error) = check("cuCtxPopCurrent", unsafe {
+            (self.context.driver.cu_ctx_pop_current)(&mut popped)
+        }) {
+            tracing::error!("Failed to pop the CUDA context: {error}");
+        }
+    }
+}
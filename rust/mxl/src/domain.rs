@@ -0,0 +1,526 @@
+// SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    ffi::CString,
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{MetadataExt, PermissionsExt},
+    },
+    path::{Path, PathBuf},
+};
+#[cfg(feature = "archive")]
+use std::{fs::File, path::Component};
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{Error, MXL_DATA_FORMAT_GRAIN_SIZE, Result, dump::v210_line_length, flowdef::FlowDef};
+
+// These mirror the layout defined in `lib/internal/include/mxl-internal/PathUtils.hpp`.
+const FLOW_DIRECTORY_NAME_SUFFIX: &str = ".mxl-flow";
+const FLOW_DESCRIPTOR_FILE_NAME: &str = "flow_def.json";
+const FLOW_DATA_FILE_NAME: &str = "data";
+#[cfg(feature = "archive")]
+const GRAIN_DIRECTORY_NAME: &str = "grains";
+const DOMAIN_OPTIONS_FILE_NAME: &str = "options.json";
+
+// These mirror the sizing of flows in `lib/internal/src/Instance.cpp`.
+/// History duration of flows unless the domain options or the flow options set one.
+const DEFAULT_HISTORY_DURATION_NS: u64 = 200_000_000;
+/// Domain option setting the history duration in nanoseconds.
+const HISTORY_DURATION_OPTION: &str = "urn:x-mxl:option:history_duration/v1.0";
+/// Bytes of a grain before its payload, see `MXL_GRAIN_PAYLOAD_OFFSET`.
+const GRAIN_PAYLOAD_OFFSET: u64 = 8192;
+const PAGE_SIZE: u64 = 4096;
+
+/// File system level view of an MXL domain.
+///
+/// Unlike [`crate::MxlInstance`], this does not need the MXL library and only works on the
+/// directory structure of the domain. It is meant for support and diagnostic tooling.
+#[derive(Debug, Clone)]
+pub struct MxlDomain {
+    path: PathBuf,
+}
+
+impl MxlDomain {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the IDs of all flows that currently have a directory in the domain.
+    pub fn flow_ids(&self) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&self.path)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            if let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(FLOW_DIRECTORY_NAME_SUFFIX))
+                .and_then(|id| Uuid::parse_str(id).ok())
+            {
+                ids.push(id);
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Returns the parsed definition of a flow of the domain, read from its `flow_def.json`.
+    pub fn flow_def(&self, flow_id: &Uuid) -> Result<FlowDef> {
+        let json =
+            std::fs::read_to_string(self.flow_directory(flow_id).join(FLOW_DESCRIPTOR_FILE_NAME))?;
+        serde_json::from_str(&json).map_err(|error| {
+            Error::Other(format!(
+                "Invalid flow definition of flow {flow_id}: {error}"
+            ))
+        })
+    }
+
+    /// Returns the memory used by every flow of the domain and by the file system holding it.
+    ///
+    /// Flows are measured by the blocks allocated to their files, which on a tmpfs is the memory
+    /// they occupy. On other file systems, see [`crate::MxlInstance::is_domain_on_tmpfs`], they
+    /// are the blocks the flows take on its storage instead.
+    pub fn usage(&self) -> Result<DomainUsage> {
+        let mut flows = Vec::new();
+        for flow_id in self.flow_ids()? {
+            let bytes = match allocated_bytes(&self.flow_directory(&flow_id)) {
+                Ok(bytes) => bytes,
+                // The flow was removed while walking the domain.
+                Err(Error::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+            flows.push(FlowUsage { flow_id, bytes });
+        }
+
+        let path = self.c_path()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let fragment_size = stat.f_frsize as u64;
+
+        Ok(DomainUsage {
+            total: flows.iter().map(|flow| flow.bytes).sum(),
+            flows,
+            capacity: stat.f_blocks as u64 * fragment_size,
+            available: stat.f_bavail as u64 * fragment_size,
+        })
+    }
+
+    /// Estimates the memory a flow of the JSON `flow_def` takes once created with the JSON
+    /// `options`, as [`MxlDomain::usage`] counts it: the ring of grains or sample buffers the
+    /// library sizes from the history duration, and a page for the flow's state. This is an upper
+    /// bound, as the grains of flows with their payloads in device memory take less.
+    pub fn projected_flow_size(&self, flow_def: &str, options: Option<&str>) -> Result<u64> {
+        let flow_def: Value = serde_json::from_str(flow_def)
+            .map_err(|error| Error::Other(format!("Invalid flow definition: {error}")))?;
+        let options: Value = match options {
+            Some(options) => serde_json::from_str(options)
+                .map_err(|error| Error::Other(format!("Invalid flow options: {error}")))?,
+            None => Value::Null,
+        };
+        let history = match options["historyDuration"].as_f64() {
+            Some(duration) => duration as u64,
+            None => self.history_duration_ns(),
+        };
+        let number = |key: &str| {
+            flow_def[key]
+                .as_f64()
+                .map(|value| value as u64)
+                .ok_or_else(|| Error::Other(format!("The flow definition has no valid {key}.")))
+        };
+        let rate = |key: &str| {
+            let numerator = flow_def[key]["numerator"].as_f64().unwrap_or_default() as u64;
+            let denominator = flow_def[key]["denominator"].as_f64().unwrap_or(1.0) as u64;
+            if numerator == 0 || denominator == 0 {
+                return Err(Error::Other(format!(
+                    "The flow definition has no valid {key}."
+                )));
+            }
+            Ok((numerator, denominator))
+        };
+
+        let ring = match flow_def["format"].as_str() {
+            Some("urn:x-nmos:format:audio") => {
+                let (numerator, denominator) = rate("sample_rate")?;
+                let word = (number("bit_depth")? / 8).max(1);
+                // Twice the history, only half of the buffer is readable at any point in time.
+                let length = history.saturating_mul(numerator) / (500_000_000 * denominator);
+                let length = length.next_multiple_of((PAGE_SIZE / word).max(1));
+                number("channel_count")?.saturating_mul(length * word)
+            }
+            Some(format @ ("urn:x-nmos:format:video" | "urn:x-nmos:format:data")) => {
+                let (mut numerator, denominator) = rate("grain_rate")?;
+                let payload = if format == "urn:x-nmos:format:data" {
+                    MXL_DATA_FORMAT_GRAIN_SIZE as u64
+                } else {
+                    let interlaced = flow_def["interlace_mode"]
+                        .as_str()
+                        .is_some_and(|mode| mode != "progressive");
+                    if interlaced {
+                        // Grains are fields.
+                        numerator *= 2;
+                    }
+                    video_payload_size(
+                        flow_def["media_type"].as_str().unwrap_or_default(),
+                        number("frame_width")?,
+                        number("frame_height")?,
+                        interlaced,
+                    )?
+                };
+                let grains = history.saturating_mul(numerator) / (1_000_000_000 * denominator);
+                grains.saturating_mul((GRAIN_PAYLOAD_OFFSET + payload).next_multiple_of(PAGE_SIZE))
+            }
+            format => {
+                return Err(Error::Other(format!(
+                    "Unsupported flow format {}.",
+                    format.unwrap_or_default()
+                )));
+            }
+        };
+        Ok(PAGE_SIZE.saturating_add(ring.next_multiple_of(PAGE_SIZE)))
+    }
+
+    /// History duration of the flows created without one in their options, from the domain's
+    /// `options.json`.
+    fn history_duration_ns(&self) -> u64 {
+        std::fs::read_to_string(self.path.join(DOMAIN_OPTIONS_FILE_NAME))
+            .ok()
+            .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+            .and_then(|options| options[HISTORY_DURATION_OPTION].as_f64())
+            .map_or(DEFAULT_HISTORY_DURATION_NS, |duration| duration as u64)
+    }
+
+    /// Returns the processes that have the flow mapped, found through `/proc`.
+    ///
+    /// The MXL library does not track its readers: they map the flow read-only without taking a
+    /// lock, and the index each of them reads is private to it, so neither their number nor the
+    /// slowest reader's index is published. This is a best-effort view for diagnostics instead. It
+    /// only sees the processes of the caller's PID namespace that the caller may inspect, which
+    /// for an unprivileged caller are its user's. A process both writing and reading the flow is
+    /// reported as a writer only.
+    #[cfg(target_os = "linux")]
+    pub fn attachments(&self, flow_id: &Uuid) -> Result<FlowAttachments> {
+        let metadata = std::fs::metadata(self.flow_directory(flow_id).join(FLOW_DATA_FILE_NAME))?;
+        let file = (
+            libc::major(metadata.dev()),
+            libc::minor(metadata.dev()),
+            metadata.ino(),
+        );
+
+        // Writers hold a shared lock on the data file while the flow is open.
+        let mut writers: Vec<u32> = std::fs::read_to_string("/proc/locks")?
+            .lines()
+            .filter_map(|line| {
+                // `1: FLOCK  ADVISORY  READ 1234 00:1a:5678 0 EOF`. Waiters, with a `->` after
+                // the id, do not parse.
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let (pid, device) = (fields.get(4)?, fields.get(5)?);
+                let (major, rest) = device.split_once(':')?;
+                let (minor, inode) = rest.split_once(':')?;
+                (parse_device(major, minor)? == (file.0, file.1)
+                    && inode.parse().ok() == Some(file.2))
+                .then(|| pid.parse().ok())
+                .flatten()
+            })
+            .collect();
+        writers.sort_unstable();
+        writers.dedup();
+
+        let mut readers = Vec::new();
+        for entry in std::fs::read_dir("/proc")? {
+            let Some(pid) = entry?
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse().ok())
+            else {
+                continue;
+            };
+            if writers.contains(&pid) {
+                continue;
+            }
+            // The process exited or cannot be inspected by the caller.
+            let Ok(maps) = std::fs::read_to_string(format!("/proc/{pid}/maps")) else {
+                continue;
+            };
+            let mapped = maps.lines().any(|line| {
+                // `address perms offset major:minor inode path`
+                let mut fields = line.split_whitespace().skip(3);
+                let (Some(device), Some(inode)) = (fields.next(), fields.next()) else {
+                    return false;
+                };
+                device
+                    .split_once(':')
+                    .and_then(|(major, minor)| parse_device(major, minor))
+                    == Some((file.0, file.1))
+                    && inode.parse().ok() == Some(file.2)
+            });
+            if mapped {
+                readers.push(pid);
+            }
+        }
+        readers.sort_unstable();
+        Ok(FlowAttachments { writers, readers })
+    }
+
+    /// Sets the permission bits and ownership of every file of a flow, leaving what is `None`
+    /// unchanged. Directories additionally get the search bit of every class that can read them,
+    /// so `0o640` lets the group list and map the flow. See [`crate::FlowOptions::with_mode`] to
+    /// apply this to flows as they are created.
+    ///
+    /// The files are changed one after the other, so a failure, e.g. a `chown` without the
+    /// privilege, leaves the changes made before it.
+    pub fn set_flow_permissions(
+        &self,
+        flow_id: &Uuid,
+        mode: Option<u32>,
+        owner: Option<u32>,
+        group: Option<u32>,
+    ) -> Result<()> {
+        if mode.is_none() && owner.is_none() && group.is_none() {
+            return Ok(());
+        }
+        set_permissions(&self.flow_directory(flow_id), mode, owner, group)
+    }
+
+    pub(crate) fn c_path(&self) -> Result<CString> {
+        Ok(CString::new(self.path.as_os_str().as_bytes())?)
+    }
+
+    pub fn flow_directory(&self, flow_id: &Uuid) -> PathBuf {
+        self.path
+            .join(format!("{}{}", flow_id, FLOW_DIRECTORY_NAME_SUFFIX))
+    }
+}
+
+/// Export and import of domains as tar archives, with the `archive` feature.
+#[cfg(feature = "archive")]
+impl MxlDomain {
+    /// Writes a tar archive of the domain to `archive_path`.
+    ///
+    /// The archive always contains the domain options (if any) and every flow definition. With
+    /// `include_content`, the flow data files and grain ring buffers are added as well, giving a
+    /// snapshot of the latest grains or samples. The content is copied while writers may still be
+    /// active, so the snapshot is not guaranteed to be consistent across flows.
+    pub fn export(&self, archive_path: impl AsRef<Path>, include_content: bool) -> Result<()> {
+        let mut builder = tar::Builder::new(File::create(archive_path)?);
+        builder.follow_symlinks(false);
+
+        let options = self.path.join(DOMAIN_OPTIONS_FILE_NAME);
+        if options.is_file() {
+            builder.append_path_with_name(&options, DOMAIN_OPTIONS_FILE_NAME)?;
+        }
+
+        for flow_id in self.flow_ids()? {
+            let flow_dir = self.flow_directory(&flow_id);
+            let archive_dir = PathBuf::from(format!("{}{}", flow_id, FLOW_DIRECTORY_NAME_SUFFIX));
+
+            let flow_def = flow_dir.join(FLOW_DESCRIPTOR_FILE_NAME);
+            if !flow_def.is_file() {
+                // The flow is still being created or is being torn down.
+                continue;
+            }
+            builder
+                .append_path_with_name(&flow_def, archive_dir.join(FLOW_DESCRIPTOR_FILE_NAME))?;
+
+            if include_content {
+                let data = flow_dir.join(FLOW_DATA_FILE_NAME);
+                if data.is_file() {
+                    builder.append_path_with_name(&data, archive_dir.join(FLOW_DATA_FILE_NAME))?;
+                }
+                let grains = flow_dir.join(GRAIN_DIRECTORY_NAME);
+                if grains.is_dir() {
+                    builder.append_dir_all(archive_dir.join(GRAIN_DIRECTORY_NAME), &grains)?;
+                }
+            }
+        }
+
+        builder.into_inner()?.sync_all()?;
+        Ok(())
+    }
+
+    /// Unpacks an archive produced by [`MxlDomain::export`] into this domain.
+    ///
+    /// Returns the IDs of the imported flows. Flows that already exist in the domain are not
+    /// overwritten; importing them fails before anything is written. Existing domain options are
+    /// kept. Imported flows have no active writer, so they are reclaimed by
+    /// [`crate::MxlInstance::garbage_collect_flows`]. Import into a scratch domain for offline
+    /// analysis rather than into a live one.
+    pub fn import(&self, archive_path: impl AsRef<Path>) -> Result<Vec<Uuid>> {
+        std::fs::create_dir_all(&self.path)?;
+
+        // Validate the whole archive before writing anything.
+        let mut flow_ids = Vec::new();
+        let mut archive = tar::Archive::new(File::open(archive_path.as_ref())?);
+        for entry in archive.entries()? {
+            let entry = entry?;
+            let path = entry.path()?;
+            match top_level_flow_id(&path)? {
+                Some(flow_id) if !flow_ids.contains(&flow_id) => {
+                    if self.flow_directory(&flow_id).exists() {
+                        return Err(Error::Other(format!(
+                            "Flow {} already exists in domain \"{}\"",
+                            flow_id,
+                            self.path.display()
+                        )));
+                    }
+                    flow_ids.push(flow_id);
+                }
+                _ => {}
+            }
+        }
+
+        let keep_options = self.path.join(DOMAIN_OPTIONS_FILE_NAME).exists();
+        let mut archive = tar::Archive::new(File::open(archive_path.as_ref())?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if keep_options && entry.path()?.as_os_str() == DOMAIN_OPTIONS_FILE_NAME {
+                continue;
+            }
+            entry.unpack_in(&self.path)?;
+        }
+
+        flow_ids.sort();
+        Ok(flow_ids)
+    }
+}
+
+/// Memory used by an MXL domain, see [`MxlDomain::usage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainUsage {
+    /// Flows in flow ID order.
+    pub flows: Vec<FlowUsage>,
+    /// Bytes used by all flows.
+    pub total: u64,
+    /// Size in bytes of the file system holding the domain, typically the tmpfs of `/dev/shm`.
+    pub capacity: u64,
+    /// Bytes still available on that file system, which other users may share.
+    pub available: u64,
+}
+
+/// Processes attached to a flow, see [`MxlDomain::attachments`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlowAttachments {
+    /// Ids of the processes holding the flow open for writing.
+    pub writers: Vec<u32>,
+    /// Ids of the other processes mapping the flow.
+    pub readers: Vec<u32>,
+}
+
+impl FlowAttachments {
+    pub fn reader_count(&self) -> usize {
+        self.readers.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowUsage {
+    pub flow_id: Uuid,
+    pub bytes: u64,
+}
+
+/// Bytes allocated to the files below `path`.
+/// Payload size of a grain of a video flow, a field for interlaced flows.
+fn video_payload_size(media_type: &str, width: u64, height: u64, interlaced: bool) -> Result<u64> {
+    let lines = if interlaced { height / 2 } else { height };
+    let luma_line = width.div_ceil(4) * 4;
+    let chroma_line = width.div_ceil(2).div_ceil(4) * 4;
+    Ok(match media_type {
+        "video/v210" => v210_line_length(width as usize) as u64 * lines,
+        "video/v210a" => (v210_line_length(width as usize) as u64 + width.div_ceil(3) * 4) * lines,
+        "video/uyvy" => width.div_ceil(2) * 4 * lines,
+        "video/rgba" | "video/bgra" => width * 4 * lines,
+        // Slices of two luma lines and their chroma.
+        "video/i420" => (2 * luma_line + 2 * chroma_line) * height.div_ceil(2),
+        "video/nv12" => 3 * luma_line * height.div_ceil(2),
+        _ => {
+            return Err(Error::Other(format!(
+                "Unsupported video media type {media_type}."
+            )));
+        }
+    })
+}
+
+fn allocated_bytes(path: &Path) -> Result<u64> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    // `blocks` counts 512-byte units whatever the file system's block size.
+    let mut bytes = metadata.blocks() * 512;
+    if metadata.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            bytes += allocated_bytes(&entry?.path())?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Parses the hexadecimal `major:minor` device numbers of `/proc/locks` and `/proc/<pid>/maps`.
+#[cfg(target_os = "linux")]
+fn parse_device(major: &str, minor: &str) -> Option<(u32, u32)> {
+    Some((
+        u32::from_str_radix(major, 16).ok()?,
+        u32::from_str_radix(minor, 16).ok()?,
+    ))
+}
+
+/// Applies [`MxlDomain::set_flow_permissions`] to `path` and everything below it.
+fn set_permissions(
+    path: &Path,
+    mode: Option<u32>,
+    owner: Option<u32>,
+    group: Option<u32>,
+) -> Result<()> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+    if owner.is_some() || group.is_some() {
+        std::os::unix::fs::chown(path, owner, group)?;
+    }
+    if metadata.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            set_permissions(&entry?.path(), mode, owner, group)?;
+        }
+    }
+    if let Some(mode) = mode {
+        let mode = if metadata.is_dir() {
+            mode | ((mode & 0o444) >> 2)
+        } else {
+            mode
+        };
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    Ok(())
+}
+
+/// Returns the flow ID of the flow directory an archive entry belongs to, `None` for the domain
+/// options file, and an error for anything else.
+#[cfg(feature = "archive")]
+fn top_level_flow_id(path: &Path) -> Result<Option<Uuid>> {
+    let invalid = || {
+        Error::Other(format!(
+            "Unexpected entry in domain archive: {}",
+            path.display()
+        ))
+    };
+    let first = match path.components().next() {
+        Some(Component::Normal(first)) => first.to_str().ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+    if first == DOMAIN_OPTIONS_FILE_NAME {
+        return Ok(None);
+    }
+    first
+        .strip_suffix(FLOW_DIRECTORY_NAME_SUFFIX)
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .map(Some)
+        .ok_or_else(invalid)
+}
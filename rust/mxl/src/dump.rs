@@ -0,0 +1,598 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dumps of video grains and audio samples to files, and replay of such dumps.
+//!
+//! When frames in shared memory look corrupted, the grains can be captured with a [`GrainDump`]
+//! and inspected with ordinary tools: as the raw payloads, as planar 10-bit 4:2:2 (`yuv422p10le`
+//! in ffmpeg terms) or as a Y4M file that players open directly. The planar formats unpack v210,
+//! [`GrainReplay`] packs them again, so a dump can be written back into a flow.
+//!
+//! Interlaced flows carry a field per grain, which is dumped as a picture of half the frame height.
+//!
+//! Audio is dumped interleaved to WAV files with [`WavWriter`] and read back with [`WavReader`].
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::{
+    Error, Result, SampleFormat,
+    flowdef::{FlowDefVideo, InterlaceMode},
+};
+
+const V210: &str = "video/v210";
+const V210A: &str = "video/v210a";
+const Y4M_SIGNATURE: &str = "YUV4MPEG2";
+const Y4M_FRAME: &str = "FRAME";
+/// Longest header or frame line accepted when replaying a Y4M file.
+const Y4M_MAX_LINE: u64 = 1024;
+
+/// Components of the 12 samples of a v210 block of 6 pixels: plane (Y, Cb, Cr) and sample offset
+/// in the plane.
+const V210_BLOCK: [(usize, usize); 12] = [
+    (1, 0),
+    (0, 0),
+    (2, 0),
+    (0, 1),
+    (1, 1),
+    (0, 2),
+    (2, 1),
+    (0, 3),
+    (1, 2),
+    (0, 4),
+    (2, 2),
+    (0, 5),
+];
+
+/// Size in bytes of a v210 line of `width` pixels, padded to 128 bytes.
+pub fn v210_line_length(width: usize) -> usize {
+    width.div_ceil(48) * 128
+}
+
+/// Size in bytes of a line of the key of a `video/v210a` grain, 3 samples per 32-bit word.
+fn alpha_line_length(width: usize) -> usize {
+    width.div_ceil(3) * 4
+}
+
+/// Widths of the Y and chroma planes of 4:2:2 video.
+fn plane_widths(width: usize) -> [usize; 3] {
+    let chroma = width.div_ceil(2);
+    [width, chroma, chroma]
+}
+
+/// Unpacks a v210 picture to planar 10-bit 4:2:2: the Y plane followed by the Cb and Cr planes,
+/// one sample per `u16`.
+pub fn unpack_v210(payload: &[u8], width: usize, height: usize) -> Result<Vec<u16>> {
+    let line_length = v210_line_length(width);
+    if payload.len() < line_length * height {
+        return Err(Error::Other(format!(
+            "A v210 picture of {width}x{height} takes {} bytes, got {}.",
+            line_length * height,
+            payload.len()
+        )));
+    }
+    let widths = plane_widths(width);
+    let mut planes = widths.map(|plane_width| vec![0u16; plane_width * height]);
+    for (row, line) in payload.chunks_exact(line_length).take(height).enumerate() {
+        for (block_index, block) in line.chunks_exact(16).take(width.div_ceil(6)).enumerate() {
+            let words: Vec<u32> = block
+                .chunks_exact(4)
+                .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+                .collect();
+            for (position, (plane, offset)) in V210_BLOCK.iter().enumerate() {
+                let per_block = if *plane == 0 { 6 } else { 3 };
+                let column = block_index * per_block + offset;
+                if column < widths[*plane] {
+                    let sample = (words[position / 3] >> (10 * (position % 3))) & 0x3ff;
+                    planes[*plane][row * widths[*plane] + column] = sample as u16;
+                }
+            }
+        }
+    }
+    Ok(planes.concat())
+}
+
+/// Packs planar 10-bit 4:2:2 samples, as returned by [`unpack_v210`], to a v210 picture.
+pub fn pack_v210(planar: &[u16], width: usize, height: usize) -> Result<Vec<u8>> {
+    let widths = plane_widths(width);
+    let plane_sizes = widths.map(|plane_width| plane_width * height);
+    if planar.len() != plane_sizes.iter().sum::<usize>() {
+        return Err(Error::Other(format!(
+            "A planar 4:2:2 picture of {width}x{height} has {} samples, got {}.",
+            plane_sizes.iter().sum::<usize>(),
+            planar.len()
+        )));
+    }
+    let (y, chroma) = planar.split_at(plane_sizes[0]);
+    let planes = [y, &chroma[..plane_sizes[1]], &chroma[plane_sizes[1]..]];
+    let line_length = v210_line_length(width);
+    let mut payload = vec![0; line_length * height];
+    for (row, line) in payload.chunks_exact_mut(line_length).enumerate() {
+        for (block_index, block) in line
+            .chunks_exact_mut(16)
+            .take(width.div_ceil(6))
+            .enumerate()
+        {
+            let mut words = [0u32; 4];
+            for (position, (plane, offset)) in V210_BLOCK.iter().enumerate() {
+                let per_block = if *plane == 0 { 6 } else { 3 };
+                let column = block_index * per_block + offset;
+                if column < widths[*plane] {
+                    let sample = u32::from(planes[*plane][row * widths[*plane] + column] & 0x3ff);
+                    words[position / 3] |= sample << (10 * (position % 3));
+                }
+            }
+            for (bytes, word) in block.chunks_exact_mut(4).zip(words) {
+                bytes.copy_from_slice(&word.to_le_bytes());
+            }
+        }
+    }
+    Ok(payload)
+}
+
+/// File format of a [`GrainDump`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// The grain payloads as they are in the flow, back to back.
+    #[default]
+    Raw,
+    /// v210 unpacked to planar 10-bit 4:2:2, little-endian `u16` samples without a header.
+    Planar,
+    /// v210 unpacked to a `C422p10` YUV4MPEG2 stream.
+    Y4m,
+}
+
+/// Picture geometry of the grains of a video flow.
+#[derive(Clone, Copy, Debug)]
+struct Picture {
+    width: usize,
+    /// Lines per grain, half the frame height for interlaced flows.
+    height: usize,
+    payload_size: usize,
+}
+
+impl Picture {
+    /// Geometry of the flow of the JSON `flow_def`, as returned by
+    /// [`crate::MxlInstance::get_flow_def`].
+    fn new(flow_def: &str, format: DumpFormat) -> Result<(Self, FlowDefVideo)> {
+        let invalid = |error| Error::Other(format!("Invalid video flow definition: {error}"));
+        let flow_def: serde_json::Value = serde_json::from_str(flow_def).map_err(invalid)?;
+        let media_type = flow_def["media_type"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let video: FlowDefVideo = serde_json::from_value(flow_def).map_err(invalid)?;
+        if media_type != V210 && (format != DumpFormat::Raw || media_type != V210A) {
+            return Err(Error::Other(format!(
+                "{format:?} dumps of {media_type} flows are not supported."
+            )));
+        }
+        let width = usize::try_from(video.frame_width).unwrap_or_default();
+        let mut height = usize::try_from(video.frame_height).unwrap_or_default();
+        if video.interlace_mode != InterlaceMode::Progressive {
+            height /= 2;
+        }
+        if width == 0 || height == 0 {
+            return Err(Error::Other(format!(
+                "Invalid picture size {}x{}.",
+                video.frame_width, video.frame_height
+            )));
+        }
+        let mut line_length = v210_line_length(width);
+        if media_type == V210A {
+            line_length += alpha_line_length(width);
+        }
+        let picture = Self {
+            width,
+            height,
+            payload_size: line_length * height,
+        };
+        Ok((picture, video))
+    }
+
+    fn planar_samples(&self) -> usize {
+        plane_widths(self.width).iter().sum::<usize>() * self.height
+    }
+}
+
+/// Writes the grains of a video flow to a file, see the [module documentation](self).
+#[derive(Debug)]
+pub struct GrainDump<W: Write> {
+    writer: W,
+    format: DumpFormat,
+    picture: Picture,
+    grains: u64,
+}
+
+impl GrainDump<BufWriter<File>> {
+    /// Creates the dump file at `path`.
+    pub fn create(path: impl AsRef<Path>, flow_def: &str, format: DumpFormat) -> Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), flow_def, format)
+    }
+}
+
+impl<W: Write> GrainDump<W> {
+    /// Starts a dump of grains of the flow of the JSON `flow_def`, a `video/v210` flow or, for raw
+    /// dumps, a `video/v210a` flow.
+    pub fn new(mut writer: W, flow_def: &str, format: DumpFormat) -> Result<Self> {
+        let (picture, video) = Picture::new(flow_def, format)?;
+        if format == DumpFormat::Y4m {
+            // Fields are dumped as pictures of their own, so the stream is always progressive.
+            writeln!(
+                writer,
+                "{Y4M_SIGNATURE} W{} H{} F{}:{} Ip A1:1 C422p10 XYSCSS=422P10",
+                picture.width,
+                picture.height,
+                video.grain_rate.numerator,
+                video.grain_rate.denominator
+            )?;
+        }
+        Ok(Self {
+            writer,
+            format,
+            picture,
+            grains: 0,
+        })
+    }
+
+    /// Appends the payload of a grain. Only the payload size of the flow is written, so grains read
+    /// with padding can be passed whole.
+    pub fn write_grain(&mut self, payload: &[u8]) -> Result<()> {
+        let size = self.picture.payload_size;
+        if payload.len() < size {
+            return Err(Error::Other(format!(
+                "Grains of the flow take {size} bytes, got {}.",
+                payload.len()
+            )));
+        }
+        if self.format == DumpFormat::Raw {
+            self.writer.write_all(&payload[..size])?;
+        } else {
+            let planar = unpack_v210(payload, self.picture.width, self.picture.height)?;
+            if self.format == DumpFormat::Y4m {
+                writeln!(self.writer, "{Y4M_FRAME}")?;
+            }
+            let bytes: Vec<u8> = planar.iter().flat_map(|s| s.to_le_bytes()).collect();
+            self.writer.write_all(&bytes)?;
+        }
+        self.grains += 1;
+        Ok(())
+    }
+
+    /// Number of grains written.
+    pub fn grains(&self) -> u64 {
+        self.grains
+    }
+
+    /// Flushes the file and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads back the grain payloads of a [`GrainDump`], packed to the flow's format.
+#[derive(Debug)]
+pub struct GrainReplay<R: BufRead> {
+    reader: R,
+    format: DumpFormat,
+    picture: Picture,
+}
+
+impl GrainReplay<BufReader<File>> {
+    /// Opens the dump file at `path`.
+    pub fn open(path: impl AsRef<Path>, flow_def: &str, format: DumpFormat) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?), flow_def, format)
+    }
+}
+
+impl<R: BufRead> GrainReplay<R> {
+    /// Opens a dump of grains of the flow of the JSON `flow_def`. The picture size of a Y4M stream
+    /// has to match the flow.
+    pub fn new(mut reader: R, flow_def: &str, format: DumpFormat) -> Result<Self> {
+        let (picture, _) = Picture::new(flow_def, format)?;
+        if format == DumpFormat::Y4m {
+            let header = read_line(&mut reader)?
+                .ok_or_else(|| Error::Other("Empty Y4M stream.".to_string()))?;
+            let mut fields = header.split_ascii_whitespace();
+            if fields.next() != Some(Y4M_SIGNATURE) {
+                return Err(Error::Other("Not a YUV4MPEG2 stream.".to_string()));
+            }
+            for field in fields {
+                let (tag, value) = field.split_at(1);
+                let mismatch = match tag {
+                    "W" => value.parse() != Ok(picture.width),
+                    "H" => value.parse() != Ok(picture.height),
+                    "C" => !value.starts_with("422p10"),
+                    _ => false,
+                };
+                if mismatch {
+                    return Err(Error::Other(format!(
+                        "Y4M parameter {field} does not match the flow's {}x{} 4:2:2 10-bit \
+                         pictures.",
+                        picture.width, picture.height
+                    )));
+                }
+            }
+        }
+        Ok(Self {
+            reader,
+            format,
+            picture,
+        })
+    }
+
+    /// Size of the grain payloads returned by [`GrainReplay::next_grain`].
+    pub fn payload_size(&self) -> usize {
+        self.picture.payload_size
+    }
+
+    /// Reads the next grain payload, `None` at the end of the dump.
+    pub fn next_grain(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.format == DumpFormat::Y4m {
+            match read_line(&mut self.reader)? {
+                None => return Ok(None),
+                Some(line) if line.starts_with(Y4M_FRAME) => {}
+                Some(line) => {
+                    return Err(Error::Other(format!(
+                        "Expected a Y4M frame, got \"{line}\"."
+                    )));
+                }
+            }
+        }
+        let size = match self.format {
+            DumpFormat::Raw => self.picture.payload_size,
+            DumpFormat::Planar | DumpFormat::Y4m => 2 * self.picture.planar_samples(),
+        };
+        let mut bytes = vec![0; size];
+        if !read_exact_or_eof(&mut self.reader, &mut bytes)? {
+            return Ok(None);
+        }
+        if self.format == DumpFormat::Raw {
+            return Ok(Some(bytes));
+        }
+        let planar: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|sample| u16::from_le_bytes([sample[0], sample[1]]))
+            .collect();
+        pack_v210(&planar, self.picture.width, self.picture.height).map(Some)
+    }
+}
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+/// Size of the RIFF header, `fmt ` chunk and `data` chunk header written by [`WavWriter`].
+const WAV_HEADER_SIZE: u64 = 44;
+
+/// Writes interleaved samples to a WAV file. The chunk sizes are set by [`WavWriter::finish`].
+#[derive(Debug)]
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    data_size: u64,
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Creates the WAV file at `path`.
+    pub fn create(
+        path: impl AsRef<Path>,
+        sample_rate: u32,
+        channels: u16,
+        format: SampleFormat,
+    ) -> Result<Self> {
+        Self::new(
+            BufWriter::new(File::create(path)?),
+            sample_rate,
+            channels,
+            format,
+        )
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(
+        mut writer: W,
+        sample_rate: u32,
+        channels: u16,
+        format: SampleFormat,
+    ) -> Result<Self> {
+        let tag = match format {
+            SampleFormat::F32 => WAVE_FORMAT_IEEE_FLOAT,
+            SampleFormat::S16 | SampleFormat::S24 | SampleFormat::S32 => WAVE_FORMAT_PCM,
+            SampleFormat::S24In32 => {
+                return Err(Error::Other(
+                    "WAV files do not hold 24-bit samples in 32-bit words, use S24 or S32.".into(),
+                ));
+            }
+        };
+        let block_align = channels * format.bytes_per_sample() as u16;
+        let mut header = Vec::with_capacity(WAV_HEADER_SIZE as usize);
+        header.extend_from_slice(b"RIFF\0\0\0\0WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&tag.to_le_bytes());
+        header.extend_from_slice(&channels.to_le_bytes());
+        header.extend_from_slice(&sample_rate.to_le_bytes());
+        header.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+        header.extend_from_slice(&block_align.to_le_bytes());
+        header.extend_from_slice(&(8 * format.bytes_per_sample() as u16).to_le_bytes());
+        header.extend_from_slice(b"data\0\0\0\0");
+        writer.write_all(&header)?;
+        Ok(Self {
+            writer,
+            data_size: 0,
+        })
+    }
+
+    /// Appends interleaved samples, e.g. from [`crate::SamplesData::interleave`].
+    pub fn write_interleaved(&mut self, samples: &[u8]) -> Result<()> {
+        self.writer.write_all(samples)?;
+        self.data_size += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Sets the chunk sizes and returns the underlying writer. Files past 4 GiB keep the maximum
+    /// sizes, which most readers take as "up to the end of the file".
+    pub fn finish(mut self) -> Result<W> {
+        let data_size = u32::try_from(self.data_size).unwrap_or(u32::MAX);
+        let riff_size = data_size.saturating_add(WAV_HEADER_SIZE as u32 - 8);
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&riff_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(WAV_HEADER_SIZE - 4))?;
+        self.writer.write_all(&data_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads the interleaved samples of a WAV file of 16, 24 or 32-bit integer or 32-bit float samples.
+#[derive(Debug)]
+pub struct WavReader<R: Read> {
+    reader: R,
+    sample_rate: u32,
+    channels: u16,
+    format: SampleFormat,
+    remaining: u64,
+}
+
+impl WavReader<BufReader<File>> {
+    /// Opens the WAV file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> WavReader<R> {
+    /// Reads the header up to the start of the samples.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let invalid = |reason: &str| Error::Other(format!("Invalid WAV file: {reason}."));
+        let mut riff = [0; 12];
+        reader.read_exact(&mut riff)?;
+        if &riff[..4] != b"RIFF" || &riff[8..] != b"WAVE" {
+            return Err(invalid("no RIFF WAVE header"));
+        }
+        let mut fmt = None;
+        loop {
+            let mut chunk = [0; 8];
+            reader.read_exact(&mut chunk)?;
+            let size = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+            match &chunk[..4] {
+                b"fmt " => {
+                    let mut body = vec![0; size as usize];
+                    reader.read_exact(&mut body)?;
+                    if body.len() < 16 {
+                        return Err(invalid("short fmt chunk"));
+                    }
+                    let field = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+                    let mut tag = field(0);
+                    if tag == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26 {
+                        // The sub-format GUID starts with the format tag.
+                        tag = field(24);
+                    }
+                    let format = match (tag, field(14)) {
+                        (WAVE_FORMAT_IEEE_FLOAT, 32) => SampleFormat::F32,
+                        (WAVE_FORMAT_PCM, 16) => SampleFormat::S16,
+                        (WAVE_FORMAT_PCM, 24) => SampleFormat::S24,
+                        (WAVE_FORMAT_PCM, 32) => SampleFormat::S32,
+                        (tag, bits) => {
+                            return Err(Error::Other(format!(
+                                "Unsupported WAV format {tag:#06x} with {bits} bits per sample."
+                            )));
+                        }
+                    };
+                    let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                    fmt = Some((field(2), sample_rate, format));
+                }
+                b"data" => {
+                    let (channels, sample_rate, format) =
+                        fmt.ok_or_else(|| invalid("data chunk before the fmt chunk"))?;
+                    if channels == 0 {
+                        return Err(invalid("no channels"));
+                    }
+                    return Ok(Self {
+                        reader,
+                        sample_rate,
+                        channels,
+                        format,
+                        // Writers that could not finish the file leave the size unset.
+                        remaining: match size {
+                            0 | u32::MAX => u64::MAX,
+                            size => u64::from(size),
+                        },
+                    });
+                }
+                _ => {
+                    // Chunks are padded to an even size.
+                    let skip = u64::from(size) + u64::from(size % 2);
+                    std::io::copy(&mut (&mut reader).take(skip), &mut std::io::sink())?;
+                }
+            }
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// Reads whole frames of interleaved samples into `buffer`. Returns the number of bytes read,
+    /// `0` at the end of the samples.
+    pub fn read_interleaved(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let frame_size = usize::from(self.channels) * self.format.bytes_per_sample();
+        let len = (buffer.len() as u64).min(self.remaining) as usize / frame_size * frame_size;
+        let mut filled = 0;
+        while filled < len {
+            match self.reader.read(&mut buffer[filled..len])? {
+                0 => {
+                    // A file cut short, e.g. by a recorder that was killed, ends early.
+                    self.remaining = 0;
+                    break;
+                }
+                read => filled += read,
+            }
+        }
+        let filled = filled / frame_size * frame_size;
+        self.remaining = self.remaining.saturating_sub(filled as u64);
+        Ok(filled)
+    }
+}
+
+/// Reads a `\n` terminated line, `None` at the end of the stream.
+fn read_line(reader: &mut impl BufRead) -> Result<Option<String>> {
+    let mut line = String::new();
+    if reader.take(Y4M_MAX_LINE).read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') {
+        return Err(Error::Other("Truncated Y4M line.".to_string()));
+    }
+    Ok(Some(line.trim_end().to_string()))
+}
+
+/// Fills `buffer`, returning false if the stream ends before the first byte.
+fn read_exact_or_eof(reader: &mut impl Read, buffer: &mut [u8]) -> Result<bool> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 if filled == 0 => return Ok(false),
+            0 => {
+                return Err(Error::Other(format!(
+                    "Truncated dump, the last grain has {filled} of {} bytes.",
+                    buffer.len()
+                )));
+            }
+            read => filled += read,
+        }
+    }
+    Ok(true)
+}
@@ -3,9 +3,15 @@
 
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// Errors returned by the MXL wrapper.
+///
+/// The MXL C library does not expose a last-error or log-callback API; its diagnostics go to
+/// stderr through spdlog and can be enabled with the `MXL_LOG_LEVEL` environment variable. The
+/// best we can do on the Rust side is to map every status code to a descriptive variant and keep
+/// the raw status available through [`Error::status`].
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("Unknown error: {0}")]
+    #[error("Unknown error: {name} ({0})", name = status_name(*.0))]
     Unknown(mxl_sys::Status),
     #[error("Flow not found")]
     FlowNotFound,
@@ -23,6 +29,19 @@ pub enum Error {
     InvalidArg,
     #[error("Conflict")]
     Conflict,
+    #[error("Permission denied")]
+    PermissionDenied,
+    /// The flow data file has been replaced since the reader was created, typically because the
+    /// writer restarted and recreated the flow. The reader has to be recreated.
+    #[error("Flow invalid - the flow was recreated since the reader was opened")]
+    FlowInvalid,
+    /// Creating the flow would take the domain past the quota set with
+    /// [`crate::MxlInstance::set_quota`].
+    #[error(
+        "Domain quota exceeded: {used} bytes used, the new flow needs {flow} bytes, the quota is \
+         {quota} bytes"
+    )]
+    QuotaExceeded { used: u64, flow: u64, quota: u64 },
     /// The error is not defined in the MXL API, but it is used to wrap other errors.
     #[error("Other error: {0}")]
     Other(String),
@@ -32,6 +51,23 @@ pub enum Error {
 
     #[error("Loading library: {0}")]
     LibLoading(#[from] libloading::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The loaded library does not match the version the bindings were generated for.
+    #[error(
+        "Incompatible MXL library version {found}, the bindings require {expected} or a later minor release"
+    )]
+    IncompatibleVersion { found: String, expected: String },
+}
+
+/// Lets conversions that cannot fail, e.g. of a [`uuid::Uuid`] to a [`crate::FlowId`], be used
+/// where a fallible one is accepted.
+impl From<std::convert::Infallible> for Error {
+    fn from(value: std::convert::Infallible) -> Self {
+        match value {}
+    }
 }
 
 impl Error {
@@ -47,7 +83,60 @@ impl Error {
             mxl_sys::MXL_ERR_TIMEOUT => Err(Error::Timeout),
             mxl_sys::MXL_ERR_INVALID_ARG => Err(Error::InvalidArg),
             mxl_sys::MXL_ERR_CONFLICT => Err(Error::Conflict),
+            mxl_sys::MXL_ERR_PERMISSION_DENIED => Err(Error::PermissionDenied),
+            mxl_sys::MXL_ERR_FLOW_INVALID => Err(Error::FlowInvalid),
             other => Err(Error::Unknown(other)),
         }
     }
+
+    /// Returns the MXL status code this error was created from, if it originates from the C API.
+    pub fn status(&self) -> Option<mxl_sys::Status> {
+        match self {
+            Error::Unknown(status) => Some(*status),
+            Error::FlowNotFound => Some(mxl_sys::MXL_ERR_FLOW_NOT_FOUND),
+            Error::OutOfRangeTooLate => Some(mxl_sys::MXL_ERR_OUT_OF_RANGE_TOO_LATE),
+            Error::OutOfRangeTooEarly => Some(mxl_sys::MXL_ERR_OUT_OF_RANGE_TOO_EARLY),
+            Error::InvalidFlowReader => Some(mxl_sys::MXL_ERR_INVALID_FLOW_READER),
+            Error::InvalidFlowWriter => Some(mxl_sys::MXL_ERR_INVALID_FLOW_WRITER),
+            Error::Timeout => Some(mxl_sys::MXL_ERR_TIMEOUT),
+            Error::InvalidArg => Some(mxl_sys::MXL_ERR_INVALID_ARG),
+            Error::Conflict => Some(mxl_sys::MXL_ERR_CONFLICT),
+            Error::PermissionDenied => Some(mxl_sys::MXL_ERR_PERMISSION_DENIED),
+            Error::FlowInvalid => Some(mxl_sys::MXL_ERR_FLOW_INVALID),
+            Error::QuotaExceeded { .. }
+            | Error::Other(_)
+            | Error::NulString(_)
+            | Error::LibLoading(_)
+            | Error::Io(_)
+            | Error::IncompatibleVersion { .. } => None,
+        }
+    }
+}
+
+/// Returns the symbolic name of an MXL status code, as declared in `mxl.h`.
+pub fn status_name(status: mxl_sys::Status) -> &'static str {
+    match status {
+        mxl_sys::MXL_STATUS_OK => "MXL_STATUS_OK",
+        mxl_sys::MXL_ERR_UNKNOWN => "MXL_ERR_UNKNOWN",
+        mxl_sys::MXL_ERR_FLOW_NOT_FOUND => "MXL_ERR_FLOW_NOT_FOUND",
+        mxl_sys::MXL_ERR_OUT_OF_RANGE_TOO_LATE => "MXL_ERR_OUT_OF_RANGE_TOO_LATE",
+        mxl_sys::MXL_ERR_OUT_OF_RANGE_TOO_EARLY => "MXL_ERR_OUT_OF_RANGE_TOO_EARLY",
+        mxl_sys::MXL_ERR_INVALID_FLOW_READER => "MXL_ERR_INVALID_FLOW_READER",
+        mxl_sys::MXL_ERR_INVALID_FLOW_WRITER => "MXL_ERR_INVALID_FLOW_WRITER",
+        mxl_sys::MXL_ERR_TIMEOUT => "MXL_ERR_TIMEOUT",
+        mxl_sys::MXL_ERR_INVALID_ARG => "MXL_ERR_INVALID_ARG",
+        mxl_sys::MXL_ERR_CONFLICT => "MXL_ERR_CONFLICT",
+        mxl_sys::MXL_ERR_PERMISSION_DENIED => "MXL_ERR_PERMISSION_DENIED",
+        mxl_sys::MXL_ERR_FLOW_INVALID => "MXL_ERR_FLOW_INVALID",
+        mxl_sys::MXL_ERR_STRLEN => "MXL_ERR_STRLEN",
+        mxl_sys::MXL_ERR_INTERRUPTED => "MXL_ERR_INTERRUPTED",
+        mxl_sys::MXL_ERR_NO_FABRIC => "MXL_ERR_NO_FABRIC",
+        mxl_sys::MXL_ERR_INVALID_STATE => "MXL_ERR_INVALID_STATE",
+        mxl_sys::MXL_ERR_INTERNAL => "MXL_ERR_INTERNAL",
+        mxl_sys::MXL_ERR_NOT_READY => "MXL_ERR_NOT_READY",
+        mxl_sys::MXL_ERR_NOT_FOUND => "MXL_ERR_NOT_FOUND",
+        mxl_sys::MXL_ERR_EXISTS => "MXL_ERR_EXISTS",
+        mxl_sys::MXL_ERR_UNSUPPORTED_OPERATION => "MXL_ERR_UNSUPPORTED_OPERATION",
+        _ => "unrecognized status",
+    }
 }
@@ -0,0 +1,217 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON messages over data flows, for control and metadata channels next to the media.
+//!
+//! An [`EventPublisher`] creates an `application/json` data flow at a declared grain rate and
+//! writes each batch of events as one grain, paced to that rate. An [`EventSubscriber`] follows
+//! the flow's head and hands out the events published since it last looked:
+//!
+//! ```ignore
+//! let mut publisher = EventPublisher::create(&instance, flow_id, "tally", rates::FPS_25)?;
+//! publisher.publish(&json!({ "tally": "red" }))?;
+//!
+//! let mut subscriber = EventSubscriber::open(&instance, flow_id)?;
+//! for event in subscriber.recv(Duration::from_secs(1))? {
+//!     println!("{}: {}", event.index, event.value);
+//! }
+//! ```
+//!
+//! Events are kept in the flow's ring only, so a subscriber that falls further behind than the
+//! ring's history loses the oldest ones.
+
+use std::{sync::Arc, time::Duration};
+
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    DataGrainReader, DataGrainWriter, Error, FlowId, MxlInstance, Rational, RationalExt, Result,
+    TimeSource, flowdef::FlowDefDetails,
+};
+
+/// Media type of the data flows carrying events.
+pub const EVENT_MEDIA_TYPE: &str = "application/json";
+
+/// Flow definition of an `application/json` data flow.
+pub fn event_flow_def(id: Uuid, label: &str, rate: Rational) -> String {
+    json!({
+        "id": id,
+        "description": label,
+        "label": label,
+        "format": "urn:x-nmos:format:data",
+        "tags": {},
+        "parents": [],
+        "media_type": EVENT_MEDIA_TYPE,
+        "grain_rate": { "numerator": rate.numerator, "denominator": rate.denominator },
+    })
+    .to_string()
+}
+
+/// An event and the index of the grain it was published in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub index: u64,
+    pub value: serde_json::Value,
+}
+
+/// Publishes JSON events to a data flow, see the [module documentation](self).
+#[derive(Debug)]
+pub struct EventPublisher {
+    flow_id: Uuid,
+    writer: DataGrainWriter,
+}
+
+impl EventPublisher {
+    /// Creates the event flow `flow_id`, or opens it if it exists already.
+    pub fn create(
+        instance: &MxlInstance,
+        flow_id: Uuid,
+        label: &str,
+        rate: Rational,
+    ) -> Result<Self> {
+        let (writer, _, _) =
+            instance.create_flow_writer(&event_flow_def(flow_id, label, rate), None)?;
+        Ok(Self {
+            flow_id,
+            writer: writer.to_data_writer()?,
+        })
+    }
+
+    pub fn flow_id(&self) -> Uuid {
+        self.flow_id
+    }
+
+    /// See [`DataGrainWriter::time_source`].
+    pub fn time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.writer = self.writer.time_source(time_source);
+        self
+    }
+
+    /// Publishes `event` in a grain of its own and returns the grain's index. Waits for the next
+    /// grain interval if a grain was already published in the current one.
+    pub fn publish<T: Serialize>(&mut self, event: &T) -> Result<u64> {
+        self.publish_all(std::slice::from_ref(event))
+    }
+
+    /// Publishes `events` together in one grain and returns its index. They must fit the grain,
+    /// [`crate::MXL_DATA_FORMAT_GRAIN_SIZE`] bytes including a 4-byte prefix per event.
+    pub fn publish_all<T: Serialize>(&mut self, events: &[T]) -> Result<u64> {
+        if events.is_empty() {
+            return Err(Error::Other("No events to publish.".to_string()));
+        }
+        let encoded = events
+            .iter()
+            .map(serde_json::to_vec)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|error| Error::Other(format!("Failed to serialize event: {error}")))?;
+        let packets: Vec<&[u8]> = encoded.iter().map(Vec::as_slice).collect();
+        self.writer.write_packets(&packets)
+    }
+}
+
+/// Receives the JSON events of a data flow, see the [module documentation](self).
+#[derive(Debug)]
+pub struct EventSubscriber {
+    flow_id: Uuid,
+    reader: DataGrainReader,
+    grain_rate: Rational,
+    time_source: Arc<dyn TimeSource>,
+    /// Number of grains in the flow's ring, older grains are overwritten.
+    ring_length: u64,
+    /// Index of the next grain to read.
+    next_index: u64,
+}
+
+impl EventSubscriber {
+    /// Opens the event flow `flow_id`. Only events published from now on are received.
+    pub fn open(
+        instance: &MxlInstance,
+        flow_id: impl TryInto<FlowId, Error: Into<Error>>,
+    ) -> Result<Self> {
+        let flow_id = flow_id.try_into().map_err(Into::into)?;
+        let flow_def = instance.get_flow_def_typed(flow_id)?;
+        let FlowDefDetails::Data(data) = &flow_def.details else {
+            return Err(Error::Other(format!("Flow {flow_id} is not a data flow.")));
+        };
+        if flow_def.media_type != EVENT_MEDIA_TYPE {
+            return Err(Error::Other(format!(
+                "Flow {flow_id} carries {}, not {EVENT_MEDIA_TYPE} events.",
+                flow_def.media_type
+            )));
+        }
+        let reader = instance.create_flow_reader(flow_id)?;
+        let ring_length = u64::from(reader.get_info()?.config.ring_length()).max(1);
+        let reader = reader.to_data_reader()?;
+        let next_index = reader.head_index()?.saturating_add(1);
+        Ok(Self {
+            flow_id: flow_id.uuid(),
+            reader,
+            grain_rate: (&data.grain_rate).into(),
+            time_source: Arc::new(instance.clone()),
+            ring_length,
+            next_index,
+        })
+    }
+
+    pub fn flow_id(&self) -> Uuid {
+        self.flow_id
+    }
+
+    /// Clock [`EventSubscriber::recv`] waits on, the instance's by default. See
+    /// [`crate::VirtualClock`] for tests.
+    pub fn time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Returns the events published since the previous call, oldest first, without waiting.
+    pub fn poll(&mut self) -> Result<Vec<Event>> {
+        let end = self.reader.head_index()?.saturating_add(1);
+        if end <= self.next_index {
+            return Ok(Vec::new());
+        }
+        // Grains older than the ring are gone, including all of them after a pause.
+        let start = self.next_index.max(end.saturating_sub(self.ring_length));
+        let grains = self.reader.read_packets(start..end)?;
+        self.next_index = end;
+        let mut events = Vec::new();
+        for grain in grains {
+            for packet in grain.packets {
+                let value = serde_json::from_slice(&packet).map_err(|error| {
+                    Error::Other(format!(
+                        "Invalid event in grain {} of flow {}: {error}",
+                        grain.index, self.flow_id
+                    ))
+                })?;
+                events.push(Event {
+                    index: grain.index,
+                    value,
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    /// Waits up to `timeout` for events, see [`EventSubscriber::poll`]. Returns no events if
+    /// none were published in time.
+    pub fn recv(&mut self, timeout: Duration) -> Result<Vec<Event>> {
+        let nanos = |duration: Duration| u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let deadline = self.time_source.now().saturating_add(nanos(timeout));
+        let interval = nanos(
+            self.grain_rate
+                .period()
+                .unwrap_or(Duration::from_millis(10)),
+        );
+        loop {
+            let events = self.poll()?;
+            let now = self.time_source.now();
+            if !events.is_empty() || now >= deadline {
+                return Ok(events);
+            }
+            self.time_source
+                .sleep_until(now.saturating_add(interval).min(deadline));
+        }
+    }
+}
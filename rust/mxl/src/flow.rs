@@ -5,11 +5,18 @@ pub mod flowdef;
 pub mod reader;
 pub mod writer;
 
+use std::{fmt, str::FromStr, time::Duration};
+
+use serde::{Serialize, Serializer};
 use uuid::Uuid;
 
-use crate::{Error, Result};
+use crate::{
+    Error, PayloadLocation, RationalExt, Result, SampleFormat,
+    instance::{InstanceContext, get_flow_def},
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DataFormat {
     Unspecified,
     Video,
@@ -29,16 +36,113 @@ impl From<u32> for DataFormat {
     }
 }
 
+/// Id of a flow. The MXL API identifies flows by the string form of their UUID; the methods
+/// taking a flow id accept anything convertible to a `FlowId`, so a malformed string is rejected
+/// before calling into the library:
+///
+/// ```ignore
+/// let reader = instance.create_flow_reader(flow_id)?; // A Uuid, a FlowId or a string.
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FlowId(Uuid);
+
+impl FlowId {
+    pub fn uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl fmt::Display for FlowId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<Uuid> for FlowId {
+    fn from(value: Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&Uuid> for FlowId {
+    fn from(value: &Uuid) -> Self {
+        Self(*value)
+    }
+}
+
+impl From<FlowId> for Uuid {
+    fn from(value: FlowId) -> Self {
+        value.0
+    }
+}
+
+impl FromStr for FlowId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Uuid::parse_str(s)
+            .map(Self)
+            .map_err(|error| Error::Other(format!("Invalid flow id \"{s}\": {error}")))
+    }
+}
+
+impl TryFrom<&str> for FlowId {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl TryFrom<&String> for FlowId {
+    type Error = Error;
+
+    fn try_from(value: &String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for FlowId {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
 pub(crate) fn is_discrete_data_format(format: u32) -> bool {
     // Check is based on mxlIsDiscreteDataFormat, which is inline, thus not accessible in mxl_sys.
     format == mxl_sys::MXL_DATA_FORMAT_VIDEO || format == mxl_sys::MXL_DATA_FORMAT_DATA
 }
 
+/// Sample format of the continuous flow `flow_id`, read from its flow definition.
+pub(crate) fn sample_format(context: &InstanceContext, flow_id: &Uuid) -> Result<SampleFormat> {
+    let invalid = |error| {
+        Error::Other(format!(
+            "Invalid flow definition of flow {flow_id}: {error}"
+        ))
+    };
+    let flow_def: serde_json::Value =
+        serde_json::from_str(&get_flow_def(context, flow_id.into())?).map_err(invalid)?;
+    let media_type = flow_def["media_type"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let audio: flowdef::FlowDefAudio = serde_json::from_value(flow_def).map_err(invalid)?;
+    audio.sample_format(&media_type)
+}
+
+/// Configuration and runtime state of a flow. Serializes to a JSON object with `config` and
+/// `runtime` members, see [`FlowConfigInfo`] and [`FlowRuntimeInfo`].
+#[derive(Debug, Serialize)]
 pub struct FlowInfo {
     pub config: FlowConfigInfo,
     pub runtime: FlowRuntimeInfo,
 }
 
+/// Serializes the common configuration with snake case names, plus a `discrete` or `continuous`
+/// object depending on the flow's format. Reserved bytes are left out.
+#[derive(Clone)]
 pub struct FlowConfigInfo {
     pub(crate) value: mxl_sys::FlowConfigInfo,
 }
@@ -71,8 +175,125 @@ impl FlowConfigInfo {
     pub fn is_discrete_flow(&self) -> bool {
         is_discrete_data_format(self.value.common.format)
     }
+
+    /// Number of grains (discrete flows) or samples per channel (continuous flows) in the ring
+    /// buffer of the flow.
+    pub fn ring_length(&self) -> u32 {
+        match (self.discrete(), self.continuous()) {
+            (Ok(discrete), _) => discrete.grainCount,
+            (_, Ok(continuous)) => continuous.bufferLength,
+            _ => 0,
+        }
+    }
+
+    /// How far back grains or samples stay available for reading.
+    ///
+    /// Only half of a continuous flow's buffer is readable at any point in time, so this is half
+    /// of the buffer length for continuous flows.
+    pub fn history_duration(&self) -> Duration {
+        let mut length = self.ring_length() as u64;
+        if !self.is_discrete_flow() {
+            length /= 2;
+        }
+        self.value
+            .common
+            .grainRate
+            .duration_of(length)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize)]
+struct RateRepr {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl From<mxl_sys::Rational> for RateRepr {
+    fn from(rate: mxl_sys::Rational) -> Self {
+        Self {
+            numerator: rate.numerator,
+            denominator: rate.denominator,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DiscreteRepr {
+    slice_sizes: [u32; 4],
+    grain_count: u32,
+}
+
+#[derive(Serialize)]
+struct ContinuousRepr {
+    channel_count: u32,
+    buffer_length: u32,
+}
+
+#[derive(Serialize)]
+struct FlowConfigRepr {
+    id: Uuid,
+    format: DataFormat,
+    flags: u32,
+    grain_rate: RateRepr,
+    max_commit_batch_size_hint: u32,
+    max_sync_batch_size_hint: u32,
+    payload_location: &'static str,
+    device_index: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    discrete: Option<DiscreteRepr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    continuous: Option<ContinuousRepr>,
+}
+
+impl Serialize for FlowConfigInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let common = self.common();
+        FlowConfigRepr {
+            id: common.id(),
+            format: common.data_format(),
+            flags: self.value.common.flags,
+            grain_rate: common.grain_or_sample_rate().into(),
+            max_commit_batch_size_hint: common.max_commit_batch_size_hint(),
+            max_sync_batch_size_hint: common.max_sync_batch_size_hint(),
+            payload_location: if common.payload_location().is_host() {
+                "host"
+            } else {
+                "device"
+            },
+            device_index: common.device_index(),
+            discrete: self.discrete().ok().map(|discrete| DiscreteRepr {
+                slice_sizes: discrete.sliceSizes,
+                grain_count: discrete.grainCount,
+            }),
+            continuous: self.continuous().ok().map(|continuous| ContinuousRepr {
+                channel_count: continuous.channelCount,
+                buffer_length: continuous.bufferLength,
+            }),
+        }
+        .serialize(serializer)
+    }
 }
 
+impl fmt::Debug for FlowConfigInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let common = self.common();
+        let mut debug = f.debug_struct("FlowConfigInfo");
+        debug
+            .field("id", &common.id())
+            .field("format", &common.data_format())
+            .field("rate", &common.grain_or_sample_rate())
+            .field("payload_location", &common.payload_location());
+        match (self.discrete(), self.continuous()) {
+            (Ok(discrete), _) => debug.field("discrete", discrete),
+            (_, Ok(continuous)) => debug.field("continuous", continuous),
+            _ => &mut debug,
+        };
+        debug.finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct CommonFlowConfigInfo<'a>(&'a mxl_sys::CommonFlowConfigInfo);
 
 impl CommonFlowConfigInfo<'_> {
@@ -122,8 +343,9 @@ impl CommonFlowConfigInfo<'_> {
         self.0.maxSyncBatchSizeHint
     }
 
-    pub fn payload_location(&self) -> u32 {
-        self.0.payloadLocation
+    /// Where the grain payloads are located, see [`crate::GrainReader::get_device_grain`].
+    pub fn payload_location(&self) -> PayloadLocation {
+        PayloadLocation::new(self.0.payloadLocation, self.0.deviceIndex)
     }
 
     pub fn device_index(&self) -> i32 {
@@ -131,10 +353,34 @@ impl CommonFlowConfigInfo<'_> {
     }
 }
 
+/// Serializes to `head_index`, `last_write_time` and `last_read_time`, the times in TAI
+/// nanoseconds.
 pub struct FlowRuntimeInfo {
     pub(crate) value: mxl_sys::FlowRuntimeInfo,
 }
 
+impl Serialize for FlowRuntimeInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("FlowRuntimeInfo", 3)?;
+        state.serialize_field("head_index", &self.head_index())?;
+        state.serialize_field("last_write_time", &self.last_write_time())?;
+        state.serialize_field("last_read_time", &self.last_read_time())?;
+        state.end()
+    }
+}
+
+impl fmt::Debug for FlowRuntimeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlowRuntimeInfo")
+            .field("head_index", &self.head_index())
+            .field("last_write_time", &self.last_write_time())
+            .field("last_read_time", &self.last_read_time())
+            .finish()
+    }
+}
+
 impl FlowRuntimeInfo {
     pub fn head_index(&self) -> u64 {
         self.value.headIndex
@@ -147,4 +393,11 @@ impl FlowRuntimeInfo {
     pub fn last_read_time(&self) -> u64 {
         self.value.lastReadTime
     }
+
+    /// Whether the writer has not written to the flow for more than `threshold`, as seen at
+    /// `now_tai` (see [`crate::MxlInstance::get_time`]). A stale flow usually means its writer is
+    /// gone, which a reader would otherwise only notice through read timeouts.
+    pub fn is_stale(&self, now_tai: u64, threshold: Duration) -> bool {
+        now_tai.saturating_sub(self.value.lastWriteTime) as u128 > threshold.as_nanos()
+    }
 }
@@ -5,11 +5,24 @@ use std::{collections::HashMap, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{Error, SampleFormat};
+
+/// Media type of audio flows of 32-bit float samples.
+pub const AUDIO_FLOAT32: &str = "audio/float32";
+/// Media type of audio flows of 16-bit linear PCM samples.
+pub const AUDIO_L16: &str = "audio/L16";
+/// Media type of audio flows of 24-bit linear PCM samples.
+pub const AUDIO_L24: &str = "audio/L24";
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(from = "FlowDefRepr")]
 pub struct FlowDef {
     pub id: uuid::Uuid,
     pub description: String,
     pub tags: HashMap<String, Vec<String>>,
+    /// The NMOS format URN. On the wire this is the tag of the flattened `details`, which is
+    /// serialized in its place and which it is parsed from.
+    #[serde(skip_serializing)]
     pub format: String,
     pub label: String,
     pub parents: Vec<String>,
@@ -18,6 +31,53 @@ pub struct FlowDef {
     pub details: FlowDefDetails,
 }
 
+/// A parsed [`FlowDef`]. The `format` member is consumed by the tag of the flattened details, so
+/// it cannot be a member here as well. The descriptive members are optional, so definitions
+/// written by other tools parse.
+#[derive(Deserialize)]
+struct FlowDefRepr {
+    id: uuid::Uuid,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    parents: Vec<String>,
+    media_type: String,
+    #[serde(flatten)]
+    details: FlowDefDetails,
+}
+
+impl From<FlowDefRepr> for FlowDef {
+    fn from(repr: FlowDefRepr) -> Self {
+        Self {
+            id: repr.id,
+            description: repr.description,
+            tags: repr.tags,
+            format: repr.details.format().to_string(),
+            label: repr.label,
+            parents: repr.parents,
+            media_type: repr.media_type,
+            details: repr.details,
+        }
+    }
+}
+
+impl FlowDef {
+    /// Sample format of an audio flow, see [`FlowDefAudio::sample_format`].
+    pub fn audio_sample_format(&self) -> crate::Result<SampleFormat> {
+        match &self.details {
+            FlowDefDetails::Audio(audio) => audio.sample_format(&self.media_type),
+            _ => Err(Error::Other(format!(
+                "Flow {} is not an audio flow.",
+                self.id
+            ))),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(tag = "format")]
 pub enum FlowDefDetails {
@@ -30,6 +90,17 @@ pub enum FlowDefDetails {
     Data(FlowDefData),
 }
 
+impl FlowDefDetails {
+    /// The NMOS format URN of the details, e.g. `urn:x-nmos:format:video`.
+    pub fn format(&self) -> &'static str {
+        match self {
+            FlowDefDetails::Video(_) => "urn:x-nmos:format:video",
+            FlowDefDetails::Audio(_) => "urn:x-nmos:format:audio",
+            FlowDefDetails::Data(_) => "urn:x-nmos:format:data",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct FlowDefVideo {
     pub grain_rate: Rate,
@@ -82,6 +153,41 @@ pub struct FlowDefAudio {
     pub bit_depth: u8,
 }
 
+impl FlowDefAudio {
+    /// Format of the samples in the channel buffers of a flow of `media_type`. Samples are stored
+    /// little-endian, so `audio/L16` and `audio/L24` flows hold [`SampleFormat::S16`] and
+    /// [`SampleFormat::S24`] rather than the network byte order of ST 2110-30.
+    ///
+    /// The bit depth has to match the media type: 32 for `audio/float32`, 16 for `audio/L16` and 24
+    /// for `audio/L24`.
+    pub fn sample_format(&self, media_type: &str) -> crate::Result<SampleFormat> {
+        let format = if media_type.eq_ignore_ascii_case(AUDIO_FLOAT32) {
+            SampleFormat::F32
+        } else if media_type.eq_ignore_ascii_case(AUDIO_L16) {
+            SampleFormat::S16
+        } else if media_type.eq_ignore_ascii_case(AUDIO_L24) {
+            SampleFormat::S24
+        } else {
+            return Err(Error::Other(format!(
+                "Unsupported audio media type \"{media_type}\"."
+            )));
+        };
+        if usize::from(self.bit_depth) != 8 * format.bytes_per_sample() {
+            return Err(Error::Other(format!(
+                "Bit depth {} does not match the audio media type \"{media_type}\".",
+                self.bit_depth
+            )));
+        }
+        Ok(format)
+    }
+
+    /// Size of one sample of one channel in the flow's buffers, the bit depth rounded up to whole
+    /// bytes.
+    pub fn bytes_per_sample(&self) -> usize {
+        usize::from(self.bit_depth).div_ceil(8)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct FlowDefData {
     pub grain_rate: Rate,
@@ -4,28 +4,42 @@
 use std::sync::Arc;
 
 use crate::{
-    DataFormat, Error, FlowConfigInfo, FlowRuntimeInfo, GrainReader, Result, SamplesReader,
-    flow::{FlowInfo, is_discrete_data_format},
+    DataFormat, DataGrainReader, Error, FlowConfigInfo, FlowRuntimeInfo, GrainReader, HeadChanges,
+    Result, SamplesReader,
+    flow::{FlowInfo, is_discrete_data_format, sample_format},
     instance::InstanceContext,
+    instrument::ffi_call,
+    leaks::{LiveObjectKind, Tracked},
 };
 
 pub struct FlowReader {
     context: Arc<InstanceContext>,
     reader: mxl_sys::FlowReader,
+    id: uuid::Uuid,
+    _tracked: Tracked,
 }
 
 /// The MXL readers and writers are not thread-safe, so we do not implement `Sync` for them, but
 /// there is no reason to not implement `Send`.
 unsafe impl Send for FlowReader {}
 
+impl std::fmt::Debug for FlowReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlowReader")
+            .field("flow_id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
 pub(crate) fn get_flow_info(
     context: &Arc<InstanceContext>,
     reader: mxl_sys::FlowReader,
+    flow_id: &uuid::Uuid,
 ) -> Result<FlowInfo> {
     let mut flow_info: mxl_sys::FlowInfo = unsafe { std::mem::zeroed() };
-    unsafe {
-        Error::from_status(context.api.flow_reader_get_info(reader, &mut flow_info))?;
-    }
+    Error::from_status(ffi_call!("mxlFlowReaderGetInfo", flow = flow_id; unsafe {
+        context.api.flow_reader_get_info(reader, &mut flow_info)
+    }))?;
     Ok(FlowInfo {
         config: FlowConfigInfo {
             value: flow_info.config,
@@ -39,56 +53,111 @@ pub(crate) fn get_flow_info(
 pub(crate) fn get_config_info(
     context: &Arc<InstanceContext>,
     reader: mxl_sys::FlowReader,
+    flow_id: &uuid::Uuid,
 ) -> Result<FlowConfigInfo> {
     let mut config_info: mxl_sys::FlowConfigInfo = unsafe { std::mem::zeroed() };
-    unsafe {
-        Error::from_status(
+    Error::from_status(
+        ffi_call!("mxlFlowReaderGetConfigInfo", flow = flow_id; unsafe {
             context
                 .api
-                .flow_reader_get_config_info(reader, &mut config_info),
-        )?;
-    }
+                .flow_reader_get_config_info(reader, &mut config_info)
+        }),
+    )?;
     Ok(FlowConfigInfo { value: config_info })
 }
 
 pub(crate) fn get_runtime_info(
     context: &Arc<InstanceContext>,
     reader: mxl_sys::FlowReader,
+    flow_id: &uuid::Uuid,
 ) -> Result<mxl_sys::FlowRuntimeInfo> {
     let mut runtime_info: mxl_sys::FlowRuntimeInfo = unsafe { std::mem::zeroed() };
-    unsafe {
-        Error::from_status(
+    Error::from_status(
+        ffi_call!("mxlFlowReaderGetRuntimeInfo", flow = flow_id; unsafe {
             context
                 .api
-                .flow_reader_get_runtime_info(reader, &mut runtime_info),
-        )?;
-    }
+                .flow_reader_get_runtime_info(reader, &mut runtime_info)
+        }),
+    )?;
     Ok(runtime_info)
 }
 
 impl FlowReader {
-    pub(crate) fn new(context: Arc<InstanceContext>, reader: mxl_sys::FlowReader) -> Self {
-        Self { context, reader }
+    pub(crate) fn new(
+        context: Arc<InstanceContext>,
+        reader: mxl_sys::FlowReader,
+        id: uuid::Uuid,
+    ) -> Self {
+        context.open_handles.reader_opened(reader);
+        Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::FlowReader, id),
+            context,
+            reader,
+            id,
+        }
+    }
+
+    pub fn flow_id(&self) -> uuid::Uuid {
+        self.id
     }
 
     pub fn get_info(&self) -> Result<FlowInfo> {
-        get_flow_info(&self.context, self.reader)
+        get_flow_info(&self.context, self.reader, &self.id)
     }
 
-    pub fn to_grain_reader(mut self) -> Result<GrainReader> {
-        let flow_type = self.get_info()?.config.value.common.format;
+    pub fn to_grain_reader(self) -> Result<GrainReader> {
+        let config = self.get_info()?.config;
+        let flow_type = config.value.common.format;
         if !is_discrete_data_format(flow_type) {
             return Err(Error::Other(format!(
                 "Cannot convert FlowReader to GrainReader for continuous flow of type \"{:?}\".",
                 DataFormat::from(flow_type)
             )));
         }
-        let result = GrainReader::new(self.context.clone(), self.reader);
+        Ok(self.into_grain_reader(&config))
+    }
+
+    /// Fails for video and audio flows.
+    pub fn to_data_reader(self) -> Result<DataGrainReader> {
+        let config = self.get_info()?.config;
+        let format = config.common().data_format();
+        if format != DataFormat::Data {
+            return Err(Error::Other(format!(
+                "Cannot convert FlowReader to DataGrainReader for flow of type \"{format:?}\"."
+            )));
+        }
+        Ok(DataGrainReader::new(self.into_grain_reader(&config)))
+    }
+
+    /// The grain or samples reader matching the flow's format, with a single query of the flow's
+    /// configuration.
+    pub fn into_typed(self) -> Result<TypedReader> {
+        let config = self.get_info()?.config;
+        if config.is_discrete_flow() {
+            Ok(TypedReader::Grain(self.into_grain_reader(&config)))
+        } else {
+            self.into_samples_reader().map(TypedReader::Samples)
+        }
+    }
+
+    fn into_grain_reader(mut self, config: &FlowConfigInfo) -> GrainReader {
+        let result = GrainReader::new(
+            self.context.clone(),
+            self.reader,
+            self.id,
+            config.common().payload_location(),
+        );
         self.reader = std::ptr::null_mut();
-        Ok(result)
+        result
+    }
+
+    /// Blocking iterator of the flow's head index, for monitoring progress without polling. See
+    /// [`crate::HeadNotifier`] to wait on the flow from an event loop instead.
+    pub fn head_changes(self) -> Result<HeadChanges> {
+        HeadChanges::new(self)
     }
 
-    pub fn to_samples_reader(mut self) -> Result<SamplesReader> {
+    pub fn to_samples_reader(self) -> Result<SamplesReader> {
         let flow_type = self.get_info()?.config.value.common.format;
         if is_discrete_data_format(flow_type) {
             return Err(Error::Other(format!(
@@ -96,7 +165,12 @@ impl FlowReader {
                 DataFormat::from(flow_type)
             )));
         }
-        let result = SamplesReader::new(self.context.clone(), self.reader);
+        self.into_samples_reader()
+    }
+
+    fn into_samples_reader(mut self) -> Result<SamplesReader> {
+        let format = sample_format(&self.context, &self.id)?;
+        let result = SamplesReader::new(self.context.clone(), self.reader, self.id, format);
         self.reader = std::ptr::null_mut();
         Ok(result)
     }
@@ -105,13 +179,39 @@ impl FlowReader {
 impl Drop for FlowReader {
     fn drop(&mut self) {
         if !self.reader.is_null()
-            && let Err(err) = Error::from_status(unsafe {
-                self.context
-                    .api
-                    .release_flow_reader(self.context.instance, self.reader)
-            })
+            && self.context.open_handles.reader_closing(self.reader)
+            && let Err(err) = Error::from_status(ffi_call!(
+                "mxlReleaseFlowReader", flow = self.id; unsafe {
+                    self.context
+                        .api
+                        .release_flow_reader(self.context.instance, self.reader)
+                }
+            ))
         {
             tracing::error!("Failed to release MXL flow reader: {:?}", err);
         }
     }
 }
+
+/// Reader of either kind of flow, see [`FlowReader::into_typed`].
+#[derive(Debug)]
+pub enum TypedReader {
+    Grain(GrainReader),
+    Samples(SamplesReader),
+}
+
+impl TypedReader {
+    pub fn flow_id(&self) -> uuid::Uuid {
+        match self {
+            TypedReader::Grain(reader) => reader.flow_id(),
+            TypedReader::Samples(reader) => reader.flow_id(),
+        }
+    }
+
+    pub fn get_info(&self) -> Result<FlowInfo> {
+        match self {
+            TypedReader::Grain(reader) => reader.get_info(),
+            TypedReader::Samples(reader) => reader.get_info(),
+        }
+    }
+}
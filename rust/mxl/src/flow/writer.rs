@@ -4,9 +4,12 @@
 use std::sync::Arc;
 
 use crate::{
-    DataFormat, Error, GrainWriter, Result, SamplesWriter,
-    flow::is_discrete_data_format,
-    instance::{InstanceContext, create_flow_reader},
+    DataFormat, DataGrainWriter, Error, FlowConfigInfo, FlowRuntimeInfo, GrainWriter, MxlInstance,
+    Result, SampleFormat, SamplesWriter,
+    flow::{FlowInfo, sample_format},
+    instance::InstanceContext,
+    instrument::ffi_call,
+    leaks::{LiveObjectKind, Tracked},
 };
 
 /// Generic MXL Flow Writer, which can be further used to build either the "discrete" (grain-based
@@ -15,78 +18,176 @@ pub struct FlowWriter {
     context: Arc<InstanceContext>,
     writer: mxl_sys::FlowWriter,
     id: uuid::Uuid,
+    format: DataFormat,
+    /// Format of the channel buffers, `None` for discrete flows.
+    sample_format: Option<SampleFormat>,
+    config: FlowConfigInfo,
+    _tracked: Tracked,
 }
 
 /// The MXL readers and writers are not thread-safe, so we do not implement `Sync` for them, but
 /// there is no reason to not implement `Send`.
 unsafe impl Send for FlowWriter {}
 
+impl std::fmt::Debug for FlowWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlowWriter")
+            .field("flow_id", &self.id)
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Unlike querying the flow through a reader, this does not count as a read of the flow.
+pub(crate) fn get_flow_info(
+    context: &Arc<InstanceContext>,
+    writer: mxl_sys::FlowWriter,
+    flow_id: &uuid::Uuid,
+) -> Result<FlowInfo> {
+    let mut flow_info: mxl_sys::FlowInfo = unsafe { std::mem::zeroed() };
+    Error::from_status(ffi_call!("mxlFlowWriterGetInfo", flow = flow_id; unsafe {
+        context.api.flow_writer_get_info(writer, &mut flow_info)
+    }))?;
+    Ok(FlowInfo {
+        config: FlowConfigInfo {
+            value: flow_info.config,
+        },
+        runtime: FlowRuntimeInfo {
+            value: flow_info.runtime,
+        },
+    })
+}
+
+pub(crate) fn get_runtime_info(
+    context: &Arc<InstanceContext>,
+    writer: mxl_sys::FlowWriter,
+    flow_id: &uuid::Uuid,
+) -> Result<mxl_sys::FlowRuntimeInfo> {
+    let mut runtime_info: mxl_sys::FlowRuntimeInfo = unsafe { std::mem::zeroed() };
+    Error::from_status(
+        ffi_call!("mxlFlowWriterGetRuntimeInfo", flow = flow_id; unsafe {
+            context
+                .api
+                .flow_writer_get_runtime_info(writer, &mut runtime_info)
+        }),
+    )?;
+    Ok(runtime_info)
+}
+
 impl FlowWriter {
+    /// Takes ownership of `writer`, which is released if the sample format of a continuous flow
+    /// cannot be determined.
     pub(crate) fn new(
         context: Arc<InstanceContext>,
         writer: mxl_sys::FlowWriter,
-        id: uuid::Uuid,
-    ) -> Self {
-        Self {
+        config: &FlowConfigInfo,
+    ) -> Result<Self> {
+        context.open_handles.writer_opened(writer);
+        let common = config.common();
+        let mut result = Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::FlowWriter, common.id()),
             context,
             writer,
-            id,
+            id: common.id(),
+            format: common.data_format(),
+            sample_format: None,
+            config: config.clone(),
+        };
+        if !config.is_discrete_flow() {
+            // Resolved while the flow's files are known to exist, so converting the writer later
+            // needs no access to them.
+            result.sample_format = Some(sample_format(&result.context, &result.id)?);
         }
+        Ok(result)
+    }
+
+    pub fn flow_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    pub fn data_format(&self) -> DataFormat {
+        self.format
+    }
+
+    /// Configuration of the flow, as returned when the writer was created. It does not change
+    /// while the flow exists, so this needs no call into MXL.
+    pub fn config_info(&self) -> &FlowConfigInfo {
+        &self.config
     }
 
+    pub fn get_info(&self) -> Result<FlowInfo> {
+        get_flow_info(&self.context, self.writer, &self.id)
+    }
+
+    pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
+        get_runtime_info(&self.context, self.writer, &self.id)
+    }
+
+    /// Fails only for continuous flows.
     pub fn to_grain_writer(mut self) -> Result<GrainWriter> {
-        let flow_type = self.get_flow_type()?;
-        if !is_discrete_data_format(flow_type) {
+        if self.sample_format.is_some() {
             return Err(Error::Other(format!(
                 "Cannot convert FlowWriter to GrainWriter for continuous flow of type \"{:?}\".",
-                DataFormat::from(flow_type)
+                self.format
             )));
         }
-        let result = GrainWriter::new(self.context.clone(), self.writer);
+        let result = GrainWriter::new(self.context.clone(), self.writer, self.config.clone());
         self.writer = std::ptr::null_mut();
         Ok(result)
     }
 
+    /// Fails for video and audio flows.
+    pub fn to_data_writer(self) -> Result<DataGrainWriter> {
+        if self.format != DataFormat::Data {
+            return Err(Error::Other(format!(
+                "Cannot convert FlowWriter to DataGrainWriter for flow of type \"{:?}\".",
+                self.format
+            )));
+        }
+        let grain_rate = self.config.common().grain_rate()?;
+        let instance = MxlInstance::from_context(self.context.clone());
+        Ok(DataGrainWriter::new(
+            self.to_grain_writer()?,
+            instance,
+            grain_rate,
+        ))
+    }
+
+    /// Fails only for discrete flows.
     pub fn to_samples_writer(mut self) -> Result<SamplesWriter> {
-        let flow_type = self.get_flow_type()?;
-        if is_discrete_data_format(flow_type) {
+        let Some(format) = self.sample_format else {
             return Err(Error::Other(format!(
                 "Cannot convert FlowWriter to SamplesWriter for discrete flow of type \"{:?}\".",
-                DataFormat::from(flow_type)
+                self.format
             )));
-        }
-        let result = SamplesWriter::new(self.context.clone(), self.writer);
+        };
+        let result = SamplesWriter::new(
+            self.context.clone(),
+            self.writer,
+            self.config.clone(),
+            format,
+        );
         self.writer = std::ptr::null_mut();
         Ok(result)
     }
-
-    fn get_flow_type(&self) -> Result<u32> {
-        // This feels pretty ugly, but currently, the only way how to get a flow type in MXL is to
-        // use a reader.
-        let reader = create_flow_reader(&self.context, &self.id.to_string()).map_err(|error| {
-            Error::Other(format!(
-                "Error while creating flow reader to get the flow type: {error}"
-            ))
-        })?;
-        let flow_info = reader.get_info().map_err(|error| {
-            Error::Other(format!(
-                "Error while getting flow type from temporary reader: {error}"
-            ))
-        })?;
-        Ok(flow_info.config.value.common.format)
-    }
 }
 
 impl Drop for FlowWriter {
     fn drop(&mut self) {
-        if !self.writer.is_null()
-            && let Err(err) = Error::from_status(unsafe {
-                self.context
-                    .api
-                    .release_flow_writer(self.context.instance, self.writer)
-            })
+        if self.writer.is_null() {
+            return;
+        }
+        if self.context.open_handles.writer_closing(self.writer)
+            && let Err(err) = Error::from_status(ffi_call!(
+                "mxlReleaseFlowWriter", flow = self.id; unsafe {
+                    self.context
+                        .api
+                        .release_flow_writer(self.context.instance, self.writer)
+                }
+            ))
         {
             tracing::error!("Failed to release MXL flow writer: {:?}", err);
         }
+        self.context.flow_writer_released(&self.id);
     }
 }
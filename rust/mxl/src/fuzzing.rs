@@ -0,0 +1,12 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Internals the fuzz targets in `rust/mxl/fuzz` call directly, enabled with the `fuzzing`
+//! feature. Not part of the API.
+
+use crate::Result;
+
+/// See `mxlGetFlowDef`: the buffer the library filled and the size it reported.
+pub fn flow_def_from_buffer(buffer: Vec<u8>, size: usize) -> Result<String> {
+    crate::instance::flow_def_from_buffer(buffer, size)
+}
@@ -0,0 +1,263 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Test signals for examples, tests and demos: colour bars and a frame counter for `video/v210`
+//! flows, a sine tone or silence for `audio/float32` flows, and the flow definitions to create
+//! such flows.
+//!
+//! The signals are a function of the grain or sample index only, so grains written out of order
+//! or by several writers in turn line up, and a reader can check what it received:
+//!
+//! ```ignore
+//! let generator = VideoGenerator::new(1920, 1080, VideoPattern::Counter)?;
+//! let mut access = writer.open_grain(index)?;
+//! generator.render(index, access.payload_mut())?;
+//! ```
+
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{Error, Rational, Result, dump::pack_v210};
+
+const F32_SIZE: usize = std::mem::size_of::<f32>();
+
+/// 75% colour bars in 10-bit BT.709 Y, Cb, Cr: white, yellow, cyan, green, magenta, red, blue and
+/// black.
+const BARS: [[u16; 3]; 8] = [
+    [721, 512, 512],
+    [674, 176, 543],
+    [581, 589, 176],
+    [534, 253, 207],
+    [251, 771, 817],
+    [204, 435, 848],
+    [111, 848, 481],
+    [64, 512, 512],
+];
+const BLACK: [u16; 3] = [64, 512, 512];
+const WHITE: [u16; 3] = [940, 512, 512];
+
+/// Digits 0 to 9 in a 3x5 font, a row per 3 bits, most significant bit to the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Flow definition of a progressive BT.709 `video/v210` flow.
+pub fn video_flow_def(id: Uuid, label: &str, width: u32, height: u32, rate: Rational) -> String {
+    json!({
+        "id": id,
+        "description": label,
+        "label": label,
+        "format": "urn:x-nmos:format:video",
+        "tags": {},
+        "parents": [],
+        "media_type": "video/v210",
+        "grain_rate": { "numerator": rate.numerator, "denominator": rate.denominator },
+        "frame_width": width,
+        "frame_height": height,
+        "interlace_mode": "progressive",
+        "colorspace": "BT709",
+        "components": [
+            { "name": "Y", "width": width, "height": height, "bit_depth": 10 },
+            { "name": "Cb", "width": width.div_ceil(2), "height": height, "bit_depth": 10 },
+            { "name": "Cr", "width": width.div_ceil(2), "height": height, "bit_depth": 10 },
+        ],
+    })
+    .to_string()
+}
+
+/// Flow definition of an `audio/float32` flow.
+pub fn audio_flow_def(id: Uuid, label: &str, channels: u32, rate: Rational) -> String {
+    json!({
+        "id": id,
+        "description": label,
+        "label": label,
+        "format": "urn:x-nmos:format:audio",
+        "tags": {},
+        "parents": [],
+        "media_type": "audio/float32",
+        "sample_rate": { "numerator": rate.numerator, "denominator": rate.denominator },
+        "channel_count": channels,
+        "bit_depth": 32,
+    })
+    .to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VideoPattern {
+    /// Eight vertical 75% colour bars.
+    #[default]
+    ColorBars,
+    /// The grain index in white digits on black.
+    Counter,
+}
+
+/// Renders the v210 pictures of a [`VideoPattern`].
+#[derive(Debug, Clone)]
+pub struct VideoGenerator {
+    width: usize,
+    height: usize,
+    pattern: VideoPattern,
+    /// The colour bars, rendered once since they do not change.
+    bars: Vec<u8>,
+}
+
+impl VideoGenerator {
+    pub fn new(width: usize, height: usize, pattern: VideoPattern) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(Error::Other(format!(
+                "Invalid picture size {width}x{height}."
+            )));
+        }
+        let bars = match pattern {
+            VideoPattern::ColorBars => {
+                pack_v210(&planar(width, height, |x, _| bar(x, width)), width, height)?
+            }
+            VideoPattern::Counter => Vec::new(),
+        };
+        Ok(Self {
+            width,
+            height,
+            pattern,
+            bars,
+        })
+    }
+
+    /// Size in bytes of a rendered picture.
+    pub fn payload_size(&self) -> usize {
+        crate::dump::v210_line_length(self.width) * self.height
+    }
+
+    /// Renders the picture of grain `index` to the start of `payload`.
+    pub fn render(&self, index: u64, payload: &mut [u8]) -> Result<()> {
+        let size = self.payload_size();
+        if payload.len() < size {
+            return Err(Error::Other(format!(
+                "A v210 picture of {}x{} takes {size} bytes, the payload has {}.",
+                self.width,
+                self.height,
+                payload.len()
+            )));
+        }
+        match self.pattern {
+            VideoPattern::ColorBars => payload[..size].copy_from_slice(&self.bars),
+            VideoPattern::Counter => {
+                let text = index.to_string();
+                // Digits are 3 cells wide with a cell of space in between, and 5 cells high.
+                let columns = text.len() * 4 - 1;
+                let cell = (self.width / (columns + 2)).min(self.height / 7).max(1);
+                let left = self.width.saturating_sub(columns * cell) / 2;
+                let top = self.height.saturating_sub(5 * cell) / 2;
+                let digits: Vec<usize> =
+                    text.bytes().map(|digit| (digit - b'0') as usize).collect();
+                let planar = planar(self.width, self.height, |x, y| {
+                    let (Some(x), Some(y)) = (x.checked_sub(left), y.checked_sub(top)) else {
+                        return BLACK;
+                    };
+                    let (column, row) = (x / cell, y / cell);
+                    if row >= 5 || column >= columns || column % 4 == 3 {
+                        return BLACK;
+                    }
+                    let glyph_row = DIGITS[digits[column / 4]][row];
+                    if glyph_row & (0b100 >> (column % 4)) != 0 {
+                        WHITE
+                    } else {
+                        BLACK
+                    }
+                });
+                payload[..size].copy_from_slice(&pack_v210(&planar, self.width, self.height)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Colour of the bar at column `x`.
+fn bar(x: usize, width: usize) -> [u16; 3] {
+    BARS[x * BARS.len() / width]
+}
+
+/// Planar 10-bit 4:2:2 picture of the colours returned by `pixel(x, y)`. Chroma is taken from the
+/// even pixels.
+fn planar(width: usize, height: usize, pixel: impl Fn(usize, usize) -> [u16; 3]) -> Vec<u16> {
+    let chroma_width = width.div_ceil(2);
+    let mut planes = [
+        Vec::with_capacity(width * height),
+        Vec::with_capacity(chroma_width * height),
+        Vec::with_capacity(chroma_width * height),
+    ];
+    for y in 0..height {
+        for x in 0..width {
+            let [luma, cb, cr] = pixel(x, y);
+            planes[0].push(luma);
+            if x % 2 == 0 {
+                planes[1].push(cb);
+                planes[2].push(cr);
+            }
+        }
+    }
+    planes.concat()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AudioPattern {
+    /// A sine of `frequency` Hz and `amplitude` relative to full scale.
+    Tone { frequency: f64, amplitude: f64 },
+    #[default]
+    Silence,
+}
+
+/// Produces the float samples of an [`AudioPattern`].
+#[derive(Debug, Clone, Copy)]
+pub struct AudioGenerator {
+    pattern: AudioPattern,
+    sample_rate: u64,
+}
+
+impl AudioGenerator {
+    /// `sample_rate` is in whole Hz, as the flow's sample rate should be.
+    pub fn new(pattern: AudioPattern, sample_rate: u64) -> Result<Self> {
+        if sample_rate == 0 {
+            return Err(Error::Other("The sample rate must not be 0.".to_string()));
+        }
+        Ok(Self {
+            pattern,
+            sample_rate,
+        })
+    }
+
+    /// Sample at `index`. The phase restarts every second, which is seamless for whole-Hz
+    /// frequencies and keeps the sample exact at indices of the current time.
+    pub fn sample(&self, index: u64) -> f32 {
+        match self.pattern {
+            AudioPattern::Tone {
+                frequency,
+                amplitude,
+            } => {
+                let time = (index % self.sample_rate) as f64 / self.sample_rate as f64;
+                (amplitude * (std::f64::consts::TAU * frequency * time).sin()) as f32
+            }
+            AudioPattern::Silence => 0.0,
+        }
+    }
+
+    /// Fills the channel `fragments`, as returned by
+    /// [`crate::SamplesWriteAccess::channel_data_mut`], with the samples from `first_index` on.
+    pub fn fill(&self, first_index: u64, fragments: (&mut [u8], &mut [u8])) {
+        let outputs = fragments
+            .0
+            .chunks_exact_mut(F32_SIZE)
+            .chain(fragments.1.chunks_exact_mut(F32_SIZE));
+        for (index, output) in (first_index..).zip(outputs) {
+            output.copy_from_slice(&self.sample(index).to_le_bytes());
+        }
+    }
+}
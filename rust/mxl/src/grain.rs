@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod data;
+pub mod packets;
 pub mod reader;
 pub mod write_access;
 pub mod writer;
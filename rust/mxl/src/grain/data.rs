@@ -1,6 +1,10 @@
 // SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::DevicePayload;
+
+/// A grain read from a flow. `Debug` shows the payload's length rather than its bytes.
+#[derive(Clone)]
 pub struct GrainData<'a> {
     /// The grain payload. This may be a partial payload if the grain is not complete.
     /// The length of this slice is given by `commitedSize` in `mxlGrainInfo`.
@@ -11,6 +15,12 @@ pub struct GrainData<'a> {
 
     pub flags: u32,
 
+    /// Number of slices (lines for video) committed by the writer. Slices past this one may still
+    /// hold data of a previous grain.
+    pub valid_slices: u16,
+
+    pub total_slices: u16,
+
     /// Absolute epoch grain index actually occupying the ring slot that was read.
     /// A reader that requested index `R` may find a different grain here: an older
     /// index means `R` has not been produced yet, a newer one means the writer
@@ -19,21 +29,76 @@ pub struct GrainData<'a> {
 }
 
 impl<'a> GrainData<'a> {
+    pub fn is_complete(&self) -> bool {
+        self.valid_slices == self.total_slices
+    }
+
     pub fn to_owned(&self) -> OwnedGrainData {
         self.into()
     }
 }
 
+impl std::fmt::Debug for GrainData<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrainData")
+            .field("payload_len", &self.payload.len())
+            .field("total_size", &self.total_size)
+            .field("flags", &self.flags)
+            .field("valid_slices", &self.valid_slices)
+            .field("total_slices", &self.total_slices)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
 impl<'a> AsRef<GrainData<'a>> for GrainData<'a> {
     fn as_ref(&self) -> &GrainData<'a> {
         self
     }
 }
 
+/// A grain of a flow whose payloads are in device memory, see
+/// [`crate::GrainReader::get_device_grain`].
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceGrainData<'a> {
+    /// The complete grain payload, of which the first `valid_slices` slices are committed.
+    pub payload: DevicePayload<'a>,
+
+    pub flags: u32,
+
+    pub valid_slices: u16,
+
+    pub total_slices: u16,
+
+    /// See [`GrainData::index`].
+    pub index: u64,
+}
+
+impl<'a> DeviceGrainData<'a> {
+    pub fn is_complete(&self) -> bool {
+        self.valid_slices == self.total_slices
+    }
+
+    /// Device address of the payload, see [`DevicePayload::device_ptr`].
+    #[cfg(feature = "cuda")]
+    pub fn device_ptr(&self) -> crate::cuda::CuDevicePtr {
+        self.payload.device_ptr()
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Eq)]
 pub struct OwnedGrainData {
     pub payload: Vec<u8>,
 }
 
+impl std::fmt::Debug for OwnedGrainData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwnedGrainData")
+            .field("payload_len", &self.payload.len())
+            .finish()
+    }
+}
+
 impl<'a> From<&GrainData<'a>> for OwnedGrainData {
     fn from(value: &GrainData<'a>) -> Self {
         Self {
@@ -47,3 +112,45 @@ impl<'a> From<GrainData<'a>> for OwnedGrainData {
         value.as_ref().into()
     }
 }
+
+/// A grain copied out of the ring by [`crate::GrainReader::snapshot`]. `Debug` shows the payload's
+/// length rather than its bytes.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SnapshotGrain {
+    pub index: u64,
+    pub flags: u32,
+    pub valid_slices: u16,
+    pub total_slices: u16,
+    /// The complete grain payload, of which the first `valid_slices` slices are committed.
+    pub payload: Vec<u8>,
+}
+
+impl SnapshotGrain {
+    pub fn is_complete(&self) -> bool {
+        self.valid_slices == self.total_slices
+    }
+}
+
+impl std::fmt::Debug for SnapshotGrain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapshotGrain")
+            .field("index", &self.index)
+            .field("flags", &self.flags)
+            .field("valid_slices", &self.valid_slices)
+            .field("total_slices", &self.total_slices)
+            .field("payload_len", &self.payload.len())
+            .finish()
+    }
+}
+
+impl<'a> From<&GrainData<'a>> for SnapshotGrain {
+    fn from(value: &GrainData<'a>) -> Self {
+        Self {
+            index: value.index,
+            flags: value.flags,
+            valid_slices: value.valid_slices,
+            total_slices: value.total_slices,
+            payload: value.payload.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Packet oriented access to data flows.
+//!
+//! Applications exchanging small metadata blobs (JSON, KLV, ...) write each batch of packets as
+//! one grain. Every packet is prefixed with its length as a little-endian `u32`, and a zero length
+//! ends the grain's packets when they do not fill it.
+//!
+//! Flows carrying one message per grain, e.g. an event per frame, use
+//! [`DataFraming::SingleMessage`] on both ends, so that a writer cannot batch messages readers do
+//! not expect.
+//!
+//! The readers and writers are created from a [`crate::FlowReader`] or [`crate::FlowWriter`] with
+//! `to_data_reader` and `to_data_writer`, which fail for video and audio flows.
+//!
+//! Low-rate flows do not write a grain at every index. The writer puts each batch at the next
+//! unwritten index that is not in the past, waiting for it if needed, and readers skip indices
+//! whose ring slot still holds an older grain.
+
+use std::{ops::Range, sync::Arc, time::Duration};
+
+use crate::{Error, FlowRuntimeInfo, GrainReader, GrainWriter, MxlInstance, Result, TimeSource};
+
+const LENGTH_PREFIX_SIZE: usize = std::mem::size_of::<u32>();
+
+/// How packets are laid out in the grains of a data flow, see the
+/// [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataFraming {
+    /// Any number of length-prefixed packets per grain.
+    #[default]
+    LengthPrefixed,
+    /// Exactly one length-prefixed packet per grain.
+    SingleMessage,
+}
+
+/// Packets read from the grain at `index`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPackets {
+    pub index: u64,
+    pub packets: Vec<Vec<u8>>,
+}
+
+/// Writes length-prefixed packets to a data flow, at most one grain per grain interval.
+#[derive(Debug)]
+pub struct DataGrainWriter {
+    writer: GrainWriter,
+    time_source: Arc<dyn TimeSource>,
+    grain_rate: mxl_sys::Rational,
+    framing: DataFraming,
+    last_index: Option<u64>,
+}
+
+impl DataGrainWriter {
+    pub fn new(writer: GrainWriter, instance: MxlInstance, grain_rate: mxl_sys::Rational) -> Self {
+        Self {
+            writer,
+            time_source: Arc::new(instance),
+            grain_rate,
+            framing: DataFraming::default(),
+            last_index: None,
+        }
+    }
+
+    pub fn framing(mut self, framing: DataFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    pub fn get_framing(&self) -> DataFraming {
+        self.framing
+    }
+
+    /// Clock the writer paces its grains to, the instance's by default. See [`crate::VirtualClock`]
+    /// for tests.
+    pub fn time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    pub fn into_inner(self) -> GrainWriter {
+        self.writer
+    }
+
+    /// Writes `packet` as a grain of its own, see [`DataGrainWriter::write_packets`].
+    pub fn write_packet(&mut self, packet: &[u8]) -> Result<u64> {
+        self.write_packets(&[packet])
+    }
+
+    /// Writes `packets` together in one grain and returns its index.
+    ///
+    /// The grain goes to the current index, or to the one after the previously written grain if
+    /// that is later, in which case this sleeps until that index is current. Empty packets cannot
+    /// be written, as a zero length ends the grain's packets. With
+    /// [`DataFraming::SingleMessage`], `packets` must hold exactly one packet.
+    pub fn write_packets(&mut self, packets: &[&[u8]]) -> Result<u64> {
+        if self.framing == DataFraming::SingleMessage && packets.len() != 1 {
+            return Err(Error::Other(format!(
+                "Single message data flows take one packet per grain, got {}.",
+                packets.len()
+            )));
+        }
+        let current = self.time_source.current_index(&self.grain_rate);
+        let index = match self.last_index {
+            Some(last) if last >= current => last + 1,
+            _ => current,
+        };
+        if index > current {
+            self.time_source
+                .sleep_until_index(index, &self.grain_rate)?;
+        }
+
+        let mut access = self.writer.open_grain(index)?;
+        encode_packets(packets, access.payload_mut())?;
+        let total_slices = access.total_slices();
+        access.commit(total_slices)?;
+        self.last_index = Some(index);
+        Ok(index)
+    }
+}
+
+/// Reads length-prefixed packets written by a [`DataGrainWriter`].
+#[derive(Debug)]
+pub struct DataGrainReader {
+    reader: GrainReader,
+    framing: DataFraming,
+}
+
+impl DataGrainReader {
+    pub fn new(reader: GrainReader) -> Self {
+        Self {
+            reader,
+            framing: DataFraming::default(),
+        }
+    }
+
+    /// With [`DataFraming::SingleMessage`], grains holding more than one packet fail to read.
+    pub fn framing(mut self, framing: DataFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    pub fn get_framing(&self) -> DataFraming {
+        self.framing
+    }
+
+    pub fn into_inner(self) -> GrainReader {
+        self.reader
+    }
+
+    /// Index of the newest grain written to the flow.
+    pub fn head_index(&self) -> Result<u64> {
+        Ok(FlowRuntimeInfo {
+            value: self.reader.get_runtime_info()?,
+        }
+        .head_index())
+    }
+
+    /// Returns the packets of the grains written within `range`, oldest first, without waiting.
+    /// Indices past the head, never written, or already overwritten in the ring are skipped.
+    pub fn read_packets(&self, range: Range<u64>) -> Result<Vec<DataPackets>> {
+        let end = range.end.min(self.head_index()?.saturating_add(1));
+        let mut grains = Vec::new();
+        for index in range.start..end {
+            let Ok(grain) = self.reader.get_grain_non_blocking(index) else {
+                continue;
+            };
+            if grain.index != index || !grain.is_complete() {
+                continue;
+            }
+            grains.push(DataPackets {
+                index,
+                packets: self.decode(grain.payload)?,
+            });
+        }
+        Ok(grains)
+    }
+
+    /// Waits up to `timeout` for the grain at `index` and returns its packets.
+    pub fn read_grain_packets(&self, index: u64, timeout: Duration) -> Result<DataPackets> {
+        let grain = self.reader.get_complete_grain(index, timeout)?;
+        if grain.index != index {
+            return Err(Error::Other(format!(
+                "No grain at index {index}, the ring slot holds index {}.",
+                grain.index
+            )));
+        }
+        Ok(DataPackets {
+            index,
+            packets: self.decode(grain.payload)?,
+        })
+    }
+
+    fn decode(&self, payload: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let packets = decode_packets(payload)?;
+        if self.framing == DataFraming::SingleMessage && packets.len() > 1 {
+            return Err(Error::Other(format!(
+                "Single message data flow grain holds {} packets.",
+                packets.len()
+            )));
+        }
+        Ok(packets)
+    }
+}
+
+/// Writes `packets` to the grain payload `destination`, for writers that choose the grain index
+/// themselves. Fails on empty packets and on packets exceeding the payload.
+pub fn encode_packets(packets: &[&[u8]], destination: &mut [u8]) -> Result<()> {
+    let mut offset = 0;
+    for packet in packets {
+        if packet.is_empty() {
+            return Err(Error::Other("Cannot write an empty packet.".to_string()));
+        }
+        let length = u32::try_from(packet.len())
+            .map_err(|_| Error::Other(format!("Packet of {} bytes is too large.", packet.len())))?;
+        let end = offset + LENGTH_PREFIX_SIZE + packet.len();
+        if end > destination.len() {
+            return Err(Error::Other(format!(
+                "Packets exceed the grain size of {} bytes.",
+                destination.len()
+            )));
+        }
+        destination[offset..offset + LENGTH_PREFIX_SIZE].copy_from_slice(&length.to_le_bytes());
+        destination[offset + LENGTH_PREFIX_SIZE..end].copy_from_slice(packet);
+        offset = end;
+    }
+    // The ring slot still holds the previous grain, terminate the packets.
+    if let Some(terminator) = destination.get_mut(offset..offset + LENGTH_PREFIX_SIZE) {
+        terminator.fill(0);
+    }
+    Ok(())
+}
+
+/// Returns the packets of the grain payload `payload`, see [`encode_packets`].
+pub fn decode_packets(payload: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut packets = Vec::new();
+    let mut offset = 0;
+    while let Some(prefix) = payload.get(offset..offset + LENGTH_PREFIX_SIZE) {
+        let length = u32::from_le_bytes(prefix.try_into().unwrap_or_default()) as usize;
+        if length == 0 {
+            break;
+        }
+        let start = offset + LENGTH_PREFIX_SIZE;
+        let packet = payload.get(start..start + length).ok_or_else(|| {
+            Error::Other(format!(
+                "Packet of {length} bytes at offset {offset} exceeds the grain payload of {} bytes.",
+                payload.len()
+            ))
+        })?;
+        packets.push(packet.to_vec());
+        offset = start + length;
+    }
+    Ok(packets)
+}
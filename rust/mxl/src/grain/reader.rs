@@ -4,26 +4,62 @@
 use std::{sync::Arc, time::Duration};
 
 use crate::{
-    Error, FlowConfigInfo, GrainData, Result,
+    AccessOptions, DeviceGrainData, DevicePayload, Error, FlowConfigInfo, FlowRuntimeInfo,
+    GrainData, PayloadLocation, Result, SnapshotGrain,
     flow::{
         FlowInfo,
         reader::{get_config_info, get_flow_info, get_runtime_info},
     },
     instance::InstanceContext,
+    instrument::{Media, ReaderMetrics, ffi_call},
+    leaks::{LiveObjectKind, Tracked},
 };
 
 pub struct GrainReader {
     context: Arc<InstanceContext>,
     reader: mxl_sys::FlowReader,
+    id: uuid::Uuid,
+    payload_location: PayloadLocation,
+    metrics: ReaderMetrics,
+    _tracked: Tracked,
 }
 
 /// The MXL readers and writers are not thread-safe, so we do not implement `Sync` for them, but
 /// there is no reason to not implement `Send`.
 unsafe impl Send for GrainReader {}
 
+impl std::fmt::Debug for GrainReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrainReader")
+            .field("flow_id", &self.id)
+            .field("payload_location", &self.payload_location)
+            .finish_non_exhaustive()
+    }
+}
+
 impl GrainReader {
-    pub(crate) fn new(context: Arc<InstanceContext>, reader: mxl_sys::FlowReader) -> Self {
-        Self { context, reader }
+    pub(crate) fn new(
+        context: Arc<InstanceContext>,
+        reader: mxl_sys::FlowReader,
+        id: uuid::Uuid,
+        payload_location: PayloadLocation,
+    ) -> Self {
+        Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::GrainReader, id),
+            context,
+            reader,
+            id,
+            payload_location,
+            metrics: ReaderMetrics::new(&id, Media::Grains),
+        }
+    }
+
+    pub fn flow_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    pub fn payload_location(&self) -> PayloadLocation {
+        self.payload_location
     }
 
     pub fn destroy(mut self) -> Result<()> {
@@ -33,35 +69,55 @@ impl GrainReader {
     /// The whole FlowInfo is quite a chunk of data. Go for `get_config_info` or `get_runtime_info`
     /// if they contain what you need.
     pub fn get_info(&self) -> Result<FlowInfo> {
-        get_flow_info(&self.context, self.reader)
+        get_flow_info(&self.context, self.reader, &self.id)
     }
 
     pub fn get_config_info(&self) -> Result<FlowConfigInfo> {
-        get_config_info(&self.context, self.reader)
+        get_config_info(&self.context, self.reader, &self.id)
     }
 
     pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
-        get_runtime_info(&self.context, self.reader)
+        get_runtime_info(&self.context, self.reader, &self.id)
+    }
+
+    /// See [`FlowRuntimeInfo::is_stale`].
+    pub fn is_flow_stale(&self, threshold: Duration) -> Result<bool> {
+        let runtime = FlowRuntimeInfo {
+            value: self.get_runtime_info()?,
+        };
+        let now = ffi_call!("mxlGetTime"; unsafe { self.context.api.get_time() });
+        Ok(runtime.is_stale(now, threshold))
     }
 
     pub fn get_complete_grain<'a>(
         &'a self,
         index: u64,
         timeout: Duration,
+    ) -> Result<GrainData<'a>> {
+        self.ensure_host_payload()?;
+        self.record_read(index, self.get_complete_grain_inner(index, timeout))
+    }
+
+    fn get_complete_grain_inner<'a>(
+        &'a self,
+        index: u64,
+        timeout: Duration,
     ) -> Result<GrainData<'a>> {
         let mut grain_info: mxl_sys::GrainInfo = unsafe { std::mem::zeroed() };
         let mut payload_ptr: *mut u8 = std::ptr::null_mut();
         let timeout_ns = timeout.as_nanos() as u64;
         loop {
-            unsafe {
-                Error::from_status(self.context.api.flow_reader_get_grain(
-                    self.reader,
-                    index,
-                    timeout_ns,
-                    &mut grain_info,
-                    &mut payload_ptr,
-                ))?;
-            }
+            Error::from_status(ffi_call!(
+                "mxlFlowReaderGetGrain", flow = self.id, index = index; unsafe {
+                    self.context.api.flow_reader_get_grain(
+                        self.reader,
+                        index,
+                        timeout_ns,
+                        &mut grain_info,
+                        &mut payload_ptr,
+                    )
+                }
+            ))?;
             if grain_info.validSlices != grain_info.totalSlices {
                 // We don't need partial grains. Wait for the grain to be complete.
                 continue;
@@ -84,23 +140,109 @@ impl GrainReader {
             payload,
             total_size: grain_info.grainSize as usize,
             flags: grain_info.flags,
+            valid_slices: grain_info.validSlices,
+            total_slices: grain_info.totalSlices,
             index: grain_info.index,
         })
     }
 
+    /// Waits until at least `min_valid_slices` slices of the grain are committed and returns it,
+    /// possibly partial. This allows consumers to start processing the top of a frame before the
+    /// writer has finished it. `min_valid_slices` is clamped to the grain's total slice count by
+    /// the library.
+    pub fn get_grain_slice<'a>(
+        &'a self,
+        index: u64,
+        min_valid_slices: u16,
+        timeout: Duration,
+    ) -> Result<GrainData<'a>> {
+        self.ensure_host_payload()?;
+        let mut grain_info: mxl_sys::GrainInfo = unsafe { std::mem::zeroed() };
+        let mut payload_ptr: *mut u8 = std::ptr::null_mut();
+        let result = Error::from_status(ffi_call!(
+            "mxlFlowReaderGetGrainSlice", flow = self.id, index = index; unsafe {
+                self.context.api.flow_reader_get_grain_slice(
+                    self.reader,
+                    index,
+                    min_valid_slices,
+                    timeout.as_nanos() as u64,
+                    &mut grain_info,
+                    &mut payload_ptr,
+                )
+            }
+        ))
+        .and_then(|()| grain_data(index, &grain_info, payload_ptr));
+        self.record_read(index, result)
+    }
+
+    /// Same as [`GrainReader::get_complete_grain`], with per-access options.
+    pub fn get_complete_grain_with_options<'a>(
+        &'a self,
+        index: u64,
+        timeout: Duration,
+        options: &AccessOptions,
+    ) -> Result<GrainData<'a>> {
+        options.warn_unsupported("get_complete_grain");
+        self.get_complete_grain(index, timeout)
+    }
+
+    /// Same as [`GrainReader::get_grain_slice`], with per-access options.
+    pub fn get_grain_slice_with_options<'a>(
+        &'a self,
+        index: u64,
+        min_valid_slices: u16,
+        timeout: Duration,
+        options: &AccessOptions,
+    ) -> Result<GrainData<'a>> {
+        options.warn_unsupported("get_grain_slice");
+        self.get_grain_slice(index, min_valid_slices, timeout)
+    }
+
+    /// Non-blocking version of `get_grain_slice`. If fewer than `min_valid_slices` slices are
+    /// available, returns an error.
+    pub fn get_grain_slice_non_blocking<'a>(
+        &'a self,
+        index: u64,
+        min_valid_slices: u16,
+    ) -> Result<GrainData<'a>> {
+        self.ensure_host_payload()?;
+        let mut grain_info: mxl_sys::GrainInfo = unsafe { std::mem::zeroed() };
+        let mut payload_ptr: *mut u8 = std::ptr::null_mut();
+        let result = Error::from_status(ffi_call!(
+            "mxlFlowReaderGetGrainSliceNonBlocking", flow = self.id, index = index; unsafe {
+                self.context.api.flow_reader_get_grain_slice_non_blocking(
+                    self.reader,
+                    index,
+                    min_valid_slices,
+                    &mut grain_info,
+                    &mut payload_ptr,
+                )
+            }
+        ))
+        .and_then(|()| grain_data(index, &grain_info, payload_ptr));
+        self.record_read(index, result)
+    }
+
     /// Non-blocking version of `get_complete_grain`. If the grain is not available, returns an error.
     /// If the grain is partial, it is returned as is and the payload length will be smaller than the total grain size.
     pub fn get_grain_non_blocking<'a>(&'a self, index: u64) -> Result<GrainData<'a>> {
+        self.ensure_host_payload()?;
+        self.record_read(index, self.get_grain_non_blocking_inner(index))
+    }
+
+    fn get_grain_non_blocking_inner<'a>(&'a self, index: u64) -> Result<GrainData<'a>> {
         let mut grain_info: mxl_sys::GrainInfo = unsafe { std::mem::zeroed() };
         let mut payload_ptr: *mut u8 = std::ptr::null_mut();
-        unsafe {
-            Error::from_status(self.context.api.flow_reader_get_grain_non_blocking(
-                self.reader,
-                index,
-                &mut grain_info,
-                &mut payload_ptr,
-            ))?;
-        }
+        Error::from_status(ffi_call!(
+            "mxlFlowReaderGetGrainNonBlocking", flow = self.id, index = index; unsafe {
+                self.context.api.flow_reader_get_grain_non_blocking(
+                    self.reader,
+                    index,
+                    &mut grain_info,
+                    &mut payload_ptr,
+                )
+            }
+        ))?;
 
         if payload_ptr.is_null() {
             return Err(Error::Other(format!(
@@ -118,10 +260,103 @@ impl GrainReader {
             payload,
             total_size: grain_info.grainSize as usize,
             flags: grain_info.flags,
+            valid_slices: grain_info.validSlices,
+            total_slices: grain_info.totalSlices,
             index: grain_info.index,
         })
     }
 
+    /// Copies every grain currently in the ring, oldest first, e.g. to look into what a consumer
+    /// received when it reported a glitch.
+    ///
+    /// Grains that were never written, and grains the writer overwrote while they were copied, are
+    /// left out. Partial grains are included as they are, see [`SnapshotGrain::valid_slices`].
+    pub fn snapshot(&self) -> Result<Vec<SnapshotGrain>> {
+        self.ensure_host_payload()?;
+        let grain_count = u64::from(self.get_config_info()?.ring_length()).max(1);
+        let head = self.get_runtime_info()?.headIndex;
+        let mut grains = Vec::with_capacity(grain_count as usize);
+        for index in head.saturating_sub(grain_count - 1)..=head {
+            // Not through `get_grain_non_blocking`, missing grains are no reader misses.
+            match self.get_grain_non_blocking_inner(index) {
+                Ok(grain) if grain.index == index => grains.push(SnapshotGrain::from(&grain)),
+                Ok(_) | Err(Error::OutOfRangeTooLate | Error::OutOfRangeTooEarly) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        // The writer reuses the slot of `index` when it opens `index + grain_count`, and it may
+        // have the grain after the head open.
+        let head_after = self.get_runtime_info()?.headIndex;
+        grains.retain(|grain| grain.index + grain_count > head_after + 1);
+        Ok(grains)
+    }
+
+    /// Device memory counterpart of [`GrainReader::get_grain_slice`] for flows whose payloads are
+    /// in GPU memory. `u16::MAX` slices waits for the complete grain.
+    pub fn get_device_grain<'a>(
+        &'a self,
+        index: u64,
+        min_valid_slices: u16,
+        timeout: Duration,
+    ) -> Result<DeviceGrainData<'a>> {
+        let PayloadLocation::DeviceMemory { device_index } = self.payload_location else {
+            return Err(Error::Other(format!(
+                "Grain payloads of flow {} are in host memory.",
+                self.id
+            )));
+        };
+        let mut grain_info: mxl_sys::GrainInfo = unsafe { std::mem::zeroed() };
+        let mut payload_ptr: *mut u8 = std::ptr::null_mut();
+        let result = Error::from_status(ffi_call!(
+            "mxlFlowReaderGetGrainSlice", flow = self.id, index = index; unsafe {
+                self.context.api.flow_reader_get_grain_slice(
+                    self.reader,
+                    index,
+                    min_valid_slices,
+                    timeout.as_nanos() as u64,
+                    &mut grain_info,
+                    &mut payload_ptr,
+                )
+            }
+        ))
+        .and_then(|()| {
+            if payload_ptr.is_null() {
+                return Err(Error::Other(format!(
+                    "Failed to get grain payload for index {index}.",
+                )));
+            }
+            Ok(DeviceGrainData {
+                payload: DevicePayload::new(
+                    payload_ptr,
+                    grain_info.grainSize as usize,
+                    device_index,
+                ),
+                flags: grain_info.flags,
+                valid_slices: grain_info.validSlices,
+                total_slices: grain_info.totalSlices,
+                index: grain_info.index,
+            })
+        });
+        self.record_read(index, result)
+    }
+
+    /// Host payload accessors would hand out device addresses as slices.
+    fn ensure_host_payload(&self) -> Result<()> {
+        if !self.payload_location.is_host() {
+            return Err(Error::Other(format!(
+                "Grain payloads of flow {} are in device memory, use get_device_grain.",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+
+    fn record_read<T>(&self, index: u64, result: Result<T>) -> Result<T> {
+        self.metrics
+            .record(&result, index, 1, || Ok(self.get_runtime_info()?.headIndex));
+        result
+    }
+
     fn destroy_inner(&mut self) -> Result<()> {
         if self.reader.is_null() {
             return Err(Error::InvalidArg);
@@ -130,12 +365,42 @@ impl GrainReader {
         let mut reader = std::ptr::null_mut();
         std::mem::swap(&mut self.reader, &mut reader);
 
-        Error::from_status(unsafe {
+        // A cleanup guard may have released it already.
+        if !self.context.open_handles.reader_closing(reader) {
+            return Ok(());
+        }
+        Error::from_status(ffi_call!("mxlReleaseFlowReader", flow = self.id; unsafe {
             self.context
                 .api
                 .release_flow_reader(self.context.instance, reader)
-        })
+        }))
+    }
+}
+
+fn grain_data<'a>(
+    index: u64,
+    grain_info: &mxl_sys::GrainInfo,
+    payload_ptr: *mut u8,
+) -> Result<GrainData<'a>> {
+    if payload_ptr.is_null() {
+        return Err(Error::Other(format!(
+            "Failed to get grain payload for index {index}.",
+        )));
     }
+
+    // SAFETY
+    // We know that the lifetime is as long as the flow, so it is at least the reader's lifetime.
+    // It may happen that the buffer is overwritten by a subsequent write, but it is safe.
+    let payload = unsafe { std::slice::from_raw_parts(payload_ptr, grain_info.grainSize as usize) };
+
+    Ok(GrainData {
+        payload,
+        total_size: grain_info.grainSize as usize,
+        flags: grain_info.flags,
+        valid_slices: grain_info.validSlices,
+        total_slices: grain_info.totalSlices,
+        index: grain_info.index,
+    })
 }
 
 impl Drop for GrainReader {
@@ -5,7 +5,12 @@ use std::{marker::PhantomData, sync::Arc};
 
 use tracing::error;
 
-use crate::{Error, Result, instance::InstanceContext};
+use crate::{
+    DevicePayload, Error, PayloadLocation, Result,
+    instance::InstanceContext,
+    instrument::{WriterMetrics, ffi_call},
+    leaks::{LiveObjectKind, Tracked},
+};
 
 /// RAII grain writing session
 ///
@@ -13,23 +18,34 @@ use crate::{Error, Result, instance::InstanceContext};
 pub struct GrainWriteAccess<'a> {
     context: Arc<InstanceContext>,
     writer: mxl_sys::FlowWriter,
+    flow_id: uuid::Uuid,
+    metrics: WriterMetrics,
+    payload_location: PayloadLocation,
     grain_info: mxl_sys::GrainInfo,
     payload_ptr: *mut u8,
     /// Serves as a flag to know whether to cancel the grain on drop.
     committed_or_canceled: bool,
     phantom: PhantomData<&'a ()>,
+    _tracked: Tracked,
 }
 
 impl<'a> GrainWriteAccess<'a> {
     pub(crate) fn new(
         context: Arc<InstanceContext>,
         writer: mxl_sys::FlowWriter,
+        flow_id: uuid::Uuid,
+        metrics: WriterMetrics,
+        payload_location: PayloadLocation,
         grain_info: mxl_sys::GrainInfo,
         payload_ptr: *mut u8,
     ) -> Self {
         Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::GrainWriteAccess, flow_id),
             context,
             writer,
+            flow_id,
+            metrics,
+            payload_location,
             grain_info,
             payload_ptr,
             committed_or_canceled: false,
@@ -37,12 +53,33 @@ impl<'a> GrainWriteAccess<'a> {
         }
     }
 
+    /// # Panics
+    ///
+    /// If the flow's payloads are in device memory, see [`GrainWriteAccess::device_payload`].
     pub fn payload_mut(&mut self) -> &mut [u8] {
+        assert!(
+            self.payload_location.is_host(),
+            "Grain payloads of flow {} are in device memory.",
+            self.flow_id
+        );
         unsafe {
             std::slice::from_raw_parts_mut(self.payload_ptr, self.grain_info.grainSize as usize)
         }
     }
 
+    /// The payload of a flow in device memory, `None` for host memory where
+    /// [`GrainWriteAccess::payload_mut`] applies.
+    pub fn device_payload(&mut self) -> Option<DevicePayload<'_>> {
+        match self.payload_location {
+            PayloadLocation::HostMemory => None,
+            PayloadLocation::DeviceMemory { device_index } => Some(DevicePayload::new(
+                self.payload_ptr,
+                self.grain_info.grainSize as usize,
+                device_index,
+            )),
+        }
+    }
+
     pub fn max_size(&self) -> u32 {
         self.grain_info.grainSize
     }
@@ -51,6 +88,15 @@ impl<'a> GrainWriteAccess<'a> {
         self.grain_info.totalSlices
     }
 
+    pub fn flags(&self) -> u32 {
+        self.grain_info.flags
+    }
+
+    /// Sets the grain flags published on commit, see [`crate::MXL_GRAIN_FLAG_INVALID`].
+    pub fn set_flags(&mut self, flags: u32) {
+        self.grain_info.flags = flags;
+    }
+
     pub fn commit(mut self, valid_slices: u16) -> Result<()> {
         self.committed_or_canceled = true;
 
@@ -62,13 +108,19 @@ impl<'a> GrainWriteAccess<'a> {
         }
         self.grain_info.validSlices = valid_slices;
 
-        unsafe {
-            Error::from_status(
+        Error::from_status(ffi_call!(
+            "mxlFlowWriterCommitGrain", flow = self.flow_id, index = self.grain_info.index;
+            unsafe {
                 self.context
                     .api
-                    .flow_writer_commit_grain(self.writer, &self.grain_info),
-            )
-        }
+                    .flow_writer_commit_grain(self.writer, &self.grain_info)
+            }
+        ))?;
+        // Slice by slice commits count the grain once, when it is complete.
+        let complete = valid_slices == self.grain_info.totalSlices;
+        self.metrics
+            .record(self.grain_info.index, usize::from(complete));
+        Ok(())
     }
 
     /// Please note that the behavior of canceling a grain writing is dependent on the behavior
@@ -78,16 +130,124 @@ impl<'a> GrainWriteAccess<'a> {
     pub fn cancel(mut self) -> Result<()> {
         self.committed_or_canceled = true;
 
-        unsafe { Error::from_status(self.context.api.flow_writer_cancel_grain(self.writer)) }
+        self.cancel_inner()
+    }
+
+    /// Releases the borrow of the writer while the grain stays open, so the payload can be filled
+    /// by a party that does not hold the writer, e.g. a buffer handed to another library. The
+    /// grain is committed or canceled after [`GrainWriter::resume_grain`].
+    ///
+    /// # Safety
+    ///
+    /// The borrow of the writer no longer guards the payload, so the caller must:
+    ///
+    /// - access the payload only while the grain is open, i.e. until it is resumed and committed or
+    ///   canceled, and while its writer is alive;
+    /// - not hand out references to the payload that outlive these;
+    /// - resume the grain on its writer before opening another one there, as MXL holds a single
+    ///   open grain per writer.
+    ///
+    /// [`GrainWriter::resume_grain`]: crate::GrainWriter::resume_grain
+    pub unsafe fn detach(mut self) -> DetachedGrain {
+        self.committed_or_canceled = true;
+        DetachedGrain {
+            _tracked: Tracked::new(
+                &self.context,
+                LiveObjectKind::GrainWriteAccess,
+                self.flow_id,
+            ),
+            writer: self.writer,
+            flow_id: self.flow_id,
+            payload_location: self.payload_location,
+            grain_info: self.grain_info,
+            payload_ptr: self.payload_ptr,
+        }
+    }
+
+    fn cancel_inner(&self) -> Result<()> {
+        Error::from_status(ffi_call!(
+            "mxlFlowWriterCancelGrain", flow = self.flow_id, index = self.grain_info.index;
+            unsafe { self.context.api.flow_writer_cancel_grain(self.writer) }
+        ))
+    }
+}
+
+impl std::fmt::Debug for GrainWriteAccess<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrainWriteAccess")
+            .field("flow_id", &self.flow_id)
+            .field("index", &self.grain_info.index)
+            .field("grain_size", &self.grain_info.grainSize)
+            .field("total_slices", &self.grain_info.totalSlices)
+            .field("payload_location", &self.payload_location)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Grain left open by [`GrainWriteAccess::detach`], whose safety contract covers its payload.
+///
+/// It stays open until it is resumed and committed or canceled, or its writer is released;
+/// dropping it does not cancel it. Its writer cannot open another grain meanwhile.
+pub struct DetachedGrain {
+    writer: mxl_sys::FlowWriter,
+    flow_id: uuid::Uuid,
+    payload_location: PayloadLocation,
+    grain_info: mxl_sys::GrainInfo,
+    payload_ptr: *mut u8,
+    _tracked: Tracked,
+}
+
+/// The payload is plain memory of the mapped flow, only the writer calls the library.
+unsafe impl Send for DetachedGrain {}
+
+impl DetachedGrain {
+    pub fn index(&self) -> u64 {
+        self.grain_info.index
+    }
+
+    /// Start of the payload, valid for [`DetachedGrain::max_size`] bytes while the grain is open
+    /// and its writer alive. In device memory for flows whose payloads are, see
+    /// [`DetachedGrain::payload_location`].
+    pub fn payload_ptr(&self) -> *mut u8 {
+        self.payload_ptr
+    }
+
+    pub fn payload_location(&self) -> PayloadLocation {
+        self.payload_location
+    }
+
+    pub fn max_size(&self) -> u32 {
+        self.grain_info.grainSize
+    }
+
+    pub fn total_slices(&self) -> u16 {
+        self.grain_info.totalSlices
+    }
+
+    pub(crate) fn writer(&self) -> mxl_sys::FlowWriter {
+        self.writer
+    }
+
+    pub(crate) fn grain_info(&self) -> mxl_sys::GrainInfo {
+        self.grain_info
+    }
+}
+
+impl std::fmt::Debug for DetachedGrain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DetachedGrain")
+            .field("flow_id", &self.flow_id)
+            .field("index", &self.grain_info.index)
+            .field("grain_size", &self.grain_info.grainSize)
+            .field("payload_location", &self.payload_location)
+            .finish_non_exhaustive()
     }
 }
 
 impl<'a> Drop for GrainWriteAccess<'a> {
     fn drop(&mut self) {
         if !self.committed_or_canceled
-            && let Err(error) = unsafe {
-                Error::from_status(self.context.api.flow_writer_cancel_grain(self.writer))
-            }
+            && let Err(error) = self.cancel_inner()
         {
             error!("Failed to cancel grain write on drop: {:?}", error);
         }
@@ -3,44 +3,104 @@
 
 use std::sync::Arc;
 
-use super::write_access::GrainWriteAccess;
+use super::write_access::{DetachedGrain, GrainWriteAccess};
 
-use crate::{Error, Result, instance::InstanceContext};
+use crate::{
+    AccessOptions, Error, FlowConfigInfo, PayloadLocation, Result,
+    flow::{
+        FlowInfo,
+        writer::{get_flow_info, get_runtime_info},
+    },
+    instance::InstanceContext,
+    instrument::{Media, WriterMetrics, ffi_call},
+    leaks::{LiveObjectKind, Tracked},
+};
 
 /// MXL Flow Writer for discrete flows (grain-based data like video frames)
 pub struct GrainWriter {
     context: Arc<InstanceContext>,
     writer: mxl_sys::FlowWriter,
+    id: uuid::Uuid,
+    payload_location: PayloadLocation,
+    config: FlowConfigInfo,
+    metrics: WriterMetrics,
+    _tracked: Tracked,
 }
 
 /// The MXL readers and writers are not thread-safe, so we do not implement `Sync` for them, but
 /// there is no reason to not implement `Send`.
 unsafe impl Send for GrainWriter {}
 
+impl std::fmt::Debug for GrainWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrainWriter")
+            .field("flow_id", &self.id)
+            .field("payload_location", &self.payload_location)
+            .finish_non_exhaustive()
+    }
+}
+
 impl GrainWriter {
-    pub(crate) fn new(context: Arc<InstanceContext>, writer: mxl_sys::FlowWriter) -> Self {
-        Self { context, writer }
+    pub(crate) fn new(
+        context: Arc<InstanceContext>,
+        writer: mxl_sys::FlowWriter,
+        config: FlowConfigInfo,
+    ) -> Self {
+        let id = config.common().id();
+        Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::GrainWriter, id),
+            context,
+            writer,
+            id,
+            payload_location: config.common().payload_location(),
+            config,
+            metrics: WriterMetrics::new(&id, Media::Grains),
+        }
+    }
+
+    pub fn flow_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    pub fn payload_location(&self) -> PayloadLocation {
+        self.payload_location
+    }
+
+    /// See [`crate::FlowWriter::config_info`].
+    pub fn config_info(&self) -> &FlowConfigInfo {
+        &self.config
     }
 
     pub fn destroy(mut self) -> Result<()> {
         self.destroy_inner()
     }
 
-    /// The current MXL implementation states a TODO to allow multiple grains to be edited at the
-    /// same time. For this reason, there is no protection on the Rust level against trying to open
-    /// multiple grains. If the TODO ever gets removed, it may be worth considering pattern where
-    /// opening grain would consume the writer and then return it back on commit or cancel.
-    pub fn open_grain<'a>(&'a self, index: u64) -> Result<GrainWriteAccess<'a>> {
+    /// The flow as the writer sees it, without a reader that would count as one of its consumers.
+    /// The whole FlowInfo is quite a chunk of data. Go for `config_info` or `get_runtime_info` if
+    /// they contain what you need.
+    pub fn get_info(&self) -> Result<FlowInfo> {
+        get_flow_info(&self.context, self.writer, &self.id)
+    }
+
+    pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
+        get_runtime_info(&self.context, self.writer, &self.id)
+    }
+
+    /// The MXL writer supports a single open grain at a time, so the returned session mutably
+    /// borrows the writer until it is committed, canceled or dropped.
+    pub fn open_grain<'a>(&'a mut self, index: u64) -> Result<GrainWriteAccess<'a>> {
         let mut grain_info: mxl_sys::GrainInfo = unsafe { std::mem::zeroed() };
         let mut payload_ptr: *mut u8 = std::ptr::null_mut();
-        unsafe {
-            Error::from_status(self.context.api.flow_writer_open_grain(
-                self.writer,
-                index,
-                &mut grain_info,
-                &mut payload_ptr,
-            ))?;
-        }
+        Error::from_status(ffi_call!(
+            "mxlFlowWriterOpenGrain", flow = self.id, index = index; unsafe {
+                self.context.api.flow_writer_open_grain(
+                    self.writer,
+                    index,
+                    &mut grain_info,
+                    &mut payload_ptr,
+                )
+            }
+        ))?;
 
         if payload_ptr.is_null() {
             return Err(Error::Other(format!(
@@ -51,11 +111,96 @@ impl GrainWriter {
         Ok(GrainWriteAccess::new(
             self.context.clone(),
             self.writer,
+            self.id,
+            self.metrics.clone(),
+            self.payload_location,
             grain_info,
             payload_ptr,
         ))
     }
 
+    /// Writes `payload` as the complete grain at `index`. The payload must be exactly the grain's
+    /// payload size, see [`GrainWriter::write_grain_padded`] for shorter payloads.
+    ///
+    /// Fails for flows whose payloads are in device memory, which [`GrainWriter::open_grain`]
+    /// gives access to.
+    pub fn write_grain(&mut self, index: u64, payload: &[u8]) -> Result<()> {
+        self.copy_grain(index, payload, false)
+    }
+
+    /// Same as [`GrainWriter::write_grain`] for payloads up to the grain's payload size, e.g. the
+    /// packets of a data grain. The rest of the grain is zeroed so that nothing of the grain the
+    /// ring slot held before is published with it.
+    pub fn write_grain_padded(&mut self, index: u64, payload: &[u8]) -> Result<()> {
+        self.copy_grain(index, payload, true)
+    }
+
+    fn copy_grain(&mut self, index: u64, payload: &[u8], padded: bool) -> Result<()> {
+        if !self.payload_location.is_host() {
+            return Err(Error::Other(format!(
+                "Grain payloads of flow {} are in device memory.",
+                self.id
+            )));
+        }
+        let mut access = self.open_grain(index)?;
+        let destination = access.payload_mut();
+        if payload.len() > destination.len() || (!padded && payload.len() < destination.len()) {
+            return Err(Error::Other(format!(
+                "Payload of {} bytes does not match the grain size {}.",
+                payload.len(),
+                destination.len()
+            )));
+        }
+        destination[..payload.len()].copy_from_slice(payload);
+        destination[payload.len()..].fill(0);
+        let total_slices = access.total_slices();
+        access.commit(total_slices)
+    }
+
+    /// Lets `fill` write the payload of the grain at `index` in place and commits the complete
+    /// grain.
+    ///
+    /// # Panics
+    ///
+    /// If the flow's payloads are in device memory, see [`GrainWriteAccess::payload_mut`].
+    pub fn write_grain_with(&mut self, index: u64, fill: impl FnOnce(&mut [u8])) -> Result<()> {
+        let mut access = self.open_grain(index)?;
+        fill(access.payload_mut());
+        let total_slices = access.total_slices();
+        access.commit(total_slices)
+    }
+
+    /// Same as [`GrainWriter::open_grain`], with per-access options.
+    pub fn open_grain_with_options<'a>(
+        &'a mut self,
+        index: u64,
+        options: &AccessOptions,
+    ) -> Result<GrainWriteAccess<'a>> {
+        options.warn_unsupported("open_grain");
+        self.open_grain(index)
+    }
+
+    /// Resumes a grain this writer opened and [detached](GrainWriteAccess::detach), to commit or
+    /// cancel it.
+    pub fn resume_grain<'a>(&'a mut self, grain: DetachedGrain) -> Result<GrainWriteAccess<'a>> {
+        if grain.writer() != self.writer {
+            return Err(Error::Other(format!(
+                "Grain {} was not opened by the writer of flow {}.",
+                grain.index(),
+                self.id
+            )));
+        }
+        Ok(GrainWriteAccess::new(
+            self.context.clone(),
+            self.writer,
+            self.id,
+            self.metrics.clone(),
+            self.payload_location,
+            grain.grain_info(),
+            grain.payload_ptr(),
+        ))
+    }
+
     fn destroy_inner(&mut self) -> Result<()> {
         if self.writer.is_null() {
             return Err(Error::InvalidArg);
@@ -64,11 +209,18 @@ impl GrainWriter {
         let mut writer = std::ptr::null_mut();
         std::mem::swap(&mut self.writer, &mut writer);
 
-        Error::from_status(unsafe {
-            self.context
-                .api
-                .release_flow_writer(self.context.instance, writer)
-        })
+        // A cleanup guard may have released it already.
+        let result = if self.context.open_handles.writer_closing(writer) {
+            Error::from_status(ffi_call!("mxlReleaseFlowWriter", flow = self.id; unsafe {
+                self.context
+                    .api
+                    .release_flow_writer(self.context.instance, writer)
+            }))
+        } else {
+            Ok(())
+        };
+        self.context.flow_writer_released(&self.id);
+        result
     }
 }
 
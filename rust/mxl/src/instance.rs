@@ -1,9 +1,22 @@
 // SPDX-FileCopyrightText: 2025 2025 Contributors to the Media eXchange Layer project.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{ffi::CString, sync::Arc};
+use std::{
+    ffi::CString,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
-use crate::{Error, FlowConfigInfo, FlowReader, FlowWriter, Result, api::MxlApiHandle};
+use crate::{
+    Error, FlowConfigInfo, FlowId, FlowOptions, FlowReader, FlowWriter, InstanceOptions, MxlDomain,
+    Result,
+    api::MxlApiHandle,
+    flowdef::FlowDef,
+    instrument::ffi_call,
+    leaks::{LiveObject, LiveObjects},
+};
 
 /// This struct stores the context that is shared by all objects.
 /// It is separated out from `MxlInstance` so that it can be cloned
@@ -12,6 +25,22 @@ use crate::{Error, FlowConfigInfo, FlowReader, FlowWriter, Result, api::MxlApiHa
 pub(crate) struct InstanceContext {
     pub(crate) api: MxlApiHandle,
     pub(crate) instance: mxl_sys::Instance,
+    pub(crate) read_only: bool,
+    /// See [`InstanceOptions::epoch_offset`].
+    pub(crate) epoch_offset: i64,
+    pub(crate) domain: MxlDomain,
+    /// See [`MxlInstance::set_quota`], `0` without a quota.
+    pub(crate) quota: AtomicU64,
+    /// Held from the quota check to the creation of the flow, so creators sharing the instance
+    /// cannot both pass the check.
+    pub(crate) quota_lock: std::sync::Mutex<()>,
+    /// See [`InstanceOptions::track_live_objects`], `None` without tracking.
+    pub(crate) live_objects: Option<Arc<LiveObjects>>,
+    /// Released by a [`crate::CleanupGuard`] when the process terminates.
+    pub(crate) open_handles: crate::cleanup::OpenHandles,
+    /// See [`MxlInstance::set_nmos_node`].
+    #[cfg(feature = "nmos")]
+    pub(crate) nmos: std::sync::Mutex<Option<Arc<crate::nmos::NmosNode>>>,
 }
 
 // Allow sharing the context across threads and tasks freely.
@@ -21,44 +50,106 @@ unsafe impl Send for InstanceContext {}
 unsafe impl Sync for InstanceContext {}
 
 impl InstanceContext {
+    #[cfg(feature = "nmos")]
+    pub(crate) fn nmos_node(&self) -> Option<Arc<crate::nmos::NmosNode>> {
+        self.nmos
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Called when a writer of `flow_id` is released.
+    #[cfg_attr(not(feature = "nmos"), allow(unused_variables))]
+    pub(crate) fn flow_writer_released(&self, flow_id: &uuid::Uuid) {
+        #[cfg(feature = "nmos")]
+        if let Some(node) = self.nmos_node() {
+            node.withdraw_flow(flow_id);
+        }
+    }
+
     /// This function forces the destruction of the MXL instance.
     /// It is meant mainly for testing purposes.
     pub fn destroy(mut self) -> Result<()> {
-        unsafe {
-            let mut instance = std::ptr::null_mut();
-            std::mem::swap(&mut self.instance, &mut instance);
-            Error::from_status(self.api.destroy_instance(instance))
-        }
+        let mut instance = std::ptr::null_mut();
+        std::mem::swap(&mut self.instance, &mut instance);
+        Error::from_status(ffi_call!("mxlDestroyInstance"; unsafe {
+            self.api.destroy_instance(instance)
+        }))
     }
 }
 
 impl Drop for InstanceContext {
     fn drop(&mut self) {
         if !self.instance.is_null() {
-            unsafe { self.api.destroy_instance(self.instance) };
+            ffi_call!("mxlDestroyInstance"; unsafe { self.api.destroy_instance(self.instance) });
         }
     }
 }
 
 pub(crate) fn create_flow_reader(
     context: &Arc<InstanceContext>,
-    flow_id: &str,
+    flow_id: FlowId,
 ) -> Result<FlowReader> {
-    let flow_id = CString::new(flow_id)?;
+    let flow_id_c = CString::new(flow_id.to_string())?;
     let options = CString::new("")?;
     let mut reader: mxl_sys::FlowReader = std::ptr::null_mut();
-    unsafe {
-        Error::from_status(context.api.create_flow_reader(
+    Error::from_status(ffi_call!("mxlCreateFlowReader", flow = flow_id; unsafe {
+        context.api.create_flow_reader(
             context.instance,
-            flow_id.as_ptr(),
+            flow_id_c.as_ptr(),
             options.as_ptr(),
             &mut reader,
-        ))?;
-    }
+        )
+    }))?;
     if reader.is_null() {
         return Err(Error::Other("Failed to create flow reader.".to_string()));
     }
-    Ok(FlowReader::new(context.clone(), reader))
+    Ok(FlowReader::new(context.clone(), reader, flow_id.uuid()))
+}
+
+pub(crate) fn get_flow_def(context: &InstanceContext, flow_id: FlowId) -> Result<String> {
+    let flow_id_c = CString::new(flow_id.to_string())?;
+    const INITIAL_BUFFER_SIZE: usize = 4096;
+    let mut buffer: Vec<u8> = vec![0; INITIAL_BUFFER_SIZE];
+    let mut buffer_size = INITIAL_BUFFER_SIZE;
+
+    let status = ffi_call!("mxlGetFlowDef", flow = flow_id; unsafe {
+        context.api.get_flow_def(
+            context.instance,
+            flow_id_c.as_ptr(),
+            buffer.as_mut_ptr() as *mut std::os::raw::c_char,
+            &mut buffer_size,
+        )
+    });
+
+    if status == mxl_sys::MXL_ERR_INVALID_ARG && buffer_size > INITIAL_BUFFER_SIZE {
+        buffer = vec![0; buffer_size];
+        Error::from_status(ffi_call!("mxlGetFlowDef", flow = flow_id; unsafe {
+            context.api.get_flow_def(
+                context.instance,
+                flow_id_c.as_ptr(),
+                buffer.as_mut_ptr() as *mut std::os::raw::c_char,
+                &mut buffer_size,
+            )
+        }))?;
+    } else {
+        Error::from_status(status)?;
+    }
+
+    flow_def_from_buffer(buffer, buffer_size)
+}
+
+/// Turns the buffer filled by `mxlGetFlowDef` into the flow definition. `size` is the size the
+/// library reported, including the terminating NUL, and is not trusted to fit the buffer. The
+/// definition ends at the first NUL as it would for C consumers.
+pub(crate) fn flow_def_from_buffer(mut buffer: Vec<u8>, size: usize) -> Result<String> {
+    buffer.truncate(size);
+    if let Some(nul) = buffer.iter().position(|byte| *byte == 0) {
+        buffer.truncate(nul);
+    }
+
+    String::from_utf8(buffer)
+        .map_err(|_| Error::Other("Invalid UTF-8 in flow definition".to_string()))
 }
 
 #[derive(Clone)]
@@ -66,99 +157,327 @@ pub struct MxlInstance {
     context: Arc<InstanceContext>,
 }
 
+impl std::fmt::Debug for MxlInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MxlInstance")
+            .field("domain", &self.context.domain.path())
+            .field("read_only", &self.context.read_only)
+            .finish_non_exhaustive()
+    }
+}
+
 impl MxlInstance {
+    pub(crate) fn from_context(context: Arc<InstanceContext>) -> Self {
+        Self { context }
+    }
+
+    pub(crate) fn context(&self) -> &Arc<InstanceContext> {
+        &self.context
+    }
+
     pub fn new(api: MxlApiHandle, domain: &str, options: &str) -> Result<Self> {
-        let instance = unsafe {
-            api.create_instance(
-                CString::new(domain)?.as_ptr(),
-                CString::new(options)?.as_ptr(),
-            )
-        };
+        Self::create(api, domain, options, false, 0, false)
+    }
+
+    /// Opens the domain for monitoring only.
+    ///
+    /// Readers always map flows read-only, so the only operations that can disturb a domain are
+    /// creating flow writers and garbage-collecting flows. Both fail with
+    /// [`Error::PermissionDenied`] on a read-only instance. Note that readers still try to
+    /// update the flow's last read time; that is silently skipped on read-only volumes.
+    pub fn open_read_only(api: MxlApiHandle, domain: &str) -> Result<Self> {
+        Self::create(api, domain, "", true, 0, false)
+    }
+
+    fn create(
+        api: MxlApiHandle,
+        domain: &str,
+        options: &str,
+        read_only: bool,
+        epoch_offset: i64,
+        track_live_objects: bool,
+    ) -> Result<Self> {
+        let domain_c = CString::new(domain)?;
+        let options = CString::new(options)?;
+        let instance = ffi_call!("mxlCreateInstance", domain = domain; unsafe {
+            api.create_instance(domain_c.as_ptr(), options.as_ptr())
+        });
         if instance.is_null() {
-            Err(Error::Other("Failed to create MXL instance.".to_string()))
+            Err(Error::Other(format!(
+                "Failed to create MXL instance for domain \"{domain}\". Set MXL_LOG_LEVEL=debug \
+                 to see the library's diagnostics."
+            )))
         } else {
-            let context = Arc::new(InstanceContext { api, instance });
-            Ok(Self { context })
+            let context = Arc::new(InstanceContext {
+                api,
+                instance,
+                read_only,
+                epoch_offset,
+                domain: MxlDomain::new(domain),
+                quota: AtomicU64::new(0),
+                quota_lock: std::sync::Mutex::new(()),
+                live_objects: track_live_objects.then(Default::default),
+                open_handles: Default::default(),
+                #[cfg(feature = "nmos")]
+                nmos: std::sync::Mutex::new(None),
+            });
+            let instance = Self { context };
+            match instance.is_domain_on_tmpfs() {
+                Ok(false) => tracing::warn!(
+                    "MXL domain \"{domain}\" is not on a tmpfs, flows are written back to storage."
+                ),
+                Ok(true) => {}
+                Err(error) => {
+                    tracing::warn!(
+                        "Failed to check the file system of MXL domain \"{domain}\": {error}"
+                    )
+                }
+            }
+            Ok(instance)
         }
     }
 
-    pub fn create_flow_reader(&self, flow_id: &str) -> Result<FlowReader> {
-        create_flow_reader(&self.context, flow_id)
+    pub fn is_read_only(&self) -> bool {
+        self.context.read_only
+    }
+
+    /// Same as [`MxlInstance::new`], with typed options.
+    pub fn with_options(
+        api: MxlApiHandle,
+        domain: &str,
+        options: &InstanceOptions,
+    ) -> Result<Self> {
+        Self::create(
+            api,
+            domain,
+            options.to_json_string()?.as_str(),
+            options.is_read_only(),
+            options.get_epoch_offset(),
+            options.get_track_live_objects(),
+        )
+    }
+
+    pub fn create_flow_reader(
+        &self,
+        flow_id: impl TryInto<FlowId, Error: Into<Error>>,
+    ) -> Result<FlowReader> {
+        create_flow_reader(&self.context, flow_id.try_into().map_err(Into::into)?)
+    }
+
+    /// File system view of the instance's domain.
+    pub fn domain(&self) -> &MxlDomain {
+        &self.context.domain
+    }
+
+    /// Limits the memory the flows of the domain may use, as reported by [`MxlDomain::usage`].
+    /// `None` removes the limit.
+    ///
+    /// The quota is checked by [`MxlInstance::create_flow_writer`] of this instance and its clones
+    /// before the flow is created: creating a flow fails with [`Error::QuotaExceeded`] if the
+    /// domain plus the size of the new flow, see [`MxlDomain::projected_flow_size`], is past the
+    /// quota. Opening existing flows and other processes are not affected, so flows created by
+    /// other processes at the same time can still take the domain past it.
+    pub fn set_quota(&self, quota: Option<u64>) {
+        self.context
+            .quota
+            .store(quota.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Registers the flows created by this instance and its clones with an NMOS registry from now
+    /// on, see [`crate::nmos`]. `None` detaches the node, which withdraws everything it registered.
+    #[cfg(feature = "nmos")]
+    pub fn set_nmos_node(&self, node: Option<crate::nmos::NmosNode>) {
+        *self
+            .context
+            .nmos
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = node.map(Arc::new);
+    }
+
+    #[cfg(feature = "nmos")]
+    pub fn nmos_node(&self) -> Option<Arc<crate::nmos::NmosNode>> {
+        self.context.nmos_node()
+    }
+
+    pub fn quota(&self) -> Option<u64> {
+        match self.context.quota.load(Ordering::Relaxed) {
+            0 => None,
+            quota => Some(quota),
+        }
     }
 
     pub fn create_flow_writer(
         &self,
         flow_def: &str,
         options: Option<&str>,
+    ) -> Result<(FlowWriter, FlowConfigInfo, bool)> {
+        let created = self.create_flow_writer_within_quota(flow_def, options)?;
+        #[cfg(feature = "nmos")]
+        if let Some(node) = self.nmos_node() {
+            let (writer, _, was_created) = &created;
+            // Flows created elsewhere are registered by their creator.
+            if !*was_created {
+                node.add_writer(&writer.flow_id());
+            } else if let Err(error) = node.register_flow(flow_def) {
+                tracing::warn!(
+                    "Failed to register flow {} with NMOS: {error}",
+                    writer.flow_id()
+                );
+            }
+        }
+        Ok(created)
+    }
+
+    fn create_flow_writer_within_quota(
+        &self,
+        flow_def: &str,
+        options: Option<&str>,
+    ) -> Result<(FlowWriter, FlowConfigInfo, bool)> {
+        if self.context.read_only {
+            return Err(Error::PermissionDenied);
+        }
+        let Some(quota) = self.quota() else {
+            return self.create_flow_writer_unchecked(flow_def, options);
+        };
+        let _checked = self
+            .context
+            .quota_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let exists = serde_json::from_str::<serde_json::Value>(flow_def)
+            .ok()
+            .and_then(|flow_def| uuid::Uuid::parse_str(flow_def["id"].as_str()?).ok())
+            .is_some_and(|flow_id| self.context.domain.flow_directory(&flow_id).exists());
+        if !exists {
+            let used = self.context.domain.usage()?.total;
+            let flow = self.context.domain.projected_flow_size(flow_def, options)?;
+            if used.saturating_add(flow) > quota {
+                return Err(Error::QuotaExceeded { used, flow, quota });
+            }
+        }
+        self.create_flow_writer_unchecked(flow_def, options)
+    }
+
+    fn create_flow_writer_unchecked(
+        &self,
+        flow_def: &str,
+        options: Option<&str>,
     ) -> Result<(FlowWriter, FlowConfigInfo, bool)> {
         let flow_def = CString::new(flow_def)?;
         let options = options.map(CString::new).transpose()?;
         let mut writer: mxl_sys::FlowWriter = std::ptr::null_mut();
         let mut info_unsafe = std::mem::MaybeUninit::<mxl_sys::FlowConfigInfo>::uninit();
         let mut was_created = false;
-        unsafe {
-            Error::from_status(self.context.api.create_flow_writer(
+        Error::from_status(ffi_call!("mxlCreateFlowWriter"; unsafe {
+            self.context.api.create_flow_writer(
                 self.context.instance,
                 flow_def.as_ptr(),
-                options.map(|cs| cs.as_ptr()).unwrap_or(std::ptr::null()),
+                options
+                    .as_ref()
+                    .map(|cs| cs.as_ptr())
+                    .unwrap_or(std::ptr::null()),
                 &mut writer,
                 info_unsafe.as_mut_ptr(),
                 &mut was_created,
-            ))?;
-        }
+            )
+        }))?;
         if writer.is_null() {
             return Err(Error::Other("Failed to create flow writer.".to_string()));
         }
 
-        let info = unsafe { info_unsafe.assume_init() };
-
-        Ok((
-            FlowWriter::new(
-                self.context.clone(),
-                writer,
-                uuid::Uuid::from_bytes(info.common.id),
-            ),
-            FlowConfigInfo { value: info },
-            was_created,
-        ))
+        let info = FlowConfigInfo {
+            value: unsafe { info_unsafe.assume_init() },
+        };
+        let writer = FlowWriter::new(self.context.clone(), writer, &info)?;
+        Ok((writer, info, was_created))
     }
 
-    pub fn get_flow_def(&self, flow_id: &str) -> Result<String> {
-        let flow_id = CString::new(flow_id)?;
-        const INITIAL_BUFFER_SIZE: usize = 4096;
-        let mut buffer: Vec<u8> = vec![0; INITIAL_BUFFER_SIZE];
-        let mut buffer_size = INITIAL_BUFFER_SIZE;
-
-        let status = unsafe {
-            self.context.api.get_flow_def(
-                self.context.instance,
-                flow_id.as_ptr(),
-                buffer.as_mut_ptr() as *mut std::os::raw::c_char,
-                &mut buffer_size,
+    /// Same as [`MxlInstance::create_flow_writer`], with typed options.
+    pub fn create_flow_writer_with_options(
+        &self,
+        flow_def: &str,
+        options: &FlowOptions,
+    ) -> Result<(FlowWriter, FlowConfigInfo, bool)> {
+        let (writer, info, was_created) =
+            self.create_flow_writer(flow_def, Some(options.to_json_string()?.as_str()))?;
+        if let Some(requested) = options.requested_payload_location() {
+            let location = info.common().payload_location();
+            if was_created && location != requested {
+                // Library versions without device memory support create such flows on the host.
+                tracing::warn!(
+                    "Requested {requested:?} payloads for flow {}, the library created {location:?}",
+                    writer.flow_id()
+                );
+            }
+        }
+        if was_created
+            && let Err(error) = self.context.domain.set_flow_permissions(
+                &writer.flow_id(),
+                options.mode(),
+                options.owner(),
+                options.group(),
             )
-        };
-
-        if status == mxl_sys::MXL_ERR_INVALID_ARG && buffer_size > INITIAL_BUFFER_SIZE {
-            buffer = vec![0; buffer_size];
-            unsafe {
-                Error::from_status(self.context.api.get_flow_def(
-                    self.context.instance,
-                    flow_id.as_ptr(),
-                    buffer.as_mut_ptr() as *mut std::os::raw::c_char,
-                    &mut buffer_size,
-                ))?;
+        {
+            // Readers must not find the flow with only part of the permissions applied. Releasing
+            // the writer that created it deletes it, unless another writer opened it meanwhile.
+            let flow_id = writer.flow_id();
+            drop(writer);
+            if self.context.domain.flow_directory(&flow_id).exists() {
+                tracing::warn!(
+                    "Flow {flow_id} is kept without its permissions, it has other writers."
+                );
             }
-        } else {
-            Error::from_status(status)?;
+            return Err(error);
         }
+        Ok((writer, info, was_created))
+    }
 
-        if buffer_size > 0 && buffer[buffer_size - 1] == 0 {
-            buffer_size -= 1;
-        }
-        buffer.truncate(buffer_size);
+    /// Same as [`MxlInstance::create_flow_writer`] for a typed flow definition. Also returns the
+    /// definition the flow has, which is not `flow_def` if the flow existed already.
+    pub fn create_flow_writer_typed(
+        &self,
+        flow_def: &FlowDef,
+        options: Option<&FlowOptions>,
+    ) -> Result<(FlowWriter, FlowConfigInfo, FlowDef, bool)> {
+        // Through a `Value`, which keeps a single `format` member of the flattened details.
+        let json = serde_json::to_value(flow_def)
+            .map_err(|error| {
+                Error::Other(format!(
+                    "Cannot serialize the definition of flow {}: {error}",
+                    flow_def.id
+                ))
+            })?
+            .to_string();
+        let (writer, info, was_created) = match options {
+            Some(options) => self.create_flow_writer_with_options(&json, options)?,
+            None => self.create_flow_writer(&json, None)?,
+        };
+        let flow_def = if was_created {
+            flow_def.clone()
+        } else {
+            self.get_flow_def_typed(writer.flow_id())?
+        };
+        Ok((writer, info, flow_def, was_created))
+    }
+
+    pub fn get_flow_def(
+        &self,
+        flow_id: impl TryInto<FlowId, Error: Into<Error>>,
+    ) -> Result<String> {
+        get_flow_def(&self.context, flow_id.try_into().map_err(Into::into)?)
+    }
 
-        String::from_utf8(buffer)
-            .map_err(|_| Error::Other("Invalid UTF-8 in flow definition".to_string()))
+    /// Same as [`MxlInstance::get_flow_def`], parsed.
+    pub fn get_flow_def_typed(
+        &self,
+        flow_id: impl TryInto<FlowId, Error: Into<Error>>,
+    ) -> Result<FlowDef> {
+        let flow_id = flow_id.try_into().map_err(Into::into)?;
+        serde_json::from_str(&self.get_flow_def(flow_id)?).map_err(|error| {
+            Error::Other(format!(
+                "Invalid flow definition of flow {flow_id}: {error}"
+            ))
+        })
     }
 
     /// Garbage-collect orphan flow directories in the MXL domain.
@@ -169,17 +488,54 @@ impl MxlInstance {
     /// happens when a writer process exits or crashes without unwinding its
     /// destructors (SIGKILL, segfault, host reboot).
     pub fn garbage_collect_flows(&self) -> Result<()> {
-        unsafe {
-            Error::from_status(
-                self.context
-                    .api
-                    .garbage_collect_flows(self.context.instance),
-            )
+        if self.context.read_only {
+            return Err(Error::PermissionDenied);
         }
+        Error::from_status(ffi_call!("mxlGarbageCollectFlows"; unsafe {
+            self.context
+                .api
+                .garbage_collect_flows(self.context.instance)
+        }))
     }
 
+    /// Whether the domain is on a tmpfs, as the MXL library sees it. Flows are only shared at
+    /// memory speed there; anywhere else the kernel writes the rings back to storage. Always
+    /// `false` on platforms other than Linux.
+    pub fn is_domain_on_tmpfs(&self) -> Result<bool> {
+        let path = self.context.domain.c_path()?;
+        let mut is_tmp_fs = false;
+        Error::from_status(ffi_call!("mxlIsTmpFs"; unsafe {
+            self.context.api.is_tmp_fs(path.as_ptr(), &mut is_tmp_fs)
+        }))?;
+        Ok(is_tmp_fs)
+    }
+
+    /// Offset of the house epoch from the ST 2059 epoch in nanoseconds, see
+    /// [`InstanceOptions::epoch_offset`].
+    pub fn epoch_offset(&self) -> i64 {
+        self.context.epoch_offset
+    }
+
+    /// Shifts a TAI timestamp into the time base the library's index functions use.
+    fn to_library_time(&self, timestamp: u64) -> Result<u64> {
+        u64::try_from(timestamp as i128 - self.context.epoch_offset as i128).map_err(|_| {
+            Error::Other(format!(
+                "Timestamp {timestamp} is before the epoch offset {}.",
+                self.context.epoch_offset
+            ))
+        })
+    }
+
+    /// Returns 0 for invalid rates, as the library does, or if the current time is before the
+    /// epoch offset.
     pub fn get_current_index(&self, rational: &mxl_sys::Rational) -> u64 {
-        unsafe { self.context.api.get_current_index(rational) }
+        if self.context.epoch_offset == 0 {
+            return ffi_call!("mxlGetCurrentIndex"; unsafe {
+                self.context.api.get_current_index(rational)
+            });
+        }
+        self.timestamp_to_index(self.get_time(), rational)
+            .unwrap_or(0)
     }
 
     pub fn get_duration_until_index(
@@ -187,7 +543,16 @@ impl MxlInstance {
         index: u64,
         rate: &mxl_sys::Rational,
     ) -> Result<std::time::Duration> {
-        let duration_ns = unsafe { self.context.api.get_ns_until_index(index, rate) };
+        let duration_ns = if self.context.epoch_offset == 0 {
+            ffi_call!("mxlGetNsUntilIndex", index = index; unsafe {
+                self.context.api.get_ns_until_index(index, rate)
+            })
+        } else {
+            match self.index_to_timestamp(index, rate) {
+                Ok(timestamp) => timestamp.saturating_sub(self.get_time()),
+                Err(_) => u64::MAX,
+            }
+        };
         if duration_ns == u64::MAX {
             Err(Error::Other(format!(
                 "Failed to get duration until index, invalid rate {}/{}.",
@@ -200,7 +565,10 @@ impl MxlInstance {
 
     /// TODO: Make timestamp a strong type.
     pub fn timestamp_to_index(&self, timestamp: u64, rate: &mxl_sys::Rational) -> Result<u64> {
-        let index = unsafe { self.context.api.timestamp_to_index(rate, timestamp) };
+        let timestamp = self.to_library_time(timestamp)?;
+        let index = ffi_call!("mxlTimestampToIndex", timestamp = timestamp; unsafe {
+            self.context.api.timestamp_to_index(rate, timestamp)
+        });
         if index == u64::MAX {
             Err(Error::Other(format!(
                 "Failed to convert timestamp to index, invalid rate {}/{}.",
@@ -212,32 +580,76 @@ impl MxlInstance {
     }
 
     pub fn index_to_timestamp(&self, index: u64, rate: &mxl_sys::Rational) -> Result<u64> {
-        let timestamp = unsafe { self.context.api.index_to_timestamp(rate, index) };
+        let timestamp = ffi_call!("mxlIndexToTimestamp", index = index; unsafe {
+            self.context.api.index_to_timestamp(rate, index)
+        });
         if timestamp == u64::MAX {
             Err(Error::Other(format!(
                 "Failed to convert index to timestamp, invalid rate {}/{}.",
                 rate.numerator, rate.denominator
             )))
         } else {
-            Ok(timestamp)
+            u64::try_from(timestamp as i128 + self.context.epoch_offset as i128)
+                .map_err(|_| Error::Other(format!("Timestamp of index {index} is out of range.")))
         }
     }
 
     pub fn sleep_for(&self, duration: std::time::Duration) {
-        unsafe { self.context.api.sleep_for_ns(duration.as_nanos() as u64) }
+        let duration_ns = duration.as_nanos() as u64;
+        ffi_call!("mxlSleepForNs", requested_ns = duration_ns; unsafe {
+            self.context.api.sleep_for_ns(duration_ns)
+        })
+    }
+
+    /// Sleeps until `index` is the current index, combining
+    /// [`MxlInstance::get_duration_until_index`] and [`MxlInstance::sleep_for`].
+    ///
+    /// Returns how late the caller was: zero if it had to wait, or the time elapsed since `index`
+    /// began if that is already in the past, in which case it returns immediately.
+    pub fn sleep_until_index(
+        &self,
+        index: u64,
+        rate: &mxl_sys::Rational,
+    ) -> Result<std::time::Duration> {
+        let due = self.index_to_timestamp(index, rate)?;
+        let now = self.get_time();
+        if now >= due {
+            return Ok(std::time::Duration::from_nanos(now - due));
+        }
+        self.sleep_for(std::time::Duration::from_nanos(due - now));
+        Ok(std::time::Duration::ZERO)
     }
 
     pub fn get_time(&self) -> u64 {
-        unsafe { self.context.api.get_time() }
+        ffi_call!("mxlGetTime"; unsafe { self.context.api.get_time() })
     }
 
     /// This function forces the destruction of the MXL instance.
     /// It is meant mainly for testing purposes.
     /// The caller must ensure that no other objects are using the MXL instance when this function
     /// is called.
+    /// With [`InstanceOptions::track_live_objects`], the objects still using the instance are
+    /// logged when it fails.
     pub fn destroy(self) -> Result<()> {
-        let context = Arc::into_inner(self.context)
-            .ok_or_else(|| Error::Other("Instance is still in use.".to_string()))?;
+        let live_objects = self.live_objects();
+        let context = Arc::into_inner(self.context).ok_or_else(|| {
+            for object in &live_objects {
+                tracing::error!("MXL instance still used by {object}");
+            }
+            Error::Other("Instance is still in use.".to_string())
+        })?;
         context.destroy()
     }
+
+    /// The readers, writers and write sessions alive in the instance, oldest first. Always empty
+    /// unless the instance was created with [`InstanceOptions::track_live_objects`].
+    ///
+    /// Clones of the instance keep it alive too, but are not listed.
+    pub fn live_objects(&self) -> Vec<LiveObject> {
+        self.context
+            .live_objects
+            .as_ref()
+            .map(|live_objects| live_objects.snapshot())
+            .unwrap_or_default()
+    }
 }
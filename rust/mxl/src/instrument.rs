@@ -0,0 +1,174 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Calls into the MXL library.
+///
+/// With the `instrument` feature, the call runs inside a `TRACE` span named after the C function,
+/// with target `mxl::ffi`, the given fields and the call's `duration_ns`. `flow` is recorded as
+/// `flow_id`. Without the feature, this is the bare call. Field values must be cheap,
+/// side-effect free expressions.
+#[cfg(feature = "instrument")]
+macro_rules! ffi_call {
+    ($name:literal, flow = $flow:expr $(, $field:ident = $value:expr)* ; $call:expr) => {
+        $crate::instrument::timed(
+            &tracing::trace_span!(
+                target: "mxl::ffi",
+                $name,
+                flow_id = %$flow,
+                $($field = $value,)*
+                duration_ns = tracing::field::Empty
+            ),
+            || $call,
+        )
+    };
+    ($name:literal $(, $field:ident = $value:expr)* ; $call:expr) => {
+        $crate::instrument::timed(
+            &tracing::trace_span!(
+                target: "mxl::ffi",
+                $name,
+                $($field = $value,)*
+                duration_ns = tracing::field::Empty
+            ),
+            || $call,
+        )
+    };
+}
+
+#[cfg(not(feature = "instrument"))]
+macro_rules! ffi_call {
+    ($name:literal $(, $field:ident = $value:expr)* ; $call:expr) => {{
+        $(let _ = &$value;)*
+        $call
+    }};
+}
+
+pub(crate) use ffi_call;
+
+#[cfg(feature = "instrument")]
+pub(crate) fn timed<T>(span: &tracing::Span, call: impl FnOnce() -> T) -> T {
+    let _entered = span.enter();
+    let start = std::time::Instant::now();
+    let result = call();
+    span.record(
+        "duration_ns",
+        u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX),
+    );
+    result
+}
+
+/// Grain or sample counts, selecting the metrics of a reader or writer.
+#[derive(Clone, Copy)]
+pub(crate) enum Media {
+    Grains,
+    Samples,
+}
+
+/// Metrics of a reader, registered on creation, see [`crate::metrics`].
+#[cfg(feature = "metrics")]
+pub(crate) struct ReaderMetrics {
+    read: ::metrics::Counter,
+    head_lag: ::metrics::Gauge,
+    catch_ups: ::metrics::Counter,
+}
+
+#[cfg(feature = "metrics")]
+impl ReaderMetrics {
+    pub(crate) fn new(flow_id: &uuid::Uuid, media: Media) -> Self {
+        use crate::metrics::*;
+
+        let flow_id = flow_id.to_string();
+        let read = match media {
+            Media::Grains => GRAINS_READ,
+            Media::Samples => SAMPLES_READ,
+        };
+        Self {
+            read: ::metrics::counter!(read, FLOW_ID_LABEL => flow_id.clone()),
+            head_lag: ::metrics::gauge!(READER_HEAD_LAG, FLOW_ID_LABEL => flow_id.clone()),
+            catch_ups: ::metrics::counter!(READER_CATCH_UPS, FLOW_ID_LABEL => flow_id),
+        }
+    }
+
+    /// Records a read of `count` grains or samples at `index`. `head` is only queried for
+    /// successful reads.
+    pub(crate) fn record<T>(
+        &self,
+        result: &crate::Result<T>,
+        index: u64,
+        count: usize,
+        head: impl FnOnce() -> crate::Result<u64>,
+    ) {
+        match result {
+            Ok(_) => {
+                self.read.increment(count as u64);
+                if let Ok(head) = head() {
+                    self.head_lag.set(head.saturating_sub(index) as f64);
+                }
+            }
+            Err(crate::Error::OutOfRangeTooLate) => self.catch_ups.increment(1),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Metrics of a writer, registered on creation, see [`crate::metrics`].
+#[cfg(feature = "metrics")]
+#[derive(Clone)]
+pub(crate) struct WriterMetrics {
+    written: ::metrics::Counter,
+    head_index: ::metrics::Gauge,
+}
+
+#[cfg(feature = "metrics")]
+impl WriterMetrics {
+    pub(crate) fn new(flow_id: &uuid::Uuid, media: Media) -> Self {
+        use crate::metrics::*;
+
+        let flow_id = flow_id.to_string();
+        let written = match media {
+            Media::Grains => GRAINS_WRITTEN,
+            Media::Samples => SAMPLES_WRITTEN,
+        };
+        Self {
+            written: ::metrics::counter!(written, FLOW_ID_LABEL => flow_id.clone()),
+            head_index: ::metrics::gauge!(WRITER_HEAD_INDEX, FLOW_ID_LABEL => flow_id),
+        }
+    }
+
+    /// Records the commit of `count` grains or samples at `index`.
+    pub(crate) fn record(&self, index: u64, count: usize) {
+        self.written.increment(count as u64);
+        self.head_index.set(index as f64);
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) struct ReaderMetrics;
+
+#[cfg(not(feature = "metrics"))]
+impl ReaderMetrics {
+    pub(crate) fn new(_flow_id: &uuid::Uuid, _media: Media) -> Self {
+        Self
+    }
+
+    pub(crate) fn record<T>(
+        &self,
+        _result: &crate::Result<T>,
+        _index: u64,
+        _count: usize,
+        _head: impl FnOnce() -> crate::Result<u64>,
+    ) {
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+#[derive(Clone)]
+pub(crate) struct WriterMetrics;
+
+#[cfg(not(feature = "metrics"))]
+impl WriterMetrics {
+    pub(crate) fn new(_flow_id: &uuid::Uuid, _media: Media) -> Self {
+        Self
+    }
+
+    pub(crate) fn record(&self, _index: u64, _count: usize) {}
+}
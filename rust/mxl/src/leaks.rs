@@ -0,0 +1,104 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Registry of the readers, writers and write sessions alive in an instance, for finding the
+//! objects that make [`crate::MxlInstance::destroy`] fail with "Instance is still in use".
+//!
+//! Tracking is enabled with [`crate::InstanceOptions::track_live_objects`]. Every tracked object
+//! records a backtrace when it is created, so it is meant for debugging sessions rather than
+//! production.
+
+use std::{
+    backtrace::Backtrace,
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use uuid::Uuid;
+
+use crate::instance::InstanceContext;
+
+/// Kind of a [`LiveObject`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LiveObjectKind {
+    FlowReader,
+    FlowWriter,
+    GrainReader,
+    GrainWriter,
+    SamplesReader,
+    SamplesWriter,
+    GrainWriteAccess,
+    SamplesWriteAccess,
+}
+
+/// A reader, writer or write session that keeps its instance alive, see
+/// [`crate::MxlInstance::live_objects`].
+#[derive(Debug, Clone)]
+pub struct LiveObject {
+    pub kind: LiveObjectKind,
+    pub flow_id: Uuid,
+    pub created_at: SystemTime,
+    /// Where the object was created.
+    pub backtrace: Arc<Backtrace>,
+}
+
+impl std::fmt::Display for LiveObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:?} of flow {} created at:", self.kind, self.flow_id)?;
+        write!(f, "{}", self.backtrace)
+    }
+}
+
+/// Objects alive in an instance with tracking enabled, in creation order.
+#[derive(Default)]
+pub(crate) struct LiveObjects {
+    next_id: AtomicU64,
+    objects: Mutex<BTreeMap<u64, LiveObject>>,
+}
+
+impl LiveObjects {
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, LiveObject>> {
+        self.objects
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<LiveObject> {
+        self.lock().values().cloned().collect()
+    }
+}
+
+/// Entry of an object in the registry of its instance, removed when the object is dropped. Does
+/// nothing if tracking is disabled.
+pub(crate) struct Tracked(Option<(Arc<LiveObjects>, u64)>);
+
+impl Tracked {
+    pub(crate) fn new(context: &InstanceContext, kind: LiveObjectKind, flow_id: Uuid) -> Self {
+        let Some(live_objects) = &context.live_objects else {
+            return Self(None);
+        };
+        let id = live_objects.next_id.fetch_add(1, Ordering::Relaxed);
+        live_objects.lock().insert(
+            id,
+            LiveObject {
+                kind,
+                flow_id,
+                created_at: SystemTime::now(),
+                backtrace: Arc::new(Backtrace::force_capture()),
+            },
+        );
+        Self(Some((live_objects.clone(), id)))
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        if let Some((live_objects, id)) = &self.0 {
+            live_objects.lock().remove(id);
+        }
+    }
+}
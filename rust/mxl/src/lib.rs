@@ -2,23 +2,87 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod api;
+mod cleanup;
+mod clock;
+mod domain;
 mod error;
 mod flow;
 mod grain;
 mod instance;
+mod instrument;
+mod leaks;
+mod notify;
+mod options;
+mod payload;
+mod probe;
+mod rational;
+mod report;
 mod samples;
+mod service;
+mod sync;
+mod tai;
+mod timecode;
 
+pub mod anc;
 pub mod config;
+#[cfg(feature = "crc")]
+pub mod crc;
+#[cfg(feature = "cuda")]
+pub mod cuda;
+pub mod dump;
+pub mod events;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+pub mod generate;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "nmos")]
+pub mod nmos;
+pub mod st2110;
 
-pub use api::{MxlApi, load_api};
-pub use error::{Error, Result};
-pub use flow::{reader::FlowReader, writer::FlowWriter, *};
+pub use api::{MxlApi, MxlApiExt, Version, load_api};
+pub use cleanup::{CleanupAction, CleanupGuard};
+pub use clock::{TimeSource, VirtualClock};
+pub use domain::{DomainUsage, FlowAttachments, FlowUsage, MxlDomain};
+pub use error::{Error, Result, status_name};
+pub use flow::{
+    reader::{FlowReader, TypedReader},
+    writer::FlowWriter,
+    *,
+};
 pub use grain::{
-    data::*, reader::GrainReader, write_access::GrainWriteAccess, writer::GrainWriter,
+    data::*,
+    packets::{
+        DataFraming, DataGrainReader, DataGrainWriter, DataPackets, decode_packets, encode_packets,
+    },
+    reader::GrainReader,
+    write_access::{DetachedGrain, GrainWriteAccess},
+    writer::GrainWriter,
 };
 pub use instance::MxlInstance;
+pub use leaks::{LiveObject, LiveObjectKind};
+pub use notify::{HeadChanges, HeadNotifier};
+pub use options::{AccessOptions, FlowOptions, InstanceOptions};
+pub use payload::{DevicePayload, PayloadLocation};
+pub use probe::{LatencyProbe, PROBE_STAMP_SIZE, read_stamp, stamp_payload};
+pub use rational::{RationalExt, rates};
+pub use report::{LatencyReport, REPORT_SCHEMA_VERSION, ThroughputReport};
 pub const MXL_DATA_FORMAT_GRAIN_SIZE: usize = mxl_sys::MXL_DATA_FORMAT_GRAIN_SIZE as usize;
+pub const MXL_GRAIN_FLAG_INVALID: u32 = mxl_sys::MXL_GRAIN_FLAG_INVALID;
 pub use mxl_sys::Rational;
 pub use samples::{
-    data::*, reader::SamplesReader, write_access::SamplesWriteAccess, writer::SamplesWriter,
+    batcher::{DEFAULT_BATCH_DURATION, SampleBatcher},
+    convert::{I24, PcmSample, float_to_pcm, pcm_to_float},
+    data::*,
+    interleave::{Dither, InterleaveOptions, SampleFormat},
+    reader::SamplesReader,
+    write_access::SamplesWriteAccess,
+    writer::SamplesWriter,
+};
+pub use service::{DEFAULT_STALE_THRESHOLD, FlowEvent, ReaderService};
+pub use sync::{SyncGrainReader, SyncGrainWriter, SyncSamplesReader, SyncSamplesWriter};
+pub use tai::{
+    LeapSecond, LeapSecondTable, set_leap_second_table, set_tai_utc_offset_override, tai_ns_to_utc,
+    tai_utc_offset_at, utc_to_tai_ns,
 };
+pub use timecode::{Timecode, index_to_timecode, timecode_to_index};
@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-flow metrics, recorded through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! Every grain or samples reader and writer registers its metrics in the global recorder when it
+//! is created, labelled with the flow id. The application installs the exporter, for example
+//! Prometheus with `metrics-exporter-prometheus`:
+//!
+//! ```ignore
+//! metrics_exporter_prometheus::PrometheusBuilder::new().install()?;
+//! mxl::metrics::describe();
+//! ```
+//!
+//! Read and write rates are derived from the `_total` counters by the monitoring system, e.g.
+//! `rate(mxl_grains_read_total[1m])`.
+
+use ::metrics::{Unit, describe_counter, describe_gauge};
+
+/// Label holding the flow id of every metric.
+pub const FLOW_ID_LABEL: &str = "flow_id";
+
+pub const GRAINS_READ: &str = "mxl_grains_read_total";
+pub const SAMPLES_READ: &str = "mxl_samples_read_total";
+pub const GRAINS_WRITTEN: &str = "mxl_grains_written_total";
+pub const SAMPLES_WRITTEN: &str = "mxl_samples_written_total";
+/// Distance from the last index read to the flow's head index, in grains or samples.
+pub const READER_HEAD_LAG: &str = "mxl_reader_head_lag";
+/// Reads of an index the ring no longer holds, after which the reader has to catch up.
+pub const READER_CATCH_UPS: &str = "mxl_reader_catch_ups_total";
+/// Index of the last grain or samples committed.
+pub const WRITER_HEAD_INDEX: &str = "mxl_writer_head_index";
+
+/// Registers the unit and description of every metric with the installed recorder.
+pub fn describe() {
+    describe_counter!(GRAINS_READ, Unit::Count, "Grains read from the flow.");
+    describe_counter!(SAMPLES_READ, Unit::Count, "Samples read from the flow.");
+    describe_counter!(GRAINS_WRITTEN, Unit::Count, "Grains written to the flow.");
+    describe_counter!(SAMPLES_WRITTEN, Unit::Count, "Samples written to the flow.");
+    describe_gauge!(
+        READER_HEAD_LAG,
+        Unit::Count,
+        "Grains or samples between the last read and the flow's head."
+    );
+    describe_counter!(
+        READER_CATCH_UPS,
+        Unit::Count,
+        "Reads that were too late because the ring had already moved past the index."
+    );
+    describe_gauge!(
+        WRITER_HEAD_INDEX,
+        Unit::Count,
+        "Index of the last grain or samples committed."
+    );
+}
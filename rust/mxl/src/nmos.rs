@@ -0,0 +1,473 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Registration of MXL flows with an NMOS IS-04 registry.
+//!
+//! MXL flow definitions are IS-04 flow resources, so the flows a process writes can be advertised
+//! to a broadcast control system with little more than the surrounding node, device, source and
+//! sender resources. An [`NmosNode`] registers the node and a device for the process and keeps
+//! them alive with heartbeats. Once attached with [`crate::MxlInstance::set_nmos_node`], every flow
+//! created through `create_flow_writer` is registered as a source, flow and sender of that device,
+//! and withdrawn when its last writer is dropped.
+//!
+//! ```ignore
+//! let node = NmosNode::register(NmosConfig::new("http://registry.local:8010", "Playout 1"))?;
+//! instance.set_nmos_node(Some(node));
+//! let (writer, info, _) = instance.create_flow_writer(&flow_def, None)?;
+//! ```
+//!
+//! Registry errors are logged rather than failing flow creation: the media path must not depend on
+//! the control plane. For the same reason, flows are registered and withdrawn by the heartbeat
+//! thread of the node, so creating or dropping a writer never waits for the registry. If the
+//! registry forgets the node (e.g. after a restart, answered with a 404
+//! to the heartbeat), all resources are registered again.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+pub const DEFAULT_API_VERSION: &str = "v1.3";
+/// Heartbeat interval recommended by IS-04, the registry expires nodes after 12 seconds.
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// Transport of the senders: MXL has no registered transport URN yet.
+pub const MXL_TRANSPORT: &str = "urn:x-nmos:transport:mxl";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Settings of an [`NmosNode`].
+#[derive(Debug, Clone)]
+pub struct NmosConfig {
+    registry_url: String,
+    api_version: String,
+    label: String,
+    node_id: Uuid,
+    device_id: Uuid,
+    hostname: String,
+    heartbeat_interval: Duration,
+}
+
+impl NmosConfig {
+    /// `registry_url` is the base of the Registration API, e.g. `http://registry.local:8010`.
+    pub fn new(registry_url: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            registry_url: registry_url.into().trim_end_matches('/').to_string(),
+            api_version: DEFAULT_API_VERSION.to_string(),
+            label: label.into(),
+            node_id: Uuid::new_v4(),
+            device_id: Uuid::new_v4(),
+            hostname: std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string()),
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
+        }
+    }
+
+    pub fn api_version(mut self, version: impl Into<String>) -> Self {
+        self.api_version = version.into();
+        self
+    }
+
+    /// Stable ids keep the node's identity across restarts of the process. Random by default.
+    pub fn ids(mut self, node_id: Uuid, device_id: Uuid) -> Self {
+        self.node_id = node_id;
+        self.device_id = device_id;
+        self
+    }
+
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    pub fn heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+}
+
+/// IS-04 `<seconds>:<nanoseconds>` version of a resource changed now.
+fn resource_version() -> String {
+    let now = crate::utc_to_tai_ns(SystemTime::now()).unwrap_or_default();
+    format!("{}:{}", now / 1_000_000_000, now % 1_000_000_000)
+}
+
+/// Resources registered for a flow.
+struct RegisteredFlow {
+    source: Value,
+    flow: Value,
+    sender: Value,
+    /// Writers of the flow in this process, withdrawn when the last one is dropped.
+    writers: usize,
+}
+
+/// Registry requests queued for the heartbeat thread.
+enum Operation {
+    Register(Uuid),
+    Withdraw(RegisteredFlow),
+}
+
+struct Registry {
+    agent: ureq::Agent,
+    config: NmosConfig,
+    flows: Mutex<HashMap<Uuid, RegisteredFlow>>,
+}
+
+impl Registry {
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/x-nmos/registration/{}/{path}",
+            self.config.registry_url, self.config.api_version
+        )
+    }
+
+    fn post(&self, path: &str, body: &Value) -> std::result::Result<(), ureq::Error> {
+        self.agent
+            .post(self.url(path))
+            .header("Content-Type", "application/json")
+            .send(body.to_string())
+            .map(|_| ())
+    }
+
+    fn register(&self, kind: &str, data: &Value) -> Result<()> {
+        self.post("resource", &json!({ "type": kind, "data": data }))
+            .map_err(|error| Error::Other(format!("Failed to register NMOS {kind}: {error}")))
+    }
+
+    fn delete(&self, kind: &str, id: &Value) {
+        let id = id.as_str().unwrap_or_default();
+        if let Err(error) = self
+            .agent
+            .delete(self.url(&format!("resource/{kind}s/{id}")))
+            .call()
+        {
+            tracing::warn!("Failed to withdraw NMOS {kind} {id}: {error}");
+        }
+    }
+
+    fn delete_flow(&self, registered: &RegisteredFlow) {
+        self.delete("sender", &registered.sender["id"]);
+        self.delete("flow", &registered.flow["id"]);
+        self.delete("source", &registered.source["id"]);
+    }
+
+    fn lock_flows(&self) -> MutexGuard<'_, HashMap<Uuid, RegisteredFlow>> {
+        self.flows
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn node(&self) -> Value {
+        let config = &self.config;
+        json!({
+            "id": config.node_id,
+            "version": resource_version(),
+            "label": config.label,
+            "description": config.label,
+            "tags": {},
+            "href": format!("http://{}/", config.hostname),
+            "hostname": config.hostname,
+            "api": { "versions": [config.api_version], "endpoints": [] },
+            "caps": {},
+            "services": [],
+            "clocks": [],
+            "interfaces": [],
+        })
+    }
+
+    fn device(&self, flows: &HashMap<Uuid, RegisteredFlow>) -> Value {
+        let config = &self.config;
+        let senders: Vec<&Value> = flows.values().map(|flow| &flow.sender["id"]).collect();
+        json!({
+            "id": config.device_id,
+            "version": resource_version(),
+            "label": config.label,
+            "description": config.label,
+            "tags": {},
+            "type": "urn:x-nmos:device:generic",
+            "node_id": config.node_id,
+            "senders": senders,
+            "receivers": [],
+            "controls": [],
+        })
+    }
+
+    /// Registers the device with the senders of the flows registered now.
+    fn register_device(&self) -> Result<()> {
+        let device = self.device(&self.lock_flows());
+        self.register("device", &device)
+    }
+
+    /// Registers the source, flow and sender of a flow unless it was withdrawn in the meantime.
+    fn register_flow(&self, flow_id: &Uuid) -> Result<()> {
+        let resources = self
+            .lock_flows()
+            .get(flow_id)
+            .map(|flow| [flow.source.clone(), flow.flow.clone(), flow.sender.clone()]);
+        let Some([source, flow, sender]) = resources else {
+            return Ok(());
+        };
+        self.register("source", &source)?;
+        self.register("flow", &flow)?;
+        self.register("sender", &sender)?;
+        self.register_device()
+    }
+
+    /// Registers the node, the device and every flow, e.g. after the registry lost them.
+    fn register_all(&self) -> Result<()> {
+        self.register("node", &self.node())?;
+        self.register_device()?;
+        let flows: Vec<Uuid> = self.lock_flows().keys().copied().collect();
+        for flow_id in &flows {
+            self.register_flow(flow_id)?;
+        }
+        Ok(())
+    }
+
+    fn run(&self, operation: Operation) {
+        let result = match operation {
+            Operation::Register(flow_id) => self.register_flow(&flow_id),
+            Operation::Withdraw(registered) => {
+                self.delete_flow(&registered);
+                self.register_device()
+            }
+        };
+        if let Err(error) = result {
+            tracing::warn!("{error}");
+        }
+    }
+
+    /// Runs queued operations and sends heartbeats until the node is dropped.
+    fn serve(&self, operations: Receiver<Operation>) {
+        let mut next_heartbeat = Instant::now() + self.config.heartbeat_interval;
+        loop {
+            let timeout = next_heartbeat.saturating_duration_since(Instant::now());
+            match operations.recv_timeout(timeout) {
+                Ok(operation) => self.run(operation),
+                Err(RecvTimeoutError::Timeout) => {
+                    next_heartbeat = Instant::now() + self.config.heartbeat_interval;
+                    self.heartbeat();
+                }
+                // Queued operations are received before the disconnection.
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    fn heartbeat(&self) {
+        let path = format!("health/nodes/{}", self.config.node_id);
+        match self.post(&path, &json!({})) {
+            Ok(()) => {}
+            Err(ureq::Error::StatusCode(404)) => {
+                tracing::info!("The NMOS registry forgot node {}", self.config.node_id);
+                if let Err(error) = self.register_all() {
+                    tracing::warn!("{error}");
+                }
+            }
+            Err(error) => tracing::warn!("NMOS heartbeat failed: {error}"),
+        }
+    }
+}
+
+/// The NMOS node and device of the process, registered for as long as it lives.
+pub struct NmosNode {
+    registry: Arc<Registry>,
+    /// Dropped to stop the heartbeat thread once it ran the queued operations.
+    operations: Option<Sender<Operation>>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for NmosNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NmosNode")
+            .field("config", &self.registry.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NmosNode {
+    /// Registers the node and its device, and starts sending heartbeats.
+    pub fn register(config: NmosConfig) -> Result<Self> {
+        let agent = ureq::Agent::new_with_config(
+            ureq::Agent::config_builder()
+                .timeout_global(Some(REQUEST_TIMEOUT))
+                .build(),
+        );
+        let registry = Arc::new(Registry {
+            agent,
+            config,
+            flows: Mutex::new(HashMap::new()),
+        });
+        registry.register_all()?;
+        let (operations, received) = crossbeam_channel::unbounded();
+        let heartbeat = std::thread::Builder::new()
+            .name("mxl-nmos-heartbeat".to_string())
+            .spawn({
+                let registry = registry.clone();
+                move || registry.serve(received)
+            })?;
+        Ok(Self {
+            registry,
+            operations: Some(operations),
+            heartbeat: Some(heartbeat),
+        })
+    }
+
+    pub fn node_id(&self) -> Uuid {
+        self.registry.config.node_id
+    }
+
+    pub fn device_id(&self) -> Uuid {
+        self.registry.config.device_id
+    }
+
+    fn queue(&self, operation: Operation) {
+        if let Some(operations) = &self.operations {
+            // The heartbeat thread only stops once the node is dropped.
+            let _ = operations.send(operation);
+        }
+    }
+
+    /// Registers the source, flow and sender of a flow definition, or counts another writer of an
+    /// already registered flow. Only the resources are built here, the heartbeat thread registers
+    /// them.
+    pub fn register_flow(&self, flow_def: &str) -> Result<()> {
+        let mut flow: Value = serde_json::from_str(flow_def)
+            .map_err(|error| Error::Other(format!("Invalid flow definition: {error}")))?;
+        let object = flow
+            .as_object_mut()
+            .ok_or_else(|| Error::Other("The flow definition is not an object.".to_string()))?;
+        let flow_id = object
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .ok_or_else(|| Error::Other("The flow definition has no valid id.".to_string()))?;
+
+        let mut flows = self.registry.lock_flows();
+        if let Some(registered) = flows.get_mut(&flow_id) {
+            registered.writers += 1;
+            return Ok(());
+        }
+
+        // `$copyright` and such are not IS-04 properties.
+        object.retain(|key, _| !key.starts_with('$'));
+        let config = &self.registry.config;
+        let version = resource_version();
+        let source_id = object
+            .get("source_id")
+            .cloned()
+            .unwrap_or_else(|| json!(Uuid::new_v4()));
+        let label = object.get("label").cloned().unwrap_or_else(|| json!(""));
+        let description = object.get("description").cloned().unwrap_or(json!(""));
+        let tags = object.get("tags").cloned().unwrap_or_else(|| json!({}));
+        let format = object.get("format").cloned().unwrap_or(Value::Null);
+        object.insert("version".to_string(), json!(version));
+        object.insert("source_id".to_string(), source_id.clone());
+        object.insert("device_id".to_string(), json!(config.device_id));
+
+        let mut source = json!({
+            "id": source_id,
+            "version": version,
+            "label": label,
+            "description": description,
+            "tags": tags,
+            "caps": {},
+            "device_id": config.device_id,
+            "parents": [],
+            "clock_name": null,
+            "format": format,
+        });
+        if let Some(channels) = object.get("channel_count").and_then(Value::as_u64) {
+            source["channels"] = (0..channels)
+                .map(|channel| json!({ "label": format!("Channel {}", channel + 1) }))
+                .collect();
+        }
+        let sender = json!({
+            "id": Uuid::new_v4(),
+            "version": version,
+            "label": label,
+            "description": description,
+            "tags": tags,
+            "flow_id": flow_id,
+            "transport": MXL_TRANSPORT,
+            "device_id": config.device_id,
+            "manifest_href": null,
+            "interface_bindings": [],
+            "subscription": { "receiver_id": null, "active": true },
+        });
+
+        flows.insert(
+            flow_id,
+            RegisteredFlow {
+                source,
+                flow,
+                sender,
+                writers: 1,
+            },
+        );
+        drop(flows);
+        self.queue(Operation::Register(flow_id));
+        Ok(())
+    }
+
+    /// Counts another writer of a registered flow, e.g. one opened on a flow this process created.
+    /// Returns `false` if the flow is not registered.
+    pub fn add_writer(&self, flow_id: &Uuid) -> bool {
+        match self.registry.lock_flows().get_mut(flow_id) {
+            Some(registered) => {
+                registered.writers += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Withdraws the resources of a flow once its last writer is gone, on the heartbeat thread.
+    pub fn withdraw_flow(&self, flow_id: &Uuid) {
+        let mut flows = self.registry.lock_flows();
+        let Some(registered) = flows.get_mut(flow_id) else {
+            return;
+        };
+        registered.writers -= 1;
+        if registered.writers > 0 {
+            return;
+        }
+        if let Some(registered) = flows.remove(flow_id) {
+            drop(flows);
+            self.queue(Operation::Withdraw(registered));
+        }
+    }
+
+    /// Flows currently registered, or queued for registration.
+    pub fn flows(&self) -> Vec<Uuid> {
+        self.registry.lock_flows().keys().copied().collect()
+    }
+}
+
+impl Drop for NmosNode {
+    fn drop(&mut self) {
+        self.operations.take();
+        if let Some(heartbeat) = self.heartbeat.take()
+            && heartbeat.join().is_err()
+        {
+            tracing::error!("The NMOS heartbeat thread panicked.");
+        }
+        let flows: Vec<RegisteredFlow> = self
+            .registry
+            .lock_flows()
+            .drain()
+            .map(|(_, registered)| registered)
+            .collect();
+        for registered in &flows {
+            self.registry.delete_flow(registered);
+        }
+        let config = &self.registry.config;
+        self.registry.delete("device", &json!(config.device_id));
+        self.registry.delete("node", &json!(config.node_id));
+    }
+}
@@ -0,0 +1,288 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Readiness notifications for event loops.
+//!
+//! The library only offers blocking waits on a flow, which costs a thread per flow or spinning on
+//! the non-blocking calls. A [`HeadNotifier`] does the blocking wait on its own thread and makes a
+//! file descriptor readable whenever the flow's head index advances, so flows can be registered
+//! with `poll`, `epoll`, `mio` (`SourceFd`) or tokio (`AsyncFd`) next to sockets and timers:
+//!
+//! ```ignore
+//! let notifier = HeadNotifier::new(instance.create_flow_reader(&flow_id)?)?;
+//! let readiness = tokio::io::unix::AsyncFd::new(notifier)?;
+//! loop {
+//!     let mut guard = readiness.readable().await?;
+//!     guard.get_inner().clear()?;
+//!     guard.clear_ready();
+//!     let head = guard.get_inner().head_index();
+//!     // Read up to `head` with the non-blocking reader calls.
+//! }
+//! ```
+//!
+//! Monitoring code without an event loop can block on the head index directly with
+//! [`FlowReader::head_changes`], which needs no extra thread:
+//!
+//! ```ignore
+//! for head in instance.create_flow_reader(&flow_id)?.head_changes()? {
+//!     println!("{flow_id} is at {}", head?);
+//! }
+//! ```
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+        unix::net::UnixStream,
+    },
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, FlowReader, GrainReader, Result, SamplesReader, TypedReader};
+
+/// Longest blocking wait of the watcher thread, which bounds how long dropping a notifier takes.
+const WAIT_TIMEOUT: Duration = Duration::from_millis(50);
+/// Pause after a wait that failed without blocking, so a failing flow is not spun on.
+const RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+enum Watched {
+    Grains(GrainReader),
+    Samples(SamplesReader),
+}
+
+impl Watched {
+    fn head_index(&self) -> Result<u64> {
+        Ok(match self {
+            Watched::Grains(reader) => reader.get_runtime_info()?.headIndex,
+            Watched::Samples(reader) => reader.get_runtime_info()?.headIndex,
+        })
+    }
+
+    /// Blocks until `index` is committed or `timeout` expires.
+    fn wait_for(&self, index: u64, timeout: Duration) -> Result<()> {
+        match self {
+            Watched::Grains(reader) if reader.payload_location().is_host() => {
+                reader.get_grain_slice(index, 1, timeout).map(|_| ())
+            }
+            Watched::Grains(reader) => reader.get_device_grain(index, 1, timeout).map(|_| ()),
+            Watched::Samples(reader) => reader.get_samples(index, 1, timeout).map(|_| ()),
+        }
+    }
+}
+
+/// Blocking iterator of the head indices of a flow, see [`FlowReader::head_changes`].
+///
+/// Each item is the head index after it advanced, several commits while the consumer was busy are
+/// coalesced into one item. The iterator ends after yielding the error that stopped it, typically
+/// because the flow was deleted or recreated.
+pub struct HeadChanges {
+    watched: Watched,
+    last_head: u64,
+    closed: bool,
+}
+
+impl std::fmt::Debug for HeadChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeadChanges")
+            .field("last_head", &self.last_head)
+            .field("closed", &self.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HeadChanges {
+    pub(crate) fn new(reader: FlowReader) -> Result<Self> {
+        let watched = match reader.into_typed()? {
+            TypedReader::Grain(reader) => Watched::Grains(reader),
+            TypedReader::Samples(reader) => Watched::Samples(reader),
+        };
+        Ok(Self {
+            last_head: watched.head_index()?,
+            watched,
+            closed: false,
+        })
+    }
+
+    /// Head index as of the last item.
+    pub fn head_index(&self) -> u64 {
+        self.last_head
+    }
+
+    /// Waits at most `timeout` for the head index to advance. Returns `None` if it did not.
+    pub fn next_within(&mut self, timeout: Duration) -> Result<Option<u64>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let waited = self.watched.wait_for(self.last_head + 1, remaining);
+            let head = self.watched.head_index()?;
+            if head != self.last_head {
+                self.last_head = head;
+                return Ok(Some(head));
+            }
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            if let Err(error) = waited
+                && !matches!(error, Error::Timeout)
+            {
+                std::thread::sleep(RETRY_INTERVAL.min(remaining));
+            }
+        }
+    }
+}
+
+impl Iterator for HeadChanges {
+    type Item = Result<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.closed {
+            return None;
+        }
+        loop {
+            match self.next_within(WAIT_TIMEOUT) {
+                Ok(Some(head)) => return Some(Ok(head)),
+                Ok(None) => continue,
+                Err(error) => {
+                    self.closed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+struct Shared {
+    head_index: AtomicU64,
+    stop: AtomicBool,
+    closed: AtomicBool,
+}
+
+/// File descriptor that becomes readable when the head index of a flow advances.
+///
+/// The notifier owns the reader it is created from and uses it from a background thread, so
+/// create a dedicated reader for it. The descriptor stays readable until [`HeadNotifier::clear`]
+/// is called; notifications of several commits in between are coalesced. If the flow disappears
+/// or is recreated the watcher stops and the descriptor reports end of file, see
+/// [`HeadNotifier::is_closed`].
+pub struct HeadNotifier {
+    readable: UnixStream,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for HeadNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeadNotifier")
+            .field("fd", &self.readable.as_raw_fd())
+            .field(
+                "head_index",
+                &self.shared.head_index.load(Ordering::Acquire),
+            )
+            .field("closed", &self.shared.closed.load(Ordering::Acquire))
+            .finish()
+    }
+}
+
+impl HeadNotifier {
+    pub fn new(reader: FlowReader) -> Result<Self> {
+        let changes = HeadChanges::new(reader)?;
+        let (readable, writable) = UnixStream::pair()?;
+        readable.set_nonblocking(true)?;
+        writable.set_nonblocking(true)?;
+        let shared = Arc::new(Shared {
+            head_index: AtomicU64::new(changes.head_index()),
+            stop: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+        });
+        let thread = std::thread::Builder::new()
+            .name("mxl-head-notifier".to_string())
+            .spawn({
+                let shared = shared.clone();
+                move || watch(changes, writable, &shared)
+            })?;
+        Ok(Self {
+            readable,
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// Head index of the flow as of the last notification.
+    pub fn head_index(&self) -> u64 {
+        self.shared.head_index.load(Ordering::Acquire)
+    }
+
+    /// Whether the watcher stopped because the flow can no longer be read.
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+
+    /// Consumes the pending notifications, so the descriptor is readable again only after the next
+    /// head advance.
+    pub fn clear(&self) -> Result<()> {
+        let mut buffer = [0u8; 64];
+        loop {
+            match (&self.readable).read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(_) => continue,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(Error::Io(error)),
+            }
+        }
+    }
+}
+
+fn watch(mut changes: HeadChanges, mut writable: UnixStream, shared: &Shared) {
+    while !shared.stop.load(Ordering::Acquire) {
+        match changes.next_within(WAIT_TIMEOUT) {
+            Ok(Some(head)) => {
+                shared.head_index.store(head, Ordering::Release);
+                match writable.write(&[1]) {
+                    // A full socket buffer is readable already.
+                    Ok(_) => {}
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => {}
+                    Err(error) => {
+                        tracing::debug!("Stopping head notifications: {error}");
+                        break;
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(error) => {
+                tracing::debug!("Stopping head notifications: {error}");
+                break;
+            }
+        }
+    }
+    shared.closed.store(true, Ordering::Release);
+    // Dropping `writable` hangs up the descriptor, which wakes up the event loop.
+}
+
+impl AsFd for HeadNotifier {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.readable.as_fd()
+    }
+}
+
+impl AsRawFd for HeadNotifier {
+    fn as_raw_fd(&self) -> RawFd {
+        self.readable.as_raw_fd()
+    }
+}
+
+impl Drop for HeadNotifier {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take()
+            && thread.join().is_err()
+        {
+            tracing::error!("The head notifier thread panicked.");
+        }
+    }
+}
@@ -0,0 +1,324 @@
+// SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::Serialize;
+
+use crate::{Error, PayloadLocation, Result};
+
+/// Options passed to `mxlCreateInstance`.
+///
+/// The library currently does not interpret any instance level option (the history duration is a
+/// domain level setting, see `docs/Configuration.md`), but it does require the options to be a
+/// JSON object. Arbitrary entries can be added with [`InstanceOptions::option`] for options
+/// introduced by newer library versions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct InstanceOptions {
+    /// Enforced on the Rust side only, see [`crate::MxlInstance::open_read_only`].
+    #[serde(skip)]
+    read_only: bool,
+    /// Enforced on the Rust side only, see [`InstanceOptions::epoch_offset`].
+    #[serde(skip)]
+    epoch_offset: i64,
+    /// See [`InstanceOptions::track_live_objects`].
+    #[serde(skip)]
+    track_live_objects: bool,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl InstanceOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prevents the instance from creating flow writers or garbage-collecting flows.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Offsets the epoch used to compute grain and sample indices, in nanoseconds.
+    ///
+    /// Facilities whose genlock runs at an offset from the ST 2059 epoch set this so that the
+    /// index functions of [`crate::MxlInstance`] (`get_current_index`, `timestamp_to_index`,
+    /// ...) align frame boundaries with house sync rather than with raw TAI. Timestamps stay TAI.
+    /// Every instance reading or writing the same flows must use the same offset, or they will
+    /// disagree on which grain an index refers to.
+    pub fn epoch_offset(mut self, offset_ns: i64) -> Self {
+        self.epoch_offset = offset_ns;
+        self
+    }
+
+    pub fn get_epoch_offset(&self) -> i64 {
+        self.epoch_offset
+    }
+
+    /// Records the readers, writers and write sessions of the instance with the backtrace of
+    /// their creation, see [`crate::MxlInstance::live_objects`]. Capturing the backtraces is slow,
+    /// so this is meant for debugging.
+    pub fn track_live_objects(mut self, track: bool) -> Self {
+        self.track_live_objects = track;
+        self
+    }
+
+    pub fn get_track_live_objects(&self) -> bool {
+        self.track_live_objects
+    }
+
+    /// Sets a raw option entry, overriding any previous value for `key`.
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+
+    /// Serializes the options to the JSON string expected by the C API. Empty options serialize
+    /// to an empty string, which the library treats as "no options".
+    pub fn to_json_string(&self) -> Result<String> {
+        if self.extra.is_empty() {
+            return Ok(String::new());
+        }
+        serde_json::to_string(self)
+            .map_err(|error| Error::Other(format!("Failed to serialize instance options: {error}")))
+    }
+}
+
+/// Options passed to `mxlCreateFlowWriter`.
+///
+/// The batch size hints describe how many grain slices (discrete flows) or samples (continuous
+/// flows) the writer commits at once, and how many of them readers should wait for before
+/// synchronizing. They are published in the flow's `CommonFlowConfigInfo`.
+///
+/// The history duration sets the depth of the flow's ring buffer, overriding the domain level
+/// setting. The resulting size is available from [`crate::FlowConfigInfo::ring_length`].
+///
+/// With the `cuda` feature, [`FlowOptions::device_index`] requests the flow's payloads in the
+/// memory of a GPU.
+///
+/// [`FlowOptions::with_mode`], [`FlowOptions::with_owner`] and [`FlowOptions::with_group`] are
+/// applied on the Rust side to the flow's files once the library created them, see
+/// [`crate::MxlDomain::set_flow_permissions`]. Until then, the flow has the permissions the
+/// library creates flows with, following the writer's umask. If they cannot be applied, creating
+/// the writer fails and releases it, which makes the library delete the flow again, so no flow is
+/// left with part of them unless another writer opened it meanwhile. A domain
+/// shared by several users, e.g. a root writer and consumer containers running as another user,
+/// is typically a directory owned by a group all of them belong to and closed to others, which
+/// also keeps others out of flows in that window, with flows created group-readable for that
+/// group.
+///
+/// All options only apply when the writer creates the flow. Opening an existing flow keeps its
+/// configuration, so check the returned `FlowConfigInfo` instead of assuming the options took
+/// effect.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_commit_batch_size_hint: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_sync_batch_size_hint: Option<u32>,
+    /// In nanoseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload_location: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_index: Option<i32>,
+    /// Applied on the Rust side, see [`FlowOptions::with_mode`] and the following.
+    #[serde(skip)]
+    mode: Option<u32>,
+    #[serde(skip)]
+    owner: Option<u32>,
+    #[serde(skip)]
+    group: Option<u32>,
+}
+
+impl FlowOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_commit_batch_size_hint(mut self, hint: u32) -> Self {
+        self.max_commit_batch_size_hint = Some(hint);
+        self
+    }
+
+    /// Must be a multiple of the commit batch size hint.
+    pub fn max_sync_batch_size_hint(mut self, hint: u32) -> Self {
+        self.max_sync_batch_size_hint = Some(hint);
+        self
+    }
+
+    /// Keeps at least `duration` worth of grains or samples in the ring buffer.
+    pub fn history_duration(mut self, duration: Duration) -> Self {
+        self.history_duration = Some(duration.as_nanos().try_into().unwrap_or(u64::MAX));
+        self
+    }
+
+    /// Places the flow's payloads in the memory of the GPU with this index, a CUDA device ordinal.
+    #[cfg(feature = "cuda")]
+    pub fn device_index(mut self, device_index: i32) -> Self {
+        self.payload_location = Some(mxl_sys::MXL_PAYLOAD_LOCATION_DEVICE_MEMORY);
+        self.device_index = Some(device_index);
+        self
+    }
+
+    /// Permission bits of the created flow's files, e.g. `0o640` for consumers in the writer's
+    /// group. Directories additionally get the search bit of every class they are readable by.
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// User id owning the created flow's files. Changing it requires `CAP_CHOWN`.
+    pub fn with_owner(mut self, uid: u32) -> Self {
+        self.owner = Some(uid);
+        self
+    }
+
+    /// Group id owning the created flow's files. Unprivileged writers can only use their own
+    /// groups.
+    pub fn with_group(mut self, gid: u32) -> Self {
+        self.group = Some(gid);
+        self
+    }
+
+    pub fn mode(&self) -> Option<u32> {
+        self.mode
+    }
+
+    pub fn owner(&self) -> Option<u32> {
+        self.owner
+    }
+
+    pub fn group(&self) -> Option<u32> {
+        self.group
+    }
+
+    /// Where the payloads of the created flow are requested to be.
+    pub fn requested_payload_location(&self) -> Option<PayloadLocation> {
+        self.payload_location
+            .map(|location| PayloadLocation::new(location, self.device_index.unwrap_or(-1)))
+    }
+
+    /// Checks the same constraints the C library enforces, so that invalid options are reported
+    /// with a meaningful message instead of a bare `MXL_ERR_INVALID_ARG`.
+    pub fn validate(&self) -> Result<()> {
+        if self.max_commit_batch_size_hint == Some(0) {
+            return Err(Error::Other(
+                "maxCommitBatchSizeHint must be greater or equal to 1.".to_string(),
+            ));
+        }
+        if let Some(sync) = self.max_sync_batch_size_hint {
+            if sync == 0 {
+                return Err(Error::Other(
+                    "maxSyncBatchSizeHint must be greater or equal to 1.".to_string(),
+                ));
+            }
+            if sync % self.max_commit_batch_size_hint.unwrap_or(1) != 0 {
+                return Err(Error::Other(
+                    "maxSyncBatchSizeHint must be a multiple of maxCommitBatchSizeHint."
+                        .to_string(),
+                ));
+            }
+        }
+        if self.device_index.is_some_and(|index| index < 0) {
+            return Err(Error::Other(
+                "deviceIndex must be greater or equal to 0.".to_string(),
+            ));
+        }
+        if self.mode.is_some_and(|mode| mode & !0o7777 != 0) {
+            return Err(Error::Other(format!(
+                "mode {:o} has bits beyond the permission bits.",
+                self.mode.unwrap_or_default()
+            )));
+        }
+        if self.history_duration == Some(0) {
+            return Err(Error::Other(
+                "historyDuration must be greater or equal to 1.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates the options and serializes them to the JSON string expected by the C API.
+    pub fn to_json_string(&self) -> Result<String> {
+        self.validate()?;
+        serde_json::to_string(self)
+            .map_err(|error| Error::Other(format!("Failed to serialize flow options: {error}")))
+    }
+}
+
+/// Options of a single grain or samples access, passed to the `*_with_options` variants of
+/// [`crate::GrainWriter::open_grain`], [`crate::SamplesWriter::open_samples`],
+/// [`crate::GrainReader::get_complete_grain`], [`crate::GrainReader::get_grain_slice`] and
+/// [`crate::SamplesReader::get_samples`].
+///
+/// The library does not accept per-access options yet, so every entry is currently ignored.
+/// Callers can already forward options such as a priority or a partial read policy, and they
+/// will take effect once a library version interprets them, without another API change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AccessOptions {
+    #[serde(flatten)]
+    entries: BTreeMap<String, serde_json::Value>,
+}
+
+impl AccessOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets an option entry, overriding any previous value for `key`.
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.entries.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.entries.get(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Parses comma separated `key=value` entries, as set through element properties. Values that
+    /// are valid JSON (numbers, booleans, ...) keep their type, anything else is a string.
+    pub fn parse(options: &str) -> Result<Self> {
+        let mut parsed = Self::new();
+        for entry in options.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (key, value) = entry
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| Error::Other(format!("Invalid access option '{entry}'")))?;
+            let value = serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+            parsed.entries.insert(key.to_string(), value);
+        }
+        Ok(parsed)
+    }
+
+    /// Serializes the options to a JSON object string. Empty options serialize to an empty string.
+    pub fn to_json_string(&self) -> Result<String> {
+        if self.entries.is_empty() {
+            return Ok(String::new());
+        }
+        serde_json::to_string(self)
+            .map_err(|error| Error::Other(format!("Failed to serialize access options: {error}")))
+    }
+
+    /// Reports entries the library cannot apply to `access`.
+    pub(crate) fn warn_unsupported(&self, access: &str) {
+        if !self.entries.is_empty() {
+            tracing::debug!(
+                "Ignoring {access} options {:?}, the library does not support them",
+                self.entries.keys().collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Grain payloads in host or GPU memory.
+//!
+//! A flow created with a device payload location keeps its grain payloads in the memory of a GPU.
+//! Those payloads are handed out as [`DevicePayload`]s, device addresses for the GPU's API (CUDA,
+//! ...), instead of the `&[u8]` of host payloads, which the host must not dereference.
+
+use std::marker::PhantomData;
+
+/// Where the payloads of a flow are located.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadLocation {
+    HostMemory,
+    /// Memory of the GPU with this index, e.g. a CUDA device ordinal.
+    DeviceMemory {
+        device_index: i32,
+    },
+}
+
+impl PayloadLocation {
+    /// Location from the `payloadLocation` and `deviceIndex` of a flow's common config. Unknown
+    /// locations are taken as device memory, so their payloads are never dereferenced on the host.
+    pub(crate) fn new(location: u32, device_index: i32) -> Self {
+        match location {
+            mxl_sys::MXL_PAYLOAD_LOCATION_HOST_MEMORY => Self::HostMemory,
+            _ => Self::DeviceMemory { device_index },
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        *self == Self::HostMemory
+    }
+}
+
+/// A grain payload in the memory of a GPU, valid for as long as the reader or write access it
+/// comes from.
+#[derive(Clone, Copy, Debug)]
+pub struct DevicePayload<'a> {
+    pointer: *mut u8,
+    len: usize,
+    device_index: i32,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> DevicePayload<'a> {
+    pub(crate) fn new(pointer: *mut u8, len: usize, device_index: i32) -> Self {
+        Self {
+            pointer,
+            len,
+            device_index,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Device address of the payload, e.g. a `CUdeviceptr`. Only the GPU's API may access it.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.pointer
+    }
+
+    /// Device address of the payload for the CUDA driver API.
+    #[cfg(feature = "cuda")]
+    pub fn device_ptr(&self) -> crate::cuda::CuDevicePtr {
+        self.pointer as crate::cuda::CuDevicePtr
+    }
+
+    /// Size of the payload in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Index of the GPU holding the payload.
+    pub fn device_index(&self) -> i32 {
+        self.device_index
+    }
+}
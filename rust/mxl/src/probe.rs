@@ -0,0 +1,147 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Write to read latency of a flow, as seen by a reader.
+//!
+//! The write time of a grain or samples batch comes from one of:
+//!
+//! - A stamp the writer puts in the payload with [`stamp_payload`], recorded with
+//!   [`LatencyProbe::record_stamped`]. Every read is measured, but the stamp overwrites the first
+//!   [`PROBE_STAMP_SIZE`] bytes of the payload, so it is meant for test flows or a payload region
+//!   the format leaves unused.
+//! - The flow's last write time, recorded with [`LatencyProbe::record_commit`]. Needs nothing from
+//!   the writer, but only measures reads of the head index, the only one the time belongs to.
+//!
+//! Both are TAI times from [`crate::MxlInstance::get_time`], which every process of a domain
+//! shares.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::{Error, FlowRuntimeInfo, LatencyReport, Result};
+
+/// Size in bytes of a probe stamp at the start of a payload.
+pub const PROBE_STAMP_SIZE: usize = 16;
+
+const PROBE_MAGIC: [u8; 8] = *b"MXLPROBE";
+
+/// Writes the write time `tai_ns` at the start of `payload`, see [`read_stamp`].
+pub fn stamp_payload(payload: &mut [u8], tai_ns: u64) -> Result<()> {
+    let Some(stamp) = payload.get_mut(..PROBE_STAMP_SIZE) else {
+        return Err(Error::Other(format!(
+            "A probe stamp takes {PROBE_STAMP_SIZE} bytes, the payload has {}.",
+            payload.len()
+        )));
+    };
+    stamp[..8].copy_from_slice(&PROBE_MAGIC);
+    stamp[8..].copy_from_slice(&tai_ns.to_le_bytes());
+    Ok(())
+}
+
+/// Write time stamped at the start of `payload` by [`stamp_payload`], if any.
+pub fn read_stamp(payload: &[u8]) -> Option<u64> {
+    let stamp = payload.get(..PROBE_STAMP_SIZE)?;
+    if stamp[..8] != PROBE_MAGIC {
+        return None;
+    }
+    Some(u64::from_le_bytes(stamp[8..].try_into().ok()?))
+}
+
+/// Latencies of the last reads of a flow, summarized as percentiles.
+///
+/// The probe keeps a sliding window of the most recent measurements, so a long running reader
+/// reports its current latency rather than an average over its lifetime.
+#[derive(Clone, Debug)]
+pub struct LatencyProbe {
+    flow_id: Uuid,
+    window: usize,
+    latencies: VecDeque<u64>,
+}
+
+impl LatencyProbe {
+    /// Measurements kept by [`LatencyProbe::new`].
+    pub const DEFAULT_WINDOW: usize = 10_000;
+
+    pub fn new(flow_id: Uuid) -> Self {
+        Self::with_window(flow_id, Self::DEFAULT_WINDOW)
+    }
+
+    /// A probe keeping the last `window` measurements (at least one).
+    pub fn with_window(flow_id: Uuid, window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            flow_id,
+            window,
+            latencies: VecDeque::with_capacity(window.min(Self::DEFAULT_WINDOW)),
+        }
+    }
+
+    pub fn flow_id(&self) -> Uuid {
+        self.flow_id
+    }
+
+    /// Records a latency measured by the application.
+    pub fn record(&mut self, latency: Duration) {
+        self.push(latency.as_nanos().min(u64::MAX as u128) as u64);
+    }
+
+    /// Records the latency of a payload stamped by [`stamp_payload`], read at `now_tai`. Returns
+    /// whether the payload carried a stamp.
+    pub fn record_stamped(&mut self, payload: &[u8], now_tai: u64) -> bool {
+        let Some(written) = read_stamp(payload) else {
+            return false;
+        };
+        self.push(now_tai.saturating_sub(written));
+        true
+    }
+
+    /// Records the latency of the read of `index` at `now_tai` from the flow's last write time.
+    /// Returns whether it was recorded, which requires `index` to be the head index of `runtime`,
+    /// read after `index` was.
+    pub fn record_commit(&mut self, runtime: &FlowRuntimeInfo, index: u64, now_tai: u64) -> bool {
+        if runtime.head_index() != index {
+            return false;
+        }
+        self.push(now_tai.saturating_sub(runtime.last_write_time()));
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.latencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latencies.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.latencies.clear();
+    }
+
+    /// Latency in nanoseconds below which `percentile` percent of the measurements fall (nearest
+    /// rank). `None` without measurements or for a percentile outside `0..=100`.
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        if self.latencies.is_empty() || !(0.0..=100.0).contains(&percentile) {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.latencies.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = ((sorted.len() - 1) as f64 * percentile / 100.0).ceil() as usize;
+        Some(sorted[rank])
+    }
+
+    /// Summary of the measurements in the window, see [`LatencyReport::from_samples`].
+    pub fn summary(&self, source: impl Into<String>, timestamp: u64) -> Option<LatencyReport> {
+        let (front, back) = self.latencies.as_slices();
+        LatencyReport::from_samples(self.flow_id, source, timestamp, &[front, back].concat())
+    }
+
+    fn push(&mut self, latency: u64) {
+        if self.latencies.len() == self.window {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+    }
+}
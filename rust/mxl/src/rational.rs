@@ -0,0 +1,179 @@
+// SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cmp::Ordering, time::Duration};
+
+use crate::flow::flowdef;
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// Commonly used grain and sample rates.
+pub mod rates {
+    use mxl_sys::Rational;
+
+    pub const FPS_23_98: Rational = Rational {
+        numerator: 24000,
+        denominator: 1001,
+    };
+    pub const FPS_24: Rational = Rational {
+        numerator: 24,
+        denominator: 1,
+    };
+    pub const FPS_25: Rational = Rational {
+        numerator: 25,
+        denominator: 1,
+    };
+    pub const FPS_29_97: Rational = Rational {
+        numerator: 30000,
+        denominator: 1001,
+    };
+    pub const FPS_30: Rational = Rational {
+        numerator: 30,
+        denominator: 1,
+    };
+    pub const FPS_50: Rational = Rational {
+        numerator: 50,
+        denominator: 1,
+    };
+    pub const FPS_59_94: Rational = Rational {
+        numerator: 60000,
+        denominator: 1001,
+    };
+    pub const FPS_60: Rational = Rational {
+        numerator: 60,
+        denominator: 1,
+    };
+    pub const HZ_48000: Rational = Rational {
+        numerator: 48000,
+        denominator: 1,
+    };
+}
+
+/// Arithmetic on grain and sample rates.
+///
+/// `mxl_sys::Rational` is generated from the C headers, so the helpers are provided as an
+/// extension trait rather than inherent methods.
+pub trait RationalExt: Sized {
+    /// Both terms are strictly positive.
+    fn is_valid(&self) -> bool;
+
+    /// The same rate with numerator and denominator divided by their greatest common divisor.
+    fn reduced(&self) -> Self;
+
+    fn as_f64(&self) -> f64;
+
+    /// Compares the rates by value, so that `50/2` equals `25/1`.
+    fn rate_cmp(&self, other: &Self) -> Ordering;
+
+    fn rate_eq(&self, other: &Self) -> bool {
+        self.rate_cmp(other) == Ordering::Equal
+    }
+
+    /// Duration of `count` grains or samples, truncated to whole nanoseconds. `None` for invalid
+    /// rates or if the result does not fit.
+    fn duration_of(&self, count: u64) -> Option<Duration>;
+
+    /// Duration of a single grain or sample.
+    fn period(&self) -> Option<Duration> {
+        self.duration_of(1)
+    }
+
+    /// Number of whole grains or samples within `duration`. `0` for invalid rates.
+    fn count_in(&self, duration: Duration) -> u64;
+
+    #[cfg(feature = "gstreamer")]
+    fn from_fraction(fraction: gstreamer::Fraction) -> Self;
+
+    /// `None` if a term does not fit into an `i32`.
+    #[cfg(feature = "gstreamer")]
+    fn to_fraction(&self) -> Option<gstreamer::Fraction>;
+}
+
+impl RationalExt for mxl_sys::Rational {
+    fn is_valid(&self) -> bool {
+        self.numerator > 0 && self.denominator > 0
+    }
+
+    fn reduced(&self) -> Self {
+        let divisor = gcd(
+            self.numerator.unsigned_abs(),
+            self.denominator.unsigned_abs(),
+        );
+        if divisor <= 1 {
+            return *self;
+        }
+        Self {
+            numerator: self.numerator / divisor as i64,
+            denominator: self.denominator / divisor as i64,
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    fn rate_cmp(&self, other: &Self) -> Ordering {
+        // Cross-multiply, normalizing the sign so that a negative denominator does not flip the
+        // comparison.
+        let sign = |rate: &Self| if rate.denominator < 0 { -1i128 } else { 1 };
+        let lhs = self.numerator as i128 * other.denominator as i128 * sign(self) * sign(other);
+        let rhs = other.numerator as i128 * self.denominator as i128 * sign(self) * sign(other);
+        lhs.cmp(&rhs)
+    }
+
+    fn duration_of(&self, count: u64) -> Option<Duration> {
+        if !self.is_valid() {
+            return None;
+        }
+        let nanos =
+            count as u128 * self.denominator as u128 * NANOS_PER_SECOND / self.numerator as u128;
+        Some(Duration::from_nanos(nanos.try_into().ok()?))
+    }
+
+    fn count_in(&self, duration: Duration) -> u64 {
+        if !self.is_valid() {
+            return 0;
+        }
+        let count = duration.as_nanos() * self.numerator as u128
+            / (self.denominator as u128 * NANOS_PER_SECOND);
+        count.try_into().unwrap_or(u64::MAX)
+    }
+
+    #[cfg(feature = "gstreamer")]
+    fn from_fraction(fraction: gstreamer::Fraction) -> Self {
+        Self {
+            numerator: fraction.numer() as i64,
+            denominator: fraction.denom() as i64,
+        }
+    }
+
+    #[cfg(feature = "gstreamer")]
+    fn to_fraction(&self) -> Option<gstreamer::Fraction> {
+        Some(gstreamer::Fraction::new(
+            self.numerator.try_into().ok()?,
+            self.denominator.try_into().ok()?,
+        ))
+    }
+}
+
+impl From<flowdef::Rate> for mxl_sys::Rational {
+    fn from(rate: flowdef::Rate) -> Self {
+        Self {
+            numerator: rate.numerator as i64,
+            denominator: rate.denominator as i64,
+        }
+    }
+}
+
+impl From<&flowdef::Rate> for mxl_sys::Rational {
+    fn from(rate: &flowdef::Rate) -> Self {
+        rate.clone().into()
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
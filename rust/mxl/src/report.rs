@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Report types shared by the examples, tools and GStreamer elements that measure MXL flows.
+//!
+//! Everything that emits latency or throughput figures should produce these types, so that
+//! dashboards can consume a single JSON schema.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Version of the report schema, bumped on incompatible changes.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Latency of a flow over a measurement window.
+///
+/// Latency is measured from the TAI timestamp of a grain or sample index to the moment it was
+/// observed by the reporting component. All values are in nanoseconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LatencyReport {
+    pub schema_version: u32,
+    pub flow_id: Uuid,
+    /// Name of the component that produced the report (tool, element name, ...).
+    pub source: String,
+    /// TAI time at the end of the measurement window.
+    pub timestamp: u64,
+    pub count: u64,
+    pub min: u64,
+    pub max: u64,
+    pub mean: u64,
+    pub p50: u64,
+    pub p99: u64,
+}
+
+impl LatencyReport {
+    /// Builds a report from raw latency measurements in nanoseconds. Returns `None` if there are
+    /// no measurements.
+    pub fn from_samples(
+        flow_id: Uuid,
+        source: impl Into<String>,
+        timestamp: u64,
+        samples: &[u64],
+    ) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        let sum: u128 = sorted.iter().map(|&v| v as u128).sum();
+        let percentile = |p: usize| sorted[((count - 1) * p).div_ceil(100)];
+        Some(Self {
+            schema_version: REPORT_SCHEMA_VERSION,
+            flow_id,
+            source: source.into(),
+            timestamp,
+            count: count as u64,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean: (sum / count as u128) as u64,
+            p50: percentile(50),
+            p99: percentile(99),
+        })
+    }
+}
+
+/// Throughput of a flow over a measurement window.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ThroughputReport {
+    pub schema_version: u32,
+    pub flow_id: Uuid,
+    /// Name of the component that produced the report (tool, element name, ...).
+    pub source: String,
+    /// TAI time at the end of the measurement window.
+    pub timestamp: u64,
+    /// Length of the measurement window in nanoseconds.
+    pub window: u64,
+    /// Grains (discrete flows) or samples (continuous flows) transferred.
+    pub units: u64,
+    /// Grains or samples that were expected but missed (skipped, too late, ...).
+    pub dropped: u64,
+    pub bytes: u64,
+    pub units_per_second: f64,
+    pub bytes_per_second: f64,
+}
+
+impl ThroughputReport {
+    pub fn new(
+        flow_id: Uuid,
+        source: impl Into<String>,
+        timestamp: u64,
+        window: Duration,
+        units: u64,
+        dropped: u64,
+        bytes: u64,
+    ) -> Self {
+        let seconds = window.as_secs_f64();
+        let per_second = |value: u64| {
+            if seconds > 0.0 {
+                value as f64 / seconds
+            } else {
+                0.0
+            }
+        };
+        Self {
+            schema_version: REPORT_SCHEMA_VERSION,
+            flow_id,
+            source: source.into(),
+            timestamp,
+            window: window.as_nanos() as u64,
+            units,
+            dropped,
+            bytes,
+            units_per_second: per_second(units),
+            bytes_per_second: per_second(bytes),
+        }
+    }
+}
@@ -1,7 +1,10 @@
 // SPDX-FileCopyrightText: 2025 2025 Contributors to the Media eXchange Layer project.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod batcher;
+pub mod convert;
 pub mod data;
+pub mod interleave;
 pub mod reader;
 pub mod write_access;
 pub mod writer;
@@ -0,0 +1,160 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cuts interleaved samples of any size into the commits a continuous flow expects.
+//!
+//! Readers of a continuous flow wait for batches of the flow's commit batch size hint, and the
+//! writer must never have more than half of the ring open, so producers writing whatever their
+//! source hands them would each have to buffer and chunk. A [`SampleBatcher`] does that once:
+//!
+//! ```ignore
+//! let mut batcher = SampleBatcher::new(writer, &config, instance.get_current_index(&rate))?
+//!     .options(InterleaveOptions::new().format(SampleFormat::S16));
+//! while let Some(packet) = source.next_packet()? {
+//!     batcher.push(&packet)?;
+//! }
+//! batcher.flush()?;
+//! ```
+
+use std::time::Duration;
+
+use crate::{Error, FlowConfigInfo, InterleaveOptions, RationalExt, Result, SamplesWriter};
+
+/// Duration of a batch when the flow has no commit batch size hint.
+pub const DEFAULT_BATCH_DURATION: Duration = Duration::from_millis(10);
+
+/// Commits interleaved samples to a [`SamplesWriter`] in batches of the flow's commit batch size
+/// hint, see the [module documentation](self).
+///
+/// Samples that do not fill a batch are kept until the next [`SampleBatcher::push`]. They are
+/// lost unless [`SampleBatcher::flush`] is called before the batcher is dropped.
+#[derive(Debug)]
+pub struct SampleBatcher {
+    writer: SamplesWriter,
+    options: InterleaveOptions,
+    channels: usize,
+    batch: usize,
+    /// Largest batch the ring allows, half of its length.
+    max_batch: usize,
+    /// Index of the last sample committed.
+    index: u64,
+    /// Interleaved samples of the next batch.
+    pending: Vec<u8>,
+}
+
+impl SampleBatcher {
+    /// The first batch is committed right after `index`, which is typically the current index of
+    /// the flow's sample rate.
+    pub fn new(writer: SamplesWriter, config: &FlowConfigInfo, index: u64) -> Result<Self> {
+        let continuous = config.continuous()?;
+        let max_batch = (continuous.bufferLength as usize / 2).max(1);
+        let batch = match config.common().max_commit_batch_size_hint() {
+            0 => config
+                .common()
+                .sample_rate()?
+                .count_in(DEFAULT_BATCH_DURATION) as usize,
+            hint => hint as usize,
+        };
+        Ok(Self {
+            writer,
+            options: InterleaveOptions::new(),
+            channels: continuous.channelCount as usize,
+            batch: batch.clamp(1, max_batch),
+            max_batch,
+            index,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Format, gains and dither of the pushed samples. Defaults to [`InterleaveOptions::new`].
+    pub fn options(mut self, options: InterleaveOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Number of samples per commit instead of the flow's hint, at most half of the ring.
+    pub fn batch_size(mut self, count: usize) -> Self {
+        self.batch = count.clamp(1, self.max_batch);
+        self
+    }
+
+    pub fn get_batch_size(&self) -> usize {
+        self.batch
+    }
+
+    /// Index of the last sample committed.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Number of samples pushed but not committed yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len() / self.frame_size()
+    }
+
+    /// Appends the interleaved `samples` and commits every batch they complete. Returns the number
+    /// of samples committed.
+    pub fn push(&mut self, mut samples: &[u8]) -> Result<usize> {
+        let frame_size = self.frame_size();
+        if !samples.len().is_multiple_of(frame_size) {
+            return Err(Error::Other(format!(
+                "{} bytes are not a whole number of {}-channel {:?} samples.",
+                samples.len(),
+                self.channels,
+                self.options.get_format()
+            )));
+        }
+        let batch_len = self.batch * frame_size;
+        let mut committed = 0;
+        if !self.pending.is_empty() {
+            let missing = (batch_len - self.pending.len()).min(samples.len());
+            self.pending.extend_from_slice(&samples[..missing]);
+            samples = &samples[missing..];
+            if self.pending.len() < batch_len {
+                return Ok(0);
+            }
+            let pending = std::mem::take(&mut self.pending);
+            committed += self.commit(&pending)?;
+            self.pending = pending;
+            self.pending.clear();
+        }
+        // Whole batches are committed straight from the input.
+        while samples.len() >= batch_len {
+            committed += self.commit(&samples[..batch_len])?;
+            samples = &samples[batch_len..];
+        }
+        self.pending.extend_from_slice(samples);
+        Ok(committed)
+    }
+
+    /// Commits the pending samples as a short batch. Returns the number of samples committed.
+    pub fn flush(&mut self) -> Result<usize> {
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        let pending = std::mem::take(&mut self.pending);
+        let committed = self.commit(&pending)?;
+        self.pending = pending;
+        self.pending.clear();
+        Ok(committed)
+    }
+
+    /// Returns the writer, dropping the pending samples.
+    pub fn into_writer(self) -> SamplesWriter {
+        self.writer
+    }
+
+    fn frame_size(&self) -> usize {
+        self.options.interleaved_len(1, self.channels)
+    }
+
+    fn commit(&mut self, samples: &[u8]) -> Result<usize> {
+        let count = samples.len() / self.frame_size();
+        let index = self.index + count as u64;
+        let mut access = self.writer.open_samples(index, count)?;
+        access.deinterleave_from(samples, &self.options)?;
+        access.commit()?;
+        self.index = index;
+        Ok(count)
+    }
+}
@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversions between the `audio/float32` samples of a channel and integer PCM, for consumers
+//! handing flow data to APIs such as ALSA or codecs that take `i16` or `i32` buffers.
+//!
+//! The functions work on the two ring fragments of a channel as returned by
+//! [`crate::SamplesData::channel_data`] and [`crate::SamplesWriteAccess::channel_data_mut`], so a
+//! wrapped ring needs no intermediate copy:
+//!
+//! ```ignore
+//! let samples = reader.get_samples(index, 480, timeout)?;
+//! let mut pcm = [0i16; 480];
+//! float_to_pcm(samples.channel_data(0)?, &mut pcm, Dither::None)?;
+//! ```
+
+use crate::{Error, Result, samples::interleave::Dither};
+
+const F32_SIZE: usize = std::mem::size_of::<f32>();
+
+/// Integer PCM sample type of the conversions.
+pub trait PcmSample: Copy {
+    /// Bits of the sample's range.
+    const BITS: u32;
+
+    fn from_i32(value: i32) -> Self;
+
+    fn to_i32(self) -> i32;
+}
+
+impl PcmSample for i16 {
+    const BITS: u32 = 16;
+
+    fn from_i32(value: i32) -> Self {
+        value as i16
+    }
+
+    fn to_i32(self) -> i32 {
+        i32::from(self)
+    }
+}
+
+impl PcmSample for i32 {
+    const BITS: u32 = 32;
+
+    fn from_i32(value: i32) -> Self {
+        value
+    }
+
+    fn to_i32(self) -> i32 {
+        self
+    }
+}
+
+/// 24-bit sample, right-aligned and sign-extended in an `i32` as in the `S24_LE` format of ALSA.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct I24(pub i32);
+
+impl PcmSample for I24 {
+    const BITS: u32 = 24;
+
+    fn from_i32(value: i32) -> Self {
+        Self(value)
+    }
+
+    fn to_i32(self) -> i32 {
+        self.0
+    }
+}
+
+fn full_scale<T: PcmSample>() -> f64 {
+    f64::from(1u32 << (T::BITS - 1))
+}
+
+fn check_len(samples: usize, fragments: usize) -> Result<()> {
+    if samples != fragments {
+        return Err(Error::Other(format!(
+            "The channel fragments hold {fragments} samples, the PCM buffer {samples}."
+        )));
+    }
+    Ok(())
+}
+
+/// Quantizes the float samples of the channel `fragments` into `output`, which must hold as many
+/// samples. Values outside of the full-scale range are clipped.
+pub fn float_to_pcm<T: PcmSample>(
+    fragments: (&[u8], &[u8]),
+    output: &mut [T],
+    dither: Dither,
+) -> Result<()> {
+    check_len(
+        output.len(),
+        (fragments.0.len() + fragments.1.len()) / F32_SIZE,
+    )?;
+    let full_scale = full_scale::<T>();
+    let inputs = fragments
+        .0
+        .chunks_exact(F32_SIZE)
+        .chain(fragments.1.chunks_exact(F32_SIZE));
+    for (input, output) in inputs.zip(output) {
+        let sample = f32::from_le_bytes([input[0], input[1], input[2], input[3]]);
+        let value = f64::from(sample) * full_scale + dither.sample();
+        *output = T::from_i32(value.round().clamp(-full_scale, full_scale - 1.0) as i32);
+    }
+    Ok(())
+}
+
+/// Converts the integer samples of `input` to float samples in the channel `fragments`, which must
+/// hold as many samples.
+pub fn pcm_to_float<T: PcmSample>(input: &[T], fragments: (&mut [u8], &mut [u8])) -> Result<()> {
+    check_len(
+        input.len(),
+        (fragments.0.len() + fragments.1.len()) / F32_SIZE,
+    )?;
+    let full_scale = full_scale::<T>();
+    let outputs = fragments
+        .0
+        .chunks_exact_mut(F32_SIZE)
+        .chain(fragments.1.chunks_exact_mut(F32_SIZE));
+    for (input, output) in input.iter().zip(outputs) {
+        let value = f64::from(input.to_i32()) / full_scale;
+        output.copy_from_slice(&(value as f32).to_le_bytes());
+    }
+    Ok(())
+}
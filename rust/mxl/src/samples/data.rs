@@ -3,17 +3,30 @@
 
 use std::marker::PhantomData;
 
-use crate::Error;
+use crate::{
+    Error,
+    samples::interleave::{
+        InterleaveOptions, SampleFormat, deinterleave_channel, interleave_channel,
+    },
+};
 
+/// Samples read from a flow, a view into the ring. `Debug` shows the number of channels and samples
+/// rather than the bytes.
+#[derive(Clone)]
 pub struct SamplesData<'a> {
     buffer_slice: mxl_sys::WrappedMultiBufferSlice,
+    format: SampleFormat,
     phantom: PhantomData<&'a ()>,
 }
 
 impl<'a> SamplesData<'a> {
-    pub(crate) fn new(buffer_slice: mxl_sys::WrappedMultiBufferSlice) -> Self {
+    pub(crate) fn new(
+        buffer_slice: mxl_sys::WrappedMultiBufferSlice,
+        format: SampleFormat,
+    ) -> Self {
         Self {
             buffer_slice,
+            format,
             phantom: Default::default(),
         }
     }
@@ -22,6 +35,17 @@ impl<'a> SamplesData<'a> {
         self.buffer_slice.count
     }
 
+    /// Number of samples of each channel.
+    pub fn num_of_samples(&self) -> usize {
+        let fragments = &self.buffer_slice.base.fragments;
+        (fragments[0].size + fragments[1].size) / self.format.bytes_per_sample()
+    }
+
+    /// Format of the samples in [`SamplesData::channel_data`].
+    pub fn sample_format(&self) -> SampleFormat {
+        self.format
+    }
+
     pub fn channel_data(&self, channel: usize) -> crate::Result<(&[u8], &[u8])> {
         if channel >= self.buffer_slice.count {
             return Err(Error::InvalidArg);
@@ -43,6 +67,49 @@ impl<'a> SamplesData<'a> {
     pub fn to_owned(&self) -> OwnedSamplesData {
         self.into()
     }
+
+    /// Interleaves the samples of all channels into `destination`, converted as set by `options`.
+    /// Returns the number of bytes written.
+    pub fn interleave_into(
+        &self,
+        options: &InterleaveOptions,
+        destination: &mut [u8],
+    ) -> crate::Result<usize> {
+        let channels = self.num_of_channels();
+        let mut len = 0;
+        for channel in 0..channels {
+            len = interleave_channel(
+                options,
+                self.format,
+                self.channel_data(channel)?,
+                channel,
+                channels,
+                destination,
+            )?;
+        }
+        Ok(len)
+    }
+
+    /// See [`SamplesData::interleave_into`].
+    pub fn interleave(&self, options: &InterleaveOptions) -> crate::Result<Vec<u8>> {
+        let samples = match self.num_of_channels() {
+            0 => 0,
+            _ => self.num_of_samples(),
+        };
+        let mut interleaved = vec![0; options.interleaved_len(samples, self.num_of_channels())];
+        self.interleave_into(options, &mut interleaved)?;
+        Ok(interleaved)
+    }
+}
+
+impl std::fmt::Debug for SamplesData<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SamplesData")
+            .field("channels", &self.num_of_channels())
+            .field("samples", &self.num_of_samples())
+            .field("format", &self.format)
+            .finish()
+    }
 }
 
 impl<'a> AsRef<SamplesData<'a>> for SamplesData<'a> {
@@ -51,9 +118,90 @@ impl<'a> AsRef<SamplesData<'a>> for SamplesData<'a> {
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct OwnedSamplesData {
     /// Data belonging to each of the channels.
     pub payload: Vec<Vec<u8>>,
+    /// Format of the samples in `payload`.
+    pub format: SampleFormat,
+}
+
+impl std::fmt::Debug for OwnedSamplesData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwnedSamplesData")
+            .field("channels", &self.payload.len())
+            .field("samples", &self.num_of_samples())
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+impl OwnedSamplesData {
+    /// Number of samples of each channel.
+    pub fn num_of_samples(&self) -> usize {
+        self.payload
+            .first()
+            .map_or(0, |channel| channel.len() / self.format.bytes_per_sample())
+    }
+
+    /// Splits the interleaved `source` of `channels` channels into per-channel `audio/float32`
+    /// samples, converted as set by `options`.
+    pub fn deinterleave(
+        source: &[u8],
+        channels: usize,
+        options: &InterleaveOptions,
+    ) -> crate::Result<Self> {
+        Self::deinterleave_as(source, channels, options, SampleFormat::F32)
+    }
+
+    /// Same as [`OwnedSamplesData::deinterleave`], into samples of `format`, e.g. for flows of
+    /// `audio/L16` or `audio/L24`.
+    pub fn deinterleave_as(
+        source: &[u8],
+        channels: usize,
+        options: &InterleaveOptions,
+        format: SampleFormat,
+    ) -> crate::Result<Self> {
+        let frame_len = options.interleaved_len(1, channels);
+        if frame_len == 0 || !source.len().is_multiple_of(frame_len) {
+            return Err(Error::Other(format!(
+                "{} bytes do not hold whole frames of {channels} channels.",
+                source.len()
+            )));
+        }
+        let samples = source.len() / frame_len;
+        let mut payload = Vec::with_capacity(channels);
+        for channel in 0..channels {
+            let mut channel_payload = vec![0; samples * format.bytes_per_sample()];
+            deinterleave_channel(
+                options,
+                format,
+                source,
+                channel,
+                channels,
+                (&mut channel_payload, &mut []),
+            )?;
+            payload.push(channel_payload);
+        }
+        Ok(Self { payload, format })
+    }
+
+    /// Interleaves the samples of all channels, converted as set by `options`.
+    pub fn interleave(&self, options: &InterleaveOptions) -> crate::Result<Vec<u8>> {
+        let channels = self.payload.len();
+        let mut interleaved = vec![0; options.interleaved_len(self.num_of_samples(), channels)];
+        for (channel, channel_payload) in self.payload.iter().enumerate() {
+            interleave_channel(
+                options,
+                self.format,
+                (channel_payload, &[]),
+                channel,
+                channels,
+                &mut interleaved,
+            )?;
+        }
+        Ok(interleaved)
+    }
 }
 
 impl<'a> From<&SamplesData<'a>> for OwnedSamplesData {
@@ -67,7 +215,10 @@ impl<'a> From<&SamplesData<'a>> for OwnedSamplesData {
             channel_payload.extend(data_2);
             payload.push(channel_payload);
         }
-        Self { payload }
+        Self {
+            payload,
+            format: value.format,
+        }
     }
 }
 
@@ -76,3 +227,18 @@ impl<'a> From<SamplesData<'a>> for OwnedSamplesData {
         value.as_ref().into()
     }
 }
+
+/// The samples copied out of the ring by [`crate::SamplesReader::snapshot`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SamplesSnapshot {
+    /// Index of the last sample of `samples`, the head index of the flow when it was copied.
+    pub head_index: u64,
+    pub samples: OwnedSamplesData,
+}
+
+impl SamplesSnapshot {
+    /// Index of the first sample of `samples`.
+    pub fn first_index(&self) -> u64 {
+        (self.head_index + 1).saturating_sub(self.samples.num_of_samples() as u64)
+    }
+}
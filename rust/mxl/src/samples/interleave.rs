@@ -0,0 +1,273 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversions between the per-channel samples of MXL audio flows and interleaved buffers, such as
+//! GStreamer's `layout=interleaved` audio.
+//!
+//! A gain per channel and, when quantizing to an integer format, dither are applied in the same
+//! pass as the (de-)interleaving, so format conversions do not need a second traversal of the data.
+
+use std::cell::Cell;
+
+use crate::{Error, Result};
+
+/// Little-endian sample format of an interleaved buffer or of the channel buffers of a flow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// 32-bit float, the format of `audio/float32` flows.
+    #[default]
+    F32,
+    /// 16-bit signed integer, the format of `audio/L16` flows.
+    S16,
+    /// 24-bit signed integer, packed in 3 bytes, the format of `audio/L24` flows.
+    S24,
+    /// 32-bit signed integer.
+    S32,
+    /// 24-bit signed integer in the low 3 bytes of a sign-extended 32-bit word, e.g. GStreamer's
+    /// `S24_32LE`. Only for interleaved buffers, flows pack 24-bit samples in 3 bytes.
+    S24In32,
+}
+
+impl SampleFormat {
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            Self::F32 | Self::S32 | Self::S24In32 => 4,
+            Self::S16 => 2,
+            Self::S24 => 3,
+        }
+    }
+
+    /// Significant bits of a sample.
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::F32 | Self::S32 => 32,
+            Self::S16 => 16,
+            Self::S24 | Self::S24In32 => 24,
+        }
+    }
+
+    fn encode(self, value: f64, dither: Dither, output: &mut [u8]) {
+        if self == Self::F32 {
+            output.copy_from_slice(&(value as f32).to_le_bytes());
+            return;
+        }
+        // Quantize at the format's scale, the low bytes of the `i32` are the sample, sign-extended
+        // to the sample size.
+        let full_scale = f64::from(1u32 << (self.bits() - 1));
+        let quantized = (value * full_scale + dither.sample())
+            .round()
+            .clamp(-full_scale, full_scale - 1.0) as i32;
+        output.copy_from_slice(&quantized.to_le_bytes()[..self.bytes_per_sample()]);
+    }
+
+    fn decode(self, input: &[u8]) -> f64 {
+        let mut bytes = [0; 4];
+        if self == Self::F32 {
+            bytes.copy_from_slice(input);
+            return f64::from(f32::from_le_bytes(bytes));
+        }
+        // Left-align the significant bytes in an `i32` so every integer format has the same full
+        // scale.
+        let significant = self.bits() as usize / 8;
+        bytes[4 - significant..].copy_from_slice(&input[..significant]);
+        f64::from(i32::from_le_bytes(bytes)) / f64::from(1u32 << 31)
+    }
+}
+
+/// Noise added before quantizing to an integer [`SampleFormat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    #[default]
+    None,
+    /// Triangular probability density noise of up to ±1 LSB, which decorrelates the quantization
+    /// error from the signal.
+    Tpdf,
+}
+
+thread_local! {
+    static DITHER_STATE: Cell<u32> = const { Cell::new(0x9e37_79b9) };
+}
+
+impl Dither {
+    /// Noise in LSB of the target format.
+    pub(crate) fn sample(self) -> f64 {
+        match self {
+            Self::None => 0.0,
+            Self::Tpdf => uniform() - uniform(),
+        }
+    }
+}
+
+/// Xorshift32 in `[0, 1)`. The state is kept per thread so consecutive buffers continue the
+/// sequence rather than repeat it.
+fn uniform() -> f64 {
+    DITHER_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        state.set(x);
+        f64::from(x >> 8) / f64::from(1u32 << 24)
+    })
+}
+
+/// Format and per-channel conversion of the interleave helpers, e.g.
+/// [`crate::SamplesData::interleave`] and [`crate::SamplesWriteAccess::deinterleave_from`].
+///
+/// The default converts `F32` samples without gain or dither.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterleaveOptions {
+    format: SampleFormat,
+    gains: Vec<f32>,
+    dither: Dither,
+}
+
+impl InterleaveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sample format of the interleaved buffer.
+    pub fn format(mut self, format: SampleFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn get_format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// Linear gain of each channel, in channel order. Channels past the end are left at unity.
+    pub fn gains(mut self, gains: impl Into<Vec<f32>>) -> Self {
+        self.gains = gains.into();
+        self
+    }
+
+    /// Dither applied when converting to an integer format: the interleaved format when
+    /// interleaving, the flow's format when de-interleaving. Conversions to float ignore it.
+    pub fn dither(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Size in bytes of the interleaved buffer for `samples` samples of `channels` channels.
+    pub fn interleaved_len(&self, samples: usize, channels: usize) -> usize {
+        samples * channels * self.format.bytes_per_sample()
+    }
+
+    fn gain(&self, channel: usize) -> f64 {
+        self.gains.get(channel).copied().map_or(1.0, f64::from)
+    }
+}
+
+/// Writes the `flow_format` samples of `channel`, split over the ring fragments `planes`, to their
+/// slots in the interleaved `destination` of `channels` channels. Returns the interleaved size.
+pub(crate) fn interleave_channel(
+    options: &InterleaveOptions,
+    flow_format: SampleFormat,
+    planes: (&[u8], &[u8]),
+    channel: usize,
+    channels: usize,
+    destination: &mut [u8],
+) -> Result<usize> {
+    let bytes_per_sample = options.format.bytes_per_sample();
+    let flow_bytes_per_sample = flow_format.bytes_per_sample();
+    let samples = (planes.0.len() + planes.1.len()) / flow_bytes_per_sample;
+    let len = options.interleaved_len(samples, channels);
+    if destination.len() < len {
+        return Err(Error::Other(format!(
+            "Interleaving {samples} samples of {channels} channels takes {len} bytes, got {}.",
+            destination.len()
+        )));
+    }
+
+    let gain = options.gain(channel);
+    let inputs = planes
+        .0
+        .chunks_exact(flow_bytes_per_sample)
+        .chain(planes.1.chunks_exact(flow_bytes_per_sample));
+    let outputs = destination[..len]
+        .chunks_exact_mut(channels * bytes_per_sample)
+        .map(|frame| &mut frame[channel * bytes_per_sample..(channel + 1) * bytes_per_sample]);
+    for (input, output) in inputs.zip(outputs) {
+        let value = flow_format.decode(input) * gain;
+        options.format.encode(value, options.dither, output);
+    }
+    Ok(len)
+}
+
+/// Reads the samples of `channel` from the interleaved `source` of `channels` channels into the
+/// ring fragments `planes` as `flow_format` samples. The fragments must hold as many samples as
+/// `source` has frames.
+pub(crate) fn deinterleave_channel(
+    options: &InterleaveOptions,
+    flow_format: SampleFormat,
+    source: &[u8],
+    channel: usize,
+    channels: usize,
+    planes: (&mut [u8], &mut [u8]),
+) -> Result<()> {
+    let bytes_per_sample = options.format.bytes_per_sample();
+    let flow_bytes_per_sample = flow_format.bytes_per_sample();
+    let samples = (planes.0.len() + planes.1.len()) / flow_bytes_per_sample;
+    let len = options.interleaved_len(samples, channels);
+    if source.len() != len {
+        return Err(Error::Other(format!(
+            "{samples} samples of {channels} channels take {len} interleaved bytes, got {}.",
+            source.len()
+        )));
+    }
+
+    let gain = options.gain(channel);
+    let inputs = source
+        .chunks_exact(channels * bytes_per_sample)
+        .map(|frame| &frame[channel * bytes_per_sample..(channel + 1) * bytes_per_sample]);
+    let outputs = planes
+        .0
+        .chunks_exact_mut(flow_bytes_per_sample)
+        .chain(planes.1.chunks_exact_mut(flow_bytes_per_sample));
+    for (input, output) in inputs.zip(outputs) {
+        let value = options.format.decode(input) * gain;
+        flow_format.encode(value, options.dither, output);
+    }
+    Ok(())
+}
+
+/// Writes the `source` samples of one channel, e.g. a plane of a non-interleaved buffer, to the
+/// ring fragments `planes` as `flow_format` samples, with the gain of `channel`. The bytes are
+/// copied as they are when there is nothing to convert.
+pub(crate) fn write_channel(
+    options: &InterleaveOptions,
+    flow_format: SampleFormat,
+    source: &[u8],
+    channel: usize,
+    planes: (&mut [u8], &mut [u8]),
+) -> Result<()> {
+    let bytes_per_sample = options.format.bytes_per_sample();
+    let flow_bytes_per_sample = flow_format.bytes_per_sample();
+    let samples = (planes.0.len() + planes.1.len()) / flow_bytes_per_sample;
+    if source.len() != samples * bytes_per_sample {
+        return Err(Error::Other(format!(
+            "{samples} samples of a channel take {} bytes, got {}.",
+            samples * bytes_per_sample,
+            source.len()
+        )));
+    }
+
+    let gain = options.gain(channel);
+    if options.format == flow_format && gain == 1.0 && options.dither == Dither::None {
+        let (first, second) = source.split_at(planes.0.len());
+        planes.0.copy_from_slice(first);
+        planes.1.copy_from_slice(second);
+        return Ok(());
+    }
+    let outputs = planes
+        .0
+        .chunks_exact_mut(flow_bytes_per_sample)
+        .chain(planes.1.chunks_exact_mut(flow_bytes_per_sample));
+    for (input, output) in source.chunks_exact(bytes_per_sample).zip(outputs) {
+        let value = options.format.decode(input) * gain;
+        flow_format.encode(value, options.dither, output);
+    }
+    Ok(())
+}
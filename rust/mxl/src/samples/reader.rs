@@ -4,26 +4,67 @@
 use std::{sync::Arc, time::Duration};
 
 use crate::{
-    Error, Result, SamplesData,
+    AccessOptions, Error, OwnedSamplesData, Result, SampleFormat, SamplesData, SamplesSnapshot,
     flow::{
-        FlowConfigInfo, FlowInfo,
+        FlowConfigInfo, FlowInfo, FlowRuntimeInfo,
         reader::{get_config_info, get_flow_info, get_runtime_info},
     },
     instance::InstanceContext,
+    instrument::{Media, ReaderMetrics, ffi_call},
+    leaks::{LiveObjectKind, Tracked},
 };
 
+/// Copies of the latest samples tried by [`SamplesReader::peek_latest`] and
+/// [`SamplesReader::snapshot`] before giving up.
+const PEEK_ATTEMPTS: usize = 3;
+
 pub struct SamplesReader {
     context: Arc<InstanceContext>,
     reader: mxl_sys::FlowReader,
+    id: uuid::Uuid,
+    format: SampleFormat,
+    metrics: ReaderMetrics,
+    _tracked: Tracked,
 }
 
 /// The MXL readers and writers are not thread-safe, so we do not implement `Sync` for them, but
 /// there is no reason to not implement `Send`.
 unsafe impl Send for SamplesReader {}
 
+impl std::fmt::Debug for SamplesReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SamplesReader")
+            .field("flow_id", &self.id)
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
+
 impl SamplesReader {
-    pub(crate) fn new(context: Arc<InstanceContext>, reader: mxl_sys::FlowReader) -> Self {
-        Self { context, reader }
+    pub(crate) fn new(
+        context: Arc<InstanceContext>,
+        reader: mxl_sys::FlowReader,
+        id: uuid::Uuid,
+        format: SampleFormat,
+    ) -> Self {
+        Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::SamplesReader, id),
+            context,
+            reader,
+            id,
+            format,
+            metrics: ReaderMetrics::new(&id, Media::Samples),
+        }
+    }
+
+    pub fn flow_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    /// Format of the samples in the channel buffers, see
+    /// [`crate::flowdef::FlowDefAudio::sample_format`].
+    pub fn sample_format(&self) -> SampleFormat {
+        self.format
     }
 
     pub fn destroy(mut self) -> Result<()> {
@@ -33,15 +74,24 @@ impl SamplesReader {
     /// The whole FlowInfo is quite a chunk of data. Go for `get_config_info` or `get_runtime_info`
     /// if they contain what you need.
     pub fn get_info(&self) -> Result<FlowInfo> {
-        get_flow_info(&self.context, self.reader)
+        get_flow_info(&self.context, self.reader, &self.id)
     }
 
     pub fn get_config_info(&self) -> Result<FlowConfigInfo> {
-        get_config_info(&self.context, self.reader)
+        get_config_info(&self.context, self.reader, &self.id)
     }
 
     pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
-        get_runtime_info(&self.context, self.reader)
+        get_runtime_info(&self.context, self.reader, &self.id)
+    }
+
+    /// See [`FlowRuntimeInfo::is_stale`].
+    pub fn is_flow_stale(&self, threshold: Duration) -> Result<bool> {
+        let runtime = FlowRuntimeInfo {
+            value: self.get_runtime_info()?,
+        };
+        let now = ffi_call!("mxlGetTime"; unsafe { self.context.api.get_time() });
+        Ok(runtime.is_stale(now, threshold))
     }
 
     pub fn get_samples(
@@ -52,29 +102,104 @@ impl SamplesReader {
     ) -> Result<SamplesData<'_>> {
         let timeout_ns = timeout.as_nanos() as u64;
         let mut buffer_slice: mxl_sys::WrappedMultiBufferSlice = unsafe { std::mem::zeroed() };
-        unsafe {
-            Error::from_status(self.context.api.flow_reader_get_samples(
-                self.reader,
-                index,
-                count,
-                timeout_ns,
-                &mut buffer_slice,
-            ))?;
-        }
-        Ok(SamplesData::new(buffer_slice))
+        let result = Error::from_status(ffi_call!(
+            "mxlFlowReaderGetSamples", flow = self.id, index = index, count = count; unsafe {
+                self.context.api.flow_reader_get_samples(
+                    self.reader,
+                    index,
+                    count,
+                    timeout_ns,
+                    &mut buffer_slice,
+                )
+            }
+        ))
+        .map(|()| SamplesData::new(buffer_slice, self.format));
+        self.record_read(index, count, result)
+    }
+
+    /// Same as [`SamplesReader::get_samples`], with per-access options.
+    pub fn get_samples_with_options(
+        &self,
+        index: u64,
+        count: usize,
+        timeout: Duration,
+        options: &AccessOptions,
+    ) -> Result<SamplesData<'_>> {
+        options.warn_unsupported("get_samples");
+        self.get_samples(index, count, timeout)
     }
 
     pub fn get_samples_non_blocking(&self, index: u64, count: usize) -> Result<SamplesData<'_>> {
         let mut buffer_slice: mxl_sys::WrappedMultiBufferSlice = unsafe { std::mem::zeroed() };
-        unsafe {
-            Error::from_status(self.context.api.flow_reader_get_samples_non_blocking(
-                self.reader,
-                index,
-                count,
-                &mut buffer_slice,
-            ))?;
+        let result = Error::from_status(ffi_call!(
+            "mxlFlowReaderGetSamplesNonBlocking", flow = self.id, index = index, count = count;
+            unsafe {
+                self.context.api.flow_reader_get_samples_non_blocking(
+                    self.reader,
+                    index,
+                    count,
+                    &mut buffer_slice,
+                )
+            }
+        ))
+        .map(|()| SamplesData::new(buffer_slice, self.format));
+        self.record_read(index, count, result)
+    }
+
+    /// Copies the latest `count` samples of every channel, ending at the flow's head index, for
+    /// consumers such as meters that do not track indices.
+    ///
+    /// The writer keeps writing while the samples are copied. The copy is only returned if the head
+    /// index afterwards shows the writer cannot have reached the copied range, otherwise the peek
+    /// is retried a few times before failing.
+    pub fn peek_latest(&self, count: usize) -> Result<OwnedSamplesData> {
+        let config = self.get_config_info()?;
+        self.copy_latest(&config, count)
+            .map(|snapshot| snapshot.samples)
+    }
+
+    /// Copies all samples still readable, the half of the ring up to the head index, e.g. to look
+    /// into what a consumer received when it reported a glitch. See
+    /// [`SamplesReader::peek_latest`] for how overwrites are detected.
+    pub fn snapshot(&self) -> Result<SamplesSnapshot> {
+        let config = self.get_config_info()?;
+        let readable = config.continuous()?.bufferLength as usize / 2;
+        self.copy_latest(&config, readable)
+    }
+
+    fn copy_latest(&self, config: &FlowConfigInfo, count: usize) -> Result<SamplesSnapshot> {
+        let buffer_length = u64::from(config.continuous()?.bufferLength);
+        // The writer may fill up to one commit batch past the head before committing it.
+        let in_flight = u64::from(config.common().max_commit_batch_size_hint());
+        if count as u64 + in_flight > buffer_length {
+            return Err(Error::Other(format!(
+                "Cannot peek {count} samples of a ring of {buffer_length} with batches of {in_flight}."
+            )));
+        }
+
+        for _ in 0..PEEK_ATTEMPTS {
+            let head = self.get_runtime_info()?.headIndex;
+            let samples = self.get_samples_non_blocking(head, count)?.to_owned();
+            let head_after = self.get_runtime_info()?.headIndex;
+            // The writer only overwrote the copied range if it moved `buffer_length` past the
+            // oldest copied sample.
+            if head_after + in_flight + count as u64 <= head + buffer_length {
+                return Ok(SamplesSnapshot {
+                    head_index: head,
+                    samples,
+                });
+            }
         }
-        Ok(SamplesData::new(buffer_slice))
+        Err(Error::Other(format!(
+            "The writer overwrote the latest {count} samples while they were peeked."
+        )))
+    }
+
+    fn record_read<T>(&self, index: u64, count: usize, result: Result<T>) -> Result<T> {
+        self.metrics.record(&result, index, count, || {
+            Ok(self.get_runtime_info()?.headIndex)
+        });
+        result
     }
 
     fn destroy_inner(&mut self) -> Result<()> {
@@ -85,11 +210,15 @@ impl SamplesReader {
         let mut reader = std::ptr::null_mut();
         std::mem::swap(&mut self.reader, &mut reader);
 
-        Error::from_status(unsafe {
+        // A cleanup guard may have released it already.
+        if !self.context.open_handles.reader_closing(reader) {
+            return Ok(());
+        }
+        Error::from_status(ffi_call!("mxlReleaseFlowReader", flow = self.id; unsafe {
             self.context
                 .api
                 .release_flow_reader(self.context.instance, reader)
-        })
+        }))
     }
 }
 
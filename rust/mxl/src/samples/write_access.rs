@@ -5,7 +5,13 @@ use std::{marker::PhantomData, sync::Arc};
 
 use tracing::error;
 
-use crate::{Error, instance::InstanceContext};
+use crate::{
+    Error,
+    instance::InstanceContext,
+    instrument::{WriterMetrics, ffi_call},
+    leaks::{LiveObjectKind, Tracked},
+    samples::interleave::{InterleaveOptions, SampleFormat, deinterleave_channel, write_channel},
+};
 
 /// RAII samples writing session
 ///
@@ -16,21 +22,39 @@ use crate::{Error, instance::InstanceContext};
 pub struct SamplesWriteAccess<'a> {
     context: Arc<InstanceContext>,
     writer: mxl_sys::FlowWriter,
+    flow_id: uuid::Uuid,
+    metrics: WriterMetrics,
+    index: u64,
+    count: usize,
+    format: SampleFormat,
     buffer_slice: mxl_sys::MutableWrappedMultiBufferSlice,
     /// Serves as a flag to know whether to cancel the samples on drop.
     committed_or_canceled: bool,
     phantom: PhantomData<&'a ()>,
+    _tracked: Tracked,
 }
 
 impl<'a> SamplesWriteAccess<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         context: Arc<InstanceContext>,
         writer: mxl_sys::FlowWriter,
+        flow_id: uuid::Uuid,
+        metrics: WriterMetrics,
+        index: u64,
+        count: usize,
+        format: SampleFormat,
         buffer_slice: mxl_sys::MutableWrappedMultiBufferSlice,
     ) -> Self {
         Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::SamplesWriteAccess, flow_id),
             context,
             writer,
+            flow_id,
+            metrics,
+            index,
+            count,
+            format,
             buffer_slice,
             committed_or_canceled: false,
             phantom: PhantomData,
@@ -40,7 +64,14 @@ impl<'a> SamplesWriteAccess<'a> {
     pub fn commit(mut self) -> crate::Result<()> {
         self.committed_or_canceled = true;
 
-        unsafe { Error::from_status(self.context.api.flow_writer_commit_samples(self.writer)) }
+        Error::from_status(ffi_call!(
+            "mxlFlowWriterCommitSamples", flow = self.flow_id, index = self.index,
+            count = self.count; unsafe {
+                self.context.api.flow_writer_commit_samples(self.writer)
+            }
+        ))?;
+        self.metrics.record(self.index, self.count);
+        Ok(())
     }
 
     /// Please note that the behavior of canceling samples writing is dependent on the behavior
@@ -50,20 +81,33 @@ impl<'a> SamplesWriteAccess<'a> {
     pub fn cancel(mut self) -> crate::Result<()> {
         self.committed_or_canceled = true;
 
-        unsafe { Error::from_status(self.context.api.flow_writer_cancel_samples(self.writer)) }
+        self.cancel_inner()
+    }
+
+    fn cancel_inner(&self) -> crate::Result<()> {
+        Error::from_status(
+            ffi_call!("mxlFlowWriterCancelSamples", flow = self.flow_id; unsafe {
+                self.context.api.flow_writer_cancel_samples(self.writer)
+            }),
+        )
     }
 
     pub fn channels(&self) -> usize {
         self.buffer_slice.count
     }
 
+    /// Format of the samples in [`SamplesWriteAccess::channel_data_mut`].
+    pub fn sample_format(&self) -> SampleFormat {
+        self.format
+    }
+
     /// Provides direct access to buffer of the given channel. The access is split into two slices
     /// to cover cases when the ring is not continuous.
     ///
     /// Currently, we provide just raw bytes access. Probably we should provide some sample-based
     /// access and some index-based access (where we hide the complexity of 2 slices) as well?
     ///
-    /// Samples are f32?
+    /// Samples are stored in [`SamplesWriteAccess::sample_format`].
     pub fn channel_data_mut(&mut self, channel: usize) -> crate::Result<(&mut [u8], &mut [u8])> {
         if channel >= self.buffer_slice.count {
             return Err(Error::InvalidArg);
@@ -81,14 +125,64 @@ impl<'a> SamplesWriteAccess<'a> {
             ))
         }
     }
+
+    /// Fills every channel from the interleaved `source`, converted as set by `options`. `source`
+    /// must hold exactly the number of samples opened.
+    pub fn deinterleave_from(
+        &mut self,
+        source: &[u8],
+        options: &InterleaveOptions,
+    ) -> crate::Result<()> {
+        let channels = self.channels();
+        for channel in 0..channels {
+            deinterleave_channel(
+                options,
+                self.format,
+                source,
+                channel,
+                channels,
+                self.channel_data_mut(channel)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Fills `channel` from `source`, the samples of that channel alone, e.g. a plane of a
+    /// non-interleaved buffer, converted as set by `options`. `source` must hold exactly the
+    /// number of samples opened. Without a conversion to make, the bytes are copied as they are.
+    pub fn write_channel_from(
+        &mut self,
+        channel: usize,
+        source: &[u8],
+        options: &InterleaveOptions,
+    ) -> crate::Result<()> {
+        let format = self.format;
+        write_channel(
+            options,
+            format,
+            source,
+            channel,
+            self.channel_data_mut(channel)?,
+        )
+    }
+}
+
+impl std::fmt::Debug for SamplesWriteAccess<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SamplesWriteAccess")
+            .field("flow_id", &self.flow_id)
+            .field("index", &self.index)
+            .field("count", &self.count)
+            .field("channels", &self.buffer_slice.count)
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a> Drop for SamplesWriteAccess<'a> {
     fn drop(&mut self) {
         if !self.committed_or_canceled
-            && let Err(error) = unsafe {
-                Error::from_status(self.context.api.flow_writer_cancel_samples(self.writer))
-            }
+            && let Err(error) = self.cancel_inner()
         {
             error!("Failed to cancel grain write on drop: {:?}", error);
         }
@@ -3,45 +3,132 @@
 
 use std::sync::Arc;
 
-use crate::{Error, Result, SamplesWriteAccess, instance::InstanceContext};
+use crate::{
+    AccessOptions, Error, FlowConfigInfo, Result, SampleFormat, SamplesWriteAccess,
+    flow::{
+        FlowInfo,
+        writer::{get_flow_info, get_runtime_info},
+    },
+    instance::InstanceContext,
+    instrument::{Media, WriterMetrics, ffi_call},
+    leaks::{LiveObjectKind, Tracked},
+};
 
 /// MXL Flow Writer for continuous flows (samples-based data like audio)
 pub struct SamplesWriter {
     context: Arc<InstanceContext>,
     writer: mxl_sys::FlowWriter,
+    id: uuid::Uuid,
+    format: SampleFormat,
+    config: FlowConfigInfo,
+    metrics: WriterMetrics,
+    _tracked: Tracked,
 }
 
 /// The MXL readers and writers are not thread-safe, so we do not implement `Sync` for them, but
 /// there is no reason to not implement `Send`.
 unsafe impl Send for SamplesWriter {}
 
+impl std::fmt::Debug for SamplesWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SamplesWriter")
+            .field("flow_id", &self.id)
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
+
 impl SamplesWriter {
-    pub(crate) fn new(context: Arc<InstanceContext>, writer: mxl_sys::FlowWriter) -> Self {
-        Self { context, writer }
+    pub(crate) fn new(
+        context: Arc<InstanceContext>,
+        writer: mxl_sys::FlowWriter,
+        config: FlowConfigInfo,
+        format: SampleFormat,
+    ) -> Self {
+        let id = config.common().id();
+        Self {
+            _tracked: Tracked::new(&context, LiveObjectKind::SamplesWriter, id),
+            context,
+            writer,
+            id,
+            format,
+            config,
+            metrics: WriterMetrics::new(&id, Media::Samples),
+        }
+    }
+
+    pub fn flow_id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    /// Format of the samples in the channel buffers, see
+    /// [`crate::flowdef::FlowDefAudio::sample_format`].
+    pub fn sample_format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// See [`crate::FlowWriter::config_info`].
+    pub fn config_info(&self) -> &FlowConfigInfo {
+        &self.config
     }
 
     pub fn destroy(mut self) -> Result<()> {
         self.destroy_inner()
     }
 
-    pub fn open_samples<'a>(&'a self, index: u64, count: usize) -> Result<SamplesWriteAccess<'a>> {
+    /// The flow as the writer sees it, without a reader that would count as one of its consumers.
+    /// The whole FlowInfo is quite a chunk of data. Go for `config_info` or `get_runtime_info` if
+    /// they contain what you need.
+    pub fn get_info(&self) -> Result<FlowInfo> {
+        get_flow_info(&self.context, self.writer, &self.id)
+    }
+
+    pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
+        get_runtime_info(&self.context, self.writer, &self.id)
+    }
+
+    /// The MXL writer supports a single open sample range at a time, so the returned session
+    /// mutably borrows the writer until it is committed, canceled or dropped.
+    pub fn open_samples<'a>(
+        &'a mut self,
+        index: u64,
+        count: usize,
+    ) -> Result<SamplesWriteAccess<'a>> {
         let mut buffer_slice: mxl_sys::MutableWrappedMultiBufferSlice =
             unsafe { std::mem::zeroed() };
-        unsafe {
-            Error::from_status(self.context.api.flow_writer_open_samples(
-                self.writer,
-                index,
-                count,
-                &mut buffer_slice,
-            ))?;
-        }
+        Error::from_status(ffi_call!(
+            "mxlFlowWriterOpenSamples", flow = self.id, index = index, count = count; unsafe {
+                self.context.api.flow_writer_open_samples(
+                    self.writer,
+                    index,
+                    count,
+                    &mut buffer_slice,
+                )
+            }
+        ))?;
         Ok(SamplesWriteAccess::new(
             self.context.clone(),
             self.writer,
+            self.id,
+            self.metrics.clone(),
+            index,
+            count,
+            self.format,
             buffer_slice,
         ))
     }
 
+    /// Same as [`SamplesWriter::open_samples`], with per-access options.
+    pub fn open_samples_with_options<'a>(
+        &'a mut self,
+        index: u64,
+        count: usize,
+        options: &AccessOptions,
+    ) -> Result<SamplesWriteAccess<'a>> {
+        options.warn_unsupported("open_samples");
+        self.open_samples(index, count)
+    }
+
     fn destroy_inner(&mut self) -> Result<()> {
         if self.writer.is_null() {
             return Err(Error::InvalidArg);
@@ -50,11 +137,18 @@ impl SamplesWriter {
         let mut writer = std::ptr::null_mut();
         std::mem::swap(&mut self.writer, &mut writer);
 
-        Error::from_status(unsafe {
-            self.context
-                .api
-                .release_flow_writer(self.context.instance, writer)
-        })
+        // A cleanup guard may have released it already.
+        let result = if self.context.open_handles.writer_closing(writer) {
+            Error::from_status(ffi_call!("mxlReleaseFlowWriter", flow = self.id; unsafe {
+                self.context
+                    .api
+                    .release_flow_writer(self.context.instance, writer)
+            }))
+        } else {
+            Ok(())
+        };
+        self.context.flow_writer_released(&self.id);
+        result
     }
 }
 
@@ -0,0 +1,336 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Background reading of flows.
+//!
+//! A [`ReaderService`] runs a thread per flow that follows the flow's head and hands every new
+//! grain, or batch of samples, to a callback or a channel. The thread takes care of what every
+//! consumer loop has to: attaching live, catching up when it falls behind the ring, reporting
+//! writers that stopped, and stopping when the service is dropped.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use crate::{
+    Error, FlowId, FlowReader, GrainReader, MxlInstance, OwnedGrainData, OwnedSamplesData, Result,
+    SamplesReader, TypedReader,
+};
+
+/// Longest blocking wait of a flow thread, which bounds how long removing a flow takes.
+const WAIT_TIMEOUT: Duration = Duration::from_millis(100);
+pub const DEFAULT_STALE_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// What a [`ReaderService`] reports for a flow.
+#[derive(Debug)]
+pub enum FlowEvent {
+    Grain {
+        index: u64,
+        grain: OwnedGrainData,
+    },
+    /// Samples up to and including `index`.
+    Samples {
+        index: u64,
+        samples: OwnedSamplesData,
+    },
+    /// The reader fell behind the ring and jumped from index `from` to the head at `to`, the
+    /// grains or samples in between were not delivered.
+    Skipped {
+        from: u64,
+        to: u64,
+    },
+    /// Nothing was written for longer than the stale threshold.
+    Stale,
+    /// The writer is writing again after [`FlowEvent::Stale`].
+    Resumed,
+    /// The flow cannot be read anymore, typically because it was recreated. This is the last event
+    /// of the flow.
+    Closed(Error),
+}
+
+/// Where a flow thread delivers its events.
+enum Sink {
+    Callback(Box<dyn FnMut(FlowEvent) + Send>),
+    Channel(crossbeam_channel::Sender<FlowEvent>),
+}
+
+impl Sink {
+    /// Returns false once nobody listens anymore.
+    fn deliver(&mut self, event: FlowEvent) -> bool {
+        match self {
+            Sink::Callback(callback) => {
+                callback(event);
+                true
+            }
+            Sink::Channel(sender) => sender.send(event).is_ok(),
+        }
+    }
+}
+
+struct Worker {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+/// Reads flows on background threads, see the [module documentation](self).
+pub struct ReaderService {
+    instance: MxlInstance,
+    stale_threshold: Duration,
+    samples_batch: Option<usize>,
+    workers: HashMap<FlowId, Worker>,
+}
+
+impl std::fmt::Debug for ReaderService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReaderService")
+            .field("instance", &self.instance)
+            .field("stale_threshold", &self.stale_threshold)
+            .field("samples_batch", &self.samples_batch)
+            .field("flows", &self.workers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ReaderService {
+    pub fn new(instance: MxlInstance) -> Self {
+        Self {
+            instance,
+            stale_threshold: DEFAULT_STALE_THRESHOLD,
+            samples_batch: None,
+            workers: HashMap::new(),
+        }
+    }
+
+    /// Time without writes after which a flow is reported [`FlowEvent::Stale`]. Applies to flows
+    /// added afterwards.
+    pub fn stale_threshold(mut self, threshold: Duration) -> Self {
+        self.stale_threshold = threshold;
+        self
+    }
+
+    /// Number of samples per [`FlowEvent::Samples`]. Defaults to the flow's commit batch size hint.
+    /// Applies to flows added afterwards.
+    pub fn samples_batch(mut self, count: usize) -> Self {
+        self.samples_batch = Some(count.max(1));
+        self
+    }
+
+    /// Starts reading `flow_id`, calling `callback` on the flow's thread for every event.
+    pub fn add_flow(
+        &mut self,
+        flow_id: impl TryInto<FlowId, Error: Into<Error>>,
+        callback: impl FnMut(FlowEvent) + Send + 'static,
+    ) -> Result<()> {
+        self.start(
+            flow_id.try_into().map_err(Into::into)?,
+            Sink::Callback(Box::new(callback)),
+        )
+    }
+
+    /// Starts reading `flow_id` and returns the channel its events are sent to. The flow stops
+    /// when the receiver is dropped; the channel is unbounded, so a slow receiver is never
+    /// skipped ahead.
+    pub fn subscribe(
+        &mut self,
+        flow_id: impl TryInto<FlowId, Error: Into<Error>>,
+    ) -> Result<crossbeam_channel::Receiver<FlowEvent>> {
+        let flow_id = flow_id.try_into().map_err(Into::into)?;
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        self.start(flow_id, Sink::Channel(sender))?;
+        Ok(receiver)
+    }
+
+    /// Stops reading `flow_id`, waiting for its thread to finish.
+    pub fn remove_flow(&mut self, flow_id: impl TryInto<FlowId, Error: Into<Error>>) -> Result<()> {
+        let flow_id = flow_id.try_into().map_err(Into::into)?;
+        let worker = self
+            .workers
+            .remove(&flow_id)
+            .ok_or_else(|| Error::Other(format!("Flow {flow_id} is not read by the service.")))?;
+        stop(worker);
+        Ok(())
+    }
+
+    /// Flows being read.
+    pub fn flows(&self) -> impl Iterator<Item = FlowId> + '_ {
+        self.workers.keys().copied()
+    }
+
+    fn start(&mut self, flow_id: FlowId, sink: Sink) -> Result<()> {
+        if self.workers.contains_key(&flow_id) {
+            return Err(Error::Other(format!(
+                "Flow {flow_id} is already read by the service."
+            )));
+        }
+        let reader = FlowFollower::new(
+            self.instance.create_flow_reader(flow_id)?,
+            self.stale_threshold,
+            self.samples_batch,
+        )?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::Builder::new()
+            .name(format!("mxl-reader-{flow_id}"))
+            .spawn({
+                let stop = stop.clone();
+                move || reader.run(sink, &stop)
+            })?;
+        self.workers.insert(flow_id, Worker { stop, thread });
+        Ok(())
+    }
+}
+
+fn stop(worker: Worker) {
+    worker.stop.store(true, Ordering::Release);
+    if worker.thread.join().is_err() {
+        tracing::error!("A reader service thread panicked.");
+    }
+}
+
+impl Drop for ReaderService {
+    fn drop(&mut self) {
+        for (_, worker) in self.workers.drain() {
+            stop(worker);
+        }
+    }
+}
+
+enum Media {
+    Grains(GrainReader),
+    Samples { reader: SamplesReader, batch: usize },
+}
+
+/// Reading state of a flow thread.
+struct FlowFollower {
+    media: Media,
+    stale_threshold: Duration,
+    /// Next grain index, or the last sample index of the next batch. `None` until attached.
+    next: Option<u64>,
+    stale: bool,
+}
+
+impl FlowFollower {
+    fn new(reader: FlowReader, stale_threshold: Duration, batch: Option<usize>) -> Result<Self> {
+        let media = match reader.into_typed()? {
+            TypedReader::Grain(reader) => Media::Grains(reader),
+            TypedReader::Samples(reader) => {
+                let batch = match batch {
+                    Some(batch) => batch,
+                    None => reader
+                        .get_config_info()?
+                        .common()
+                        .max_commit_batch_size_hint() as usize,
+                };
+                Media::Samples {
+                    reader,
+                    batch: batch.max(1),
+                }
+            }
+        };
+        Ok(Self {
+            media,
+            stale_threshold,
+            next: None,
+            stale: false,
+        })
+    }
+
+    fn run(mut self, mut sink: Sink, stop: &AtomicBool) {
+        while !stop.load(Ordering::Acquire) {
+            match self.step() {
+                Ok(events) => {
+                    if !events.into_iter().all(|event| sink.deliver(event)) {
+                        return;
+                    }
+                }
+                Err(error) => {
+                    sink.deliver(FlowEvent::Closed(error));
+                    return;
+                }
+            }
+        }
+    }
+
+    fn head_index(&self) -> Result<u64> {
+        Ok(match &self.media {
+            Media::Grains(reader) => reader.get_runtime_info()?.headIndex,
+            Media::Samples { reader, .. } => reader.get_runtime_info()?.headIndex,
+        })
+    }
+
+    /// Waits for the next grain or batch. Returns the events to deliver; errors end the flow.
+    fn step(&mut self) -> Result<Vec<FlowEvent>> {
+        let next = match self.next {
+            Some(next) => next,
+            None => {
+                let head = self.head_index()?;
+                if head == 0 {
+                    // The writer has not committed anything yet.
+                    std::thread::sleep(WAIT_TIMEOUT);
+                    return Ok(vec![]);
+                }
+                // Attach live, at the head grain or the batch ending at the head.
+                head
+            }
+        };
+        let read =
+            match &self.media {
+                Media::Grains(reader) => {
+                    reader
+                        .get_complete_grain(next, WAIT_TIMEOUT)
+                        .map(|grain| FlowEvent::Grain {
+                            index: next,
+                            grain: OwnedGrainData::from(&grain),
+                        })
+                }
+                Media::Samples { reader, batch } => reader
+                    .get_samples(next, *batch, WAIT_TIMEOUT)
+                    .map(|samples| FlowEvent::Samples {
+                        index: next,
+                        samples: samples.to_owned(),
+                    }),
+            };
+        let mut events = vec![];
+        match read {
+            Ok(event) => {
+                if std::mem::take(&mut self.stale) {
+                    events.push(FlowEvent::Resumed);
+                }
+                events.push(event);
+                self.next = Some(next + self.stride());
+            }
+            Err(Error::Timeout | Error::OutOfRangeTooEarly) => {
+                self.next = Some(next);
+                let stale = match &self.media {
+                    Media::Grains(reader) => reader.is_flow_stale(self.stale_threshold)?,
+                    Media::Samples { reader, .. } => reader.is_flow_stale(self.stale_threshold)?,
+                };
+                if stale && !self.stale {
+                    events.push(FlowEvent::Stale);
+                }
+                self.stale = stale;
+            }
+            Err(Error::OutOfRangeTooLate) => {
+                let to = self.head_index()?;
+                tracing::debug!("Fell behind the ring, skipping from {next} to {to}");
+                events.push(FlowEvent::Skipped { from: next, to });
+                self.next = Some(to);
+            }
+            Err(error) => return Err(error),
+        }
+        Ok(events)
+    }
+
+    /// Distance between consecutive reads.
+    fn stride(&self) -> u64 {
+        match &self.media {
+            Media::Grains(_) => 1,
+            Media::Samples { batch, .. } => *batch as u64,
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! SMPTE ST 2110 packing and pacing parameters of MXL flows.
+//!
+//! Gateways bridging MXL and ST 2110 need the packet counts of ST 2110-20 (video) and
+//! ST 2110-30 (audio) and the sender timing model of ST 2110-21. These helpers derive them from a
+//! flow definition, along with the parameters of the SDP `a=fmtp` line.
+//!
+//! Video uses the general packing mode (`2110GPM`) with packets that never span lines. The
+//! ST 2110-21 active ratio and read offset are those of the 1080 and 720 line formats; other
+//! formats use `RACTIVE = 1` and `TRO = 0`.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, Result,
+    flow::flowdef::{FlowDefAudio, FlowDefVideo, InterlaceMode, Rate},
+};
+
+/// Maximum UDP payload of ST 2110-10 standard UDP size limit senders.
+pub const STANDARD_UDP_SIZE_LIMIT: usize = 1460;
+/// Maximum UDP payload of ST 2110-10 extended UDP size limit senders.
+pub const EXTENDED_UDP_SIZE_LIMIT: usize = 8960;
+
+const RTP_HEADER_SIZE: usize = 12;
+/// Extended sequence number and one sample row data header of ST 2110-20.
+const VIDEO_PAYLOAD_HEADER_SIZE: usize = 2 + 6;
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// ST 2110-21 sender type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SenderType {
+    /// Narrow gapped (`2110TPN`).
+    #[serde(rename = "2110TPN")]
+    Narrow,
+    /// Narrow linear (`2110TPNL`).
+    #[serde(rename = "2110TPNL")]
+    NarrowLinear,
+    /// Wide (`2110TPW`).
+    #[serde(rename = "2110TPW")]
+    Wide,
+}
+
+impl SenderType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Narrow => "2110TPN",
+            Self::NarrowLinear => "2110TPNL",
+            Self::Wide => "2110TPW",
+        }
+    }
+}
+
+/// ST 2110-20 packing of a video flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VideoPacking {
+    /// Octets of a pixel group.
+    pub pgroup_octets: usize,
+    /// Pixels of a pixel group.
+    pub pgroup_pixels: usize,
+    pub octets_per_line: usize,
+    /// Sample data octets of a full packet.
+    pub octets_per_packet: usize,
+    pub packets_per_line: usize,
+    /// Packets per frame, both fields for interlaced video.
+    pub packets_per_frame: usize,
+}
+
+/// ST 2110-21 timing of a video sender.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VideoPacing {
+    pub sender_type: SenderType,
+    /// `TFRAME`.
+    pub frame_period: Duration,
+    /// `TRS`, the time between two packets.
+    pub packet_spacing: Duration,
+    /// `TRO`, the time from the frame's reference point to its first packet.
+    pub read_offset: Duration,
+    /// `CMAX`, the network compatibility model buffer size in packets.
+    pub cmax: u64,
+    /// `VRX_FULL`, the virtual receiver buffer size in packets.
+    pub vrx_full: u64,
+}
+
+/// ST 2110-30 packing of an audio flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AudioPacking {
+    pub samples_per_packet: u64,
+    pub octets_per_packet: usize,
+    pub packets_per_second: u64,
+}
+
+/// Parameters of the `a=fmtp` line of an ST 2110-20 SDP.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoSdpParams {
+    /// `YCbCr-4:2:2`, `YCbCr-4:4:4` or `RGB`.
+    pub sampling: String,
+    pub depth: u8,
+    pub width: u32,
+    pub height: u32,
+    /// `25` or `30000/1001`.
+    pub exact_frame_rate: String,
+    pub colorimetry: String,
+    pub interlace: bool,
+    pub sender_type: SenderType,
+}
+
+impl VideoSdpParams {
+    pub fn new(flow: &FlowDefVideo, sender_type: SenderType) -> Result<Self> {
+        let (sampling, depth) = video_sampling(flow)?;
+        Ok(Self {
+            sampling: sampling.to_string(),
+            depth,
+            width: positive(flow.frame_width, "frame width")?,
+            height: positive(flow.frame_height, "frame height")?,
+            exact_frame_rate: match flow.grain_rate.denominator {
+                1 => flow.grain_rate.numerator.to_string(),
+                denominator => format!("{}/{}", flow.grain_rate.numerator, denominator),
+            },
+            colorimetry: flow.colorspace.to_uppercase(),
+            interlace: flow.interlace_mode != InterlaceMode::Progressive,
+            sender_type,
+        })
+    }
+
+    /// Format specific parameters, as they follow the payload type on the `a=fmtp` line.
+    pub fn fmtp(&self) -> String {
+        let mut fmtp = format!(
+            "sampling={}; width={}; height={}; exactframerate={}; depth={}; TCS=SDR; \
+             colorimetry={}; PM=2110GPM; SSN=ST2110-20:2017; TP={};",
+            self.sampling,
+            self.width,
+            self.height,
+            self.exact_frame_rate,
+            self.depth,
+            self.colorimetry,
+            self.sender_type.as_str()
+        );
+        if self.interlace {
+            fmtp.push_str(" interlace;");
+        }
+        fmtp
+    }
+}
+
+/// Parameters of the `a=rtpmap` and `a=ptime` lines of an ST 2110-30 SDP.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioSdpParams {
+    /// `L16` or `L24`.
+    pub encoding: String,
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// Packet time in microseconds.
+    pub packet_time_us: u32,
+}
+
+impl AudioSdpParams {
+    pub fn new(flow: &FlowDefAudio, bit_depth: u8, packet_time: Duration) -> Result<Self> {
+        audio_octets_per_sample(bit_depth)?;
+        Ok(Self {
+            encoding: format!("L{bit_depth}"),
+            sample_rate: positive(flow.sample_rate.numerator, "sample rate")?,
+            channels: positive(flow.channel_count, "channel count")?,
+            packet_time_us: packet_time.as_micros().try_into().map_err(|_| {
+                Error::Other(format!("Packet time {packet_time:?} is out of range"))
+            })?,
+        })
+    }
+
+    /// `L24/48000/2`, as it follows the payload type on the `a=rtpmap` line.
+    pub fn rtpmap(&self) -> String {
+        format!("{}/{}/{}", self.encoding, self.sample_rate, self.channels)
+    }
+
+    /// Value of the `a=ptime` line in milliseconds, `1` or `0.125`.
+    pub fn ptime(&self) -> String {
+        let ms = self.packet_time_us / 1000;
+        match self.packet_time_us % 1000 {
+            0 => ms.to_string(),
+            us => format!("{ms}.{us:03}").trim_end_matches('0').to_string(),
+        }
+    }
+}
+
+/// Packing of `flow` by a sender with the given UDP size limit, see
+/// [`STANDARD_UDP_SIZE_LIMIT`].
+pub fn video_packing(flow: &FlowDefVideo, udp_size_limit: usize) -> Result<VideoPacking> {
+    let (sampling, depth) = video_sampling(flow)?;
+    let (pgroup_octets, pgroup_pixels) = match (sampling, depth) {
+        ("YCbCr-4:2:2", 8) => (4, 2),
+        ("YCbCr-4:2:2", 10) => (5, 2),
+        ("YCbCr-4:2:2", 12) => (6, 2),
+        ("YCbCr-4:2:2", 16) => (8, 2),
+        (_, 8) => (3, 1),
+        (_, 10) => (15, 4),
+        (_, 12) => (9, 2),
+        (_, 16) => (6, 1),
+        _ => {
+            return Err(Error::Other(format!(
+                "Unsupported ST 2110-20 bit depth {depth}"
+            )));
+        }
+    };
+    let width = positive(flow.frame_width, "frame width")? as usize;
+    let height = positive(flow.frame_height, "frame height")? as usize;
+    let sample_data_limit = udp_size_limit
+        .checked_sub(RTP_HEADER_SIZE + VIDEO_PAYLOAD_HEADER_SIZE)
+        .filter(|limit| *limit >= pgroup_octets)
+        .ok_or_else(|| Error::Other(format!("UDP size limit {udp_size_limit} is too small")))?;
+    let octets_per_line = width.div_ceil(pgroup_pixels) * pgroup_octets;
+    let octets_per_packet =
+        (sample_data_limit / pgroup_octets * pgroup_octets).min(octets_per_line);
+    let packets_per_line = octets_per_line.div_ceil(octets_per_packet);
+    Ok(VideoPacking {
+        pgroup_octets,
+        pgroup_pixels,
+        octets_per_line,
+        octets_per_packet,
+        packets_per_line,
+        packets_per_frame: packets_per_line * height,
+    })
+}
+
+/// ST 2110-21 timing of a sender of `flow` packed as `packing`.
+pub fn video_pacing(
+    flow: &FlowDefVideo,
+    packing: &VideoPacking,
+    sender_type: SenderType,
+) -> Result<VideoPacing> {
+    let (numerator, denominator) = rate_terms(&flow.grain_rate)?;
+    let packets = packing.packets_per_frame as u128;
+    if packets == 0 {
+        return Err(Error::Other("Frame has no packets".to_string()));
+    }
+    // RACTIVE = active / total lines, TRO = offset_lines / total lines of TFRAME.
+    let interlaced = flow.interlace_mode != InterlaceMode::Progressive;
+    let (active, total, offset_lines) = match (flow.frame_height, interlaced) {
+        (1080, false) => (1080, 1125, 43),
+        (1080, true) => (1080, 1125, 22),
+        (720, false) => (720, 750, 28),
+        _ => (1, 1, 0),
+    };
+    // TFRAME in nanoseconds is denominator * 1e9 / numerator.
+    let frame_ns = denominator * NANOS_PER_SECOND;
+    let trs_ns = match sender_type {
+        SenderType::NarrowLinear => frame_ns / (numerator * packets),
+        SenderType::Narrow | SenderType::Wide => frame_ns * active / (numerator * total * packets),
+    };
+    // NPACKETS / (k * TFRAME) = NPACKETS * numerator / (k * denominator).
+    let per_frame_time = |k: u128| packets * numerator / (k * denominator);
+    let (cmax, vrx_full) = match sender_type {
+        SenderType::Narrow | SenderType::NarrowLinear => (
+            (packets * numerator * total / (43_200 * active * denominator)).max(4),
+            per_frame_time(27_000).max(8),
+        ),
+        SenderType::Wide => (per_frame_time(21_600).max(16), per_frame_time(300).max(720)),
+    };
+    Ok(VideoPacing {
+        sender_type,
+        frame_period: nanos(frame_ns / numerator)?,
+        packet_spacing: nanos(trs_ns)?,
+        read_offset: nanos(frame_ns * offset_lines / (numerator * total))?,
+        cmax: cmax.try_into().unwrap_or(u64::MAX),
+        vrx_full: vrx_full.try_into().unwrap_or(u64::MAX),
+    })
+}
+
+/// ST 2110-30 packing of `flow` as `L16` or `L24` (`bit_depth` 16 or 24) with the given packet
+/// time, typically 1 ms or 125 µs. The packet time must hold a whole number of samples.
+pub fn audio_packing(
+    flow: &FlowDefAudio,
+    bit_depth: u8,
+    packet_time: Duration,
+    udp_size_limit: usize,
+) -> Result<AudioPacking> {
+    let octets_per_sample = audio_octets_per_sample(bit_depth)?;
+    let (numerator, denominator) = rate_terms(&flow.sample_rate)?;
+    let channels = positive(flow.channel_count, "channel count")? as usize;
+    let packet_ns = packet_time.as_nanos();
+    let samples = packet_ns * numerator;
+    let divisor = denominator * NANOS_PER_SECOND;
+    if packet_ns == 0 || !samples.is_multiple_of(divisor) {
+        return Err(Error::Other(format!(
+            "Packet time {packet_time:?} does not hold a whole number of samples"
+        )));
+    }
+    let samples_per_packet = (samples / divisor) as u64;
+    let octets_per_packet = samples_per_packet as usize * channels * octets_per_sample;
+    if octets_per_packet + RTP_HEADER_SIZE > udp_size_limit {
+        return Err(Error::Other(format!(
+            "{octets_per_packet} octets per packet exceed the UDP size limit {udp_size_limit}"
+        )));
+    }
+    Ok(AudioPacking {
+        samples_per_packet,
+        octets_per_packet,
+        packets_per_second: (NANOS_PER_SECOND / packet_ns) as u64,
+    })
+}
+
+/// ST 2110-20 sampling and bit depth. Flows without components are v210.
+fn video_sampling(flow: &FlowDefVideo) -> Result<(&'static str, u8)> {
+    let Some(first) = flow.components.first() else {
+        return Ok(("YCbCr-4:2:2", 10));
+    };
+    let names: Vec<&str> = flow.components.iter().map(|c| c.name.as_str()).collect();
+    let sampling = match names.as_slice() {
+        ["Y", "Cb", "Cr"] if flow.components[1].width * 2 == first.width => "YCbCr-4:2:2",
+        ["Y", "Cb", "Cr"] if flow.components[1].width == first.width => "YCbCr-4:4:4",
+        ["R", "G", "B"] => "RGB",
+        _ => {
+            return Err(Error::Other(format!(
+                "Unsupported ST 2110-20 components {names:?}"
+            )));
+        }
+    };
+    Ok((sampling, first.bit_depth))
+}
+
+fn audio_octets_per_sample(bit_depth: u8) -> Result<usize> {
+    match bit_depth {
+        16 => Ok(2),
+        24 => Ok(3),
+        _ => Err(Error::Other(format!(
+            "ST 2110-30 supports L16 and L24, not {bit_depth} bits"
+        ))),
+    }
+}
+
+fn rate_terms(rate: &Rate) -> Result<(u128, u128)> {
+    if rate.numerator <= 0 || rate.denominator <= 0 {
+        return Err(Error::Other(format!(
+            "Invalid rate {}/{}",
+            rate.numerator, rate.denominator
+        )));
+    }
+    Ok((rate.numerator as u128, rate.denominator as u128))
+}
+
+fn positive(value: i32, what: &str) -> Result<u32> {
+    u32::try_from(value)
+        .ok()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| Error::Other(format!("Invalid {what} {value}")))
+}
+
+fn nanos(value: u128) -> Result<Duration> {
+    Ok(Duration::from_nanos(value.try_into().map_err(|_| {
+        Error::Other("Duration is out of range".to_string())
+    })?))
+}
@@ -0,0 +1,234 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `Send + Sync` wrappers around the flow readers and writers.
+//!
+//! The readers and writers are `Send` but not `Sync`, because the library does not support
+//! concurrent calls on a single reader or writer. The wrappers serialize every call with a mutex,
+//! so they can be shared through an `Arc` between threads or async tasks. The convenience methods
+//! return owned data, as borrowed grains and samples cannot outlive the lock; [`lock`] gives
+//! access to the full API for the duration of a guard.
+//!
+//! The calls with a timeout block the calling thread. From an async executor, run them on a
+//! blocking thread (e.g. `tokio::task::spawn_blocking`), or use the non-blocking variants.
+//!
+//! [`lock`]: SyncGrainReader::lock
+
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use crate::{
+    Error, GrainReader, GrainWriter, OwnedGrainData, OwnedSamplesData, Result, SamplesReader,
+    SamplesWriter,
+};
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| Error::Other("A previous call panicked while holding the lock.".to_string()))
+}
+
+fn into_inner<T>(mutex: Mutex<T>) -> Result<T> {
+    mutex
+        .into_inner()
+        .map_err(|_| Error::Other("A previous call panicked while holding the lock.".to_string()))
+}
+
+/// [`GrainReader`] that can be shared between threads.
+#[derive(Debug)]
+pub struct SyncGrainReader {
+    reader: Mutex<GrainReader>,
+}
+
+impl SyncGrainReader {
+    pub fn new(reader: GrainReader) -> Self {
+        Self {
+            reader: Mutex::new(reader),
+        }
+    }
+
+    /// Locks the reader for the duration of the guard, blocking other callers.
+    pub fn lock(&self) -> Result<MutexGuard<'_, GrainReader>> {
+        lock(&self.reader)
+    }
+
+    pub fn into_inner(self) -> Result<GrainReader> {
+        into_inner(self.reader)
+    }
+
+    /// See [`GrainReader::get_complete_grain`].
+    pub fn get_complete_grain(&self, index: u64, timeout: Duration) -> Result<OwnedGrainData> {
+        Ok(self.lock()?.get_complete_grain(index, timeout)?.into())
+    }
+
+    /// See [`GrainReader::get_grain_non_blocking`].
+    pub fn get_grain_non_blocking(&self, index: u64) -> Result<OwnedGrainData> {
+        Ok(self.lock()?.get_grain_non_blocking(index)?.into())
+    }
+
+    pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
+        self.lock()?.get_runtime_info()
+    }
+}
+
+impl From<GrainReader> for SyncGrainReader {
+    fn from(reader: GrainReader) -> Self {
+        Self::new(reader)
+    }
+}
+
+/// [`GrainWriter`] that can be shared between threads.
+#[derive(Debug)]
+pub struct SyncGrainWriter {
+    writer: Mutex<GrainWriter>,
+}
+
+impl SyncGrainWriter {
+    pub fn new(writer: GrainWriter) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Locks the writer for the duration of the guard, blocking other callers.
+    pub fn lock(&self) -> Result<MutexGuard<'_, GrainWriter>> {
+        lock(&self.writer)
+    }
+
+    pub fn into_inner(self) -> Result<GrainWriter> {
+        into_inner(self.writer)
+    }
+
+    /// See [`GrainWriter::write_grain`].
+    pub fn write_grain(&self, index: u64, payload: &[u8]) -> Result<()> {
+        self.lock()?.write_grain(index, payload)
+    }
+
+    /// See [`GrainWriter::write_grain_padded`].
+    pub fn write_grain_padded(&self, index: u64, payload: &[u8]) -> Result<()> {
+        self.lock()?.write_grain_padded(index, payload)
+    }
+
+    pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
+        self.lock()?.get_runtime_info()
+    }
+}
+
+impl From<GrainWriter> for SyncGrainWriter {
+    fn from(writer: GrainWriter) -> Self {
+        Self::new(writer)
+    }
+}
+
+/// [`SamplesReader`] that can be shared between threads.
+#[derive(Debug)]
+pub struct SyncSamplesReader {
+    reader: Mutex<SamplesReader>,
+}
+
+impl SyncSamplesReader {
+    pub fn new(reader: SamplesReader) -> Self {
+        Self {
+            reader: Mutex::new(reader),
+        }
+    }
+
+    /// Locks the reader for the duration of the guard, blocking other callers.
+    pub fn lock(&self) -> Result<MutexGuard<'_, SamplesReader>> {
+        lock(&self.reader)
+    }
+
+    pub fn into_inner(self) -> Result<SamplesReader> {
+        into_inner(self.reader)
+    }
+
+    /// See [`SamplesReader::get_samples`].
+    pub fn get_samples(
+        &self,
+        index: u64,
+        count: usize,
+        timeout: Duration,
+    ) -> Result<OwnedSamplesData> {
+        Ok(self.lock()?.get_samples(index, count, timeout)?.into())
+    }
+
+    /// See [`SamplesReader::get_samples_non_blocking`].
+    pub fn get_samples_non_blocking(&self, index: u64, count: usize) -> Result<OwnedSamplesData> {
+        Ok(self.lock()?.get_samples_non_blocking(index, count)?.into())
+    }
+
+    pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
+        self.lock()?.get_runtime_info()
+    }
+}
+
+impl From<SamplesReader> for SyncSamplesReader {
+    fn from(reader: SamplesReader) -> Self {
+        Self::new(reader)
+    }
+}
+
+/// [`SamplesWriter`] that can be shared between threads.
+#[derive(Debug)]
+pub struct SyncSamplesWriter {
+    writer: Mutex<SamplesWriter>,
+}
+
+impl SyncSamplesWriter {
+    pub fn new(writer: SamplesWriter) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Locks the writer for the duration of the guard, blocking other callers.
+    pub fn lock(&self) -> Result<MutexGuard<'_, SamplesWriter>> {
+        lock(&self.writer)
+    }
+
+    pub fn into_inner(self) -> Result<SamplesWriter> {
+        into_inner(self.writer)
+    }
+
+    /// Writes `count` samples of every channel at `index`, with the index semantics of
+    /// [`SamplesWriter::open_samples`]. `channels` holds the raw bytes of each channel, in order,
+    /// and must cover all channels of the flow with the same length.
+    pub fn write_samples(&self, index: u64, count: usize, channels: &[&[u8]]) -> Result<()> {
+        let mut writer = self.lock()?;
+        let mut access = writer.open_samples(index, count)?;
+        if channels.len() != access.channels() {
+            return Err(Error::Other(format!(
+                "Got {} channels, the flow has {}.",
+                channels.len(),
+                access.channels()
+            )));
+        }
+        for (channel, data) in channels.iter().enumerate() {
+            let (first, second) = access.channel_data_mut(channel)?;
+            if data.len() != first.len() + second.len() {
+                return Err(Error::Other(format!(
+                    "Channel {channel} has {} bytes, {} samples take {}.",
+                    data.len(),
+                    count,
+                    first.len() + second.len()
+                )));
+            }
+            let (data_first, data_second) = data.split_at(first.len());
+            first.copy_from_slice(data_first);
+            second.copy_from_slice(data_second);
+        }
+        access.commit()
+    }
+
+    pub fn get_runtime_info(&self) -> Result<mxl_sys::FlowRuntimeInfo> {
+        self.lock()?.get_runtime_info()
+    }
+}
+
+impl From<SamplesWriter> for SyncSamplesWriter {
+    fn from(writer: SamplesWriter) -> Self {
+        Self::new(writer)
+    }
+}
@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversion between MXL timestamps and UTC.
+//!
+//! MXL timestamps are nanoseconds of TAI since the SMPTE ST 2059 epoch (1970-01-01T00:00:00 TAI).
+//! UTC lags TAI by an offset that grows by one second with every leap second. The offset is looked
+//! up in a leap-second table, which can be replaced when new leap seconds are announced, or
+//! overridden with a fixed value (for example the `currentUtcOffset` announced by a PTP
+//! grandmaster).
+
+use std::{
+    sync::{LazyLock, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Error, Result};
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// Seconds between the NTP epoch (1900) used by `leap-seconds.list` and the Unix epoch.
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
+/// Leap seconds up to the one at the end of 2016, as `(UTC Unix seconds, TAI - UTC)`.
+const BUILTIN_LEAP_SECONDS: &[(u64, i32)] = &[
+    (63_072_000, 10),
+    (78_796_800, 11),
+    (94_694_400, 12),
+    (126_230_400, 13),
+    (157_766_400, 14),
+    (189_302_400, 15),
+    (220_924_800, 16),
+    (252_460_800, 17),
+    (283_996_800, 18),
+    (315_532_800, 19),
+    (362_793_600, 20),
+    (394_329_600, 21),
+    (425_865_600, 22),
+    (489_024_000, 23),
+    (567_993_600, 24),
+    (631_152_000, 25),
+    (662_688_000, 26),
+    (709_948_800, 27),
+    (741_484_800, 28),
+    (773_020_800, 29),
+    (820_454_400, 30),
+    (867_715_200, 31),
+    (915_148_800, 32),
+    (1_136_073_600, 33),
+    (1_230_768_000, 34),
+    (1_341_100_800, 35),
+    (1_435_708_800, 36),
+    (1_483_228_800, 37),
+];
+
+static LEAP_SECONDS: LazyLock<RwLock<LeapSecondTable>> =
+    LazyLock::new(|| RwLock::new(LeapSecondTable::builtin()));
+static OFFSET_OVERRIDE: RwLock<Option<i32>> = RwLock::new(None);
+
+/// A change of the TAI - UTC offset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeapSecond {
+    /// UTC time, in seconds since the Unix epoch, from which `tai_utc_offset` applies.
+    pub utc_seconds: u64,
+    /// TAI - UTC in seconds.
+    pub tai_utc_offset: i32,
+}
+
+/// TAI - UTC offsets over time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeapSecondTable {
+    entries: Vec<LeapSecond>,
+}
+
+impl LeapSecondTable {
+    /// The table compiled into the library.
+    pub fn builtin() -> Self {
+        Self {
+            entries: BUILTIN_LEAP_SECONDS
+                .iter()
+                .map(|&(utc_seconds, tai_utc_offset)| LeapSecond {
+                    utc_seconds,
+                    tai_utc_offset,
+                })
+                .collect(),
+        }
+    }
+
+    /// Fails if `entries` is empty or not strictly ordered by time.
+    pub fn new(entries: Vec<LeapSecond>) -> Result<Self> {
+        if entries.is_empty() {
+            return Err(Error::Other("Leap second table is empty".to_string()));
+        }
+        if entries
+            .windows(2)
+            .any(|pair| pair[0].utc_seconds >= pair[1].utc_seconds)
+        {
+            return Err(Error::Other(
+                "Leap second table is not ordered by time".to_string(),
+            ));
+        }
+        Ok(Self { entries })
+    }
+
+    /// Parses the `leap-seconds.list` file published by the IERS and IETF (also shipped by
+    /// tzdata). Only the data lines are read; the expiry date and hash are ignored.
+    pub fn parse_leap_seconds_list(text: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        for line in text.lines() {
+            let data = line.split('#').next().unwrap_or_default().trim();
+            if data.is_empty() {
+                continue;
+            }
+            let mut fields = data.split_whitespace();
+            let (Some(ntp_seconds), Some(offset)) = (fields.next(), fields.next()) else {
+                return Err(Error::Other(format!("Invalid leap second line: {line}")));
+            };
+            let ntp_seconds: u64 = ntp_seconds
+                .parse()
+                .map_err(|e| Error::Other(format!("Invalid leap second time: {e}")))?;
+            let tai_utc_offset = offset
+                .parse()
+                .map_err(|e| Error::Other(format!("Invalid leap second offset: {e}")))?;
+            let utc_seconds = ntp_seconds.checked_sub(NTP_UNIX_OFFSET).ok_or_else(|| {
+                Error::Other(format!("Leap second predates the Unix epoch: {line}"))
+            })?;
+            entries.push(LeapSecond {
+                utc_seconds,
+                tai_utc_offset,
+            });
+        }
+        Self::new(entries)
+    }
+
+    pub fn entries(&self) -> &[LeapSecond] {
+        &self.entries
+    }
+
+    /// TAI - UTC at `utc_seconds` since the Unix epoch. Times before the first entry use its
+    /// offset.
+    pub fn offset_at_utc(&self, utc_seconds: u64) -> i32 {
+        let applied = self
+            .entries
+            .partition_point(|entry| entry.utc_seconds <= utc_seconds);
+        self.entries[applied.saturating_sub(1)].tai_utc_offset
+    }
+
+    /// TAI - UTC at `tai_seconds` since the ST 2059 epoch.
+    ///
+    /// A new offset applies from the TAI second that would otherwise have been the inserted
+    /// `23:59:60`, so that second maps to a repeated `23:59:59` UTC, as on POSIX clocks.
+    pub fn offset_at_tai(&self, tai_seconds: u64) -> i32 {
+        let mut offset = self.entries[0].tai_utc_offset;
+        for entry in &self.entries[1..] {
+            if (tai_seconds as i128) < entry.utc_seconds as i128 + offset as i128 {
+                break;
+            }
+            offset = entry.tai_utc_offset;
+        }
+        offset
+    }
+}
+
+/// Replaces the leap-second table used by the conversion functions, for example with a freshly
+/// downloaded `leap-seconds.list`.
+pub fn set_leap_second_table(table: LeapSecondTable) {
+    *LEAP_SECONDS.write().unwrap_or_else(|e| e.into_inner()) = table;
+}
+
+/// Makes the conversion functions use a fixed TAI - UTC offset instead of the leap-second table.
+/// `None` goes back to the table.
+pub fn set_tai_utc_offset_override(offset: Option<i32>) {
+    *OFFSET_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = offset;
+}
+
+/// TAI - UTC in seconds at the MXL timestamp `tai_ns`.
+pub fn tai_utc_offset_at(tai_ns: u64) -> i32 {
+    if let Some(offset) = *OFFSET_OVERRIDE.read().unwrap_or_else(|e| e.into_inner()) {
+        return offset;
+    }
+    LEAP_SECONDS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .offset_at_tai(tai_ns / NANOS_PER_SECOND as u64)
+}
+
+/// Converts an MXL timestamp (see [`crate::MxlInstance::get_time`]) to UTC.
+pub fn tai_ns_to_utc(tai_ns: u64) -> SystemTime {
+    let offset = tai_utc_offset_at(tai_ns) as i128 * NANOS_PER_SECOND;
+    let utc_ns = tai_ns as i128 - offset;
+    let since_epoch = Duration::from_nanos(utc_ns.unsigned_abs() as u64);
+    if utc_ns >= 0 {
+        UNIX_EPOCH + since_epoch
+    } else {
+        UNIX_EPOCH - since_epoch
+    }
+}
+
+/// Converts a UTC time to an MXL timestamp. Fails for times before the ST 2059 epoch or beyond
+/// the range of the timestamp.
+pub fn utc_to_tai_ns(utc: SystemTime) -> Result<u64> {
+    let since_epoch = utc
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| Error::Other("UTC time predates the Unix epoch".to_string()))?;
+    let offset = match *OFFSET_OVERRIDE.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(offset) => offset,
+        None => LEAP_SECONDS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .offset_at_utc(since_epoch.as_secs()),
+    };
+    let tai_ns = since_epoch.as_nanos() as i128 + offset as i128 * NANOS_PER_SECOND;
+    u64::try_from(tai_ns)
+        .map_err(|_| Error::Other(format!("UTC time {utc:?} is outside the MXL time range")))
+}
@@ -0,0 +1,179 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! SMPTE ST 12 timecodes for grain indices.
+//!
+//! A grain index counts grains since the ST 2059 epoch, so the timecode of an index is its
+//! position within the current 24 hour timecode period. Drop-frame timecode is supported for the
+//! 1000/1001 rates that are multiples of 30 (29.97 and 59.94 fps). Rates above 30 fps count frames
+//! beyond 29 rather than using the frame pair flag of ST 12-1.
+
+use std::{fmt, str::FromStr};
+
+use crate::{Error, RationalExt, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub drop_frame: bool,
+}
+
+impl fmt::Display for Timecode {
+    /// `HH:MM:SS:FF`, with a `;` frame separator for drop-frame timecode.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+impl FromStr for Timecode {
+    type Err = Error;
+
+    /// Parses `HH:MM:SS:FF`. A `;` or `,` before the frames marks drop-frame timecode.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Other(format!("Invalid timecode \"{s}\""));
+        let (time, frames, drop_frame) = match s.rfind([':', ';', '.', ',']) {
+            Some(position) => (
+                &s[..position],
+                &s[position + 1..],
+                matches!(s.as_bytes()[position], b';' | b','),
+            ),
+            None => return Err(invalid()),
+        };
+        let fields = time
+            .split(':')
+            .map(|field| field.parse::<u8>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>>>()?;
+        let [hours, minutes, seconds] = fields[..] else {
+            return Err(invalid());
+        };
+        Ok(Self {
+            hours,
+            minutes,
+            seconds,
+            frames: frames.parse().map_err(|_| invalid())?,
+            drop_frame,
+        })
+    }
+}
+
+/// Frame counting parameters of a timecode rate.
+struct TimecodeRate {
+    /// Frames per timecode second (30 for 29.97 fps).
+    nominal: u64,
+    /// Frame numbers skipped at the start of every minute not divisible by 10.
+    dropped: u64,
+}
+
+impl TimecodeRate {
+    fn new(rate: &mxl_sys::Rational, drop_frame: bool) -> Result<Self> {
+        if !rate.is_valid() {
+            return Err(Error::Other(format!(
+                "Invalid timecode rate {}/{}",
+                rate.numerator, rate.denominator
+            )));
+        }
+        let nominal = (rate.numerator as u64).div_ceil(rate.denominator as u64);
+        if nominal > u8::MAX as u64 {
+            return Err(Error::Other(format!(
+                "Rate of {nominal} fps is too high for timecode"
+            )));
+        }
+        let dropped = if drop_frame {
+            if rate.reduced().denominator != 1001 || !nominal.is_multiple_of(30) {
+                return Err(Error::Other(format!(
+                    "Drop-frame timecode is not defined for {}/{}",
+                    rate.numerator, rate.denominator
+                )));
+            }
+            nominal / 30 * 2
+        } else {
+            0
+        };
+        Ok(Self { nominal, dropped })
+    }
+
+    fn frames_per_minute(&self) -> u64 {
+        self.nominal * 60 - self.dropped
+    }
+
+    fn frames_per_ten_minutes(&self) -> u64 {
+        self.nominal * 600 - self.dropped * 9
+    }
+
+    fn frames_per_day(&self) -> u64 {
+        self.frames_per_ten_minutes() * 6 * 24
+    }
+}
+
+/// Timecode of grain `index` of a flow with grain rate `rate`.
+pub fn index_to_timecode(
+    index: u64,
+    rate: &mxl_sys::Rational,
+    drop_frame: bool,
+) -> Result<Timecode> {
+    let tc_rate = TimecodeRate::new(rate, drop_frame)?;
+    let mut frame = index % tc_rate.frames_per_day();
+    if tc_rate.dropped > 0 {
+        // Add back the frame numbers skipped so far to count in nominal frames.
+        let tens = frame / tc_rate.frames_per_ten_minutes();
+        let rest = frame % tc_rate.frames_per_ten_minutes();
+        frame += tc_rate.dropped * 9 * tens;
+        if rest >= tc_rate.dropped {
+            frame += tc_rate.dropped * ((rest - tc_rate.dropped) / tc_rate.frames_per_minute());
+        }
+    }
+    let nominal = tc_rate.nominal;
+    Ok(Timecode {
+        hours: (frame / (nominal * 3600) % 24) as u8,
+        minutes: (frame / (nominal * 60) % 60) as u8,
+        seconds: (frame / nominal % 60) as u8,
+        frames: (frame % nominal) as u8,
+        drop_frame,
+    })
+}
+
+/// Grain index carrying `timecode`, choosing the 24 hour period that puts it closest to `near`
+/// (typically the current index, see [`crate::MxlInstance::get_current_index`]).
+pub fn timecode_to_index(timecode: &Timecode, rate: &mxl_sys::Rational, near: u64) -> Result<u64> {
+    let tc_rate = TimecodeRate::new(rate, timecode.drop_frame)?;
+    let nominal = tc_rate.nominal;
+    let total_minutes = timecode.hours as u64 * 60 + timecode.minutes as u64;
+    let skipped = tc_rate.dropped > 0
+        && timecode.seconds == 0
+        && !total_minutes.is_multiple_of(10)
+        && (timecode.frames as u64) < tc_rate.dropped;
+    if timecode.hours >= 24
+        || timecode.minutes >= 60
+        || timecode.seconds >= 60
+        || timecode.frames as u64 >= nominal
+        || skipped
+    {
+        return Err(Error::Other(format!(
+            "Timecode {timecode} does not exist at {}/{}",
+            rate.numerator, rate.denominator
+        )));
+    }
+    let frame = (total_minutes * 60 + timecode.seconds as u64) * nominal + timecode.frames as u64
+        - tc_rate.dropped * (total_minutes - total_minutes / 10);
+
+    let day = tc_rate.frames_per_day();
+    let candidate = near - near % day + frame;
+    let index = [
+        candidate.checked_sub(day),
+        Some(candidate),
+        candidate.checked_add(day),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by_key(|index| index.abs_diff(near))
+    .unwrap_or(candidate);
+    Ok(index)
+}
@@ -0,0 +1,102 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use mxl::{
+    MXL_DATA_FORMAT_GRAIN_SIZE, Timecode,
+    anc::{AncField, AncGrain, AncPacket},
+    rates,
+};
+
+#[test]
+fn anc_grain_round_trip() {
+    let grain = AncGrain {
+        field: AncField::Second,
+        packets: vec![
+            AncPacket::cea708(vec![0x96, 0x69, 0x10, 0x4f, 0x43, 0x00, 0x01]).at_line(9),
+            AncPacket::cea608(0x8a, [0x94, 0x2c]),
+            AncPacket::scte104(&[0xff, 0xff, 0x00, 0x0b]).unwrap(),
+        ],
+    };
+    let mut payload = vec![0xaa; MXL_DATA_FORMAT_GRAIN_SIZE];
+    grain.encode(&mut payload).unwrap();
+    assert!(payload[grain.encoded_size()..].iter().all(|&b| b == 0));
+
+    let decoded = AncGrain::decode(&payload).unwrap();
+    assert_eq!(decoded, grain);
+    assert!(decoded.packets[0].is_cea708());
+    assert_eq!(decoded.packets[0].line, 9);
+    assert!(decoded.packets[1].is_cea608());
+    assert_eq!(
+        decoded.packets[2].scte104_message(),
+        Some(&[0xff, 0xff, 0x00, 0x0b][..])
+    );
+}
+
+#[test]
+fn anc_words_carry_parity_and_checksum() {
+    let grain = AncGrain::new(vec![AncPacket::cea608(0x8a, [0x94, 0x2c])]);
+    let mut payload = vec![0; 64];
+    grain.encode(&mut payload).unwrap();
+    // Length of one packet: 32 location bits and 7 10-bit words, in 4 32-bit words.
+    assert_eq!(&payload[..3], &[0x00, 0x10, 0x01]);
+    // C = 0, line 0x7ff, offset 0xfff, S = 0, StreamNum = 0, then DID 0x161, SDID 0x102
+    // and Data_Count 0x203.
+    assert_eq!(&payload[6..10], &[0x7f, 0xff, 0xff, 0x00]);
+    assert_eq!(&payload[10..13], &[0x58, 0x50, 0x28]);
+
+    // Flip a bit of the SDID word.
+    payload[11] ^= 0x10;
+    assert!(AncGrain::decode(&payload).is_err());
+}
+
+#[test]
+fn anc_grain_rejects_what_does_not_fit() {
+    let grain = AncGrain::new(vec![AncPacket::cea708(vec![0; 200])]);
+    assert!(grain.encode(&mut [0; 64]).is_err());
+    assert!(AncPacket::scte104(&[0; 255]).is_err());
+
+    let mut payload = vec![0; 64];
+    AncGrain::new(vec![]).encode(&mut payload).unwrap();
+    payload[1] = 0xff;
+    assert!(AncGrain::decode(&payload).is_err());
+}
+
+#[test]
+fn atc_packet_round_trip() {
+    let timecode: Timecode = "10:59:58;29".parse().unwrap();
+    let packet = AncPacket::timecode(&timecode, &rates::FPS_29_97).unwrap();
+    assert!(packet.is_atc());
+    // Frame units 9 in the first word, tens 2 and the drop-frame flag in the third.
+    assert_eq!(packet.user_data.len(), 16);
+    assert_eq!(packet.user_data[0], 0x90);
+    assert_eq!(packet.user_data[2], 0x60);
+    assert_eq!(packet.atc_timecode(&rates::FPS_29_97), Some(timecode));
+
+    let mut payload = vec![0; MXL_DATA_FORMAT_GRAIN_SIZE];
+    AncGrain::new(vec![packet]).encode(&mut payload).unwrap();
+    let decoded = AncGrain::decode(&payload).unwrap();
+    assert_eq!(
+        decoded.packets[0].atc_timecode(&rates::FPS_29_97),
+        Some(timecode)
+    );
+}
+
+#[test]
+fn atc_packet_counts_frame_pairs_above_30_fps() {
+    for (rate, timecode) in [
+        (rates::FPS_50, "23:59:59:49"),
+        (rates::FPS_59_94, "00:01:00;04"),
+    ] {
+        let timecode: Timecode = timecode.parse().unwrap();
+        let packet = AncPacket::timecode(&timecode, &rate).unwrap();
+        assert_eq!(packet.atc_timecode(&rate), Some(timecode));
+    }
+
+    let timecode: Timecode = "00:00:00:40".parse().unwrap();
+    assert!(AncPacket::timecode(&timecode, &rates::FPS_25).is_err());
+    assert!(
+        AncPacket::cea608(0x8a, [0x94, 0x2c])
+            .atc_timecode(&rates::FPS_25)
+            .is_none()
+    );
+}
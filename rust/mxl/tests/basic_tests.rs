@@ -7,94 +7,27 @@
 /// change in the future. For now, feel free to just edit the path to your library.
 use std::time::Duration;
 
-use mxl::{MxlInstance, OwnedGrainData, OwnedSamplesData, config::get_mxl_so_path};
+use mxl::{
+    DataFraming, FlowEvent, FlowId, FlowOptions, HeadNotifier, InstanceOptions, InterleaveOptions,
+    LiveObjectKind, MxlInstance, OwnedGrainData, OwnedSamplesData, PayloadLocation, ReaderService,
+    SampleBatcher, TimeSource, TypedReader, VirtualClock, config::get_mxl_so_path,
+};
+use mxl_test_utils::{
+    TestDomainGuard, audio_flow_def, data_flow_def,
+    loopback::{Loopback, PatternMismatch, fill_pattern, verify_grain, verify_pattern},
+    setup_test, v210_flow_def,
+};
 use tracing::info;
 
-static LOG_ONCE: std::sync::Once = std::sync::Once::new();
-
-struct TestDomainGuard {
-    dir: std::path::PathBuf,
-}
-
-impl TestDomainGuard {
-    fn new(test: &str) -> Self {
-        let dir = std::path::PathBuf::from(format!(
-            "/dev/shm/mxl_rust_unit_tests_domain_{}_{}",
-            test,
-            uuid::Uuid::new_v4()
-        ));
-        std::fs::create_dir_all(dir.as_path()).unwrap_or_else(|_| {
-            panic!(
-                "Failed to create test domain directory \"{}\".",
-                dir.display()
-            )
-        });
-        Self { dir }
-    }
-
-    fn domain(&self) -> String {
-        self.dir.to_string_lossy().to_string()
-    }
-}
-
-impl Drop for TestDomainGuard {
-    fn drop(&mut self) {
-        std::fs::remove_dir_all(self.dir.as_path()).unwrap_or_else(|_| {
-            panic!(
-                "Failed to remove test domain directory \"{}\".",
-                self.dir.display()
-            )
-        });
-    }
-}
-
-fn setup_test(test: &str) -> (MxlInstance, TestDomainGuard) {
-    // Set up the logging to use the RUST_LOG environment variable and if not present, print INFO
-    // and higher.
-    LOG_ONCE.call_once(|| {
-        tracing_subscriber::fmt()
-            .with_env_filter(
-                tracing_subscriber::EnvFilter::builder()
-                    .with_default_directive(tracing::level_filters::LevelFilter::INFO.into())
-                    .from_env_lossy(),
-            )
-            .init();
-    });
-
-    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
-    let domain_guard = TestDomainGuard::new(test);
-    (
-        MxlInstance::new(mxl_api, domain_guard.domain().as_str(), "").unwrap(),
-        domain_guard,
-    )
-}
-
-fn read_flow_def<P: AsRef<std::path::Path>>(path: P) -> String {
-    let flow_config_file = mxl::config::get_mxl_repo_root().join(path);
-
-    std::fs::read_to_string(flow_config_file.as_path())
-        .map_err(|error| {
-            mxl::Error::Other(format!(
-                "Error while reading flow definition from \"{}\": {}",
-                flow_config_file.display(),
-                error
-            ))
-        })
-        .unwrap()
-}
-
 #[test]
 fn basic_mxl_grain_writing_reading() {
     let (mxl_instance, _domain_guard) = setup_test("grains");
     let (flow_writer, flow_config_info, was_created) = mxl_instance
-        .create_flow_writer(
-            read_flow_def("lib/tests/data/v210_flow.json").as_str(),
-            None,
-        )
+        .create_flow_writer(v210_flow_def().as_str(), None)
         .unwrap();
     assert!(was_created);
     let flow_id = flow_config_info.common().id().to_string();
-    let grain_writer = flow_writer.to_grain_writer().unwrap();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
     let flow_reader = mxl_instance.create_flow_reader(flow_id.as_str()).unwrap();
     let grain_reader = flow_reader.to_grain_reader().unwrap();
     let rate = flow_config_info.common().grain_rate().unwrap();
@@ -116,14 +49,11 @@ fn basic_mxl_grain_writing_reading() {
 fn basic_mxl_samples_writing_reading() {
     let (mxl_instance, _domain_guard) = setup_test("samples");
     let (flow_writer, flow_config_info, was_created) = mxl_instance
-        .create_flow_writer(
-            read_flow_def("lib/tests/data/audio_flow.json").as_str(),
-            None,
-        )
+        .create_flow_writer(audio_flow_def().as_str(), None)
         .unwrap();
     assert!(was_created);
     let flow_id = flow_config_info.common().id().to_string();
-    let samples_writer = flow_writer.to_samples_writer().unwrap();
+    let mut samples_writer = flow_writer.to_samples_writer().unwrap();
     let flow_reader = mxl_instance.create_flow_reader(flow_id.as_str()).unwrap();
     let samples_reader = flow_reader.to_samples_reader().unwrap();
     let rate = flow_config_info.common().sample_rate().unwrap();
@@ -144,10 +74,52 @@ fn basic_mxl_samples_writing_reading() {
     mxl_instance.destroy().unwrap();
 }
 
+#[test]
+fn channels_are_written_from_planes() {
+    let (mxl_instance, _domain_guard) = setup_test("samples_planes");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut samples_writer = flow_writer.to_samples_writer().unwrap();
+    let flow_reader = mxl_instance.create_flow_reader(flow_id.as_str()).unwrap();
+    let samples_reader = flow_reader.to_samples_reader().unwrap();
+    let rate = flow_config_info.common().sample_rate().unwrap();
+    let current_index = mxl_instance.get_current_index(&rate);
+
+    let plane =
+        |samples: [f32; 4]| -> Vec<u8> { samples.iter().flat_map(|s| s.to_le_bytes()).collect() };
+    // Channel 0 is copied, channel 1 goes through its gain.
+    let options = InterleaveOptions::new().gains([1.0, 0.5]);
+    let mut access = samples_writer.open_samples(current_index, 4).unwrap();
+    access
+        .write_channel_from(0, &plane([0.1, 0.2, 0.3, 0.4]), &options)
+        .unwrap();
+    access
+        .write_channel_from(1, &plane([0.2, 0.4, 0.6, 0.8]), &options)
+        .unwrap();
+    assert!(
+        access
+            .write_channel_from(1, &plane([0.0; 4])[..8], &options)
+            .is_err()
+    );
+    access.commit().unwrap();
+
+    let samples: OwnedSamplesData = samples_reader
+        .get_samples(current_index, 4, Duration::from_secs(5))
+        .unwrap()
+        .into();
+    assert_eq!(samples.payload[0], plane([0.1, 0.2, 0.3, 0.4]));
+    assert_eq!(samples.payload[1], plane([0.1, 0.2, 0.3, 0.4]));
+    samples_reader.destroy().unwrap();
+    samples_writer.destroy().unwrap();
+    mxl_instance.destroy().unwrap();
+}
+
 #[test]
 fn get_flow_def() {
     let (mxl_instance, _domain_guard) = setup_test("flow_def");
-    let flow_def = read_flow_def("lib/tests/data/v210_flow.json");
+    let flow_def = v210_flow_def();
     let (flow_writer, flow_info, was_created) = mxl_instance
         .create_flow_writer(flow_def.as_str(), None)
         .unwrap();
@@ -159,6 +131,39 @@ fn get_flow_def() {
     mxl_instance.destroy().unwrap();
 }
 
+#[test]
+fn flow_info_serializes_to_json() {
+    let (mxl_instance, _domain_guard) = setup_test("flow_info_json");
+    let (_flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let info = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .get_info()
+        .unwrap();
+    let json = serde_json::to_value(&info).unwrap();
+    assert_eq!(json["config"]["id"], flow_id);
+    assert_eq!(json["config"]["format"], "audio");
+    assert_eq!(json["config"]["grain_rate"]["numerator"], 48000);
+    assert_eq!(json["config"]["continuous"]["channel_count"], 2);
+    assert!(json["config"].get("discrete").is_none());
+    assert_eq!(json["runtime"]["head_index"], info.runtime.head_index());
+    info!("{info:?}");
+    mxl_instance.destroy().unwrap();
+}
+
+#[test]
+fn debug_summarizes_payloads() {
+    let grain = OwnedGrainData {
+        payload: vec![0xab; 4096],
+    };
+    let debug = format!("{grain:?}");
+    assert!(debug.contains("payload_len: 4096"), "{debug}");
+    assert!(debug.len() < 100, "{debug}");
+}
+
 #[test]
 fn garbage_collect_flows_succeeds() {
     // Smoke test that the `mxlGarbageCollectFlows` FFI binding is wired
@@ -169,3 +174,1143 @@ fn garbage_collect_flows_succeeds() {
     mxl_instance.garbage_collect_flows().unwrap();
     mxl_instance.destroy().unwrap();
 }
+
+#[test]
+fn library_version_matches_bindings() {
+    use mxl::MxlApiExt;
+
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    let version = mxl_api.version().unwrap();
+    info!("MXL library version: {version}");
+    #[cfg(not(feature = "mxl-not-built"))]
+    assert_eq!(u32::from(version.major), mxl_sys::MXL_VERSION_MAJOR);
+}
+
+#[cfg(feature = "archive")]
+#[test]
+fn domain_export_import_round_trip() {
+    let (mxl_instance, domain_guard) = setup_test("export");
+    let (_flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id();
+
+    let archive = domain_guard.path().join("export.tar");
+    mxl::MxlDomain::new(domain_guard.domain())
+        .export(&archive, true)
+        .unwrap();
+
+    let target_guard = TestDomainGuard::new("import");
+    let target = mxl::MxlDomain::new(target_guard.domain());
+    assert_eq!(target.import(&archive).unwrap(), vec![flow_id]);
+    assert!(
+        target
+            .flow_directory(&flow_id)
+            .join("flow_def.json")
+            .is_file()
+    );
+    // Importing the same flows twice must not clobber them.
+    assert!(target.import(&archive).is_err());
+}
+
+#[test]
+fn read_only_instance_cannot_write() {
+    let (mxl_instance, domain_guard) = setup_test("read_only");
+    let (_flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    let monitor = MxlInstance::open_read_only(mxl_api, domain_guard.domain().as_str()).unwrap();
+    assert!(monitor.is_read_only());
+    assert!(matches!(
+        monitor.create_flow_writer(audio_flow_def().as_str(), None),
+        Err(mxl::Error::PermissionDenied)
+    ));
+    assert!(matches!(
+        monitor.garbage_collect_flows(),
+        Err(mxl::Error::PermissionDenied)
+    ));
+    let flow_id = flow_config_info.common().id().to_string();
+    let reader = monitor.create_flow_reader(flow_id.as_str()).unwrap();
+    reader.get_info().unwrap();
+}
+
+#[test]
+fn flow_options_set_ring_depth() {
+    let (mxl_instance, _domain_guard) = setup_test("ring_depth");
+    let (_flow_writer, flow_config_info, was_created) = mxl_instance
+        .create_flow_writer_with_options(
+            v210_flow_def().as_str(),
+            &FlowOptions::new().history_duration(Duration::from_secs(1)),
+        )
+        .unwrap();
+    assert!(was_created);
+    // 1s worth of grains at 30000/1001.
+    assert_eq!(flow_config_info.ring_length(), 29);
+    assert!(flow_config_info.history_duration() <= Duration::from_secs(1));
+    assert!(flow_config_info.history_duration() > Duration::from_millis(900));
+}
+
+#[test]
+fn partial_grain_reading() {
+    let (mxl_instance, _domain_guard) = setup_test("partial_grain");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let grain_reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let current_index = mxl_instance.get_current_index(&rate);
+    grain_writer
+        .open_grain(current_index)
+        .unwrap()
+        .commit(10)
+        .unwrap();
+    let grain_data = grain_reader
+        .get_grain_slice(current_index, 10, Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(grain_data.valid_slices, 10);
+    assert!(!grain_data.is_complete());
+    assert!(
+        grain_reader
+            .get_grain_slice_non_blocking(current_index, 11)
+            .is_err()
+    );
+}
+
+#[test]
+fn host_payload_flows_refuse_device_access() {
+    let (mxl_instance, _domain_guard) = setup_test("host_payload");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    assert_eq!(
+        flow_config_info.common().payload_location(),
+        PayloadLocation::HostMemory
+    );
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let grain_reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    assert_eq!(grain_reader.payload_location(), PayloadLocation::HostMemory);
+
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let current_index = mxl_instance.get_current_index(&rate);
+    let mut access = grain_writer.open_grain(current_index).unwrap();
+    assert!(access.device_payload().is_none());
+    let total_slices = access.total_slices();
+    access.commit(total_slices).unwrap();
+    assert!(
+        grain_reader
+            .get_device_grain(current_index, u16::MAX, Duration::from_secs(5))
+            .is_err()
+    );
+}
+
+#[test]
+fn flow_staleness() {
+    let (mxl_instance, _domain_guard) = setup_test("staleness");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let grain_reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let grain_write_access = grain_writer
+        .open_grain(mxl_instance.get_current_index(&rate))
+        .unwrap();
+    let total_slices = grain_write_access.total_slices();
+    grain_write_access.commit(total_slices).unwrap();
+
+    assert!(!grain_reader.is_flow_stale(Duration::from_secs(5)).unwrap());
+    std::thread::sleep(Duration::from_millis(10));
+    assert!(
+        grain_reader
+            .is_flow_stale(Duration::from_millis(5))
+            .unwrap()
+    );
+}
+
+#[test]
+fn writer_flow_info_does_not_count_as_read() {
+    let (mxl_instance, _domain_guard) = setup_test("writer_info");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let grain_reader = mxl_instance
+        .create_flow_reader(flow_id.to_string().as_str())
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let index = mxl_instance.get_current_index(&rate);
+    let grain_write_access = grain_writer.open_grain(index).unwrap();
+    let total_slices = grain_write_access.total_slices();
+    grain_write_access.commit(total_slices).unwrap();
+
+    let before = grain_reader.get_runtime_info().unwrap();
+    let runtime = grain_writer.get_runtime_info().unwrap();
+    assert_eq!(runtime.headIndex, index);
+    assert_eq!(
+        grain_writer.get_info().unwrap().config.common().id(),
+        flow_id
+    );
+    assert_eq!(
+        grain_reader.get_runtime_info().unwrap().lastReadTime,
+        before.lastReadTime
+    );
+}
+
+#[test]
+fn epoch_offset_shifts_index_boundaries() {
+    let (mxl_instance, domain_guard) = setup_test("epoch_offset");
+    // Half a frame at 25 fps.
+    let offset = 20_000_000;
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    let house = MxlInstance::with_options(
+        mxl_api,
+        domain_guard.domain().as_str(),
+        &InstanceOptions::new().epoch_offset(offset),
+    )
+    .unwrap();
+    assert_eq!(house.epoch_offset(), offset);
+
+    let rate = mxl::rates::FPS_25;
+    let timestamp = 1_000_000_000_000;
+    assert_eq!(
+        house.timestamp_to_index(timestamp, &rate).unwrap(),
+        mxl_instance
+            .timestamp_to_index(timestamp - offset as u64, &rate)
+            .unwrap()
+    );
+    let index = mxl_instance.timestamp_to_index(timestamp, &rate).unwrap();
+    assert_eq!(
+        house.index_to_timestamp(index, &rate).unwrap(),
+        mxl_instance.index_to_timestamp(index, &rate).unwrap() + offset as u64
+    );
+    assert!(house.timestamp_to_index(0, &rate).is_err());
+
+    let current = house.get_current_index(&rate);
+    assert!(current > 0);
+    assert!(
+        house.get_duration_until_index(current + 2, &rate).unwrap() <= Duration::from_millis(80)
+    );
+}
+
+#[test]
+fn sleep_until_index_reports_lateness() {
+    let (mxl_instance, _domain_guard) = setup_test("sleep_until_index");
+    let rate = mxl::rates::FPS_25;
+
+    let current = mxl_instance.get_current_index(&rate);
+    assert_eq!(
+        mxl_instance.sleep_until_index(current + 1, &rate).unwrap(),
+        Duration::ZERO
+    );
+    assert!(mxl_instance.get_current_index(&rate) > current);
+
+    // Ten frames in the past returns immediately with at least 400 ms of lateness.
+    let current = mxl_instance.get_current_index(&rate);
+    assert!(
+        mxl_instance.sleep_until_index(current - 10, &rate).unwrap() >= Duration::from_millis(400)
+    );
+}
+
+#[test]
+fn sync_wrappers_share_reader_and_writer_between_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<mxl::SyncGrainReader>();
+    assert_send_sync::<mxl::SyncGrainWriter>();
+    assert_send_sync::<mxl::SyncSamplesReader>();
+    assert_send_sync::<mxl::SyncSamplesWriter>();
+
+    let (mxl_instance, _domain_guard) = setup_test("sync_wrappers");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let writer = std::sync::Arc::new(mxl::SyncGrainWriter::new(
+        flow_writer.to_grain_writer().unwrap(),
+    ));
+    let reader = std::sync::Arc::new(mxl::SyncGrainReader::new(
+        mxl_instance
+            .create_flow_reader(flow_id.as_str())
+            .unwrap()
+            .to_grain_reader()
+            .unwrap(),
+    ));
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let index = mxl_instance.get_current_index(&rate);
+
+    let handles: Vec<_> = (0..2u64)
+        .map(|offset| {
+            let writer = writer.clone();
+            std::thread::spawn(move || {
+                writer.write_grain_padded(index + offset, &[offset as u8; 16])
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap().unwrap();
+    }
+    for offset in 0..2u64 {
+        let reader = reader.clone();
+        let grain = std::thread::spawn(move || {
+            reader.get_complete_grain(index + offset, Duration::from_secs(5))
+        })
+        .join()
+        .unwrap()
+        .unwrap();
+        assert_eq!(&grain.payload[..16], &[offset as u8; 16]);
+    }
+}
+
+#[test]
+fn write_grain_copies_or_fills_in_place() {
+    let (mxl_instance, _domain_guard) = setup_test("write_grain");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut writer = flow_writer.to_grain_writer().unwrap();
+    let reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let index = mxl_instance.get_current_index(&rate);
+
+    let access = writer.open_grain(index).unwrap();
+    let grain_size = access.max_size() as usize;
+    access.cancel().unwrap();
+    writer.write_grain(index, &vec![7; grain_size]).unwrap();
+    let grain = reader
+        .get_complete_grain(index, Duration::from_secs(5))
+        .unwrap();
+    assert!(grain.payload.iter().all(|&byte| byte == 7));
+
+    // Only the padded variant takes shorter payloads, neither takes longer ones.
+    assert!(writer.write_grain(index + 1, &[7; 16]).is_err());
+    assert!(
+        writer
+            .write_grain(index + 1, &vec![0; grain_size + 1])
+            .is_err()
+    );
+    assert!(
+        writer
+            .write_grain_padded(index + 1, &vec![0; grain_size + 1])
+            .is_err()
+    );
+
+    writer
+        .write_grain_with(index + 1, |payload| payload.fill(9))
+        .unwrap();
+    let grain = reader
+        .get_complete_grain(index + 1, Duration::from_secs(5))
+        .unwrap();
+    assert!(grain.payload.iter().all(|&byte| byte == 9));
+
+    // A shorter payload in the same ring slot leaves none of the previous grain behind.
+    let same_slot = index + 1 + u64::from(flow_config_info.ring_length());
+    writer.write_grain_padded(same_slot, &[7; 16]).unwrap();
+    let grain = reader
+        .get_complete_grain(same_slot, Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(&grain.payload[..16], &[7; 16]);
+    assert!(grain.payload[16..].iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn snapshot_copies_the_ring() {
+    let (mxl_instance, _domain_guard) = setup_test("snapshot");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut writer = flow_writer.to_grain_writer().unwrap();
+    let reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let index = mxl_instance.get_current_index(&rate);
+    for offset in 0..3 {
+        writer
+            .write_grain_padded(index + offset, &[offset as u8; 16])
+            .unwrap();
+    }
+    let grains = reader.snapshot().unwrap();
+    let indices: Vec<u64> = grains.iter().map(|grain| grain.index).collect();
+    assert_eq!(indices, [index, index + 1, index + 2]);
+    for (offset, grain) in grains.iter().enumerate() {
+        assert!(grain.is_complete());
+        assert_eq!(&grain.payload[..16], &[offset as u8; 16]);
+    }
+
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut writer = flow_writer.to_samples_writer().unwrap();
+    let reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_samples_reader()
+        .unwrap();
+    let rate = flow_config_info.common().sample_rate().unwrap();
+    let head = mxl_instance.get_current_index(&rate);
+    let mut access = writer.open_samples(head, 8).unwrap();
+    for channel in 0..access.channels() {
+        let (first, second) = access.channel_data_mut(channel).unwrap();
+        first.fill(3);
+        second.fill(3);
+    }
+    access.commit().unwrap();
+    let snapshot = reader.snapshot().unwrap();
+    let buffer_length = flow_config_info.continuous().unwrap().bufferLength as usize;
+    assert_eq!(snapshot.head_index, head);
+    assert_eq!(snapshot.samples.num_of_samples(), buffer_length / 2);
+    assert_eq!(
+        snapshot.first_index(),
+        head + 1 - (buffer_length / 2) as u64
+    );
+    let tail = snapshot.samples.payload[0].len() - 8 * std::mem::size_of::<f32>();
+    assert!(snapshot.samples.payload[0][tail..].iter().all(|&b| b == 3));
+}
+
+#[test]
+fn data_grain_packets_round_trip() {
+    let (mxl_instance, _domain_guard) = setup_test("data_packets");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(data_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let mut writer = mxl::DataGrainWriter::new(
+        flow_writer.to_grain_writer().unwrap(),
+        mxl_instance.clone(),
+        rate,
+    );
+    let reader = mxl::DataGrainReader::new(
+        mxl_instance
+            .create_flow_reader(flow_id.as_str())
+            .unwrap()
+            .to_grain_reader()
+            .unwrap(),
+    );
+
+    let first = writer.write_packet(br#"{"tally":"red"}"#).unwrap();
+    // A second write in the same grain interval is paced to the next index.
+    let second = writer
+        .write_packets(&[b"\x06\x0e\x2b\x34", b"klv"])
+        .unwrap();
+    assert_eq!(second, first + 1);
+    assert!(writer.write_packet(&[]).is_err());
+    assert!(
+        writer
+            .write_packet(&vec![0; mxl::MXL_DATA_FORMAT_GRAIN_SIZE])
+            .is_err()
+    );
+
+    let grains = reader.read_packets(first - 5..second + 5).unwrap();
+    assert_eq!(
+        grains,
+        vec![
+            mxl::DataPackets {
+                index: first,
+                packets: vec![br#"{"tally":"red"}"#.to_vec()],
+            },
+            mxl::DataPackets {
+                index: second,
+                packets: vec![b"\x06\x0e\x2b\x34".to_vec(), b"klv".to_vec()],
+            },
+        ]
+    );
+    assert_eq!(
+        reader
+            .read_grain_packets(second, Duration::from_secs(1))
+            .unwrap()
+            .packets
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn peek_latest_samples_ends_at_head() {
+    let (mxl_instance, _domain_guard) = setup_test("peek_latest");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut samples_writer = flow_writer.to_samples_writer().unwrap();
+    let samples_reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_samples_reader()
+        .unwrap();
+    let rate = flow_config_info.common().sample_rate().unwrap();
+    let head = mxl_instance.get_current_index(&rate);
+
+    let mut access = samples_writer.open_samples(head, 32).unwrap();
+    for channel in 0..access.channels() {
+        let (first, second) = access.channel_data_mut(channel).unwrap();
+        first.fill(channel as u8 + 1);
+        second.fill(channel as u8 + 1);
+    }
+    access.commit().unwrap();
+
+    let peeked = samples_reader.peek_latest(16).unwrap();
+    let continuous = flow_config_info.continuous().unwrap();
+    assert_eq!(peeked.payload.len(), continuous.channelCount as usize);
+    for (channel, payload) in peeked.payload.iter().enumerate() {
+        assert_eq!(payload.len(), 16 * std::mem::size_of::<f32>());
+        assert!(payload.iter().all(|&b| b == channel as u8 + 1));
+    }
+
+    let buffer_length = continuous.bufferLength as usize;
+    assert!(samples_reader.peek_latest(buffer_length).is_err());
+}
+
+#[test]
+fn sample_batcher_commits_whole_batches() {
+    let (mxl_instance, _domain_guard) = setup_test("sample_batcher");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let samples_reader = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .to_samples_reader()
+        .unwrap();
+    let rate = flow_config_info.common().sample_rate().unwrap();
+    let start = mxl_instance.get_current_index(&rate);
+    let channels = flow_config_info.continuous().unwrap().channelCount as usize;
+    let mut batcher = SampleBatcher::new(
+        flow_writer.to_samples_writer().unwrap(),
+        &flow_config_info,
+        start,
+    )
+    .unwrap()
+    .batch_size(48);
+
+    // Interleaved F32 frames numbered from 0, channel `c` of frame `n` holds `n + c / 10`.
+    let frames = |range: std::ops::Range<usize>| -> Vec<u8> {
+        range
+            .flat_map(|frame| (0..channels).map(move |c| frame as f32 + c as f32 / 10.0))
+            .flat_map(f32::to_le_bytes)
+            .collect()
+    };
+    assert_eq!(batcher.push(&frames(0..30)).unwrap(), 0);
+    assert_eq!(batcher.pending(), 30);
+    assert_eq!(batcher.push(&frames(30..130)).unwrap(), 96);
+    assert_eq!(batcher.index(), start + 96);
+    assert_eq!(batcher.pending(), 34);
+    assert!(batcher.push(&frames(0..1)[1..]).is_err());
+    assert_eq!(batcher.flush().unwrap(), 34);
+    assert_eq!(batcher.index(), start + 130);
+
+    let samples: OwnedSamplesData = samples_reader
+        .get_samples(start + 130, 130, Duration::from_secs(5))
+        .unwrap()
+        .into();
+    let interleaved = samples.interleave(&InterleaveOptions::new()).unwrap();
+    assert_eq!(interleaved, frames(0..130));
+}
+
+#[test]
+fn head_notifier_signals_commits() {
+    use std::os::fd::AsRawFd;
+
+    let (mxl_instance, _domain_guard) = setup_test("head_notifier");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let notifier =
+        HeadNotifier::new(mxl_instance.create_flow_reader(flow_id.as_str()).unwrap()).unwrap();
+    let readable = |timeout_ms| {
+        let mut poll_fd = libc::pollfd {
+            fd: notifier.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) == 1 }
+    };
+    assert!(!readable(100));
+
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let current_index = mxl_instance.get_current_index(&rate);
+    let access = grain_writer.open_grain(current_index).unwrap();
+    let total_slices = access.total_slices();
+    access.commit(total_slices).unwrap();
+
+    assert!(readable(5000));
+    assert_eq!(notifier.head_index(), current_index);
+    notifier.clear().unwrap();
+    assert!(!readable(100));
+    assert!(!notifier.is_closed());
+}
+
+#[test]
+fn create_flow_writer_from_typed_definition() {
+    let (mxl_instance, _domain_guard) = setup_test("typed_writer");
+    let flow_def: mxl::flowdef::FlowDef = serde_json::from_str(&v210_flow_def()).unwrap();
+    let (writer, info, stored, was_created) = mxl_instance
+        .create_flow_writer_typed(&flow_def, None)
+        .unwrap();
+    assert!(was_created);
+    assert_eq!(info.common().id(), flow_def.id);
+    assert_eq!(stored, flow_def);
+    writer.to_grain_writer().unwrap();
+
+    let options = FlowOptions::new().history_duration(Duration::from_secs(1));
+    let (_writer, _, stored, was_created) = mxl_instance
+        .create_flow_writer_typed(&flow_def, Some(&options))
+        .unwrap();
+    assert!(!was_created);
+    assert_eq!(stored, flow_def);
+}
+
+#[test]
+fn flow_writer_converts_without_the_flow_files() {
+    let (mxl_instance, domain_guard) = setup_test("writer_conversion");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+    assert_eq!(flow_writer.data_format(), mxl::DataFormat::Audio);
+    let flow_id = flow_config_info.common().id();
+    // The flow definition is no longer needed once the writer exists.
+    std::fs::remove_file(
+        mxl::MxlDomain::new(domain_guard.domain())
+            .flow_directory(&flow_id)
+            .join("flow_def.json"),
+    )
+    .unwrap();
+    let samples_writer = flow_writer.to_samples_writer().unwrap();
+    assert_eq!(samples_writer.sample_format(), mxl::SampleFormat::F32);
+
+    let (flow_writer, _, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    assert!(flow_writer.to_samples_writer().is_err());
+}
+
+#[test]
+fn into_typed_matches_the_flow_format() {
+    let (mxl_instance, _domain_guard) = setup_test("into_typed");
+    for (flow_def, discrete) in [(v210_flow_def(), true), (audio_flow_def(), false)] {
+        let (_flow_writer, flow_config_info, _) = mxl_instance
+            .create_flow_writer(flow_def.as_str(), None)
+            .unwrap();
+        let flow_id = flow_config_info.common().id();
+        let reader = mxl_instance
+            .create_flow_reader(flow_id.to_string().as_str())
+            .unwrap()
+            .into_typed()
+            .unwrap();
+        assert_eq!(reader.flow_id(), flow_id);
+        assert_eq!(matches!(reader, TypedReader::Grain(_)), discrete);
+    }
+}
+
+#[test]
+fn head_changes_follow_commits() {
+    let (mxl_instance, _domain_guard) = setup_test("head_changes");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let mut changes = mxl_instance
+        .create_flow_reader(flow_id.as_str())
+        .unwrap()
+        .head_changes()
+        .unwrap();
+    assert_eq!(
+        changes.next_within(Duration::from_millis(100)).unwrap(),
+        None
+    );
+
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let current_index = mxl_instance.get_current_index(&rate);
+    grain_writer.write_grain_padded(current_index, &[]).unwrap();
+    assert_eq!(changes.next().unwrap().unwrap(), current_index);
+
+    let waiter = std::thread::spawn(move || changes.next().unwrap().unwrap());
+    grain_writer
+        .write_grain_padded(current_index + 1, &[])
+        .unwrap();
+    assert_eq!(waiter.join().unwrap(), current_index + 1);
+}
+
+#[test]
+fn reader_service_delivers_grains_and_staleness() {
+    let (mxl_instance, _domain_guard) = setup_test("reader_service");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id().to_string();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let current_index = mxl_instance.get_current_index(&rate);
+    let mut access = grain_writer.open_grain(current_index).unwrap();
+    access.payload_mut().fill(42);
+    let total_slices = access.total_slices();
+    access.commit(total_slices).unwrap();
+
+    let mut service =
+        ReaderService::new(mxl_instance.clone()).stale_threshold(Duration::from_millis(200));
+    let events = service.subscribe(flow_id.as_str()).unwrap();
+    match events.recv_timeout(Duration::from_secs(5)).unwrap() {
+        FlowEvent::Grain { index, grain } => {
+            assert_eq!(index, current_index);
+            assert!(grain.payload.iter().all(|&b| b == 42));
+        }
+        _ => panic!("expected the head grain"),
+    }
+    assert!(matches!(
+        events.recv_timeout(Duration::from_secs(5)).unwrap(),
+        FlowEvent::Stale
+    ));
+    assert!(service.subscribe(flow_id.as_str()).is_err());
+    service.remove_flow(flow_id.as_str()).unwrap();
+    assert_eq!(service.flows().count(), 0);
+}
+
+#[test]
+fn domain_quota_rejects_flow_creation() {
+    let (mxl_instance, domain_guard) = setup_test("quota");
+    let (_video_writer, video_config, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let usage = mxl_instance.domain().usage().unwrap();
+    assert_eq!(usage.flows.len(), 1);
+    assert_eq!(usage.flows[0].flow_id, video_config.common().id());
+    assert!(usage.total > 0);
+    assert!(usage.capacity >= usage.available);
+    // Test domains are created in /dev/shm.
+    assert!(mxl_instance.is_domain_on_tmpfs().unwrap());
+
+    // The domain is already at its quota.
+    mxl_instance.set_quota(Some(usage.total));
+    assert!(matches!(
+        mxl_instance.create_flow_writer(audio_flow_def().as_str(), None),
+        Err(mxl::Error::QuotaExceeded { .. })
+    ));
+
+    // The new flow would take the domain past its quota, so it is not created.
+    let audio_size = mxl_instance
+        .domain()
+        .projected_flow_size(audio_flow_def().as_str(), None)
+        .unwrap();
+    assert!(audio_size > 0);
+    // An upper bound, the grains of the video flow are only allocated once written.
+    assert!(
+        mxl_instance
+            .domain()
+            .projected_flow_size(v210_flow_def().as_str(), None)
+            .unwrap()
+            >= usage.total
+    );
+    mxl_instance.set_quota(Some(usage.total + audio_size - 1));
+    assert!(matches!(
+        mxl_instance.create_flow_writer(audio_flow_def().as_str(), None),
+        Err(mxl::Error::QuotaExceeded { .. })
+    ));
+    assert_eq!(
+        mxl::MxlDomain::new(domain_guard.domain())
+            .flow_ids()
+            .unwrap(),
+        vec![video_config.common().id()]
+    );
+
+    assert_eq!(
+        mxl::MxlDomain::new(domain_guard.domain())
+            .flow_def(&video_config.common().id())
+            .unwrap(),
+        mxl_instance
+            .get_flow_def_typed(video_config.common().id())
+            .unwrap()
+    );
+
+    // Opening the existing flow is not affected.
+    mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+
+    mxl_instance.set_quota(None);
+    assert_eq!(mxl_instance.quota(), None);
+    mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+}
+
+#[test]
+fn writers_keep_the_flow_config() {
+    let (mxl_instance, _domain_guard) = setup_test("writer_config");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id();
+    assert_eq!(flow_writer.config_info().common().id(), flow_id);
+    let grain_writer = flow_writer.to_grain_writer().unwrap();
+    assert_eq!(grain_writer.flow_id(), flow_id);
+    assert_eq!(
+        grain_writer.config_info().discrete().unwrap().grainCount,
+        flow_config_info.discrete().unwrap().grainCount
+    );
+
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(audio_flow_def().as_str(), None)
+        .unwrap();
+    let samples_writer = flow_writer.to_samples_writer().unwrap();
+    assert_eq!(samples_writer.flow_id(), flow_config_info.common().id());
+    assert_eq!(
+        samples_writer
+            .config_info()
+            .continuous()
+            .unwrap()
+            .channelCount,
+        flow_config_info.continuous().unwrap().channelCount
+    );
+}
+
+#[test]
+fn flow_ids_are_validated_before_the_library_call() {
+    let (mxl_instance, _domain_guard) = setup_test("flow_ids");
+    let (_flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let uuid = flow_config_info.common().id();
+    let flow_id = FlowId::from(uuid);
+    assert_eq!(
+        FlowId::try_from(uuid.to_string().to_uppercase()).unwrap(),
+        flow_id
+    );
+    assert_eq!(flow_id.to_string(), uuid.to_string());
+
+    assert_eq!(
+        mxl_instance.create_flow_reader(uuid).unwrap().flow_id(),
+        uuid
+    );
+    assert_eq!(
+        mxl_instance.create_flow_reader(flow_id).unwrap().flow_id(),
+        uuid
+    );
+    assert_eq!(mxl_instance.get_flow_def_typed(flow_id).unwrap().id, uuid);
+    let error = mxl_instance
+        .create_flow_reader("not-a-flow-id")
+        .unwrap_err();
+    assert!(matches!(error, mxl::Error::Other(_)), "{error:?}");
+}
+
+#[test]
+fn flow_options_set_the_permissions_of_created_flows() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (mxl_instance, domain_guard) = setup_test("flow_permissions");
+    let (_flow_writer, flow_config_info, was_created) = mxl_instance
+        .create_flow_writer_with_options(
+            v210_flow_def().as_str(),
+            &FlowOptions::new().with_mode(0o640),
+        )
+        .unwrap();
+    assert!(was_created);
+    let flow_dir =
+        mxl::MxlDomain::new(domain_guard.domain()).flow_directory(&flow_config_info.common().id());
+    let mode =
+        |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+    assert_eq!(mode(&flow_dir), 0o750);
+    assert_eq!(mode(&flow_dir.join("data")), 0o640);
+    assert_eq!(mode(&flow_dir.join("flow_def.json")), 0o640);
+
+    assert!(FlowOptions::new().with_mode(0o10644).validate().is_err());
+}
+
+#[test]
+fn flow_is_removed_when_its_permissions_cannot_be_applied() {
+    // Only unprivileged writers fail to give their flows to another user.
+    if unsafe { libc::geteuid() } == 0 {
+        return;
+    }
+    let (mxl_instance, domain_guard) = setup_test("flow_permissions_denied");
+    let options = FlowOptions::new().with_mode(0o640).with_owner(0);
+    assert_eq!(options.mode(), Some(0o640));
+    assert_eq!(options.owner(), Some(0));
+    assert_eq!(options.group(), None);
+
+    let error = mxl_instance
+        .create_flow_writer_with_options(v210_flow_def().as_str(), &options)
+        .unwrap_err();
+    assert!(matches!(error, mxl::Error::Io(_)), "{error:?}");
+    let domain = mxl::MxlDomain::new(domain_guard.domain());
+    assert!(domain.flow_ids().unwrap().is_empty());
+}
+
+#[test]
+fn data_flows_convert_to_framed_readers_and_writers() {
+    let (mxl_instance, _domain_guard) = setup_test("data_framing");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(data_flow_def().as_str(), None)
+        .unwrap();
+    let mut writer = flow_writer
+        .to_data_writer()
+        .unwrap()
+        .framing(DataFraming::SingleMessage);
+    let reader = mxl_instance
+        .create_flow_reader(flow_config_info.common().id())
+        .unwrap()
+        .to_data_reader()
+        .unwrap()
+        .framing(DataFraming::SingleMessage);
+
+    assert!(writer.write_packets(&[b"first", b"second"]).is_err());
+    let index = writer.write_packet(b"event").unwrap();
+    assert_eq!(
+        reader
+            .read_grain_packets(index, Duration::from_secs(1))
+            .unwrap()
+            .packets,
+        vec![b"event".to_vec()]
+    );
+
+    let (video_writer, video_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    assert!(
+        mxl_instance
+            .create_flow_reader(video_config_info.common().id())
+            .unwrap()
+            .to_data_reader()
+            .is_err()
+    );
+    assert!(video_writer.to_data_writer().is_err());
+}
+
+#[test]
+fn events_round_trip_as_json() {
+    use mxl::events::{EventPublisher, EventSubscriber};
+
+    let (mxl_instance, _domain_guard) = setup_test("events");
+    let flow_id = uuid::Uuid::new_v4();
+    let mut publisher =
+        EventPublisher::create(&mxl_instance, flow_id, "tally", mxl::rates::FPS_50).unwrap();
+    let mut subscriber = EventSubscriber::open(&mxl_instance, flow_id).unwrap();
+    assert!(subscriber.poll().unwrap().is_empty());
+
+    let first = publisher
+        .publish(&serde_json::json!({ "tally": "red" }))
+        .unwrap();
+    let second = publisher.publish_all(&["cut", "fade"]).unwrap();
+    assert_eq!(second, first + 1);
+    assert!(publisher.publish_all::<u32>(&[]).is_err());
+
+    let mut events = subscriber.recv(Duration::from_secs(1)).unwrap();
+    while events.len() < 3 {
+        events.extend(subscriber.recv(Duration::from_secs(1)).unwrap());
+    }
+    let received: Vec<_> = events
+        .iter()
+        .map(|event| (event.index, event.value.clone()))
+        .collect();
+    assert_eq!(
+        received,
+        vec![
+            (first, serde_json::json!({ "tally": "red" })),
+            (second, serde_json::json!("cut")),
+            (second, serde_json::json!("fade")),
+        ]
+    );
+
+    // Only event flows can be subscribed to.
+    let (_video_writer, video_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    assert!(EventSubscriber::open(&mxl_instance, video_config_info.common().id()).is_err());
+}
+
+#[test]
+fn attachments_report_the_writer_process() {
+    let (mxl_instance, _domain_guard) = setup_test("attachments");
+    let (_flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id();
+    let _reader = mxl_instance.create_flow_reader(flow_id).unwrap();
+    let attachments = mxl_instance.domain().attachments(&flow_id).unwrap();
+    // The reader is in the writer's process, so it is not reported separately.
+    assert_eq!(attachments.writers, vec![std::process::id()]);
+    assert_eq!(attachments.reader_count(), 0);
+}
+
+#[test]
+fn live_objects_list_what_keeps_the_instance_in_use() {
+    let domain_guard = TestDomainGuard::new("live_objects");
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    let mxl_instance = MxlInstance::with_options(
+        mxl_api,
+        domain_guard.domain().as_str(),
+        &InstanceOptions::new().track_live_objects(true),
+    )
+    .unwrap();
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let flow_id = flow_config_info.common().id();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let grain_reader = mxl_instance
+        .create_flow_reader(flow_id)
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let access = grain_writer.open_grain(0).unwrap();
+
+    let kinds = |instance: &MxlInstance| {
+        instance
+            .live_objects()
+            .into_iter()
+            .map(|object| object.kind)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        kinds(&mxl_instance),
+        [
+            LiveObjectKind::GrainWriter,
+            LiveObjectKind::GrainReader,
+            LiveObjectKind::GrainWriteAccess
+        ]
+    );
+    assert!(
+        mxl_instance
+            .live_objects()
+            .iter()
+            .all(|object| object.flow_id == flow_id)
+    );
+
+    drop(access);
+    drop(grain_reader);
+    assert_eq!(kinds(&mxl_instance), [LiveObjectKind::GrainWriter]);
+    assert!(mxl_instance.clone().destroy().is_err());
+    drop(grain_writer);
+    assert!(mxl_instance.live_objects().is_empty());
+    mxl_instance.destroy().unwrap();
+}
+
+#[test]
+fn data_writers_pace_grains_to_their_time_source() {
+    let (mxl_instance, _domain_guard) = setup_test("time_source");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(data_flow_def().as_str(), None)
+        .unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let start = mxl_instance.get_current_index(&rate);
+    let clock = VirtualClock::at_index(start, &rate);
+    assert_eq!(
+        TimeSource::timestamp_to_index(&clock, clock.now(), &rate).unwrap(),
+        mxl_instance.timestamp_to_index(clock.now(), &rate).unwrap()
+    );
+    let mut writer = flow_writer
+        .to_data_writer()
+        .unwrap()
+        .time_source(std::sync::Arc::new(clock.clone()));
+
+    // Writing ten grains at once takes ten grain intervals, of virtual time only.
+    let wall_clock = std::time::Instant::now();
+    for offset in 0..10 {
+        assert_eq!(writer.write_packet(b"tick").unwrap(), start + offset);
+    }
+    assert!(wall_clock.elapsed() < Duration::from_millis(100));
+    assert_eq!(clock.current_index(&rate), start + 9);
+}
+
+#[test]
+fn loopback_grains_arrive_intact() {
+    let (mxl_instance, _domain_guard) = setup_test("loopback");
+    let loopback = Loopback::start(
+        &mxl_instance,
+        &mxl_test_utils::resize_video_flow_def(&v210_flow_def(), 64, 16),
+    );
+    let reader = loopback.reader();
+    assert!(loopback.wait_for_grains(1, Duration::from_secs(5)));
+    let first = reader.get_info().unwrap().runtime.head_index();
+    for index in first..first + 5 {
+        let grain = reader
+            .get_complete_grain(index, Duration::from_secs(1))
+            .unwrap();
+        verify_grain(&grain).unwrap();
+    }
+
+    let mut corrupted = reader
+        .get_complete_grain(first, Duration::from_secs(1))
+        .unwrap()
+        .to_owned();
+    corrupted.payload[100] ^= 1;
+    assert!(matches!(
+        verify_pattern(&corrupted.payload),
+        Err(PatternMismatch::Byte { offset: 100, .. })
+    ));
+    assert!(loopback.stop() >= 6);
+}
+
+#[test]
+fn detached_grains_are_filled_without_the_writer() {
+    let (mxl_instance, _domain_guard) = setup_test("detached_grains");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(
+            &mxl_test_utils::resize_video_flow_def(&v210_flow_def(), 64, 16),
+            None,
+        )
+        .unwrap();
+    let flow_id = flow_config_info.common().id();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let grain_reader = mxl_instance
+        .create_flow_reader(flow_id)
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let index = mxl_instance.get_current_index(&rate);
+
+    // SAFETY: the payload is only filled before the grain is resumed, and the writer outlives it.
+    let detached = unsafe { grain_writer.open_grain(index).unwrap().detach() };
+    assert_eq!(detached.index(), index);
+    // The writer's single open grain is taken until the detached one is resumed.
+    assert!(grain_writer.open_grain(index + 1).is_err());
+    let filler = std::thread::spawn(move || {
+        // SAFETY: the grain is open until it is resumed below, after the thread is joined.
+        let payload = unsafe {
+            std::slice::from_raw_parts_mut(detached.payload_ptr(), detached.max_size() as usize)
+        };
+        fill_pattern(index, payload);
+        detached
+    });
+    let detached = filler.join().unwrap();
+    let access = grain_writer.resume_grain(detached).unwrap();
+    let total_slices = access.total_slices();
+    access.commit(total_slices).unwrap();
+    let grain = grain_reader
+        .get_complete_grain(index, Duration::from_secs(5))
+        .unwrap();
+    verify_grain(&grain).unwrap();
+}
@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The cleanup guard is process-wide, so its tests live in a binary of their own. The signal
+//! tests run this binary again as a child process, which the signals terminate.
+
+use std::{
+    os::unix::process::ExitStatusExt,
+    path::PathBuf,
+    process::{Command, Output},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use mxl::{CleanupAction, CleanupGuard, MxlDomain, MxlInstance, config::get_mxl_so_path};
+use mxl_test_utils::{TestDomainGuard, setup_test, v210_flow_def};
+
+/// Set in the environment of child processes to the domain they create their flow in.
+const CHILD_ENV: &str = "MXL_CLEANUP_CHILD";
+/// Prefix of the line a child prints with the directory of its flow.
+const FLOW_DIR_LINE: &str = "flow directory: ";
+
+static HANDLED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sigterm(_: libc::c_int) {
+    HANDLED.store(true, Ordering::Release);
+}
+
+#[test]
+fn cleanup_guard_removes_the_flows_still_written() {
+    let (mxl_instance, domain_guard) = setup_test("cleanup_guard");
+    let guard = CleanupGuard::install(&mxl_instance, CleanupAction::DeleteCreatedFlows).unwrap();
+    assert!(CleanupGuard::install(&mxl_instance, CleanupAction::Keep).is_err());
+
+    let (flow_writer, flow_config_info, was_created) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    assert!(was_created);
+    let flow_id = flow_config_info.common().id();
+    let flow_dir = MxlDomain::new(domain_guard.domain()).flow_directory(&flow_id);
+    assert!(flow_dir.exists());
+    let flow_reader = mxl_instance.create_flow_reader(flow_id).unwrap();
+
+    guard.cleanup_now();
+    assert!(!flow_dir.exists());
+    // Released by the guard, so dropping them does not release them again.
+    drop(flow_reader);
+    drop(flow_writer);
+    drop(guard);
+
+    let guard = CleanupGuard::install(&mxl_instance, CleanupAction::Keep).unwrap();
+    assert_eq!(guard.action(), CleanupAction::Keep);
+}
+
+#[test]
+fn signal_removes_the_flows_and_terminates() {
+    let domain_guard = TestDomainGuard::new("cleanup_signal");
+    let (output, flow_dir) = run_child("child_raises_sigterm", &domain_guard);
+    assert_eq!(output.status.signal(), Some(libc::SIGTERM), "{output:?}");
+    assert!(!flow_dir.exists());
+}
+
+#[test]
+fn signal_handled_by_the_application_is_passed_on() {
+    let domain_guard = TestDomainGuard::new("cleanup_chained");
+    let (output, flow_dir) = run_child("child_handles_sigterm", &domain_guard);
+    assert!(output.status.success(), "{output:?}");
+    // The exit hook is opt-in.
+    assert!(flow_dir.exists());
+}
+
+#[test]
+fn exit_hook_removes_the_flows_when_enabled() {
+    let domain_guard = TestDomainGuard::new("cleanup_exit");
+    let (output, flow_dir) = run_child("child_exits_with_exit_hook", &domain_guard);
+    assert!(output.status.success(), "{output:?}");
+    assert!(!flow_dir.exists());
+}
+
+/// Runs the child test `name` in a domain of its own and returns its output and the directory of
+/// the flow it created.
+fn run_child(name: &str, domain_guard: &TestDomainGuard) -> (Output, PathBuf) {
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", name, "--nocapture"])
+        .env(CHILD_ENV, domain_guard.domain())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let flow_dir = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix(FLOW_DIR_LINE))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| panic!("The child did not create its flow: {output:?}"));
+    (output, flow_dir)
+}
+
+/// Creates a flow under a guard in the domain of [`CHILD_ENV`], `None` unless spawned by a test.
+fn child_flow(on_exit: bool) -> Option<(MxlInstance, CleanupGuard, mxl::FlowWriter, PathBuf)> {
+    let domain = std::env::var(CHILD_ENV).ok()?;
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    let instance = MxlInstance::new(mxl_api, &domain, "").unwrap();
+    let guard = CleanupGuard::install(&instance, CleanupAction::DeleteCreatedFlows)
+        .unwrap()
+        .cleanup_on_exit(on_exit);
+    let (flow_writer, flow_config_info, was_created) = instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    assert!(was_created);
+    let flow_dir = MxlDomain::new(domain).flow_directory(&flow_config_info.common().id());
+    assert!(flow_dir.exists());
+    println!("{FLOW_DIR_LINE}{}", flow_dir.display());
+    Some((instance, guard, flow_writer, flow_dir))
+}
+
+/// Child of `signal_removes_the_flows_and_terminates`, a no-op unless spawned by it.
+#[test]
+fn child_raises_sigterm() {
+    let Some(_flow) = child_flow(false) else {
+        return;
+    };
+    unsafe { libc::raise(libc::SIGTERM) };
+    // The cleanup thread terminates the process.
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Child of `signal_handled_by_the_application_is_passed_on`, a no-op unless spawned by it.
+#[test]
+fn child_handles_sigterm() {
+    if std::env::var(CHILD_ENV).is_err() {
+        return;
+    }
+    unsafe {
+        libc::signal(
+            libc::SIGTERM,
+            on_sigterm as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+        libc::signal(libc::SIGHUP, libc::SIG_IGN);
+    }
+    let (_instance, _guard, _flow_writer, flow_dir) = child_flow(false).unwrap();
+    unsafe {
+        libc::raise(libc::SIGTERM);
+        libc::raise(libc::SIGHUP);
+    }
+    assert!(HANDLED.load(Ordering::Acquire));
+    // The flows are left to the application, which exits without releasing its writer.
+    assert!(flow_dir.exists());
+    std::process::exit(0);
+}
+
+/// Child of `exit_hook_removes_the_flows_when_enabled`, a no-op unless spawned by it.
+#[test]
+fn child_exits_with_exit_hook() {
+    let Some((_instance, _guard, _flow_writer, flow_dir)) = child_flow(true) else {
+        return;
+    };
+    assert!(flow_dir.exists());
+    std::process::exit(0);
+}
@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mxl::{Rational, TimeSource, VirtualClock, rates};
+
+#[test]
+fn index_conversions_round_like_the_library() {
+    let clock = VirtualClock::default();
+    assert_eq!(
+        clock.index_to_timestamp(1, &rates::FPS_25).unwrap(),
+        40_000_000
+    );
+    // 1001 / 30 ms, rounded to the nearest nanosecond.
+    assert_eq!(
+        clock.index_to_timestamp(1, &rates::FPS_29_97).unwrap(),
+        33_366_667
+    );
+    assert_eq!(
+        clock
+            .timestamp_to_index(33_366_667, &rates::FPS_29_97)
+            .unwrap(),
+        1
+    );
+    // Timestamps round to the nearest index.
+    assert_eq!(
+        clock
+            .timestamp_to_index(19_999_999, &rates::FPS_25)
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        clock
+            .timestamp_to_index(20_000_000, &rates::FPS_25)
+            .unwrap(),
+        1
+    );
+    let invalid = Rational {
+        numerator: 25,
+        denominator: 0,
+    };
+    assert!(clock.timestamp_to_index(0, &invalid).is_err());
+    assert_eq!(clock.current_index(&invalid), 0);
+}
+
+#[test]
+fn virtual_clock_moves_only_when_told() {
+    let rate = rates::FPS_50;
+    let clock = VirtualClock::at_index(1_000, &rate);
+    let shared = clock.clone();
+    assert_eq!(clock.current_index(&rate), 1_000);
+
+    shared.advance(Duration::from_millis(40));
+    assert_eq!(clock.current_index(&rate), 1_002);
+
+    assert_eq!(
+        clock.sleep_until_index(1_010, &rate).unwrap(),
+        Duration::ZERO
+    );
+    assert_eq!(shared.current_index(&rate), 1_010);
+    // Sleeping never moves the clock back, and reports how late the caller is.
+    assert_eq!(
+        clock.sleep_until_index(1_008, &rate).unwrap(),
+        Duration::from_millis(40)
+    );
+    assert_eq!(
+        shared.now(),
+        clock.index_to_timestamp(1_010, &rate).unwrap()
+    );
+}
@@ -0,0 +1,41 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use mxl::{Dither, I24, float_to_pcm, pcm_to_float};
+
+fn f32_bytes(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+#[test]
+fn float_to_pcm_spans_wrapped_fragments_and_clips() {
+    let first = f32_bytes(&[0.5, -1.0]);
+    let second = f32_bytes(&[2.0]);
+
+    let mut s16 = [0i16; 3];
+    float_to_pcm((&first, &second), &mut s16, Dither::None).unwrap();
+    assert_eq!(s16, [16384, -32768, 32767]);
+
+    let mut s24 = [I24::default(); 3];
+    float_to_pcm((&first, &second), &mut s24, Dither::None).unwrap();
+    assert_eq!(s24, [I24(0x40_0000), I24(-0x80_0000), I24(0x7f_ffff)]);
+
+    let mut s32 = [0i32; 3];
+    float_to_pcm((&first, &second), &mut s32, Dither::None).unwrap();
+    assert_eq!(s32, [0x4000_0000, i32::MIN, i32::MAX]);
+
+    assert!(float_to_pcm((&first, &second), &mut [0i16; 2], Dither::None).is_err());
+}
+
+#[test]
+fn pcm_to_float_fills_both_fragments() {
+    let mut first = vec![0; 4];
+    let mut second = vec![0; 4];
+    pcm_to_float(
+        &[I24(0x40_0000), I24(-0x80_0000)],
+        (&mut first, &mut second),
+    )
+    .unwrap();
+    assert_eq!(first, f32_bytes(&[0.5]));
+    assert_eq!(second, f32_bytes(&[-1.0]));
+}
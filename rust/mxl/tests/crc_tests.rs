@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "crc")]
+
+use std::time::Duration;
+
+use mxl::crc::{CrcCheck, CrcPublisher, CrcVerifier, payload_crc};
+use mxl_test_utils::{setup_test, v210_flow_def};
+
+#[test]
+fn crc32_matches_the_ieee_check_value() {
+    assert_eq!(payload_crc(b"123456789"), 0xcbf4_3926);
+}
+
+#[test]
+fn readers_verify_grains_against_recorded_checksums() {
+    let (mxl_instance, _domain_guard) = setup_test("crc");
+    let (flow_writer, flow_config_info, _) = mxl_instance
+        .create_flow_writer(v210_flow_def().as_str(), None)
+        .unwrap();
+    let rate = flow_config_info.common().grain_rate().unwrap();
+    let mut grain_writer = flow_writer.to_grain_writer().unwrap();
+    let grain_reader = mxl_instance
+        .create_flow_reader(flow_config_info.common().id())
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    let crc_flow_id = uuid::Uuid::new_v4();
+    let mut checksums = CrcPublisher::create(&mxl_instance, crc_flow_id, rate).unwrap();
+    let mut verifier = CrcVerifier::open(&mxl_instance, crc_flow_id).unwrap();
+
+    let index = mxl_instance.get_current_index(&rate);
+    let mut written = Vec::new();
+    grain_writer
+        .write_grain_with(index, |payload| {
+            payload.fill(0x5a);
+            written = payload.to_vec();
+        })
+        .unwrap();
+    checksums.record(index, &written).unwrap();
+    let grain = grain_reader
+        .get_complete_grain(index, Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(
+        verifier.verify(index, grain.payload).unwrap(),
+        CrcCheck::Match
+    );
+
+    // A grain damaged after the writer recorded it.
+    checksums.record(index + 1, &written).unwrap();
+    let mut corrupted = written.clone();
+    corrupted[0] ^= 1;
+    assert!(matches!(
+        verifier.verify(index + 1, &corrupted).unwrap(),
+        CrcCheck::Mismatch { .. }
+    ));
+    assert_eq!(
+        verifier.verify(index + 2, &written).unwrap(),
+        CrcCheck::Unknown
+    );
+}
@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::Cursor;
+
+use mxl::{
+    SampleFormat,
+    dump::{
+        DumpFormat, GrainDump, GrainReplay, WavReader, WavWriter, pack_v210, unpack_v210,
+        v210_line_length,
+    },
+};
+
+/// The v210 test flow with a picture small enough to compare by hand.
+fn flow_def(width: i32, height: i32) -> String {
+    let mut flow: serde_json::Value =
+        serde_json::from_str(include_str!("../../../lib/tests/data/v210_flow.json")).unwrap();
+    flow["frame_width"] = width.into();
+    flow["frame_height"] = height.into();
+    flow.to_string()
+}
+
+/// Planar samples numbered from 1, Y plane first.
+fn ramp(width: usize, height: usize) -> Vec<u16> {
+    let samples = (width + 2 * width.div_ceil(2)) * height;
+    (1..=samples as u16).collect()
+}
+
+#[test]
+fn v210_unpacks_a_block_in_component_order() {
+    // Cb0 Y0 Cr0 | Y1 Cb1 Y2 | Cr1 Y3 Cb2 | Y4 Cr2 Y5
+    let words: [u32; 4] = [
+        0x200 | (0x040 << 10) | (0x201 << 20),
+        0x041 | (0x202 << 10) | (0x042 << 20),
+        0x203 | (0x043 << 10) | (0x204 << 20),
+        0x044 | (0x205 << 10) | (0x045 << 20),
+    ];
+    let mut line = vec![0; v210_line_length(6)];
+    for (bytes, word) in line.chunks_exact_mut(4).zip(words) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    let planar = unpack_v210(&line, 6, 1).unwrap();
+    assert_eq!(
+        planar,
+        [
+            0x040, 0x041, 0x042, 0x043, 0x044, 0x045, 0x200, 0x202, 0x204, 0x201, 0x203, 0x205
+        ]
+    );
+    assert_eq!(pack_v210(&planar, 6, 1).unwrap(), line);
+}
+
+#[test]
+fn v210_round_trips_widths_not_multiple_of_six() {
+    let planar = ramp(50, 2);
+    let packed = pack_v210(&planar, 50, 2).unwrap();
+    assert_eq!(packed.len(), 2 * 256);
+    assert_eq!(unpack_v210(&packed, 50, 2).unwrap(), planar);
+}
+
+#[test]
+fn y4m_dump_replays_the_grains() {
+    let flow = flow_def(8, 2);
+    let grains: Vec<Vec<u8>> = (0..3)
+        .map(|i| {
+            let planar: Vec<u16> = ramp(8, 2).iter().map(|s| s + i).collect();
+            pack_v210(&planar, 8, 2).unwrap()
+        })
+        .collect();
+
+    let mut dump = GrainDump::new(Vec::new(), &flow, DumpFormat::Y4m).unwrap();
+    for grain in &grains {
+        dump.write_grain(grain).unwrap();
+    }
+    assert_eq!(dump.grains(), 3);
+    let file = dump.finish().unwrap();
+    assert!(file.starts_with(b"YUV4MPEG2 W8 H2 F30000:1001 Ip A1:1 C422p10"));
+
+    let mut replay = GrainReplay::new(file.as_slice(), &flow, DumpFormat::Y4m).unwrap();
+    for grain in &grains {
+        assert_eq!(replay.next_grain().unwrap().as_ref(), Some(grain));
+    }
+    assert_eq!(replay.next_grain().unwrap(), None);
+
+    assert!(GrainReplay::new(file.as_slice(), &flow_def(16, 2), DumpFormat::Y4m).is_err());
+}
+
+#[test]
+fn wav_round_trips_interleaved_samples() {
+    let samples: Vec<u8> = [1i16, -1, 2, -2, 3, -3]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let mut wav = WavWriter::new(Cursor::new(Vec::new()), 48000, 2, SampleFormat::S16).unwrap();
+    wav.write_interleaved(&samples).unwrap();
+    let file = wav.finish().unwrap().into_inner();
+    assert_eq!(file.len(), 44 + samples.len());
+    assert_eq!(&file[4..8], &(36 + samples.len() as u32).to_le_bytes());
+
+    let mut wav = WavReader::new(file.as_slice()).unwrap();
+    assert_eq!(
+        (wav.sample_rate(), wav.channels(), wav.format()),
+        (48000, 2, SampleFormat::S16)
+    );
+    // Only whole frames are returned.
+    let mut buffer = [0; 10];
+    assert_eq!(wav.read_interleaved(&mut buffer).unwrap(), 8);
+    assert_eq!(buffer[..8], samples[..8]);
+    assert_eq!(wav.read_interleaved(&mut buffer).unwrap(), 4);
+    assert_eq!(wav.read_interleaved(&mut buffer).unwrap(), 0);
+}
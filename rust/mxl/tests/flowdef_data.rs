@@ -1,7 +1,10 @@
 // SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
 // SPDX-License-Identifier: Apache-2.0
 
-use mxl::flowdef::{FlowDef, FlowDefData, FlowDefDetails, Rate};
+use mxl::{
+    SampleFormat,
+    flowdef::{FlowDef, FlowDefAudio, FlowDefData, FlowDefDetails, Rate},
+};
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -41,10 +44,8 @@ fn flow_def_data_details_roundtrip_serde() {
     assert_eq!(details, parsed);
 }
 
-/// Full [`FlowDef`] is not serde round-tripped here: `format` on the struct and
-/// `#[serde(tag = "format")]` on flattened [`FlowDefDetails`] duplicate the JSON
-/// key on serialize (same for video and audio). MXL code serializes to JSON for
-/// the C API; use this test only to lock data-flow JSON shape.
+/// Locks the data-flow JSON shape mxlsink hands to the C API. The `format`
+/// member is written once, as the tag of the flattened [`FlowDefDetails`].
 #[test]
 fn flow_def_data_serializes_like_mxlsink() {
     let flow = FlowDef {
@@ -74,4 +75,52 @@ fn flow_def_data_serializes_like_mxlsink() {
     );
     assert_eq!(v["media_type"].as_str(), Some("video/smpte291"));
     assert_eq!(v["grain_rate"]["numerator"], 50);
+    assert_eq!(json.matches("\"format\"").count(), 1);
+    let parsed: FlowDef = serde_json::from_str(&json).expect("deserialize FlowDef data");
+    assert_eq!(parsed, flow);
+}
+
+#[test]
+fn flow_def_parses_with_and_without_descriptive_members() {
+    let raw = include_str!("../../../lib/tests/data/data_flow.json");
+    let flow: FlowDef = serde_json::from_str(raw).expect("data_flow.json parses as FlowDef");
+    assert_eq!(flow.format, "urn:x-nmos:format:data");
+    assert!(matches!(flow.details, FlowDefDetails::Data(_)));
+
+    let minimal = serde_json::json!({
+        "id": Uuid::new_v4(),
+        "format": "urn:x-nmos:format:data",
+        "media_type": "video/smpte291",
+        "grain_rate": { "numerator": 50, "denominator": 1 },
+    });
+    let flow: FlowDef = serde_json::from_value(minimal).expect("minimal definition parses");
+    assert!(flow.label.is_empty() && flow.tags.is_empty() && flow.parents.is_empty());
+    assert!(matches!(flow.details, FlowDefDetails::Data(_)));
+}
+
+#[test]
+fn audio_sample_format_follows_media_type_and_bit_depth() {
+    let audio = |bit_depth| FlowDefAudio {
+        sample_rate: Rate {
+            numerator: 48000,
+            denominator: 1,
+        },
+        channel_count: 2,
+        bit_depth,
+    };
+    assert_eq!(
+        audio(32).sample_format("audio/float32").unwrap(),
+        SampleFormat::F32
+    );
+    assert_eq!(
+        audio(16).sample_format("audio/L16").unwrap(),
+        SampleFormat::S16
+    );
+    assert_eq!(
+        audio(24).sample_format("audio/l24").unwrap(),
+        SampleFormat::S24
+    );
+    assert_eq!(audio(24).bytes_per_sample(), 3);
+    assert!(audio(32).sample_format("audio/L24").is_err());
+    assert!(audio(8).sample_format("audio/L8").is_err());
 }
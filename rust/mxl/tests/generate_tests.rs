@@ -0,0 +1,79 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use mxl::{
+    dump::{unpack_v210, v210_line_length},
+    flowdef::{FlowDefAudio, FlowDefVideo},
+    generate::{
+        AudioGenerator, AudioPattern, VideoGenerator, VideoPattern, audio_flow_def, video_flow_def,
+    },
+    rates,
+};
+use uuid::Uuid;
+
+#[test]
+fn flow_defs_describe_the_generated_signals() {
+    let id = Uuid::new_v4();
+    let video: serde_json::Value =
+        serde_json::from_str(&video_flow_def(id, "bars", 1280, 720, rates::FPS_50)).unwrap();
+    assert_eq!(video["id"], id.to_string());
+    assert_eq!(video["media_type"], "video/v210");
+    let details: FlowDefVideo = serde_json::from_value(video).unwrap();
+    assert_eq!((details.frame_width, details.frame_height), (1280, 720));
+    assert_eq!(details.components[1].width, 640);
+
+    let audio: serde_json::Value =
+        serde_json::from_str(&audio_flow_def(id, "tone", 2, rates::HZ_48000)).unwrap();
+    assert_eq!(audio["media_type"], "audio/float32");
+    let details: FlowDefAudio = serde_json::from_value(audio).unwrap();
+    assert_eq!(details.channel_count, 2);
+}
+
+#[test]
+fn video_patterns_render_bars_and_digits() {
+    let (width, height) = (96, 14);
+    let mut payload = vec![0; v210_line_length(width) * height];
+
+    let bars = VideoGenerator::new(width, height, VideoPattern::ColorBars).unwrap();
+    bars.render(0, &mut payload).unwrap();
+    let planar = unpack_v210(&payload, width, height).unwrap();
+    // White on the left, black on the right.
+    assert_eq!(planar[0], 721);
+    assert_eq!(planar[width - 1], 64);
+
+    let counter = VideoGenerator::new(width, height, VideoPattern::Counter).unwrap();
+    counter.render(1, &mut payload).unwrap();
+    let planar = unpack_v210(&payload, width, height).unwrap();
+    let luma = &planar[..width * height];
+    assert_eq!(luma[0], 64);
+    assert!(luma.contains(&940));
+
+    assert!(counter.render(1, &mut payload[1..]).is_err());
+}
+
+#[test]
+fn tone_continues_across_fragments() {
+    let generator = AudioGenerator::new(
+        AudioPattern::Tone {
+            frequency: 12_000.0,
+            amplitude: 0.5,
+        },
+        48_000,
+    )
+    .unwrap();
+    let (mut first, mut second) = ([0u8; 8], [0u8; 8]);
+    generator.fill(48_000, (&mut first, &mut second));
+    let samples: Vec<f32> = first
+        .chunks_exact(4)
+        .chain(second.chunks_exact(4))
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    // A quarter of a period per sample.
+    let expected = [0.0, 0.5, 0.0, -0.5];
+    for (sample, expected) in samples.iter().zip(expected) {
+        assert!((sample - expected).abs() < 1e-6, "{samples:?}");
+    }
+
+    let silence = AudioGenerator::new(AudioPattern::Silence, 48_000).unwrap();
+    assert_eq!(silence.sample(123), 0.0);
+}
@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use mxl::{Dither, InterleaveOptions, OwnedSamplesData, SampleFormat};
+
+fn f32_bytes(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+fn planar(channels: &[&[f32]]) -> OwnedSamplesData {
+    OwnedSamplesData {
+        payload: channels.iter().map(|c| f32_bytes(c)).collect(),
+        format: SampleFormat::F32,
+    }
+}
+
+#[test]
+fn interleave_round_trips_f32() {
+    let samples = planar(&[&[0.1, 0.2, 0.3], &[-0.1, -0.2, -0.3]]);
+    let options = InterleaveOptions::new();
+    let interleaved = samples.interleave(&options).unwrap();
+    assert_eq!(interleaved, f32_bytes(&[0.1, -0.1, 0.2, -0.2, 0.3, -0.3]));
+
+    let planar = OwnedSamplesData::deinterleave(&interleaved, 2, &options).unwrap();
+    assert_eq!(planar.payload, samples.payload);
+}
+
+#[test]
+fn interleave_applies_per_channel_gain() {
+    let samples = planar(&[&[0.5, -0.5], &[0.5, -0.5], &[0.5, -0.5]]);
+    let options = InterleaveOptions::new().gains([2.0, 0.5]);
+    let interleaved = samples.interleave(&options).unwrap();
+    // The third channel has no gain and stays at unity.
+    assert_eq!(interleaved, f32_bytes(&[1.0, 0.25, 0.5, -1.0, -0.25, -0.5]));
+}
+
+#[test]
+fn interleave_quantizes_and_clips_to_integer_formats() {
+    let samples = planar(&[&[0.5, -1.0, 2.0]]);
+
+    let s16 = samples
+        .interleave(&InterleaveOptions::new().format(SampleFormat::S16))
+        .unwrap();
+    assert_eq!(
+        s16,
+        [16384i16, -32768, 32767]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect::<Vec<_>>()
+    );
+
+    let s24 = samples
+        .interleave(&InterleaveOptions::new().format(SampleFormat::S24))
+        .unwrap();
+    assert_eq!(s24, [0x00, 0x00, 0x40, 0x00, 0x00, 0x80, 0xff, 0xff, 0x7f]);
+
+    let planar = OwnedSamplesData::deinterleave(
+        &s24,
+        1,
+        &InterleaveOptions::new().format(SampleFormat::S24),
+    )
+    .unwrap();
+    assert_eq!(planar.payload[0][..8], f32_bytes(&[0.5, -1.0]));
+}
+
+#[test]
+fn interleave_sign_extends_24_bit_samples_in_32_bit_words() {
+    let samples = planar(&[&[0.5, -1.0]]);
+    let options = InterleaveOptions::new().format(SampleFormat::S24In32);
+    let s24_32 = samples.interleave(&options).unwrap();
+    assert_eq!(
+        s24_32,
+        [0x40_0000i32, -0x80_0000]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect::<Vec<_>>()
+    );
+
+    // An `audio/L24` flow keeps the 3 significant bytes.
+    let planar =
+        OwnedSamplesData::deinterleave_as(&s24_32, 1, &options, SampleFormat::S24).unwrap();
+    assert_eq!(planar.payload[0], [0x00, 0x00, 0x40, 0x00, 0x00, 0x80]);
+}
+
+#[test]
+fn tpdf_dither_stays_within_one_lsb() {
+    let samples = planar(&[&[0.25; 1000]]);
+    let options = InterleaveOptions::new()
+        .format(SampleFormat::S16)
+        .dither(Dither::Tpdf);
+    let interleaved = samples.interleave(&options).unwrap();
+    let values: Vec<i16> = interleaved
+        .chunks_exact(2)
+        .map(|s| i16::from_le_bytes([s[0], s[1]]))
+        .collect();
+    assert!(values.iter().all(|v| (8191..=8193).contains(v)));
+    assert!(values.iter().any(|v| *v != 8192));
+}
+
+#[test]
+fn deinterleave_rejects_partial_frames() {
+    let options = InterleaveOptions::new().format(SampleFormat::S16);
+    assert!(OwnedSamplesData::deinterleave(&[0; 6], 2, &options).is_err());
+}
+
+#[test]
+fn l24_flow_samples_convert_to_f32_and_back() {
+    // Two channels of two `audio/L24` samples, 0.5 and -1.0 full scale.
+    let samples = OwnedSamplesData {
+        payload: vec![vec![0x00, 0x00, 0x40, 0x00, 0x00, 0x80]; 2],
+        format: SampleFormat::S24,
+    };
+    assert_eq!(samples.num_of_samples(), 2);
+    let interleaved = samples.interleave(&InterleaveOptions::new()).unwrap();
+    assert_eq!(interleaved, f32_bytes(&[0.5, 0.5, -1.0, -1.0]));
+
+    let planar = OwnedSamplesData::deinterleave_as(
+        &interleaved,
+        2,
+        &InterleaveOptions::new(),
+        SampleFormat::S24,
+    )
+    .unwrap();
+    assert_eq!(planar.payload, samples.payload);
+}
@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "nmos")]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use mxl::nmos::{NmosConfig, NmosNode};
+
+/// Requests received by the mock registry: method, path and JSON body.
+type Requests = Arc<Mutex<Vec<(String, String, serde_json::Value)>>>;
+
+/// Answers every request with `201 Created` and records it.
+fn mock_registry() -> (String, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Requests::default();
+    let recorded = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap().to_string();
+            let path = parts.next().unwrap().to_string();
+            let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+            recorded.lock().unwrap().push((method, path, body));
+            stream
+                .write_all(
+                    b"HTTP/1.1 201 Created\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+                .unwrap();
+        }
+    });
+    (url, requests)
+}
+
+fn registered_types(requests: &Requests) -> Vec<String> {
+    requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(method, path, _)| method == "POST" && path.ends_with("/resource"))
+        .map(|(_, _, body)| body["type"].as_str().unwrap().to_string())
+        .collect()
+}
+
+/// Waits for the heartbeat thread to send `count` registrations.
+fn wait_for_registrations(requests: &Requests, count: usize) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let types = registered_types(requests);
+        if types.len() >= count || Instant::now() > deadline {
+            return types;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn flows_are_registered_and_withdrawn() {
+    let (url, requests) = mock_registry();
+    let node = NmosNode::register(NmosConfig::new(url, "test node")).unwrap();
+    assert_eq!(registered_types(&requests), ["node", "device"]);
+
+    let flow_def = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../lib/tests/data/v210_flow.json"),
+    )
+    .unwrap();
+    node.register_flow(&flow_def).unwrap();
+    node.register_flow(&flow_def).unwrap();
+    assert_eq!(
+        wait_for_registrations(&requests, 6),
+        ["node", "device", "source", "flow", "sender", "device"]
+    );
+    {
+        let requests = requests.lock().unwrap();
+        let flow = &requests[3].2["data"];
+        assert_eq!(flow["id"], "5fbec3b1-1b0f-417d-9059-8b94a47197ed");
+        assert_eq!(flow["device_id"], node.device_id().to_string());
+        assert_eq!(flow["source_id"], requests[2].2["data"]["id"]);
+        assert!(flow.get("$copyright").is_none());
+        let device = &requests[5].2["data"];
+        assert_eq!(device["senders"][0], requests[4].2["data"]["id"]);
+    }
+
+    let flow_id = uuid::Uuid::parse_str("5fbec3b1-1b0f-417d-9059-8b94a47197ed").unwrap();
+    assert!(node.add_writer(&flow_id));
+    assert!(!node.add_writer(&uuid::Uuid::new_v4()));
+    // The other writers keep the flow registered.
+    node.withdraw_flow(&flow_id);
+    node.withdraw_flow(&flow_id);
+    assert_eq!(node.flows(), [flow_id]);
+    node.withdraw_flow(&flow_id);
+    assert!(node.flows().is_empty());
+
+    // Dropping the node runs the queued withdrawal first.
+    drop(node);
+    let deleted: Vec<String> = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(method, _, _)| method == "DELETE")
+        .map(|(_, path, _)| path.split('/').nth_back(1).unwrap().to_string())
+        .collect();
+    assert_eq!(deleted, ["senders", "flows", "sources", "devices", "nodes"]);
+}
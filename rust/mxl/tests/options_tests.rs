@@ -0,0 +1,144 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mxl::{AccessOptions, FlowOptions, InstanceOptions};
+
+#[test]
+fn flow_options_serialize_to_library_keys() {
+    let options = FlowOptions::new()
+        .max_commit_batch_size_hint(48)
+        .max_sync_batch_size_hint(96)
+        .history_duration(Duration::from_millis(500));
+    let json: serde_json::Value = serde_json::from_str(&options.to_json_string().unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "maxCommitBatchSizeHint": 48,
+            "maxSyncBatchSizeHint": 96,
+            "historyDuration": 500_000_000,
+        })
+    );
+}
+
+#[test]
+fn empty_flow_options_serialize_to_empty_object() {
+    assert_eq!(FlowOptions::new().to_json_string().unwrap(), "{}");
+}
+
+#[test]
+fn flow_options_reject_what_the_library_rejects() {
+    assert!(
+        FlowOptions::new()
+            .max_commit_batch_size_hint(0)
+            .validate()
+            .is_err()
+    );
+    assert!(
+        FlowOptions::new()
+            .max_commit_batch_size_hint(48)
+            .max_sync_batch_size_hint(50)
+            .validate()
+            .is_err()
+    );
+    assert!(
+        FlowOptions::new()
+            .history_duration(Duration::ZERO)
+            .validate()
+            .is_err()
+    );
+    assert!(
+        FlowOptions::new()
+            .max_sync_batch_size_hint(50)
+            .validate()
+            .is_ok()
+    );
+}
+
+#[cfg(feature = "cuda")]
+#[test]
+fn flow_options_request_device_payloads() {
+    let options = FlowOptions::new().device_index(1);
+    let json: serde_json::Value = serde_json::from_str(&options.to_json_string().unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "payloadLocation": 1, "deviceIndex": 1 })
+    );
+    assert_eq!(
+        options.requested_payload_location(),
+        Some(mxl::PayloadLocation::DeviceMemory { device_index: 1 })
+    );
+    assert!(FlowOptions::new().device_index(-1).validate().is_err());
+}
+
+#[test]
+fn instance_options_pass_raw_entries_through() {
+    assert_eq!(InstanceOptions::new().to_json_string().unwrap(), "");
+    let options = InstanceOptions::new().option("urn:x-example:option/v1.0", 5);
+    assert_eq!(
+        options.to_json_string().unwrap(),
+        r#"{"urn:x-example:option/v1.0":5}"#
+    );
+}
+
+#[test]
+fn instance_epoch_offset_is_not_passed_to_the_library() {
+    let options = InstanceOptions::new().epoch_offset(-500);
+    assert_eq!(options.get_epoch_offset(), -500);
+    assert_eq!(options.to_json_string().unwrap(), "");
+}
+
+#[test]
+fn access_options_parse_property_strings() {
+    assert!(AccessOptions::parse("").unwrap().is_empty());
+    assert_eq!(AccessOptions::new().to_json_string().unwrap(), "");
+
+    let options =
+        AccessOptions::parse("priority=3, partialReadPolicy=latest,realtime=true").unwrap();
+    assert_eq!(options.get("priority"), Some(&serde_json::json!(3)));
+    assert_eq!(
+        options.get("partialReadPolicy"),
+        Some(&serde_json::json!("latest"))
+    );
+    assert_eq!(
+        options,
+        AccessOptions::new()
+            .option("priority", 3)
+            .option("partialReadPolicy", "latest")
+            .option("realtime", true)
+    );
+    assert_eq!(
+        options.to_json_string().unwrap(),
+        r#"{"partialReadPolicy":"latest","priority":3,"realtime":true}"#
+    );
+
+    assert!(AccessOptions::parse("priority").is_err());
+    assert!(AccessOptions::parse("=3").is_err());
+}
+
+#[test]
+fn history_duration_sizes_the_projected_flow() {
+    let domain = mxl::MxlDomain::new("/nonexistent/mxl-domain");
+    let v210 = mxl_test_utils::v210_flow_def();
+    // 5 grains of 1080p v210 at 29.97 Hz in the default 200 ms, plus a page for the flow state.
+    assert_eq!(
+        domain.projected_flow_size(&v210, None).unwrap(),
+        5 * (8192 + 5120 * 1080) + 4096
+    );
+    let options = FlowOptions::new()
+        .history_duration(Duration::from_secs(1))
+        .to_json_string()
+        .unwrap();
+    assert_eq!(
+        domain.projected_flow_size(&v210, Some(&options)).unwrap(),
+        29 * (8192 + 5120 * 1080) + 4096
+    );
+    // Two channels of 19200 32-bit samples, twice the history, rounded up to pages.
+    assert_eq!(
+        domain
+            .projected_flow_size(&mxl_test_utils::audio_flow_def(), None)
+            .unwrap(),
+        2 * 19456 * 4 + 4096
+    );
+}
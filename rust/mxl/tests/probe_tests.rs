@@ -0,0 +1,51 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mxl::{LatencyProbe, PROBE_STAMP_SIZE, read_stamp, stamp_payload};
+use uuid::Uuid;
+
+#[test]
+fn payload_stamp_round_trips() {
+    let mut payload = vec![0xaa; 64];
+    stamp_payload(&mut payload, 1_234_567_890).unwrap();
+    assert_eq!(read_stamp(&payload), Some(1_234_567_890));
+    assert_eq!(payload[PROBE_STAMP_SIZE..], [0xaa; 64 - PROBE_STAMP_SIZE]);
+
+    assert_eq!(read_stamp(&[0xaa; 64]), None);
+    assert!(stamp_payload(&mut [0; PROBE_STAMP_SIZE - 1], 0).is_err());
+}
+
+#[test]
+fn probe_records_stamped_payloads() {
+    let mut probe = LatencyProbe::new(Uuid::new_v4());
+    let mut payload = vec![0; 32];
+    stamp_payload(&mut payload, 1_000).unwrap();
+    assert!(probe.record_stamped(&payload, 1_500));
+    assert!(!probe.record_stamped(&[0; 32], 1_500));
+    assert_eq!(probe.len(), 1);
+    assert_eq!(probe.percentile(50.0), Some(500));
+}
+
+#[test]
+fn probe_percentiles_over_sliding_window() {
+    let mut probe = LatencyProbe::with_window(Uuid::new_v4(), 100);
+    for latency in 1..=200 {
+        probe.record(Duration::from_nanos(latency));
+    }
+    assert_eq!(probe.len(), 100);
+    assert_eq!(probe.percentile(0.0), Some(101));
+    assert_eq!(probe.percentile(50.0), Some(151));
+    assert_eq!(probe.percentile(99.9), Some(200));
+    assert_eq!(probe.percentile(100.5), None);
+
+    let report = probe.summary("test", 42).unwrap();
+    assert_eq!(report.flow_id, probe.flow_id());
+    assert_eq!((report.min, report.max, report.p99), (101, 200, 200));
+
+    probe.clear();
+    assert!(probe.is_empty());
+    assert_eq!(probe.percentile(50.0), None);
+    assert!(probe.summary("test", 42).is_none());
+}
@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cmp::Ordering, time::Duration};
+
+use mxl::{Rational, RationalExt, flowdef, rates};
+
+fn rational(numerator: i64, denominator: i64) -> Rational {
+    Rational {
+        numerator,
+        denominator,
+    }
+}
+
+#[test]
+fn reduce_and_compare() {
+    let rate = rational(50, 2).reduced();
+    assert_eq!((rate.numerator, rate.denominator), (25, 1));
+    assert!(rational(50, 2).rate_eq(&rates::FPS_25));
+    assert_eq!(rates::FPS_29_97.rate_cmp(&rates::FPS_30), Ordering::Less);
+    assert_eq!(
+        rational(-1, -2).rate_cmp(&rational(1, 3)),
+        Ordering::Greater
+    );
+    assert!(!rational(25, 0).is_valid());
+    assert!((rates::FPS_59_94.as_f64() - 59.94).abs() < 0.001);
+}
+
+#[test]
+fn durations_and_counts() {
+    assert_eq!(rates::FPS_25.period(), Some(Duration::from_millis(40)));
+    assert_eq!(
+        rates::FPS_29_97.duration_of(30),
+        Some(Duration::from_nanos(1_001_000_000))
+    );
+    assert_eq!(rates::HZ_48000.count_in(Duration::from_millis(10)), 480);
+    assert_eq!(rational(0, 1).period(), None);
+    assert_eq!(rational(0, 1).count_in(Duration::from_secs(1)), 0);
+}
+
+#[test]
+fn from_flow_def_rate() {
+    let rate = flowdef::Rate {
+        numerator: 60000,
+        denominator: 1001,
+    };
+    assert!(Rational::from(&rate).rate_eq(&rates::FPS_59_94));
+}
+
+#[cfg(feature = "gstreamer")]
+#[test]
+fn gstreamer_fraction_round_trip() {
+    let fraction = rates::FPS_29_97.to_fraction().unwrap();
+    assert_eq!((fraction.numer(), fraction.denom()), (30000, 1001));
+    assert!(Rational::from_fraction(fraction).rate_eq(&rates::FPS_29_97));
+    assert!(rational(i64::MAX, 1).to_fraction().is_none());
+}
@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2025-2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mxl::{LatencyReport, REPORT_SCHEMA_VERSION, ThroughputReport};
+use uuid::Uuid;
+
+#[test]
+fn latency_report_statistics() {
+    let flow_id = Uuid::new_v4();
+    let samples: Vec<u64> = (1..=100).rev().collect();
+    let report = LatencyReport::from_samples(flow_id, "test", 42, &samples).unwrap();
+    assert_eq!(report.schema_version, REPORT_SCHEMA_VERSION);
+    assert_eq!(report.count, 100);
+    assert_eq!(report.min, 1);
+    assert_eq!(report.max, 100);
+    assert_eq!(report.mean, 50);
+    assert_eq!(report.p50, 51);
+    assert_eq!(report.p99, 100);
+
+    assert!(LatencyReport::from_samples(flow_id, "test", 42, &[]).is_none());
+}
+
+#[test]
+fn throughput_report_rates() {
+    let report = ThroughputReport::new(
+        Uuid::new_v4(),
+        "test",
+        42,
+        Duration::from_millis(500),
+        25,
+        1,
+        1000,
+    );
+    assert_eq!(report.window, 500_000_000);
+    assert_eq!(report.units_per_second, 50.0);
+    assert_eq!(report.bytes_per_second, 2000.0);
+
+    let empty = ThroughputReport::new(Uuid::new_v4(), "test", 42, Duration::ZERO, 0, 0, 0);
+    assert_eq!(empty.units_per_second, 0.0);
+}
+
+#[test]
+fn reports_round_trip_through_json() {
+    let flow_id = Uuid::new_v4();
+    let latency = LatencyReport::from_samples(flow_id, "mxlsrc0", 7, &[10, 20, 30]).unwrap();
+    let json = serde_json::to_value(&latency).unwrap();
+    assert_eq!(json["flow_id"], flow_id.to_string());
+    assert_eq!(json["source"], "mxlsrc0");
+    assert_eq!(
+        serde_json::from_value::<LatencyReport>(json).unwrap(),
+        latency
+    );
+
+    let throughput =
+        ThroughputReport::new(flow_id, "mxlsink0", 7, Duration::from_secs(1), 30, 0, 300);
+    let json = serde_json::to_string(&throughput).unwrap();
+    assert_eq!(
+        serde_json::from_str::<ThroughputReport>(&json).unwrap(),
+        throughput
+    );
+}
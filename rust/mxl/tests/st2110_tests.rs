@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mxl::{
+    flowdef::{FlowDefAudio, FlowDefVideo, InterlaceMode, Rate},
+    st2110::{
+        AudioSdpParams, EXTENDED_UDP_SIZE_LIMIT, STANDARD_UDP_SIZE_LIMIT, SenderType,
+        VideoSdpParams, audio_packing, video_pacing, video_packing,
+    },
+};
+
+fn video_1080p2997() -> FlowDefVideo {
+    FlowDefVideo {
+        grain_rate: Rate {
+            numerator: 30000,
+            denominator: 1001,
+        },
+        frame_width: 1920,
+        frame_height: 1080,
+        interlace_mode: InterlaceMode::Progressive,
+        colorspace: "BT709".into(),
+        components: vec![],
+    }
+}
+
+fn audio_48k(channel_count: i32) -> FlowDefAudio {
+    FlowDefAudio {
+        sample_rate: Rate {
+            numerator: 48000,
+            denominator: 1,
+        },
+        channel_count,
+        bit_depth: 32,
+    }
+}
+
+#[test]
+fn v210_packing() {
+    let flow = video_1080p2997();
+    let packing = video_packing(&flow, STANDARD_UDP_SIZE_LIMIT).unwrap();
+    assert_eq!((packing.pgroup_octets, packing.pgroup_pixels), (5, 2));
+    assert_eq!(packing.octets_per_line, 4800);
+    assert_eq!(packing.octets_per_packet, 1440);
+    assert_eq!(packing.packets_per_line, 4);
+    assert_eq!(packing.packets_per_frame, 4320);
+
+    let packing = video_packing(&flow, EXTENDED_UDP_SIZE_LIMIT).unwrap();
+    assert_eq!(packing.octets_per_packet, 4800);
+    assert_eq!(packing.packets_per_frame, 1080);
+
+    assert!(video_packing(&flow, 20).is_err());
+}
+
+#[test]
+fn narrow_and_wide_pacing() {
+    let flow = video_1080p2997();
+    let packing = video_packing(&flow, STANDARD_UDP_SIZE_LIMIT).unwrap();
+
+    let narrow = video_pacing(&flow, &packing, SenderType::Narrow).unwrap();
+    assert_eq!(narrow.frame_period, Duration::from_nanos(33_366_666));
+    assert_eq!(narrow.packet_spacing, Duration::from_nanos(7_414));
+    assert_eq!(narrow.read_offset, Duration::from_nanos(1_275_348));
+    assert_eq!((narrow.cmax, narrow.vrx_full), (4, 8));
+
+    let linear = video_pacing(&flow, &packing, SenderType::NarrowLinear).unwrap();
+    assert_eq!(linear.packet_spacing, Duration::from_nanos(7_723));
+
+    let wide = video_pacing(&flow, &packing, SenderType::Wide).unwrap();
+    assert_eq!(wide.packet_spacing, narrow.packet_spacing);
+    assert_eq!((wide.cmax, wide.vrx_full), (16, 720));
+}
+
+#[test]
+fn video_sdp_params() {
+    let mut flow = video_1080p2997();
+    flow.interlace_mode = InterlaceMode::InterlacedTff;
+    let params = VideoSdpParams::new(&flow, SenderType::Narrow).unwrap();
+    assert_eq!(
+        params.fmtp(),
+        "sampling=YCbCr-4:2:2; width=1920; height=1080; exactframerate=30000/1001; depth=10; \
+         TCS=SDR; colorimetry=BT709; PM=2110GPM; SSN=ST2110-20:2017; TP=2110TPN; interlace;"
+    );
+
+    let json = serde_json::to_value(&params).unwrap();
+    assert_eq!(json["exactFrameRate"], "30000/1001");
+    assert_eq!(json["senderType"], "2110TPN");
+    let parsed: VideoSdpParams = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, params);
+}
+
+#[test]
+fn audio_packing_and_sdp_params() {
+    let flow = audio_48k(2);
+    let packing =
+        audio_packing(&flow, 24, Duration::from_millis(1), STANDARD_UDP_SIZE_LIMIT).unwrap();
+    assert_eq!(packing.samples_per_packet, 48);
+    assert_eq!(packing.octets_per_packet, 288);
+    assert_eq!(packing.packets_per_second, 1000);
+
+    let packing = audio_packing(
+        &flow,
+        16,
+        Duration::from_micros(125),
+        STANDARD_UDP_SIZE_LIMIT,
+    )
+    .unwrap();
+    assert_eq!(packing.samples_per_packet, 6);
+    assert_eq!(packing.octets_per_packet, 24);
+    assert_eq!(packing.packets_per_second, 8000);
+
+    // 64 channels of L24 do not fit a standard 1 ms packet, and 20 bits is not an ST 2110-30
+    // encoding.
+    let wide = audio_48k(64);
+    assert!(audio_packing(&wide, 24, Duration::from_millis(1), STANDARD_UDP_SIZE_LIMIT).is_err());
+    assert!(audio_packing(&flow, 20, Duration::from_millis(1), STANDARD_UDP_SIZE_LIMIT).is_err());
+
+    let params = AudioSdpParams::new(&flow, 24, Duration::from_micros(125)).unwrap();
+    assert_eq!(params.rtpmap(), "L24/48000/2");
+    assert_eq!(params.ptime(), "0.125");
+    let params = AudioSdpParams::new(&flow, 24, Duration::from_millis(1)).unwrap();
+    assert_eq!(params.ptime(), "1");
+}
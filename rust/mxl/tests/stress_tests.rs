@@ -0,0 +1,394 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Concurrency stress tests: one writer and many readers in threads or processes, for minutes.
+///
+/// The writer runs far faster than real time so the rings wrap constantly and readers fall behind
+/// and have to catch up with the head. Every grain and sample carries its index, so readers detect
+/// corrupted or misplaced data. A test that does not finish well after its duration is reported
+/// as a deadlock.
+///
+/// The tests are ignored by default; run them with
+/// `MXL_STRESS_SECONDS=300 cargo test -p mxl --test stress_tests -- --ignored --test-threads=1`.
+use std::{
+    process::Command,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use mxl::{Error, GrainReader, MxlInstance, SamplesReader, config::get_mxl_so_path};
+use mxl_test_utils::{TestDomainGuard, audio_flow_def, resize_video_flow_def, v210_flow_def};
+
+const READERS: usize = 8;
+const CHILD_PROCESSES: usize = 4;
+const GRAIN_INTERVAL: Duration = Duration::from_millis(1);
+const SAMPLE_BATCH: usize = 64;
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+/// Time without writes after which readers give up on the writer.
+const STALE_THRESHOLD: Duration = Duration::from_secs(1);
+/// Time a test may take beyond its duration before it is reported as deadlocked.
+const DEADLOCK_MARGIN: Duration = Duration::from_secs(30);
+/// Set in the environment of child reader processes to `<domain> <flow id>`.
+const CHILD_ENV: &str = "MXL_STRESS_CHILD";
+
+fn stress_duration() -> Duration {
+    let seconds = std::env::var("MXL_STRESS_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .unwrap_or(120);
+    Duration::from_secs(seconds)
+}
+
+fn instance(domain: &str) -> MxlInstance {
+    mxl_test_utils::setup_logging();
+    mxl::config::check_sanitizer_env().unwrap();
+    let mxl_api = mxl::load_api(get_mxl_so_path()).unwrap();
+    MxlInstance::new(mxl_api, domain, "").unwrap()
+}
+
+/// The v210 test flow shrunk to a picture that is quick to check.
+fn video_flow_def() -> String {
+    resize_video_flow_def(&v210_flow_def(), 192, 108)
+}
+
+/// Stamps the grain with its index: the index in the first 8 bytes, its low byte in the rest.
+fn stamp_grain(index: u64, payload: &mut [u8]) {
+    let (head, rest) = payload.split_at_mut(8);
+    head.copy_from_slice(&index.to_le_bytes());
+    rest.fill(index as u8);
+}
+
+fn grain_is_stamped(index: u64, payload: &[u8]) -> bool {
+    let (head, rest) = payload.split_at(8);
+    head == index.to_le_bytes() && rest.iter().all(|byte| *byte == index as u8)
+}
+
+/// Value of the sample at `index` in `channel`, exact in an `f32`.
+fn sample_value(index: u64, channel: usize) -> f32 {
+    ((index % (1 << 20)) as f32) + channel as f32 / 8.0
+}
+
+/// Runs `test` on a thread and fails if it does not finish within the stress duration and margin.
+fn with_deadline(name: &str, test: impl FnOnce() + Send + 'static) {
+    let (done_tx, done_rx) = crossbeam_channel::bounded(1);
+    let handle = thread::spawn(move || {
+        test();
+        let _ = done_tx.send(());
+    });
+    match done_rx.recv_timeout(stress_duration() + DEADLOCK_MARGIN) {
+        Ok(()) | Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
+            if let Err(panic) = handle.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+        Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
+            panic!("{name} did not finish, a thread is deadlocked.")
+        }
+    }
+}
+
+/// State shared between the writer and the readers of a test.
+#[derive(Default)]
+struct Progress {
+    /// Index of the last grain or sample written, once the writer is done.
+    last: AtomicU64,
+    done: AtomicBool,
+}
+
+impl Progress {
+    fn finished_before(&self, index: u64) -> bool {
+        self.done.load(Ordering::Acquire) && index > self.last.load(Ordering::Acquire)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReadStats {
+    verified: u64,
+    catch_ups: u64,
+    /// Grains that changed while being checked because the writer lapped the reader.
+    overwritten: u64,
+}
+
+/// Reads grains from `start` on until the writer is done or stopped writing, catching up with the
+/// head whenever the reader falls out of the ring.
+fn read_grains(reader: &GrainReader, start: u64, progress: &Progress) -> ReadStats {
+    let ring_length = u64::from(reader.get_config_info().unwrap().ring_length());
+    let mut stats = ReadStats::default();
+    let mut index = start;
+    while !progress.finished_before(index) {
+        match reader.get_complete_grain(index, READ_TIMEOUT) {
+            Ok(grain) => {
+                if grain_is_stamped(index, grain.payload) {
+                    stats.verified += 1;
+                } else {
+                    let head = reader.get_runtime_info().unwrap().headIndex;
+                    assert!(
+                        head + 1 >= index + ring_length,
+                        "Grain {index} is corrupted while the head is at {head}."
+                    );
+                    stats.overwritten += 1;
+                }
+                index += 1;
+            }
+            Err(Error::OutOfRangeTooLate) => {
+                index = reader.get_runtime_info().unwrap().headIndex;
+                stats.catch_ups += 1;
+            }
+            Err(Error::Timeout | Error::OutOfRangeTooEarly) => {
+                if reader.is_flow_stale(STALE_THRESHOLD).unwrap() {
+                    break;
+                }
+            }
+            Err(error) => panic!("Reading grain {index} failed: {error}"),
+        }
+    }
+    stats
+}
+
+fn read_samples(reader: &SamplesReader, start: u64, progress: &Progress) -> ReadStats {
+    let ring_length = u64::from(reader.get_config_info().unwrap().ring_length());
+    let mut stats = ReadStats::default();
+    let mut index = start;
+    while !progress.finished_before(index) {
+        match reader.get_samples(index, SAMPLE_BATCH, READ_TIMEOUT) {
+            Ok(samples) => {
+                let intact = (0..samples.num_of_channels()).all(|channel| {
+                    let (first, second) = samples.channel_data(channel).unwrap();
+                    let values = first.chunks_exact(4).chain(second.chunks_exact(4));
+                    (index + 1 - SAMPLE_BATCH as u64..)
+                        .zip(values)
+                        .all(|(sample, bytes)| {
+                            f32::from_le_bytes(bytes.try_into().unwrap())
+                                == sample_value(sample, channel)
+                        })
+                });
+                if intact {
+                    stats.verified += 1;
+                } else {
+                    let head = reader.get_runtime_info().unwrap().headIndex;
+                    // Only half of the buffer is readable, see `FlowConfigInfo::history_duration`.
+                    assert!(
+                        head + SAMPLE_BATCH as u64 >= index + ring_length / 2,
+                        "Samples up to {index} are corrupted while the head is at {head}."
+                    );
+                    stats.overwritten += 1;
+                }
+                index += SAMPLE_BATCH as u64;
+            }
+            Err(Error::OutOfRangeTooLate) => {
+                index = reader.get_runtime_info().unwrap().headIndex;
+                stats.catch_ups += 1;
+            }
+            Err(Error::Timeout | Error::OutOfRangeTooEarly) => {
+                if reader.is_flow_stale(STALE_THRESHOLD).unwrap() {
+                    break;
+                }
+            }
+            Err(error) => panic!("Reading samples up to {index} failed: {error}"),
+        }
+    }
+    stats
+}
+
+/// Writes stamped grains every [`GRAIN_INTERVAL`] for the stress duration. Returns the flow id and
+/// the writer's thread.
+fn spawn_grain_writer(
+    instance: &MxlInstance,
+    progress: Arc<Progress>,
+) -> (String, u64, thread::JoinHandle<()>) {
+    let (writer, config, _) = instance
+        .create_flow_writer(&video_flow_def(), None)
+        .unwrap();
+    let flow_id = config.common().id().to_string();
+    let mut writer = writer.to_grain_writer().unwrap();
+    let start = instance.get_current_index(&config.common().grain_rate().unwrap());
+    let handle = thread::spawn(move || {
+        let deadline = Instant::now() + stress_duration();
+        let mut index = start;
+        while Instant::now() < deadline {
+            let mut access = writer.open_grain(index).unwrap();
+            let total_slices = access.total_slices();
+            stamp_grain(index, access.payload_mut());
+            access.commit(total_slices).unwrap();
+            index += 1;
+            thread::sleep(GRAIN_INTERVAL);
+        }
+        progress.last.store(index - 1, Ordering::Release);
+        progress.done.store(true, Ordering::Release);
+    });
+    (flow_id, start, handle)
+}
+
+#[test]
+#[ignore]
+fn grains_one_writer_many_reader_threads() {
+    with_deadline("grains_one_writer_many_reader_threads", || {
+        let domain_guard = TestDomainGuard::new("grain_threads");
+        let instance = instance(&domain_guard.domain());
+        let progress = Arc::new(Progress::default());
+        let (flow_id, start, writer) = spawn_grain_writer(&instance, progress.clone());
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| {
+                let reader = instance
+                    .create_flow_reader(&flow_id)
+                    .unwrap()
+                    .to_grain_reader()
+                    .unwrap();
+                let progress = progress.clone();
+                thread::spawn(move || read_grains(&reader, start, &progress))
+            })
+            .collect();
+        writer.join().unwrap();
+        for reader in readers {
+            let stats = reader.join().unwrap();
+            assert!(stats.verified > 0, "{stats:?}");
+        }
+    });
+}
+
+#[test]
+#[ignore]
+fn samples_one_writer_many_reader_threads() {
+    with_deadline("samples_one_writer_many_reader_threads", || {
+        let domain_guard = TestDomainGuard::new("samples_threads");
+        let instance = instance(&domain_guard.domain());
+        let (writer, config, _) = instance
+            .create_flow_writer(&audio_flow_def(), None)
+            .unwrap();
+        let flow_id = config.common().id().to_string();
+        let mut writer = writer.to_samples_writer().unwrap();
+        let start = instance.get_current_index(&config.common().sample_rate().unwrap());
+        let progress = Arc::new(Progress::default());
+
+        let writer = {
+            let progress = progress.clone();
+            thread::spawn(move || {
+                let deadline = Instant::now() + stress_duration();
+                let mut index = start;
+                while Instant::now() < deadline {
+                    index += SAMPLE_BATCH as u64;
+                    let mut access = writer.open_samples(index, SAMPLE_BATCH).unwrap();
+                    for channel in 0..access.channels() {
+                        let (first, second) = access.channel_data_mut(channel).unwrap();
+                        let outputs = first.chunks_exact_mut(4).chain(second.chunks_exact_mut(4));
+                        for (sample, output) in (index + 1 - SAMPLE_BATCH as u64..).zip(outputs) {
+                            output.copy_from_slice(&sample_value(sample, channel).to_le_bytes());
+                        }
+                    }
+                    access.commit().unwrap();
+                    thread::sleep(GRAIN_INTERVAL);
+                }
+                progress.last.store(index, Ordering::Release);
+                progress.done.store(true, Ordering::Release);
+            })
+        };
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| {
+                let reader = instance
+                    .create_flow_reader(&flow_id)
+                    .unwrap()
+                    .to_samples_reader()
+                    .unwrap();
+                let progress = progress.clone();
+                thread::spawn(move || read_samples(&reader, start + SAMPLE_BATCH as u64, &progress))
+            })
+            .collect();
+        writer.join().unwrap();
+        for reader in readers {
+            let stats = reader.join().unwrap();
+            assert!(stats.verified > 0, "{stats:?}");
+        }
+    });
+}
+
+#[test]
+#[ignore]
+fn grains_reader_churn() {
+    with_deadline("grains_reader_churn", || {
+        let domain_guard = TestDomainGuard::new("grain_churn");
+        let instance = instance(&domain_guard.domain());
+        let progress = Arc::new(Progress::default());
+        let (flow_id, _, writer) = spawn_grain_writer(&instance, progress.clone());
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| {
+                let instance = instance.clone();
+                let flow_id = flow_id.clone();
+                let progress = progress.clone();
+                thread::spawn(move || {
+                    let mut generations = 0u64;
+                    while !progress.done.load(Ordering::Acquire) {
+                        // A short-lived reader that checks a few grains from the head.
+                        let reader = instance
+                            .create_flow_reader(&flow_id)
+                            .unwrap()
+                            .to_grain_reader()
+                            .unwrap();
+                        let head = reader.get_runtime_info().unwrap().headIndex;
+                        let few = Progress {
+                            last: AtomicU64::new(head + 4),
+                            done: AtomicBool::new(true),
+                        };
+                        read_grains(&reader, head, &few);
+                        reader.destroy().unwrap();
+                        generations += 1;
+                    }
+                    generations
+                })
+            })
+            .collect();
+        writer.join().unwrap();
+        for reader in readers {
+            assert!(reader.join().unwrap() > 0);
+        }
+    });
+}
+
+#[test]
+#[ignore]
+fn grains_one_writer_many_reader_processes() {
+    with_deadline("grains_one_writer_many_reader_processes", || {
+        let domain_guard = TestDomainGuard::new("grain_processes");
+        let instance = instance(&domain_guard.domain());
+        let progress = Arc::new(Progress::default());
+        let (flow_id, _, writer) = spawn_grain_writer(&instance, progress);
+        // The children are this test binary running `child_grain_reader`.
+        let children: Vec<_> = (0..CHILD_PROCESSES)
+            .map(|_| {
+                Command::new(std::env::current_exe().unwrap())
+                    .args(["--ignored", "--exact", "child_grain_reader", "--nocapture"])
+                    .env(CHILD_ENV, format!("{} {flow_id}", domain_guard.domain()))
+                    .spawn()
+                    .unwrap()
+            })
+            .collect();
+        writer.join().unwrap();
+        for mut child in children {
+            assert!(child.wait().unwrap().success());
+        }
+    });
+}
+
+/// Reader process of `grains_one_writer_many_reader_processes`, a no-op unless spawned by it.
+#[test]
+#[ignore]
+fn child_grain_reader() {
+    let Ok(arguments) = std::env::var(CHILD_ENV) else {
+        return;
+    };
+    let (domain, flow_id) = arguments.split_once(' ').unwrap();
+    let instance = instance(domain);
+    let reader = instance
+        .create_flow_reader(flow_id)
+        .unwrap()
+        .to_grain_reader()
+        .unwrap();
+    // The writer's progress is not shared across processes, so read until the flow goes stale.
+    let unbounded = Progress::default();
+    let start = reader.get_runtime_info().unwrap().headIndex;
+    let stats = read_grains(&reader, start, &unbounded);
+    assert!(stats.verified > 0, "{stats:?}");
+}
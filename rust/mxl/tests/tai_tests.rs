@@ -0,0 +1,94 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use mxl::{
+    LeapSecond, LeapSecondTable, set_leap_second_table, set_tai_utc_offset_override, tai_ns_to_utc,
+    tai_utc_offset_at, utc_to_tai_ns,
+};
+
+/// 2017-01-01T00:00:00 UTC, the most recent leap second.
+const LEAP_2017: u64 = 1_483_228_800;
+const NANOS: u64 = 1_000_000_000;
+
+#[test]
+fn builtin_offsets() {
+    let table = LeapSecondTable::builtin();
+    assert_eq!(table.offset_at_utc(0), 10);
+    assert_eq!(table.offset_at_utc(LEAP_2017 - 1), 36);
+    assert_eq!(table.offset_at_utc(LEAP_2017), 37);
+
+    // The inserted second (TAI LEAP_2017 + 36) repeats 23:59:59 UTC.
+    assert_eq!(table.offset_at_tai(LEAP_2017 + 35), 36);
+    assert_eq!(table.offset_at_tai(LEAP_2017 + 36), 37);
+    assert_eq!(table.offset_at_tai(LEAP_2017 + 37), 37);
+}
+
+#[test]
+fn parse_leap_seconds_list() {
+    let text = "\
+#\tleap-seconds.list excerpt
+#@\t3960057600
+2272060800\t10\t# 1 Jan 1972
+3692217600\t37\t# 1 Jan 2017
+#h\t16edd0f0 3666784f 37db6bdd e74ced87 59af48f1
+";
+    let table = LeapSecondTable::parse_leap_seconds_list(text).unwrap();
+    assert_eq!(
+        table.entries(),
+        &[
+            LeapSecond {
+                utc_seconds: 63_072_000,
+                tai_utc_offset: 10,
+            },
+            LeapSecond {
+                utc_seconds: LEAP_2017,
+                tai_utc_offset: 37,
+            },
+        ]
+    );
+    assert!(LeapSecondTable::parse_leap_seconds_list("# only comments\n").is_err());
+    assert!(LeapSecondTable::parse_leap_seconds_list("3692217600\n").is_err());
+}
+
+#[test]
+fn table_must_be_ordered() {
+    let entry = |utc_seconds, tai_utc_offset| LeapSecond {
+        utc_seconds,
+        tai_utc_offset,
+    };
+    assert!(LeapSecondTable::new(vec![entry(10, 1), entry(5, 2)]).is_err());
+    assert!(LeapSecondTable::new(vec![]).is_err());
+}
+
+// The table and the override are process-wide, so everything touching them runs in one test.
+#[test]
+fn conversions_table_and_override() {
+    let utc = UNIX_EPOCH + Duration::from_secs(LEAP_2017 + 60);
+    let tai_ns = utc_to_tai_ns(utc).unwrap();
+    assert_eq!(tai_ns, (LEAP_2017 + 60 + 37) * NANOS);
+    assert_eq!(tai_ns_to_utc(tai_ns), utc);
+    assert_eq!(tai_utc_offset_at(tai_ns), 37);
+    assert_eq!(
+        tai_ns_to_utc((LEAP_2017 + 36) * NANOS),
+        UNIX_EPOCH + Duration::from_secs(LEAP_2017 - 1)
+    );
+    assert!(utc_to_tai_ns(UNIX_EPOCH - Duration::from_secs(1)).is_err());
+
+    set_tai_utc_offset_override(Some(40));
+    assert_eq!(utc_to_tai_ns(utc).unwrap(), (LEAP_2017 + 60 + 40) * NANOS);
+    assert_eq!(tai_ns_to_utc(tai_ns), utc - Duration::from_secs(3));
+    set_tai_utc_offset_override(None);
+
+    let future_leap = LEAP_2017 + 30;
+    let mut entries = LeapSecondTable::builtin().entries().to_vec();
+    entries.push(LeapSecond {
+        utc_seconds: future_leap,
+        tai_utc_offset: 38,
+    });
+    set_leap_second_table(LeapSecondTable::new(entries).unwrap());
+    assert_eq!(utc_to_tai_ns(utc).unwrap(), (LEAP_2017 + 60 + 38) * NANOS);
+    set_leap_second_table(LeapSecondTable::builtin());
+    assert_eq!(utc_to_tai_ns(utc).unwrap(), tai_ns);
+}
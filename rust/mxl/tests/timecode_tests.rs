@@ -0,0 +1,93 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Media eXchange Layer project.
+// SPDX-License-Identifier: Apache-2.0
+
+use mxl::{Timecode, index_to_timecode, rates, timecode_to_index};
+
+fn timecode(s: &str) -> Timecode {
+    s.parse().unwrap()
+}
+
+#[test]
+fn non_drop_frame() {
+    let tc = index_to_timecode(25 * 3600 + 25 * 61 + 3, &rates::FPS_25, false).unwrap();
+    assert_eq!(tc.to_string(), "01:01:01:03");
+    assert_eq!(
+        timecode_to_index(&tc, &rates::FPS_25, 0).unwrap(),
+        25 * 3661 + 3
+    );
+
+    // Timecode wraps every 24 hours.
+    let day = 25 * 86400;
+    let tc = index_to_timecode(day * 1000 + 5, &rates::FPS_25, false).unwrap();
+    assert_eq!(tc.to_string(), "00:00:00:05");
+    assert_eq!(
+        timecode_to_index(&tc, &rates::FPS_25, day * 1000 - 10).unwrap(),
+        day * 1000 + 5
+    );
+    assert_eq!(
+        timecode_to_index(&timecode("23:59:59:24"), &rates::FPS_25, day * 1000 + 5).unwrap(),
+        day * 1000 - 1
+    );
+}
+
+#[test]
+fn drop_frame_29_97() {
+    let rate = rates::FPS_29_97;
+    let expected = [
+        (1799, "00:00:59;29"),
+        (1800, "00:01:00;02"),
+        (17982, "00:10:00;00"),
+        (17981, "00:09:59;29"),
+        (107892, "01:00:00;00"),
+    ];
+    for (index, text) in expected {
+        let tc = index_to_timecode(index, &rate, true).unwrap();
+        assert_eq!(tc.to_string(), text);
+        assert_eq!(timecode_to_index(&tc, &rate, index).unwrap(), index);
+    }
+    // Frames 0 and 1 do not exist at the start of minutes not divisible by 10.
+    assert!(timecode_to_index(&timecode("00:01:00;00"), &rate, 0).is_err());
+    assert!(timecode_to_index(&timecode("00:10:00;00"), &rate, 0).is_ok());
+
+    // Every index round-trips across a full ten minute cycle.
+    for index in 0..17982 * 2 {
+        let tc = index_to_timecode(index, &rate, true).unwrap();
+        assert_eq!(timecode_to_index(&tc, &rate, index).unwrap(), index);
+    }
+}
+
+#[test]
+fn drop_frame_59_94() {
+    let rate = rates::FPS_59_94;
+    assert_eq!(
+        index_to_timecode(3600, &rate, true).unwrap().to_string(),
+        "00:01:00;04"
+    );
+    assert_eq!(
+        index_to_timecode(35964, &rate, true).unwrap().to_string(),
+        "00:10:00;00"
+    );
+    assert!(timecode_to_index(&timecode("00:01:00;03"), &rate, 0).is_err());
+}
+
+#[test]
+fn drop_frame_requires_a_1001_rate() {
+    assert!(index_to_timecode(0, &rates::FPS_25, true).is_err());
+    assert!(index_to_timecode(0, &rates::FPS_23_98, true).is_err());
+    assert!(index_to_timecode(0, &rates::FPS_29_97, false).is_ok());
+}
+
+#[test]
+fn parse_and_format() {
+    let tc = timecode("10:20:30;15");
+    assert!(tc.drop_frame);
+    assert_eq!(
+        (tc.hours, tc.minutes, tc.seconds, tc.frames),
+        (10, 20, 30, 15)
+    );
+    assert_eq!(tc.to_string(), "10:20:30;15");
+    assert!(!timecode("10:20:30:15").drop_frame);
+    assert!("10:20:30".parse::<Timecode>().is_err());
+    assert!("aa:20:30:15".parse::<Timecode>().is_err());
+    assert!(timecode_to_index(&timecode("24:00:00:00"), &rates::FPS_25, 0).is_err());
+}